@@ -1030,6 +1030,18 @@ impl Currency {
             | Self::ZMW => false,
         }
     }
+
+    /// Number of digits of the minor unit of the currency, after the decimal point of an amount
+    /// in major units.
+    pub fn exponent(self) -> u32 {
+        if self.is_zero_decimal_currency() {
+            0
+        } else if self.is_three_decimal_currency() {
+            3
+        } else {
+            2
+        }
+    }
 }
 
 #[derive(
@@ -1091,6 +1103,10 @@ pub enum MerchantStorageScheme {
     #[default]
     PostgresOnly,
     RedisKv,
+    /// Transitional scheme while moving a merchant from `PostgresOnly` to `RedisKv`. Payouts are
+    /// written to Postgres and mirrored to Redis on a best effort basis, reads are served from
+    /// Postgres. Other entities are stored as with `PostgresOnly`.
+    DualWrite,
 }
 
 #[derive(
@@ -2028,6 +2044,7 @@ pub enum CanadaStatesAbbreviation {
     Debug,
     Default,
     Eq,
+    Hash,
     PartialEq,
     ToSchema,
     serde::Deserialize,
@@ -2048,6 +2065,50 @@ pub enum PayoutStatus {
     RequiresCreation,
     RequiresPayoutMethodData,
     RequiresFulfillment,
+    HeldForReview,
+    Scheduled,
+    RequiresConfirmation,
+}
+
+impl PayoutStatus {
+    /// Whether a payout in this status can no longer move to another status.
+    pub fn is_terminal_status(self) -> bool {
+        match self {
+            Self::Success | Self::Failed | Self::Cancelled | Self::Ineligible => true,
+            Self::Pending
+            | Self::RequiresCreation
+            | Self::RequiresPayoutMethodData
+            | Self::RequiresFulfillment
+            | Self::HeldForReview
+            | Self::Scheduled
+            | Self::RequiresConfirmation => false,
+        }
+    }
+}
+
+/// The channel through which a payout was created
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[router_derive::diesel_enum(storage_type = "text")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PayoutCreationSource {
+    #[default]
+    Api,
+    Dashboard,
+    BatchFile,
+    Recurring,
 }
 
 #[derive(
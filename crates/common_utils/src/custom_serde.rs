@@ -14,7 +14,7 @@ pub mod iso8601 {
             Iso8601,
         },
         serde::iso8601,
-        PrimitiveDateTime, UtcOffset,
+        PrimitiveDateTime,
     };
 
     const FORMAT_CONFIG: EncodedConfig = Config::DEFAULT
@@ -40,10 +40,7 @@ pub mod iso8601 {
     where
         D: Deserializer<'a>,
     {
-        iso8601::deserialize(deserializer).map(|offset_date_time| {
-            let utc_date_time = offset_date_time.to_offset(UtcOffset::UTC);
-            PrimitiveDateTime::new(utc_date_time.date(), utc_date_time.time())
-        })
+        iso8601::deserialize(deserializer).map(crate::date_time::convert_to_pdt)
     }
 
     /// Use the well-known ISO 8601 format when serializing and deserializing an
@@ -77,10 +74,7 @@ pub mod iso8601 {
             D: Deserializer<'a>,
         {
             iso8601::option::deserialize(deserializer).map(|option_offset_date_time| {
-                option_offset_date_time.map(|offset_date_time| {
-                    let utc_date_time = offset_date_time.to_offset(UtcOffset::UTC);
-                    PrimitiveDateTime::new(utc_date_time.date(), utc_date_time.time())
-                })
+                option_offset_date_time.map(crate::date_time::convert_to_pdt)
             })
         }
     }
@@ -140,7 +134,7 @@ pub mod iso8601 {
 pub mod timestamp {
 
     use serde::{Deserializer, Serialize, Serializer};
-    use time::{serde::timestamp, PrimitiveDateTime, UtcOffset};
+    use time::{serde::timestamp, PrimitiveDateTime};
 
     /// Serialize a [`PrimitiveDateTime`] using UNIX timestamp.
     pub fn serialize<S>(date_time: &PrimitiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
@@ -158,10 +152,7 @@ pub mod timestamp {
     where
         D: Deserializer<'a>,
     {
-        timestamp::deserialize(deserializer).map(|offset_date_time| {
-            let utc_date_time = offset_date_time.to_offset(UtcOffset::UTC);
-            PrimitiveDateTime::new(utc_date_time.date(), utc_date_time.time())
-        })
+        timestamp::deserialize(deserializer).map(crate::date_time::convert_to_pdt)
     }
 
     /// Use the UNIX timestamp when serializing and deserializing an
@@ -192,10 +183,7 @@ pub mod timestamp {
             D: Deserializer<'a>,
         {
             timestamp::option::deserialize(deserializer).map(|option_offset_date_time| {
-                option_offset_date_time.map(|offset_date_time| {
-                    let utc_date_time = offset_date_time.to_offset(UtcOffset::UTC);
-                    PrimitiveDateTime::new(utc_date_time.date(), utc_date_time.time())
-                })
+                option_offset_date_time.map(crate::date_time::convert_to_pdt)
             })
         }
     }
@@ -231,7 +219,7 @@ pub mod iso8601custom {
             Iso8601,
         },
         serde::iso8601,
-        PrimitiveDateTime, UtcOffset,
+        PrimitiveDateTime,
     };
 
     const FORMAT_CONFIG: EncodedConfig = Config::DEFAULT
@@ -259,9 +247,6 @@ pub mod iso8601custom {
     where
         D: Deserializer<'a>,
     {
-        iso8601::deserialize(deserializer).map(|offset_date_time| {
-            let utc_date_time = offset_date_time.to_offset(UtcOffset::UTC);
-            PrimitiveDateTime::new(utc_date_time.date(), utc_date_time.time())
-        })
+        iso8601::deserialize(deserializer).map(crate::date_time::convert_to_pdt)
     }
 }
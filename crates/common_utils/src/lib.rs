@@ -31,9 +31,10 @@ pub mod date_time {
     use time::{
         format_description::{
             well_known::iso8601::{Config, EncodedConfig, Iso8601, TimePrecision},
+            well_known::Rfc3339,
             FormatItem,
         },
-        OffsetDateTime, PrimitiveDateTime,
+        OffsetDateTime, PrimitiveDateTime, UtcOffset,
     };
     /// Struct to represent milliseconds in time sensitive data fields
     #[derive(Debug)]
@@ -54,9 +55,18 @@ pub mod date_time {
         PrimitiveDateTime::new(utc_date_time.date(), utc_date_time.time())
     }
 
-    /// Convert from OffsetDateTime to PrimitiveDateTime
+    /// Convert from OffsetDateTime to PrimitiveDateTime, normalizing the date and time to UTC
     pub fn convert_to_pdt(offset_time: OffsetDateTime) -> PrimitiveDateTime {
-        PrimitiveDateTime::new(offset_time.date(), offset_time.time())
+        let utc_date_time = offset_time.to_offset(UtcOffset::UTC);
+        PrimitiveDateTime::new(utc_date_time.date(), utc_date_time.time())
+    }
+
+    /// Parse an RFC 3339 date and time into a [`PrimitiveDateTime`] in UTC.
+    ///
+    /// The input must carry an explicit UTC offset, inputs without one are rejected since the
+    /// timezone they are expressed in is ambiguous.
+    pub fn parse_to_utc(input: &str) -> Result<PrimitiveDateTime, time::error::Parse> {
+        OffsetDateTime::parse(input, &Rfc3339).map(convert_to_pdt)
     }
 
     /// Return the UNIX timestamp of the current date and time in UTC
@@ -184,6 +194,40 @@ pub mod date_time {
             f.write_str(&output)
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        #![allow(clippy::unwrap_used)]
+
+        use time::macros::datetime;
+
+        use super::*;
+
+        #[test]
+        fn test_convert_to_pdt_normalizes_offset_to_utc() {
+            assert_eq!(
+                convert_to_pdt(datetime!(2024-03-14 10:30:00 +05:30)),
+                datetime!(2024-03-14 05:00:00)
+            );
+        }
+
+        #[test]
+        fn test_parse_to_utc_normalizes_offset() {
+            assert_eq!(
+                parse_to_utc("2024-03-14T01:15:00-08:00").unwrap(),
+                datetime!(2024-03-14 09:15:00)
+            );
+            assert_eq!(
+                parse_to_utc("2024-03-14T09:15:00Z").unwrap(),
+                datetime!(2024-03-14 09:15:00)
+            );
+        }
+
+        #[test]
+        fn test_parse_to_utc_rejects_input_without_offset() {
+            assert!(parse_to_utc("2024-03-14T09:15:00").is_err());
+        }
+    }
 }
 
 /// Generate a nanoid with the given prefix and length
@@ -211,3 +211,49 @@ where
         <serde_json::Value as ToSql<Jsonb, diesel::pg::Pg>>::to_sql(&value, &mut out.reborrow())
     }
 }
+
+/// The change made by an update to a nullable field, telling apart clearing the field from
+/// leaving it as is
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "op", content = "value")]
+pub enum Maybe<T> {
+    /// The field is left as is
+    #[default]
+    Unchanged,
+    /// The field is set to `NULL`
+    SetNull,
+    /// The field is set to the value
+    Set(T),
+}
+
+impl<T> Maybe<T> {
+    /// `Set` for `Some` and `Unchanged` for `None`, for updates where a missing value leaves the
+    /// field as is
+    pub fn set_or_unchanged(value: Option<T>) -> Self {
+        value.map_or(Self::Unchanged, Self::Set)
+    }
+
+    /// The value of the field in a diesel changeset: `None` leaves the column as is, while
+    /// `Some(None)` sets it to `NULL`
+    pub fn into_changeset(self) -> Option<Option<T>> {
+        match self {
+            Self::Unchanged => None,
+            Self::SetNull => Some(None),
+            Self::Set(value) => Some(Some(value)),
+        }
+    }
+
+    /// The value of the field once the change is applied to `current`
+    pub fn apply(self, current: Option<T>) -> Option<T> {
+        match self {
+            Self::Unchanged => current,
+            Self::SetNull => None,
+            Self::Set(value) => Some(value),
+        }
+    }
+
+    /// Whether the field is left as is
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Self::Unchanged)
+    }
+}
@@ -1,4 +1,4 @@
-use diesel_models::errors::DatabaseError;
+use diesel_models::{enums::MerchantStorageScheme, errors::DatabaseError};
 
 pub type StorageResult<T> = error_stack::Result<T, StorageError>;
 
@@ -16,10 +16,32 @@ pub enum StorageError {
         entity: &'static str,
         key: Option<String>,
     },
+    #[error("InvalidValue: {field} is invalid")]
+    InvalidValue { field: &'static str },
+    #[error("AmountOutOfRange: amount must be between {min} and {max}")]
+    AmountOutOfRange { min: i64, max: i64 },
+    #[error("ConcurrencyLimitReached: at most {limit} payouts may be in flight")]
+    ConcurrencyLimitReached { limit: u32 },
+    #[error("InsufficientFunds: available balance is {available}")]
+    InsufficientFunds { available: i64 },
+    #[error("StaleUpdate: {entity} was updated since it was read")]
+    StaleUpdate { entity: &'static str },
+    #[error("ImportsDisabled: payouts cannot be imported through this store")]
+    ImportsDisabled,
+    #[error("ReadOnlyMode: the store does not accept writes during maintenance")]
+    ReadOnlyMode,
+    #[error("SchemeUnsupported: the {scheme} storage scheme is not supported by this store")]
+    SchemeUnsupported { scheme: MerchantStorageScheme },
     #[error("Timed out while trying to connect to the database")]
     DatabaseConnectionError,
+    #[error("DeadlineExceeded: the deadline of the operation passed before it completed")]
+    DeadlineExceeded,
     #[error("KV error")]
     KVError,
+    #[error("KvCircuitOpen: Redis is failing, KV writes are rejected until it recovers")]
+    KvCircuitOpen,
+    #[error("IntegrityCheckFailed: the checksum of the value does not match")]
+    IntegrityCheckFailed,
     #[error("Serialization failure")]
     SerializationFailed,
     #[error("MockDb error")]
@@ -10,6 +10,9 @@ pub trait PayoutAttemptInterface {}
 #[cfg(not(feature = "payouts"))]
 pub trait PayoutsInterface {}
 
+#[cfg(not(feature = "payouts"))]
+pub trait PayoutWebhookDeliveryInterface {}
+
 #[derive(Clone, Debug, Eq, PartialEq, serde::Serialize)]
 pub enum RemoteStorageObject<T: ForeignIDRef> {
     ForeignID(String),
@@ -1,3 +1,7 @@
+pub mod amount_adjustment;
+pub mod decline_code;
+pub mod ids;
 pub mod payout_attempt;
 #[allow(clippy::module_inception)]
 pub mod payouts;
+pub mod webhook_delivery;
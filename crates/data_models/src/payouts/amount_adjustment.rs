@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
+
+/// A change of the amount of a payout, recorded along with the update making it, see
+/// [`super::payouts::PayoutsInterface::list_amount_adjustments`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PayoutAmountAdjustment {
+    pub merchant_id: String,
+    pub payout_id: String,
+    pub old_amount: i64,
+    pub new_amount: i64,
+    /// The kind of update which changed the amount.
+    pub reason: String,
+    pub created_at: PrimitiveDateTime,
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+/// Connector-agnostic reason for a payout being declined by a connector.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnifiedCode {
+    InsufficientFunds,
+    InvalidAccount,
+    LimitExceeded,
+    SuspectedFraud,
+    Declined,
+    /// The connector's code is not present in [`PAYOUT_DECLINE_CODES`].
+    Unknown,
+}
+
+impl UnifiedCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InsufficientFunds => "insufficient_funds",
+            Self::InvalidAccount => "invalid_account",
+            Self::LimitExceeded => "limit_exceeded",
+            Self::SuspectedFraud => "suspected_fraud",
+            Self::Declined => "declined",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for UnifiedCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Decline codes returned by connectors, along with the unified code each of them maps to.
+///
+/// Entries are keyed by the connector name, as stored in the `connector` field of the payout
+/// attempt, and the raw code returned by that connector.
+pub const PAYOUT_DECLINE_CODES: &[(&str, &str, UnifiedCode)] = &[
+    ("adyen", "2", UnifiedCode::Declined),
+    ("adyen", "6", UnifiedCode::InvalidAccount),
+    ("adyen", "12", UnifiedCode::InsufficientFunds),
+    ("adyen", "14", UnifiedCode::InvalidAccount),
+    ("adyen", "20", UnifiedCode::SuspectedFraud),
+    ("adyen", "28", UnifiedCode::LimitExceeded),
+    ("adyen", "29", UnifiedCode::LimitExceeded),
+];
+
+/// Maps the decline code returned by `connector` to its unified code, [`UnifiedCode::Unknown`]
+/// if the code is not known for that connector.
+pub fn normalize_payout_decline_code(connector: &str, raw_code: &str) -> UnifiedCode {
+    PAYOUT_DECLINE_CODES
+        .iter()
+        .find(|(code_connector, code, _)| *code_connector == connector && *code == raw_code)
+        .map(|(_, _, unified_code)| *unified_code)
+        .unwrap_or(UnifiedCode::Unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_decline_code_is_normalized() {
+        assert_eq!(
+            normalize_payout_decline_code("adyen", "12"),
+            UnifiedCode::InsufficientFunds
+        );
+    }
+
+    #[test]
+    fn test_unknown_decline_code_maps_to_unknown() {
+        assert_eq!(
+            normalize_payout_decline_code("adyen", "999"),
+            UnifiedCode::Unknown
+        );
+    }
+
+    #[test]
+    fn test_connector_without_entries_maps_to_unknown() {
+        assert_eq!(
+            normalize_payout_decline_code("wise", "12"),
+            UnifiedCode::Unknown
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use std::{borrow::Borrow, fmt, ops::Deref};
+
+use serde::{Deserialize, Serialize};
+
+/// Identifies a payout within its merchant.
+///
+/// Payout and merchant ids are distinct types so that passing one for the other to a
+/// [`PayoutsInterface`](super::payouts::PayoutsInterface) method is a type error:
+///
+/// ```no_run
+/// # use common_enums::MerchantStorageScheme;
+/// # use data_models::payouts::{ids::{MerchantId, PayoutId}, payouts::PayoutsInterface};
+/// async fn find(store: &dyn PayoutsInterface, merchant_id: MerchantId, payout_id: PayoutId) {
+///     let _ = store
+///         .find_payout_by_merchant_id_payout_id(
+///             &merchant_id,
+///             &payout_id,
+///             MerchantStorageScheme::PostgresOnly,
+///         )
+///         .await;
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use common_enums::MerchantStorageScheme;
+/// # use data_models::payouts::{ids::{MerchantId, PayoutId}, payouts::PayoutsInterface};
+/// async fn find(store: &dyn PayoutsInterface, merchant_id: MerchantId, payout_id: PayoutId) {
+///     let _ = store
+///         .find_payout_by_merchant_id_payout_id(
+///             &payout_id,
+///             &merchant_id,
+///             MerchantStorageScheme::PostgresOnly,
+///         )
+///         .await;
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PayoutId(String);
+
+/// Identifies the merchant a payout belongs to, see [`PayoutId`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MerchantId(String);
+
+/// Identifies the customer a payout is made to, see [`PayoutId`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomerId(String);
+
+macro_rules! impl_string_id {
+    ($id:ident) => {
+        impl $id {
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl Deref for $id {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $id {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        // Hashes like its inner `String`, so maps keyed by ids can be looked up by `&str`
+        impl Borrow<str> for $id {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $id {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<String> for $id {
+            fn from(value: String) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<&str> for $id {
+            fn from(value: &str) -> Self {
+                Self(value.to_string())
+            }
+        }
+
+        impl From<&String> for $id {
+            fn from(value: &String) -> Self {
+                Self(value.clone())
+            }
+        }
+
+        impl PartialEq<str> for $id {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<String> for $id {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+    };
+}
+
+impl_string_id!(PayoutId);
+impl_string_id!(MerchantId);
+impl_string_id!(CustomerId);
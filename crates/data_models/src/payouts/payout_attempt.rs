@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use storage_enums::MerchantStorageScheme;
 use time::PrimitiveDateTime;
 
+use super::decline_code::normalize_payout_decline_code;
 use crate::errors;
 
 #[async_trait::async_trait]
@@ -26,6 +27,15 @@ pub trait PayoutAttemptInterface {
         _payout_attempt_id: &str,
         _storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PayoutAttempt, errors::StorageError>;
+
+    /// Lists the attempts of a payout, one per connector it was tried on, in the order of
+    /// [`PayoutAttempt::sort_for_timeline`].
+    async fn list_payout_attempts(
+        &self,
+        _merchant_id: &str,
+        _payout_id: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutAttempt>, errors::StorageError>;
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -51,6 +61,36 @@ pub struct PayoutAttempt {
     pub profile_id: String,
     pub merchant_connector_id: Option<String>,
     pub routing_info: Option<serde_json::Value>,
+    pub unified_code: Option<String>,
+}
+
+impl PayoutAttempt {
+    /// Sorts the attempts of a payout into its timeline: oldest `created_at` first, with ties
+    /// broken by `payout_attempt_id`, as ordered by the database query listing them.
+    pub fn sort_for_timeline(attempts: &mut [Self]) {
+        attempts.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.payout_attempt_id.cmp(&b.payout_attempt_id))
+        });
+    }
+
+    /// Merges the attempts of a payout held in KV with the ones stored in the database into its
+    /// timeline. The KV copy of an attempt found in both is the current one, the database copy
+    /// only catches up once it is drained.
+    pub fn merge_into_timeline(held: Vec<Self>, stored: Vec<Self>) -> Vec<Self> {
+        let mut attempts = stored
+            .into_iter()
+            .filter(|stored| {
+                !held
+                    .iter()
+                    .any(|held| held.payout_attempt_id == stored.payout_attempt_id)
+            })
+            .chain(held)
+            .collect::<Vec<_>>();
+        Self::sort_for_timeline(&mut attempts);
+        attempts
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -74,6 +114,7 @@ pub struct PayoutAttemptNew {
     pub profile_id: String,
     pub merchant_connector_id: Option<String>,
     pub routing_info: Option<serde_json::Value>,
+    pub unified_code: Option<String>,
 }
 
 impl Default for PayoutAttemptNew {
@@ -100,6 +141,36 @@ impl Default for PayoutAttemptNew {
             profile_id: String::default(),
             merchant_connector_id: None,
             routing_info: None,
+            unified_code: None,
+        }
+    }
+}
+
+impl PayoutAttemptNew {
+    /// The attempt as stored once inserted at `now`, which stamps it unless its timestamps are
+    /// set already.
+    pub fn into_attempt(self, now: PrimitiveDateTime) -> PayoutAttempt {
+        PayoutAttempt {
+            payout_attempt_id: self.payout_attempt_id,
+            payout_id: self.payout_id,
+            customer_id: self.customer_id,
+            merchant_id: self.merchant_id,
+            address_id: self.address_id,
+            connector: self.connector,
+            connector_payout_id: self.connector_payout_id,
+            payout_token: self.payout_token,
+            status: self.status,
+            is_eligible: self.is_eligible,
+            error_message: self.error_message,
+            error_code: self.error_code,
+            business_country: self.business_country,
+            business_label: self.business_label,
+            created_at: self.created_at.unwrap_or(now),
+            last_modified_at: self.last_modified_at.unwrap_or(now),
+            profile_id: self.profile_id,
+            merchant_connector_id: self.merchant_connector_id,
+            routing_info: self.routing_info,
+            unified_code: self.unified_code,
         }
     }
 }
@@ -113,6 +184,15 @@ pub enum PayoutAttemptUpdate {
         error_code: Option<String>,
         is_eligible: Option<bool>,
     },
+    /// Records a decline by `connector`, normalizing `error_code` into a unified code.
+    ErrorUpdate {
+        connector: String,
+        connector_payout_id: String,
+        status: storage_enums::PayoutStatus,
+        error_message: Option<String>,
+        error_code: String,
+        is_eligible: Option<bool>,
+    },
     PayoutTokenUpdate {
         payout_token: String,
     },
@@ -138,6 +218,7 @@ pub struct PayoutAttemptUpdateInternal {
     pub business_label: Option<String>,
     pub connector: Option<String>,
     pub routing_info: Option<serde_json::Value>,
+    pub unified_code: Option<String>,
 }
 
 impl From<PayoutAttemptUpdate> for PayoutAttemptUpdateInternal {
@@ -161,6 +242,24 @@ impl From<PayoutAttemptUpdate> for PayoutAttemptUpdateInternal {
                 is_eligible,
                 ..Default::default()
             },
+            PayoutAttemptUpdate::ErrorUpdate {
+                connector,
+                connector_payout_id,
+                status,
+                error_message,
+                error_code,
+                is_eligible,
+            } => Self {
+                connector_payout_id: Some(connector_payout_id),
+                status: Some(status),
+                error_message,
+                unified_code: Some(
+                    normalize_payout_decline_code(&connector, &error_code).to_string(),
+                ),
+                error_code: Some(error_code),
+                is_eligible,
+                ..Default::default()
+            },
             PayoutAttemptUpdate::BusinessUpdate {
                 business_country,
                 business_label,
@@ -180,3 +279,91 @@ impl From<PayoutAttemptUpdate> for PayoutAttemptUpdateInternal {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(payout_attempt_id: &str, minutes_ago: i64) -> PayoutAttempt {
+        let now = common_utils::date_time::now();
+        PayoutAttempt {
+            payout_attempt_id: payout_attempt_id.to_string(),
+            payout_id: "po_1".to_string(),
+            customer_id: "cus_1".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            address_id: "add_1".to_string(),
+            connector: None,
+            connector_payout_id: String::default(),
+            payout_token: None,
+            status: storage_enums::PayoutStatus::default(),
+            is_eligible: None,
+            error_message: None,
+            error_code: None,
+            business_country: None,
+            business_label: None,
+            created_at: now - time::Duration::minutes(minutes_ago),
+            last_modified_at: now,
+            profile_id: "pro_1".to_string(),
+            merchant_connector_id: None,
+            routing_info: None,
+            unified_code: None,
+        }
+    }
+
+    #[test]
+    fn test_timeline_orders_attempts_oldest_first() {
+        let mut attempts = vec![
+            attempt("po_1_2", 5),
+            attempt("po_1_3", 0),
+            attempt("po_1_1", 10),
+            attempt("po_1_0", 10),
+        ];
+        PayoutAttempt::sort_for_timeline(&mut attempts);
+
+        assert_eq!(
+            attempts
+                .iter()
+                .map(|attempt| attempt.payout_attempt_id.as_str())
+                .collect::<Vec<_>>(),
+            ["po_1_0", "po_1_1", "po_1_2", "po_1_3"]
+        );
+    }
+
+    #[test]
+    fn test_merged_timeline_prefers_attempts_held_in_kv() {
+        let stored = vec![
+            attempt("po_1_1", 10),
+            PayoutAttempt {
+                status: storage_enums::PayoutStatus::Pending,
+                ..attempt("po_1_2", 5)
+            },
+        ];
+        let held = vec![
+            attempt("po_1_3", 0),
+            PayoutAttempt {
+                status: storage_enums::PayoutStatus::Failed,
+                ..attempt("po_1_2", 5)
+            },
+        ];
+
+        let attempts = PayoutAttempt::merge_into_timeline(held, stored);
+        assert_eq!(
+            attempts
+                .iter()
+                .map(|attempt| (attempt.payout_attempt_id.as_str(), attempt.status))
+                .collect::<Vec<_>>(),
+            [
+                ("po_1_1", storage_enums::PayoutStatus::default()),
+                ("po_1_2", storage_enums::PayoutStatus::Failed),
+                ("po_1_3", storage_enums::PayoutStatus::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timeline_of_payout_without_attempts_is_empty() {
+        let mut attempts = Vec::new();
+        PayoutAttempt::sort_for_timeline(&mut attempts);
+        assert!(attempts.is_empty());
+    }
+}
@@ -1,11 +1,30 @@
+use std::collections::HashMap;
+
 use common_enums as storage_enums;
-use common_utils::pii;
+use common_utils::{pii, types::Maybe};
+use error_stack::{IntoReport, ResultExt};
+use masking::PeekInterface;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use serde::{Deserialize, Serialize};
 use storage_enums::MerchantStorageScheme;
 use time::PrimitiveDateTime;
 
+use super::{
+    amount_adjustment::PayoutAmountAdjustment,
+    ids::{CustomerId, MerchantId, PayoutId},
+    payout_attempt::{PayoutAttempt, PayoutAttemptNew},
+};
 use crate::errors;
 
+/// Storage operations on payouts.
+///
+/// Methods returning several payouts order them by `created_at` descending, then `payout_id`
+/// descending (see [`Payouts::sort_for_listing`]).
+///
+/// While a store is in read-only mode for database maintenance, `insert_payout`,
+/// `insert_split_payouts`, `update_payout`, `delete_payout` and
+/// `soft_delete_all_payouts_for_merchant` fail with
+/// [`errors::StorageError::ReadOnlyMode`] before touching storage, the finders keep working.
 #[async_trait::async_trait]
 pub trait PayoutsInterface {
     async fn insert_payout(
@@ -16,11 +35,15 @@ pub trait PayoutsInterface {
 
     async fn find_payout_by_merchant_id_payout_id(
         &self,
-        _merchant_id: &str,
-        _payout_id: &str,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
         _storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<Payouts, errors::StorageError>;
 
+    /// Applies `_payout` to `_this` and bumps its sequence number.
+    ///
+    /// The sequence number of `_this` is the one the update is expected to apply on, the update
+    /// is rejected with [`errors::StorageError::StaleUpdate`] if the payout was updated since.
     async fn update_payout(
         &self,
         _this: &Payouts,
@@ -30,13 +53,772 @@ pub trait PayoutsInterface {
 
     async fn find_optional_payout_by_merchant_id_payout_id(
         &self,
-        _merchant_id: &str,
-        _payout_id: &str,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, errors::StorageError>;
+
+    async fn clone_payout_as_new(
+        &self,
+        _merchant_id: &MerchantId,
+        _source_payout_id: &PayoutId,
+        _new_payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, errors::StorageError>;
+
+    async fn update_payout_metadata_path(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _json_path: &[String],
+        _value: pii::SecretSerdeValue,
+        _create_missing: bool,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, errors::StorageError>;
+
+    /// Fails with `InsufficientFunds` unless the merchant's float in `_currency` covers
+    /// `_amount`. Advisory only, the float is not reserved.
+    async fn check_payout_balance(
+        &self,
+        _merchant_id: &MerchantId,
+        _amount: i64,
+        _currency: storage_enums::Currency,
+    ) -> error_stack::Result<(), errors::StorageError>;
+
+    async fn distinct_payout_currencies(
+        &self,
+        _merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<storage_enums::Currency>, errors::StorageError>;
+
+    async fn list_payouts_by_source(
+        &self,
+        _merchant_id: &MerchantId,
+        _created_by: storage_enums::PayoutCreationSource,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Lists up to `_limit` of the merchant's payouts in any of `_statuses`, most recent first, in
+    /// the order of [`Payouts::sort_for_listing`]. An empty `_statuses` does not filter on the
+    /// status.
+    async fn list_payouts_by_merchant_id_statuses(
+        &self,
+        _merchant_id: &MerchantId,
+        _statuses: Vec<storage_enums::PayoutStatus>,
+        _limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Lists the merchant's payouts flagged as high-risk with a score of at least `_min_score`,
+    /// most recent first.
+    async fn list_high_risk_payouts(
+        &self,
+        _merchant_id: &MerchantId,
+        _min_score: i16,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    async fn find_latest_payout_by_customer_id(
+        &self,
+        _merchant_id: &MerchantId,
+        _customer_id: &CustomerId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, errors::StorageError>;
+
+    /// Batched form of [`Self::find_optional_payout_by_merchant_id_payout_id`], the result holds
+    /// an entry for every requested payout id.
+    async fn find_optional_payouts_batch(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_ids: &[PayoutId],
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<HashMap<PayoutId, Option<Payouts>>, errors::StorageError>;
+
+    /// Lists the merchant's payouts referencing the given stored network token, for cleaning
+    /// them up when the token is revoked.
+    async fn find_payouts_by_network_token_ref(
+        &self,
+        _merchant_id: &MerchantId,
+        _network_token_ref: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Claims a pending payout for a retry by bumping its attempt count, returns `None` if the
+    /// payout is not eligible for a retry or was already claimed by another worker.
+    ///
+    /// A claimed payout can be claimed again once [`PAYOUT_RETRY_CLAIM_LEASE`] has elapsed
+    /// without it being modified.
+    async fn claim_payout_for_retry(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _max_attempts: i16,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, errors::StorageError>;
+
+    /// Returns up to `limit` pending payouts with fewer than `max_attempts` attempts which can be
+    /// retried at `now`, across merchants, least recently modified first.
+    ///
+    /// A payout whose connector asked for it to be retried later is only returned once its
+    /// `next_retry_at` is due. The returned payouts must still be claimed with
+    /// [`PayoutsInterface::claim_payout_for_retry`] before being retried.
+    async fn find_payouts_needing_retry(
+        &self,
+        _now: PrimitiveDateTime,
+        _max_attempts: i16,
+        _limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Returns up to `limit` payouts in `status` last modified before `before`, across
+    /// merchants, ordered by `(last_modified_at, payout_id)`. The next page starts after the
+    /// [`Payouts::page_cursor`] of the last payout of the previous one.
+    ///
+    /// A payout updated while paging gets a `last_modified_at` past `before` and leaves the
+    /// window, so the payouts still in it keep their place and none of them is skipped. Read
+    /// from the database only, payouts held in the KV store are seen once drained.
+    async fn list_payouts_by_status_before(
+        &self,
+        _status: storage_enums::PayoutStatus,
+        _before: PrimitiveDateTime,
+        _after_cursor: Option<&PayoutPageCursor>,
+        _limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Counts the merchant's payouts in each status.
+    ///
+    /// Statuses without any payout are omitted from the result rather than counted as `0`.
+    async fn count_payouts_by_status(
+        &self,
+        _merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<HashMap<storage_enums::PayoutStatus, i64>, errors::StorageError>;
+
+    /// Sums the amounts of the merchant's payouts, in minor units whatever their currency.
+    ///
+    /// The sum is computed by the database as a `numeric`, so that the total of a high-volume
+    /// merchant does not overflow the `bigint` of a single amount. A merchant without payouts sums
+    /// to 0. Read from the database only, payouts held in the KV store are counted once drained.
+    async fn sum_payout_amounts_by_merchant_id(
+        &self,
+        _merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<i128, errors::StorageError>;
+
+    /// Averages the time it took the merchant's payouts matching `_filters` to be fulfilled, in
+    /// seconds from `created_at` to `fulfilled_at`, see [`Payouts::processing_seconds`].
+    ///
+    /// Payouts which were not fulfilled are left out, `None` is returned if no fulfilled payout
+    /// matches. Computed by the database, payouts held in the KV store are counted once drained.
+    async fn average_payout_processing_seconds(
+        &self,
+        _merchant_id: &MerchantId,
+        _filters: &PayoutProcessingTimeFilters,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<f64>, errors::StorageError>;
+
+    /// Finds the merchant's payout whose connector request is deduplicated with the given key.
+    async fn find_payout_by_connector_idempotency_key(
+        &self,
+        _merchant_id: &MerchantId,
+        _connector_idempotency_key: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, errors::StorageError>;
+
+    /// Finds the merchant's payout with the display sequence number `_display_seq`, see
+    /// [`Payouts::display_seq`].
+    async fn find_payout_by_display_seq(
+        &self,
+        _merchant_id: &MerchantId,
+        _display_seq: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, errors::StorageError>;
+
+    /// Lists the merchant's payouts modified at or after `_since`, for reconciliation jobs
+    /// computing what changed between two snapshots.
+    async fn diff_payouts_since(
+        &self,
+        _merchant_id: &MerchantId,
+        _since: PrimitiveDateTime,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Lists the merchant's payouts last updated by the operator `_actor` and modified at or
+    /// after `_since`, see [`Payouts::last_modified_by`], for auditing what an operator changed.
+    ///
+    /// A payout updated by another operator since is no longer listed for `_actor`. Read from the
+    /// database only, payouts held in the KV store are seen once drained.
+    async fn list_payouts_modified_by(
+        &self,
+        _merchant_id: &MerchantId,
+        _actor: &str,
+        _since: PrimitiveDateTime,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Lists the merchant's payouts in any of `_statuses` which have not changed status for
+    /// `_no_change_for` or longer, for alerting on stuck payouts. Terminal statuses are ignored,
+    /// see [`is_non_terminal_status`].
+    ///
+    /// Payouts are matched on [`Payouts::status_unchanged_since`], so that updates which leave
+    /// the status as is do not hide a stuck payout. Read from the database only, payouts held in
+    /// the KV store are seen once drained.
+    async fn find_stale_payouts(
+        &self,
+        _merchant_id: &MerchantId,
+        _no_change_for: time::Duration,
+        _statuses: Vec<storage_enums::PayoutStatus>,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Finds a payout along with its attempts, in the order of
+    /// [`PayoutAttempt::sort_for_timeline`].
+    async fn find_payout_with_attempts(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, Vec<PayoutAttempt>), errors::StorageError>;
+
+    /// Repairs the `attempt_count` of a payout which diverged from its attempt records, setting
+    /// it to the number of attempts of the payout in the attempts table. Returns the corrected
+    /// count, the payout is left untouched if it was right already.
+    async fn recompute_payout_attempt_count(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<i16, errors::StorageError>;
+
+    /// Moves up to `_limit` scheduled payouts whose `scheduled_at` is no later than `_now` to
+    /// `Pending`, across merchants, earliest scheduled first. Returns the ids of the activated
+    /// payouts.
+    async fn activate_due_payouts(
+        &self,
+        _now: PrimitiveDateTime,
+        _limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutId>, errors::StorageError>;
+
+    /// Confirms a draft payout, moving it from `RequiresConfirmation` to `Pending`, see
+    /// [`Payouts::confirm_update`]. Confirming a payout which is already confirmed returns it as
+    /// is.
+    async fn confirm_payout(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, errors::StorageError>;
+
+    /// Moves the payout to `_new_connector` for its next attempt, see
+    /// [`Payouts::reroute_update`].
+    ///
+    /// The connector, the cleared connector payout id and response, the fresh connector
+    /// idempotency key and the bumped attempt count are written in a single update, which fails
+    /// as a whole if the payout is not being processed or was updated concurrently.
+    async fn reroute_payout(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _new_connector: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, errors::StorageError>;
+
+    /// Applies `_payout` only if the stored payout is still in the status of `_this`, checking
+    /// and writing atomically. Returns `None` if the status of the payout changed meanwhile, for
+    /// instance because a concurrent conditional update applied first.
+    async fn update_payout_if_status(
+        &self,
+        _this: &Payouts,
+        _payout: PayoutsUpdate,
         _storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<Option<Payouts>, errors::StorageError>;
+
+    /// Inserts the payout like [`PayoutsInterface::insert_payout`], also telling where the write
+    /// landed.
+    async fn insert_payout_with_persistence_info(
+        &self,
+        _payout: PayoutsNew,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, PersistenceInfo), errors::StorageError>;
+
+    /// Inserts a payout imported from another system, see [`PayoutsNew::for_import`].
+    ///
+    /// With `_preserve_timestamps`, the supplied timestamps are written verbatim so that
+    /// historical payouts keep their original `created_at` and `last_modified_at`, otherwise the
+    /// payout is stamped with the time of the import. Fails with `ImportsDisabled` unless imports
+    /// are enabled on the store.
+    async fn import_payout(
+        &self,
+        _payout: PayoutsNew,
+        _preserve_timestamps: bool,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, errors::StorageError>;
+
+    /// Soft deletes every payout of the merchant, as done when the merchant account is closed,
+    /// returning how many payouts were deleted.
+    ///
+    /// Payouts which were already deleted keep their `deleted_at` and are not counted again, so
+    /// calling this again for the same merchant returns 0.
+    async fn soft_delete_all_payouts_for_merchant(
+        &self,
+        _merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<usize, errors::StorageError>;
+
+    /// Deletes the payout, returning whether there was one to delete. Deleting a payout which
+    /// does not exist, e.g. because it was deleted already, is a no-op returning `false` rather
+    /// than failing with `NotFound`.
+    ///
+    /// Unlike [`PayoutsInterface::soft_delete_all_payouts_for_merchant`], the payout is removed
+    /// rather than marked as deleted, from the KV store as well as from the database. It is
+    /// dropped from the KV index of its merchant whether it was found or not.
+    async fn delete_payout(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<bool, errors::StorageError>;
+
+    /// Assigns `_default_profile_id` to the legacy payouts of the merchant which have no profile,
+    /// see [`Payouts::backfill_profile_id`], returning how many payouts were updated.
+    ///
+    /// Payouts are updated `_batch_size` at a time, each batch in its own transaction. Payouts
+    /// which already have a profile are left as is, so calling this again for the same merchant
+    /// returns 0.
+    async fn backfill_payout_profile_ids(
+        &self,
+        _merchant_id: &MerchantId,
+        _default_profile_id: &str,
+        _batch_size: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<usize, errors::StorageError>;
+
+    /// Moves each payout of the merchant to the status it is paired with in `_transitions`,
+    /// returning the outcome of every transition in the same order.
+    ///
+    /// A payout which is missing, whose transition is illegal or whose update fails is reported
+    /// with its error, without preventing the other payouts from being moved. The whole batch
+    /// only fails if the payouts cannot be read.
+    async fn update_payouts_status_batch(
+        &self,
+        _merchant_id: &MerchantId,
+        _transitions: Vec<(PayoutId, storage_enums::PayoutStatus)>,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<
+        Vec<(PayoutId, error_stack::Result<(), errors::StorageError>)>,
+        errors::StorageError,
+    >;
+
+    /// Reads the status of the payout only, without deserializing the whole payout on the Redis
+    /// KV scheme.
+    async fn get_payout_status(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<storage_enums::PayoutStatus, errors::StorageError>;
+
+    /// Lists the merchant's payouts refunding the given payment to its original payment method.
+    async fn find_payouts_by_source_payment_id(
+        &self,
+        _merchant_id: &MerchantId,
+        _source_payment_id: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Inserts `_parent` together with `_splits`, the payouts it is split into across several
+    /// beneficiaries, returning the parent and its splits, in the order given, as inserted. Each split is linked to
+    /// the parent through its `parent_payout_id`.
+    ///
+    /// The splits must add up to the parent, see [`PayoutsNew::link_splits`]. Either all the
+    /// payouts are inserted or none is: as KV offers no atomic insert across payouts, they are
+    /// written to Postgres in a single transaction whatever the storage scheme, and mirrored to
+    /// the KV store for merchants on KV.
+    async fn insert_split_payouts(
+        &self,
+        _parent: PayoutsNew,
+        _splits: Vec<PayoutsNew>,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, Vec<Payouts>), errors::StorageError>;
+
+    /// Lists the splits of the payout, see [`PayoutsInterface::insert_split_payouts`].
+    async fn list_child_payouts(
+        &self,
+        _merchant_id: &MerchantId,
+        _parent_payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Lists the amount adjustments of the payout, oldest first.
+    ///
+    /// An update changing the amount of a payout records an adjustment in the same database
+    /// transaction. Under `RedisKv` the adjustment is pushed to the drainer stream right after
+    /// the update, and listed once drained.
+    async fn list_amount_adjustments(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutAmountAdjustment>, errors::StorageError>;
+
+    /// Lists the merchant's payouts whose metadata holds `value` under the top level `key`,
+    /// newest first.
+    ///
+    /// Matched with jsonb containment, see [`Payouts::metadata_contains`]. Served from the
+    /// database, payouts held in the KV store are matched once drained.
+    async fn find_payouts_by_metadata_key(
+        &self,
+        _merchant_id: &MerchantId,
+        _key: &str,
+        _value: &serde_json::Value,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Records that the webhook event `_event_id` of `_connector` was processed, returning
+    /// `false` if it was already recorded, i.e. the event is a retry which must not be applied
+    /// again.
+    async fn record_webhook_event_once(
+        &self,
+        _connector: &str,
+        _event_id: &str,
+    ) -> error_stack::Result<bool, errors::StorageError>;
+
+    /// Forgets that the webhook event `_event_id` of `_connector` was processed, so that its next
+    /// delivery is applied, as done when applying an event recorded by
+    /// [`PayoutsInterface::record_webhook_event_once`] failed. Forgetting an event which was not
+    /// recorded does nothing.
+    async fn forget_webhook_event(
+        &self,
+        _connector: &str,
+        _event_id: &str,
+    ) -> error_stack::Result<(), errors::StorageError>;
+
+    /// Returns how many payouts the merchant created on `_date`, in UTC, from a counter bumped on
+    /// every insert and rolled over at midnight. A day without payouts, or which is over, counts
+    /// 0.
+    async fn get_payout_daily_count(
+        &self,
+        _merchant_id: &MerchantId,
+        _date: time::Date,
+    ) -> error_stack::Result<i64, errors::StorageError>;
+
+    /// Cancels the payout, recording `_reason`, and inserts `_reversal` as a new attempt of it
+    /// if given, see [`Payouts::cancel_update`]. Returns the cancelled payout and the reversal
+    /// attempt.
+    ///
+    /// The cancellation and the reversal are written in a single transaction: if the reversal
+    /// cannot be inserted, the payout is left as is. In KV, the insert of the reversal is pushed
+    /// to the drainer stream of the payout right after its cancellation, so that the drainer
+    /// applies both in order.
+    async fn cancel_payout_with_reversal(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _reason: String,
+        _reversal: Option<PayoutAttemptNew>,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, Option<PayoutAttempt>), errors::StorageError>;
+
+    /// Finds a payout like `find_payout_by_merchant_id_payout_id`, loading only the heavy fields
+    /// requested by `_load_options`, the others are `None`.
+    ///
+    /// The payout returned must not be passed to `update_payout`: in KV, the whole payout is
+    /// written back, which would drop the heavy fields which were not loaded.
+    async fn find_payout_with_load_options(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _load_options: PayoutLoadOptions,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, errors::StorageError>;
+
+    /// Returns the payouts of the merchant whose `last_modified_at` is earlier than their
+    /// `created_at`, see [`Payouts::has_inconsistent_timestamps`].
+    async fn find_payouts_with_inconsistent_timestamps(
+        &self,
+        _merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, errors::StorageError>;
+
+    /// Clamps the `last_modified_at` of the payouts of the merchant to at least their
+    /// `created_at`, returning how many payouts were repaired.
+    ///
+    /// Consistent payouts are left as is, so calling this again for the same merchant returns 0.
+    async fn repair_payout_timestamps(
+        &self,
+        _merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<usize, errors::StorageError>;
+}
+
+/// Where a write of a payout landed, see
+/// [`PayoutsInterface::insert_payout_with_persistence_info`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PersistenceInfo {
+    /// Whether the payout is committed to the database.
+    pub db_committed: bool,
+    /// Whether the payout is held in the KV store.
+    pub kv_populated: bool,
+}
+
+impl PersistenceInfo {
+    /// Where an insert on `storage_scheme` landed. `kv_mirrored` tells whether a `DualWrite`
+    /// insert, committed to the database first, was mirrored to the KV store.
+    ///
+    /// A `RedisKv` insert is only committed to the database once drained.
+    pub fn for_insert(storage_scheme: MerchantStorageScheme, kv_mirrored: bool) -> Self {
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => Self {
+                db_committed: true,
+                kv_populated: false,
+            },
+            MerchantStorageScheme::DualWrite => Self {
+                db_committed: true,
+                kv_populated: kv_mirrored,
+            },
+            MerchantStorageScheme::RedisKv => Self {
+                db_committed: false,
+                kv_populated: true,
+            },
+        }
+    }
+}
+
+/// The mode, live or test, of the merchant a store serves payouts for.
+///
+/// Payouts are inserted in the mode of the store and only the payouts of that mode are found,
+/// so that test payouts never mix with live ones. Reading the payouts of both modes requires the
+/// explicit [`Self::CrossMode`] override.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TestModeContext {
+    #[default]
+    Live,
+    Test,
+    /// Finds the payouts of both modes, and inserts payouts in the mode they are given in.
+    CrossMode,
+}
+
+impl TestModeContext {
+    pub fn from_test_mode(test_mode: bool) -> Self {
+        if test_mode {
+            Self::Test
+        } else {
+            Self::Live
+        }
+    }
+
+    /// The mode of the payouts found, `None` for [`Self::CrossMode`].
+    pub fn test_mode(self) -> Option<bool> {
+        match self {
+            Self::Live => Some(false),
+            Self::Test => Some(true),
+            Self::CrossMode => None,
+        }
+    }
+
+    pub fn admits(self, test_mode: bool) -> bool {
+        self.test_mode()
+            .map_or(true, |context_test_mode| context_test_mode == test_mode)
+    }
+
+    /// Sets the mode of a payout about to be inserted to the one of this context.
+    pub fn apply_to(self, mut new: PayoutsNew) -> PayoutsNew {
+        if let Some(test_mode) = self.test_mode() {
+            new.test_mode = test_mode;
+        }
+        new
+    }
+
+    pub fn admit(self, payout: Option<Payouts>) -> Option<Payouts> {
+        payout.filter(|payout| self.admits(payout.test_mode))
+    }
+
+    pub fn retain(self, payouts: Vec<Payouts>) -> Vec<Payouts> {
+        payouts
+            .into_iter()
+            .filter(|payout| self.admits(payout.test_mode))
+            .collect()
+    }
+}
+
+/// Time during which a payout claimed for a retry cannot be claimed again, see
+/// [`PayoutsInterface::claim_payout_for_retry`].
+pub const PAYOUT_RETRY_CLAIM_LEASE: time::Duration = time::Duration::minutes(5);
+
+fn json_contains(container: &serde_json::Value, contained: &serde_json::Value) -> bool {
+    match (container, contained) {
+        (serde_json::Value::Object(container), serde_json::Value::Object(contained)) => {
+            contained.iter().all(|(key, contained)| {
+                container
+                    .get(key)
+                    .is_some_and(|held| json_contains(held, contained))
+            })
+        }
+        (serde_json::Value::Array(container), serde_json::Value::Array(contained)) => contained
+            .iter()
+            .all(|contained| container.iter().any(|held| json_contains(held, contained))),
+        (container, contained) => container == contained,
+    }
+}
+
+/// The position of a payout in the order of
+/// [`PayoutsInterface::list_payouts_by_status_before`], a page starts after it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PayoutPageCursor {
+    pub last_modified_at: PrimitiveDateTime,
+    pub payout_id: String,
+}
+
+impl PayoutPageCursor {
+    /// Whether `payout` comes after this cursor in the paging order.
+    pub fn precedes(&self, payout: &Payouts) -> bool {
+        (self.last_modified_at, self.payout_id.as_str())
+            < (payout.last_modified_at, payout.payout_id.as_str())
+    }
+}
+
+/// Narrows the payouts averaged by [`PayoutsInterface::average_payout_processing_seconds`], a
+/// filter left unset matching every payout.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PayoutProcessingTimeFilters {
+    pub profile_id: Option<String>,
+    pub connectors: Option<Vec<String>>,
+    /// Matched against the destination currency of the payout.
+    pub currencies: Option<Vec<storage_enums::Currency>>,
+    /// Only payouts fulfilled at or after this time.
+    pub fulfilled_after: Option<PrimitiveDateTime>,
+    /// Only payouts fulfilled before this time.
+    pub fulfilled_before: Option<PrimitiveDateTime>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl PayoutProcessingTimeFilters {
+    /// Whether `payout` passes the filters, regardless of it being fulfilled.
+    pub fn matches(&self, payout: &Payouts) -> bool {
+        self.profile_id
+            .as_ref()
+            .map_or(true, |profile_id| *profile_id == payout.profile_id)
+            && self.connectors.as_ref().map_or(true, |connectors| {
+                payout
+                    .connector
+                    .as_ref()
+                    .is_some_and(|connector| connectors.contains(connector))
+            })
+            && self.currencies.as_ref().map_or(true, |currencies| {
+                currencies.contains(&payout.destination_currency)
+            })
+            && self.fulfilled_after.map_or(true, |after| {
+                payout
+                    .fulfilled_at
+                    .is_some_and(|fulfilled_at| fulfilled_at >= after)
+            })
+            && self.fulfilled_before.map_or(true, |before| {
+                payout
+                    .fulfilled_at
+                    .is_some_and(|fulfilled_at| fulfilled_at < before)
+            })
+    }
+}
+
+/// Generates a fresh key for deduplicating the requests of a payout attempt at the connector.
+pub fn generate_connector_idempotency_key() -> String {
+    common_utils::generate_id_with_default_len("idem")
+}
+
+/// The routing algorithm which chose the connector of a payout, and the score it gave it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PayoutRoutingDecision {
+    pub algorithm_id: String,
+    pub score: f64,
+}
+
+impl PayoutRoutingDecision {
+    /// The blob stored in the `routing_decision` column of the payout.
+    pub fn to_secret_value(&self) -> pii::SecretSerdeValue {
+        masking::Secret::new(serde_json::json!({
+            "algorithm_id": self.algorithm_id,
+            "score": self.score,
+        }))
+    }
+}
+
+/// The heavy fields of a payout to load when reading it, see
+/// [`PayoutsInterface::find_payout_with_load_options`]. The default loads none of them.
+///
+/// From the database, the fields not loaded are left out of the selected columns. From KV, they
+/// are skipped over when the payout is deserialized.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PayoutLoadOptions {
+    pub connector_response: bool,
+    pub routing_decision: bool,
+    pub customer_snapshot: bool,
+}
+
+impl PayoutLoadOptions {
+    /// Loads every heavy field, as the other finders do.
+    pub const ALL: Self = Self {
+        connector_response: true,
+        routing_decision: true,
+        customer_snapshot: true,
+    };
+
+    /// The names of the heavy fields which are not loaded, which are also their column names.
+    pub fn skipped_fields(&self) -> Vec<&'static str> {
+        [
+            ("connector_response", self.connector_response),
+            ("routing_decision", self.routing_decision),
+            ("customer_snapshot", self.customer_snapshot),
+        ]
+        .into_iter()
+        .filter_map(|(field, loaded)| (!loaded).then_some(field))
+        .collect()
+    }
+
+    /// Clears the heavy fields of `payout` which are not loaded.
+    pub fn apply(&self, payout: Payouts) -> Payouts {
+        Payouts {
+            connector_response: payout
+                .connector_response
+                .filter(|_| self.connector_response),
+            routing_decision: payout.routing_decision.filter(|_| self.routing_decision),
+            customer_snapshot: payout.customer_snapshot.filter(|_| self.customer_snapshot),
+            ..payout
+        }
+    }
+}
+
+/// The details of the customer of a payout, captured from the customer record when the payout is
+/// created, so that later changes to the customer do not change what the payout was made for.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct PayoutCustomerSnapshot {
+    pub customer_id: String,
+    pub name: Option<masking::Secret<String>>,
+    pub email: Option<masking::Secret<String>>,
+    pub phone: Option<masking::Secret<String>>,
+    pub phone_country_code: Option<String>,
+    pub description: Option<String>,
+    pub metadata: Option<pii::SecretSerdeValue>,
+}
+
+impl PayoutCustomerSnapshot {
+    /// The blob stored in the `customer_snapshot` column of the payout.
+    pub fn to_secret_value(&self) -> pii::SecretSerdeValue {
+        masking::Secret::new(serde_json::json!({
+            "customer_id": self.customer_id,
+            "name": self.name.as_ref().map(|name| name.peek()),
+            "email": self.email.as_ref().map(|email| email.peek()),
+            "phone": self.phone.as_ref().map(|phone| phone.peek()),
+            "phone_country_code": self.phone_country_code,
+            "description": self.description,
+            "metadata": self.metadata.as_ref().map(|metadata| metadata.peek()),
+        }))
+    }
+}
+
+/// Its `Debug` output redacts the free-form fields which may carry personal data, see
+/// [`REDACTED`].
+#[derive(Clone, Eq, PartialEq, router_derive::FieldNames)]
 pub struct Payouts {
     pub payout_id: String,
     pub merchant_id: String,
@@ -58,9 +840,70 @@ pub struct Payouts {
     pub attempt_count: i16,
     pub profile_id: String,
     pub status: storage_enums::PayoutStatus,
+    pub original_payout_id: Option<String>,
+    pub hold_reason: Option<String>,
+    pub created_by: storage_enums::PayoutCreationSource,
+    pub network_token_ref: Option<String>,
+    pub connector_idempotency_key: Option<String>,
+    /// Bumped on every update of the payout, see [`PayoutsInterface::update_payout`].
+    pub sequence_number: i64,
+    /// When a `Scheduled` payout becomes due, see [`PayoutsInterface::activate_due_payouts`].
+    pub scheduled_at: Option<PrimitiveDateTime>,
+    /// How the connector of the payout was chosen, see [`PayoutRoutingDecision`]. Kept for
+    /// audit only, it is never returned to the merchant.
+    pub routing_decision: Option<pii::SecretSerdeValue>,
+    /// When the payout was soft deleted, see
+    /// [`PayoutsInterface::soft_delete_all_payouts_for_merchant`].
+    pub deleted_at: Option<PrimitiveDateTime>,
+    /// The payment this payout refunds to the original payment method, see
+    /// [`PayoutsInterface::find_payouts_by_source_payment_id`].
+    pub source_payment_id: Option<String>,
+    /// Whether the payout was made in test mode, see [`TestModeContext`].
+    pub test_mode: bool,
+    /// The earliest time at which the payout is retried, as asked by its connector, see
+    /// [`PayoutsInterface::find_payouts_needing_retry`].
+    pub next_retry_at: Option<PrimitiveDateTime>,
+    /// The connector the current attempt of the payout is made with, see
+    /// [`PayoutsInterface::reroute_payout`].
+    pub connector: Option<String>,
+    /// The id of the payout at its connector, once the connector accepted it.
+    pub connector_payout_id: Option<String>,
+    /// The last response of the connector to the payout. Kept for troubleshooting only, it may
+    /// hold personal data.
+    pub connector_response: Option<pii::SecretSerdeValue>,
+    /// The rate applied to convert the payout from its source to its destination currency, see
+    /// [`PayoutsUpdate::FxUpdate`].
+    pub exchange_rate: Option<Decimal>,
+    /// When the exchange rate was quoted.
+    pub exchange_rate_timestamp: Option<PrimitiveDateTime>,
+    /// Score given to the payout by a risk engine, see [`PayoutsUpdate::RiskUpdate`].
+    pub risk_score: Option<i16>,
+    /// Whether a risk engine flagged the payout as high-risk.
+    pub is_high_risk: bool,
+    /// Human-friendly sequential number of the payout among the payouts of its merchant, shown
+    /// as `PO-000123`, assigned by the store on insert. Numbers are never assigned twice but may
+    /// be skipped.
+    pub display_seq: i64,
+    /// The customer of the payout as they were when it was created, see
+    /// [`PayoutCustomerSnapshot`]. Never changed afterwards.
+    pub customer_snapshot: Option<pii::SecretSerdeValue>,
+    /// Why the payout was cancelled, see [`PayoutsInterface::cancel_payout_with_reversal`].
+    pub cancellation_reason: Option<String>,
+    /// The operator who last updated the payout, see
+    /// [`PayoutsInterface::list_payouts_modified_by`]. Updates which do not name their operator
+    /// leave it as is.
+    pub last_modified_by: Option<String>,
+    /// When the payout was fulfilled, stamped by the update which moved it to `Success`.
+    pub fulfilled_at: Option<PrimitiveDateTime>,
+    /// The payout this one is a split of, see [`PayoutsInterface::insert_split_payouts`].
+    pub parent_payout_id: Option<String>,
+    /// When the payout last moved to another status, stamped by the update which moved it.
+    /// `None` until its status changes after the insert, see [`Payouts::status_unchanged_since`].
+    pub status_changed_at: Option<PrimitiveDateTime>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Its `Debug` output redacts the same fields as the one of [`Payouts`].
+#[derive(Clone, Eq, PartialEq, router_derive::FieldNames)]
 pub struct PayoutsNew {
     pub payout_id: String,
     pub merchant_id: String,
@@ -82,6 +925,164 @@ pub struct PayoutsNew {
     pub profile_id: String,
     pub status: storage_enums::PayoutStatus,
     pub attempt_count: i16,
+    pub original_payout_id: Option<String>,
+    pub hold_reason: Option<String>,
+    pub created_by: storage_enums::PayoutCreationSource,
+    pub network_token_ref: Option<String>,
+    pub connector_idempotency_key: Option<String>,
+    /// Bumped on every update of the payout, see [`PayoutsInterface::update_payout`].
+    pub sequence_number: i64,
+    /// When a `Scheduled` payout becomes due, see [`PayoutsInterface::activate_due_payouts`].
+    pub scheduled_at: Option<PrimitiveDateTime>,
+    /// How the connector of the payout was chosen, see [`PayoutRoutingDecision`]. Kept for
+    /// audit only, it is never returned to the merchant.
+    pub routing_decision: Option<pii::SecretSerdeValue>,
+    /// When the payout was soft deleted, see
+    /// [`PayoutsInterface::soft_delete_all_payouts_for_merchant`].
+    pub deleted_at: Option<PrimitiveDateTime>,
+    /// The payment this payout refunds to the original payment method, see
+    /// [`PayoutsInterface::find_payouts_by_source_payment_id`].
+    pub source_payment_id: Option<String>,
+    /// Whether the payout was made in test mode, see [`TestModeContext`].
+    pub test_mode: bool,
+    /// The earliest time at which the payout is retried, as asked by its connector, see
+    /// [`PayoutsInterface::find_payouts_needing_retry`].
+    pub next_retry_at: Option<PrimitiveDateTime>,
+    /// The connector the current attempt of the payout is made with, see
+    /// [`PayoutsInterface::reroute_payout`].
+    pub connector: Option<String>,
+    /// The id of the payout at its connector, once the connector accepted it.
+    pub connector_payout_id: Option<String>,
+    /// The last response of the connector to the payout. Kept for troubleshooting only, it may
+    /// hold personal data.
+    pub connector_response: Option<pii::SecretSerdeValue>,
+    /// The rate applied to convert the payout from its source to its destination currency, see
+    /// [`PayoutsUpdate::FxUpdate`].
+    pub exchange_rate: Option<Decimal>,
+    /// When the exchange rate was quoted.
+    pub exchange_rate_timestamp: Option<PrimitiveDateTime>,
+    /// Score given to the payout by a risk engine, see [`PayoutsUpdate::RiskUpdate`].
+    pub risk_score: Option<i16>,
+    /// Whether a risk engine flagged the payout as high-risk.
+    pub is_high_risk: bool,
+    /// Assigned by the store on insert, any value set here is overwritten, see
+    /// [`Payouts::display_seq`].
+    pub display_seq: i64,
+    /// See [`Payouts::customer_snapshot`].
+    pub customer_snapshot: Option<pii::SecretSerdeValue>,
+    /// See [`Payouts::parent_payout_id`].
+    pub parent_payout_id: Option<String>,
+}
+
+/// Stands in for the payout description, metadata, hold and cancellation reasons, routing
+/// decision, connector response and customer snapshot in `Debug` output, so that logging a payout
+/// never leaks the personal data those may hold.
+pub const REDACTED: &str = "[REDACTED]";
+
+fn redacted<T>(value: &Option<T>) -> Option<&'static str> {
+    value.as_ref().map(|_| REDACTED)
+}
+
+impl std::fmt::Debug for Payouts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Payouts")
+            .field("payout_id", &self.payout_id)
+            .field("merchant_id", &self.merchant_id)
+            .field("customer_id", &self.customer_id)
+            .field("address_id", &self.address_id)
+            .field("payout_type", &self.payout_type)
+            .field("payout_method_id", &self.payout_method_id)
+            .field("amount", &self.amount)
+            .field("destination_currency", &self.destination_currency)
+            .field("source_currency", &self.source_currency)
+            .field("description", &redacted(&self.description))
+            .field("recurring", &self.recurring)
+            .field("auto_fulfill", &self.auto_fulfill)
+            .field("return_url", &self.return_url)
+            .field("entity_type", &self.entity_type)
+            .field("metadata", &redacted(&self.metadata))
+            .field("created_at", &self.created_at)
+            .field("last_modified_at", &self.last_modified_at)
+            .field("attempt_count", &self.attempt_count)
+            .field("profile_id", &self.profile_id)
+            .field("status", &self.status)
+            .field("original_payout_id", &self.original_payout_id)
+            .field("hold_reason", &redacted(&self.hold_reason))
+            .field("created_by", &self.created_by)
+            .field("network_token_ref", &self.network_token_ref)
+            .field("connector_idempotency_key", &self.connector_idempotency_key)
+            .field("sequence_number", &self.sequence_number)
+            .field("scheduled_at", &self.scheduled_at)
+            .field("routing_decision", &redacted(&self.routing_decision))
+            .field("deleted_at", &self.deleted_at)
+            .field("source_payment_id", &self.source_payment_id)
+            .field("test_mode", &self.test_mode)
+            .field("next_retry_at", &self.next_retry_at)
+            .field("connector", &self.connector)
+            .field("connector_payout_id", &self.connector_payout_id)
+            .field("connector_response", &redacted(&self.connector_response))
+            .field("exchange_rate", &self.exchange_rate)
+            .field("exchange_rate_timestamp", &self.exchange_rate_timestamp)
+            .field("display_seq", &self.display_seq)
+            .field("risk_score", &self.risk_score)
+            .field("is_high_risk", &self.is_high_risk)
+            .field("customer_snapshot", &redacted(&self.customer_snapshot))
+            .field("cancellation_reason", &redacted(&self.cancellation_reason))
+            .field("last_modified_by", &self.last_modified_by)
+            .field("fulfilled_at", &self.fulfilled_at)
+            .field("parent_payout_id", &self.parent_payout_id)
+            .field("status_changed_at", &self.status_changed_at)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for PayoutsNew {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PayoutsNew")
+            .field("payout_id", &self.payout_id)
+            .field("merchant_id", &self.merchant_id)
+            .field("customer_id", &self.customer_id)
+            .field("address_id", &self.address_id)
+            .field("payout_type", &self.payout_type)
+            .field("payout_method_id", &self.payout_method_id)
+            .field("amount", &self.amount)
+            .field("destination_currency", &self.destination_currency)
+            .field("source_currency", &self.source_currency)
+            .field("description", &redacted(&self.description))
+            .field("recurring", &self.recurring)
+            .field("auto_fulfill", &self.auto_fulfill)
+            .field("return_url", &self.return_url)
+            .field("entity_type", &self.entity_type)
+            .field("metadata", &redacted(&self.metadata))
+            .field("created_at", &self.created_at)
+            .field("last_modified_at", &self.last_modified_at)
+            .field("profile_id", &self.profile_id)
+            .field("status", &self.status)
+            .field("attempt_count", &self.attempt_count)
+            .field("original_payout_id", &self.original_payout_id)
+            .field("hold_reason", &redacted(&self.hold_reason))
+            .field("created_by", &self.created_by)
+            .field("network_token_ref", &self.network_token_ref)
+            .field("connector_idempotency_key", &self.connector_idempotency_key)
+            .field("sequence_number", &self.sequence_number)
+            .field("scheduled_at", &self.scheduled_at)
+            .field("routing_decision", &redacted(&self.routing_decision))
+            .field("deleted_at", &self.deleted_at)
+            .field("source_payment_id", &self.source_payment_id)
+            .field("test_mode", &self.test_mode)
+            .field("next_retry_at", &self.next_retry_at)
+            .field("connector", &self.connector)
+            .field("connector_payout_id", &self.connector_payout_id)
+            .field("connector_response", &redacted(&self.connector_response))
+            .field("exchange_rate", &self.exchange_rate)
+            .field("exchange_rate_timestamp", &self.exchange_rate_timestamp)
+            .field("display_seq", &self.display_seq)
+            .field("risk_score", &self.risk_score)
+            .field("is_high_risk", &self.is_high_risk)
+            .field("customer_snapshot", &redacted(&self.customer_snapshot))
+            .field("parent_payout_id", &self.parent_payout_id)
+            .finish()
+    }
 }
 
 impl Default for PayoutsNew {
@@ -109,51 +1110,1095 @@ impl Default for PayoutsNew {
             profile_id: String::default(),
             status: storage_enums::PayoutStatus::default(),
             attempt_count: 1,
+            original_payout_id: None,
+            hold_reason: None,
+            created_by: storage_enums::PayoutCreationSource::default(),
+            network_token_ref: None,
+            connector_idempotency_key: None,
+            sequence_number: 0,
+            scheduled_at: None,
+            routing_decision: None,
+            deleted_at: None,
+            source_payment_id: None,
+            test_mode: false,
+            next_retry_at: None,
+            connector: None,
+            connector_payout_id: None,
+            connector_response: None,
+            exchange_rate: None,
+            exchange_rate_timestamp: None,
+            display_seq: 0,
+            risk_score: None,
+            is_high_risk: false,
+            customer_snapshot: None,
+            parent_payout_id: None,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum PayoutsUpdate {
-    Update {
-        amount: i64,
-        destination_currency: storage_enums::Currency,
-        source_currency: storage_enums::Currency,
-        description: Option<String>,
-        recurring: bool,
-        auto_fulfill: bool,
-        return_url: Option<String>,
-        entity_type: storage_enums::PayoutEntityType,
-        metadata: Option<pii::SecretSerdeValue>,
-        profile_id: Option<String>,
-        status: Option<storage_enums::PayoutStatus>,
-    },
-    PayoutMethodIdUpdate {
-        payout_method_id: Option<String>,
-    },
-    RecurringUpdate {
-        recurring: bool,
-    },
-    AttemptCountUpdate {
-        attempt_count: i16,
-    },
-}
+impl PayoutsNew {
+    /// Makes this payout a draft, inserted in `RequiresConfirmation` and only processed once
+    /// confirmed with [`PayoutsInterface::confirm_payout`].
+    pub fn into_draft(self) -> Self {
+        Self {
+            status: storage_enums::PayoutStatus::RequiresConfirmation,
+            ..self
+        }
+    }
+
+    /// Prepares this payout to be imported with [`PayoutsInterface::import_payout`].
+    ///
+    /// With `preserve_timestamps`, the supplied `created_at` and `last_modified_at` are kept as
+    /// is and must both be set, `last_modified_at` being no earlier than `created_at`. Otherwise
+    /// both are set to `now`.
+    pub fn for_import(
+        self,
+        preserve_timestamps: bool,
+        now: PrimitiveDateTime,
+    ) -> error_stack::Result<Self, errors::StorageError> {
+        if !preserve_timestamps {
+            return Ok(Self {
+                created_at: Some(now),
+                last_modified_at: Some(now),
+                ..self
+            });
+        }
+
+        let created_at = self.created_at.ok_or(errors::StorageError::InvalidValue {
+            field: "created_at",
+        })?;
+        let last_modified_at = self
+            .last_modified_at
+            .ok_or(errors::StorageError::InvalidValue {
+                field: "last_modified_at",
+            })?;
+        if last_modified_at < created_at {
+            return Err(errors::StorageError::InvalidValue {
+                field: "last_modified_at",
+            })
+            .into_report()
+            .attach_printable(format!(
+                "last_modified_at {last_modified_at} is earlier than created_at {created_at}"
+            ));
+        }
+        Ok(self)
+    }
+
+    /// Links `splits` to this payout as its splits, see
+    /// [`PayoutsInterface::insert_split_payouts`].
+    ///
+    /// Fails if there are no splits, or if they do not all belong to the merchant of this payout
+    /// and pay out in its destination currency, or if their amounts do not add up to its amount.
+    pub fn link_splits(
+        &self,
+        splits: Vec<Self>,
+    ) -> error_stack::Result<Vec<Self>, errors::StorageError> {
+        if splits.is_empty() {
+            return Err(errors::StorageError::InvalidValue { field: "splits" })
+                .into_report()
+                .attach_printable(format!("payout {} has no splits", self.payout_id));
+        }
+        if let Some(split) = splits
+            .iter()
+            .find(|split| split.merchant_id != self.merchant_id)
+        {
+            return Err(errors::StorageError::InvalidValue {
+                field: "merchant_id",
+            })
+            .into_report()
+            .attach_printable(format!(
+                "split {} does not belong to the merchant of payout {}",
+                split.payout_id, self.payout_id
+            ));
+        }
+        if let Some(split) = splits
+            .iter()
+            .find(|split| split.destination_currency != self.destination_currency)
+        {
+            return Err(errors::StorageError::InvalidValue {
+                field: "destination_currency",
+            })
+            .into_report()
+            .attach_printable(format!(
+                "split {} is not in the destination currency of payout {}",
+                split.payout_id, self.payout_id
+            ));
+        }
+        // Summed as i128 so that splits adding up past i64::MAX are a mismatch, not an overflow
+        let total = splits
+            .iter()
+            .map(|split| i128::from(split.amount))
+            .sum::<i128>();
+        if total != i128::from(self.amount) {
+            return Err(errors::StorageError::InvalidValue { field: "amount" })
+                .into_report()
+                .attach_printable(format!(
+                    "splits add up to {total}, payout {} amounts to {}",
+                    self.payout_id, self.amount
+                ));
+        }
+
+        Ok(splits
+            .into_iter()
+            .map(|split| Self {
+                parent_payout_id: Some(self.payout_id.clone()),
+                ..split
+            })
+            .collect())
+    }
+
+    /// The payout as inserted, its timestamps defaulting to `now`.
+    pub fn into_payout(self, now: PrimitiveDateTime) -> Payouts {
+        Payouts {
+            payout_id: self.payout_id,
+            merchant_id: self.merchant_id,
+            customer_id: self.customer_id,
+            address_id: self.address_id,
+            payout_type: self.payout_type,
+            payout_method_id: self.payout_method_id,
+            amount: self.amount,
+            destination_currency: self.destination_currency,
+            source_currency: self.source_currency,
+            description: self.description,
+            recurring: self.recurring,
+            auto_fulfill: self.auto_fulfill,
+            return_url: self.return_url,
+            entity_type: self.entity_type,
+            metadata: self.metadata,
+            created_at: self.created_at.unwrap_or(now),
+            last_modified_at: self.last_modified_at.unwrap_or(now),
+            profile_id: self.profile_id,
+            status: self.status,
+            attempt_count: self.attempt_count,
+            original_payout_id: self.original_payout_id,
+            hold_reason: self.hold_reason,
+            created_by: self.created_by,
+            network_token_ref: self.network_token_ref,
+            connector_idempotency_key: self.connector_idempotency_key,
+            sequence_number: self.sequence_number,
+            scheduled_at: self.scheduled_at,
+            routing_decision: self.routing_decision,
+            deleted_at: self.deleted_at,
+            source_payment_id: self.source_payment_id,
+            test_mode: self.test_mode,
+            next_retry_at: self.next_retry_at,
+            connector: self.connector,
+            connector_payout_id: self.connector_payout_id,
+            connector_response: self.connector_response,
+            exchange_rate: self.exchange_rate,
+            exchange_rate_timestamp: self.exchange_rate_timestamp,
+            display_seq: self.display_seq,
+            risk_score: self.risk_score,
+            is_high_risk: self.is_high_risk,
+            customer_snapshot: self.customer_snapshot,
+            cancellation_reason: None,
+            last_modified_by: None,
+            fulfilled_at: None,
+            parent_payout_id: self.parent_payout_id,
+            status_changed_at: None,
+        }
+    }
+}
+
+/// Deduplicates `currencies` and sorts them by their ISO 4217 code.
+pub fn sort_distinct_currencies(
+    currencies: impl IntoIterator<Item = storage_enums::Currency>,
+) -> Vec<storage_enums::Currency> {
+    let mut currencies = currencies
+        .into_iter()
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    currencies.sort_by_key(|currency| currency.to_string());
+    currencies
+}
+
+/// Sums `status_counts` per status, statuses absent from `status_counts` are absent from the
+/// result.
+pub fn sum_status_counts(
+    status_counts: impl IntoIterator<Item = (storage_enums::PayoutStatus, i64)>,
+) -> HashMap<storage_enums::PayoutStatus, i64> {
+    status_counts
+        .into_iter()
+        .fold(HashMap::new(), |mut counts, (status, count)| {
+            *counts.entry(status).or_default() += count;
+            counts
+        })
+}
+
+/// The total of the payout amounts summed by the database, see
+/// [`PayoutsInterface::sum_payout_amounts_by_merchant_id`]. `SUM` over no rows is `NULL`, which
+/// totals 0.
+pub fn total_payout_amount(
+    sum: Option<Decimal>,
+) -> error_stack::Result<i128, errors::StorageError> {
+    sum.map_or(Ok(0), |sum| {
+        sum.to_i128()
+            .ok_or(errors::StorageError::DeserializationFailed)
+            .into_report()
+            .attach_printable(format!("payout amounts sum to {sum}, out of range"))
+    })
+}
+
+/// Converts `major_amount`, an amount of `currency` in major units such as `"10.50"`, to the
+/// minor units stored as the payout `amount`.
+///
+/// Fails if the amount isn't a non-negative decimal number, or is more precise than the minor
+/// unit of the currency, e.g. fractional cents for USD.
+pub fn amount_from_major_units(
+    major_amount: &str,
+    currency: storage_enums::Currency,
+) -> error_stack::Result<i64, errors::StorageError> {
+    let invalid_amount = || {
+        Err(errors::StorageError::InvalidValue { field: "amount" })
+            .into_report()
+            .attach_printable(format!(
+                "{major_amount} is not a valid amount of {currency}"
+            ))
+    };
+
+    let (units, fraction) = major_amount.split_once('.').unwrap_or((major_amount, ""));
+    let fraction = fraction.trim_end_matches('0');
+    let is_decimal = |digits: &str| digits.chars().all(|digit| digit.is_ascii_digit());
+    let exponent = usize::try_from(currency.exponent()).unwrap_or_default();
+    if units.is_empty() || !is_decimal(units) || !is_decimal(fraction) || fraction.len() > exponent
+    {
+        return invalid_amount();
+    }
+
+    // Pads the fraction to the minor unit of the currency, "10.5" being 1050 cents
+    match format!("{units}{fraction:0<exponent$}").parse::<i64>() {
+        Ok(amount) => Ok(amount),
+        Err(_) => invalid_amount(),
+    }
+}
+
+/// Sets `value` at `path` within `target`, following the semantics of Postgres `jsonb_set`.
+///
+/// Every segment of `path` addresses a key of a JSON object. The leaf key is inserted or
+/// overwritten. Intermediate objects are created only when `create_missing` is set, otherwise
+/// `target` is left untouched if any of them is absent.
+pub fn set_json_path(
+    target: &mut serde_json::Value,
+    path: &[String],
+    value: serde_json::Value,
+    create_missing: bool,
+) {
+    let Some((leaf, parents)) = path.split_last() else {
+        return;
+    };
+
+    let mut current = target;
+    for key in parents {
+        let Some(object) = current.as_object_mut() else {
+            return;
+        };
+        current = if create_missing {
+            object
+                .entry(key.clone())
+                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
+        } else {
+            match object.get_mut(key) {
+                Some(next) => next,
+                None => return,
+            }
+        };
+    }
+
+    if let Some(object) = current.as_object_mut() {
+        object.insert(leaf.clone(), value);
+    }
+}
+
+impl Payouts {
+    /// The reference of this payout shown to merchants, built from its
+    /// [`Payouts::display_seq`], such as `PO-000123`.
+    pub fn display_reference(&self) -> String {
+        format!("PO-{:06}", self.display_seq)
+    }
+
+    /// The update confirming this payout, `None` if it is already confirmed.
+    ///
+    /// Only drafts, in `RequiresConfirmation`, can be confirmed. A payout in `Pending` is
+    /// taken as already confirmed, so that confirming a payout twice is not an error.
+    pub fn confirm_update(
+        &self,
+    ) -> error_stack::Result<Option<PayoutsUpdate>, errors::StorageError> {
+        match self.status {
+            storage_enums::PayoutStatus::RequiresConfirmation => {
+                Ok(Some(PayoutsUpdate::ConfirmUpdate))
+            }
+            storage_enums::PayoutStatus::Pending => Ok(None),
+            status => Err(errors::StorageError::InvalidValue { field: "status" })
+                .into_report()
+                .attach_printable(format!(
+                    "payout {} in status {status} cannot be confirmed",
+                    self.payout_id
+                )),
+        }
+    }
+
+    /// The update cancelling this payout for `reason`, see
+    /// [`PayoutsInterface::cancel_payout_with_reversal`].
+    ///
+    /// Only payouts in a non-terminal state can be cancelled, and the reversal attempt, if any,
+    /// must be an attempt of this payout.
+    pub fn cancel_update(
+        &self,
+        reason: String,
+        reversal: Option<&PayoutAttemptNew>,
+    ) -> error_stack::Result<PayoutsUpdate, errors::StorageError> {
+        if let Some(reversal) = reversal {
+            if reversal.merchant_id != self.merchant_id || reversal.payout_id != self.payout_id {
+                return Err(errors::StorageError::InvalidValue { field: "payout_id" })
+                    .into_report()
+                    .attach_printable(format!(
+                        "reversal attempt {} is not an attempt of payout {}",
+                        reversal.payout_attempt_id, self.payout_id
+                    ));
+            }
+        }
+        let cancel_update = PayoutsUpdate::CancelUpdate {
+            cancellation_reason: reason,
+        };
+        cancel_update.validate_transition(self)?;
+        Ok(cancel_update)
+    }
+
+    /// The update setting the attempt count of this payout to `attempts`, the number of its
+    /// attempt records, `None` if the count is right already.
+    pub fn attempt_count_repair(
+        &self,
+        attempts: i64,
+    ) -> error_stack::Result<Option<PayoutsUpdate>, errors::StorageError> {
+        let attempt_count = i16::try_from(attempts)
+            .into_report()
+            .change_context(errors::StorageError::InvalidValue {
+                field: "attempt_count",
+            })
+            .attach_printable_lazy(|| {
+                format!("payout {} has {attempts} attempts", self.payout_id)
+            })?;
+        Ok((attempt_count != self.attempt_count)
+            .then_some(PayoutsUpdate::AttemptCountUpdate { attempt_count }))
+    }
+
+    /// The adjustment to record along with `update`, `None` if it leaves the amount of this
+    /// payout as is.
+    pub fn amount_adjustment(&self, update: &PayoutsUpdate) -> Option<PayoutAmountAdjustment> {
+        let (new_amount, reason) = match update {
+            PayoutsUpdate::Update { amount, .. } => (*amount, "payout_update"),
+            PayoutsUpdate::CombinedUpdate {
+                amount: Some(amount),
+                ..
+            } => (*amount, "combined_update"),
+            _ => return None,
+        };
+        (new_amount != self.amount).then(|| PayoutAmountAdjustment {
+            merchant_id: self.merchant_id.clone(),
+            payout_id: self.payout_id.clone(),
+            old_amount: self.amount,
+            new_amount,
+            reason: reason.to_string(),
+            created_at: common_utils::date_time::now(),
+        })
+    }
+
+    /// Whether the metadata of this payout holds `value` under the top level `key`, following
+    /// the jsonb `@>` containment of `{key: value}`: objects match on a subset of their keys,
+    /// arrays on a subset of their elements, and scalars on equality.
+    pub fn metadata_contains(&self, key: &str, value: &serde_json::Value) -> bool {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.peek().get(key))
+            .is_some_and(|held| json_contains(held, value))
+    }
+
+    /// The cursor to pass to [`PayoutsInterface::list_payouts_by_status_before`] for the page
+    /// following the one this payout ends.
+    pub fn page_cursor(&self) -> PayoutPageCursor {
+        PayoutPageCursor {
+            last_modified_at: self.last_modified_at,
+            payout_id: self.payout_id.clone(),
+        }
+    }
+
+    /// Whether a retry worker can claim this payout at `now`.
+    ///
+    /// This mirrors the conditions applied by the claim query.
+    pub fn is_claimable_for_retry(&self, now: PrimitiveDateTime, max_attempts: i16) -> bool {
+        self.status == storage_enums::PayoutStatus::Pending
+            && self.attempt_count < max_attempts
+            && self.last_modified_at <= now - PAYOUT_RETRY_CLAIM_LEASE
+            && self.is_due_for_retry(now)
+    }
+
+    /// Whether the retry delay asked by the connector of this payout has elapsed at `now`, which
+    /// is the case when it didn't ask for any.
+    pub fn is_due_for_retry(&self, now: PrimitiveDateTime) -> bool {
+        self.next_retry_at
+            .map_or(true, |next_retry_at| next_retry_at <= now)
+    }
+
+    /// The update moving this payout to `connector` for its next attempt.
+    ///
+    /// The details of the attempt at the previous connector are cleared, and the attempt count
+    /// is bumped along with a fresh connector idempotency key, so that the new connector never
+    /// sees a key or id which was issued for another one.
+    pub fn reroute_update(&self, connector: String) -> PayoutsUpdate {
+        PayoutsUpdate::RerouteUpdate {
+            connector,
+            connector_idempotency_key: generate_connector_idempotency_key(),
+            attempt_count: self.attempt_count + 1,
+        }
+    }
+
+    /// Parses the routing decision recorded on this payout, `None` if none was recorded.
+    pub fn parsed_routing_decision(
+        &self,
+    ) -> error_stack::Result<Option<PayoutRoutingDecision>, errors::StorageError> {
+        self.routing_decision
+            .as_ref()
+            .map(|routing_decision| {
+                serde_json::from_value(routing_decision.peek().clone())
+                    .into_report()
+                    .change_context(errors::StorageError::DeserializationFailed)
+                    .attach_printable("invalid routing decision stored on payout")
+            })
+            .transpose()
+    }
+
+    /// Parses the customer snapshot taken when this payout was created, `None` for payouts
+    /// created before snapshots were taken.
+    pub fn parsed_customer_snapshot(
+        &self,
+    ) -> error_stack::Result<Option<PayoutCustomerSnapshot>, errors::StorageError> {
+        self.customer_snapshot
+            .as_ref()
+            .map(|customer_snapshot| {
+                serde_json::from_value(customer_snapshot.peek().clone())
+                    .into_report()
+                    .change_context(errors::StorageError::DeserializationFailed)
+                    .attach_printable("invalid customer snapshot stored on payout")
+            })
+            .transpose()
+    }
+
+    /// Marks this payout as soft deleted at `deleted_at`, returning whether it was not already.
+    ///
+    /// A payout keeps the time it was first deleted at, so that deleting it again is a no-op.
+    pub fn mark_deleted(&mut self, deleted_at: PrimitiveDateTime) -> bool {
+        if self.deleted_at.is_some() {
+            return false;
+        }
+        self.deleted_at = Some(deleted_at);
+        true
+    }
+
+    /// Assigns `profile_id` to this payout if it has no profile, returning whether it had none.
+    ///
+    /// Payouts created before profiles were required hold an empty `profile_id`.
+    pub fn backfill_profile_id(&mut self, profile_id: &str) -> bool {
+        if !self.profile_id.is_empty() {
+            return false;
+        }
+        self.profile_id = profile_id.to_owned();
+        true
+    }
+
+    /// Whether `last_modified_at` is earlier than `created_at`, as clock skew between the
+    /// writers of a payout can leave it, which breaks the ordering of payouts by modification.
+    pub fn has_inconsistent_timestamps(&self) -> bool {
+        self.last_modified_at < self.created_at
+    }
+
+    /// Clamps `last_modified_at` to at least `created_at`, returning whether it was earlier.
+    pub fn repair_timestamps(&mut self) -> bool {
+        if !self.has_inconsistent_timestamps() {
+            return false;
+        }
+        self.last_modified_at = self.created_at;
+        true
+    }
+
+    /// Whether this payout is scheduled and due at `now`.
+    ///
+    /// This mirrors the conditions applied by the activation query.
+    pub fn is_due_for_activation(&self, now: PrimitiveDateTime) -> bool {
+        self.status == storage_enums::PayoutStatus::Scheduled
+            && self
+                .scheduled_at
+                .is_some_and(|scheduled_at| scheduled_at <= now)
+    }
+
+    /// Keeps at most `limit` of the payouts of `payouts` due for activation at `now`, earliest
+    /// `scheduled_at` first, as the activation query does.
+    pub fn due_for_activation(
+        payouts: Vec<Self>,
+        now: PrimitiveDateTime,
+        limit: usize,
+    ) -> Vec<Self> {
+        let mut payouts = payouts
+            .into_iter()
+            .filter(|payout| payout.is_due_for_activation(now))
+            .collect::<Vec<_>>();
+        payouts.sort_by(|a, b| {
+            a.scheduled_at
+                .cmp(&b.scheduled_at)
+                .then_with(|| a.payout_id.cmp(&b.payout_id))
+        });
+        payouts.truncate(limit);
+        payouts
+    }
+
+    /// Since when the payout has been in its current status: the time of its last status change,
+    /// or its creation if its status never changed.
+    pub fn status_unchanged_since(&self) -> PrimitiveDateTime {
+        self.status_changed_at.unwrap_or(self.created_at)
+    }
+
+    /// The seconds it took to fulfil the payout since it was created, `None` if it was not
+    /// fulfilled.
+    pub fn processing_seconds(&self) -> Option<f64> {
+        self.fulfilled_at
+            .map(|fulfilled_at| (fulfilled_at - self.created_at).as_seconds_f64())
+    }
+
+    /// Averages the [`Payouts::processing_seconds`] of the fulfilled `payouts`, as done by the
+    /// database for [`PayoutsInterface::average_payout_processing_seconds`].
+    pub fn average_processing_seconds<'a>(
+        payouts: impl IntoIterator<Item = &'a Self>,
+    ) -> Option<f64> {
+        let (total, count) = payouts
+            .into_iter()
+            .filter_map(Self::processing_seconds)
+            .fold((0.0, 0u32), |(total, count), seconds| {
+                (total + seconds, count + 1)
+            });
+        (count > 0).then(|| total / f64::from(count))
+    }
+
+    /// Sorts `payouts` in the order every payout list or search result is returned in: newest
+    /// `created_at` first, with ties broken by `payout_id`, also descending.
+    ///
+    /// This mirrors the `ORDER BY created_at DESC, payout_id DESC` applied by the database
+    /// queries, so that results read from KV are ordered the same way and pagination is stable.
+    pub fn sort_for_listing(payouts: &mut [Self]) {
+        payouts.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.payout_id.cmp(&a.payout_id))
+        });
+    }
+
+    /// Returns the most recent of `payouts`, in the order of [`Payouts::sort_for_listing`].
+    pub fn latest(payouts: Vec<Self>) -> Option<Self> {
+        let mut payouts = payouts;
+        Self::sort_for_listing(&mut payouts);
+        payouts.into_iter().next()
+    }
+
+    /// Keeps the payouts of `payouts` modified at or after `since`, in the order of
+    /// [`Payouts::sort_for_listing`].
+    pub fn modified_since(payouts: Vec<Self>, since: PrimitiveDateTime) -> Vec<Self> {
+        let mut payouts = payouts
+            .into_iter()
+            .filter(|payout| payout.last_modified_at >= since)
+            .collect::<Vec<_>>();
+        Self::sort_for_listing(&mut payouts);
+        payouts
+    }
+
+    /// Builds a new payout carrying over the details of this payout, for connectors which
+    /// require a fresh payout id when retrying.
+    ///
+    /// The status and attempt count are reset, and the new payout is linked back to this one
+    /// through `original_payout_id`.
+    pub fn to_new_payout_for_retry(&self, new_payout_id: String) -> PayoutsNew {
+        PayoutsNew {
+            payout_id: new_payout_id,
+            merchant_id: self.merchant_id.clone(),
+            customer_id: self.customer_id.clone(),
+            address_id: self.address_id.clone(),
+            payout_type: self.payout_type,
+            payout_method_id: self.payout_method_id.clone(),
+            amount: self.amount,
+            destination_currency: self.destination_currency,
+            source_currency: self.source_currency,
+            description: self.description.clone(),
+            recurring: self.recurring,
+            auto_fulfill: self.auto_fulfill,
+            return_url: self.return_url.clone(),
+            entity_type: self.entity_type,
+            metadata: self.metadata.clone(),
+            profile_id: self.profile_id.clone(),
+            status: storage_enums::PayoutStatus::default(),
+            attempt_count: 0,
+            original_payout_id: Some(self.payout_id.clone()),
+            created_by: self.created_by,
+            network_token_ref: self.network_token_ref.clone(),
+            connector_idempotency_key: Some(generate_connector_idempotency_key()),
+            source_payment_id: self.source_payment_id.clone(),
+            test_mode: self.test_mode,
+            customer_snapshot: self.customer_snapshot.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PayoutsUpdate {
+    Update {
+        amount: i64,
+        destination_currency: storage_enums::Currency,
+        source_currency: storage_enums::Currency,
+        description: Option<String>,
+        recurring: bool,
+        auto_fulfill: bool,
+        return_url: Option<String>,
+        entity_type: storage_enums::PayoutEntityType,
+        metadata: Option<pii::SecretSerdeValue>,
+        profile_id: Option<String>,
+        status: Option<storage_enums::PayoutStatus>,
+        /// The operator making the update, recorded as [`Payouts::last_modified_by`].
+        last_modified_by: Option<String>,
+    },
+    PayoutMethodIdUpdate {
+        payout_method_id: Option<String>,
+    },
+    RecurringUpdate {
+        recurring: bool,
+    },
+    AttemptCountUpdate {
+        attempt_count: i16,
+    },
+    MetadataUpdate {
+        metadata: Option<pii::SecretSerdeValue>,
+    },
+    HoldUpdate {
+        hold_reason: String,
+    },
+    ResumeUpdate {
+        status: storage_enums::PayoutStatus,
+    },
+    NetworkTokenUpdate {
+        network_token_ref: Option<String>,
+    },
+    /// Sets the token sent to the connector to deduplicate retried requests of the attempt.
+    ConnectorIdempotencyKeyUpdate {
+        connector_idempotency_key: String,
+    },
+    /// Moves a scheduled payout which became due to `Pending`, see
+    /// [`PayoutsInterface::activate_due_payouts`].
+    ActivateUpdate,
+    /// Moves a draft payout to `Pending`, see [`PayoutsInterface::confirm_payout`].
+    ConfirmUpdate,
+    /// Moves the payout to `status`, see [`PayoutsInterface::update_payouts_status_batch`].
+    StatusUpdate {
+        status: storage_enums::PayoutStatus,
+    },
+    /// Records how the connector of the payout was chosen.
+    RoutingDecisionUpdate {
+        routing_decision: PayoutRoutingDecision,
+    },
+    /// Links the payout to the payment it refunds to the original payment method.
+    SourcePaymentUpdate {
+        source_payment_id: String,
+    },
+    /// Sets the earliest time at which the payout is retried, as asked by its connector, or
+    /// clears it so that the payout is retried as soon as possible.
+    NextRetryUpdate {
+        next_retry_at: Option<PrimitiveDateTime>,
+    },
+    /// Moves the payout to another connector for its next attempt, see
+    /// [`Payouts::reroute_update`].
+    RerouteUpdate {
+        connector: String,
+        connector_idempotency_key: String,
+        attempt_count: i16,
+    },
+    /// Records the exchange rate applied to convert the payout, which must be positive, and when
+    /// it was quoted.
+    FxUpdate {
+        exchange_rate: Decimal,
+        exchange_rate_timestamp: PrimitiveDateTime,
+    },
+    /// Records the score a risk engine gave to the payout, and whether it flagged it as
+    /// high-risk, see [`PayoutsInterface::list_high_risk_payouts`].
+    RiskUpdate {
+        risk_score: i16,
+        is_high_risk: bool,
+    },
+    /// Moves the payout to `Cancelled`, recording why, see
+    /// [`PayoutsInterface::cancel_payout_with_reversal`].
+    CancelUpdate {
+        cancellation_reason: String,
+    },
+    /// Several updates merged into a single changeset, see [`PayoutsUpdate::combine`].
+    CombinedUpdate {
+        amount: Option<i64>,
+        destination_currency: Option<storage_enums::Currency>,
+        source_currency: Option<storage_enums::Currency>,
+        description: Maybe<String>,
+        recurring: Option<bool>,
+        auto_fulfill: Option<bool>,
+        return_url: Maybe<String>,
+        entity_type: Option<storage_enums::PayoutEntityType>,
+        metadata: Maybe<pii::SecretSerdeValue>,
+        payout_method_id: Maybe<String>,
+        profile_id: Option<String>,
+        status: Option<storage_enums::PayoutStatus>,
+        attempt_count: Option<i16>,
+        network_token_ref: Maybe<String>,
+        connector_idempotency_key: Option<String>,
+        routing_decision: Option<pii::SecretSerdeValue>,
+        source_payment_id: Option<String>,
+        next_retry_at: Maybe<PrimitiveDateTime>,
+        exchange_rate: Option<Decimal>,
+        exchange_rate_timestamp: Option<PrimitiveDateTime>,
+        risk_score: Option<i16>,
+        is_high_risk: Option<bool>,
+        last_modified_by: Option<String>,
+    },
+    /// Changes the nullable fields of the payout, each of which is either left as is, cleared
+    /// or set.
+    Patch {
+        payout_method_id: Maybe<String>,
+        description: Maybe<String>,
+        return_url: Maybe<String>,
+        metadata: Maybe<pii::SecretSerdeValue>,
+        network_token_ref: Maybe<String>,
+    },
+}
+
+impl PayoutsUpdate {
+    /// The return URL this update sets on the payout, `None` if it leaves it as is or clears it.
+    pub fn return_url(&self) -> Option<&str> {
+        match self {
+            Self::Update { return_url, .. } => return_url.as_deref(),
+            Self::CombinedUpdate {
+                return_url: Maybe::Set(return_url),
+                ..
+            }
+            | Self::Patch {
+                return_url: Maybe::Set(return_url),
+                ..
+            } => Some(return_url),
+            _ => None,
+        }
+    }
+
+    /// Checks that this update is a legal status transition for `current`.
+    ///
+    /// Only payouts in a non-terminal state can be held or cancelled, and only held payouts can
+    /// be resumed, into a non-terminal state other than `HeldForReview`. Only scheduled payouts
+    /// can be activated, and only drafts confirmed. A plain status update moves a payout out of a
+    /// non-terminal state other than those, into any state but `HeldForReview`, and only such a
+    /// payout can be rerouted.
+    ///
+    /// The exchange rate set by the update, if any, must be positive.
+    pub fn validate_transition(
+        &self,
+        current: &Payouts,
+    ) -> error_stack::Result<(), errors::StorageError> {
+        if let Some(exchange_rate) = self.exchange_rate() {
+            validate_exchange_rate(exchange_rate)?;
+        }
+
+        let is_legal = match self {
+            Self::HoldUpdate { .. } => {
+                is_non_terminal_status(current.status)
+                    && current.status != storage_enums::PayoutStatus::HeldForReview
+            }
+            Self::ResumeUpdate { status } => {
+                current.status == storage_enums::PayoutStatus::HeldForReview
+                    && *status != storage_enums::PayoutStatus::HeldForReview
+                    && is_non_terminal_status(*status)
+            }
+            Self::ActivateUpdate => current.status == storage_enums::PayoutStatus::Scheduled,
+            Self::ConfirmUpdate => {
+                current.status == storage_enums::PayoutStatus::RequiresConfirmation
+            }
+            Self::StatusUpdate { status } => {
+                is_in_processing(current.status)
+                    && *status != storage_enums::PayoutStatus::HeldForReview
+            }
+            Self::RerouteUpdate { .. } => is_in_processing(current.status),
+            Self::CancelUpdate { .. } => is_non_terminal_status(current.status),
+            Self::Update { .. }
+            | Self::PayoutMethodIdUpdate { .. }
+            | Self::RecurringUpdate { .. }
+            | Self::AttemptCountUpdate { .. }
+            | Self::MetadataUpdate { .. }
+            | Self::NetworkTokenUpdate { .. }
+            | Self::ConnectorIdempotencyKeyUpdate { .. }
+            | Self::RoutingDecisionUpdate { .. }
+            | Self::SourcePaymentUpdate { .. }
+            | Self::NextRetryUpdate { .. }
+            | Self::FxUpdate { .. }
+            | Self::RiskUpdate { .. }
+            | Self::CombinedUpdate { .. }
+            | Self::Patch { .. } => true,
+        };
+
+        if is_legal {
+            Ok(())
+        } else {
+            Err(errors::StorageError::InvalidValue { field: "status" })
+                .into_report()
+                .attach_printable(format!(
+                    "illegal status transition for payout {} in status {}",
+                    current.payout_id, current.status
+                ))
+        }
+    }
+
+    /// Merges two updates to the same payout into a single changeset, so that they can be
+    /// persisted with one write.
+    ///
+    /// The updates are compatible as long as they don't set the same field to different values.
+    /// Hold, resume, activate, confirm and reroute updates carry their own transition rules and
+    /// are never combined.
+    pub fn combine(first: Self, second: Self) -> error_stack::Result<Self, errors::StorageError> {
+        if first.has_own_transition_rules() || second.has_own_transition_rules() {
+            return Err(errors::StorageError::InvalidValue { field: "status" })
+                .into_report()
+                .attach_printable("status transitions cannot be combined with other updates");
+        }
+
+        let first = PayoutsUpdateInternal::from(first);
+        let second = PayoutsUpdateInternal::from(second);
+        Ok(Self::CombinedUpdate {
+            amount: merge_update_field("amount", first.amount, second.amount)?,
+            destination_currency: merge_update_field(
+                "destination_currency",
+                first.destination_currency,
+                second.destination_currency,
+            )?,
+            source_currency: merge_update_field(
+                "source_currency",
+                first.source_currency,
+                second.source_currency,
+            )?,
+            description: merge_maybe_field("description", first.description, second.description)?,
+            recurring: merge_update_field("recurring", first.recurring, second.recurring)?,
+            auto_fulfill: merge_update_field(
+                "auto_fulfill",
+                first.auto_fulfill,
+                second.auto_fulfill,
+            )?,
+            return_url: merge_maybe_field("return_url", first.return_url, second.return_url)?,
+            entity_type: merge_update_field("entity_type", first.entity_type, second.entity_type)?,
+            metadata: merge_maybe_field("metadata", first.metadata, second.metadata)?,
+            payout_method_id: merge_maybe_field(
+                "payout_method_id",
+                first.payout_method_id,
+                second.payout_method_id,
+            )?,
+            profile_id: merge_update_field("profile_id", first.profile_id, second.profile_id)?,
+            status: merge_update_field("status", first.status, second.status)?,
+            attempt_count: merge_update_field(
+                "attempt_count",
+                first.attempt_count,
+                second.attempt_count,
+            )?,
+            network_token_ref: merge_maybe_field(
+                "network_token_ref",
+                first.network_token_ref,
+                second.network_token_ref,
+            )?,
+            connector_idempotency_key: merge_update_field(
+                "connector_idempotency_key",
+                first.connector_idempotency_key,
+                second.connector_idempotency_key,
+            )?,
+            routing_decision: merge_update_field(
+                "routing_decision",
+                first.routing_decision,
+                second.routing_decision,
+            )?,
+            source_payment_id: merge_update_field(
+                "source_payment_id",
+                first.source_payment_id,
+                second.source_payment_id,
+            )?,
+            next_retry_at: merge_maybe_field(
+                "next_retry_at",
+                first.next_retry_at,
+                second.next_retry_at,
+            )?,
+            exchange_rate: merge_update_field(
+                "exchange_rate",
+                first.exchange_rate,
+                second.exchange_rate,
+            )?,
+            exchange_rate_timestamp: merge_update_field(
+                "exchange_rate_timestamp",
+                first.exchange_rate_timestamp,
+                second.exchange_rate_timestamp,
+            )?,
+            risk_score: merge_update_field("risk_score", first.risk_score, second.risk_score)?,
+            is_high_risk: merge_update_field(
+                "is_high_risk",
+                first.is_high_risk,
+                second.is_high_risk,
+            )?,
+            last_modified_by: merge_update_field(
+                "last_modified_by",
+                first.last_modified_by,
+                second.last_modified_by,
+            )?,
+        })
+    }
+
+    fn exchange_rate(&self) -> Option<&Decimal> {
+        match self {
+            Self::FxUpdate { exchange_rate, .. } => Some(exchange_rate),
+            Self::CombinedUpdate { exchange_rate, .. } => exchange_rate.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn has_own_transition_rules(&self) -> bool {
+        matches!(
+            self,
+            Self::HoldUpdate { .. }
+                | Self::ResumeUpdate { .. }
+                | Self::ActivateUpdate
+                | Self::ConfirmUpdate
+                | Self::StatusUpdate { .. }
+                | Self::RerouteUpdate { .. }
+                | Self::CancelUpdate { .. }
+        )
+    }
+}
+
+/// Checks each of `transitions` against the payout read for it in `current`, see
+/// [`PayoutsUpdate::StatusUpdate`]. Payouts missing from `current` fail with `ValueNotFound`.
+pub fn check_status_transitions(
+    current: &HashMap<PayoutId, Option<Payouts>>,
+    transitions: &[(PayoutId, storage_enums::PayoutStatus)],
+) -> Vec<(PayoutId, error_stack::Result<(), errors::StorageError>)> {
+    transitions
+        .iter()
+        .map(|(payout_id, status)| {
+            let outcome = match current.get(payout_id) {
+                Some(Some(payout)) => {
+                    PayoutsUpdate::StatusUpdate { status: *status }.validate_transition(payout)
+                }
+                _ => Err(errors::StorageError::ValueNotFound(format!(
+                    "payout {payout_id}"
+                )))
+                .into_report(),
+            };
+            (payout_id.clone(), outcome)
+        })
+        .collect()
+}
+
+fn validate_exchange_rate(
+    exchange_rate: &Decimal,
+) -> error_stack::Result<(), errors::StorageError> {
+    if exchange_rate.is_sign_positive() && !exchange_rate.is_zero() {
+        Ok(())
+    } else {
+        Err(errors::StorageError::InvalidValue {
+            field: "exchange_rate",
+        })
+        .into_report()
+        .attach_printable(format!("exchange rate {exchange_rate} is not positive"))
+    }
+}
+
+fn merge_update_field<T: PartialEq>(
+    field: &'static str,
+    first: Option<T>,
+    second: Option<T>,
+) -> error_stack::Result<Option<T>, errors::StorageError> {
+    match (first, second) {
+        (Some(first), Some(second)) if first != second => {
+            Err(errors::StorageError::InvalidValue { field })
+                .into_report()
+                .attach_printable(format!("conflicting values for {field} in combined update"))
+        }
+        (first, second) => Ok(first.or(second)),
+    }
+}
+
+fn merge_maybe_field<T: PartialEq>(
+    field: &'static str,
+    first: Maybe<T>,
+    second: Maybe<T>,
+) -> error_stack::Result<Maybe<T>, errors::StorageError> {
+    match (first, second) {
+        (first, second) if !first.is_unchanged() && !second.is_unchanged() && first != second => {
+            Err(errors::StorageError::InvalidValue { field })
+                .into_report()
+                .attach_printable(format!("conflicting values for {field} in combined update"))
+        }
+        (Maybe::Unchanged, second) => Ok(second),
+        (first, _) => Ok(first),
+    }
+}
+
+/// Whether the payout is being processed, i.e. in a non-terminal state and neither held,
+/// scheduled nor a draft.
+fn is_in_processing(status: storage_enums::PayoutStatus) -> bool {
+    is_non_terminal_status(status)
+        && !matches!(
+            status,
+            storage_enums::PayoutStatus::HeldForReview
+                | storage_enums::PayoutStatus::Scheduled
+                | storage_enums::PayoutStatus::RequiresConfirmation
+        )
+}
+
+/// The statuses among `statuses` a payout can be stale in, see
+/// [`PayoutsInterface::find_stale_payouts`].
+pub fn stale_payout_statuses(
+    statuses: Vec<storage_enums::PayoutStatus>,
+) -> Vec<storage_enums::PayoutStatus> {
+    statuses
+        .into_iter()
+        .filter(|status| is_non_terminal_status(*status))
+        .collect()
+}
+
+/// Whether a payout in `status` can still move to another status.
+pub fn is_non_terminal_status(status: storage_enums::PayoutStatus) -> bool {
+    !status.is_terminal_status()
+}
 
 #[derive(Clone, Debug, Default)]
 pub struct PayoutsUpdateInternal {
     pub amount: Option<i64>,
     pub destination_currency: Option<storage_enums::Currency>,
     pub source_currency: Option<storage_enums::Currency>,
-    pub description: Option<String>,
+    pub description: Maybe<String>,
     pub recurring: Option<bool>,
     pub auto_fulfill: Option<bool>,
-    pub return_url: Option<String>,
+    pub return_url: Maybe<String>,
     pub entity_type: Option<storage_enums::PayoutEntityType>,
-    pub metadata: Option<pii::SecretSerdeValue>,
-    pub payout_method_id: Option<String>,
+    pub metadata: Maybe<pii::SecretSerdeValue>,
+    pub payout_method_id: Maybe<String>,
     pub profile_id: Option<String>,
     pub status: Option<storage_enums::PayoutStatus>,
     pub attempt_count: Option<i16>,
+    pub hold_reason: Maybe<String>,
+    pub network_token_ref: Maybe<String>,
+    pub connector_idempotency_key: Option<String>,
+    pub routing_decision: Option<pii::SecretSerdeValue>,
+    pub source_payment_id: Option<String>,
+    pub next_retry_at: Maybe<PrimitiveDateTime>,
+    pub connector: Option<String>,
+    pub connector_payout_id: Maybe<String>,
+    pub connector_response: Maybe<pii::SecretSerdeValue>,
+    pub exchange_rate: Option<Decimal>,
+    pub exchange_rate_timestamp: Option<PrimitiveDateTime>,
+    pub risk_score: Option<i16>,
+    pub is_high_risk: Option<bool>,
+    pub cancellation_reason: Option<String>,
+    pub last_modified_by: Option<String>,
 }
 
 impl From<PayoutsUpdate> for PayoutsUpdateInternal {
@@ -171,22 +2216,24 @@ impl From<PayoutsUpdate> for PayoutsUpdateInternal {
                 metadata,
                 profile_id,
                 status,
+                last_modified_by,
             } => Self {
                 amount: Some(amount),
                 destination_currency: Some(destination_currency),
                 source_currency: Some(source_currency),
-                description,
+                description: Maybe::set_or_unchanged(description),
                 recurring: Some(recurring),
                 auto_fulfill: Some(auto_fulfill),
-                return_url,
+                return_url: Maybe::set_or_unchanged(return_url),
                 entity_type: Some(entity_type),
-                metadata,
+                metadata: Maybe::set_or_unchanged(metadata),
                 profile_id,
                 status,
+                last_modified_by,
                 ..Default::default()
             },
             PayoutsUpdate::PayoutMethodIdUpdate { payout_method_id } => Self {
-                payout_method_id,
+                payout_method_id: Maybe::set_or_unchanged(payout_method_id),
                 ..Default::default()
             },
             PayoutsUpdate::RecurringUpdate { recurring } => Self {
@@ -197,6 +2244,1799 @@ impl From<PayoutsUpdate> for PayoutsUpdateInternal {
                 attempt_count: Some(attempt_count),
                 ..Default::default()
             },
-        }
+            PayoutsUpdate::MetadataUpdate { metadata } => Self {
+                metadata: Maybe::set_or_unchanged(metadata),
+                ..Default::default()
+            },
+            PayoutsUpdate::HoldUpdate { hold_reason } => Self {
+                status: Some(storage_enums::PayoutStatus::HeldForReview),
+                hold_reason: Maybe::Set(hold_reason),
+                ..Default::default()
+            },
+            PayoutsUpdate::ResumeUpdate { status } => Self {
+                status: Some(status),
+                hold_reason: Maybe::SetNull,
+                ..Default::default()
+            },
+            PayoutsUpdate::StatusUpdate { status } => Self {
+                status: Some(status),
+                ..Default::default()
+            },
+            PayoutsUpdate::CancelUpdate {
+                cancellation_reason,
+            } => Self {
+                status: Some(storage_enums::PayoutStatus::Cancelled),
+                cancellation_reason: Some(cancellation_reason),
+                ..Default::default()
+            },
+            PayoutsUpdate::NetworkTokenUpdate { network_token_ref } => Self {
+                network_token_ref: Maybe::set_or_unchanged(network_token_ref),
+                ..Default::default()
+            },
+            PayoutsUpdate::ConnectorIdempotencyKeyUpdate {
+                connector_idempotency_key,
+            } => Self {
+                connector_idempotency_key: Some(connector_idempotency_key),
+                ..Default::default()
+            },
+            PayoutsUpdate::ActivateUpdate | PayoutsUpdate::ConfirmUpdate => Self {
+                status: Some(storage_enums::PayoutStatus::Pending),
+                ..Default::default()
+            },
+            PayoutsUpdate::RoutingDecisionUpdate { routing_decision } => Self {
+                routing_decision: Some(routing_decision.to_secret_value()),
+                ..Default::default()
+            },
+            PayoutsUpdate::SourcePaymentUpdate { source_payment_id } => Self {
+                source_payment_id: Some(source_payment_id),
+                ..Default::default()
+            },
+            PayoutsUpdate::NextRetryUpdate { next_retry_at } => Self {
+                next_retry_at: next_retry_at.map_or(Maybe::SetNull, Maybe::Set),
+                ..Default::default()
+            },
+            PayoutsUpdate::RerouteUpdate {
+                connector,
+                connector_idempotency_key,
+                attempt_count,
+            } => Self {
+                connector: Some(connector),
+                connector_payout_id: Maybe::SetNull,
+                connector_response: Maybe::SetNull,
+                connector_idempotency_key: Some(connector_idempotency_key),
+                attempt_count: Some(attempt_count),
+                ..Default::default()
+            },
+            PayoutsUpdate::FxUpdate {
+                exchange_rate,
+                exchange_rate_timestamp,
+            } => Self {
+                exchange_rate: Some(exchange_rate),
+                exchange_rate_timestamp: Some(exchange_rate_timestamp),
+                ..Default::default()
+            },
+            PayoutsUpdate::RiskUpdate {
+                risk_score,
+                is_high_risk,
+            } => Self {
+                risk_score: Some(risk_score),
+                is_high_risk: Some(is_high_risk),
+                ..Default::default()
+            },
+            PayoutsUpdate::CombinedUpdate {
+                amount,
+                destination_currency,
+                source_currency,
+                description,
+                recurring,
+                auto_fulfill,
+                return_url,
+                entity_type,
+                metadata,
+                payout_method_id,
+                profile_id,
+                status,
+                attempt_count,
+                network_token_ref,
+                connector_idempotency_key,
+                routing_decision,
+                source_payment_id,
+                next_retry_at,
+                exchange_rate,
+                exchange_rate_timestamp,
+                risk_score,
+                is_high_risk,
+                last_modified_by,
+            } => Self {
+                amount,
+                destination_currency,
+                source_currency,
+                description,
+                recurring,
+                auto_fulfill,
+                return_url,
+                entity_type,
+                metadata,
+                payout_method_id,
+                profile_id,
+                status,
+                attempt_count,
+                network_token_ref,
+                connector_idempotency_key,
+                routing_decision,
+                source_payment_id,
+                next_retry_at,
+                exchange_rate,
+                exchange_rate_timestamp,
+                risk_score,
+                is_high_risk,
+                last_modified_by,
+                ..Default::default()
+            },
+            PayoutsUpdate::Patch {
+                payout_method_id,
+                description,
+                return_url,
+                metadata,
+                network_token_ref,
+            } => Self {
+                payout_method_id,
+                description,
+                return_url,
+                metadata,
+                network_token_ref,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn sample_payout() -> Payouts {
+        let now = common_utils::date_time::now();
+        Payouts {
+            payout_id: "po_source".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            customer_id: "cus_1".to_string(),
+            address_id: "add_1".to_string(),
+            payout_type: storage_enums::PayoutType::Bank,
+            payout_method_id: Some("pm_1".to_string()),
+            amount: 1000,
+            destination_currency: storage_enums::Currency::EUR,
+            source_currency: storage_enums::Currency::USD,
+            description: Some("payout".to_string()),
+            recurring: true,
+            auto_fulfill: true,
+            return_url: Some("https://example.com".to_string()),
+            entity_type: storage_enums::PayoutEntityType::Individual,
+            metadata: None,
+            created_at: now,
+            last_modified_at: now,
+            attempt_count: 3,
+            profile_id: "pro_1".to_string(),
+            status: storage_enums::PayoutStatus::Failed,
+            original_payout_id: None,
+            hold_reason: None,
+            created_by: storage_enums::PayoutCreationSource::Api,
+            network_token_ref: Some("ntr_1".to_string()),
+            connector_idempotency_key: Some("idem_1".to_string()),
+            sequence_number: 4,
+            scheduled_at: None,
+            routing_decision: None,
+            deleted_at: None,
+            source_payment_id: Some("pay_1".to_string()),
+            test_mode: false,
+            next_retry_at: None,
+            connector: Some("adyen".to_string()),
+            connector_payout_id: Some("adyen_po_1".to_string()),
+            connector_response: Some(masking::Secret::new(
+                serde_json::json!({ "resultCode": "Refused" }),
+            )),
+            exchange_rate: None,
+            exchange_rate_timestamp: None,
+            display_seq: 123,
+            risk_score: None,
+            is_high_risk: false,
+            customer_snapshot: None,
+            cancellation_reason: None,
+            last_modified_by: None,
+            fulfilled_at: None,
+            parent_payout_id: None,
+            status_changed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_debug_redacts_personal_data() {
+        let payout = Payouts {
+            description: Some("rent for jane doe".to_string()),
+            metadata: Some(masking::Secret::new(
+                serde_json::json!({ "iban": "DE89370400440532013000" }),
+            )),
+            hold_reason: Some("called by jane doe".to_string()),
+            ..sample_payout()
+        };
+        let new = payout.to_new_payout_for_retry("po_retry".to_string());
+
+        for debug in [format!("{payout:?}"), format!("{new:?}")] {
+            assert!(debug.contains(REDACTED));
+            assert!(!debug.contains("jane doe"));
+            assert!(!debug.contains("DE89370400440532013000"));
+            assert!(debug.contains(&payout.merchant_id));
+            assert!(debug.contains(&payout.customer_id));
+        }
+        assert!(format!("{payout:?}").contains(&payout.payout_id));
+        assert!(format!("{new:?}").contains("po_retry"));
+    }
+
+    #[test]
+    fn test_new_payout_for_retry_copies_details() {
+        let source = sample_payout();
+        let new = source.to_new_payout_for_retry("po_retry".to_string());
+
+        assert_eq!(new.payout_id, "po_retry");
+        assert_eq!(new.merchant_id, source.merchant_id);
+        assert_eq!(new.customer_id, source.customer_id);
+        assert_eq!(new.address_id, source.address_id);
+        assert_eq!(new.payout_type, source.payout_type);
+        assert_eq!(new.payout_method_id, source.payout_method_id);
+        assert_eq!(new.amount, source.amount);
+        assert_eq!(new.destination_currency, source.destination_currency);
+        assert_eq!(new.source_currency, source.source_currency);
+        assert_eq!(new.description, source.description);
+        assert_eq!(new.recurring, source.recurring);
+        assert_eq!(new.auto_fulfill, source.auto_fulfill);
+        assert_eq!(new.return_url, source.return_url);
+        assert_eq!(new.entity_type, source.entity_type);
+        assert_eq!(new.profile_id, source.profile_id);
+    }
+
+    fn path(segments: &[&str]) -> Vec<String> {
+        segments.iter().map(|segment| segment.to_string()).collect()
+    }
+
+    #[test]
+    fn test_set_json_path_sets_nested_key() {
+        let mut metadata = serde_json::json!({ "tracking": { "source": "api" } });
+        set_json_path(
+            &mut metadata,
+            &path(&["tracking", "step"]),
+            serde_json::json!("x"),
+            false,
+        );
+        assert_eq!(
+            metadata,
+            serde_json::json!({ "tracking": { "source": "api", "step": "x" } })
+        );
+    }
+
+    #[test]
+    fn test_set_json_path_overwrites_existing_key() {
+        let mut metadata = serde_json::json!({ "tracking": { "step": "x" } });
+        set_json_path(
+            &mut metadata,
+            &path(&["tracking", "step"]),
+            serde_json::json!("y"),
+            false,
+        );
+        assert_eq!(metadata, serde_json::json!({ "tracking": { "step": "y" } }));
+    }
+
+    #[test]
+    fn test_set_json_path_create_missing() {
+        let mut metadata = serde_json::json!({});
+        set_json_path(
+            &mut metadata,
+            &path(&["tracking", "step"]),
+            serde_json::json!("x"),
+            false,
+        );
+        assert_eq!(metadata, serde_json::json!({}));
+
+        set_json_path(
+            &mut metadata,
+            &path(&["tracking", "step"]),
+            serde_json::json!("x"),
+            true,
+        );
+        assert_eq!(metadata, serde_json::json!({ "tracking": { "step": "x" } }));
+    }
+
+    #[test]
+    fn test_new_payout_for_retry_resets_state_and_links_source() {
+        let source = sample_payout();
+        let new = source.to_new_payout_for_retry("po_retry".to_string());
+
+        assert_eq!(new.status, storage_enums::PayoutStatus::RequiresCreation);
+        assert_eq!(new.attempt_count, 0);
+        assert_eq!(new.original_payout_id.as_deref(), Some("po_source"));
+    }
+
+    #[test]
+    fn test_new_payout_for_retry_keeps_network_token_ref() {
+        let source = sample_payout();
+        let new = source.to_new_payout_for_retry("po_retry".to_string());
+
+        assert_eq!(new.network_token_ref.as_deref(), Some("ntr_1"));
+    }
+
+    #[test]
+    fn test_new_payout_for_retry_regenerates_connector_idempotency_key() {
+        let source = sample_payout();
+        let first = source.to_new_payout_for_retry("po_retry_1".to_string());
+        let second = source.to_new_payout_for_retry("po_retry_2".to_string());
+
+        let first_key = first.connector_idempotency_key.unwrap();
+        assert!(first_key.starts_with("idem_"));
+        assert_ne!(Some(&first_key), source.connector_idempotency_key.as_ref());
+        assert_ne!(Some(first_key), second.connector_idempotency_key);
+    }
+
+    #[test]
+    fn test_connector_idempotency_key_update() {
+        let update = PayoutsUpdate::ConnectorIdempotencyKeyUpdate {
+            connector_idempotency_key: "idem_2".to_string(),
+        };
+        assert!(update.validate_transition(&sample_payout()).is_ok());
+
+        let internal = PayoutsUpdateInternal::from(update);
+        assert_eq!(
+            internal.connector_idempotency_key.as_deref(),
+            Some("idem_2")
+        );
+    }
+
+    #[test]
+    fn test_new_payout_for_retry_keeps_source_payment_id() {
+        let new = sample_payout().to_new_payout_for_retry("po_retry".to_string());
+
+        assert_eq!(new.source_payment_id.as_deref(), Some("pay_1"));
+    }
+
+    fn payouts_in_both_modes() -> Vec<Payouts> {
+        [("po_live", false), ("po_test", true)]
+            .into_iter()
+            .map(|(payout_id, test_mode)| Payouts {
+                payout_id: payout_id.to_string(),
+                test_mode,
+                ..sample_payout()
+            })
+            .collect()
+    }
+
+    fn payout_ids(payouts: &[Payouts]) -> Vec<&str> {
+        payouts
+            .iter()
+            .map(|payout| payout.payout_id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_live_context_never_finds_test_payouts() {
+        let payouts = TestModeContext::Live.retain(payouts_in_both_modes());
+        assert_eq!(payout_ids(&payouts), ["po_live"]);
+
+        let test_payout = payouts_in_both_modes().pop();
+        assert!(TestModeContext::Live.admit(test_payout).is_none());
+    }
+
+    #[test]
+    fn test_test_context_never_finds_live_payouts() {
+        let payouts = TestModeContext::Test.retain(payouts_in_both_modes());
+        assert_eq!(payout_ids(&payouts), ["po_test"]);
+
+        let live_payout = payouts_in_both_modes().into_iter().next();
+        assert!(TestModeContext::Test.admit(live_payout).is_none());
+    }
+
+    #[test]
+    fn test_cross_mode_context_finds_payouts_of_both_modes() {
+        let payouts = TestModeContext::CrossMode.retain(payouts_in_both_modes());
+        assert_eq!(payout_ids(&payouts), ["po_live", "po_test"]);
+    }
+
+    #[test]
+    fn test_payouts_are_inserted_in_the_mode_of_the_context() {
+        let new = PayoutsNew {
+            test_mode: true,
+            ..Default::default()
+        };
+        assert!(!TestModeContext::Live.apply_to(new.clone()).test_mode);
+        assert!(
+            TestModeContext::Test
+                .apply_to(PayoutsNew::default())
+                .test_mode
+        );
+        assert!(TestModeContext::CrossMode.apply_to(new).test_mode);
+
+        let test_payout = Payouts {
+            test_mode: true,
+            ..sample_payout()
+        };
+        assert!(
+            test_payout
+                .to_new_payout_for_retry("po_retry".to_string())
+                .test_mode
+        );
+    }
+
+    #[test]
+    fn test_source_payment_update() {
+        let update = PayoutsUpdate::SourcePaymentUpdate {
+            source_payment_id: "pay_2".to_string(),
+        };
+        assert!(update.validate_transition(&sample_payout()).is_ok());
+
+        let combined = PayoutsUpdate::combine(
+            update,
+            PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 },
+        )
+        .unwrap();
+        let internal = PayoutsUpdateInternal::from(combined);
+        assert_eq!(internal.source_payment_id.as_deref(), Some("pay_2"));
+        assert_eq!(internal.attempt_count, Some(2));
+
+        let error = PayoutsUpdate::combine(
+            PayoutsUpdate::SourcePaymentUpdate {
+                source_payment_id: "pay_2".to_string(),
+            },
+            PayoutsUpdate::SourcePaymentUpdate {
+                source_payment_id: "pay_3".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            errors::StorageError::InvalidValue {
+                field: "source_payment_id"
+            }
+        ));
+    }
+
+    #[test]
+    fn test_network_token_update() {
+        let update = PayoutsUpdate::NetworkTokenUpdate {
+            network_token_ref: Some("ntr_2".to_string()),
+        };
+        assert!(update.validate_transition(&sample_payout()).is_ok());
+
+        let combined = PayoutsUpdate::combine(
+            update,
+            PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 },
+        )
+        .unwrap();
+        let internal = PayoutsUpdateInternal::from(combined);
+        assert_eq!(internal.network_token_ref, Maybe::Set("ntr_2".to_string()));
+        assert_eq!(internal.attempt_count, Some(2));
+    }
+
+    #[test]
+    fn test_routing_decision_update() {
+        let decision = PayoutRoutingDecision {
+            algorithm_id: "algo_1".to_string(),
+            score: 0.75,
+        };
+        let update = PayoutsUpdate::RoutingDecisionUpdate {
+            routing_decision: decision.clone(),
+        };
+        assert!(update.validate_transition(&sample_payout()).is_ok());
+
+        let payout = Payouts {
+            routing_decision: PayoutsUpdateInternal::from(update).routing_decision,
+            ..sample_payout()
+        };
+        assert_eq!(payout.parsed_routing_decision().unwrap(), Some(decision));
+        assert!(!format!("{payout:?}").contains("algo_1"));
+    }
+
+    #[test]
+    fn test_invalid_routing_decision_is_rejected() {
+        let payout = Payouts {
+            routing_decision: Some(masking::Secret::new(serde_json::json!({ "score": "high" }))),
+            ..sample_payout()
+        };
+        let error = payout.parsed_routing_decision().unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            errors::StorageError::DeserializationFailed
+        ));
+    }
+
+    fn sample_customer_snapshot() -> PayoutCustomerSnapshot {
+        PayoutCustomerSnapshot {
+            customer_id: "cus_1".to_string(),
+            name: Some(masking::Secret::new("jane doe".to_string())),
+            email: Some(masking::Secret::new("jane@example.com".to_string())),
+            phone: Some(masking::Secret::new("5551234567".to_string())),
+            phone_country_code: Some("+1".to_string()),
+            description: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_customer_snapshot_is_read_back_and_redacted() {
+        let snapshot = sample_customer_snapshot();
+        let payout = Payouts {
+            customer_snapshot: Some(snapshot.to_secret_value()),
+            ..sample_payout()
+        };
+
+        assert_eq!(payout.parsed_customer_snapshot().unwrap(), Some(snapshot));
+        assert_eq!(sample_payout().parsed_customer_snapshot().unwrap(), None);
+        let debug = format!("{payout:?}");
+        assert!(!debug.contains("jane"));
+        assert!(!debug.contains("5551234567"));
+    }
+
+    #[test]
+    fn test_customer_snapshot_is_frozen() {
+        let mut customer = sample_customer_snapshot();
+        let payout = Payouts {
+            customer_snapshot: Some(customer.to_secret_value()),
+            ..sample_payout()
+        };
+
+        // The customer changes after the payout was created
+        customer.email = Some(masking::Secret::new("jane@example.org".to_string()));
+        customer.name = None;
+
+        let snapshot = payout.parsed_customer_snapshot().unwrap().unwrap();
+        assert_eq!(snapshot, sample_customer_snapshot());
+        assert_ne!(snapshot, customer);
+
+        // A retry is made for the same customer, as they were when the payout was created
+        let retry = payout.to_new_payout_for_retry("po_retry".to_string());
+        assert_eq!(retry.customer_snapshot, payout.customer_snapshot);
+    }
+
+    #[test]
+    fn test_timestamps_are_clamped_to_the_creation() {
+        let mut payout = sample_payout();
+        assert!(!payout.has_inconsistent_timestamps());
+        assert!(!payout.repair_timestamps());
+
+        payout.last_modified_at = payout.created_at - time::Duration::seconds(5);
+        assert!(payout.has_inconsistent_timestamps());
+        assert!(payout.repair_timestamps());
+        assert_eq!(payout.last_modified_at, payout.created_at);
+        assert!(!payout.has_inconsistent_timestamps());
+    }
+
+    #[test]
+    fn test_load_options_clear_the_heavy_fields_not_requested() {
+        let payout = Payouts {
+            connector_response: Some(masking::Secret::new(serde_json::json!({"id": "tr_1"}))),
+            routing_decision: Some(masking::Secret::new(serde_json::json!({"score": 1}))),
+            customer_snapshot: Some(sample_customer_snapshot().to_secret_value()),
+            ..sample_payout()
+        };
+
+        let light = PayoutLoadOptions::default().apply(payout.clone());
+        assert!(light.connector_response.is_none());
+        assert!(light.routing_decision.is_none());
+        assert!(light.customer_snapshot.is_none());
+        assert_eq!(light.payout_id, payout.payout_id);
+
+        let options = PayoutLoadOptions {
+            routing_decision: true,
+            ..Default::default()
+        };
+        let partial = options.apply(payout.clone());
+        assert!(partial.connector_response.is_none());
+        assert_eq!(partial.routing_decision, payout.routing_decision);
+        assert!(partial.customer_snapshot.is_none());
+        assert_eq!(
+            options.skipped_fields(),
+            ["connector_response", "customer_snapshot"]
+        );
+
+        assert_eq!(PayoutLoadOptions::ALL.apply(payout.clone()), payout);
+        assert!(PayoutLoadOptions::ALL.skipped_fields().is_empty());
+        assert!(PayoutLoadOptions::default()
+            .skipped_fields()
+            .iter()
+            .all(|field| Payouts::FIELD_NAMES.contains(field)));
+    }
+
+    #[test]
+    fn test_hold_non_terminal_payout() {
+        let mut payout = sample_payout();
+        payout.status = storage_enums::PayoutStatus::RequiresFulfillment;
+        let update = PayoutsUpdate::HoldUpdate {
+            hold_reason: "risk_review".to_string(),
+        };
+        assert!(update.validate_transition(&payout).is_ok());
+
+        let internal = PayoutsUpdateInternal::from(update);
+        assert_eq!(
+            internal.status,
+            Some(storage_enums::PayoutStatus::HeldForReview)
+        );
+        assert_eq!(internal.hold_reason, Maybe::Set("risk_review".to_string()));
+    }
+
+    #[test]
+    fn test_resume_held_payout() {
+        let mut payout = sample_payout();
+        payout.status = storage_enums::PayoutStatus::HeldForReview;
+        let update = PayoutsUpdate::ResumeUpdate {
+            status: storage_enums::PayoutStatus::RequiresFulfillment,
+        };
+        assert!(update.validate_transition(&payout).is_ok());
+
+        let internal = PayoutsUpdateInternal::from(update);
+        assert_eq!(
+            internal.status,
+            Some(storage_enums::PayoutStatus::RequiresFulfillment)
+        );
+        assert_eq!(internal.hold_reason, Maybe::SetNull);
+    }
+
+    #[test]
+    fn test_illegal_hold_transitions() {
+        let update = PayoutsUpdate::HoldUpdate {
+            hold_reason: "risk_review".to_string(),
+        };
+        for status in [
+            storage_enums::PayoutStatus::Success,
+            storage_enums::PayoutStatus::Failed,
+            storage_enums::PayoutStatus::Cancelled,
+            storage_enums::PayoutStatus::Ineligible,
+            storage_enums::PayoutStatus::HeldForReview,
+        ] {
+            let mut payout = sample_payout();
+            payout.status = status;
+            let error = update.validate_transition(&payout).unwrap_err();
+            assert!(matches!(
+                error.current_context(),
+                errors::StorageError::InvalidValue { field: "status" }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_illegal_resume_transitions() {
+        let resume = PayoutsUpdate::ResumeUpdate {
+            status: storage_enums::PayoutStatus::Pending,
+        };
+        let mut payout = sample_payout();
+        payout.status = storage_enums::PayoutStatus::Pending;
+        assert!(resume.validate_transition(&payout).is_err());
+
+        payout.status = storage_enums::PayoutStatus::HeldForReview;
+        for status in [
+            storage_enums::PayoutStatus::HeldForReview,
+            storage_enums::PayoutStatus::Success,
+        ] {
+            let resume = PayoutsUpdate::ResumeUpdate { status };
+            assert!(resume.validate_transition(&payout).is_err());
+        }
+    }
+
+    #[test]
+    fn test_mark_deleted_skips_deleted_payouts() {
+        let first_deleted_at = common_utils::date_time::now();
+        let mut payouts = vec![
+            sample_payout(),
+            Payouts {
+                deleted_at: Some(first_deleted_at),
+                ..sample_payout()
+            },
+            sample_payout(),
+        ];
+
+        let deleted_at = first_deleted_at + time::Duration::hours(1);
+        let deleted = payouts
+            .iter_mut()
+            .filter(|payout| payout.mark_deleted(deleted_at))
+            .count();
+        assert_eq!(deleted, 2);
+        assert_eq!(payouts[1].deleted_at, Some(first_deleted_at));
+        assert!(payouts.iter().all(|payout| payout.deleted_at.is_some()));
+
+        let deleted_again = payouts
+            .iter_mut()
+            .filter(|payout| payout.mark_deleted(deleted_at))
+            .count();
+        assert_eq!(deleted_again, 0);
+    }
+
+    #[test]
+    fn test_backfill_profile_id_skips_payouts_with_a_profile() {
+        let mut payouts = vec![
+            Payouts {
+                profile_id: String::new(),
+                ..sample_payout()
+            },
+            Payouts {
+                profile_id: "pro_existing".to_string(),
+                ..sample_payout()
+            },
+        ];
+
+        let backfilled = payouts
+            .iter_mut()
+            .filter(|payout| payout.backfill_profile_id("pro_default"))
+            .count();
+        assert_eq!(backfilled, 1);
+        let profile_ids = payouts
+            .iter()
+            .map(|payout| payout.profile_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(profile_ids, ["pro_default", "pro_existing"]);
+    }
+
+    #[test]
+    fn test_due_for_activation_keeps_due_scheduled_payouts_in_order() {
+        let now = common_utils::date_time::now();
+        let scheduled = |payout_id: &str, status, scheduled_at| Payouts {
+            payout_id: payout_id.to_string(),
+            status,
+            scheduled_at,
+            ..sample_payout()
+        };
+        let payouts = vec![
+            scheduled(
+                "po_later",
+                storage_enums::PayoutStatus::Scheduled,
+                Some(now - time::Duration::minutes(1)),
+            ),
+            scheduled(
+                "po_future",
+                storage_enums::PayoutStatus::Scheduled,
+                Some(now + time::Duration::minutes(1)),
+            ),
+            scheduled(
+                "po_earlier",
+                storage_enums::PayoutStatus::Scheduled,
+                Some(now - time::Duration::minutes(5)),
+            ),
+            scheduled(
+                "po_pending",
+                storage_enums::PayoutStatus::Pending,
+                Some(now - time::Duration::minutes(10)),
+            ),
+            scheduled(
+                "po_unscheduled",
+                storage_enums::PayoutStatus::Scheduled,
+                None,
+            ),
+            scheduled("po_now", storage_enums::PayoutStatus::Scheduled, Some(now)),
+        ];
+
+        let due = Payouts::due_for_activation(payouts.clone(), now, 10)
+            .into_iter()
+            .map(|payout| payout.payout_id)
+            .collect::<Vec<_>>();
+        assert_eq!(due, ["po_earlier", "po_later", "po_now"]);
+
+        let due = Payouts::due_for_activation(payouts, now, 2)
+            .into_iter()
+            .map(|payout| payout.payout_id)
+            .collect::<Vec<_>>();
+        assert_eq!(due, ["po_earlier", "po_later"]);
+    }
+
+    #[test]
+    fn test_activate_only_scheduled_payouts() {
+        let mut payout = sample_payout();
+        payout.status = storage_enums::PayoutStatus::Scheduled;
+        assert!(PayoutsUpdate::ActivateUpdate
+            .validate_transition(&payout)
+            .is_ok());
+        assert_eq!(
+            PayoutsUpdateInternal::from(PayoutsUpdate::ActivateUpdate).status,
+            Some(storage_enums::PayoutStatus::Pending)
+        );
+
+        for status in [
+            storage_enums::PayoutStatus::Pending,
+            storage_enums::PayoutStatus::HeldForReview,
+            storage_enums::PayoutStatus::Success,
+            storage_enums::PayoutStatus::Cancelled,
+        ] {
+            payout.status = status;
+            let error = PayoutsUpdate::ActivateUpdate
+                .validate_transition(&payout)
+                .unwrap_err();
+            assert!(matches!(
+                error.current_context(),
+                errors::StorageError::InvalidValue { field: "status" }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_sort_for_listing_breaks_created_at_ties_by_payout_id() {
+        let created_at = common_utils::date_time::now();
+        let mut payouts = ["po_b", "po_c", "po_a"]
+            .into_iter()
+            .map(|payout_id| Payouts {
+                payout_id: payout_id.to_string(),
+                created_at,
+                ..sample_payout()
+            })
+            .collect::<Vec<_>>();
+        let mut older = sample_payout();
+        older.payout_id = "po_z".to_string();
+        older.created_at = created_at - time::Duration::seconds(1);
+        payouts.insert(0, older);
+
+        Payouts::sort_for_listing(&mut payouts);
+
+        let ids = payouts
+            .iter()
+            .map(|payout| payout.payout_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["po_c", "po_b", "po_a", "po_z"]);
+    }
+
+    #[test]
+    fn test_sort_distinct_currencies_dedups_and_orders_by_code() {
+        let currencies = sort_distinct_currencies([
+            storage_enums::Currency::USD,
+            storage_enums::Currency::EUR,
+            storage_enums::Currency::USD,
+            storage_enums::Currency::GBP,
+            storage_enums::Currency::EUR,
+        ]);
+        assert_eq!(
+            currencies,
+            [
+                storage_enums::Currency::EUR,
+                storage_enums::Currency::GBP,
+                storage_enums::Currency::USD,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_combine_merges_status_and_error_details() {
+        let status_update = PayoutsUpdate::Update {
+            amount: 1000,
+            destination_currency: storage_enums::Currency::EUR,
+            source_currency: storage_enums::Currency::USD,
+            description: None,
+            recurring: false,
+            auto_fulfill: true,
+            return_url: None,
+            entity_type: storage_enums::PayoutEntityType::Individual,
+            metadata: None,
+            profile_id: None,
+            status: Some(storage_enums::PayoutStatus::Failed),
+            last_modified_by: Some("operator_1".to_string()),
+        };
+        let error_metadata: pii::SecretSerdeValue =
+            masking::Secret::new(serde_json::json!({ "error": "connector_timeout" }));
+        let error_update = PayoutsUpdate::MetadataUpdate {
+            metadata: Some(error_metadata.clone()),
+        };
+
+        let combined = PayoutsUpdate::combine(status_update, error_update).unwrap();
+        let combined = PayoutsUpdate::combine(
+            combined,
+            PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 },
+        )
+        .unwrap();
+
+        let internal = PayoutsUpdateInternal::from(combined);
+        assert_eq!(internal.status, Some(storage_enums::PayoutStatus::Failed));
+        assert_eq!(internal.metadata, Maybe::Set(error_metadata));
+        assert_eq!(internal.attempt_count, Some(2));
+        assert_eq!(internal.amount, Some(1000));
+        assert_eq!(internal.last_modified_by.as_deref(), Some("operator_1"));
+    }
+
+    #[test]
+    fn test_combine_rejects_conflicting_statuses() {
+        let update_with_status = |status| PayoutsUpdate::CombinedUpdate {
+            amount: None,
+            destination_currency: None,
+            source_currency: None,
+            description: Maybe::Unchanged,
+            recurring: None,
+            auto_fulfill: None,
+            return_url: Maybe::Unchanged,
+            entity_type: None,
+            metadata: Maybe::Unchanged,
+            payout_method_id: Maybe::Unchanged,
+            profile_id: None,
+            status: Some(status),
+            attempt_count: None,
+            network_token_ref: Maybe::Unchanged,
+            connector_idempotency_key: None,
+            routing_decision: None,
+            source_payment_id: None,
+            next_retry_at: Maybe::Unchanged,
+            exchange_rate: None,
+            exchange_rate_timestamp: None,
+            risk_score: None,
+            is_high_risk: None,
+            last_modified_by: None,
+        };
+
+        let error = PayoutsUpdate::combine(
+            update_with_status(storage_enums::PayoutStatus::Pending),
+            update_with_status(storage_enums::PayoutStatus::Failed),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            errors::StorageError::InvalidValue { field: "status" }
+        ));
+    }
+
+    #[test]
+    fn test_combine_rejects_hold_updates() {
+        let result = PayoutsUpdate::combine(
+            PayoutsUpdate::HoldUpdate {
+                hold_reason: "risk_review".to_string(),
+            },
+            PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_persistence_info_per_storage_scheme() {
+        for kv_mirrored in [true, false] {
+            assert_eq!(
+                PersistenceInfo::for_insert(MerchantStorageScheme::PostgresOnly, kv_mirrored),
+                PersistenceInfo {
+                    db_committed: true,
+                    kv_populated: false,
+                }
+            );
+            assert_eq!(
+                PersistenceInfo::for_insert(MerchantStorageScheme::RedisKv, kv_mirrored),
+                PersistenceInfo {
+                    db_committed: false,
+                    kv_populated: true,
+                }
+            );
+            assert_eq!(
+                PersistenceInfo::for_insert(MerchantStorageScheme::DualWrite, kv_mirrored),
+                PersistenceInfo {
+                    db_committed: true,
+                    kv_populated: kv_mirrored,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_status_transitions_report_each_payout() {
+        let payout = |payout_id: &str, status| Payouts {
+            payout_id: payout_id.to_string(),
+            status,
+            ..sample_payout()
+        };
+        let current = HashMap::from([
+            (
+                PayoutId::from("po_pending"),
+                Some(payout("po_pending", storage_enums::PayoutStatus::Pending)),
+            ),
+            (
+                PayoutId::from("po_success"),
+                Some(payout("po_success", storage_enums::PayoutStatus::Success)),
+            ),
+            (
+                PayoutId::from("po_held"),
+                Some(payout(
+                    "po_held",
+                    storage_enums::PayoutStatus::HeldForReview,
+                )),
+            ),
+            (PayoutId::from("po_missing"), None),
+        ]);
+        let transitions = [
+            ("po_pending", storage_enums::PayoutStatus::Success),
+            ("po_success", storage_enums::PayoutStatus::Pending),
+            ("po_held", storage_enums::PayoutStatus::Pending),
+            ("po_missing", storage_enums::PayoutStatus::Success),
+            ("po_pending", storage_enums::PayoutStatus::HeldForReview),
+        ]
+        .map(|(payout_id, status)| (PayoutId::from(payout_id), status));
+
+        let outcomes = check_status_transitions(&current, &transitions);
+        let payout_ids = outcomes
+            .iter()
+            .map(|(payout_id, _)| &**payout_id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            payout_ids,
+            [
+                "po_pending",
+                "po_success",
+                "po_held",
+                "po_missing",
+                "po_pending"
+            ]
+        );
+
+        assert!(outcomes[0].1.is_ok());
+        for (_, outcome) in [&outcomes[1], &outcomes[2], &outcomes[4]] {
+            assert!(matches!(
+                outcome.as_ref().unwrap_err().current_context(),
+                errors::StorageError::InvalidValue { field: "status" }
+            ));
+        }
+        assert!(matches!(
+            outcomes[3].1.as_ref().unwrap_err().current_context(),
+            errors::StorageError::ValueNotFound(_)
+        ));
+    }
+
+    #[test]
+    fn test_import_timestamps() {
+        let now = common_utils::date_time::now();
+        let created_at = now - time::Duration::days(30);
+        let last_modified_at = now - time::Duration::days(29);
+        let new = PayoutsNew {
+            created_at: Some(created_at),
+            last_modified_at: Some(last_modified_at),
+            ..Default::default()
+        };
+
+        let preserved = new.clone().for_import(true, now).unwrap();
+        assert_eq!(preserved.created_at, Some(created_at));
+        assert_eq!(preserved.last_modified_at, Some(last_modified_at));
+
+        let stamped = new.for_import(false, now).unwrap();
+        assert_eq!(stamped.created_at, Some(now));
+        assert_eq!(stamped.last_modified_at, Some(now));
+    }
+
+    #[test]
+    fn test_import_rejects_missing_or_inverted_timestamps() {
+        let now = common_utils::date_time::now();
+        let missing = PayoutsNew {
+            last_modified_at: None,
+            ..Default::default()
+        };
+        let error = missing.for_import(true, now).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            errors::StorageError::InvalidValue {
+                field: "last_modified_at"
+            }
+        ));
+
+        let inverted = PayoutsNew {
+            created_at: Some(now),
+            last_modified_at: Some(now - time::Duration::seconds(1)),
+            ..Default::default()
+        };
+        let error = inverted.for_import(true, now).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            errors::StorageError::InvalidValue {
+                field: "last_modified_at"
+            }
+        ));
+    }
+
+    #[test]
+    fn test_confirm_draft_payout() {
+        let draft = PayoutsNew::default().into_draft();
+        assert_eq!(
+            draft.status,
+            storage_enums::PayoutStatus::RequiresConfirmation
+        );
+
+        let mut payout = sample_payout();
+        payout.status = draft.status;
+        let update = payout.confirm_update().unwrap().unwrap();
+        assert!(update.validate_transition(&payout).is_ok());
+        assert_eq!(
+            PayoutsUpdateInternal::from(update).status,
+            Some(storage_enums::PayoutStatus::Pending)
+        );
+    }
+
+    #[test]
+    fn test_double_confirm_leaves_payout_as_is() {
+        let mut payout = sample_payout();
+        payout.status = storage_enums::PayoutStatus::Pending;
+        assert!(payout.confirm_update().unwrap().is_none());
+        assert!(PayoutsUpdate::ConfirmUpdate
+            .validate_transition(&payout)
+            .is_err());
+    }
+
+    #[test]
+    fn test_confirm_from_wrong_state_is_rejected() {
+        for status in [
+            storage_enums::PayoutStatus::RequiresCreation,
+            storage_enums::PayoutStatus::Scheduled,
+            storage_enums::PayoutStatus::HeldForReview,
+            storage_enums::PayoutStatus::Success,
+            storage_enums::PayoutStatus::Failed,
+        ] {
+            let mut payout = sample_payout();
+            payout.status = status;
+            let error = payout.confirm_update().unwrap_err();
+            assert!(matches!(
+                error.current_context(),
+                errors::StorageError::InvalidValue { field: "status" }
+            ));
+            assert!(PayoutsUpdate::ConfirmUpdate
+                .validate_transition(&payout)
+                .is_err());
+        }
+
+        let result = PayoutsUpdate::combine(
+            PayoutsUpdate::ConfirmUpdate,
+            PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_combine_rejects_activate_updates() {
+        let result = PayoutsUpdate::combine(
+            PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 },
+            PayoutsUpdate::ActivateUpdate,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_display_reference_is_zero_padded() {
+        let mut payout = sample_payout();
+        assert_eq!(payout.display_reference(), "PO-000123");
+
+        payout.display_seq = 1_234_567;
+        assert_eq!(payout.display_reference(), "PO-1234567");
+    }
+
+    #[test]
+    fn test_reroute_update_changes_connector_fields_together() {
+        let mut payout = sample_payout();
+        payout.status = storage_enums::PayoutStatus::Pending;
+        let update = payout.reroute_update("wise".to_string());
+        assert!(update.validate_transition(&payout).is_ok());
+
+        let internal = PayoutsUpdateInternal::from(update);
+        assert_eq!(internal.connector.as_deref(), Some("wise"));
+        assert_eq!(internal.connector_payout_id, Maybe::SetNull);
+        assert!(matches!(internal.connector_response, Maybe::SetNull));
+        assert_eq!(internal.attempt_count, Some(payout.attempt_count + 1));
+        assert!(internal.connector_idempotency_key.is_some());
+        assert_ne!(
+            internal.connector_idempotency_key,
+            payout.connector_idempotency_key
+        );
+    }
+
+    #[test]
+    fn test_reroute_from_wrong_state_is_rejected() {
+        for status in [
+            storage_enums::PayoutStatus::HeldForReview,
+            storage_enums::PayoutStatus::Scheduled,
+            storage_enums::PayoutStatus::RequiresConfirmation,
+            storage_enums::PayoutStatus::Success,
+            storage_enums::PayoutStatus::Failed,
+        ] {
+            let mut payout = sample_payout();
+            payout.status = status;
+            let error = payout
+                .reroute_update("wise".to_string())
+                .validate_transition(&payout)
+                .unwrap_err();
+            assert!(matches!(
+                error.current_context(),
+                errors::StorageError::InvalidValue { field: "status" }
+            ));
+        }
+
+        // Merged with another update, the connector would no longer be swapped on its own terms
+        let result = PayoutsUpdate::combine(
+            sample_payout().reroute_update("wise".to_string()),
+            PayoutsUpdate::NextRetryUpdate {
+                next_retry_at: None,
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_fx_update_records_rate_and_timestamp() {
+        let quoted_at = common_utils::date_time::now();
+        let update = PayoutsUpdate::FxUpdate {
+            exchange_rate: Decimal::new(10842, 4),
+            exchange_rate_timestamp: quoted_at,
+        };
+        assert!(update.validate_transition(&sample_payout()).is_ok());
+
+        let internal = PayoutsUpdateInternal::from(update);
+        assert_eq!(internal.exchange_rate, Some(Decimal::new(10842, 4)));
+        assert_eq!(internal.exchange_rate_timestamp, Some(quoted_at));
+    }
+
+    #[test]
+    fn test_risk_update_sets_score_and_flag() {
+        let update = PayoutsUpdate::RiskUpdate {
+            risk_score: 87,
+            is_high_risk: true,
+        };
+        assert!(update.validate_transition(&sample_payout()).is_ok());
+
+        let internal = PayoutsUpdateInternal::from(update);
+        assert_eq!(internal.risk_score, Some(87));
+        assert_eq!(internal.is_high_risk, Some(true));
+
+        // Merged into a combined update, the assessment is kept
+        let combined = PayoutsUpdate::combine(
+            PayoutsUpdate::RiskUpdate {
+                risk_score: 12,
+                is_high_risk: false,
+            },
+            PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 },
+        )
+        .unwrap();
+        let internal = PayoutsUpdateInternal::from(combined);
+        assert_eq!(internal.risk_score, Some(12));
+        assert_eq!(internal.is_high_risk, Some(false));
+        assert_eq!(internal.attempt_count, Some(2));
+    }
+
+    #[test]
+    fn test_attempt_count_repair_follows_attempt_records() {
+        let payout = Payouts {
+            attempt_count: 3,
+            ..sample_payout()
+        };
+
+        assert!(matches!(
+            payout.attempt_count_repair(1).unwrap(),
+            Some(PayoutsUpdate::AttemptCountUpdate { attempt_count: 1 })
+        ));
+        assert!(payout.attempt_count_repair(3).unwrap().is_none());
+        assert!(matches!(
+            payout
+                .attempt_count_repair(i64::from(i16::MAX) + 1)
+                .unwrap_err()
+                .current_context(),
+            errors::StorageError::InvalidValue {
+                field: "attempt_count"
+            }
+        ));
+    }
+
+    fn amount_update(payout: &Payouts, amount: i64) -> PayoutsUpdate {
+        PayoutsUpdate::Update {
+            amount,
+            destination_currency: payout.destination_currency,
+            source_currency: payout.source_currency,
+            description: payout.description.clone(),
+            recurring: payout.recurring,
+            auto_fulfill: payout.auto_fulfill,
+            return_url: payout.return_url.clone(),
+            entity_type: payout.entity_type,
+            metadata: None,
+            profile_id: None,
+            status: None,
+            last_modified_by: None,
+        }
+    }
+
+    #[test]
+    fn test_amount_adjustment_only_for_amount_changes() {
+        let payout = sample_payout();
+
+        let adjustment = payout
+            .amount_adjustment(&amount_update(&payout, payout.amount + 500))
+            .unwrap();
+        assert_eq!(
+            (adjustment.old_amount, adjustment.new_amount),
+            (payout.amount, payout.amount + 500)
+        );
+        assert_eq!(adjustment.reason, "payout_update");
+        assert!(payout
+            .amount_adjustment(&amount_update(&payout, payout.amount))
+            .is_none());
+        assert!(payout
+            .amount_adjustment(&PayoutsUpdate::RecurringUpdate { recurring: true })
+            .is_none());
+
+        let combined = PayoutsUpdate::combine(
+            amount_update(&payout, payout.amount - 100),
+            PayoutsUpdate::RiskUpdate {
+                risk_score: 10,
+                is_high_risk: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            payout.amount_adjustment(&combined).unwrap().reason,
+            "combined_update"
+        );
+    }
+
+    #[test]
+    fn test_metadata_contains_follows_jsonb_containment() {
+        let payout = Payouts {
+            metadata: Some(masking::Secret::new(serde_json::json!({
+                "order_id": "ord_1",
+                "batch": { "id": 7, "region": "eu" },
+                "tags": ["refund", "priority"],
+            }))),
+            ..sample_payout()
+        };
+
+        assert!(payout.metadata_contains("order_id", &serde_json::json!("ord_1")));
+        assert!(!payout.metadata_contains("order_id", &serde_json::json!("ord_2")));
+        assert!(!payout.metadata_contains("customer", &serde_json::json!("ord_1")));
+        assert!(payout.metadata_contains("batch", &serde_json::json!({ "id": 7 })));
+        assert!(!payout.metadata_contains("batch", &serde_json::json!({ "id": "7" })));
+        assert!(payout.metadata_contains("tags", &serde_json::json!(["priority"])));
+        assert!(!payout.metadata_contains("tags", &serde_json::json!(["urgent"])));
+        assert!(!Payouts {
+            metadata: None,
+            ..sample_payout()
+        }
+        .metadata_contains("order_id", &serde_json::json!("ord_1")));
+    }
+
+    #[test]
+    fn test_page_cursor_breaks_ties_on_payout_id() {
+        let payout = sample_payout();
+        let cursor = payout.page_cursor();
+        assert!(!cursor.precedes(&payout));
+
+        let same_time = |payout_id: &str| Payouts {
+            payout_id: payout_id.to_string(),
+            ..payout.clone()
+        };
+        assert!(cursor.precedes(&same_time("po_ta")));
+        assert!(!cursor.precedes(&same_time("po_a")));
+        assert!(cursor.precedes(&Payouts {
+            last_modified_at: payout.last_modified_at + time::Duration::seconds(1),
+            ..same_time("po_a")
+        }));
+    }
+
+    #[test]
+    fn test_non_positive_exchange_rate_is_rejected() {
+        let quoted_at = common_utils::date_time::now();
+        for exchange_rate in [Decimal::ZERO, Decimal::new(-10842, 4)] {
+            let update = PayoutsUpdate::FxUpdate {
+                exchange_rate,
+                exchange_rate_timestamp: quoted_at,
+            };
+            let error = update.validate_transition(&sample_payout()).unwrap_err();
+            assert!(matches!(
+                error.current_context(),
+                errors::StorageError::InvalidValue {
+                    field: "exchange_rate"
+                }
+            ));
+        }
+
+        // Merged into a combined update, the rate is still checked
+        let combined = PayoutsUpdate::combine(
+            PayoutsUpdate::FxUpdate {
+                exchange_rate: Decimal::ZERO,
+                exchange_rate_timestamp: quoted_at,
+            },
+            PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 },
+        )
+        .unwrap();
+        assert!(combined.validate_transition(&sample_payout()).is_err());
+    }
+
+    fn patched(payout: Payouts, update: PayoutsUpdate) -> Payouts {
+        let internal = PayoutsUpdateInternal::from(update);
+        Payouts {
+            payout_method_id: internal
+                .payout_method_id
+                .apply(payout.payout_method_id.clone()),
+            description: internal.description.apply(payout.description.clone()),
+            return_url: internal.return_url.apply(payout.return_url.clone()),
+            metadata: internal.metadata.apply(payout.metadata.clone()),
+            network_token_ref: internal
+                .network_token_ref
+                .apply(payout.network_token_ref.clone()),
+            ..payout
+        }
+    }
+
+    fn sample_metadata() -> pii::SecretSerdeValue {
+        masking::Secret::new(serde_json::json!({ "source": "sample" }))
+    }
+
+    #[test]
+    fn test_patch_leaves_unchanged_fields_as_is() {
+        let payout = Payouts {
+            metadata: Some(sample_metadata()),
+            ..sample_payout()
+        };
+        let update = PayoutsUpdate::Patch {
+            payout_method_id: Maybe::Unchanged,
+            description: Maybe::Unchanged,
+            return_url: Maybe::Unchanged,
+            metadata: Maybe::Unchanged,
+            network_token_ref: Maybe::Unchanged,
+        };
+        assert!(update.validate_transition(&payout).is_ok());
+
+        let patched = patched(payout, update);
+        assert_eq!(patched.payout_method_id.as_deref(), Some("pm_1"));
+        assert_eq!(patched.description.as_deref(), Some("payout"));
+        assert_eq!(patched.return_url.as_deref(), Some("https://example.com"));
+        assert_eq!(patched.metadata, Some(sample_metadata()));
+        assert_eq!(patched.network_token_ref.as_deref(), Some("ntr_1"));
+    }
+
+    #[test]
+    fn test_patch_clears_fields_set_to_null() {
+        let payout = Payouts {
+            metadata: Some(sample_metadata()),
+            ..sample_payout()
+        };
+        let update = PayoutsUpdate::Patch {
+            payout_method_id: Maybe::SetNull,
+            description: Maybe::SetNull,
+            return_url: Maybe::SetNull,
+            metadata: Maybe::SetNull,
+            network_token_ref: Maybe::SetNull,
+        };
+
+        let patched = patched(payout, update);
+        assert!(patched.payout_method_id.is_none());
+        assert!(patched.description.is_none());
+        assert!(patched.return_url.is_none());
+        assert!(patched.metadata.is_none());
+        assert!(patched.network_token_ref.is_none());
+    }
+
+    #[test]
+    fn test_patch_sets_fields() {
+        let payout = Payouts {
+            payout_method_id: None,
+            description: None,
+            return_url: None,
+            network_token_ref: None,
+            ..sample_payout()
+        };
+        let update = PayoutsUpdate::Patch {
+            payout_method_id: Maybe::Set("pm_2".to_string()),
+            description: Maybe::Set("refund".to_string()),
+            return_url: Maybe::Set("https://example.com/return".to_string()),
+            metadata: Maybe::Set(sample_metadata()),
+            network_token_ref: Maybe::Set("ntr_2".to_string()),
+        };
+
+        let patched = patched(payout, update);
+        assert_eq!(patched.payout_method_id.as_deref(), Some("pm_2"));
+        assert_eq!(patched.description.as_deref(), Some("refund"));
+        assert_eq!(
+            patched.return_url.as_deref(),
+            Some("https://example.com/return")
+        );
+        assert_eq!(patched.metadata, Some(sample_metadata()));
+        assert_eq!(patched.network_token_ref.as_deref(), Some("ntr_2"));
+    }
+
+    #[test]
+    fn test_combine_keeps_cleared_fields_and_rejects_conflicts() {
+        let clear_description = || PayoutsUpdate::Patch {
+            payout_method_id: Maybe::Unchanged,
+            description: Maybe::SetNull,
+            return_url: Maybe::Unchanged,
+            metadata: Maybe::Unchanged,
+            network_token_ref: Maybe::Unchanged,
+        };
+
+        let combined = PayoutsUpdate::combine(
+            clear_description(),
+            PayoutsUpdate::NetworkTokenUpdate {
+                network_token_ref: Some("ntr_2".to_string()),
+            },
+        )
+        .unwrap();
+        let internal = PayoutsUpdateInternal::from(combined);
+        assert_eq!(internal.description, Maybe::SetNull);
+        assert_eq!(internal.network_token_ref, Maybe::Set("ntr_2".to_string()));
+        assert_eq!(internal.payout_method_id, Maybe::Unchanged);
+
+        let error = PayoutsUpdate::combine(
+            clear_description(),
+            PayoutsUpdate::Patch {
+                payout_method_id: Maybe::Unchanged,
+                description: Maybe::Set("payout".to_string()),
+                return_url: Maybe::Unchanged,
+                metadata: Maybe::Unchanged,
+                network_token_ref: Maybe::Unchanged,
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            errors::StorageError::InvalidValue {
+                field: "description"
+            }
+        ));
+    }
+
+    #[test]
+    fn test_latest_returns_most_recent_payout() {
+        let now = common_utils::date_time::now();
+        let payouts = [("po_old", 2), ("po_latest", 0), ("po_older", 5)]
+            .into_iter()
+            .map(|(payout_id, minutes_ago)| Payouts {
+                payout_id: payout_id.to_string(),
+                created_at: now - time::Duration::minutes(minutes_ago),
+                ..sample_payout()
+            })
+            .collect::<Vec<_>>();
+
+        let latest = Payouts::latest(payouts).unwrap();
+        assert_eq!(latest.payout_id, "po_latest");
+        assert_eq!(Payouts::latest(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_modified_since_keeps_recently_modified_payouts() {
+        let now = common_utils::date_time::now();
+        let since = now - time::Duration::days(1);
+        let payouts = [
+            ("po_today", 0, 10),
+            ("po_boundary", 2, 24 * 60),
+            ("po_yesterday", 3, 24 * 60 + 1),
+            ("po_updated_today", 4, 5),
+        ]
+        .into_iter()
+        .map(
+            |(payout_id, created_hours_ago, modified_minutes_ago)| Payouts {
+                payout_id: payout_id.to_string(),
+                created_at: now - time::Duration::hours(created_hours_ago),
+                last_modified_at: now - time::Duration::minutes(modified_minutes_ago),
+                ..sample_payout()
+            },
+        )
+        .collect::<Vec<_>>();
+
+        let diff = Payouts::modified_since(payouts, since);
+        assert_eq!(
+            diff.iter()
+                .map(|payout| payout.payout_id.as_str())
+                .collect::<Vec<_>>(),
+            ["po_today", "po_boundary", "po_updated_today"]
+        );
+        assert!(Payouts::modified_since(Vec::new(), since).is_empty());
+    }
+
+    #[test]
+    fn test_payout_claimable_for_retry() {
+        let now = common_utils::date_time::now();
+        let payout = Payouts {
+            status: storage_enums::PayoutStatus::Pending,
+            attempt_count: 2,
+            last_modified_at: now - PAYOUT_RETRY_CLAIM_LEASE,
+            ..sample_payout()
+        };
+        assert!(payout.is_claimable_for_retry(now, 3));
+        assert!(!payout.is_claimable_for_retry(now, 2));
+
+        let recently_modified = Payouts {
+            last_modified_at: now,
+            ..payout.clone()
+        };
+        assert!(!recently_modified.is_claimable_for_retry(now, 3));
+
+        let failed = Payouts {
+            status: storage_enums::PayoutStatus::Failed,
+            ..payout
+        };
+        assert!(!failed.is_claimable_for_retry(now, 3));
+    }
+
+    #[test]
+    fn test_payout_with_future_next_retry_is_not_claimable_until_due() {
+        let now = common_utils::date_time::now();
+        let payout = Payouts {
+            status: storage_enums::PayoutStatus::Pending,
+            attempt_count: 1,
+            last_modified_at: now - PAYOUT_RETRY_CLAIM_LEASE,
+            next_retry_at: Some(now + time::Duration::minutes(10)),
+            ..sample_payout()
+        };
+        assert!(!payout.is_due_for_retry(now));
+        assert!(!payout.is_claimable_for_retry(now, 3));
+
+        let due_at = now + time::Duration::minutes(10);
+        assert!(payout.is_due_for_retry(due_at));
+        assert!(payout.is_claimable_for_retry(due_at, 3));
+
+        let without_delay = Payouts {
+            next_retry_at: None,
+            ..payout
+        };
+        assert!(without_delay.is_claimable_for_retry(now, 3));
+    }
+
+    #[test]
+    fn test_next_retry_update_clears_the_delay() {
+        let now = common_utils::date_time::now();
+        let internal = PayoutsUpdateInternal::from(PayoutsUpdate::NextRetryUpdate {
+            next_retry_at: Some(now),
+        });
+        assert_eq!(internal.next_retry_at, Maybe::Set(now));
+
+        let internal = PayoutsUpdateInternal::from(PayoutsUpdate::NextRetryUpdate {
+            next_retry_at: None,
+        });
+        assert_eq!(internal.next_retry_at, Maybe::SetNull);
+    }
+
+    #[test]
+    fn test_only_one_concurrent_claimer_succeeds() {
+        let now = common_utils::date_time::now();
+        let payout = std::sync::Arc::new(std::sync::Mutex::new(Payouts {
+            status: storage_enums::PayoutStatus::Pending,
+            attempt_count: 0,
+            last_modified_at: now - PAYOUT_RETRY_CLAIM_LEASE,
+            ..sample_payout()
+        }));
+
+        // Each claimer checks and bumps the payout under the lock, as the claim query does
+        // under the row lock taken by the update
+        let claimers = (0..8)
+            .map(|_| {
+                let payout = std::sync::Arc::clone(&payout);
+                std::thread::spawn(move || {
+                    let mut payout = payout.lock().unwrap();
+                    if payout.is_claimable_for_retry(now, 3) {
+                        payout.attempt_count += 1;
+                        payout.last_modified_at = now;
+                        true
+                    } else {
+                        false
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let claimed = claimers
+            .into_iter()
+            .map(|claimer| claimer.join().unwrap())
+            .filter(|claimed| *claimed)
+            .count();
+
+        assert_eq!(claimed, 1);
+        assert_eq!(payout.lock().unwrap().attempt_count, 1);
+    }
+
+    #[test]
+    fn test_sum_status_counts_over_mixed_statuses() {
+        let statuses = [
+            storage_enums::PayoutStatus::Success,
+            storage_enums::PayoutStatus::Pending,
+            storage_enums::PayoutStatus::Success,
+            storage_enums::PayoutStatus::Failed,
+            storage_enums::PayoutStatus::Success,
+        ];
+        let counts = sum_status_counts(statuses.into_iter().map(|status| (status, 1)));
+
+        assert_eq!(
+            counts,
+            HashMap::from([
+                (storage_enums::PayoutStatus::Success, 3),
+                (storage_enums::PayoutStatus::Pending, 1),
+                (storage_enums::PayoutStatus::Failed, 1),
+            ])
+        );
+        assert!(!counts.contains_key(&storage_enums::PayoutStatus::Cancelled));
+    }
+
+    #[test]
+    fn test_total_payout_amount_beyond_i64() {
+        // What Postgres returns for `SUM(amount)` over three payouts of `i64::MAX`
+        let sum = Decimal::from(i64::MAX) * Decimal::from(3);
+
+        let total = total_payout_amount(Some(sum)).unwrap();
+        assert_eq!(total, i128::from(i64::MAX) * 3);
+        assert!(total > i128::from(i64::MAX));
+    }
+
+    #[test]
+    fn test_total_payout_amount_without_payouts_is_zero() {
+        assert_eq!(total_payout_amount(None).unwrap(), 0);
+    }
+
+    fn assert_invalid_amount(major_amount: &str, currency: storage_enums::Currency) {
+        let error = amount_from_major_units(major_amount, currency).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            errors::StorageError::InvalidValue { field: "amount" }
+        ));
+    }
+
+    #[test]
+    fn test_amount_from_major_units_for_zero_decimal_currency() {
+        let jpy = storage_enums::Currency::JPY;
+        assert_eq!(amount_from_major_units("1500", jpy).unwrap(), 1500);
+        assert_eq!(amount_from_major_units("1500.00", jpy).unwrap(), 1500);
+        assert_invalid_amount("1500.5", jpy);
+        assert_invalid_amount("0.01", jpy);
+    }
+
+    #[test]
+    fn test_amount_from_major_units_for_two_decimal_currency() {
+        let usd = storage_enums::Currency::USD;
+        assert_eq!(amount_from_major_units("10", usd).unwrap(), 1000);
+        assert_eq!(amount_from_major_units("10.5", usd).unwrap(), 1050);
+        assert_eq!(amount_from_major_units("10.05", usd).unwrap(), 1005);
+        assert_eq!(amount_from_major_units("0.010", usd).unwrap(), 1);
+        assert_invalid_amount("10.005", usd);
+        assert_invalid_amount("-10.00", usd);
+        assert_invalid_amount(".50", usd);
+        assert_invalid_amount("1e3", usd);
+        assert_invalid_amount("99999999999999999999", usd);
+    }
+
+    #[test]
+    fn test_amount_from_major_units_for_three_decimal_currency() {
+        let bhd = storage_enums::Currency::BHD;
+        assert_eq!(amount_from_major_units("1.5", bhd).unwrap(), 1500);
+        assert_eq!(amount_from_major_units("1.005", bhd).unwrap(), 1005);
+        assert_invalid_amount("1.0005", bhd);
+    }
+
+    #[test]
+    fn test_link_splits_rejects_splits_not_adding_up_to_the_parent() {
+        let split = |payout_id: &str, amount| PayoutsNew {
+            payout_id: payout_id.to_string(),
+            merchant_id: "merchant_1".to_string(),
+            amount,
+            destination_currency: storage_enums::Currency::EUR,
+            ..Default::default()
+        };
+        let parent = split("po_parent", 1000);
+        let invalid_field = |splits| match parent.link_splits(splits).unwrap_err().current_context()
+        {
+            errors::StorageError::InvalidValue { field } => Some(*field),
+            _ => None,
+        };
+
+        let linked = parent
+            .link_splits(vec![split("po_1", 600), split("po_2", 400)])
+            .unwrap();
+        assert!(linked
+            .iter()
+            .all(|split| split.parent_payout_id.as_deref() == Some("po_parent")));
+
+        assert_eq!(invalid_field(vec![]), Some("splits"));
+        assert_eq!(invalid_field(vec![split("po_1", 600)]), Some("amount"));
+        assert_eq!(
+            invalid_field(vec![split("po_1", i64::MAX), split("po_2", i64::MAX)]),
+            Some("amount")
+        );
+        assert_eq!(
+            invalid_field(vec![
+                split("po_1", 600),
+                PayoutsNew {
+                    destination_currency: storage_enums::Currency::USD,
+                    ..split("po_2", 400)
+                },
+            ]),
+            Some("destination_currency")
+        );
+        assert_eq!(
+            invalid_field(vec![
+                split("po_1", 600),
+                PayoutsNew {
+                    merchant_id: "merchant_2".to_string(),
+                    ..split("po_2", 400)
+                },
+            ]),
+            Some("merchant_id")
+        );
     }
 }
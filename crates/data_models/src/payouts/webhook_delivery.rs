@@ -0,0 +1,186 @@
+use common_enums::MerchantStorageScheme;
+use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
+
+use crate::errors;
+
+#[async_trait::async_trait]
+pub trait PayoutWebhookDeliveryInterface {
+    async fn insert_webhook_delivery(
+        &self,
+        _delivery: PayoutWebhookDeliveryNew,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<PayoutWebhookDelivery, errors::StorageError>;
+
+    /// Records an attempt to deliver `this`, see [`PayoutWebhookDeliveryUpdate`].
+    async fn update_webhook_delivery(
+        &self,
+        _this: &PayoutWebhookDelivery,
+        _delivery_update: PayoutWebhookDeliveryUpdate,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<PayoutWebhookDelivery, errors::StorageError>;
+
+    /// Lists the deliveries of notifications about a payout, oldest first.
+    async fn list_webhook_deliveries(
+        &self,
+        _merchant_id: &str,
+        _payout_id: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutWebhookDelivery>, errors::StorageError>;
+
+    /// Finds up to `limit` deliveries across merchants which are due for a retry at or before
+    /// `older_than`, see [`PayoutWebhookDelivery::is_due`], the longest due first.
+    ///
+    /// Deliveries are found in the database. With the `RedisKv` scheme, each is then checked
+    /// against its copy held in KV, which is the current one until it is drained, so that a
+    /// delivery recorded as delivered there is not retried. Deliveries created in KV are only
+    /// found once drained.
+    async fn find_pending_webhook_deliveries(
+        &self,
+        _older_than: PrimitiveDateTime,
+        _limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutWebhookDelivery>, errors::StorageError>;
+}
+
+/// The delivery of a notification about a payout to the webhook endpoint of its merchant,
+/// tracked across the attempts made to deliver it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PayoutWebhookDelivery {
+    pub delivery_id: String,
+    pub merchant_id: String,
+    pub payout_id: String,
+    pub target_url: String,
+    /// The number of attempts made so far.
+    pub attempt_count: i32,
+    /// The HTTP status code the last attempt was answered with, `None` before the first attempt
+    /// or if the last one got no response.
+    pub last_status_code: Option<i32>,
+    /// When the delivery is to be attempted next, `None` once delivered or given up on.
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub next_retry_at: Option<PrimitiveDateTime>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub delivered_at: Option<PrimitiveDateTime>,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub created_at: PrimitiveDateTime,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub last_modified_at: PrimitiveDateTime,
+}
+
+impl PayoutWebhookDelivery {
+    /// Whether the delivery is still to be made and is due for an attempt at `at`.
+    pub fn is_due(&self, at: PrimitiveDateTime) -> bool {
+        self.delivered_at.is_none()
+            && self
+                .next_retry_at
+                .is_some_and(|next_retry_at| next_retry_at <= at)
+    }
+
+    /// Sorts pending deliveries the way they are retried: the longest due first, with ties
+    /// broken by `delivery_id`.
+    pub fn sort_for_retry(deliveries: &mut [Self]) {
+        deliveries.sort_by(|a, b| {
+            a.next_retry_at
+                .cmp(&b.next_retry_at)
+                .then_with(|| a.delivery_id.cmp(&b.delivery_id))
+        });
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutWebhookDeliveryNew {
+    pub delivery_id: String,
+    pub merchant_id: String,
+    pub payout_id: String,
+    pub target_url: String,
+    /// When the first attempt is due, usually right away.
+    pub next_retry_at: Option<PrimitiveDateTime>,
+    pub created_at: PrimitiveDateTime,
+}
+
+impl PayoutWebhookDeliveryNew {
+    /// The delivery as stored once inserted, before any attempt.
+    pub fn into_delivery(self) -> PayoutWebhookDelivery {
+        PayoutWebhookDelivery {
+            delivery_id: self.delivery_id,
+            merchant_id: self.merchant_id,
+            payout_id: self.payout_id,
+            target_url: self.target_url,
+            attempt_count: 0,
+            last_status_code: None,
+            next_retry_at: self.next_retry_at,
+            delivered_at: None,
+            created_at: self.created_at,
+            last_modified_at: self.created_at,
+        }
+    }
+}
+
+/// An attempt to deliver a notification, each of which increments its `attempt_count`.
+#[derive(Clone, Debug)]
+pub enum PayoutWebhookDeliveryUpdate {
+    /// The endpoint did not acknowledge the notification, answering with `status_code` if it
+    /// answered at all. It is retried at `next_retry_at`, or given up on if unset.
+    AttemptFailed {
+        status_code: Option<i32>,
+        next_retry_at: Option<PrimitiveDateTime>,
+    },
+    /// The endpoint acknowledged the notification with `status_code`.
+    Delivered {
+        status_code: i32,
+        delivered_at: PrimitiveDateTime,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delivery(delivery_id: &str, next_retry_in_minutes: Option<i64>) -> PayoutWebhookDelivery {
+        let now = common_utils::date_time::now();
+        PayoutWebhookDeliveryNew {
+            delivery_id: delivery_id.to_string(),
+            merchant_id: "merchant_1".to_string(),
+            payout_id: "po_1".to_string(),
+            target_url: "https://merchant.example/webhooks".to_string(),
+            next_retry_at: next_retry_in_minutes
+                .map(|minutes| now + time::Duration::minutes(minutes)),
+            created_at: now,
+        }
+        .into_delivery()
+    }
+
+    #[test]
+    fn test_delivery_is_due_once_its_retry_time_passed() {
+        let now = common_utils::date_time::now();
+
+        assert!(delivery("whd_1", Some(-1)).is_due(now));
+        assert!(!delivery("whd_2", Some(1)).is_due(now));
+        // Given up on
+        assert!(!delivery("whd_3", None).is_due(now));
+        let delivered = PayoutWebhookDelivery {
+            delivered_at: Some(now),
+            ..delivery("whd_4", Some(-1))
+        };
+        assert!(!delivered.is_due(now));
+    }
+
+    #[test]
+    fn test_deliveries_are_retried_longest_due_first() {
+        let mut deliveries = vec![
+            delivery("whd_3", Some(-1)),
+            delivery("whd_2", Some(-10)),
+            delivery("whd_1", Some(-10)),
+        ];
+        deliveries[2].next_retry_at = deliveries[1].next_retry_at;
+        PayoutWebhookDelivery::sort_for_retry(&mut deliveries);
+
+        assert_eq!(
+            deliveries
+                .iter()
+                .map(|delivery| delivery.delivery_id.as_str())
+                .collect::<Vec<_>>(),
+            ["whd_1", "whd_2", "whd_3"]
+        );
+    }
+}
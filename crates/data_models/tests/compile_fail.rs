@@ -0,0 +1,9 @@
+//! Misuses of the payout ids which must not compile.
+
+#![cfg(feature = "payouts")]
+
+#[test]
+fn payout_ids_are_not_interchangeable() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
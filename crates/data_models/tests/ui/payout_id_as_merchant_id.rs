@@ -0,0 +1,8 @@
+use data_models::payouts::ids::{MerchantId, PayoutId};
+
+fn find_payout(_merchant_id: &MerchantId, _payout_id: &PayoutId) {}
+
+fn main() {
+    let payout_id = PayoutId::from("po_1");
+    find_payout(&payout_id, &payout_id);
+}
@@ -3,10 +3,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     address::{Address, AddressNew, AddressUpdateInternal},
+    enums::PayoutStatus,
     errors,
     payment_attempt::{PaymentAttempt, PaymentAttemptNew, PaymentAttemptUpdate},
     payment_intent::{PaymentIntentNew, PaymentIntentUpdate},
+    payout_amount_adjustment::{PayoutAmountAdjustment, PayoutAmountAdjustmentNew},
     payout_attempt::{PayoutAttempt, PayoutAttemptNew, PayoutAttemptUpdate},
+    payout_webhook_delivery::{
+        PayoutWebhookDelivery, PayoutWebhookDeliveryNew, PayoutWebhookDeliveryUpdate,
+    },
     payouts::{Payouts, PayoutsNew, PayoutsUpdate},
     refund::{Refund, RefundNew, RefundUpdate},
     reverse_lookup::{ReverseLookup, ReverseLookupNew},
@@ -18,6 +23,7 @@ use crate::{
 pub enum DBOperation {
     Insert { insertable: Insertable },
     Update { updatable: Updateable },
+    Delete { deletable: Deletable },
 }
 
 impl DBOperation {
@@ -25,6 +31,7 @@ impl DBOperation {
         match self {
             Self::Insert { .. } => "insert",
             Self::Update { .. } => "update",
+            Self::Delete { .. } => "delete",
         }
     }
     pub fn table<'a>(&self) -> &'a str {
@@ -36,6 +43,8 @@ impl DBOperation {
                 Insertable::Address(_) => "address",
                 Insertable::Payouts(_) => "payouts",
                 Insertable::PayoutAttempt(_) => "payout_attempt",
+                Insertable::PayoutAmountAdjustment(_) => "payout_amount_adjustments",
+                Insertable::PayoutWebhookDelivery(_) => "payout_webhook_deliveries",
                 Insertable::ReverseLookUp(_) => "reverse_lookup",
             },
             Self::Update { updatable } => match updatable {
@@ -45,9 +54,95 @@ impl DBOperation {
                 Updateable::AddressUpdate(_) => "address",
                 Updateable::PayoutsUpdate(_) => "payouts",
                 Updateable::PayoutAttemptUpdate(_) => "payout_attempt",
+                Updateable::PayoutWebhookDeliveryUpdate(_) => "payout_webhook_deliveries",
             },
+            Self::Delete { deletable } => match deletable {
+                Deletable::Payouts(_) => "payouts",
+            },
+        }
+    }
+
+    /// The status of the payout before this operation, `None` unless it updates a payout.
+    pub fn updated_payout_status(&self) -> Option<PayoutStatus> {
+        match self {
+            Self::Update {
+                updatable: Updateable::PayoutsUpdate(update),
+            } => Some(update.orig.status),
+            _ => None,
         }
     }
+
+    /// The merchant and payout ids of the payout written by this operation, or whose amount
+    /// adjustment it records, `None` if it writes any other table.
+    pub fn payout_ids(&self) -> Option<(&str, &str)> {
+        match self {
+            Self::Insert {
+                insertable: Insertable::Payouts(new),
+            } => Some((&new.merchant_id, &new.payout_id)),
+            Self::Insert {
+                insertable: Insertable::PayoutAmountAdjustment(adjustment),
+            } => Some((&adjustment.merchant_id, &adjustment.payout_id)),
+            Self::Update {
+                updatable: Updateable::PayoutsUpdate(update),
+            } => Some((&update.orig.merchant_id, &update.orig.payout_id)),
+            Self::Delete {
+                deletable: Deletable::Payouts(payout),
+            } => Some((&payout.merchant_id, &payout.payout_id)),
+            _ => None,
+        }
+    }
+}
+
+/// The stream the drainer moves the writes of a payout to once they repeatedly failed to be
+/// applied to the database.
+pub fn payout_dead_letter_stream(merchant_id: &str, payout_id: &str) -> String {
+    format!("mid_{merchant_id}_po_{payout_id}_dlq")
+}
+
+/// The set of the ids of the payouts of the merchant written to KV.
+pub fn payout_index(merchant_id: &str) -> String {
+    format!("mid_{merchant_id}_po_index")
+}
+
+/// The counter of the display sequence numbers of the payouts of the merchant written to KV. It
+/// never expires, so that a number is never assigned twice.
+pub fn payout_display_seq_counter(merchant_id: &str) -> String {
+    format!("mid_{merchant_id}_po_display_seq")
+}
+
+/// The id of the payout of the merchant written to KV with the display sequence number
+/// `display_seq`.
+pub fn payout_by_display_seq(merchant_id: &str, display_seq: i64) -> String {
+    format!("mid_{merchant_id}_po_display_seq_{display_seq}")
+}
+
+/// The number of payout writes pushed to the drainer streams and not removed from them by the
+/// drainer yet, across all merchants.
+pub const PAYOUT_PENDING_OPS: &str = "po_kv_pending_ops";
+
+/// The set of the ids of the payouts of the merchant with a dead-letter stream.
+pub fn payout_dead_letter_index(merchant_id: &str) -> String {
+    format!("mid_{merchant_id}_po_dlq")
+}
+
+/// The stream the drainer pushes a notification to once it applies an update moving a payout to
+/// a terminal status.
+pub const PAYOUT_NOTIFICATION_STREAM: &str = "po_notifications";
+
+/// Set once the notification of the payout reaching `status` was pushed to
+/// [`PAYOUT_NOTIFICATION_STREAM`], so that it is not pushed again when the update is redelivered.
+pub fn payout_notification_marker(
+    merchant_id: &str,
+    payout_id: &str,
+    status: PayoutStatus,
+) -> String {
+    format!("mid_{merchant_id}_po_{payout_id}_notified_{status}")
+}
+
+/// Whether a stream entry which failed to be applied `failed_attempts` times is given up on and
+/// moved to a dead-letter stream.
+pub fn exceeds_retry_limit(failed_attempts: i64, max_retries: u32) -> bool {
+    failed_attempts > i64::from(max_retries)
 }
 
 #[derive(Debug)]
@@ -59,6 +154,10 @@ pub enum DBResult {
     ReverseLookUp(Box<ReverseLookup>),
     Payouts(Box<Payouts>),
     PayoutAttempt(Box<PayoutAttempt>),
+    PayoutAmountAdjustment(Box<PayoutAmountAdjustment>),
+    PayoutWebhookDelivery(Box<PayoutWebhookDelivery>),
+    /// Whether a row was deleted, a row already gone is not an error.
+    Deleted(bool),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -86,6 +185,12 @@ impl DBOperation {
                 Insertable::PayoutAttempt(rev) => {
                     DBResult::PayoutAttempt(Box::new(rev.insert(conn).await?))
                 }
+                Insertable::PayoutAmountAdjustment(adjustment) => {
+                    DBResult::PayoutAmountAdjustment(Box::new(adjustment.insert(conn).await?))
+                }
+                Insertable::PayoutWebhookDelivery(delivery) => {
+                    DBResult::PayoutWebhookDelivery(Box::new(delivery.insert(conn).await?))
+                }
             },
             Self::Update { updatable } => match updatable {
                 Updateable::PaymentIntentUpdate(a) => {
@@ -106,6 +211,15 @@ impl DBOperation {
                 Updateable::PayoutAttemptUpdate(a) => DBResult::PayoutAttempt(Box::new(
                     a.orig.update_with_attempt_id(conn, a.update_data).await?,
                 )),
+                Updateable::PayoutWebhookDeliveryUpdate(a) => DBResult::PayoutWebhookDelivery(
+                    Box::new(a.orig.update(conn, a.update_data).await?),
+                ),
+            },
+            Self::Delete { deletable } => match deletable {
+                Deletable::Payouts(a) => DBResult::Deleted(
+                    Payouts::delete_by_merchant_id_payout_id(conn, &a.merchant_id, &a.payout_id)
+                        .await?,
+                ),
             },
         })
     }
@@ -143,6 +257,8 @@ pub enum Insertable {
     ReverseLookUp(ReverseLookupNew),
     Payouts(PayoutsNew),
     PayoutAttempt(PayoutAttemptNew),
+    PayoutAmountAdjustment(PayoutAmountAdjustmentNew),
+    PayoutWebhookDelivery(PayoutWebhookDeliveryNew),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -154,6 +270,13 @@ pub enum Updateable {
     AddressUpdate(Box<AddressUpdateMems>),
     PayoutsUpdate(PayoutsUpdateMems),
     PayoutAttemptUpdate(PayoutAttemptUpdateMems),
+    PayoutWebhookDeliveryUpdate(PayoutWebhookDeliveryUpdateMems),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "table", content = "data")]
+pub enum Deletable {
+    Payouts(PayoutsDeleteMems),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -191,3 +314,15 @@ pub struct PayoutAttemptUpdateMems {
     pub orig: PayoutAttempt,
     pub update_data: PayoutAttemptUpdate,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayoutWebhookDeliveryUpdateMems {
+    pub orig: PayoutWebhookDelivery,
+    pub update_data: PayoutWebhookDeliveryUpdate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayoutsDeleteMems {
+    pub merchant_id: String,
+    pub payout_id: String,
+}
@@ -34,7 +34,11 @@ pub mod payment_attempt;
 pub mod payment_intent;
 pub mod payment_link;
 pub mod payment_method;
+pub mod payout_amount_adjustment;
 pub mod payout_attempt;
+pub mod payout_display_sequence;
+pub mod payout_webhook_delivery;
+pub mod payout_webhook_event;
 pub mod payouts;
 pub mod process_tracker;
 pub mod query;
@@ -0,0 +1,34 @@
+use diesel::{Identifiable, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
+
+use crate::schema::payout_amount_adjustments;
+
+/// A change of the amount of a payout, recorded along with the update making it. Rows are never
+/// updated or deleted once written.
+#[derive(
+    Clone, Debug, Eq, PartialEq, Insertable, Serialize, Deserialize, router_derive::DebugAsDisplay,
+)]
+#[diesel(table_name = payout_amount_adjustments)]
+pub struct PayoutAmountAdjustmentNew {
+    pub merchant_id: String,
+    pub payout_id: String,
+    pub old_amount: i64,
+    pub new_amount: i64,
+    pub reason: String,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub created_at: PrimitiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Identifiable, Queryable)]
+#[diesel(table_name = payout_amount_adjustments, primary_key(id))]
+pub struct PayoutAmountAdjustment {
+    pub id: i32,
+    pub merchant_id: String,
+    pub payout_id: String,
+    pub old_amount: i64,
+    pub new_amount: i64,
+    pub reason: String,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub created_at: PrimitiveDateTime,
+}
@@ -29,6 +29,7 @@ pub struct PayoutAttempt {
     pub profile_id: String,
     pub merchant_connector_id: Option<String>,
     pub routing_info: Option<serde_json::Value>,
+    pub unified_code: Option<String>,
 }
 
 #[derive(
@@ -66,6 +67,7 @@ pub struct PayoutAttemptNew {
     pub profile_id: String,
     pub merchant_connector_id: Option<String>,
     pub routing_info: Option<serde_json::Value>,
+    pub unified_code: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +79,14 @@ pub enum PayoutAttemptUpdate {
         error_code: Option<String>,
         is_eligible: Option<bool>,
     },
+    ErrorUpdate {
+        connector_payout_id: String,
+        status: storage_enums::PayoutStatus,
+        error_message: Option<String>,
+        error_code: Option<String>,
+        unified_code: Option<String>,
+        is_eligible: Option<bool>,
+    },
     PayoutTokenUpdate {
         payout_token: String,
     },
@@ -103,6 +113,7 @@ pub struct PayoutAttemptUpdateInternal {
     pub business_label: Option<String>,
     pub connector: Option<String>,
     pub routing_info: Option<serde_json::Value>,
+    pub unified_code: Option<String>,
     pub last_modified_at: PrimitiveDateTime,
 }
 
@@ -119,6 +130,7 @@ impl Default for PayoutAttemptUpdateInternal {
             business_label: None,
             connector: None,
             routing_info: None,
+            unified_code: None,
             last_modified_at: common_utils::date_time::now(),
         }
     }
@@ -145,6 +157,22 @@ impl From<PayoutAttemptUpdate> for PayoutAttemptUpdateInternal {
                 is_eligible,
                 ..Default::default()
             },
+            PayoutAttemptUpdate::ErrorUpdate {
+                connector_payout_id,
+                status,
+                error_message,
+                error_code,
+                unified_code,
+                is_eligible,
+            } => Self {
+                connector_payout_id: Some(connector_payout_id),
+                status: Some(status),
+                error_message,
+                error_code,
+                unified_code,
+                is_eligible,
+                ..Default::default()
+            },
             PayoutAttemptUpdate::BusinessUpdate {
                 business_country,
                 business_label,
@@ -178,6 +206,7 @@ impl PayoutAttemptUpdate {
             business_label,
             connector,
             routing_info,
+            unified_code,
             last_modified_at,
         } = self.into();
         PayoutAttempt {
@@ -191,6 +220,7 @@ impl PayoutAttemptUpdate {
             business_label: business_label.or(source.business_label),
             connector: connector.or(source.connector),
             routing_info: routing_info.or(source.routing_info),
+            unified_code: unified_code.or(source.unified_code),
             last_modified_at,
             ..source
         }
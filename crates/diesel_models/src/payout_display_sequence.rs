@@ -0,0 +1,13 @@
+use diesel::{Identifiable, Queryable, QueryableByName};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::payout_display_sequences;
+
+/// The last display sequence number assigned to a payout of the merchant, see
+/// [`crate::payouts::Payouts::display_seq`].
+#[derive(Clone, Debug, Deserialize, Serialize, Identifiable, Queryable, QueryableByName)]
+#[diesel(table_name = payout_display_sequences, primary_key(merchant_id))]
+pub struct PayoutDisplaySequence {
+    pub merchant_id: String,
+    pub last_value: i64,
+}
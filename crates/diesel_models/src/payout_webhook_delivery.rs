@@ -0,0 +1,117 @@
+use diesel::{AsChangeset, Identifiable, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
+
+use crate::schema::payout_webhook_deliveries;
+
+/// The delivery of a notification about a payout to the webhook endpoint of its merchant,
+/// tracked across the attempts made to deliver it.
+#[derive(Clone, Debug, Eq, PartialEq, Identifiable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = payout_webhook_deliveries, primary_key(delivery_id))]
+pub struct PayoutWebhookDelivery {
+    pub delivery_id: String,
+    pub merchant_id: String,
+    pub payout_id: String,
+    pub target_url: String,
+    pub attempt_count: i32,
+    pub last_status_code: Option<i32>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub next_retry_at: Option<PrimitiveDateTime>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub delivered_at: Option<PrimitiveDateTime>,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub created_at: PrimitiveDateTime,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub last_modified_at: PrimitiveDateTime,
+}
+
+#[derive(
+    Clone, Debug, Eq, PartialEq, Insertable, Serialize, Deserialize, router_derive::DebugAsDisplay,
+)]
+#[diesel(table_name = payout_webhook_deliveries)]
+pub struct PayoutWebhookDeliveryNew {
+    pub delivery_id: String,
+    pub merchant_id: String,
+    pub payout_id: String,
+    pub target_url: String,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub next_retry_at: Option<PrimitiveDateTime>,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub created_at: PrimitiveDateTime,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub last_modified_at: PrimitiveDateTime,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum PayoutWebhookDeliveryUpdate {
+    /// An attempt which was not acknowledged, retried at `next_retry_at`, or given up on if unset.
+    AttemptFailed {
+        status_code: Option<i32>,
+        next_retry_at: Option<PrimitiveDateTime>,
+    },
+    /// An attempt acknowledged by the merchant with `status_code`.
+    Delivered {
+        status_code: i32,
+        delivered_at: PrimitiveDateTime,
+    },
+}
+
+/// The columns set by a [`PayoutWebhookDeliveryUpdate`], every one of them: an attempt replaces
+/// the outcome of the previous one, clearing `next_retry_at` once there is nothing to retry.
+#[derive(Clone, Debug, AsChangeset, router_derive::DebugAsDisplay)]
+#[diesel(table_name = payout_webhook_deliveries, treat_none_as_null = true)]
+pub struct PayoutWebhookDeliveryUpdateInternal {
+    pub attempt_count: i32,
+    pub last_status_code: Option<i32>,
+    pub next_retry_at: Option<PrimitiveDateTime>,
+    pub delivered_at: Option<PrimitiveDateTime>,
+    pub last_modified_at: PrimitiveDateTime,
+}
+
+impl PayoutWebhookDeliveryUpdate {
+    /// The changeset recording this attempt on `source`, the delivery as it was before it.
+    pub fn changeset(self, source: &PayoutWebhookDelivery) -> PayoutWebhookDeliveryUpdateInternal {
+        let attempt_count = source.attempt_count + 1;
+        let last_modified_at = common_utils::date_time::now();
+        match self {
+            Self::AttemptFailed {
+                status_code,
+                next_retry_at,
+            } => PayoutWebhookDeliveryUpdateInternal {
+                attempt_count,
+                last_status_code: status_code,
+                next_retry_at,
+                delivered_at: None,
+                last_modified_at,
+            },
+            Self::Delivered {
+                status_code,
+                delivered_at,
+            } => PayoutWebhookDeliveryUpdateInternal {
+                attempt_count,
+                last_status_code: Some(status_code),
+                next_retry_at: None,
+                delivered_at: Some(delivered_at),
+                last_modified_at,
+            },
+        }
+    }
+
+    pub fn apply_changeset(self, source: PayoutWebhookDelivery) -> PayoutWebhookDelivery {
+        let PayoutWebhookDeliveryUpdateInternal {
+            attempt_count,
+            last_status_code,
+            next_retry_at,
+            delivered_at,
+            last_modified_at,
+        } = self.changeset(&source);
+        PayoutWebhookDelivery {
+            attempt_count,
+            last_status_code,
+            next_retry_at,
+            delivered_at,
+            last_modified_at,
+            ..source
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use diesel::{Identifiable, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
+
+use crate::schema::payout_webhook_events;
+
+/// A connector webhook event about a payout which was processed, recorded so that the retries
+/// of the connector are not applied twice.
+#[derive(Clone, Debug, Eq, PartialEq, Insertable, router_derive::DebugAsDisplay)]
+#[diesel(table_name = payout_webhook_events)]
+pub struct PayoutWebhookEventNew {
+    pub connector: String,
+    pub event_id: String,
+    pub created_at: PrimitiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Identifiable, Queryable)]
+#[diesel(table_name = payout_webhook_events, primary_key(connector, event_id))]
+pub struct PayoutWebhookEvent {
+    pub connector: String,
+    pub event_id: String,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub created_at: PrimitiveDateTime,
+}
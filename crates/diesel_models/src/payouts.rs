@@ -1,12 +1,24 @@
-use common_utils::pii;
-use diesel::{AsChangeset, Identifiable, Insertable, Queryable};
+use common_utils::{pii, types::Maybe};
+use diesel::{AsChangeset, Identifiable, Insertable, Queryable, QueryableByName};
+use rust_decimal::Decimal;
 use serde::{self, Deserialize, Serialize};
 use time::PrimitiveDateTime;
 
 use crate::{enums as storage_enums, schema::payouts};
 
 // Payouts
-#[derive(Clone, Debug, Eq, PartialEq, Identifiable, Queryable, Serialize, Deserialize)]
+#[derive(
+    Clone,
+    Debug,
+    Eq,
+    PartialEq,
+    Identifiable,
+    Queryable,
+    QueryableByName,
+    Serialize,
+    Deserialize,
+    router_derive::FieldNames,
+)]
 #[diesel(table_name = payouts)]
 #[diesel(primary_key(payout_id))]
 pub struct Payouts {
@@ -32,6 +44,54 @@ pub struct Payouts {
     pub attempt_count: i16,
     pub profile_id: String,
     pub status: storage_enums::PayoutStatus,
+    pub original_payout_id: Option<String>,
+    pub hold_reason: Option<String>,
+    #[serde(default)]
+    pub created_by: storage_enums::PayoutCreationSource,
+    pub network_token_ref: Option<String>,
+    #[serde(default)]
+    pub sequence_number: i64,
+    pub connector_idempotency_key: Option<String>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub scheduled_at: Option<PrimitiveDateTime>,
+    #[serde(default)]
+    pub routing_decision: Option<pii::SecretSerdeValue>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub deleted_at: Option<PrimitiveDateTime>,
+    #[serde(default)]
+    pub source_payment_id: Option<String>,
+    #[serde(default)]
+    pub test_mode: bool,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub next_retry_at: Option<PrimitiveDateTime>,
+    #[serde(default)]
+    pub connector: Option<String>,
+    #[serde(default)]
+    pub connector_payout_id: Option<String>,
+    #[serde(default)]
+    pub connector_response: Option<pii::SecretSerdeValue>,
+    #[serde(default)]
+    pub exchange_rate: Option<Decimal>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub exchange_rate_timestamp: Option<PrimitiveDateTime>,
+    #[serde(default)]
+    pub display_seq: i64,
+    #[serde(default)]
+    pub risk_score: Option<i16>,
+    #[serde(default)]
+    pub is_high_risk: bool,
+    #[serde(default)]
+    pub customer_snapshot: Option<pii::SecretSerdeValue>,
+    #[serde(default)]
+    pub cancellation_reason: Option<String>,
+    #[serde(default)]
+    pub last_modified_by: Option<String>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub fulfilled_at: Option<PrimitiveDateTime>,
+    #[serde(default)]
+    pub parent_payout_id: Option<String>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub status_changed_at: Option<PrimitiveDateTime>,
 }
 
 #[derive(
@@ -45,6 +105,7 @@ pub struct Payouts {
     serde::Deserialize,
     router_derive::DebugAsDisplay,
     router_derive::Setter,
+    router_derive::FieldNames,
 )]
 #[diesel(table_name = payouts)]
 pub struct PayoutsNew {
@@ -70,6 +131,46 @@ pub struct PayoutsNew {
     pub profile_id: String,
     pub status: storage_enums::PayoutStatus,
     pub attempt_count: i16,
+    pub original_payout_id: Option<String>,
+    pub hold_reason: Option<String>,
+    #[serde(default)]
+    pub created_by: storage_enums::PayoutCreationSource,
+    pub network_token_ref: Option<String>,
+    #[serde(default)]
+    pub sequence_number: i64,
+    pub connector_idempotency_key: Option<String>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub scheduled_at: Option<PrimitiveDateTime>,
+    #[serde(default)]
+    pub routing_decision: Option<pii::SecretSerdeValue>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub deleted_at: Option<PrimitiveDateTime>,
+    #[serde(default)]
+    pub source_payment_id: Option<String>,
+    #[serde(default)]
+    pub test_mode: bool,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub next_retry_at: Option<PrimitiveDateTime>,
+    #[serde(default)]
+    pub connector: Option<String>,
+    #[serde(default)]
+    pub connector_payout_id: Option<String>,
+    #[serde(default)]
+    pub connector_response: Option<pii::SecretSerdeValue>,
+    #[serde(default)]
+    pub exchange_rate: Option<Decimal>,
+    #[serde(default, with = "common_utils::custom_serde::iso8601::option")]
+    pub exchange_rate_timestamp: Option<PrimitiveDateTime>,
+    #[serde(default)]
+    pub display_seq: i64,
+    #[serde(default)]
+    pub risk_score: Option<i16>,
+    #[serde(default)]
+    pub is_high_risk: bool,
+    #[serde(default)]
+    pub customer_snapshot: Option<pii::SecretSerdeValue>,
+    #[serde(default)]
+    pub parent_payout_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +187,8 @@ pub enum PayoutsUpdate {
         metadata: Option<pii::SecretSerdeValue>,
         profile_id: Option<String>,
         status: Option<storage_enums::PayoutStatus>,
+        #[serde(default)]
+        last_modified_by: Option<String>,
     },
     PayoutMethodIdUpdate {
         payout_method_id: Option<String>,
@@ -96,6 +199,95 @@ pub enum PayoutsUpdate {
     AttemptCountUpdate {
         attempt_count: i16,
     },
+    MetadataUpdate {
+        metadata: Option<pii::SecretSerdeValue>,
+    },
+    HoldUpdate {
+        hold_reason: String,
+    },
+    ResumeUpdate {
+        status: storage_enums::PayoutStatus,
+    },
+    NetworkTokenUpdate {
+        network_token_ref: Option<String>,
+    },
+    ConnectorIdempotencyKeyUpdate {
+        connector_idempotency_key: String,
+    },
+    /// Moves a scheduled payout which became due to `Pending`.
+    ActivateUpdate,
+    /// Moves a draft payout to `Pending`.
+    ConfirmUpdate,
+    StatusUpdate {
+        status: storage_enums::PayoutStatus,
+    },
+    RoutingDecisionUpdate {
+        routing_decision: pii::SecretSerdeValue,
+    },
+    /// Links the payout to the payment it refunds to the original payment method.
+    SourcePaymentUpdate {
+        source_payment_id: String,
+    },
+    /// Sets the earliest time at which the payout is retried, or clears it to retry it as soon
+    /// as possible.
+    NextRetryUpdate {
+        next_retry_at: Option<PrimitiveDateTime>,
+    },
+    /// Moves the payout to another connector for its next attempt.
+    RerouteUpdate {
+        connector: String,
+        connector_idempotency_key: String,
+        attempt_count: i16,
+    },
+    /// Records the exchange rate applied to convert the payout, and when it was quoted.
+    FxUpdate {
+        exchange_rate: Decimal,
+        exchange_rate_timestamp: PrimitiveDateTime,
+    },
+    /// Records the assessment of a risk engine.
+    RiskUpdate {
+        risk_score: i16,
+        is_high_risk: bool,
+    },
+    /// Moves the payout to `Cancelled`, recording why.
+    CancelUpdate {
+        cancellation_reason: String,
+    },
+    CombinedUpdate {
+        amount: Option<i64>,
+        destination_currency: Option<storage_enums::Currency>,
+        source_currency: Option<storage_enums::Currency>,
+        description: Maybe<String>,
+        recurring: Option<bool>,
+        auto_fulfill: Option<bool>,
+        return_url: Maybe<String>,
+        entity_type: Option<storage_enums::PayoutEntityType>,
+        metadata: Maybe<pii::SecretSerdeValue>,
+        payout_method_id: Maybe<String>,
+        profile_id: Option<String>,
+        status: Option<storage_enums::PayoutStatus>,
+        attempt_count: Option<i16>,
+        network_token_ref: Maybe<String>,
+        connector_idempotency_key: Option<String>,
+        routing_decision: Option<pii::SecretSerdeValue>,
+        source_payment_id: Option<String>,
+        next_retry_at: Maybe<PrimitiveDateTime>,
+        exchange_rate: Option<Decimal>,
+        exchange_rate_timestamp: Option<PrimitiveDateTime>,
+        risk_score: Option<i16>,
+        is_high_risk: Option<bool>,
+        #[serde(default)]
+        last_modified_by: Option<String>,
+    },
+    /// Sets or clears the nullable fields of the payout, leaving the fields which are
+    /// `Maybe::Unchanged` as is.
+    Patch {
+        payout_method_id: Maybe<String>,
+        description: Maybe<String>,
+        return_url: Maybe<String>,
+        metadata: Maybe<pii::SecretSerdeValue>,
+        network_token_ref: Maybe<String>,
+    },
 }
 
 #[derive(Clone, Debug, AsChangeset, router_derive::DebugAsDisplay)]
@@ -104,17 +296,33 @@ pub struct PayoutsUpdateInternal {
     pub amount: Option<i64>,
     pub destination_currency: Option<storage_enums::Currency>,
     pub source_currency: Option<storage_enums::Currency>,
-    pub description: Option<String>,
+    pub description: Option<Option<String>>,
     pub recurring: Option<bool>,
     pub auto_fulfill: Option<bool>,
-    pub return_url: Option<String>,
+    pub return_url: Option<Option<String>>,
     pub entity_type: Option<storage_enums::PayoutEntityType>,
-    pub metadata: Option<pii::SecretSerdeValue>,
-    pub payout_method_id: Option<String>,
+    pub metadata: Option<Option<pii::SecretSerdeValue>>,
+    pub payout_method_id: Option<Option<String>>,
     pub profile_id: Option<String>,
     pub status: Option<storage_enums::PayoutStatus>,
     pub last_modified_at: PrimitiveDateTime,
     pub attempt_count: Option<i16>,
+    pub hold_reason: Option<Option<String>>,
+    pub network_token_ref: Option<Option<String>>,
+    pub connector_idempotency_key: Option<String>,
+    pub routing_decision: Option<pii::SecretSerdeValue>,
+    pub source_payment_id: Option<String>,
+    pub next_retry_at: Option<Option<PrimitiveDateTime>>,
+    pub connector: Option<String>,
+    pub connector_payout_id: Option<Option<String>>,
+    pub connector_response: Option<Option<pii::SecretSerdeValue>>,
+    pub exchange_rate: Option<Decimal>,
+    pub exchange_rate_timestamp: Option<PrimitiveDateTime>,
+    pub risk_score: Option<i16>,
+    pub is_high_risk: Option<bool>,
+    pub sequence_number: Option<i64>,
+    pub cancellation_reason: Option<String>,
+    pub last_modified_by: Option<String>,
 }
 
 impl Default for PayoutsUpdateInternal {
@@ -134,6 +342,22 @@ impl Default for PayoutsUpdateInternal {
             status: None,
             last_modified_at: common_utils::date_time::now(),
             attempt_count: None,
+            hold_reason: None,
+            network_token_ref: None,
+            connector_idempotency_key: None,
+            routing_decision: None,
+            source_payment_id: None,
+            next_retry_at: None,
+            connector: None,
+            connector_payout_id: None,
+            connector_response: None,
+            exchange_rate: None,
+            exchange_rate_timestamp: None,
+            risk_score: None,
+            is_high_risk: None,
+            sequence_number: None,
+            cancellation_reason: None,
+            last_modified_by: None,
         }
     }
 }
@@ -153,22 +377,24 @@ impl From<PayoutsUpdate> for PayoutsUpdateInternal {
                 metadata,
                 profile_id,
                 status,
+                last_modified_by,
             } => Self {
                 amount: Some(amount),
                 destination_currency: Some(destination_currency),
                 source_currency: Some(source_currency),
-                description,
+                description: description.map(Some),
                 recurring: Some(recurring),
                 auto_fulfill: Some(auto_fulfill),
-                return_url,
+                return_url: return_url.map(Some),
                 entity_type: Some(entity_type),
-                metadata,
+                metadata: metadata.map(Some),
                 profile_id,
                 status,
+                last_modified_by,
                 ..Default::default()
             },
             PayoutsUpdate::PayoutMethodIdUpdate { payout_method_id } => Self {
-                payout_method_id,
+                payout_method_id: payout_method_id.map(Some),
                 ..Default::default()
             },
             PayoutsUpdate::RecurringUpdate { recurring } => Self {
@@ -179,11 +405,157 @@ impl From<PayoutsUpdate> for PayoutsUpdateInternal {
                 attempt_count: Some(attempt_count),
                 ..Default::default()
             },
+            PayoutsUpdate::MetadataUpdate { metadata } => Self {
+                metadata: metadata.map(Some),
+                ..Default::default()
+            },
+            PayoutsUpdate::HoldUpdate { hold_reason } => Self {
+                status: Some(storage_enums::PayoutStatus::HeldForReview),
+                hold_reason: Some(Some(hold_reason)),
+                ..Default::default()
+            },
+            PayoutsUpdate::ResumeUpdate { status } => Self {
+                status: Some(status),
+                hold_reason: Some(None),
+                ..Default::default()
+            },
+            PayoutsUpdate::StatusUpdate { status } => Self {
+                status: Some(status),
+                ..Default::default()
+            },
+            PayoutsUpdate::CancelUpdate {
+                cancellation_reason,
+            } => Self {
+                status: Some(storage_enums::PayoutStatus::Cancelled),
+                cancellation_reason: Some(cancellation_reason),
+                ..Default::default()
+            },
+            PayoutsUpdate::NetworkTokenUpdate { network_token_ref } => Self {
+                network_token_ref: network_token_ref.map(Some),
+                ..Default::default()
+            },
+            PayoutsUpdate::ConnectorIdempotencyKeyUpdate {
+                connector_idempotency_key,
+            } => Self {
+                connector_idempotency_key: Some(connector_idempotency_key),
+                ..Default::default()
+            },
+            PayoutsUpdate::ActivateUpdate | PayoutsUpdate::ConfirmUpdate => Self {
+                status: Some(storage_enums::PayoutStatus::Pending),
+                ..Default::default()
+            },
+            PayoutsUpdate::RoutingDecisionUpdate { routing_decision } => Self {
+                routing_decision: Some(routing_decision),
+                ..Default::default()
+            },
+            PayoutsUpdate::SourcePaymentUpdate { source_payment_id } => Self {
+                source_payment_id: Some(source_payment_id),
+                ..Default::default()
+            },
+            PayoutsUpdate::NextRetryUpdate { next_retry_at } => Self {
+                next_retry_at: Some(next_retry_at),
+                ..Default::default()
+            },
+            PayoutsUpdate::RerouteUpdate {
+                connector,
+                connector_idempotency_key,
+                attempt_count,
+            } => Self {
+                connector: Some(connector),
+                connector_payout_id: Some(None),
+                connector_response: Some(None),
+                connector_idempotency_key: Some(connector_idempotency_key),
+                attempt_count: Some(attempt_count),
+                ..Default::default()
+            },
+            PayoutsUpdate::FxUpdate {
+                exchange_rate,
+                exchange_rate_timestamp,
+            } => Self {
+                exchange_rate: Some(exchange_rate),
+                exchange_rate_timestamp: Some(exchange_rate_timestamp),
+                ..Default::default()
+            },
+            PayoutsUpdate::RiskUpdate {
+                risk_score,
+                is_high_risk,
+            } => Self {
+                risk_score: Some(risk_score),
+                is_high_risk: Some(is_high_risk),
+                ..Default::default()
+            },
+            PayoutsUpdate::CombinedUpdate {
+                amount,
+                destination_currency,
+                source_currency,
+                description,
+                recurring,
+                auto_fulfill,
+                return_url,
+                entity_type,
+                metadata,
+                payout_method_id,
+                profile_id,
+                status,
+                attempt_count,
+                network_token_ref,
+                connector_idempotency_key,
+                routing_decision,
+                source_payment_id,
+                next_retry_at,
+                exchange_rate,
+                exchange_rate_timestamp,
+                risk_score,
+                is_high_risk,
+                last_modified_by,
+            } => Self {
+                amount,
+                destination_currency,
+                source_currency,
+                description: description.into_changeset(),
+                recurring,
+                auto_fulfill,
+                return_url: return_url.into_changeset(),
+                entity_type,
+                metadata: metadata.into_changeset(),
+                payout_method_id: payout_method_id.into_changeset(),
+                profile_id,
+                status,
+                attempt_count,
+                network_token_ref: network_token_ref.into_changeset(),
+                connector_idempotency_key,
+                routing_decision,
+                source_payment_id,
+                next_retry_at: next_retry_at.into_changeset(),
+                exchange_rate,
+                exchange_rate_timestamp,
+                risk_score,
+                is_high_risk,
+                last_modified_by,
+                ..Default::default()
+            },
+            PayoutsUpdate::Patch {
+                payout_method_id,
+                description,
+                return_url,
+                metadata,
+                network_token_ref,
+            } => Self {
+                payout_method_id: payout_method_id.into_changeset(),
+                description: description.into_changeset(),
+                return_url: return_url.into_changeset(),
+                metadata: metadata.into_changeset(),
+                network_token_ref: network_token_ref.into_changeset(),
+                ..Default::default()
+            },
         }
     }
 }
 
 impl PayoutsUpdate {
+    /// Applies the update to `source`, stamping `status_changed_at` with the time of the update
+    /// if it moves the payout to another status, and `fulfilled_at` if it moves the payout to
+    /// `Success`, as the database does on updates.
     pub fn apply_changeset(self, source: Payouts) -> Payouts {
         let PayoutsUpdateInternal {
             amount,
@@ -200,23 +572,192 @@ impl PayoutsUpdate {
             status,
             last_modified_at,
             attempt_count,
+            hold_reason,
+            network_token_ref,
+            connector_idempotency_key,
+            routing_decision,
+            source_payment_id,
+            next_retry_at,
+            connector,
+            connector_payout_id,
+            connector_response,
+            exchange_rate,
+            exchange_rate_timestamp,
+            risk_score,
+            is_high_risk,
+            sequence_number,
+            cancellation_reason,
+            last_modified_by,
         } = self.into();
+        let status_changed = status.is_some_and(|status| status != source.status);
         Payouts {
             amount: amount.unwrap_or(source.amount),
             destination_currency: destination_currency.unwrap_or(source.destination_currency),
             source_currency: source_currency.unwrap_or(source.source_currency),
-            description: description.or(source.description),
+            description: description.unwrap_or(source.description),
             recurring: recurring.unwrap_or(source.recurring),
             auto_fulfill: auto_fulfill.unwrap_or(source.auto_fulfill),
-            return_url: return_url.or(source.return_url),
+            return_url: return_url.unwrap_or(source.return_url),
             entity_type: entity_type.unwrap_or(source.entity_type),
-            metadata: metadata.or(source.metadata),
-            payout_method_id: payout_method_id.or(source.payout_method_id),
+            metadata: metadata.unwrap_or(source.metadata),
+            payout_method_id: payout_method_id.unwrap_or(source.payout_method_id),
             profile_id: profile_id.unwrap_or(source.profile_id),
             status: status.unwrap_or(source.status),
             last_modified_at,
             attempt_count: attempt_count.unwrap_or(source.attempt_count),
+            hold_reason: hold_reason.unwrap_or(source.hold_reason),
+            network_token_ref: network_token_ref.unwrap_or(source.network_token_ref),
+            connector_idempotency_key: connector_idempotency_key
+                .or(source.connector_idempotency_key),
+            routing_decision: routing_decision.or(source.routing_decision),
+            source_payment_id: source_payment_id.or(source.source_payment_id),
+            next_retry_at: next_retry_at.unwrap_or(source.next_retry_at),
+            connector: connector.or(source.connector),
+            connector_payout_id: connector_payout_id.unwrap_or(source.connector_payout_id),
+            connector_response: connector_response.unwrap_or(source.connector_response),
+            exchange_rate: exchange_rate.or(source.exchange_rate),
+            exchange_rate_timestamp: exchange_rate_timestamp.or(source.exchange_rate_timestamp),
+            risk_score: risk_score.or(source.risk_score),
+            is_high_risk: is_high_risk.unwrap_or(source.is_high_risk),
+            sequence_number: sequence_number.unwrap_or(source.sequence_number + 1),
+            cancellation_reason: cancellation_reason.or(source.cancellation_reason),
+            last_modified_by: last_modified_by.or(source.last_modified_by),
+            fulfilled_at: if status_changed && status == Some(storage_enums::PayoutStatus::Success)
+            {
+                Some(last_modified_at)
+            } else {
+                source.fulfilled_at
+            },
+            status_changed_at: if status_changed {
+                Some(last_modified_at)
+            } else {
+                source.status_changed_at
+            },
             ..source
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    /// A payout as written to KV and to the drainer stream before payouts had a `created_by`.
+    fn payload_without_created_by() -> serde_json::Value {
+        let now = common_utils::date_time::now();
+        let mut payload = serde_json::to_value(PayoutsNew {
+            payout_id: "po_1".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            created_by: storage_enums::PayoutCreationSource::Dashboard,
+            ..Default::default()
+        })
+        .unwrap();
+        payload.as_object_mut().unwrap().retain(|field, _| {
+            [
+                "payout_id",
+                "merchant_id",
+                "customer_id",
+                "address_id",
+                "payout_type",
+                "payout_method_id",
+                "amount",
+                "destination_currency",
+                "source_currency",
+                "description",
+                "recurring",
+                "auto_fulfill",
+                "return_url",
+                "entity_type",
+                "metadata",
+                "created_at",
+                "last_modified_at",
+                "attempt_count",
+                "profile_id",
+                "status",
+                "original_payout_id",
+                "hold_reason",
+            ]
+            .contains(&field.as_str())
+        });
+        payload
+    }
+
+    #[test]
+    fn test_payout_written_before_created_by_deserializes() {
+        let payload = payload_without_created_by();
+
+        let payout: Payouts = serde_json::from_value(payload.clone()).unwrap();
+        assert_eq!(payout.payout_id, "po_1");
+        // Matches the default of the column
+        assert_eq!(payout.created_by, storage_enums::PayoutCreationSource::Api);
+
+        let new: PayoutsNew = serde_json::from_value(payload).unwrap();
+        assert_eq!(new.created_by, storage_enums::PayoutCreationSource::Api);
+    }
+
+    fn payout_in(status: storage_enums::PayoutStatus) -> Payouts {
+        let now = common_utils::date_time::now();
+        let new = PayoutsNew {
+            payout_id: "po_1".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            status,
+            ..Default::default()
+        };
+        serde_json::from_value(serde_json::to_value(new).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_fulfilled_at_is_stamped_only_when_moving_to_success() {
+        let fulfilled = PayoutsUpdate::StatusUpdate {
+            status: storage_enums::PayoutStatus::Success,
+        }
+        .apply_changeset(payout_in(storage_enums::PayoutStatus::Pending));
+        assert_eq!(fulfilled.fulfilled_at, Some(fulfilled.last_modified_at));
+
+        // A later update to `Success` keeps the time it was first fulfilled at
+        let fulfilled_at = fulfilled.last_modified_at - time::Duration::minutes(1);
+        let updated = PayoutsUpdate::StatusUpdate {
+            status: storage_enums::PayoutStatus::Success,
+        }
+        .apply_changeset(Payouts {
+            fulfilled_at: Some(fulfilled_at),
+            ..fulfilled
+        });
+        assert_eq!(updated.fulfilled_at, Some(fulfilled_at));
+    }
+
+    #[test]
+    fn test_resume_clears_hold_reason() {
+        let held = PayoutsUpdate::HoldUpdate {
+            hold_reason: "risk_review".to_string(),
+        }
+        .apply_changeset(payout_in(storage_enums::PayoutStatus::RequiresFulfillment));
+        assert_eq!(held.hold_reason.as_deref(), Some("risk_review"));
+
+        let resumed = PayoutsUpdate::ResumeUpdate {
+            status: storage_enums::PayoutStatus::RequiresFulfillment,
+        }
+        .apply_changeset(held);
+        assert_eq!(resumed.hold_reason, None);
+    }
+
+    #[test]
+    fn test_status_timestamps_are_serialized_like_the_other_timestamps() {
+        let fulfilled = PayoutsUpdate::StatusUpdate {
+            status: storage_enums::PayoutStatus::Success,
+        }
+        .apply_changeset(payout_in(storage_enums::PayoutStatus::Pending));
+
+        let payload = serde_json::to_value(&fulfilled).unwrap();
+        assert_eq!(payload["fulfilled_at"], payload["last_modified_at"]);
+        assert_eq!(payload["status_changed_at"], payload["last_modified_at"]);
+        let payout: Payouts = serde_json::from_value(payload).unwrap();
+        assert_eq!(payout, fulfilled);
+    }
+}
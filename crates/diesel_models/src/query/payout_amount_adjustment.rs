@@ -0,0 +1,34 @@
+use diesel::{associations::HasTable, BoolExpressionMethods, ExpressionMethods};
+
+use super::generics;
+use crate::{
+    payout_amount_adjustment::{PayoutAmountAdjustment, PayoutAmountAdjustmentNew},
+    schema::payout_amount_adjustments::dsl,
+    PgPooledConn, StorageResult,
+};
+
+impl PayoutAmountAdjustmentNew {
+    pub async fn insert(self, conn: &PgPooledConn) -> StorageResult<PayoutAmountAdjustment> {
+        generics::generic_insert(conn, self).await
+    }
+}
+
+impl PayoutAmountAdjustment {
+    /// The adjustments of the payout, in the order they were made.
+    pub async fn filter_by_merchant_id_payout_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::payout_id.eq(payout_id.to_owned())),
+            None,
+            None,
+            Some(dsl::id.asc()),
+        )
+        .await
+    }
+}
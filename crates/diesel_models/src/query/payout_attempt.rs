@@ -1,5 +1,6 @@
-use diesel::{associations::HasTable, BoolExpressionMethods, ExpressionMethods};
-use error_stack::report;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{associations::HasTable, BoolExpressionMethods, ExpressionMethods, QueryDsl};
+use error_stack::{report, IntoReport, ResultExt};
 
 use super::generics;
 use crate::{
@@ -59,6 +60,43 @@ impl PayoutAttempt {
         .await
     }
 
+    /// Returns the attempts of the payout oldest first, ties broken by `payout_attempt_id`.
+    pub async fn filter_by_merchant_id_payout_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::payout_id.eq(payout_id.to_owned())),
+            None,
+            None,
+            Some((dsl::created_at.asc(), dsl::payout_attempt_id.asc())),
+        )
+        .await
+    }
+
+    pub async fn count_by_merchant_id_payout_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> StorageResult<i64> {
+        <Self as HasTable>::table()
+            .filter(
+                dsl::merchant_id
+                    .eq(merchant_id.to_owned())
+                    .and(dsl::payout_id.eq(payout_id.to_owned())),
+            )
+            .count()
+            .get_result_async::<i64>(conn)
+            .await
+            .into_report()
+            .change_context(errors::DatabaseError::Others)
+            .attach_printable("Error counting attempts of payout")
+    }
+
     pub async fn find_by_merchant_id_payout_attempt_id(
         conn: &PgPooledConn,
         merchant_id: &str,
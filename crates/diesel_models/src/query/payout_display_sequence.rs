@@ -0,0 +1,74 @@
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{debug_query, pg::Pg, sql_types::Text};
+use error_stack::{report, IntoReport, ResultExt};
+
+use crate::{
+    errors, payout_display_sequence::PayoutDisplaySequence, query::generics::db_metrics,
+    schema::payout_display_sequences, PgPooledConn, StorageResult,
+};
+
+/// The greatest display sequence number taken by a stored payout of the merchant `$1`, which is
+/// ahead of the counter if the number was assigned by the KV store.
+const LAST_STORED_DISPLAY_SEQ: &str =
+    "COALESCE((SELECT MAX(display_seq) FROM payouts WHERE merchant_id = $1), 0)";
+
+impl PayoutDisplaySequence {
+    /// Assigns the next display sequence number of the merchant, creating its counter on the
+    /// first call.
+    ///
+    /// Relies on `INSERT ... ON CONFLICT DO UPDATE`, which locks the row of the counter and
+    /// increments its latest value, so that concurrent callers never get the same number. A number assigned to a payout which then
+    /// fails to be inserted is not reused.
+    pub async fn next(conn: &PgPooledConn, merchant_id: &str) -> StorageResult<i64> {
+        let query = diesel::sql_query(format!(
+            "INSERT INTO payout_display_sequences (merchant_id, last_value) \
+             VALUES ($1, {LAST_STORED_DISPLAY_SEQ} + 1) \
+             ON CONFLICT (merchant_id) DO UPDATE SET last_value = \
+             GREATEST(payout_display_sequences.last_value, {LAST_STORED_DISPLAY_SEQ}) + 1 \
+             RETURNING *"
+        ))
+        .bind::<Text, _>(merchant_id.to_owned());
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&query).to_string());
+
+        db_metrics::track_database_call::<payout_display_sequences::table, _, _>(
+            query.get_results_async::<Self>(conn),
+            db_metrics::DatabaseOperation::Insert,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::Others)
+        .attach_printable("Error while assigning a payout display sequence number")?
+        .pop()
+        .map(|sequence| sequence.last_value)
+        .ok_or_else(|| {
+            report!(errors::DatabaseError::Others)
+                .attach_printable("No payout display sequence number was assigned")
+        })
+    }
+
+    /// The last display sequence number taken by a payout of the merchant, `0` if none was.
+    pub async fn last_taken(conn: &PgPooledConn, merchant_id: &str) -> StorageResult<i64> {
+        let query = diesel::sql_query(format!(
+            "SELECT $1 AS merchant_id, GREATEST(COALESCE(\
+             (SELECT last_value FROM payout_display_sequences WHERE merchant_id = $1), 0), \
+             {LAST_STORED_DISPLAY_SEQ}) AS last_value"
+        ))
+        .bind::<Text, _>(merchant_id.to_owned());
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&query).to_string());
+
+        db_metrics::track_database_call::<payout_display_sequences::table, _, _>(
+            query.get_results_async::<Self>(conn),
+            db_metrics::DatabaseOperation::Filter,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::Others)
+        .attach_printable("Error while reading the last payout display sequence number")
+        .map(|mut sequences| {
+            sequences
+                .pop()
+                .map(|sequence| sequence.last_value)
+                .unwrap_or(0)
+        })
+    }
+}
@@ -0,0 +1,78 @@
+use diesel::{associations::HasTable, BoolExpressionMethods, ExpressionMethods};
+
+use super::generics;
+use crate::{
+    payout_webhook_delivery::{
+        PayoutWebhookDelivery, PayoutWebhookDeliveryNew, PayoutWebhookDeliveryUpdate,
+    },
+    schema::payout_webhook_deliveries::dsl,
+    PgPooledConn, StorageResult,
+};
+
+impl PayoutWebhookDeliveryNew {
+    pub async fn insert(self, conn: &PgPooledConn) -> StorageResult<PayoutWebhookDelivery> {
+        generics::generic_insert(conn, self).await
+    }
+}
+
+impl PayoutWebhookDelivery {
+    pub async fn update(
+        self,
+        conn: &PgPooledConn,
+        delivery_update: PayoutWebhookDeliveryUpdate,
+    ) -> StorageResult<Self> {
+        generics::generic_update_with_unique_predicate_get_result::<
+            <Self as HasTable>::Table,
+            _,
+            _,
+            _,
+        >(
+            conn,
+            dsl::delivery_id
+                .eq(self.delivery_id.to_owned())
+                .and(dsl::merchant_id.eq(self.merchant_id.to_owned())),
+            delivery_update.changeset(&self),
+        )
+        .await
+    }
+
+    /// Returns the deliveries of the payout oldest first, ties broken by `delivery_id`.
+    pub async fn filter_by_merchant_id_payout_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::payout_id.eq(payout_id.to_owned())),
+            None,
+            None,
+            Some((dsl::created_at.asc(), dsl::delivery_id.asc())),
+        )
+        .await
+    }
+
+    /// Returns up to `limit` deliveries across merchants, not delivered yet and due for a retry
+    /// at or before `older_than`, the longest due first.
+    ///
+    /// Filters on the predicate of the `payout_webhook_deliveries_pending_index` partial index.
+    pub async fn filter_pending(
+        conn: &PgPooledConn,
+        older_than: time::PrimitiveDateTime,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::delivered_at
+                .is_null()
+                .and(dsl::next_retry_at.is_not_null())
+                .and(dsl::next_retry_at.le(older_than)),
+            Some(limit),
+            None,
+            Some((dsl::next_retry_at.asc(), dsl::delivery_id.asc())),
+        )
+        .await
+    }
+}
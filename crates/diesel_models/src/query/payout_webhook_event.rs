@@ -0,0 +1,55 @@
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{
+    associations::HasTable, debug_query, pg::Pg, BoolExpressionMethods, ExpressionMethods,
+};
+use error_stack::{IntoReport, ResultExt};
+
+use crate::{
+    errors,
+    payout_webhook_event::{PayoutWebhookEvent, PayoutWebhookEventNew},
+    query::generics::{self, db_metrics},
+    schema::payout_webhook_events::{self, dsl},
+    PgPooledConn, StorageResult,
+};
+
+impl PayoutWebhookEventNew {
+    /// Records the event unless it was recorded already, returning whether it was recorded by
+    /// this call.
+    ///
+    /// Relies on `INSERT ... ON CONFLICT DO NOTHING`, so that of several concurrent deliveries
+    /// of the same event exactly one records it.
+    pub async fn insert_if_absent(self, conn: &PgPooledConn) -> StorageResult<bool> {
+        let debug_values = format!("{self:?}");
+        let query = diesel::insert_into(payout_webhook_events::table)
+            .values(self)
+            .on_conflict_do_nothing();
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&query).to_string());
+
+        db_metrics::track_database_call::<payout_webhook_events::table, _, _>(
+            query.execute_async(conn),
+            db_metrics::DatabaseOperation::Insert,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::Others)
+        .attach_printable_lazy(|| format!("Error while inserting {debug_values}"))
+        .map(|inserted_rows| inserted_rows == 1)
+    }
+}
+
+impl PayoutWebhookEvent {
+    /// Deletes the record of the event, returning whether it was recorded.
+    pub async fn delete_by_connector_event_id(
+        conn: &PgPooledConn,
+        connector: &str,
+        event_id: &str,
+    ) -> StorageResult<bool> {
+        generics::generic_delete::<<Self as HasTable>::Table, _>(
+            conn,
+            dsl::connector
+                .eq(connector.to_owned())
+                .and(dsl::event_id.eq(event_id.to_owned())),
+        )
+        .await
+    }
+}
@@ -1,10 +1,22 @@
-use diesel::{associations::HasTable, BoolExpressionMethods, ExpressionMethods};
-use error_stack::report;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use common_utils::pii;
+use diesel::{
+    associations::HasTable,
+    debug_query,
+    helper_types::Desc,
+    pg::Pg,
+    sql_types::{Array, BigInt, Double, Jsonb, Nullable, Numeric, Text, Timestamp},
+    BoolExpressionMethods, ExpressionMethods, PgJsonbExpressionMethods, QueryDsl,
+};
+use error_stack::{report, IntoReport, ResultExt};
+use rust_decimal::Decimal;
+use time::PrimitiveDateTime;
 
 use super::generics;
 use crate::{
-    errors,
+    enums as storage_enums, errors,
     payouts::{Payouts, PayoutsNew, PayoutsUpdate, PayoutsUpdateInternal},
+    query::generics::db_metrics,
     schema::payouts::dsl,
     PgPooledConn, StorageResult,
 };
@@ -15,6 +27,32 @@ impl PayoutsNew {
     }
 }
 impl Payouts {
+    /// The ordering every payout list or search query must apply: `created_at DESC, payout_id
+    /// DESC`.
+    ///
+    /// Rows sharing the same `created_at` are tie-broken by `payout_id`, so results are
+    /// reproducible across runs and offset based pagination never skips or repeats a row.
+    pub fn list_order() -> (Desc<dsl::created_at>, Desc<dsl::payout_id>) {
+        (dsl::created_at.desc(), dsl::payout_id.desc())
+    }
+
+    /// The statuses covered by the `payouts_active_status_index` and
+    /// `payouts_active_status_last_modified_at_index` partial indexes.
+    ///
+    /// A partial index is only usable by a query whose filter implies the index predicate, so the
+    /// finders scanning payouts across merchants filter on one of these statuses.
+    pub const ACTIVE_STATUSES: [storage_enums::PayoutStatus; 3] = [
+        storage_enums::PayoutStatus::Pending,
+        storage_enums::PayoutStatus::Scheduled,
+        storage_enums::PayoutStatus::RequiresFulfillment,
+    ];
+
+    /// Status filtered on by [`Self::filter_needing_retry`] and [`Self::claim_for_retry`].
+    pub const RETRY_STATUS: storage_enums::PayoutStatus = storage_enums::PayoutStatus::Pending;
+
+    /// Status filtered on by [`Self::filter_due_scheduled`] and [`Self::activate_due`].
+    pub const DUE_STATUS: storage_enums::PayoutStatus = storage_enums::PayoutStatus::Scheduled;
+
     pub async fn update(
         self,
         conn: &PgPooledConn,
@@ -25,7 +63,10 @@ impl Payouts {
             dsl::payout_id
                 .eq(self.payout_id.to_owned())
                 .and(dsl::merchant_id.eq(self.merchant_id.to_owned())),
-            PayoutsUpdateInternal::from(payout_update),
+            PayoutsUpdateInternal {
+                sequence_number: Some(self.sequence_number + 1),
+                ..PayoutsUpdateInternal::from(payout_update)
+            },
         )
         .await
         {
@@ -39,6 +80,68 @@ impl Payouts {
         }
     }
 
+    /// Applies `payout_update` only if the stored sequence number is still the one of `self`,
+    /// failing with [`errors::DatabaseError::NotFound`] if the payout was updated since `self` was
+    /// read.
+    pub async fn update_if_sequence_matches(
+        self,
+        conn: &PgPooledConn,
+        payout_update: PayoutsUpdate,
+    ) -> StorageResult<Self> {
+        generics::generic_update_with_results::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::payout_id
+                .eq(self.payout_id.to_owned())
+                .and(dsl::merchant_id.eq(self.merchant_id.to_owned()))
+                .and(dsl::sequence_number.eq(self.sequence_number)),
+            PayoutsUpdateInternal {
+                sequence_number: Some(self.sequence_number + 1),
+                ..PayoutsUpdateInternal::from(payout_update)
+            },
+        )
+        .await?
+        .pop()
+        .ok_or(error_stack::report!(errors::DatabaseError::NotFound))
+    }
+
+    /// Applies `payout_update` only if the stored payout is still in the status of `self`,
+    /// whatever its sequence number. Returns `None` if its status changed.
+    ///
+    /// The sequence number of the stored payout is bumped, so that the writers which read it
+    /// before this update see theirs rejected by [`Payouts::update_if_sequence_matches`].
+    pub async fn update_if_status(
+        self,
+        conn: &PgPooledConn,
+        payout_update: PayoutsUpdate,
+    ) -> StorageResult<Option<Self>> {
+        let query = diesel::update(
+            <Self as HasTable>::table().filter(
+                dsl::merchant_id
+                    .eq(self.merchant_id.to_owned())
+                    .and(dsl::payout_id.eq(self.payout_id.to_owned()))
+                    .and(dsl::status.eq(self.status)),
+            ),
+        )
+        .set((
+            PayoutsUpdateInternal {
+                sequence_number: None,
+                ..PayoutsUpdateInternal::from(payout_update)
+            },
+            dsl::sequence_number.eq(dsl::sequence_number + 1),
+        ));
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&query).to_string());
+
+        db_metrics::track_database_call::<<Self as HasTable>::Table, _, _>(
+            query.get_results_async::<Self>(conn),
+            db_metrics::DatabaseOperation::UpdateWithResults,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::Others)
+        .attach_printable("Error while updating payout if status matches")
+        .map(|mut updated| updated.pop())
+    }
+
     pub async fn find_by_merchant_id_payout_id(
         conn: &PgPooledConn,
         merchant_id: &str,
@@ -87,4 +190,1008 @@ impl Payouts {
         )
         .await
     }
+
+    /// Deletes the payout, returning whether it was there. Deleting a payout which is already
+    /// gone is not an error, so that a repeated delete, e.g. replayed by the drainer, succeeds.
+    pub async fn delete_by_merchant_id_payout_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> StorageResult<bool> {
+        match generics::generic_delete::<<Self as HasTable>::Table, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::payout_id.eq(payout_id.to_owned())),
+        )
+        .await
+        {
+            Err(error) if matches!(error.current_context(), errors::DatabaseError::NotFound) => {
+                Ok(false)
+            }
+            result => result,
+        }
+    }
+
+    /// The columns of a payout which [`Self::find_by_merchant_id_payout_id_skipping`] can leave
+    /// out, all of them nullable `JSONB` columns.
+    pub const SKIPPABLE_COLUMNS: [&'static str; 3] = [
+        "connector_response",
+        "routing_decision",
+        "customer_snapshot",
+    ];
+
+    /// The select list of a payout, with the columns of `skipped_columns` which are among
+    /// [`Self::SKIPPABLE_COLUMNS`] selected as `NULL`.
+    pub fn select_list(skipped_columns: &[&str]) -> String {
+        Self::FIELD_NAMES
+            .iter()
+            .map(|column| {
+                if Self::SKIPPABLE_COLUMNS.contains(column) && skipped_columns.contains(column) {
+                    format!("NULL::jsonb AS {column}")
+                } else {
+                    (*column).to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Finds a payout like [`Self::find_by_merchant_id_payout_id`], without reading the columns
+    /// of `skipped_columns`, see [`Self::select_list`]. The fields of the columns skipped are
+    /// `None`.
+    pub async fn find_by_merchant_id_payout_id_skipping(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_id: &str,
+        skipped_columns: &[&str],
+    ) -> StorageResult<Self> {
+        let query = diesel::sql_query(format!(
+            "SELECT {} FROM payouts WHERE merchant_id = $1 AND payout_id = $2",
+            Self::select_list(skipped_columns)
+        ))
+        .bind::<Text, _>(merchant_id.to_owned())
+        .bind::<Text, _>(payout_id.to_owned());
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&query).to_string());
+
+        db_metrics::track_database_call::<<Self as HasTable>::Table, _, _>(
+            query.get_results_async::<Self>(conn),
+            db_metrics::DatabaseOperation::FindOne,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::Others)
+        .attach_printable("Error while finding payout")?
+        .pop()
+        .ok_or_else(|| {
+            report!(errors::DatabaseError::NotFound).attach_printable("Error while finding payout")
+        })
+    }
+
+    pub async fn find_by_merchant_id_display_seq(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        display_seq: i64,
+    ) -> StorageResult<Self> {
+        generics::generic_find_one::<<Self as HasTable>::Table, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::display_seq.eq(display_seq)),
+        )
+        .await
+    }
+
+    /// Selects the status of the payout only, along with its test mode.
+    pub async fn find_status_by_merchant_id_payout_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> StorageResult<(storage_enums::PayoutStatus, bool)> {
+        <Self as HasTable>::table()
+            .filter(
+                dsl::merchant_id
+                    .eq(merchant_id.to_owned())
+                    .and(dsl::payout_id.eq(payout_id.to_owned())),
+            )
+            .select((dsl::status, dsl::test_mode))
+            .get_results_async::<(storage_enums::PayoutStatus, bool)>(conn)
+            .await
+            .into_report()
+            .change_context(errors::DatabaseError::Others)
+            .attach_printable("Error finding the status of payout")?
+            .into_iter()
+            .next()
+            .ok_or_else(|| report!(errors::DatabaseError::NotFound))
+    }
+
+    pub async fn filter_by_merchant_id_payout_ids(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_ids: &[String],
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::payout_id.eq_any(payout_ids.to_owned())),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    /// Bumps the attempt count of a pending payout with fewer than `max_attempts` attempts, which
+    /// was not modified within `lease` of `now` and whose `next_retry_at` is due, returning the
+    /// claimed payout.
+    ///
+    /// The conditions are evaluated and the payout updated in a single statement, so that a
+    /// payout is claimed by at most one of several concurrent callers.
+    pub async fn claim_for_retry(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_id: &str,
+        max_attempts: i16,
+        now: PrimitiveDateTime,
+        lease: time::Duration,
+    ) -> StorageResult<Option<Self>> {
+        generics::generic_update_with_results::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::payout_id.eq(payout_id.to_owned()))
+                .and(dsl::status.eq(Self::RETRY_STATUS))
+                .and(dsl::attempt_count.lt(max_attempts))
+                .and(dsl::last_modified_at.le(now - lease))
+                .and(dsl::next_retry_at.is_null().or(dsl::next_retry_at.le(now))),
+            (
+                dsl::attempt_count.eq(dsl::attempt_count + 1),
+                dsl::last_modified_at.eq(common_utils::date_time::now()),
+                dsl::sequence_number.eq(dsl::sequence_number + 1),
+            ),
+        )
+        .await
+        .map(|mut payouts: Vec<Self>| payouts.pop())
+    }
+
+    /// Returns up to `limit` pending payouts with fewer than `max_attempts` attempts, which were
+    /// not modified within `lease` of `now` and whose `next_retry_at` is due, across merchants,
+    /// least recently modified first.
+    ///
+    /// The returned payouts are candidates only, each of them must still be claimed with
+    /// [`Self::claim_for_retry`] before being retried.
+    pub async fn filter_needing_retry(
+        conn: &PgPooledConn,
+        max_attempts: i16,
+        now: PrimitiveDateTime,
+        lease: time::Duration,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        let query = Self::needing_retry_query(max_attempts, now, lease, limit);
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&query).to_string());
+
+        db_metrics::track_database_call::<<Self as HasTable>::Table, _, _>(
+            query.get_results_async::<Self>(conn),
+            db_metrics::DatabaseOperation::Filter,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::NotFound)
+        .attach_printable("Error while filtering payouts needing a retry")
+    }
+
+    /// The query of [`Self::filter_needing_retry`], served by the
+    /// `payouts_active_status_last_modified_at_index` partial index.
+    fn needing_retry_query(
+        max_attempts: i16,
+        now: PrimitiveDateTime,
+        lease: time::Duration,
+        limit: i64,
+    ) -> crate::schema::payouts::BoxedQuery<'static, Pg> {
+        <Self as HasTable>::table()
+            .into_boxed()
+            .filter(
+                dsl::status
+                    .eq(Self::RETRY_STATUS)
+                    .and(dsl::attempt_count.lt(max_attempts))
+                    .and(dsl::last_modified_at.le(now - lease))
+                    .and(dsl::next_retry_at.is_null().or(dsl::next_retry_at.le(now))),
+            )
+            .order((dsl::last_modified_at.asc(), dsl::payout_id.asc()))
+            .limit(limit)
+    }
+
+    /// Returns up to `limit` payouts in `status` last modified before `before`, across
+    /// merchants, ordered by `(last_modified_at, payout_id)`, starting after the
+    /// `(last_modified_at, payout_id)` position `after` if given.
+    ///
+    /// Served by the `(status, last_modified_at, payout_id)` index.
+    pub async fn filter_by_status_modified_before(
+        conn: &PgPooledConn,
+        status: storage_enums::PayoutStatus,
+        before: PrimitiveDateTime,
+        after: Option<(PrimitiveDateTime, &str)>,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        let in_window = dsl::status.eq(status).and(dsl::last_modified_at.lt(before));
+        match after {
+            None => {
+                generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+                    conn,
+                    in_window,
+                    Some(limit),
+                    None,
+                    Some((dsl::last_modified_at.asc(), dsl::payout_id.asc())),
+                )
+                .await
+            }
+            Some((last_modified_at, payout_id)) => {
+                generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+                    conn,
+                    in_window.and(
+                        dsl::last_modified_at
+                            .gt(last_modified_at)
+                            .or(dsl::last_modified_at
+                                .eq(last_modified_at)
+                                .and(dsl::payout_id.gt(payout_id.to_owned()))),
+                    ),
+                    Some(limit),
+                    None,
+                    Some((dsl::last_modified_at.asc(), dsl::payout_id.asc())),
+                )
+                .await
+            }
+        }
+    }
+
+    /// Sets `deleted_at` on every payout of the merchant which is not deleted yet, returning the
+    /// payouts deleted by this call.
+    pub async fn soft_delete_by_merchant_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        let now = common_utils::date_time::now();
+        generics::generic_update_with_results::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::deleted_at.is_null()),
+            (
+                dsl::deleted_at.eq(now),
+                dsl::last_modified_at.eq(now),
+                dsl::sequence_number.eq(dsl::sequence_number + 1),
+            ),
+        )
+        .await
+    }
+
+    /// Returns up to `limit` payouts of the merchant without a profile, i.e. whose `profile_id`
+    /// is the empty placeholder held by legacy payouts, ordered by payout id.
+    pub async fn filter_by_merchant_id_without_profile(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::profile_id.eq(String::new())),
+            Some(limit),
+            None,
+            Some(dsl::payout_id.asc()),
+        )
+        .await
+    }
+
+    /// Sets `profile_id` on the payouts of the merchant among `payout_ids` which still have no
+    /// profile, returning the payouts updated by this call.
+    pub async fn backfill_profile_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_ids: Vec<String>,
+        profile_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_update_with_results::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::payout_id.eq_any(payout_ids))
+                .and(dsl::profile_id.eq(String::new())),
+            (
+                dsl::profile_id.eq(profile_id.to_owned()),
+                dsl::last_modified_at.eq(common_utils::date_time::now()),
+                dsl::sequence_number.eq(dsl::sequence_number + 1),
+            ),
+        )
+        .await
+    }
+
+    /// Returns the payouts of the merchant whose `last_modified_at` is earlier than their
+    /// `created_at`.
+    pub async fn filter_by_merchant_id_inconsistent_timestamps(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::last_modified_at.lt(dsl::created_at)),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    /// Sets `last_modified_at` to `created_at` on the payouts of the merchant where it is
+    /// earlier, returning the payouts repaired by this call.
+    pub async fn repair_timestamps_by_merchant_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_update_with_results::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::last_modified_at.lt(dsl::created_at)),
+            (
+                dsl::last_modified_at.eq(dsl::created_at),
+                dsl::sequence_number.eq(dsl::sequence_number + 1),
+            ),
+        )
+        .await
+    }
+
+    pub async fn filter_by_merchant_id_network_token_ref(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        network_token_ref: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::network_token_ref.eq(network_token_ref.to_owned())),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    /// Returns the payouts with `payout_id` across all merchants, in [`Self::list_order`].
+    ///
+    /// Payout ids are only unique per merchant, `limit` bounds the number of colliding payouts
+    /// returned.
+    pub async fn filter_by_payout_id_any_merchant(
+        conn: &PgPooledConn,
+        payout_id: &str,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::payout_id.eq(payout_id.to_owned()),
+            Some(limit),
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    pub async fn find_optional_by_merchant_id_connector_idempotency_key(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        connector_idempotency_key: &str,
+    ) -> StorageResult<Option<Self>> {
+        generics::generic_find_one_optional::<<Self as HasTable>::Table, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::connector_idempotency_key.eq(connector_idempotency_key.to_owned())),
+        )
+        .await
+    }
+
+    pub async fn filter_by_merchant_id_modified_since(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        since: PrimitiveDateTime,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::last_modified_at.ge(since)),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    /// Returns the payouts of the merchant last modified by `actor` at or after `since`, in
+    /// [`Self::list_order`].
+    ///
+    /// Served by the `(merchant_id, last_modified_by, last_modified_at)` index.
+    pub async fn filter_by_merchant_id_modified_by_since(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        actor: &str,
+        since: PrimitiveDateTime,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::last_modified_by.eq(actor.to_owned()))
+                .and(dsl::last_modified_at.ge(since)),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    /// Filters the merchant's payouts in any of `statuses` whose status last changed before
+    /// `unchanged_since`, in [`Self::list_order`]. A payout whose status never changed is matched
+    /// on its `created_at`.
+    pub async fn filter_by_merchant_id_statuses_unchanged_since(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        statuses: Vec<storage_enums::PayoutStatus>,
+        unchanged_since: PrimitiveDateTime,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::status.eq_any(statuses))
+                .and(
+                    dsl::status_changed_at
+                        .lt(unchanged_since)
+                        .or(dsl::status_changed_at
+                            .is_null()
+                            .and(dsl::created_at.lt(unchanged_since))),
+                ),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    /// Returns up to `limit` payouts of the merchant in any of `statuses`, matched with
+    /// `status = ANY(...)`, in [`Self::list_order`]. An empty `statuses` does not filter on the
+    /// status.
+    pub async fn filter_by_merchant_id_statuses(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        statuses: Vec<storage_enums::PayoutStatus>,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        let of_merchant = dsl::merchant_id.eq(merchant_id.to_owned());
+        if statuses.is_empty() {
+            generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+                conn,
+                of_merchant,
+                Some(limit),
+                None,
+                Some(Self::list_order()),
+            )
+            .await
+        } else {
+            generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+                conn,
+                of_merchant.and(dsl::status.eq_any(statuses)),
+                Some(limit),
+                None,
+                Some(Self::list_order()),
+            )
+            .await
+        }
+    }
+
+    pub async fn filter_by_merchant_id_source_payment_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        source_payment_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::source_payment_id.eq(source_payment_id.to_owned())),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    pub async fn filter_by_merchant_id_parent_payout_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        parent_payout_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::parent_payout_id.eq(parent_payout_id.to_owned())),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    /// Filters the merchant's payouts on their metadata containing `{key: value}`, served by
+    /// the GIN index on `metadata`.
+    pub async fn filter_by_merchant_id_metadata_key(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> StorageResult<Vec<Self>> {
+        let mut contained = serde_json::Map::new();
+        contained.insert(key.to_owned(), value.clone());
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::metadata.contains(serde_json::Value::Object(contained))),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    pub async fn filter_by_merchant_id_created_by(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        created_by: storage_enums::PayoutCreationSource,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::created_by.eq(created_by)),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    /// Returns the payouts of the merchant flagged as high-risk with a score of at least
+    /// `min_score`, in [`Self::list_order`].
+    pub async fn filter_high_risk_by_merchant_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        min_score: i16,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::is_high_risk.eq(true))
+                .and(dsl::risk_score.ge(min_score)),
+            None,
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    /// Returns the most recently created payout of the customer, in [`Self::list_order`], as the
+    /// only element of the result.
+    pub async fn find_latest_by_merchant_id_customer_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        customer_id: &str,
+    ) -> StorageResult<Vec<Self>> {
+        generics::generic_filter::<<Self as HasTable>::Table, _, _, _>(
+            conn,
+            dsl::merchant_id
+                .eq(merchant_id.to_owned())
+                .and(dsl::customer_id.eq(customer_id.to_owned())),
+            Some(1),
+            None,
+            Some(Self::list_order()),
+        )
+        .await
+    }
+
+    pub async fn get_distinct_destination_currencies_by_merchant_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+    ) -> StorageResult<Vec<storage_enums::Currency>> {
+        <Self as HasTable>::table()
+            .filter(dsl::merchant_id.eq(merchant_id.to_owned()))
+            .select(dsl::destination_currency)
+            .distinct()
+            .get_results_async::<storage_enums::Currency>(conn)
+            .await
+            .into_report()
+            .change_context(errors::DatabaseError::Others)
+            .attach_printable("Error filtering payouts by destination currency")
+    }
+
+    pub async fn get_payout_ids_by_merchant_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+    ) -> StorageResult<Vec<String>> {
+        <Self as HasTable>::table()
+            .filter(dsl::merchant_id.eq(merchant_id.to_owned()))
+            .select(dsl::payout_id)
+            .get_results_async::<String>(conn)
+            .await
+            .into_report()
+            .change_context(errors::DatabaseError::Others)
+            .attach_printable("Error listing payout ids of merchant")
+    }
+
+    pub async fn count_by_merchant_id_statuses(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        statuses: &[storage_enums::PayoutStatus],
+    ) -> StorageResult<i64> {
+        <Self as HasTable>::table()
+            .filter(
+                dsl::merchant_id
+                    .eq(merchant_id.to_owned())
+                    .and(dsl::status.eq_any(statuses.to_owned())),
+            )
+            .count()
+            .get_result_async::<i64>(conn)
+            .await
+            .into_report()
+            .change_context(errors::DatabaseError::Others)
+            .attach_printable("Error counting payouts of merchant by status")
+    }
+
+    pub async fn get_status_counts_by_merchant_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+    ) -> StorageResult<Vec<(storage_enums::PayoutStatus, i64)>> {
+        <Self as HasTable>::table()
+            .filter(dsl::merchant_id.eq(merchant_id.to_owned()))
+            .group_by(dsl::status)
+            .select((dsl::status, diesel::dsl::count_star()))
+            .get_results_async::<(storage_enums::PayoutStatus, i64)>(conn)
+            .await
+            .into_report()
+            .change_context(errors::DatabaseError::Others)
+            .attach_printable("Error counting payouts by status")
+    }
+
+    /// Returns `SUM(amount)` over the payouts of the merchant, computed as `numeric` so that it
+    /// cannot overflow, `None` if the merchant has no payouts.
+    pub async fn sum_amount_by_merchant_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+    ) -> StorageResult<Option<Decimal>> {
+        <Self as HasTable>::table()
+            .filter(dsl::merchant_id.eq(merchant_id.to_owned()))
+            .select(diesel::dsl::sql::<Nullable<Numeric>>(
+                "SUM(amount)::numeric",
+            ))
+            .get_result_async::<Option<Decimal>>(conn)
+            .await
+            .into_report()
+            .change_context(errors::DatabaseError::Others)
+            .attach_printable("Error summing payout amounts of merchant")
+    }
+
+    /// Returns `AVG(EXTRACT(EPOCH FROM fulfilled_at - created_at))` over the fulfilled payouts of
+    /// the merchant matching the given filters, `None` if none matches.
+    pub async fn average_processing_seconds_by_merchant_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        profile_id: Option<String>,
+        connectors: Option<Vec<String>>,
+        currencies: Option<Vec<storage_enums::Currency>>,
+        fulfilled_after: Option<PrimitiveDateTime>,
+        fulfilled_before: Option<PrimitiveDateTime>,
+    ) -> StorageResult<Option<f64>> {
+        let mut filter = <Self as HasTable>::table()
+            .select(diesel::dsl::sql::<Nullable<Double>>(
+                "AVG(EXTRACT(EPOCH FROM fulfilled_at - created_at))::float8",
+            ))
+            .filter(dsl::merchant_id.eq(merchant_id.to_owned()))
+            .filter(dsl::fulfilled_at.is_not_null())
+            .into_boxed();
+
+        if let Some(profile_id) = profile_id {
+            filter = filter.filter(dsl::profile_id.eq(profile_id));
+        }
+        if let Some(connectors) = connectors {
+            filter = filter.filter(dsl::connector.eq_any(connectors));
+        }
+        if let Some(currencies) = currencies {
+            filter = filter.filter(dsl::destination_currency.eq_any(currencies));
+        }
+        if let Some(fulfilled_after) = fulfilled_after {
+            filter = filter.filter(dsl::fulfilled_at.ge(fulfilled_after));
+        }
+        if let Some(fulfilled_before) = fulfilled_before {
+            filter = filter.filter(dsl::fulfilled_at.lt(fulfilled_before));
+        }
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&filter).to_string());
+
+        db_metrics::track_database_call::<<Self as HasTable>::Table, _, _>(
+            filter.get_result_async::<Option<f64>>(conn),
+            db_metrics::DatabaseOperation::Filter,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::Others)
+        .attach_printable("Error averaging payout processing time of merchant")
+    }
+
+    /// Sets `value` at `json_path` within the payout metadata using `jsonb_set`, without
+    /// reading the rest of the metadata.
+    ///
+    /// The leaf key is inserted or overwritten. Intermediate objects along the path are created
+    /// only when `create_missing` is set, otherwise the metadata is left untouched if any of them
+    /// is absent.
+    pub async fn update_metadata_path_by_merchant_id_payout_id(
+        conn: &PgPooledConn,
+        merchant_id: &str,
+        payout_id: &str,
+        json_path: &[String],
+        value: pii::SecretSerdeValue,
+        create_missing: bool,
+    ) -> StorageResult<Self> {
+        // `$1` and `$2` are taken by the merchant id and payout id respectively
+        let mut metadata = String::from("COALESCE(metadata, '{}'::jsonb)");
+        let mut intermediate_paths = Vec::new();
+        if create_missing {
+            for depth in 1..json_path.len() {
+                let position = intermediate_paths.len() + 3;
+                metadata = format!(
+                    "jsonb_set({metadata}, ${position}, \
+                     COALESCE(metadata #> ${position}, '{{}}'::jsonb), true)"
+                );
+                intermediate_paths.push(json_path.iter().take(depth).cloned().collect::<Vec<_>>());
+            }
+        }
+        let path_position = intermediate_paths.len() + 3;
+        let query = format!(
+            "UPDATE payouts SET metadata = jsonb_set({metadata}, ${path_position}, ${}, true), \
+             last_modified_at = ${}, sequence_number = sequence_number + 1 \
+             WHERE merchant_id = $1 AND payout_id = $2 RETURNING *",
+            path_position + 1,
+            path_position + 2,
+        );
+
+        let query = intermediate_paths
+            .into_iter()
+            .fold(
+                diesel::sql_query(query)
+                    .into_boxed::<Pg>()
+                    .bind::<Text, _>(merchant_id.to_owned())
+                    .bind::<Text, _>(payout_id.to_owned()),
+                |query, path| query.bind::<Array<Text>, _>(path),
+            )
+            .bind::<Array<Text>, _>(json_path.to_vec())
+            .bind::<Jsonb, _>(value)
+            .bind::<Timestamp, _>(common_utils::date_time::now());
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&query).to_string());
+
+        db_metrics::track_database_call::<<Self as HasTable>::Table, _, _>(
+            query.get_results_async::<Self>(conn),
+            db_metrics::DatabaseOperation::UpdateWithResults,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::Others)
+        .attach_printable("Error while updating payout metadata path")?
+        .pop()
+        .ok_or_else(|| {
+            report!(errors::DatabaseError::NotFound)
+                .attach_printable("Error while updating payout metadata path")
+        })
+    }
+
+    /// Returns up to `limit` scheduled payouts due at `now`, across merchants, earliest
+    /// `scheduled_at` first.
+    pub async fn filter_due_scheduled(
+        conn: &PgPooledConn,
+        now: PrimitiveDateTime,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        let query = Self::due_scheduled_query(now, limit);
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&query).to_string());
+
+        db_metrics::track_database_call::<<Self as HasTable>::Table, _, _>(
+            query.get_results_async::<Self>(conn),
+            db_metrics::DatabaseOperation::Filter,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::NotFound)
+        .attach_printable("Error while filtering due scheduled payouts")
+    }
+
+    /// The query of [`Self::filter_due_scheduled`], served by the `payouts_active_status_index`
+    /// partial index.
+    fn due_scheduled_query(
+        now: PrimitiveDateTime,
+        limit: i64,
+    ) -> crate::schema::payouts::BoxedQuery<'static, Pg> {
+        <Self as HasTable>::table()
+            .into_boxed()
+            .filter(
+                dsl::status
+                    .eq(Self::DUE_STATUS)
+                    .and(dsl::scheduled_at.le(now)),
+            )
+            .order((dsl::scheduled_at.asc(), dsl::payout_id.asc()))
+            .limit(limit)
+    }
+
+    /// Moves up to `limit` scheduled payouts due at `now` to `Pending`, across merchants,
+    /// earliest `scheduled_at` first, returning the activated payouts.
+    ///
+    /// The payouts are selected and updated in a single statement which skips the rows locked
+    /// by concurrent callers, so that a payout is activated by at most one of them.
+    pub async fn activate_due(
+        conn: &PgPooledConn,
+        now: PrimitiveDateTime,
+        limit: i64,
+    ) -> StorageResult<Vec<Self>> {
+        let query = diesel::sql_query(
+            "UPDATE payouts SET status = 'pending', last_modified_at = $3, \
+             sequence_number = sequence_number + 1 \
+             WHERE (merchant_id, payout_id) IN (\
+             SELECT merchant_id, payout_id FROM payouts \
+             WHERE status = 'scheduled' AND scheduled_at <= $1 \
+             ORDER BY scheduled_at, payout_id LIMIT $2 FOR UPDATE SKIP LOCKED) \
+             RETURNING *",
+        )
+        .bind::<Timestamp, _>(now)
+        .bind::<BigInt, _>(limit)
+        .bind::<Timestamp, _>(common_utils::date_time::now());
+        router_env::logger::debug!(query = %debug_query::<Pg, _>(&query).to_string());
+
+        db_metrics::track_database_call::<<Self as HasTable>::Table, _, _>(
+            query.get_results_async::<Self>(conn),
+            db_metrics::DatabaseOperation::UpdateWithResults,
+        )
+        .await
+        .into_report()
+        .change_context(errors::DatabaseError::Others)
+        .attach_printable("Error while activating due payouts")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use diesel::{
+        query_builder::{AstPass, Query, QueryFragment, QueryId},
+        Connection, PgConnection, QueryResult, RunQueryDsl,
+    };
+
+    use super::*;
+
+    const ACTIVE_INDEXES_MIGRATION: &str = include_str!(
+        "../../../../migrations/2024-04-17-081205_reshape_active_payouts_partial_indexes/up.sql"
+    );
+
+    /// Mostly terminal payouts with a few active ones, so that the planner has a reason to prefer
+    /// the partial indexes over the full ones.
+    const SEED_PAYOUTS: &str = "INSERT INTO payouts (payout_id, merchant_id, customer_id, \
+         address_id, payout_type, amount, destination_currency, source_currency, recurring, \
+         auto_fulfill, entity_type, profile_id, status, display_seq, last_modified_at, \
+         scheduled_at) \
+         SELECT 'po_plan_' || g, 'merchant_plan', 'cus_1', 'add_1', 'bank', 100, 'USD', 'USD', \
+         false, false, 'Individual', 'pro_1', \
+         CASE g % 100 WHEN 0 THEN 'pending'::\"PayoutStatus\" \
+         WHEN 1 THEN 'scheduled'::\"PayoutStatus\" ELSE 'success'::\"PayoutStatus\" END, \
+         g, now() - make_interval(secs => g), now() - make_interval(secs => g) \
+         FROM generate_series(1, 10000) g";
+
+    /// `EXPLAIN` of a query, loading one row per line of its plan.
+    struct Explain<Q>(Q);
+
+    impl<Q: QueryFragment<Pg>> QueryFragment<Pg> for Explain<Q> {
+        fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+            out.push_sql("EXPLAIN ");
+            self.0.walk_ast(out.reborrow())
+        }
+    }
+
+    impl<Q> QueryId for Explain<Q> {
+        type QueryId = ();
+
+        const HAS_STATIC_QUERY_ID: bool = false;
+    }
+
+    impl<Q> Query for Explain<Q> {
+        type SqlType = Text;
+    }
+
+    impl<Q> RunQueryDsl<PgConnection> for Explain<Q> {}
+
+    #[test]
+    fn test_active_statuses_match_partial_index_predicates() {
+        let predicates = ACTIVE_INDEXES_MIGRATION
+            .split("WHERE")
+            .skip(1)
+            .map(|predicate| predicate.split(';').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(predicates.len(), 2);
+        for predicate in predicates {
+            for status in Payouts::ACTIVE_STATUSES {
+                assert!(
+                    predicate.contains(&format!("'{status}'")),
+                    "{status} is missing from the index predicate"
+                );
+            }
+            assert_eq!(
+                predicate.matches('\'').count(),
+                Payouts::ACTIVE_STATUSES.len() * 2
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "needs a database migrated to the latest schema at `DATABASE_URL`"]
+    fn test_cross_merchant_finders_use_active_partial_indexes() {
+        let mut conn = PgConnection::establish(&std::env::var("DATABASE_URL").unwrap()).unwrap();
+        conn.begin_test_transaction().unwrap();
+        diesel::sql_query(SEED_PAYOUTS).execute(&mut conn).unwrap();
+        diesel::sql_query("ANALYZE payouts")
+            .execute(&mut conn)
+            .unwrap();
+
+        let now = common_utils::date_time::now();
+        let queries = [
+            (
+                Payouts::needing_retry_query(3, now, time::Duration::minutes(5), 10),
+                "payouts_active_status_last_modified_at_index",
+            ),
+            (
+                Payouts::due_scheduled_query(now, 10),
+                "payouts_active_status_index",
+            ),
+        ];
+        for (query, index) in queries {
+            let plan = Explain(query).load::<String>(&mut conn).unwrap().join("\n");
+            assert!(
+                plan.contains(&format!(" using {index} on payouts")),
+                "{index} is not used by the plan:\n{plan}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cross_merchant_finders_filter_on_active_statuses() {
+        assert!(Payouts::ACTIVE_STATUSES.contains(&Payouts::RETRY_STATUS));
+        assert!(Payouts::ACTIVE_STATUSES.contains(&Payouts::DUE_STATUS));
+        // `activate_due` is written in raw SQL, its status literal must follow `DUE_STATUS`
+        assert_eq!(Payouts::DUE_STATUS.to_string(), "scheduled");
+    }
+
+    #[test]
+    fn test_select_list_skips_only_skippable_columns() {
+        assert_eq!(Payouts::select_list(&[]), Payouts::FIELD_NAMES.join(", "));
+
+        let select_list = Payouts::select_list(&["connector_response", "payout_id"]);
+        assert!(select_list.contains("NULL::jsonb AS connector_response"));
+        assert!(select_list.starts_with("payout_id, "));
+        assert!(select_list.contains("routing_decision, "));
+        assert_eq!(select_list.matches("NULL").count(), 1);
+
+        for column in Payouts::SKIPPABLE_COLUMNS {
+            assert!(Payouts::FIELD_NAMES.contains(&column));
+        }
+    }
 }
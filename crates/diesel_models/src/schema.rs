@@ -951,6 +951,72 @@ diesel::table! {
         #[max_length = 32]
         merchant_connector_id -> Nullable<Varchar>,
         routing_info -> Nullable<Jsonb>,
+        #[max_length = 255]
+        unified_code -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::enums::diesel_exports::*;
+
+    payout_amount_adjustments (id) {
+        id -> Int4,
+        #[max_length = 64]
+        merchant_id -> Varchar,
+        #[max_length = 64]
+        payout_id -> Varchar,
+        old_amount -> Int8,
+        new_amount -> Int8,
+        #[max_length = 255]
+        reason -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::enums::diesel_exports::*;
+
+    payout_display_sequences (merchant_id) {
+        #[max_length = 64]
+        merchant_id -> Varchar,
+        last_value -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::enums::diesel_exports::*;
+
+    payout_webhook_deliveries (delivery_id) {
+        #[max_length = 64]
+        delivery_id -> Varchar,
+        #[max_length = 64]
+        merchant_id -> Varchar,
+        #[max_length = 64]
+        payout_id -> Varchar,
+        #[max_length = 2048]
+        target_url -> Varchar,
+        attempt_count -> Int4,
+        last_status_code -> Nullable<Int4>,
+        next_retry_at -> Nullable<Timestamp>,
+        delivered_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        last_modified_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::enums::diesel_exports::*;
+
+    payout_webhook_events (connector, event_id) {
+        #[max_length = 64]
+        connector -> Varchar,
+        #[max_length = 255]
+        event_id -> Varchar,
+        created_at -> Timestamp,
     }
 }
 
@@ -988,6 +1054,43 @@ diesel::table! {
         #[max_length = 64]
         profile_id -> Varchar,
         status -> PayoutStatus,
+        #[max_length = 64]
+        original_payout_id -> Nullable<Varchar>,
+        #[max_length = 255]
+        hold_reason -> Nullable<Varchar>,
+        #[max_length = 32]
+        created_by -> Varchar,
+        #[max_length = 128]
+        network_token_ref -> Nullable<Varchar>,
+        sequence_number -> Int8,
+        #[max_length = 64]
+        connector_idempotency_key -> Nullable<Varchar>,
+        scheduled_at -> Nullable<Timestamp>,
+        routing_decision -> Nullable<Jsonb>,
+        deleted_at -> Nullable<Timestamp>,
+        #[max_length = 64]
+        source_payment_id -> Nullable<Varchar>,
+        test_mode -> Bool,
+        next_retry_at -> Nullable<Timestamp>,
+        #[max_length = 64]
+        connector -> Nullable<Varchar>,
+        #[max_length = 128]
+        connector_payout_id -> Nullable<Varchar>,
+        connector_response -> Nullable<Jsonb>,
+        exchange_rate -> Nullable<Numeric>,
+        exchange_rate_timestamp -> Nullable<Timestamp>,
+        display_seq -> Int8,
+        risk_score -> Nullable<Int2>,
+        is_high_risk -> Bool,
+        customer_snapshot -> Nullable<Jsonb>,
+        #[max_length = 255]
+        cancellation_reason -> Nullable<Varchar>,
+        #[max_length = 64]
+        last_modified_by -> Nullable<Varchar>,
+        fulfilled_at -> Nullable<Timestamp>,
+        #[max_length = 64]
+        parent_payout_id -> Nullable<Varchar>,
+        status_changed_at -> Nullable<Timestamp>,
     }
 }
 
@@ -1209,6 +1312,10 @@ diesel::allow_tables_to_appear_in_same_query!(
     payment_link,
     payment_methods,
     payout_attempt,
+    payout_amount_adjustments,
+    payout_display_sequences,
+    payout_webhook_deliveries,
+    payout_webhook_events,
     payouts,
     process_tracker,
     refund,
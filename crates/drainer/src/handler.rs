@@ -1,4 +1,7 @@
-use std::sync::{atomic, Arc};
+use std::{
+    collections::HashMap,
+    sync::{atomic, Arc},
+};
 
 use tokio::{
     sync::{mpsc, oneshot},
@@ -6,7 +9,7 @@ use tokio::{
 };
 
 use crate::{
-    errors, instrument, logger, metrics, query::ExecuteQuery, tracing, utils, DrainerSettings,
+    errors, instrument, kv, logger, metrics, query::ExecuteQuery, tracing, utils, DrainerSettings,
     Store, StreamData,
 };
 
@@ -217,9 +220,12 @@ async fn drainer(
     let session_id = common_utils::generate_id_with_default_len("drainer_session");
 
     let mut last_processed_id = String::new();
+    // Payout writes up to `last_processed_id`, removed from the stream along with it
+    let mut drained_payout_ops = 0;
+    let mut payout_ops = 0;
 
     for (entry_id, entry) in entries.clone() {
-        let data = match StreamData::from_hashmap(entry) {
+        let data = match StreamData::from_hashmap(entry.clone()) {
             Ok(data) => data,
             Err(err) => {
                 logger::error!(operation = "deserialization", err=?err);
@@ -241,18 +247,43 @@ async fn drainer(
         tracing::Span::current().record("global_id", data.global_id);
         tracing::Span::current().record("session_id", &session_id);
 
+        let payout_ids = data
+            .typed_sql
+            .payout_ids()
+            .map(|(merchant_id, payout_id)| (merchant_id.to_owned(), payout_id.to_owned()));
+        if payout_ids.is_some() {
+            payout_ops += 1;
+        }
+
         match data.typed_sql.execute_query(&store, data.pushed_at).await {
             Ok(_) => {
                 last_processed_id = entry_id;
+                drained_payout_ops = payout_ops;
             }
             Err(err) => match err.current_context() {
                 // In case of Uniqueviolation we can't really do anything to fix it so just clear
                 // it from the stream
                 diesel_models::errors::DatabaseError::UniqueViolation => {
                     last_processed_id = entry_id;
+                    drained_payout_ops = payout_ops;
+                }
+                _ => {
+                    if dead_letter_if_retries_exhausted(
+                        &store,
+                        stream_name,
+                        &entry_id,
+                        payout_ids,
+                        entry,
+                    )
+                    .await
+                    {
+                        last_processed_id = entry_id;
+                        drained_payout_ops = payout_ops;
+                    } else {
+                        // break from the loop in case of an error in query
+                        break;
+                    }
                 }
-                // break from the loop in case of an error in query
-                _ => break,
             },
         }
     }
@@ -269,9 +300,62 @@ async fn drainer(
                 "Assertion Failed no. of entries read from the stream doesn't match no. of entries trimmed"
             );
         }
+        if drained_payout_ops > 0 {
+            if let Err(error) = store.count_drained_payout_ops(drained_payout_ops).await {
+                logger::error!(operation = "count_drained_payout_ops", err=?error);
+            }
+        }
     } else {
         logger::error!(read_entries = %read_count,?entries,"No streams were processed in this session");
     }
 
     Ok(())
 }
+
+/// Records the failure of the entry `entry_id`, and moves it to the dead-letter stream of its
+/// payout once it failed more than `max_entry_retries` times, so that it stops blocking the
+/// entries after it. Returns whether the entry was moved.
+async fn dead_letter_if_retries_exhausted(
+    store: &Store,
+    stream_name: &str,
+    entry_id: &str,
+    payout_ids: Option<(String, String)>,
+    entry: HashMap<String, String>,
+) -> bool {
+    // Only payout writes have a dead-letter stream
+    let Some((merchant_id, payout_id)) = payout_ids else {
+        return false;
+    };
+
+    let failed_attempts = match store.record_failed_attempt(stream_name, entry_id).await {
+        Ok(failed_attempts) => failed_attempts,
+        Err(error) => {
+            logger::error!(operation = "record_failed_attempt", err=?error);
+            return false;
+        }
+    };
+    if !kv::exceeds_retry_limit(failed_attempts, store.config.max_entry_retries) {
+        return false;
+    }
+
+    match store
+        .dead_letter_payout_entry(stream_name, entry_id, (&merchant_id, &payout_id), entry)
+        .await
+    {
+        Ok(()) => {
+            logger::warn!(
+                %merchant_id,
+                %payout_id,
+                %entry_id,
+                failed_attempts,
+                "Moved a payout write to its dead-letter stream"
+            );
+            metrics::ENTRIES_DEAD_LETTERED.add(&metrics::CONTEXT, 1, &[]);
+            true
+        }
+        Err(error) => {
+            logger::error!(operation = "dead_letter_payout_entry", err=?error);
+            false
+        }
+    }
+}
@@ -4,6 +4,7 @@ mod handler;
 mod health_check;
 pub mod logger;
 pub(crate) mod metrics;
+mod notification;
 mod query;
 pub mod services;
 pub mod settings;
@@ -15,6 +15,8 @@ counter_metric!(SHUTDOWN_SIGNAL_RECEIVED, DRAINER_METER);
 counter_metric!(SUCCESSFUL_SHUTDOWN, DRAINER_METER);
 counter_metric!(STREAM_EMPTY, DRAINER_METER);
 counter_metric!(STREAM_PARSE_FAIL, DRAINER_METER);
+counter_metric!(ENTRIES_DEAD_LETTERED, DRAINER_METER);
+counter_metric!(PAYOUT_NOTIFICATIONS_PUSHED, DRAINER_METER);
 counter_metric!(DRAINER_HEALTH, DRAINER_METER);
 
 histogram_metric!(QUERY_EXECUTION_TIME, DRAINER_METER); // Time in (ms) milliseconds
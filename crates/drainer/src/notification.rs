@@ -0,0 +1,228 @@
+use diesel_models::{enums::PayoutStatus, payouts::Payouts};
+use error_stack::IntoReport;
+use redis_interface as redis;
+
+use crate::{errors, kv, logger, metrics, Store};
+
+/// Time for which the notification of a payout reaching a terminal status is remembered as
+/// pushed, well beyond the time a stream entry may be redelivered for (in seconds)
+const NOTIFICATION_MARKER_TTL: i64 = 7 * 24 * 60 * 60;
+
+/// The notification of a payout having moved to a terminal status, pushed to
+/// [`kv::PAYOUT_NOTIFICATION_STREAM`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutNotification {
+    pub merchant_id: String,
+    pub payout_id: String,
+    pub status: PayoutStatus,
+}
+
+impl PayoutNotification {
+    /// The notification of `payout`, as updated from `previous_status`, or `None` unless the
+    /// update moved it to a terminal status.
+    pub fn for_update(previous_status: PayoutStatus, payout: &Payouts) -> Option<Self> {
+        moves_to_terminal_status(previous_status, payout.status).then(|| Self {
+            merchant_id: payout.merchant_id.clone(),
+            payout_id: payout.payout_id.clone(),
+            status: payout.status,
+        })
+    }
+
+    fn marker(&self) -> String {
+        kv::payout_notification_marker(&self.merchant_id, &self.payout_id, self.status)
+    }
+
+    fn to_field_value_pairs(&self) -> Vec<(&str, String)> {
+        vec![
+            ("merchant_id", self.merchant_id.clone()),
+            ("payout_id", self.payout_id.clone()),
+            ("status", self.status.to_string()),
+        ]
+    }
+}
+
+/// Whether an update from `previous_status` to `status` moves a payout to a terminal status.
+pub fn moves_to_terminal_status(previous_status: PayoutStatus, status: PayoutStatus) -> bool {
+    !previous_status.is_terminal_status() && status.is_terminal_status()
+}
+
+/// Where the notifications of payouts are pushed to, along with the markers of the
+/// notifications already pushed.
+#[async_trait::async_trait]
+pub trait NotificationQueue {
+    /// Sets `marker` unless it is already set, returning whether it was.
+    async fn claim_marker(&self, marker: &str) -> errors::DrainerResult<bool>;
+
+    async fn release_marker(&self, marker: &str) -> errors::DrainerResult<()>;
+
+    async fn push_notification(
+        &self,
+        notification: &PayoutNotification,
+    ) -> errors::DrainerResult<()>;
+}
+
+#[async_trait::async_trait]
+impl NotificationQueue for Store {
+    async fn claim_marker(&self, marker: &str) -> errors::DrainerResult<bool> {
+        self.redis_conn
+            .set_key_if_not_exists_with_expiry(marker, "1", Some(NOTIFICATION_MARKER_TTL))
+            .await
+            .map(|reply| matches!(reply, redis::SetnxReply::KeySet))
+            .map_err(errors::DrainerError::from)
+            .into_report()
+    }
+
+    async fn release_marker(&self, marker: &str) -> errors::DrainerResult<()> {
+        self.redis_conn
+            .delete_key(marker)
+            .await
+            .map(|_| ())
+            .map_err(errors::DrainerError::from)
+            .into_report()
+    }
+
+    async fn push_notification(
+        &self,
+        notification: &PayoutNotification,
+    ) -> errors::DrainerResult<()> {
+        self.redis_conn
+            .stream_append_entry(
+                kv::PAYOUT_NOTIFICATION_STREAM,
+                &redis::RedisEntryId::AutoGeneratedID,
+                notification.to_field_value_pairs(),
+            )
+            .await
+            .map_err(errors::DrainerError::from)
+            .into_report()
+    }
+}
+
+/// Pushes `notification` to the queue unless it was already pushed, as when the stream entry of
+/// the update is redelivered. Returns whether it was pushed.
+///
+/// The marker of the notification is claimed before pushing it and released if the push fails,
+/// so that the notification is pushed when the update is retried.
+pub async fn push_once<Q>(
+    queue: &Q,
+    notification: &PayoutNotification,
+) -> errors::DrainerResult<bool>
+where
+    Q: NotificationQueue + Sync + ?Sized,
+{
+    let marker = notification.marker();
+    if !queue.claim_marker(&marker).await? {
+        logger::debug!(
+            merchant_id = %notification.merchant_id,
+            payout_id = %notification.payout_id,
+            "Skipped the notification of a payout already notified"
+        );
+        return Ok(false);
+    }
+
+    if let Err(error) = queue.push_notification(notification).await {
+        if let Err(release_error) = queue.release_marker(&marker).await {
+            logger::error!(operation = "release_notification_marker", err=?release_error);
+        }
+        return Err(error);
+    }
+    metrics::PAYOUT_NOTIFICATIONS_PUSHED.add(&metrics::CONTEXT, 1, &[]);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::{collections::HashSet, sync::Mutex};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeQueue {
+        markers: Mutex<HashSet<String>>,
+        pushed: Mutex<Vec<PayoutNotification>>,
+        fail_pushes: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl NotificationQueue for FakeQueue {
+        async fn claim_marker(&self, marker: &str) -> errors::DrainerResult<bool> {
+            Ok(self.markers.lock().unwrap().insert(marker.to_string()))
+        }
+
+        async fn release_marker(&self, marker: &str) -> errors::DrainerResult<()> {
+            self.markers.lock().unwrap().remove(marker);
+            Ok(())
+        }
+
+        async fn push_notification(
+            &self,
+            notification: &PayoutNotification,
+        ) -> errors::DrainerResult<()> {
+            if self.fail_pushes {
+                return Err(errors::DrainerError::UnexpectedError(
+                    "push failed".to_string(),
+                ))
+                .into_report();
+            }
+            self.pushed.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    fn notification(status: PayoutStatus) -> PayoutNotification {
+        PayoutNotification {
+            merchant_id: "merchant_1".to_string(),
+            payout_id: "po_1".to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn only_updates_to_a_terminal_status_are_notified() {
+        assert!(moves_to_terminal_status(
+            PayoutStatus::Pending,
+            PayoutStatus::Success
+        ));
+        assert!(moves_to_terminal_status(
+            PayoutStatus::RequiresFulfillment,
+            PayoutStatus::Failed
+        ));
+        assert!(!moves_to_terminal_status(
+            PayoutStatus::RequiresCreation,
+            PayoutStatus::Pending
+        ));
+        // Already terminal before the update
+        assert!(!moves_to_terminal_status(
+            PayoutStatus::Success,
+            PayoutStatus::Success
+        ));
+    }
+
+    #[tokio::test]
+    async fn terminal_update_is_notified_once() {
+        let queue = FakeQueue::default();
+        let notification = notification(PayoutStatus::Success);
+
+        assert!(push_once(&queue, &notification).await.unwrap());
+        assert_eq!(*queue.pushed.lock().unwrap(), [notification.clone()]);
+
+        // The stream entry of the update is redelivered
+        assert!(!push_once(&queue, &notification).await.unwrap());
+        assert_eq!(queue.pushed.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_push_is_retried_with_the_update() {
+        let queue = FakeQueue {
+            fail_pushes: true,
+            ..Default::default()
+        };
+        let notification = notification(PayoutStatus::Failed);
+
+        assert!(push_once(&queue, &notification).await.is_err());
+        // The marker is released, so that the retried update pushes the notification
+        assert!(queue.markers.lock().unwrap().is_empty());
+        assert!(queue.pushed.lock().unwrap().is_empty());
+    }
+}
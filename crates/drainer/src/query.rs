@@ -2,8 +2,14 @@ use std::sync::Arc;
 
 use common_utils::errors::CustomResult;
 use diesel_models::errors::DatabaseError;
+use error_stack::ResultExt;
 
-use crate::{kv, logger, metrics, pg_connection, services::Store};
+use crate::{
+    kv, logger, metrics,
+    notification::{self, PayoutNotification},
+    pg_connection,
+    services::Store,
+};
 
 #[async_trait::async_trait]
 pub trait ExecuteQuery {
@@ -24,6 +30,7 @@ impl ExecuteQuery for kv::DBOperation {
         let conn = pg_connection(&store.master_pool).await;
         let operation = self.operation();
         let table = self.table();
+        let updated_payout_status = self.updated_payout_status();
 
         let tags: &[metrics::KeyValue] = &[
             metrics::KeyValue {
@@ -46,6 +53,19 @@ impl ExecuteQuery for kv::DBOperation {
             Ok(result) => {
                 logger::info!(operation = operation, table = table, ?result);
                 metrics::SUCCESSFUL_QUERY_EXECUTION.add(&metrics::CONTEXT, 1, tags);
+                if let (Some(previous_status), kv::DBResult::Payouts(payout)) =
+                    (updated_payout_status, &result)
+                {
+                    if let Some(notification) =
+                        PayoutNotification::for_update(previous_status, payout)
+                    {
+                        // Failing the entry has it retried, along with the notification
+                        notification::push_once(&**store, &notification)
+                            .await
+                            .change_context(DatabaseError::Others)
+                            .attach_printable("Failed to push the notification of a payout")?;
+                    }
+                }
                 Ok(())
             }
             Err(err) => {
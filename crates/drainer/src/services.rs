@@ -20,6 +20,7 @@ pub struct Store {
 pub struct StoreConfig {
     pub drainer_stream_name: String,
     pub drainer_num_partitions: u8,
+    pub max_entry_retries: u32,
 }
 
 impl Store {
@@ -36,6 +37,7 @@ impl Store {
             config: StoreConfig {
                 drainer_stream_name: config.drainer.stream_name.clone(),
                 drainer_num_partitions: config.drainer.num_partitions,
+                max_entry_retries: config.drainer.max_entry_retries,
             },
             request_id: None,
         }
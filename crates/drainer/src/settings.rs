@@ -95,6 +95,7 @@ pub struct DrainerSettings {
     pub max_read_count: u64,
     pub shutdown_interval: u32, // in milliseconds
     pub loop_interval: u32,     // in milliseconds
+    pub max_entry_retries: u32,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -137,6 +138,7 @@ impl Default for DrainerSettings {
             max_read_count: 100,
             shutdown_interval: 1000, // in milliseconds
             loop_interval: 100,      // in milliseconds
+            max_entry_retries: 5,
         }
     }
 }
@@ -4,11 +4,15 @@ use error_stack::IntoReport;
 use redis_interface as redis;
 use router_env::{logger, tracing};
 
-use crate::{errors, metrics, Store};
+use crate::{errors, kv, metrics, Store};
 
 pub type StreamEntries = Vec<(String, HashMap<String, String>)>;
 pub type StreamReadResult = HashMap<String, StreamEntries>;
 
+/// Time after which the count of failed attempts at applying a stream entry is forgotten
+/// (in seconds)
+const FAILED_ATTEMPTS_TTL: i64 = 24 * 60 * 60;
+
 impl Store {
     #[inline(always)]
     pub fn drainer_stream(&self, shard_key: &str) -> String {
@@ -26,6 +30,11 @@ impl Store {
         self.drainer_stream(format!("shard_{stream_index}").as_str())
     }
 
+    #[inline(always)]
+    fn get_failed_attempts_key(stream_name: &str, entry_id: &str) -> String {
+        format!("{stream_name}_{entry_id}_failed_attempts")
+    }
+
     #[router_env::instrument(skip_all)]
     pub async fn is_stream_available(&self, stream_index: u8) -> bool {
         let stream_key_flag = self.get_stream_key_flag(stream_index);
@@ -116,4 +125,68 @@ impl Store {
         // adding 1 because we are deleting the given id too
         Ok(trim_result? + 1)
     }
+
+    /// Counts `count` payout writes removed from the drainer streams, see
+    /// [`kv::PAYOUT_PENDING_OPS`].
+    pub async fn count_drained_payout_ops(&self, count: i64) -> errors::DrainerResult<()> {
+        self.redis_conn
+            .decrement_key_by(kv::PAYOUT_PENDING_OPS, count)
+            .await
+            .map(|_| ())
+            .map_err(errors::DrainerError::from)
+            .into_report()
+    }
+
+    /// Records a failed attempt at applying the entry `entry_id` of the stream, returning the
+    /// number of failed attempts so far.
+    pub async fn record_failed_attempt(
+        &self,
+        stream_name: &str,
+        entry_id: &str,
+    ) -> errors::DrainerResult<i64> {
+        let failed_attempts_key = Self::get_failed_attempts_key(stream_name, entry_id);
+        let failed_attempts = self
+            .redis_conn
+            .increment_key(&failed_attempts_key)
+            .await
+            .map_err(errors::DrainerError::from)
+            .into_report()?;
+        self.redis_conn
+            .set_expiry(&failed_attempts_key, FAILED_ATTEMPTS_TTL)
+            .await
+            .map_err(errors::DrainerError::from)
+            .into_report()?;
+        Ok(failed_attempts)
+    }
+
+    /// Copies the entry `entry_id` of the stream, writing a payout, to the dead-letter stream of
+    /// the payout. The entry is left in the stream, to be trimmed along with the processed ones.
+    pub async fn dead_letter_payout_entry(
+        &self,
+        stream_name: &str,
+        entry_id: &str,
+        (merchant_id, payout_id): (&str, &str),
+        entry: HashMap<String, String>,
+    ) -> errors::DrainerResult<()> {
+        self.redis_conn
+            .stream_append_entry(
+                &kv::payout_dead_letter_stream(merchant_id, payout_id),
+                &redis::RedisEntryId::AutoGeneratedID,
+                entry.into_iter().collect::<Vec<_>>(),
+            )
+            .await
+            .map_err(errors::DrainerError::from)
+            .into_report()?;
+        self.redis_conn
+            .sadd(&kv::payout_dead_letter_index(merchant_id), payout_id)
+            .await
+            .map_err(errors::DrainerError::from)
+            .into_report()?;
+
+        let failed_attempts_key = Self::get_failed_attempts_key(stream_name, entry_id);
+        if let Err(error) = self.redis_conn.delete_key(&failed_attempts_key).await {
+            logger::error!(operation = "delete_failed_attempts", err=?error);
+        }
+        Ok(())
+    }
 }
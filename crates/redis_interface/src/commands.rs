@@ -15,11 +15,15 @@ use common_utils::{
 };
 use error_stack::{IntoReport, ResultExt};
 use fred::{
-    interfaces::{HashesInterface, KeysInterface, SetsInterface, StreamsInterface},
+    interfaces::{
+        ClientLike, ClusterInterface, HashesInterface, KeysInterface, LuaInterface, SetsInterface,
+        StreamsInterface,
+    },
     prelude::RedisErrorKind,
     types::{
-        Expiration, FromRedis, MultipleIDs, MultipleKeys, MultipleOrderedPairs, MultipleStrings,
-        MultipleValues, RedisKey, RedisMap, RedisValue, Scanner, SetOptions, XCap, XReadResponse,
+        ClusterHash, CustomCommand, Expiration, FromRedis, MultipleIDs, MultipleKeys,
+        MultipleOrderedPairs, MultipleStrings, MultipleValues, RedisKey, RedisMap, RedisValue,
+        Scanner, SetOptions, XCap, XReadResponse,
     },
 };
 use futures::StreamExt;
@@ -27,9 +31,30 @@ use router_env::{instrument, logger, tracing};
 
 use crate::{
     errors,
-    types::{DelReply, HsetnxReply, MsetnxReply, RedisEntryId, SaddReply, SetnxReply},
+    types::{
+        ConditionalHsetReply, DelReply, HsetnxReply, MsetnxReply, RedisEntryId, SaddReply,
+        SetnxReply,
+    },
 };
 
+/// `KEYS[1]` is the hash, `ARGV` holds the checked field, its expected value, whether a missing
+/// field passes the check, the expiry of the hash in seconds, then the fields to set and their
+/// values. Replies `1` if the fields were set, `0` if the checked field held another value and
+/// `-1` if it does not exist.
+const SET_HASH_FIELDS_IF_FIELD_EQUALS_SCRIPT: &str = r#"
+local current = redis.call('HGET', KEYS[1], ARGV[1])
+if not current then
+    if ARGV[3] ~= '1' then
+        return -1
+    end
+elseif current ~= ARGV[2] then
+    return 0
+end
+redis.call('HSET', KEYS[1], unpack(ARGV, 5))
+redis.call('EXPIRE', KEYS[1], ARGV[4])
+return 1
+"#;
+
 impl super::RedisConnectionPool {
     #[instrument(level = "DEBUG", skip(self))]
     pub async fn set_key<V>(&self, key: &str, value: V) -> CustomResult<(), errors::RedisError>
@@ -194,6 +219,24 @@ impl super::RedisConnectionPool {
             .change_context(errors::RedisError::SetExFailed)
     }
 
+    /// Sets the key only if it does not exist, without an expiry.
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn set_key_if_not_exists<V>(
+        &self,
+        key: &str,
+        value: V,
+    ) -> CustomResult<SetnxReply, errors::RedisError>
+    where
+        V: TryInto<RedisValue> + Debug + Send + Sync,
+        V::Error: Into<fred::error::RedisError> + Send + Sync,
+    {
+        self.pool
+            .set(key, value, None, Some(SetOptions::NX), false)
+            .await
+            .into_report()
+            .change_context(errors::RedisError::SetFailed)
+    }
+
     #[instrument(level = "DEBUG", skip(self))]
     pub async fn set_key_if_not_exists_with_expiry<V>(
         &self,
@@ -220,6 +263,41 @@ impl super::RedisConnectionPool {
             .change_context(errors::RedisError::SetFailed)
     }
 
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn increment_key(&self, key: &str) -> CustomResult<i64, errors::RedisError> {
+        self.pool
+            .incr(key)
+            .await
+            .into_report()
+            .change_context(errors::RedisError::SetFailed)
+    }
+
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn increment_key_by(
+        &self,
+        key: &str,
+        count: i64,
+    ) -> CustomResult<i64, errors::RedisError> {
+        self.pool
+            .incr_by(key, count)
+            .await
+            .into_report()
+            .change_context(errors::RedisError::SetFailed)
+    }
+
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn decrement_key_by(
+        &self,
+        key: &str,
+        count: i64,
+    ) -> CustomResult<i64, errors::RedisError> {
+        self.pool
+            .decr_by(key, count)
+            .await
+            .into_report()
+            .change_context(errors::RedisError::SetFailed)
+    }
+
     #[instrument(level = "DEBUG", skip(self))]
     pub async fn set_expiry(
         &self,
@@ -246,6 +324,37 @@ impl super::RedisConnectionPool {
             .change_context(errors::RedisError::SetExpiryFailed)
     }
 
+    /// Refreshes the cluster slot map cached by the clients of the pool, after a `MOVED`
+    /// redirection.
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn sync_cluster_slots(&self) -> CustomResult<(), errors::RedisError> {
+        futures::future::try_join_all(
+            self.pool
+                .clients()
+                .iter()
+                .map(|client| client.sync_cluster()),
+        )
+        .await
+        .into_report()
+        .change_context(errors::RedisError::RedirectionFailed)
+        .map(|_| ())
+    }
+
+    /// Sends `ASKING`, so that the node a slot is being migrated to serves the next command
+    /// for a key of that slot, after an `ASK` redirection.
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn asking(&self) -> CustomResult<(), errors::RedisError> {
+        self.pool
+            .next()
+            .custom::<(), String>(
+                CustomCommand::new_static("ASKING", ClusterHash::Random, false),
+                Vec::new(),
+            )
+            .await
+            .into_report()
+            .change_context(errors::RedisError::RedirectionFailed)
+    }
+
     #[instrument(level = "DEBUG", skip(self))]
     pub async fn set_hash_fields<V>(
         &self,
@@ -271,6 +380,41 @@ impl super::RedisConnectionPool {
             .await
     }
 
+    /// Sets `values` in the hash at `key` and refreshes its expiry, only if its field
+    /// `check_field` holds `expected`, in a single atomic script so that no other write can slip
+    /// between the check and the write.
+    ///
+    /// If `check_field` does not exist, nothing is set unless `set_if_missing` is set.
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn set_hash_fields_if_field_equals(
+        &self,
+        key: &str,
+        check_field: &str,
+        expected: &str,
+        set_if_missing: bool,
+        values: &[(String, String)],
+        ttl: Option<i64>,
+    ) -> CustomResult<ConditionalHsetReply, errors::RedisError> {
+        let ttl = ttl.unwrap_or(self.config.default_hash_ttl.into());
+        let mut args = vec![
+            check_field.to_owned(),
+            expected.to_owned(),
+            if set_if_missing { "1" } else { "0" }.to_owned(),
+            ttl.to_string(),
+        ];
+        args.extend(
+            values
+                .iter()
+                .flat_map(|(field, value)| [field.clone(), value.clone()]),
+        );
+
+        self.pool
+            .eval(SET_HASH_FIELDS_IF_FIELD_EQUALS_SCRIPT, key, args)
+            .await
+            .into_report()
+            .change_context(errors::RedisError::ConditionalSetHashFailed)
+    }
+
     #[instrument(level = "DEBUG", skip(self))]
     pub async fn set_hash_field_if_not_exist<V>(
         &self,
@@ -299,6 +443,19 @@ impl super::RedisConnectionPool {
             .await
     }
 
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn delete_hash_field(
+        &self,
+        key: &str,
+        field: &str,
+    ) -> CustomResult<DelReply, errors::RedisError> {
+        self.pool
+            .hdel(key, field)
+            .await
+            .into_report()
+            .change_context(errors::RedisError::DeleteFailed)
+    }
+
     #[instrument(level = "DEBUG", skip(self))]
     pub async fn serialize_and_set_hash_field_if_not_exist<V>(
         &self,
@@ -445,6 +602,53 @@ impl super::RedisConnectionPool {
             .change_context(errors::RedisError::GetHashFieldFailed)
     }
 
+    /// Fetches every field of the hash at `key` along with its raw value, ordered by field. A
+    /// missing key reads as an empty hash.
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn get_all_hash_fields(
+        &self,
+        key: &str,
+    ) -> CustomResult<Vec<(String, Vec<u8>)>, errors::RedisError> {
+        let fields = self
+            .pool
+            .hgetall::<std::collections::HashMap<String, Vec<u8>>, _>(key)
+            .await
+            .into_report()
+            .change_context(errors::RedisError::GetHashFieldFailed)?;
+
+        let mut fields = fields.into_iter().collect::<Vec<_>>();
+        fields.sort_unstable_by(|(first, _), (second, _)| first.cmp(second));
+        Ok(fields)
+    }
+
+    /// Fetches the given `(key, field)` pairs in a single pipelined round-trip, returning the raw
+    /// value of each pair in order, `None` for pairs that do not exist.
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn get_multiple_hash_fields(
+        &self,
+        key_fields: &[(String, String)],
+    ) -> CustomResult<Vec<Option<Vec<u8>>>, errors::RedisError> {
+        let pipeline = self.pool.next().pipeline();
+        for (key, field) in key_fields {
+            pipeline
+                .hget::<(), _, _>(key.as_str(), field.as_str())
+                .await
+                .into_report()
+                .change_context(errors::RedisError::GetHashFieldFailed)?;
+        }
+
+        let values = pipeline
+            .all::<Vec<RedisValue>>()
+            .await
+            .into_report()
+            .change_context(errors::RedisError::GetHashFieldFailed)?;
+
+        Ok(values
+            .iter()
+            .map(|value| value.as_bytes().map(<[u8]>::to_vec))
+            .collect())
+    }
+
     #[instrument(level = "DEBUG", skip(self))]
     pub async fn get_hash_field_and_deserialize<V>(
         &self,
@@ -483,6 +687,28 @@ impl super::RedisConnectionPool {
             .change_context(errors::RedisError::SetAddMembersFailed)
     }
 
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn smembers(&self, key: &str) -> CustomResult<Vec<String>, errors::RedisError> {
+        self.pool
+            .smembers(key)
+            .await
+            .into_report()
+            .change_context(errors::RedisError::GetSetMembersFailed)
+    }
+
+    #[instrument(level = "DEBUG", skip(self))]
+    pub async fn srem<V>(&self, key: &str, members: V) -> CustomResult<(), errors::RedisError>
+    where
+        V: TryInto<MultipleValues> + Debug + Send,
+        V::Error: Into<fred::error::RedisError> + Send,
+    {
+        self.pool
+            .srem(key, members)
+            .await
+            .into_report()
+            .change_context(errors::RedisError::SetRemoveMembersFailed)
+    }
+
     #[instrument(level = "DEBUG", skip(self))]
     pub async fn stream_append_entry<F>(
         &self,
@@ -723,7 +949,9 @@ impl super::RedisConnectionPool {
 mod tests {
     #![allow(clippy::expect_used, clippy::unwrap_used)]
 
-    use crate::{errors::RedisError, RedisConnectionPool, RedisEntryId, RedisSettings};
+    use crate::{
+        errors::RedisError, ConditionalHsetReply, RedisConnectionPool, RedisEntryId, RedisSettings,
+    };
 
     #[tokio::test]
     async fn test_consumer_group_create() {
@@ -798,4 +1026,68 @@ mod tests {
 
         assert!(is_success);
     }
+
+    #[tokio::test]
+    async fn test_racing_conditional_hash_sets_apply_once() {
+        let replies = tokio::task::spawn_blocking(move || {
+            futures::executor::block_on(async {
+                // Arrange
+                let pool = RedisConnectionPool::new(&RedisSettings::default())
+                    .await
+                    .expect("failed to create redis connection pool");
+                let key = "test_conditional_hset";
+                pool.delete_key(key)
+                    .await
+                    .expect("failed to clear the hash");
+                pool.set_hash_fields(
+                    key,
+                    vec![("status", "pending"), ("connector", "adyen")],
+                    Some(60),
+                )
+                .await
+                .expect("failed to set the hash");
+
+                // Act
+                let succeed = [("status".to_string(), "success".to_string())];
+                let fail = [("status".to_string(), "failed".to_string())];
+                let (first, second) = futures::join!(
+                    pool.set_hash_fields_if_field_equals(
+                        key, "status", "pending", false, &succeed, None
+                    ),
+                    pool.set_hash_fields_if_field_equals(
+                        key, "status", "pending", false, &fail, None
+                    ),
+                );
+                let missing = pool
+                    .set_hash_fields_if_field_equals(
+                        "test_conditional_hset_missing",
+                        "status",
+                        "pending",
+                        false,
+                        &succeed,
+                        None,
+                    )
+                    .await;
+                let status = pool
+                    .get_hash_field::<String>(key, "status")
+                    .await
+                    .expect("failed to read the status");
+
+                (first.unwrap(), second.unwrap(), missing.unwrap(), status)
+            })
+        })
+        .await
+        .expect("Spawn block failure");
+
+        // Assert
+        let (first, second, missing, status) = replies;
+        let applied = [first, second]
+            .into_iter()
+            .filter(|reply| *reply == ConditionalHsetReply::FieldsSet)
+            .count();
+        assert_eq!(applied, 1);
+        assert!([first, second].contains(&ConditionalHsetReply::ConditionFailed));
+        assert!(status == "success" || status == "failed");
+        assert_eq!(missing, ConditionalHsetReply::FieldMissing);
+    }
 }
@@ -50,8 +50,14 @@ pub enum RedisError {
     SetHashFailed,
     #[error("Failed to set hash field in Redis")]
     SetHashFieldFailed,
+    #[error("Failed to conditionally set hash fields in Redis")]
+    ConditionalSetHashFailed,
     #[error("Failed to add members to set in Redis")]
     SetAddMembersFailed,
+    #[error("Failed to get members of set in Redis")]
+    GetSetMembersFailed,
+    #[error("Failed to remove members from set in Redis")]
+    SetRemoveMembersFailed,
     #[error("Failed to get hash field in Redis")]
     GetHashFieldFailed,
     #[error("The requested value was not found in Redis")]
@@ -68,4 +74,95 @@ pub enum RedisError {
     OnMessageError,
     #[error("Got an unknown result from redis")]
     UnknownResult,
+    #[error("Failed to follow Redis cluster redirection")]
+    RedirectionFailed,
+    #[error("The deadline of the Redis operation passed before it completed")]
+    DeadlineExceeded,
+    #[error("Redis is failing, the operation was short-circuited by the circuit breaker")]
+    CircuitOpen,
+}
+
+/// A Redis Cluster redirection a command was answered with, instead of its reply.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RedisRedirection {
+    /// The slot of the key is permanently served by `node`.
+    Moved { slot: u16, node: String },
+    /// The slot of the key is being migrated to `node`, which must be sent `ASKING` before
+    /// the command.
+    Ask { slot: u16, node: String },
+}
+
+impl RedisRedirection {
+    /// Parses a `MOVED <slot> <node>` or `ASK <slot> <node>` error.
+    pub fn parse(message: &str) -> Option<Self> {
+        let mut parts = message.split_whitespace();
+        let kind = parts.next()?;
+        let slot = parts.next()?.parse().ok()?;
+        let node = parts.next()?.to_string();
+        match kind {
+            "MOVED" => Some(Self::Moved { slot, node }),
+            "ASK" => Some(Self::Ask { slot, node }),
+            _ => None,
+        }
+    }
+
+    /// Returns the redirection the failed command reported by `report` was answered with, if
+    /// any.
+    pub fn from_report(report: &error_stack::Report<RedisError>) -> Option<Self> {
+        report.frames().find_map(|frame| {
+            frame
+                .downcast_ref::<fred::error::RedisError>()
+                .and_then(|error| Self::parse(error.details()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use error_stack::{IntoReport, ResultExt};
+
+    use super::*;
+
+    #[test]
+    fn redirections_are_parsed() {
+        assert_eq!(
+            RedisRedirection::parse("MOVED 3999 127.0.0.1:6381"),
+            Some(RedisRedirection::Moved {
+                slot: 3999,
+                node: "127.0.0.1:6381".to_string()
+            })
+        );
+        assert_eq!(
+            RedisRedirection::parse("ASK 3999 127.0.0.1:6381"),
+            Some(RedisRedirection::Ask {
+                slot: 3999,
+                node: "127.0.0.1:6381".to_string()
+            })
+        );
+        assert_eq!(
+            RedisRedirection::parse("ERR wrong number of arguments"),
+            None
+        );
+        assert_eq!(RedisRedirection::parse("MOVED"), None);
+    }
+
+    #[test]
+    fn redirection_is_found_in_report() {
+        let report = Err::<(), _>(fred::error::RedisError::new(
+            fred::error::RedisErrorKind::Cluster,
+            "MOVED 3999 127.0.0.1:6381",
+        ))
+        .into_report()
+        .change_context(RedisError::GetHashFieldFailed)
+        .unwrap_err();
+
+        assert!(matches!(
+            RedisRedirection::from_report(&report),
+            Some(RedisRedirection::Moved { slot: 3999, .. })
+        ));
+        assert_eq!(
+            RedisRedirection::from_report(&error_stack::report!(RedisError::NotFound)),
+            None
+        );
+    }
 }
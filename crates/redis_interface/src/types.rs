@@ -200,6 +200,31 @@ impl fred::types::FromRedis for HsetnxReply {
     }
 }
 
+/// The outcome of [`crate::RedisConnectionPool::set_hash_fields_if_field_equals`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConditionalHsetReply {
+    /// The checked field held the expected value, the fields were set
+    FieldsSet,
+    /// The checked field held another value, nothing was set
+    ConditionFailed,
+    /// The checked field does not exist, nothing was set
+    FieldMissing,
+}
+
+impl fred::types::FromRedis for ConditionalHsetReply {
+    fn from_value(value: fred::types::RedisValue) -> Result<Self, fred::error::RedisError> {
+        match value {
+            fred::types::RedisValue::Integer(1) => Ok(Self::FieldsSet),
+            fred::types::RedisValue::Integer(0) => Ok(Self::ConditionFailed),
+            fred::types::RedisValue::Integer(-1) => Ok(Self::FieldMissing),
+            _ => Err(fred::error::RedisError::new(
+                fred::error::RedisErrorKind::Unknown,
+                "Unexpected conditional HSET script reply",
+            )),
+        }
+    }
+}
+
 #[derive(Eq, PartialEq)]
 pub enum MsetnxReply {
     KeysSet,
@@ -120,10 +120,43 @@ impl Default for super::settings::DrainerSettings {
     }
 }
 
+#[cfg(feature = "payouts")]
+impl Default for super::settings::Payouts {
+    fn default() -> Self {
+        Self {
+            payout_eligibility: false,
+            balance_check: false,
+            created_at_skew_tolerance_in_secs:
+                storage_impl::payouts::DEFAULT_CREATED_AT_SKEW_TOLERANCE_IN_SECS,
+            allow_imports: false,
+            read_cache_capacity: None,
+            read_cache_ttl_in_secs:
+                storage_impl::payouts::read_cache::DEFAULT_PAYOUT_READ_CACHE_TTL_IN_SECS,
+            summary_cache_capacity: None,
+            summary_cache_ttl_in_secs:
+                storage_impl::payouts::summary_cache::DEFAULT_PAYOUT_SUMMARY_CACHE_TTL_IN_SECS,
+            redis_key_hash_secret: None,
+            kv_checksum_secret: None,
+            previous_kv_checksum_secrets: Vec::new(),
+            kv_batch_pipeline_size:
+                storage_impl::payouts::batch_read::DEFAULT_PAYOUT_BATCH_PIPELINE_SIZE,
+            kv_batch_max_concurrent_pipelines:
+                storage_impl::payouts::batch_read::DEFAULT_PAYOUT_BATCH_MAX_CONCURRENT_PIPELINES,
+        }
+    }
+}
+
 #[cfg(feature = "kv_store")]
 impl Default for super::settings::KvConfig {
     fn default() -> Self {
-        Self { ttl: 900 }
+        Self {
+            ttl: 900,
+            storage_scheme_overrides: HashMap::new(),
+            stream_push_timeout_in_ms: None,
+            primary_read_window_in_ms: None,
+            circuit_breaker_failure_threshold: None,
+            circuit_breaker_cooldown_in_ms: None,
+        }
     }
 }
 
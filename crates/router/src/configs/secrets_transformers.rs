@@ -306,6 +306,7 @@ pub(crate) async fn fetch_raw_secrets(
         server: conf.server,
         master_database,
         redis: conf.redis,
+        redis_replica: conf.redis_replica,
         log: conf.log,
         #[cfg(feature = "kv_store")]
         drainer: conf.drainer,
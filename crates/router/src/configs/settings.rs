@@ -65,6 +65,7 @@ pub struct Settings<S: SecretState> {
     #[cfg(feature = "olap")]
     pub replica_database: SecretStateContainer<Database, S>,
     pub redis: RedisSettings,
+    pub redis_replica: Option<RedisSettings>,
     pub log: Log,
     pub secrets: SecretStateContainer<Secrets, S>,
     pub locker: Locker,
@@ -135,6 +136,20 @@ pub struct Frm {
 #[derive(Debug, Deserialize, Clone)]
 pub struct KvConfig {
     pub ttl: u32,
+    /// Merchants forced to a storage scheme, regardless of the one configured on their account
+    #[serde(default)]
+    pub storage_scheme_overrides: HashMap<String, enums::MerchantStorageScheme>,
+    /// Time after which a push to the drainer stream is failed, no timeout is applied if unset
+    pub stream_push_timeout_in_ms: Option<u64>,
+    /// Time for which the reads of a KV key are served by the Redis primary after a write of the
+    /// key, reads always follow `redis_replica` if unset
+    pub primary_read_window_in_ms: Option<u64>,
+    /// Number of consecutive Redis failures after which KV operations are short-circuited, Redis
+    /// is always attempted if unset
+    pub circuit_breaker_failure_threshold: Option<u32>,
+    /// Time for which KV operations are short-circuited once the circuit breaker opened, before
+    /// probing Redis again
+    pub circuit_breaker_cooldown_in_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -685,6 +700,14 @@ impl Settings<SecuredSecret> {
             println!("{error}");
             ApplicationError::InvalidConfigurationValueError("Redis configuration".into())
         })?;
+        if let Some(redis_replica) = self.redis_replica.as_ref() {
+            redis_replica.validate().map_err(|error| {
+                println!("{error}");
+                ApplicationError::InvalidConfigurationValueError(
+                    "Redis replica configuration".into(),
+                )
+            })?;
+        }
         if self.log.file.enabled {
             if self.log.file.file_name.is_default_or_empty() {
                 return Err(ApplicationError::InvalidConfigurationValueError(
@@ -731,9 +754,33 @@ impl Settings<SecuredSecret> {
 }
 
 #[cfg(feature = "payouts")]
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
 pub struct Payouts {
     pub payout_eligibility: bool,
+    pub balance_check: bool,
+    pub created_at_skew_tolerance_in_secs: u32,
+    /// Allows historical payouts to be imported with their original timestamps
+    pub allow_imports: bool,
+    /// Number of recently read payouts kept in memory, the cache is disabled if unset
+    pub read_cache_capacity: Option<usize>,
+    pub read_cache_ttl_in_secs: u64,
+    /// Number of payouts read in batches from Redis kept in memory, the cache is disabled if
+    /// unset
+    pub summary_cache_capacity: Option<usize>,
+    pub summary_cache_ttl_in_secs: u64,
+    /// Secret with which payout ids are hashed in the Redis keys of payouts, payout ids are kept
+    /// as is if unset
+    pub redis_key_hash_secret: Option<Secret<String>>,
+    /// Secret with which the checksums of the payouts held in Redis are computed, their integrity
+    /// is not checked if unset
+    pub kv_checksum_secret: Option<Secret<String>>,
+    /// Secrets with which checksums were computed before the last rotation, still accepted
+    pub previous_kv_checksum_secrets: Vec<Secret<String>>,
+    /// Number of payouts read from Redis in a single pipeline when finding payouts in batches
+    pub kv_batch_pipeline_size: usize,
+    /// Number of pipelines of a batch read in flight at once
+    pub kv_batch_max_concurrent_pipelines: usize,
 }
 
 #[derive(Debug, Clone, Default)]
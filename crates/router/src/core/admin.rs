@@ -1386,7 +1386,7 @@ pub async fn kv_for_merchant(
         (true, MerchantStorageScheme::RedisKv) | (false, MerchantStorageScheme::PostgresOnly) => {
             Ok(merchant_account)
         }
-        (true, MerchantStorageScheme::PostgresOnly) => {
+        (true, MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite) => {
             db.update_merchant(
                 merchant_account,
                 storage::MerchantAccountUpdate::StorageSchemeUpdate {
@@ -1396,7 +1396,7 @@ pub async fn kv_for_merchant(
             )
             .await
         }
-        (false, MerchantStorageScheme::RedisKv) => {
+        (false, MerchantStorageScheme::RedisKv | MerchantStorageScheme::DualWrite) => {
             db.update_merchant(
                 merchant_account,
                 storage::MerchantAccountUpdate::StorageSchemeUpdate {
@@ -342,6 +342,8 @@ pub async fn payouts_update_core(
         metadata: req.metadata.clone().or(payouts.metadata.clone()),
         status: Some(status),
         profile_id: Some(payout_attempt.profile_id),
+        // Requests authenticated with an API key do not identify an operator
+        last_modified_by: None,
     };
 
     let db = &*state.store;
@@ -953,10 +955,11 @@ pub async fn check_payout_eligibility(
             }
         }
         Err(err) => {
-            let updated_payout_attempt = storage::PayoutAttemptUpdate::StatusUpdate {
+            let updated_payout_attempt = storage::PayoutAttemptUpdate::ErrorUpdate {
+                connector: connector_data.connector_name.to_string(),
                 connector_payout_id: String::default(),
                 status: storage_enums::PayoutStatus::Failed,
-                error_code: Some(err.code),
+                error_code: err.code,
                 error_message: Some(err.message),
                 is_eligible: Some(false),
             };
@@ -1052,10 +1055,11 @@ pub async fn create_payout(
             }
         }
         Err(err) => {
-            let updated_payout_attempt = storage::PayoutAttemptUpdate::StatusUpdate {
+            let updated_payout_attempt = storage::PayoutAttemptUpdate::ErrorUpdate {
+                connector: connector_data.connector_name.to_string(),
                 connector_payout_id: String::default(),
                 status: storage_enums::PayoutStatus::Failed,
-                error_code: Some(err.code),
+                error_code: err.code,
                 error_message: Some(err.message),
                 is_eligible: None,
             };
@@ -1137,10 +1141,11 @@ pub async fn cancel_payout(
                 .attach_printable("Error updating payout_attempt in db")?
         }
         Err(err) => {
-            let updated_payout_attempt = storage::PayoutAttemptUpdate::StatusUpdate {
+            let updated_payout_attempt = storage::PayoutAttemptUpdate::ErrorUpdate {
+                connector: connector_data.connector_name.to_string(),
                 connector_payout_id: String::default(),
                 status: storage_enums::PayoutStatus::Failed,
-                error_code: Some(err.code),
+                error_code: err.code,
                 error_message: Some(err.message),
                 is_eligible: None,
             };
@@ -1205,9 +1210,8 @@ pub async fn fulfill_payout(
             let status = payout_response_data
                 .status
                 .unwrap_or(payout_attempt.status.to_owned());
-            payout_data.payouts.status = status;
             if payout_data.payouts.recurring && payout_data.payouts.payout_method_id.is_none() {
-                helpers::save_payout_data_to_locker(
+                payout_data.payouts = helpers::save_payout_data_to_locker(
                     state,
                     payout_data,
                     &payout_data
@@ -1219,6 +1223,7 @@ pub async fn fulfill_payout(
                 )
                 .await?;
             }
+            payout_data.payouts.status = status;
             let updated_payout_attempt = storage::PayoutAttemptUpdate::StatusUpdate {
                 connector_payout_id: payout_attempt.connector_payout_id.to_owned(),
                 status,
@@ -1244,10 +1249,11 @@ pub async fn fulfill_payout(
             }
         }
         Err(err) => {
-            let updated_payout_attempt = storage::PayoutAttemptUpdate::StatusUpdate {
+            let updated_payout_attempt = storage::PayoutAttemptUpdate::ErrorUpdate {
+                connector: connector_data.connector_name.to_string(),
                 connector_payout_id: String::default(),
                 status: storage_enums::PayoutStatus::Failed,
-                error_code: Some(err.code),
+                error_code: err.code,
                 error_message: Some(err.message),
                 is_eligible: None,
             };
@@ -1425,6 +1431,9 @@ pub async fn payout_create_db_entries(
         payout_method_id,
         profile_id: profile_id.to_string(),
         attempt_count: 1,
+        customer_snapshot: customer
+            .as_ref()
+            .map(|customer| helpers::get_customer_snapshot(customer).to_secret_value()),
         ..Default::default()
     };
     let payouts = db
@@ -1504,8 +1513,8 @@ pub async fn make_payout_data(
 
     let payouts = db
         .find_payout_by_merchant_id_payout_id(
-            merchant_id,
-            &payout_id,
+            &storage::MerchantId::from(merchant_id),
+            &storage::PayoutId::from(&payout_id),
             merchant_account.storage_scheme,
         )
         .await
@@ -189,7 +189,7 @@ pub async fn save_payout_data_to_locker(
     payout_method_data: &api::PayoutMethodData,
     merchant_account: &domain::MerchantAccount,
     key_store: &domain::MerchantKeyStore,
-) -> RouterResult<()> {
+) -> RouterResult<storage::Payouts> {
     let payout_attempt = &payout_data.payout_attempt;
     let (locker_req, card_details, bank_details, wallet_details, payment_method_type) =
         match payout_method_data {
@@ -291,14 +291,15 @@ pub async fn save_payout_data_to_locker(
     let updated_payout = storage::PayoutsUpdate::PayoutMethodIdUpdate {
         payout_method_id: Some(stored_resp.card_reference.to_owned()),
     };
-    db.update_payout(
-        &payout_data.payouts,
-        updated_payout,
-        merchant_account.storage_scheme,
-    )
-    .await
-    .change_context(errors::ApiErrorResponse::InternalServerError)
-    .attach_printable("Error updating payouts in saved payout method")?;
+    let updated_payouts = db
+        .update_payout(
+            &payout_data.payouts,
+            updated_payout,
+            merchant_account.storage_scheme,
+        )
+        .await
+        .change_context(errors::ApiErrorResponse::InternalServerError)
+        .attach_printable("Error updating payouts in saved payout method")?;
 
     // fetch card info from db
     let card_isin = card_details
@@ -393,7 +394,7 @@ pub async fn save_payout_data_to_locker(
     )
     .await?;
 
-    Ok(())
+    Ok(updated_payouts)
 }
 
 pub async fn get_or_create_customer_details(
@@ -451,6 +452,28 @@ pub async fn get_or_create_customer_details(
     }
 }
 
+/// Captures the details of the customer of a payout as they are when the payout is created.
+pub fn get_customer_snapshot(customer: &domain::Customer) -> storage::PayoutCustomerSnapshot {
+    storage::PayoutCustomerSnapshot {
+        customer_id: customer.customer_id.to_owned(),
+        name: customer
+            .name
+            .as_ref()
+            .map(|name| name.get_inner().to_owned()),
+        email: customer
+            .email
+            .as_ref()
+            .map(|email| Secret::new(email.get_inner().peek().to_owned())),
+        phone: customer
+            .phone
+            .as_ref()
+            .map(|phone| phone.get_inner().to_owned()),
+        phone_country_code: customer.phone_country_code.to_owned(),
+        description: customer.description.to_owned(),
+        metadata: customer.metadata.to_owned(),
+    }
+}
+
 pub async fn decide_payout_connector(
     state: &AppState,
     merchant_account: &domain::MerchantAccount,
@@ -707,6 +730,9 @@ pub fn is_payout_terminal_state(status: api_enums::PayoutStatus) -> bool {
             | api_enums::PayoutStatus::RequiresCreation
             | api_enums::PayoutStatus::RequiresFulfillment
             | api_enums::PayoutStatus::RequiresPayoutMethodData
+            | api_enums::PayoutStatus::HeldForReview
+            | api_enums::PayoutStatus::Scheduled
+            | api_enums::PayoutStatus::RequiresConfirmation
     )
 }
 
@@ -362,7 +362,10 @@ impl GsmValidation for PayoutData {
             | common_enums::PayoutStatus::Ineligible
             | common_enums::PayoutStatus::RequiresCreation
             | common_enums::PayoutStatus::RequiresPayoutMethodData
-            | common_enums::PayoutStatus::RequiresFulfillment => false,
+            | common_enums::PayoutStatus::RequiresFulfillment
+            | common_enums::PayoutStatus::HeldForReview
+            | common_enums::PayoutStatus::Scheduled
+            | common_enums::PayoutStatus::RequiresConfirmation => false,
             common_enums::PayoutStatus::Failed => true,
         }
     }
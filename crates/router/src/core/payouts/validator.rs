@@ -22,7 +22,11 @@ pub async fn validate_uniqueness_of_payout_id_against_merchant_id(
     storage_scheme: storage::enums::MerchantStorageScheme,
 ) -> RouterResult<Option<storage::Payouts>> {
     let maybe_payouts = db
-        .find_optional_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+        .find_optional_payout_by_merchant_id_payout_id(
+            &storage::MerchantId::from(merchant_id),
+            &storage::PayoutId::from(payout_id),
+            storage_scheme,
+        )
         .await;
     match maybe_payouts {
         Err(err) => {
@@ -39,9 +39,12 @@ use data_models::payments::{
     payment_attempt::PaymentAttemptInterface, payment_intent::PaymentIntentInterface,
 };
 #[cfg(feature = "payouts")]
-use data_models::payouts::{payout_attempt::PayoutAttemptInterface, payouts::PayoutsInterface};
+use data_models::payouts::{
+    payout_attempt::PayoutAttemptInterface, payouts::PayoutsInterface,
+    webhook_delivery::PayoutWebhookDeliveryInterface,
+};
 #[cfg(not(feature = "payouts"))]
-use data_models::{PayoutAttemptInterface, PayoutsInterface};
+use data_models::{PayoutAttemptInterface, PayoutWebhookDeliveryInterface, PayoutsInterface};
 use diesel_models::{
     fraud_check::{FraudCheck, FraudCheckNew, FraudCheckUpdate},
     organization::{Organization, OrganizationNew, OrganizationUpdate},
@@ -98,6 +101,7 @@ pub trait StorageInterface:
     + scheduler::SchedulerInterface
     + PayoutAttemptInterface
     + PayoutsInterface
+    + PayoutWebhookDeliveryInterface
     + refund::RefundInterface
     + reverse_lookup::ReverseLookupInterface
     + cards_info::CardsInfoInterface
@@ -161,6 +165,8 @@ pub trait RequestIdStore {
     fn get_request_id(&self) -> Option<String> {
         None
     }
+    /// Bounds the storage operations made for the request by its deadline.
+    fn add_deadline(&mut self, _deadline: std::time::Instant) {}
 }
 
 impl RequestIdStore for MockDb {}
@@ -173,6 +179,10 @@ impl RequestIdStore for Store {
     fn get_request_id(&self) -> Option<String> {
         self.request_id.clone()
     }
+
+    fn add_deadline(&mut self, deadline: std::time::Instant) {
+        self.set_deadline(Some(deadline))
+    }
 }
 
 pub async fn get_and_deserialize_key<T>(
@@ -197,6 +207,10 @@ impl RequestIdStore for KafkaStore {
     fn add_request_id(&mut self, request_id: String) {
         self.diesel_store.add_request_id(request_id)
     }
+
+    fn add_deadline(&mut self, deadline: std::time::Instant) {
+        self.diesel_store.add_deadline(deadline)
+    }
 }
 
 #[async_trait::async_trait]
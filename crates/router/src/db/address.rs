@@ -342,7 +342,9 @@ mod storage {
                 .into_report()
             };
             let address = match storage_scheme {
-                MerchantStorageScheme::PostgresOnly => database_call().await,
+                MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                    database_call().await
+                }
                 MerchantStorageScheme::RedisKv => {
                     let key = format!("mid_{}_pid_{}", merchant_id, payment_id);
                     let field = format!("add_{}", address_id);
@@ -402,7 +404,7 @@ mod storage {
                 .await
                 .change_context(errors::StorageError::EncryptionError)?;
             match storage_scheme {
-                MerchantStorageScheme::PostgresOnly => {
+                MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                     address
                         .update(&conn, address_update.into())
                         .await
@@ -472,7 +474,7 @@ mod storage {
                 .change_context(errors::StorageError::EncryptionError)?;
             let merchant_id = address_new.merchant_id.clone();
             match storage_scheme {
-                MerchantStorageScheme::PostgresOnly => {
+                MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                     let conn = connection::pg_connection_write(self).await?;
                     address_new
                         .insert(&conn)
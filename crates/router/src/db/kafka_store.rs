@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use common_enums::enums::MerchantStorageScheme;
 use common_utils::errors::CustomResult;
@@ -6,9 +6,18 @@ use data_models::payments::{
     payment_attempt::PaymentAttemptInterface, payment_intent::PaymentIntentInterface,
 };
 #[cfg(feature = "payouts")]
-use data_models::payouts::{payout_attempt::PayoutAttemptInterface, payouts::PayoutsInterface};
+use data_models::payouts::{
+    amount_adjustment::PayoutAmountAdjustment,
+    ids::{CustomerId, MerchantId, PayoutId},
+    payout_attempt::PayoutAttemptInterface,
+    payouts::{PayoutPageCursor, PayoutProcessingTimeFilters, PayoutsInterface, PersistenceInfo},
+    webhook_delivery::{
+        PayoutWebhookDelivery, PayoutWebhookDeliveryInterface, PayoutWebhookDeliveryNew,
+        PayoutWebhookDeliveryUpdate,
+    },
+};
 #[cfg(not(feature = "payouts"))]
-use data_models::{PayoutAttemptInterface, PayoutsInterface};
+use data_models::{PayoutAttemptInterface, PayoutWebhookDeliveryInterface, PayoutsInterface};
 use diesel_models::{
     enums,
     enums::ProcessTrackerStatus,
@@ -1403,6 +1412,17 @@ impl PayoutAttemptInterface for KafkaStore {
             .insert_payout_attempt(payout_attempt, storage_scheme)
             .await
     }
+
+    async fn list_payout_attempts(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::PayoutAttempt>, errors::DataStorageError> {
+        self.diesel_store
+            .list_payout_attempts(merchant_id, payout_id, storage_scheme)
+            .await
+    }
 }
 
 #[cfg(not(feature = "payouts"))]
@@ -1413,8 +1433,8 @@ impl PayoutsInterface for KafkaStore {}
 impl PayoutsInterface for KafkaStore {
     async fn find_payout_by_merchant_id_payout_id(
         &self,
-        merchant_id: &str,
-        payout_id: &str,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
         storage_scheme: MerchantStorageScheme,
     ) -> CustomResult<storage::Payouts, errors::DataStorageError> {
         self.diesel_store
@@ -1445,14 +1465,598 @@ impl PayoutsInterface for KafkaStore {
 
     async fn find_optional_payout_by_merchant_id_payout_id(
         &self,
-        merchant_id: &str,
-        payout_id: &str,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
         storage_scheme: MerchantStorageScheme,
     ) -> CustomResult<Option<storage::Payouts>, errors::DataStorageError> {
         self.diesel_store
             .find_optional_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
             .await
     }
+
+    async fn clone_payout_as_new(
+        &self,
+        merchant_id: &MerchantId,
+        source_payout_id: &PayoutId,
+        new_payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<storage::Payouts, errors::DataStorageError> {
+        self.diesel_store
+            .clone_payout_as_new(merchant_id, source_payout_id, new_payout_id, storage_scheme)
+            .await
+    }
+
+    async fn update_payout_metadata_path(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        json_path: &[String],
+        value: common_utils::pii::SecretSerdeValue,
+        create_missing: bool,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<storage::Payouts, errors::DataStorageError> {
+        self.diesel_store
+            .update_payout_metadata_path(
+                merchant_id,
+                payout_id,
+                json_path,
+                value,
+                create_missing,
+                storage_scheme,
+            )
+            .await
+    }
+
+    async fn check_payout_balance(
+        &self,
+        merchant_id: &MerchantId,
+        amount: i64,
+        currency: common_enums::Currency,
+    ) -> CustomResult<(), errors::DataStorageError> {
+        self.diesel_store
+            .check_payout_balance(merchant_id, amount, currency)
+            .await
+    }
+
+    async fn distinct_payout_currencies(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<common_enums::Currency>, errors::DataStorageError> {
+        self.diesel_store
+            .distinct_payout_currencies(merchant_id, storage_scheme)
+            .await
+    }
+
+    async fn list_payouts_by_source(
+        &self,
+        merchant_id: &MerchantId,
+        created_by: common_enums::PayoutCreationSource,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .list_payouts_by_source(merchant_id, created_by, storage_scheme)
+            .await
+    }
+
+    async fn list_payouts_by_merchant_id_statuses(
+        &self,
+        merchant_id: &MerchantId,
+        statuses: Vec<common_enums::PayoutStatus>,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .list_payouts_by_merchant_id_statuses(merchant_id, statuses, limit, storage_scheme)
+            .await
+    }
+
+    async fn list_high_risk_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        min_score: i16,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .list_high_risk_payouts(merchant_id, min_score, storage_scheme)
+            .await
+    }
+
+    async fn find_latest_payout_by_customer_id(
+        &self,
+        merchant_id: &MerchantId,
+        customer_id: &CustomerId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Option<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .find_latest_payout_by_customer_id(merchant_id, customer_id, storage_scheme)
+            .await
+    }
+
+    async fn find_optional_payouts_batch(
+        &self,
+        merchant_id: &MerchantId,
+        payout_ids: &[PayoutId],
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<HashMap<PayoutId, Option<storage::Payouts>>, errors::DataStorageError> {
+        self.diesel_store
+            .find_optional_payouts_batch(merchant_id, payout_ids, storage_scheme)
+            .await
+    }
+
+    async fn find_payouts_by_network_token_ref(
+        &self,
+        merchant_id: &MerchantId,
+        network_token_ref: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .find_payouts_by_network_token_ref(merchant_id, network_token_ref, storage_scheme)
+            .await
+    }
+
+    async fn find_payouts_by_source_payment_id(
+        &self,
+        merchant_id: &MerchantId,
+        source_payment_id: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .find_payouts_by_source_payment_id(merchant_id, source_payment_id, storage_scheme)
+            .await
+    }
+
+    async fn list_amount_adjustments(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<PayoutAmountAdjustment>, errors::DataStorageError> {
+        self.diesel_store
+            .list_amount_adjustments(merchant_id, payout_id, storage_scheme)
+            .await
+    }
+
+    async fn find_payouts_by_metadata_key(
+        &self,
+        merchant_id: &MerchantId,
+        key: &str,
+        value: &serde_json::Value,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .find_payouts_by_metadata_key(merchant_id, key, value, storage_scheme)
+            .await
+    }
+
+    async fn claim_payout_for_retry(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        max_attempts: i16,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Option<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .claim_payout_for_retry(merchant_id, payout_id, max_attempts, storage_scheme)
+            .await
+    }
+
+    async fn find_payouts_needing_retry(
+        &self,
+        now: PrimitiveDateTime,
+        max_attempts: i16,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .find_payouts_needing_retry(now, max_attempts, limit, storage_scheme)
+            .await
+    }
+
+    async fn list_payouts_by_status_before(
+        &self,
+        status: common_enums::PayoutStatus,
+        before: PrimitiveDateTime,
+        after_cursor: Option<&PayoutPageCursor>,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .list_payouts_by_status_before(status, before, after_cursor, limit, storage_scheme)
+            .await
+    }
+
+    async fn count_payouts_by_status(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<HashMap<common_enums::PayoutStatus, i64>, errors::DataStorageError> {
+        self.diesel_store
+            .count_payouts_by_status(merchant_id, storage_scheme)
+            .await
+    }
+
+    async fn sum_payout_amounts_by_merchant_id(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<i128, errors::DataStorageError> {
+        self.diesel_store
+            .sum_payout_amounts_by_merchant_id(merchant_id, storage_scheme)
+            .await
+    }
+
+    async fn average_payout_processing_seconds(
+        &self,
+        merchant_id: &MerchantId,
+        filters: &PayoutProcessingTimeFilters,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Option<f64>, errors::DataStorageError> {
+        self.diesel_store
+            .average_payout_processing_seconds(merchant_id, filters, storage_scheme)
+            .await
+    }
+
+    async fn find_payout_by_connector_idempotency_key(
+        &self,
+        merchant_id: &MerchantId,
+        connector_idempotency_key: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Option<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .find_payout_by_connector_idempotency_key(
+                merchant_id,
+                connector_idempotency_key,
+                storage_scheme,
+            )
+            .await
+    }
+
+    async fn find_payout_by_display_seq(
+        &self,
+        merchant_id: &MerchantId,
+        display_seq: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<storage::Payouts, errors::DataStorageError> {
+        self.diesel_store
+            .find_payout_by_display_seq(merchant_id, display_seq, storage_scheme)
+            .await
+    }
+
+    async fn diff_payouts_since(
+        &self,
+        merchant_id: &MerchantId,
+        since: PrimitiveDateTime,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .diff_payouts_since(merchant_id, since, storage_scheme)
+            .await
+    }
+
+    async fn list_payouts_modified_by(
+        &self,
+        merchant_id: &MerchantId,
+        actor: &str,
+        since: PrimitiveDateTime,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .list_payouts_modified_by(merchant_id, actor, since, storage_scheme)
+            .await
+    }
+
+    async fn find_stale_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        no_change_for: time::Duration,
+        statuses: Vec<common_enums::PayoutStatus>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .find_stale_payouts(merchant_id, no_change_for, statuses, storage_scheme)
+            .await
+    }
+
+    async fn find_payout_with_attempts(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<(storage::Payouts, Vec<storage::PayoutAttempt>), errors::DataStorageError>
+    {
+        self.diesel_store
+            .find_payout_with_attempts(merchant_id, payout_id, storage_scheme)
+            .await
+    }
+
+    async fn recompute_payout_attempt_count(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<i16, errors::DataStorageError> {
+        self.diesel_store
+            .recompute_payout_attempt_count(merchant_id, payout_id, storage_scheme)
+            .await
+    }
+
+    async fn activate_due_payouts(
+        &self,
+        now: PrimitiveDateTime,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<PayoutId>, errors::DataStorageError> {
+        self.diesel_store
+            .activate_due_payouts(now, limit, storage_scheme)
+            .await
+    }
+
+    async fn confirm_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<storage::Payouts, errors::DataStorageError> {
+        self.diesel_store
+            .confirm_payout(merchant_id, payout_id, storage_scheme)
+            .await
+    }
+
+    async fn reroute_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        new_connector: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<storage::Payouts, errors::DataStorageError> {
+        self.diesel_store
+            .reroute_payout(merchant_id, payout_id, new_connector, storage_scheme)
+            .await
+    }
+
+    async fn update_payout_if_status(
+        &self,
+        this: &storage::Payouts,
+        payout_update: storage::PayoutsUpdate,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Option<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .update_payout_if_status(this, payout_update, storage_scheme)
+            .await
+    }
+
+    async fn insert_payout_with_persistence_info(
+        &self,
+        payout: storage::PayoutsNew,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<(storage::Payouts, PersistenceInfo), errors::DataStorageError> {
+        self.diesel_store
+            .insert_payout_with_persistence_info(payout, storage_scheme)
+            .await
+    }
+
+    async fn soft_delete_all_payouts_for_merchant(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<usize, errors::DataStorageError> {
+        self.diesel_store
+            .soft_delete_all_payouts_for_merchant(merchant_id, storage_scheme)
+            .await
+    }
+
+    async fn delete_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<bool, errors::DataStorageError> {
+        self.diesel_store
+            .delete_payout(merchant_id, payout_id, storage_scheme)
+            .await
+    }
+
+    async fn insert_split_payouts(
+        &self,
+        parent: storage::PayoutsNew,
+        splits: Vec<storage::PayoutsNew>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<(storage::Payouts, Vec<storage::Payouts>), errors::DataStorageError> {
+        self.diesel_store
+            .insert_split_payouts(parent, splits, storage_scheme)
+            .await
+    }
+
+    async fn list_child_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        parent_payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .list_child_payouts(merchant_id, parent_payout_id, storage_scheme)
+            .await
+    }
+
+    async fn backfill_payout_profile_ids(
+        &self,
+        merchant_id: &MerchantId,
+        default_profile_id: &str,
+        batch_size: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<usize, errors::DataStorageError> {
+        self.diesel_store
+            .backfill_payout_profile_ids(
+                merchant_id,
+                default_profile_id,
+                batch_size,
+                storage_scheme,
+            )
+            .await
+    }
+
+    async fn import_payout(
+        &self,
+        payout: storage::PayoutsNew,
+        preserve_timestamps: bool,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<storage::Payouts, errors::DataStorageError> {
+        self.diesel_store
+            .import_payout(payout, preserve_timestamps, storage_scheme)
+            .await
+    }
+
+    async fn update_payouts_status_batch(
+        &self,
+        merchant_id: &MerchantId,
+        transitions: Vec<(PayoutId, common_enums::PayoutStatus)>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<
+        Vec<(PayoutId, CustomResult<(), errors::DataStorageError>)>,
+        errors::DataStorageError,
+    > {
+        self.diesel_store
+            .update_payouts_status_batch(merchant_id, transitions, storage_scheme)
+            .await
+    }
+
+    async fn get_payout_status(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<common_enums::PayoutStatus, errors::DataStorageError> {
+        self.diesel_store
+            .get_payout_status(merchant_id, payout_id, storage_scheme)
+            .await
+    }
+
+    async fn record_webhook_event_once(
+        &self,
+        connector: &str,
+        event_id: &str,
+    ) -> CustomResult<bool, errors::DataStorageError> {
+        self.diesel_store
+            .record_webhook_event_once(connector, event_id)
+            .await
+    }
+
+    async fn forget_webhook_event(
+        &self,
+        connector: &str,
+        event_id: &str,
+    ) -> CustomResult<(), errors::DataStorageError> {
+        self.diesel_store
+            .forget_webhook_event(connector, event_id)
+            .await
+    }
+
+    async fn get_payout_daily_count(
+        &self,
+        merchant_id: &MerchantId,
+        date: time::Date,
+    ) -> CustomResult<i64, errors::DataStorageError> {
+        self.diesel_store
+            .get_payout_daily_count(merchant_id, date)
+            .await
+    }
+
+    async fn cancel_payout_with_reversal(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        reason: String,
+        reversal: Option<storage::PayoutAttemptNew>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<(storage::Payouts, Option<storage::PayoutAttempt>), errors::DataStorageError>
+    {
+        self.diesel_store
+            .cancel_payout_with_reversal(merchant_id, payout_id, reason, reversal, storage_scheme)
+            .await
+    }
+
+    async fn find_payout_with_load_options(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        load_options: storage::PayoutLoadOptions,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<storage::Payouts, errors::DataStorageError> {
+        self.diesel_store
+            .find_payout_with_load_options(merchant_id, payout_id, load_options, storage_scheme)
+            .await
+    }
+
+    async fn find_payouts_with_inconsistent_timestamps(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage::Payouts>, errors::DataStorageError> {
+        self.diesel_store
+            .find_payouts_with_inconsistent_timestamps(merchant_id, storage_scheme)
+            .await
+    }
+
+    async fn repair_payout_timestamps(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<usize, errors::DataStorageError> {
+        self.diesel_store
+            .repair_payout_timestamps(merchant_id, storage_scheme)
+            .await
+    }
+}
+
+#[cfg(not(feature = "payouts"))]
+impl PayoutWebhookDeliveryInterface for KafkaStore {}
+
+#[cfg(feature = "payouts")]
+#[async_trait::async_trait]
+impl PayoutWebhookDeliveryInterface for KafkaStore {
+    async fn insert_webhook_delivery(
+        &self,
+        delivery: PayoutWebhookDeliveryNew,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<PayoutWebhookDelivery, errors::DataStorageError> {
+        self.diesel_store
+            .insert_webhook_delivery(delivery, storage_scheme)
+            .await
+    }
+
+    async fn update_webhook_delivery(
+        &self,
+        this: &PayoutWebhookDelivery,
+        delivery_update: PayoutWebhookDeliveryUpdate,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<PayoutWebhookDelivery, errors::DataStorageError> {
+        self.diesel_store
+            .update_webhook_delivery(this, delivery_update, storage_scheme)
+            .await
+    }
+
+    async fn list_webhook_deliveries(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<PayoutWebhookDelivery>, errors::DataStorageError> {
+        self.diesel_store
+            .list_webhook_deliveries(merchant_id, payout_id, storage_scheme)
+            .await
+    }
+
+    async fn find_pending_webhook_deliveries(
+        &self,
+        older_than: PrimitiveDateTime,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> CustomResult<Vec<PayoutWebhookDelivery>, errors::DataStorageError> {
+        self.diesel_store
+            .find_pending_webhook_deliveries(older_than, limit, storage_scheme)
+            .await
+    }
 }
 
 #[async_trait::async_trait]
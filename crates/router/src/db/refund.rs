@@ -314,7 +314,8 @@ mod storage {
                 .into_report()
             };
             match storage_scheme {
-                enums::MerchantStorageScheme::PostgresOnly => database_call().await,
+                enums::MerchantStorageScheme::PostgresOnly
+                | enums::MerchantStorageScheme::DualWrite => database_call().await,
                 enums::MerchantStorageScheme::RedisKv => {
                     let lookup_id = format!("ref_inter_ref_{merchant_id}_{internal_reference_id}");
                     let lookup = fallback_reverse_lookup_not_found!(
@@ -348,7 +349,8 @@ mod storage {
             storage_scheme: enums::MerchantStorageScheme,
         ) -> CustomResult<storage_types::Refund, errors::StorageError> {
             match storage_scheme {
-                enums::MerchantStorageScheme::PostgresOnly => {
+                enums::MerchantStorageScheme::PostgresOnly
+                | enums::MerchantStorageScheme::DualWrite => {
                     let conn = connection::pg_connection_write(self).await?;
                     new.insert(&conn).await.map_err(Into::into).into_report()
                 }
@@ -485,7 +487,8 @@ mod storage {
                 .into_report()
             };
             match storage_scheme {
-                enums::MerchantStorageScheme::PostgresOnly => database_call().await,
+                enums::MerchantStorageScheme::PostgresOnly
+                | enums::MerchantStorageScheme::DualWrite => database_call().await,
                 enums::MerchantStorageScheme::RedisKv => {
                     let lookup_id =
                         format!("pa_conn_trans_{merchant_id}_{connector_transaction_id}");
@@ -524,7 +527,8 @@ mod storage {
             storage_scheme: enums::MerchantStorageScheme,
         ) -> CustomResult<storage_types::Refund, errors::StorageError> {
             match storage_scheme {
-                enums::MerchantStorageScheme::PostgresOnly => {
+                enums::MerchantStorageScheme::PostgresOnly
+                | enums::MerchantStorageScheme::DualWrite => {
                     let conn = connection::pg_connection_write(self).await?;
                     this.update(&conn, refund)
                         .await
@@ -582,7 +586,8 @@ mod storage {
                     .into_report()
             };
             match storage_scheme {
-                enums::MerchantStorageScheme::PostgresOnly => database_call().await,
+                enums::MerchantStorageScheme::PostgresOnly
+                | enums::MerchantStorageScheme::DualWrite => database_call().await,
                 enums::MerchantStorageScheme::RedisKv => {
                     let lookup_id = format!("ref_ref_id_{merchant_id}_{refund_id}");
                     let lookup = fallback_reverse_lookup_not_found!(
@@ -630,7 +635,8 @@ mod storage {
                 .into_report()
             };
             match storage_scheme {
-                enums::MerchantStorageScheme::PostgresOnly => database_call().await,
+                enums::MerchantStorageScheme::PostgresOnly
+                | enums::MerchantStorageScheme::DualWrite => database_call().await,
                 enums::MerchantStorageScheme::RedisKv => {
                     let lookup_id =
                         format!("ref_connector_{merchant_id}_{connector_refund_id}_{connector}");
@@ -677,7 +683,8 @@ mod storage {
                 .into_report()
             };
             match storage_scheme {
-                enums::MerchantStorageScheme::PostgresOnly => database_call().await,
+                enums::MerchantStorageScheme::PostgresOnly
+                | enums::MerchantStorageScheme::DualWrite => database_call().await,
                 enums::MerchantStorageScheme::RedisKv => {
                     let key = format!("mid_{merchant_id}_pid_{payment_id}");
                     Box::pin(db_utils::try_redis_get_else_try_database_get(
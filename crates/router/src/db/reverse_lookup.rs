@@ -91,7 +91,8 @@ mod storage {
             storage_scheme: enums::MerchantStorageScheme,
         ) -> CustomResult<ReverseLookup, errors::StorageError> {
             match storage_scheme {
-                enums::MerchantStorageScheme::PostgresOnly => {
+                enums::MerchantStorageScheme::PostgresOnly
+                | enums::MerchantStorageScheme::DualWrite => {
                     let conn = connection::pg_connection_write(self).await?;
                     new.insert(&conn).await.map_err(Into::into).into_report()
                 }
@@ -145,7 +146,8 @@ mod storage {
             };
 
             match storage_scheme {
-                enums::MerchantStorageScheme::PostgresOnly => database_call().await,
+                enums::MerchantStorageScheme::PostgresOnly
+                | enums::MerchantStorageScheme::DualWrite => database_call().await,
                 enums::MerchantStorageScheme::RedisKv => {
                     let redis_fut = async {
                         kv_wrapper(
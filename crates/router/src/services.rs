@@ -15,7 +15,7 @@ pub mod email;
 
 use data_models::errors::StorageResult;
 use error_stack::{IntoReport, ResultExt};
-use masking::{ExposeInterface, StrongSecret};
+use masking::{ExposeInterface, PeekInterface, StrongSecret};
 #[cfg(feature = "kv_store")]
 use storage_impl::KVRouterStore;
 use storage_impl::RouterStore;
@@ -73,14 +73,107 @@ pub async fn get_store(
         .await?
     };
 
+    #[cfg(feature = "payouts")]
+    let store = store
+        .with_payout_balance_check(config.payouts.balance_check)
+        .with_payout_imports(config.payouts.allow_imports)
+        .with_payout_created_at_skew_tolerance(config.payouts.created_at_skew_tolerance_in_secs);
+
     #[cfg(feature = "kv_store")]
     let store = KVRouterStore::from_store(
         store,
         config.drainer.stream_name.clone(),
         config.drainer.num_partitions,
         config.kv_config.ttl,
+    )
+    .with_storage_scheme_overrides(config.kv_config.storage_scheme_overrides.clone());
+
+    #[cfg(feature = "kv_store")]
+    let store = match config.kv_config.stream_push_timeout_in_ms {
+        Some(timeout_in_ms) => {
+            store.with_drainer_stream_push_timeout(std::time::Duration::from_millis(timeout_in_ms))
+        }
+        None => store,
+    };
+
+    #[cfg(feature = "kv_store")]
+    let store = match config.kv_config.circuit_breaker_failure_threshold {
+        Some(failure_threshold) => store.with_redis_circuit_breaker(
+            failure_threshold,
+            config
+                .kv_config
+                .circuit_breaker_cooldown_in_ms
+                .map(std::time::Duration::from_millis)
+                .unwrap_or(storage_impl::redis::circuit_breaker::DEFAULT_CIRCUIT_BREAKER_COOLDOWN),
+        ),
+        None => store,
+    };
+
+    #[cfg(all(feature = "kv_store", feature = "payouts"))]
+    let store = match config.payouts.read_cache_capacity {
+        Some(capacity) => store.with_payout_read_cache(
+            capacity,
+            std::time::Duration::from_secs(config.payouts.read_cache_ttl_in_secs),
+        ),
+        None => store,
+    };
+
+    #[cfg(all(feature = "kv_store", feature = "payouts"))]
+    let store = match config.payouts.summary_cache_capacity {
+        Some(capacity) => store.with_payout_summary_cache(
+            capacity,
+            std::time::Duration::from_secs(config.payouts.summary_cache_ttl_in_secs),
+        ),
+        None => store,
+    };
+
+    #[cfg(all(feature = "kv_store", feature = "payouts"))]
+    let store = match config.payouts.redis_key_hash_secret.as_ref() {
+        Some(secret) => store.with_payout_key_hashing(secret.peek().as_bytes()),
+        None => store,
+    };
+
+    #[cfg(all(feature = "kv_store", feature = "payouts"))]
+    let store = match config.payouts.kv_checksum_secret.as_ref() {
+        Some(secret) => {
+            let previous_secrets = config
+                .payouts
+                .previous_kv_checksum_secrets
+                .iter()
+                .map(|secret| secret.peek().as_bytes())
+                .collect::<Vec<_>>();
+            store.with_payout_integrity_check(secret.peek().as_bytes(), &previous_secrets)
+        }
+        None => store,
+    };
+
+    #[cfg(all(feature = "kv_store", feature = "payouts"))]
+    let store = store.with_payout_batch_read_limits(
+        storage_impl::payouts::batch_read::PayoutBatchReadLimits::new(
+            config.payouts.kv_batch_pipeline_size,
+            config.payouts.kv_batch_max_concurrent_pipelines,
+        ),
     );
 
+    #[cfg(feature = "kv_store")]
+    let store = match config.redis_replica.as_ref() {
+        Some(redis_replica) => store.with_redis_replica(
+            storage_impl::redis::RedisStore::new(redis_replica)
+                .await
+                .change_context(data_models::errors::StorageError::InitializationError)
+                .attach_printable("Failed to create redis replica store")?,
+        ),
+        None => store,
+    };
+
+    #[cfg(feature = "kv_store")]
+    let store = match config.kv_config.primary_read_window_in_ms {
+        Some(window_in_ms) => {
+            store.with_primary_read_window(std::time::Duration::from_millis(window_in_ms))
+        }
+        None => store,
+    };
+
     Ok(store)
 }
 
@@ -46,8 +46,9 @@ pub use data_models::payments::{
 };
 #[cfg(feature = "payouts")]
 pub use data_models::payouts::{
+    ids::{CustomerId, MerchantId, PayoutId},
     payout_attempt::{PayoutAttempt, PayoutAttemptNew, PayoutAttemptUpdate},
-    payouts::{Payouts, PayoutsNew, PayoutsUpdate},
+    payouts::{PayoutCustomerSnapshot, PayoutLoadOptions, Payouts, PayoutsNew, PayoutsUpdate},
 };
 pub use diesel_models::{
     ProcessTracker, ProcessTrackerNew, ProcessTrackerRunner, ProcessTrackerUpdate,
@@ -546,6 +546,29 @@ pub fn validate_config(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
         .into()
 }
 
+/// Lists the names of the fields of a struct in an associated constant
+/// Usage
+/// ```
+/// #[derive(router_derive::FieldNames)]
+/// struct Point {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// assert_eq!(Point::FIELD_NAMES, ["x", "y"]);
+/// ```
+///
+/// This allows asserting that two structs mirroring each other, such as a storage model and its
+/// domain model, keep the same fields.
+#[proc_macro_derive(FieldNames)]
+pub fn field_names(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    macros::misc::field_names(input)
+        .unwrap_or_else(|error| error.into_compile_error())
+        .into()
+}
+
 /// Generates the function to get the value out of enum variant
 /// Usage
 /// ```
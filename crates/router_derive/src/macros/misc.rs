@@ -61,3 +61,21 @@ pub fn validate_config(input: syn::DeriveInput) -> Result<proc_macro2::TokenStre
 
     Ok(expansion)
 }
+
+/// Implement the `FIELD_NAMES` associated constant for the struct, listing its fields in order
+pub fn field_names(input: syn::DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let fields = super::helpers::get_struct_fields(input.data)?;
+
+    let struct_name = input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let field_names = fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref().map(ToString::to_string));
+
+    Ok(quote::quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// The names of the fields of the struct, in declaration order
+            pub const FIELD_NAMES: &'static [&'static str] = &[#(#field_names),*];
+        }
+    })
+}
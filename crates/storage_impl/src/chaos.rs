@@ -0,0 +1,315 @@
+//! Failure injection into the operations of the store, to check how it copes with Redis and
+//! database outages in test and staging environments.
+//!
+//! Only compiled in with the `chaos` feature. A store injects no faults unless it is given a
+//! [`FaultInjector`], see [`crate::RouterStore::with_fault_injector`].
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use common_utils::errors::CustomResult;
+use data_models::errors::StorageError;
+use error_stack::IntoReport;
+use redis_interface::errors::RedisError;
+use router_env::logger;
+
+/// The kind of store operation a fault may be injected into.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OperationKind {
+    /// A KV operation sent to Redis, named after its [`crate::redis::kv_store::KvOperation`]
+    Redis,
+    /// Taking a connection to read from the database
+    DatabaseRead,
+    /// Taking a connection to write to the database
+    DatabaseWrite,
+}
+
+/// A store operation about to be run.
+#[derive(Clone, Copy, Debug)]
+pub struct StoreOperation<'a> {
+    pub kind: OperationKind,
+    /// The name of a Redis operation, such as `Hget`, empty for database operations
+    pub name: &'a str,
+}
+
+/// The failure an operation is made to fail with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InjectedFault {
+    /// The key read from Redis is missing, as if it had expired, which sends reads to the
+    /// database
+    KeyMissing,
+    /// Redis answers with an error
+    KvError,
+    /// No connection can be made to Redis or the database
+    ConnectionError,
+}
+
+impl InjectedFault {
+    fn into_redis_error(self) -> RedisError {
+        match self {
+            Self::KeyMissing => RedisError::NotFound,
+            Self::KvError => RedisError::UnknownResult,
+            Self::ConnectionError => RedisError::RedisConnectionError,
+        }
+    }
+
+    fn into_storage_error(self) -> StorageError {
+        match self {
+            Self::KeyMissing | Self::KvError => StorageError::KVError,
+            Self::ConnectionError => StorageError::DatabaseConnectionError,
+        }
+    }
+}
+
+/// Consulted by the store before each Redis and database operation.
+pub trait FaultInjector: std::fmt::Debug + Send + Sync {
+    /// The fault to fail `operation` with, `None` to run it.
+    fn fault_for(&self, operation: &StoreOperation<'_>) -> Option<InjectedFault>;
+}
+
+/// Injects no faults, the default of every store.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoFaults;
+
+impl FaultInjector for NoFaults {
+    fn fault_for(&self, _operation: &StoreOperation<'_>) -> Option<InjectedFault> {
+        None
+    }
+}
+
+/// Fails the operations of `kind`, only the one named `operation` if set, with `fault` at the
+/// given `probability`, between 0 and 1.
+#[derive(Clone, Debug)]
+pub struct FaultRule {
+    pub kind: OperationKind,
+    pub operation: Option<String>,
+    pub fault: InjectedFault,
+    pub probability: f64,
+}
+
+impl FaultRule {
+    fn applies_to(&self, operation: &StoreOperation<'_>) -> bool {
+        self.kind == operation.kind
+            && match &self.operation {
+                Some(name) => name == operation.name,
+                None => true,
+            }
+    }
+}
+
+/// Injects faults following a set of rules, the first rule applying to an operation deciding
+/// whether it fails.
+///
+/// Faults are drawn from a generator seeded with `seed`, so that a run can be replayed with
+/// the same sequence of failures.
+#[derive(Debug)]
+pub struct RuleBasedFaultInjector {
+    rules: Vec<FaultRule>,
+    state: AtomicU64,
+}
+
+impl RuleBasedFaultInjector {
+    pub fn new(rules: Vec<FaultRule>, seed: u64) -> Self {
+        Self {
+            rules,
+            // xorshift never leaves the zero state
+            state: AtomicU64::new(seed.max(1)),
+        }
+    }
+
+    /// The next draw, uniformly distributed in `[0, 1)`.
+    fn draw(&self) -> f64 {
+        let mut current = self.state.load(Ordering::Relaxed);
+        loop {
+            let mut next = current;
+            next ^= next << 13;
+            next ^= next >> 7;
+            next ^= next << 17;
+            match self.state.compare_exchange_weak(
+                current,
+                next,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    let high = u32::try_from(next >> 32).unwrap_or(u32::MAX);
+                    return f64::from(high) / (f64::from(u32::MAX) + 1.0);
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+impl FaultInjector for RuleBasedFaultInjector {
+    fn fault_for(&self, operation: &StoreOperation<'_>) -> Option<InjectedFault> {
+        let rule = self.rules.iter().find(|rule| rule.applies_to(operation))?;
+        (self.draw() < rule.probability).then_some(rule.fault)
+    }
+}
+
+/// Fails the Redis operation `name` if `injector` injects a fault into it.
+pub(crate) fn check_redis_operation(
+    injector: &dyn FaultInjector,
+    name: &str,
+) -> CustomResult<(), RedisError> {
+    let operation = StoreOperation {
+        kind: OperationKind::Redis,
+        name,
+    };
+    match injector.fault_for(&operation) {
+        Some(fault) => {
+            logger::warn!(
+                ?fault,
+                operation = name,
+                "Injecting a fault into a Redis operation"
+            );
+            Err(fault.into_redis_error()).into_report()
+        }
+        None => Ok(()),
+    }
+}
+
+/// Fails taking a database connection if `injector` injects a fault into it.
+pub(crate) fn check_database_operation(
+    injector: &dyn FaultInjector,
+    kind: OperationKind,
+) -> CustomResult<(), StorageError> {
+    let operation = StoreOperation { kind, name: "" };
+    match injector.fault_for(&operation) {
+        Some(fault) => {
+            logger::warn!(?fault, ?kind, "Injecting a fault into a database operation");
+            Err(fault.into_storage_error()).into_report()
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    fn redis_rule(operation: Option<&str>, fault: InjectedFault, probability: f64) -> FaultRule {
+        FaultRule {
+            kind: OperationKind::Redis,
+            operation: operation.map(ToString::to_string),
+            fault,
+            probability,
+        }
+    }
+
+    /// Reads through the KV fallback the way the store does, counting the database reads.
+    async fn read_with_fallback(
+        injector: &dyn FaultInjector,
+        database_reads: &AtomicUsize,
+    ) -> error_stack::Result<&'static str, StorageError> {
+        crate::utils::try_redis_get_else_try_database_get(
+            async {
+                check_redis_operation(injector, "Hget")?;
+                Ok("from redis")
+            },
+            || async {
+                check_database_operation(injector, OperationKind::DatabaseRead)?;
+                database_reads.fetch_add(1, Ordering::Relaxed);
+                Ok("from database")
+            },
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn injected_redis_miss_falls_back_to_the_database() {
+        let database_reads = AtomicUsize::new(0);
+        let injector =
+            RuleBasedFaultInjector::new(vec![redis_rule(None, InjectedFault::KeyMissing, 1.0)], 7);
+
+        assert_eq!(
+            read_with_fallback(&injector, &database_reads)
+                .await
+                .unwrap(),
+            "from database"
+        );
+        assert_eq!(database_reads.load(Ordering::Relaxed), 1);
+
+        // Without faults the value is read from Redis
+        assert_eq!(
+            read_with_fallback(&NoFaults, &database_reads)
+                .await
+                .unwrap(),
+            "from redis"
+        );
+        assert_eq!(database_reads.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn injected_redis_error_is_not_masked_by_the_fallback() {
+        let database_reads = AtomicUsize::new(0);
+        let injector =
+            RuleBasedFaultInjector::new(vec![redis_rule(None, InjectedFault::KvError, 1.0)], 7);
+
+        assert!(read_with_fallback(&injector, &database_reads)
+            .await
+            .is_err());
+        assert_eq!(database_reads.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn injected_database_outage_fails_the_fallback() {
+        let database_reads = AtomicUsize::new(0);
+        let injector = RuleBasedFaultInjector::new(
+            vec![
+                redis_rule(None, InjectedFault::KeyMissing, 1.0),
+                FaultRule {
+                    kind: OperationKind::DatabaseRead,
+                    operation: None,
+                    fault: InjectedFault::ConnectionError,
+                    probability: 1.0,
+                },
+            ],
+            7,
+        );
+
+        let error = read_with_fallback(&injector, &database_reads)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::DatabaseConnectionError
+        ));
+        assert_eq!(database_reads.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn faults_are_injected_by_operation() {
+        let injector = RuleBasedFaultInjector::new(
+            vec![redis_rule(Some("Hget"), InjectedFault::KvError, 1.0)],
+            7,
+        );
+        assert!(check_redis_operation(&injector, "Hget").is_err());
+        assert!(check_redis_operation(&injector, "Hset").is_ok());
+        assert!(check_database_operation(&injector, OperationKind::DatabaseWrite).is_ok());
+    }
+
+    #[test]
+    fn faults_are_injected_by_probability_deterministically() {
+        let rules = vec![redis_rule(None, InjectedFault::KvError, 0.25)];
+        let outcomes = |injector: &RuleBasedFaultInjector| {
+            (0..1000)
+                .map(|_| check_redis_operation(injector, "Hget").is_err())
+                .collect::<Vec<_>>()
+        };
+
+        let first = outcomes(&RuleBasedFaultInjector::new(rules.clone(), 42));
+        let failures = first.iter().filter(|failed| **failed).count();
+        assert!((200..300).contains(&failures), "{failures} failures");
+        // The same seed replays the same failures
+        assert_eq!(first, outcomes(&RuleBasedFaultInjector::new(rules, 42)));
+
+        let never =
+            RuleBasedFaultInjector::new(vec![redis_rule(None, InjectedFault::KvError, 0.0)], 42);
+        assert!(outcomes(&never).iter().all(|failed| !failed));
+    }
+}
@@ -16,6 +16,17 @@ pub trait DatabaseStore: Clone + Send + Sync {
     async fn new(config: Self::Config, test_transaction: bool) -> StorageResult<Self>;
     fn get_master_pool(&self) -> &PgPool;
     fn get_replica_pool(&self) -> &PgPool;
+
+    /// Consulted before taking a connection to the database, see [`crate::chaos`].
+    #[cfg(feature = "chaos")]
+    fn fault_injector(&self) -> &dyn crate::chaos::FaultInjector {
+        &crate::chaos::NoFaults
+    }
+
+    /// Bounds taking a connection to the database, see [`crate::RouterStore::set_deadline`].
+    fn deadline(&self) -> Option<std::time::Instant> {
+        None
+    }
 }
 
 #[derive(Debug, Clone)]
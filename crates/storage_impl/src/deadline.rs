@@ -0,0 +1,117 @@
+//! Deadlines bounding the operations made through a store, see
+//! [`crate::RouterStore::set_deadline`].
+
+use std::{future::Future, time::Instant};
+
+use error_stack::{report, Context, Report};
+
+/// Runs `operation`, failing with `deadline_error` if `deadline` passes before it completes.
+///
+/// `operation` is never started once `deadline` has passed, so a request which already ran out
+/// of time does not issue any more queries.
+pub(crate) async fn within_deadline<T, E, F>(
+    deadline: Option<Instant>,
+    deadline_error: E,
+    operation: F,
+) -> error_stack::Result<T, E>
+where
+    E: Context,
+    F: Future<Output = error_stack::Result<T, E>>,
+{
+    let Some(deadline) = deadline else {
+        return operation.await;
+    };
+    if deadline <= Instant::now() {
+        return Err(deadline_exceeded(deadline_error, deadline));
+    }
+    match tokio::time::timeout_at(tokio::time::Instant::from_std(deadline), operation).await {
+        Ok(result) => result,
+        Err(_) => Err(deadline_exceeded(deadline_error, deadline)),
+    }
+}
+
+fn deadline_exceeded<E: Context>(error: E, deadline: Instant) -> Report<E> {
+    report!(error).attach_printable(format!(
+        "operation deadline passed {:?} ago",
+        deadline.elapsed()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use data_models::errors::StorageError;
+
+    use super::*;
+
+    async fn counted_operation(
+        issued: &AtomicUsize,
+        duration: Duration,
+    ) -> error_stack::Result<&'static str, StorageError> {
+        issued.fetch_add(1, Ordering::Relaxed);
+        tokio::time::sleep(duration).await;
+        Ok("done")
+    }
+
+    #[tokio::test]
+    async fn past_deadline_short_circuits_before_issuing_the_operation() {
+        let issued = AtomicUsize::new(0);
+        let deadline = Instant::now() - Duration::from_millis(1);
+
+        let error = within_deadline(
+            Some(deadline),
+            StorageError::DeadlineExceeded,
+            counted_operation(&issued, Duration::ZERO),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::DeadlineExceeded
+        ));
+        assert_eq!(issued.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn operation_outliving_its_deadline_is_aborted() {
+        let issued = AtomicUsize::new(0);
+        let deadline = Instant::now() + Duration::from_millis(20);
+
+        let error = within_deadline(
+            Some(deadline),
+            StorageError::DeadlineExceeded,
+            counted_operation(&issued, Duration::from_secs(5)),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::DeadlineExceeded
+        ));
+        assert_eq!(issued.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn operation_within_its_deadline_completes() {
+        let issued = AtomicUsize::new(0);
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        for deadline in [Some(deadline), None] {
+            let result = within_deadline(
+                deadline,
+                StorageError::DeadlineExceeded,
+                counted_operation(&issued, Duration::ZERO),
+            )
+            .await
+            .unwrap();
+            assert_eq!(result, "done");
+        }
+        assert_eq!(issued.load(Ordering::Relaxed), 2);
+    }
+}
@@ -5,7 +5,7 @@ use common_utils::errors::ErrorSwitch;
 use config::ConfigError;
 use data_models::errors::StorageError as DataStorageError;
 use http::StatusCode;
-pub use redis_interface::errors::RedisError;
+pub use redis_interface::errors::{RedisError, RedisRedirection};
 use router_env::opentelemetry::metrics::MetricsError;
 
 use crate::{errors as storage_errors, store::errors::DatabaseError};
@@ -117,6 +117,8 @@ impl Into<DataStorageError> for &StorageError {
                 }
                 RedisError::JsonSerializationFailed => DataStorageError::SerializationFailed,
                 RedisError::JsonDeserializationFailed => DataStorageError::DeserializationFailed,
+                RedisError::DeadlineExceeded => DataStorageError::DeadlineExceeded,
+                RedisError::CircuitOpen => DataStorageError::KvCircuitOpen,
                 i => DataStorageError::RedisError(format!("{:?}", i)),
             },
         }
@@ -157,6 +159,9 @@ impl StorageError {
 pub trait RedisErrorExt {
     #[track_caller]
     fn to_redis_failed_response(self, key: &str) -> error_stack::Report<DataStorageError>;
+
+    /// Returns the cluster redirection the failed command was answered with, if any.
+    fn redirection(&self) -> Option<RedisRedirection>;
 }
 
 impl RedisErrorExt for error_stack::Report<RedisError> {
@@ -171,9 +176,15 @@ impl RedisErrorExt for error_stack::Report<RedisError> {
                     key: Some(key.to_string()),
                 })
             }
+            RedisError::DeadlineExceeded => self.change_context(DataStorageError::DeadlineExceeded),
+            RedisError::CircuitOpen => self.change_context(DataStorageError::KvCircuitOpen),
             _ => self.change_context(DataStorageError::KVError),
         }
     }
+
+    fn redirection(&self) -> Option<RedisRedirection> {
+        RedisRedirection::from_report(self)
+    }
 }
 
 impl_error_type!(EncryptionError, "Encryption error");
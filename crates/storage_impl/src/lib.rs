@@ -1,14 +1,18 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use data_models::errors::{StorageError, StorageResult};
 use diesel_models as store;
+use diesel_models::enums::MerchantStorageScheme;
 use error_stack::ResultExt;
 use masking::StrongSecret;
 use redis::{kv_store::RedisConnInterface, RedisStore};
 mod address;
+#[cfg(feature = "chaos")]
+pub mod chaos;
 pub mod config;
 pub mod connection;
 pub mod database;
+mod deadline;
 pub mod errors;
 mod lookup;
 pub mod metrics;
@@ -23,7 +27,7 @@ mod utils;
 
 use common_utils::errors::CustomResult;
 #[cfg(not(feature = "payouts"))]
-use data_models::{PayoutAttemptInterface, PayoutsInterface};
+use data_models::{PayoutAttemptInterface, PayoutWebhookDeliveryInterface, PayoutsInterface};
 use database::store::PgPool;
 pub use mock_db::MockDb;
 use redis_interface::{errors::RedisError, SaddReply};
@@ -37,6 +41,23 @@ pub struct RouterStore<T: DatabaseStore> {
     db_store: T,
     cache_store: RedisStore,
     master_encryption_key: StrongSecret<Vec<u8>>,
+    #[cfg(feature = "payouts")]
+    payout_balance_check: bool,
+    #[cfg(feature = "payouts")]
+    payout_created_at_skew_tolerance_in_secs: u32,
+    #[cfg(feature = "payouts")]
+    payout_audit_sink: Option<Arc<dyn payouts::audit::AuditSink>>,
+    #[cfg(feature = "payouts")]
+    payout_id_validator: Option<Arc<dyn payouts::id_format::PayoutIdValidator>>,
+    #[cfg(feature = "payouts")]
+    payout_imports_enabled: bool,
+    #[cfg(feature = "payouts")]
+    payout_test_mode: data_models::payouts::payouts::TestModeContext,
+    #[cfg(feature = "payouts")]
+    payout_read_only: payouts::read_only::PayoutReadOnlyMode,
+    #[cfg(feature = "chaos")]
+    fault_injector: Arc<dyn chaos::FaultInjector>,
+    deadline: Option<std::time::Instant>,
     pub request_id: Option<String>,
 }
 
@@ -77,6 +98,13 @@ where
     fn get_replica_pool(&self) -> &PgPool {
         self.db_store.get_replica_pool()
     }
+    #[cfg(feature = "chaos")]
+    fn fault_injector(&self) -> &dyn chaos::FaultInjector {
+        self.fault_injector.as_ref()
+    }
+    fn deadline(&self) -> Option<std::time::Instant> {
+        self.deadline
+    }
 }
 
 impl<T: DatabaseStore> RedisConnInterface for RouterStore<T> {
@@ -110,6 +138,24 @@ impl<T: DatabaseStore> RouterStore<T> {
             db_store,
             cache_store,
             master_encryption_key: encryption_key,
+            #[cfg(feature = "payouts")]
+            payout_balance_check: false,
+            #[cfg(feature = "payouts")]
+            payout_created_at_skew_tolerance_in_secs:
+                payouts::DEFAULT_CREATED_AT_SKEW_TOLERANCE_IN_SECS,
+            #[cfg(feature = "payouts")]
+            payout_audit_sink: None,
+            #[cfg(feature = "payouts")]
+            payout_id_validator: None,
+            #[cfg(feature = "payouts")]
+            payout_imports_enabled: false,
+            #[cfg(feature = "payouts")]
+            payout_test_mode: data_models::payouts::payouts::TestModeContext::default(),
+            #[cfg(feature = "payouts")]
+            payout_read_only: payouts::read_only::PayoutReadOnlyMode::default(),
+            #[cfg(feature = "chaos")]
+            fault_injector: Arc::new(chaos::NoFaults),
+            deadline: None,
             request_id: None,
         })
     }
@@ -118,6 +164,80 @@ impl<T: DatabaseStore> RouterStore<T> {
         &self.master_encryption_key
     }
 
+    /// Requires the merchant's payout float to cover every payout inserted through this store.
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_balance_check(mut self, enabled: bool) -> Self {
+        self.payout_balance_check = enabled;
+        self
+    }
+
+    /// Sets how far in the future the `created_at` of an inserted payout may be, to allow for
+    /// clock skew between the client and this server.
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_created_at_skew_tolerance(mut self, tolerance_in_secs: u32) -> Self {
+        self.payout_created_at_skew_tolerance_in_secs = tolerance_in_secs;
+        self
+    }
+
+    /// Forwards every payout inserted or updated through this store to `sink`.
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_audit_sink(mut self, sink: Arc<dyn payouts::audit::AuditSink>) -> Self {
+        self.payout_audit_sink = Some(sink);
+        self
+    }
+
+    /// Rejects the payouts inserted through this store whose id is not accepted by `validator`,
+    /// such as a [`payouts::id_format::PayoutIdFormat`].
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_id_validator(
+        mut self,
+        validator: Arc<dyn payouts::id_format::PayoutIdValidator>,
+    ) -> Self {
+        self.payout_id_validator = Some(validator);
+        self
+    }
+
+    /// Allows payouts to be imported through this store, see
+    /// [`data_models::payouts::payouts::PayoutsInterface::import_payout`].
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_imports(mut self, enabled: bool) -> Self {
+        self.payout_imports_enabled = enabled;
+        self
+    }
+
+    /// Inserts and finds the payouts of the mode of the merchant this store serves, see
+    /// [`data_models::payouts::payouts::TestModeContext`].
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_test_mode(
+        mut self,
+        context: data_models::payouts::payouts::TestModeContext,
+    ) -> Self {
+        self.payout_test_mode = context;
+        self
+    }
+
+    /// Consults `injector` before each Redis and database operation of this store, see
+    /// [`chaos`].
+    #[cfg(feature = "chaos")]
+    pub fn with_fault_injector(mut self, injector: Arc<dyn chaos::FaultInjector>) -> Self {
+        self.fault_injector = injector;
+        self
+    }
+
+    /// Bounds the operations made through this store, typically cloned for a single request,
+    /// by `deadline`: taking a database connection and KV operations fail with
+    /// `DeadlineExceeded` once it has passed, and are not issued at all if it already has.
+    pub fn set_deadline(&mut self, deadline: Option<std::time::Instant>) {
+        self.deadline = deadline;
+    }
+
+    /// The switch rejecting payout inserts, updates and deletions through this store and its
+    /// clones, to be turned on at runtime for database maintenance.
+    #[cfg(feature = "payouts")]
+    pub fn payout_read_only(&self) -> &payouts::read_only::PayoutReadOnlyMode {
+        &self.payout_read_only
+    }
+
     /// # Panics
     ///
     /// Will panic if `CONNECTOR_AUTH_FILE_PATH` is not set
@@ -136,6 +256,24 @@ impl<T: DatabaseStore> RouterStore<T> {
             db_store,
             cache_store,
             master_encryption_key: encryption_key,
+            #[cfg(feature = "payouts")]
+            payout_balance_check: false,
+            #[cfg(feature = "payouts")]
+            payout_created_at_skew_tolerance_in_secs:
+                payouts::DEFAULT_CREATED_AT_SKEW_TOLERANCE_IN_SECS,
+            #[cfg(feature = "payouts")]
+            payout_audit_sink: None,
+            #[cfg(feature = "payouts")]
+            payout_id_validator: None,
+            #[cfg(feature = "payouts")]
+            payout_imports_enabled: false,
+            #[cfg(feature = "payouts")]
+            payout_test_mode: data_models::payouts::payouts::TestModeContext::default(),
+            #[cfg(feature = "payouts")]
+            payout_read_only: payouts::read_only::PayoutReadOnlyMode::default(),
+            #[cfg(feature = "chaos")]
+            fault_injector: Arc::new(chaos::NoFaults),
+            deadline: None,
             request_id: None,
         })
     }
@@ -147,6 +285,21 @@ pub struct KVRouterStore<T: DatabaseStore> {
     drainer_stream_name: String,
     drainer_num_partitions: u8,
     ttl_for_kv: u32,
+    redis_replica: Option<RedisStore>,
+    primary_read_window: Option<Arc<redis::read_after_write::PrimaryReadWindow>>,
+    storage_scheme_overrides: HashMap<String, MerchantStorageScheme>,
+    drainer_stream_push_timeout: Option<std::time::Duration>,
+    redis_circuit_breaker: Option<Arc<redis::circuit_breaker::RedisCircuitBreaker>>,
+    #[cfg(feature = "payouts")]
+    payout_read_cache: Option<Arc<payouts::read_cache::PayoutReadCache>>,
+    #[cfg(feature = "payouts")]
+    payout_summary_cache: Option<Arc<payouts::summary_cache::PayoutSummaryCache>>,
+    #[cfg(feature = "payouts")]
+    payout_key_hasher: Option<payouts::kv_key::PayoutKeyHasher>,
+    #[cfg(feature = "payouts")]
+    payout_checksum: Option<payouts::integrity::PayoutChecksum>,
+    #[cfg(feature = "payouts")]
+    payout_batch_read_limits: payouts::batch_read::PayoutBatchReadLimits,
     pub request_id: Option<String>,
 }
 
@@ -172,6 +325,13 @@ where
     fn get_replica_pool(&self) -> &PgPool {
         self.router_store.get_replica_pool()
     }
+    #[cfg(feature = "chaos")]
+    fn fault_injector(&self) -> &dyn chaos::FaultInjector {
+        self.router_store.fault_injector()
+    }
+    fn deadline(&self) -> Option<std::time::Instant> {
+        self.router_store.deadline()
+    }
 }
 
 impl<T: DatabaseStore> RedisConnInterface for KVRouterStore<T> {
@@ -196,10 +356,243 @@ impl<T: DatabaseStore> KVRouterStore<T> {
             drainer_stream_name,
             drainer_num_partitions,
             ttl_for_kv,
+            redis_replica: None,
+            primary_read_window: None,
+            storage_scheme_overrides: HashMap::new(),
+            drainer_stream_push_timeout: None,
+            redis_circuit_breaker: None,
+            #[cfg(feature = "payouts")]
+            payout_read_cache: None,
+            #[cfg(feature = "payouts")]
+            payout_summary_cache: None,
+            #[cfg(feature = "payouts")]
+            payout_key_hasher: None,
+            #[cfg(feature = "payouts")]
+            payout_checksum: None,
+            #[cfg(feature = "payouts")]
+            payout_batch_read_limits: payouts::batch_read::PayoutBatchReadLimits::default(),
             request_id,
         }
     }
 
+    /// Bounds the operations made through this store by `deadline`, see
+    /// [`RouterStore::set_deadline`].
+    pub fn set_deadline(&mut self, deadline: Option<std::time::Instant>) {
+        self.router_store.set_deadline(deadline);
+    }
+
+    /// Consults `injector` before each Redis and database operation of this store, see
+    /// [`RouterStore::with_fault_injector`].
+    #[cfg(feature = "chaos")]
+    pub fn with_fault_injector(mut self, injector: Arc<dyn chaos::FaultInjector>) -> Self {
+        self.router_store = self.router_store.with_fault_injector(injector);
+        self
+    }
+
+    /// Serves the read-only KV operations from the given Redis replica.
+    ///
+    /// Writes and drainer stream pushes always go to the primary. Reads served by the replica
+    /// are subject to its replication lag.
+    pub fn with_redis_replica(mut self, redis_replica: RedisStore) -> Self {
+        self.redis_replica = Some(redis_replica);
+        self
+    }
+
+    /// Serves the reads of a KV key from the primary for `window` after the key was written
+    /// through this store, see [`redis::read_after_write::PrimaryReadWindow`]. Only relevant
+    /// along with [`Self::with_redis_replica`].
+    pub fn with_primary_read_window(mut self, window: std::time::Duration) -> Self {
+        self.primary_read_window = Some(Arc::new(redis::read_after_write::PrimaryReadWindow::new(
+            window,
+        )));
+        self
+    }
+
+    /// Forces the given merchants to a storage scheme, regardless of the one requested by the
+    /// caller.
+    pub fn with_storage_scheme_overrides(
+        mut self,
+        storage_scheme_overrides: HashMap<String, MerchantStorageScheme>,
+    ) -> Self {
+        self.storage_scheme_overrides = storage_scheme_overrides;
+        self
+    }
+
+    /// Fails KV writes whose push to the drainer stream takes longer than `timeout`, instead of
+    /// waiting on a slow Redis.
+    pub fn with_drainer_stream_push_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.drainer_stream_push_timeout = Some(timeout);
+        self
+    }
+
+    /// Short-circuits the KV operations for `cooldown` after `failure_threshold` consecutive
+    /// Redis failures, see [`redis::circuit_breaker::RedisCircuitBreaker`].
+    ///
+    /// While the breaker is open, KV reads are served by the database and KV writes fail with
+    /// `KvCircuitOpen`, rather than being written to the database behind the payouts held in KV.
+    pub fn with_redis_circuit_breaker(
+        mut self,
+        failure_threshold: u32,
+        cooldown: std::time::Duration,
+    ) -> Self {
+        self.redis_circuit_breaker = Some(Arc::new(
+            redis::circuit_breaker::RedisCircuitBreaker::new(failure_threshold, cooldown),
+        ));
+        self
+    }
+
+    /// The state of the Redis circuit breaker, `None` if the store has none.
+    pub fn redis_circuit_state(&self) -> Option<redis::circuit_breaker::CircuitState> {
+        self.redis_circuit_breaker
+            .as_deref()
+            .map(redis::circuit_breaker::RedisCircuitBreaker::state)
+    }
+
+    /// Serves repeated reads of the same payout from a process-wide cache holding up to
+    /// `capacity` payouts for `ttl`, see [`payouts::read_cache::PayoutReadCache`].
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_read_cache(mut self, capacity: usize, ttl: std::time::Duration) -> Self {
+        self.payout_read_cache = Some(Arc::new(payouts::read_cache::PayoutReadCache::new(
+            capacity, ttl,
+        )));
+        self
+    }
+
+    /// Serves the payouts read from KV in batches from a process-wide cache holding up to
+    /// `capacity` payouts for `ttl`, see [`payouts::summary_cache::PayoutSummaryCache`].
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_summary_cache(mut self, capacity: usize, ttl: std::time::Duration) -> Self {
+        self.payout_summary_cache = Some(Arc::new(
+            payouts::summary_cache::PayoutSummaryCache::new(capacity, ttl),
+        ));
+        self
+    }
+
+    /// Bounds the pipelines issued to read payouts from KV in batches, see
+    /// [`payouts::batch_read::PayoutBatchReadLimits`].
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_batch_read_limits(
+        mut self,
+        limits: payouts::batch_read::PayoutBatchReadLimits,
+    ) -> Self {
+        self.payout_batch_read_limits = limits;
+        self
+    }
+
+    /// Hashes the payout ids appearing in the Redis keys of payouts with `secret`, see
+    /// [`payouts::kv_key::PayoutKeyHasher`].
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_key_hashing(mut self, secret: &[u8]) -> Self {
+        self.payout_key_hasher = Some(payouts::kv_key::PayoutKeyHasher::new(secret));
+        self
+    }
+
+    /// Checks the integrity of the payouts read from KV with a checksum keyed with `secret`,
+    /// see [`payouts::integrity::PayoutChecksum`]. Checksums computed with `previous_secrets`
+    /// are accepted too, for rotating the secret.
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_integrity_check(
+        mut self,
+        secret: &[u8],
+        previous_secrets: &[&[u8]],
+    ) -> Self {
+        self.payout_checksum = Some(payouts::integrity::PayoutChecksum::new(
+            secret,
+            previous_secrets,
+        ));
+        self
+    }
+
+    /// Sets the mode of the payouts inserted and found through this store, see
+    /// [`RouterStore::with_payout_test_mode`].
+    #[cfg(feature = "payouts")]
+    pub fn with_payout_test_mode(
+        mut self,
+        context: data_models::payouts::payouts::TestModeContext,
+    ) -> Self {
+        self.router_store = self.router_store.with_payout_test_mode(context);
+        self
+    }
+
+    /// The switch rejecting payout writes, shared with the underlying store, see
+    /// [`RouterStore::payout_read_only`].
+    #[cfg(feature = "payouts")]
+    pub fn payout_read_only(&self) -> &payouts::read_only::PayoutReadOnlyMode {
+        self.router_store.payout_read_only()
+    }
+
+    /// Returns the storage scheme to use for the merchant, taking operator overrides into
+    /// account.
+    pub async fn effective_storage_scheme(
+        &self,
+        merchant_id: &str,
+        requested: MerchantStorageScheme,
+    ) -> MerchantStorageScheme {
+        redis::scheme_override::effective_storage_scheme(
+            self,
+            &self.storage_scheme_overrides,
+            merchant_id,
+            requested,
+        )
+        .await
+    }
+
+    /// Resolves the storage scheme of the merchant from the scheme `configured` on its account,
+    /// see [`redis::scheme_override::resolve_storage_scheme`].
+    ///
+    /// The payout methods of this store resolve the scheme they are called with through this
+    /// before touching storage.
+    pub async fn resolve_storage_scheme(
+        &self,
+        merchant_id: &str,
+        configured: MerchantStorageScheme,
+    ) -> StorageResult<redis::scheme_override::ResolvedScheme> {
+        redis::scheme_override::resolve_storage_scheme(
+            self,
+            &self.storage_scheme_overrides,
+            merchant_id,
+            configured,
+        )
+        .await
+    }
+
+    /// Returns a connection to the Redis replica, falling back to the primary when no replica
+    /// is configured.
+    pub fn get_redis_replica_conn(
+        &self,
+    ) -> error_stack::Result<Arc<redis_interface::RedisConnectionPool>, RedisError> {
+        match &self.redis_replica {
+            Some(redis_replica) => redis_replica.get_redis_conn(),
+            None => self.get_redis_conn(),
+        }
+    }
+
+    /// Returns a connection to read `keys` from: the primary if any of them is within its
+    /// [`Self::with_primary_read_window`], the replica otherwise, see
+    /// [`Self::get_redis_replica_conn`].
+    pub fn get_redis_read_conn<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a str>,
+    ) -> error_stack::Result<Arc<redis_interface::RedisConnectionPool>, RedisError> {
+        let reads_from_primary = self
+            .primary_read_window
+            .as_deref()
+            .is_some_and(|window| keys.into_iter().any(|key| window.reads_from_primary(key)));
+        if reads_from_primary {
+            self.get_redis_conn()
+        } else {
+            self.get_redis_replica_conn()
+        }
+    }
+
+    /// Records a write of `key` made through this store, see
+    /// [`Self::with_primary_read_window`].
+    pub fn record_kv_write(&self, key: &str) {
+        if let Some(window) = self.primary_read_window.as_deref() {
+            window.record_write(key);
+        }
+    }
+
     pub fn master_key(&self) -> &StrongSecret<Vec<u8>> {
         self.router_store.master_key()
     }
@@ -218,10 +611,13 @@ impl<T: DatabaseStore> KVRouterStore<T> {
     {
         let global_id = format!("{}", partition_key);
         let request_id = self.request_id.clone().unwrap_or_default();
+        #[cfg(feature = "payouts")]
+        let is_payout_op = redis_entry.op.payout_ids().is_some();
 
         let shard_key = R::shard_key(partition_key, self.drainer_num_partitions);
         let stream_name = self.get_drainer_stream_name(&shard_key);
-        self.router_store
+        let push = self
+            .router_store
             .cache_store
             .redis_conn
             .stream_append_entry(
@@ -230,14 +626,21 @@ impl<T: DatabaseStore> KVRouterStore<T> {
                 redis_entry
                     .to_field_value_pairs(request_id, global_id)
                     .change_context(RedisError::JsonSerializationFailed)?,
-            )
+            );
+        redis::kv_store::push_with_timeout(push, self.drainer_stream_push_timeout)
             .await
             .map(|_| metrics::KV_PUSHED_TO_DRAINER.add(&metrics::CONTEXT, 1, &[]))
             .map_err(|err| {
                 metrics::KV_FAILED_TO_PUSH_TO_DRAINER.add(&metrics::CONTEXT, 1, &[]);
                 err
             })
-            .change_context(RedisError::StreamAppendFailed)
+            .change_context(RedisError::StreamAppendFailed)?;
+
+        #[cfg(feature = "payouts")]
+        if is_payout_op {
+            payouts::pending_ops::count_pushed_payout_ops(self, 1).await;
+        }
+        Ok(())
     }
 }
 
@@ -361,6 +764,16 @@ impl UniqueConstraints for diesel_models::PayoutAttempt {
     }
 }
 
+#[cfg(feature = "payouts")]
+impl UniqueConstraints for diesel_models::payout_webhook_delivery::PayoutWebhookDelivery {
+    fn unique_constraints(&self) -> Vec<String> {
+        vec![format!("powd_{}_{}", self.merchant_id, self.delivery_id)]
+    }
+    fn table_name(&self) -> &str {
+        "PayoutWebhookDelivery"
+    }
+}
+
 #[cfg(not(feature = "payouts"))]
 impl<T: DatabaseStore> PayoutAttemptInterface for KVRouterStore<T> {}
 #[cfg(not(feature = "payouts"))]
@@ -369,3 +782,7 @@ impl<T: DatabaseStore> PayoutAttemptInterface for RouterStore<T> {}
 impl<T: DatabaseStore> PayoutsInterface for KVRouterStore<T> {}
 #[cfg(not(feature = "payouts"))]
 impl<T: DatabaseStore> PayoutsInterface for RouterStore<T> {}
+#[cfg(not(feature = "payouts"))]
+impl<T: DatabaseStore> PayoutWebhookDeliveryInterface for KVRouterStore<T> {}
+#[cfg(not(feature = "payouts"))]
+impl<T: DatabaseStore> PayoutWebhookDeliveryInterface for RouterStore<T> {}
@@ -73,7 +73,8 @@ impl<T: DatabaseStore> ReverseLookupInterface for KVRouterStore<T> {
         storage_scheme: storage_enums::MerchantStorageScheme,
     ) -> CustomResult<DieselReverseLookup, errors::StorageError> {
         match storage_scheme {
-            storage_enums::MerchantStorageScheme::PostgresOnly => {
+            storage_enums::MerchantStorageScheme::PostgresOnly
+            | storage_enums::MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .insert_reverse_lookup(new, storage_scheme)
                     .await
@@ -124,7 +125,8 @@ impl<T: DatabaseStore> ReverseLookupInterface for KVRouterStore<T> {
                 .await
         };
         match storage_scheme {
-            storage_enums::MerchantStorageScheme::PostgresOnly => database_call().await,
+            storage_enums::MerchantStorageScheme::PostgresOnly
+            | storage_enums::MerchantStorageScheme::DualWrite => database_call().await,
             storage_enums::MerchantStorageScheme::RedisKv => {
                 let redis_fut = async {
                     kv_wrapper(
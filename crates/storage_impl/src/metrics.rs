@@ -10,3 +10,10 @@ counter_metric!(KV_OPERATION_SUCCESSFUL, GLOBAL_METER);
 counter_metric!(KV_OPERATION_FAILED, GLOBAL_METER);
 counter_metric!(KV_PUSHED_TO_DRAINER, GLOBAL_METER);
 counter_metric!(KV_FAILED_TO_PUSH_TO_DRAINER, GLOBAL_METER);
+// KV operations not sent to Redis while its circuit breaker is open
+counter_metric!(KV_OPERATION_SHORT_CIRCUITED, GLOBAL_METER);
+
+// Payouts found out of sync with the KV index of their merchant, by drift
+counter_metric!(PAYOUT_KV_INDEX_DRIFT, GLOBAL_METER);
+// Payouts read from KV whose checksum did not match their value
+counter_metric!(PAYOUT_KV_INTEGRITY_CHECK_FAILED, GLOBAL_METER);
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use data_models::{
     errors::StorageError,
@@ -16,10 +16,12 @@ pub mod payment_intent;
 #[cfg(feature = "payouts")]
 pub mod payout_attempt;
 #[cfg(feature = "payouts")]
+pub mod payout_webhook_delivery;
+#[cfg(feature = "payouts")]
 pub mod payouts;
 pub mod redis_conn;
 #[cfg(not(feature = "payouts"))]
-use data_models::{PayoutAttemptInterface, PayoutsInterface};
+use data_models::{PayoutAttemptInterface, PayoutWebhookDeliveryInterface, PayoutsInterface};
 
 #[derive(Clone)]
 pub struct MockDb {
@@ -55,6 +57,18 @@ pub struct MockDb {
     pub payout_attempt: Arc<Mutex<Vec<store::payout_attempt::PayoutAttempt>>>,
     #[cfg(feature = "payouts")]
     pub payouts: Arc<Mutex<Vec<store::payouts::Payouts>>>,
+    #[cfg(feature = "payouts")]
+    pub payout_webhook_deliveries:
+        Arc<Mutex<Vec<store::payout_webhook_delivery::PayoutWebhookDelivery>>>,
+    #[cfg(feature = "payouts")]
+    pub payout_webhook_events: Arc<Mutex<Vec<store::payout_webhook_event::PayoutWebhookEventNew>>>,
+    #[cfg(feature = "payouts")]
+    pub payout_amount_adjustments:
+        Arc<Mutex<Vec<store::payout_amount_adjustment::PayoutAmountAdjustmentNew>>>,
+    #[cfg(feature = "payouts")]
+    pub payout_balances: Arc<Mutex<HashMap<String, i64>>>,
+    #[cfg(feature = "payouts")]
+    pub payout_read_only: crate::payouts::read_only::PayoutReadOnlyMode,
     pub authentications: Arc<Mutex<Vec<store::authentication::Authentication>>>,
     pub roles: Arc<Mutex<Vec<store::role::Role>>>,
 }
@@ -98,6 +112,16 @@ impl MockDb {
             payout_attempt: Default::default(),
             #[cfg(feature = "payouts")]
             payouts: Default::default(),
+            #[cfg(feature = "payouts")]
+            payout_webhook_deliveries: Default::default(),
+            #[cfg(feature = "payouts")]
+            payout_webhook_events: Default::default(),
+            #[cfg(feature = "payouts")]
+            payout_amount_adjustments: Default::default(),
+            #[cfg(feature = "payouts")]
+            payout_balances: Default::default(),
+            #[cfg(feature = "payouts")]
+            payout_read_only: Default::default(),
             authentications: Default::default(),
             roles: Default::default(),
         })
@@ -109,3 +133,6 @@ impl PayoutsInterface for MockDb {}
 
 #[cfg(not(feature = "payouts"))]
 impl PayoutAttemptInterface for MockDb {}
+
+#[cfg(not(feature = "payouts"))]
+impl PayoutWebhookDeliveryInterface for MockDb {}
@@ -8,6 +8,7 @@ use data_models::{
 use diesel_models::enums as storage_enums;
 
 use super::MockDb;
+use crate::DataModelExt;
 
 #[async_trait::async_trait]
 impl PayoutAttemptInterface for MockDb {
@@ -23,11 +24,24 @@ impl PayoutAttemptInterface for MockDb {
 
     async fn insert_payout_attempt(
         &self,
-        _payout: PayoutAttemptNew,
+        payout: PayoutAttemptNew,
         _storage_scheme: storage_enums::MerchantStorageScheme,
     ) -> CustomResult<PayoutAttempt, StorageError> {
-        // TODO: Implement function for `MockDb`
-        Err(StorageError::MockDbError)?
+        let mut attempts = self.payout_attempt.lock().await;
+        if attempts.iter().any(|attempt| {
+            attempt.merchant_id == payout.merchant_id
+                && attempt.payout_attempt_id == payout.payout_attempt_id
+        }) {
+            return Err(StorageError::DuplicateValue {
+                entity: "payout attempt",
+                key: Some(payout.payout_attempt_id),
+            }
+            .into());
+        }
+
+        let attempt = payout.into_attempt(common_utils::date_time::now());
+        attempts.push(attempt.clone().to_storage_model());
+        Ok(attempt)
     }
 
     async fn find_payout_attempt_by_merchant_id_payout_attempt_id(
@@ -39,4 +53,105 @@ impl PayoutAttemptInterface for MockDb {
         // TODO: Implement function for `MockDb`
         Err(StorageError::MockDbError)?
     }
+
+    async fn list_payout_attempts(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<PayoutAttempt>, StorageError> {
+        let mut attempts = self
+            .payout_attempt
+            .lock()
+            .await
+            .iter()
+            .filter(|attempt| attempt.merchant_id == merchant_id && attempt.payout_id == payout_id)
+            .cloned()
+            .map(PayoutAttempt::from_storage_model)
+            .collect::<Vec<_>>();
+        PayoutAttempt::sort_for_timeline(&mut attempts);
+        Ok(attempts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use data_models::payouts::payout_attempt::{PayoutAttemptInterface, PayoutAttemptNew};
+    use diesel_models::enums::{MerchantStorageScheme, PayoutStatus};
+
+    use crate::MockDb;
+
+    #[tokio::test]
+    async fn attempts_are_listed_in_the_order_they_were_made() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let now = common_utils::date_time::now();
+        let attempt = |payout_id: &str, payout_attempt_id: &str, connector: &str, minutes_ago| {
+            PayoutAttemptNew {
+                payout_attempt_id: payout_attempt_id.to_string(),
+                payout_id: payout_id.to_string(),
+                merchant_id: "merchant_1".to_string(),
+                connector: Some(connector.to_string()),
+                created_at: Some(now - time::Duration::minutes(minutes_ago)),
+                ..Default::default()
+            }
+        };
+
+        // The retry on another connector is inserted first, to show listing doesn't depend on
+        // the order of insertion
+        let retried = mockdb
+            .insert_payout_attempt(
+                PayoutAttemptNew {
+                    status: PayoutStatus::Success,
+                    ..attempt("po_1", "po_1_2", "wise", 1)
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        let failed = mockdb
+            .insert_payout_attempt(
+                PayoutAttemptNew {
+                    status: PayoutStatus::Failed,
+                    error_code: Some("insufficient_funds".to_string()),
+                    ..attempt("po_1", "po_1_1", "adyen", 5)
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        mockdb
+            .insert_payout_attempt(
+                attempt("po_2", "po_2_1", "adyen", 10),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+
+        let attempts = mockdb
+            .list_payout_attempts("merchant_1", "po_1", MerchantStorageScheme::PostgresOnly)
+            .await
+            .unwrap();
+        assert_eq!(attempts, [failed, retried]);
+        assert_eq!(
+            attempts
+                .iter()
+                .map(|attempt| (attempt.connector.as_deref(), attempt.status))
+                .collect::<Vec<_>>(),
+            [
+                (Some("adyen"), PayoutStatus::Failed),
+                (Some("wise"), PayoutStatus::Success),
+            ]
+        );
+
+        assert!(mockdb
+            .list_payout_attempts("merchant_1", "po_3", MerchantStorageScheme::PostgresOnly)
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -0,0 +1,259 @@
+use common_utils::errors::CustomResult;
+use data_models::{
+    errors::StorageError,
+    payouts::webhook_delivery::{
+        PayoutWebhookDelivery, PayoutWebhookDeliveryInterface, PayoutWebhookDeliveryNew,
+        PayoutWebhookDeliveryUpdate,
+    },
+};
+use diesel_models::enums as storage_enums;
+use time::PrimitiveDateTime;
+
+use super::MockDb;
+use crate::DataModelExt;
+
+#[async_trait::async_trait]
+impl PayoutWebhookDeliveryInterface for MockDb {
+    async fn insert_webhook_delivery(
+        &self,
+        new: PayoutWebhookDeliveryNew,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<PayoutWebhookDelivery, StorageError> {
+        let mut deliveries = self.payout_webhook_deliveries.lock().await;
+        if deliveries
+            .iter()
+            .any(|delivery| delivery.delivery_id == new.delivery_id)
+        {
+            return Err(StorageError::DuplicateValue {
+                entity: "payout webhook delivery",
+                key: Some(new.delivery_id),
+            }
+            .into());
+        }
+
+        let delivery = new.into_delivery();
+        deliveries.push(delivery.clone().to_storage_model());
+        Ok(delivery)
+    }
+
+    async fn update_webhook_delivery(
+        &self,
+        this: &PayoutWebhookDelivery,
+        delivery_update: PayoutWebhookDeliveryUpdate,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<PayoutWebhookDelivery, StorageError> {
+        let mut deliveries = self.payout_webhook_deliveries.lock().await;
+        let delivery = deliveries
+            .iter_mut()
+            .find(|delivery| {
+                delivery.merchant_id == this.merchant_id && delivery.delivery_id == this.delivery_id
+            })
+            .ok_or(StorageError::ValueNotFound(format!(
+                "No payout webhook delivery found for delivery_id = {}",
+                this.delivery_id
+            )))?;
+        *delivery = delivery_update
+            .to_storage_model()
+            .apply_changeset(this.clone().to_storage_model());
+        Ok(PayoutWebhookDelivery::from_storage_model(delivery.clone()))
+    }
+
+    async fn list_webhook_deliveries(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<PayoutWebhookDelivery>, StorageError> {
+        let mut deliveries = self
+            .payout_webhook_deliveries
+            .lock()
+            .await
+            .iter()
+            .filter(|delivery| {
+                delivery.merchant_id == merchant_id && delivery.payout_id == payout_id
+            })
+            .cloned()
+            .map(PayoutWebhookDelivery::from_storage_model)
+            .collect::<Vec<_>>();
+        deliveries.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.delivery_id.cmp(&b.delivery_id))
+        });
+        Ok(deliveries)
+    }
+
+    async fn find_pending_webhook_deliveries(
+        &self,
+        older_than: PrimitiveDateTime,
+        limit: i64,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<PayoutWebhookDelivery>, StorageError> {
+        let mut deliveries = self
+            .payout_webhook_deliveries
+            .lock()
+            .await
+            .iter()
+            .cloned()
+            .map(PayoutWebhookDelivery::from_storage_model)
+            .filter(|delivery| delivery.is_due(older_than))
+            .collect::<Vec<_>>();
+        PayoutWebhookDelivery::sort_for_retry(&mut deliveries);
+        deliveries.truncate(usize::try_from(limit).unwrap_or(0));
+        Ok(deliveries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use data_models::payouts::webhook_delivery::{
+        PayoutWebhookDeliveryInterface, PayoutWebhookDeliveryNew, PayoutWebhookDeliveryUpdate,
+    };
+    use diesel_models::enums::MerchantStorageScheme;
+
+    use crate::MockDb;
+
+    fn new_delivery(
+        delivery_id: &str,
+        payout_id: &str,
+        minutes_ago: i64,
+    ) -> PayoutWebhookDeliveryNew {
+        let created_at = common_utils::date_time::now() - time::Duration::minutes(minutes_ago);
+        PayoutWebhookDeliveryNew {
+            delivery_id: delivery_id.to_string(),
+            merchant_id: "merchant_1".to_string(),
+            payout_id: payout_id.to_string(),
+            target_url: "https://merchant.example/webhooks".to_string(),
+            next_retry_at: Some(created_at),
+            created_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn delivery_is_recorded_and_marked_delivered() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let now = common_utils::date_time::now();
+
+        let delivery = mockdb
+            .insert_webhook_delivery(
+                new_delivery("whd_1", "po_1", 5),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(delivery.attempt_count, 0);
+        assert!(delivery.is_due(now));
+        assert!(mockdb
+            .insert_webhook_delivery(
+                new_delivery("whd_1", "po_1", 5),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .is_err());
+
+        let failed = mockdb
+            .update_webhook_delivery(
+                &delivery,
+                PayoutWebhookDeliveryUpdate::AttemptFailed {
+                    status_code: Some(503),
+                    next_retry_at: Some(now + time::Duration::minutes(1)),
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(failed.attempt_count, 1);
+        assert_eq!(failed.last_status_code, Some(503));
+        assert!(!failed.is_due(now));
+
+        let delivered = mockdb
+            .update_webhook_delivery(
+                &failed,
+                PayoutWebhookDeliveryUpdate::Delivered {
+                    status_code: 200,
+                    delivered_at: now,
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(delivered.attempt_count, 2);
+        assert_eq!(delivered.last_status_code, Some(200));
+        assert_eq!(delivered.delivered_at, Some(now));
+        assert_eq!(delivered.next_retry_at, None);
+
+        let deliveries = mockdb
+            .list_webhook_deliveries("merchant_1", "po_1", MerchantStorageScheme::PostgresOnly)
+            .await
+            .unwrap();
+        assert_eq!(deliveries, [delivered]);
+    }
+
+    #[tokio::test]
+    async fn pending_deliveries_are_listed_longest_due_first() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let mockdb = &mockdb;
+        let now = common_utils::date_time::now();
+        let insert = |new| async move {
+            mockdb
+                .insert_webhook_delivery(new, MerchantStorageScheme::PostgresOnly)
+                .await
+                .unwrap()
+        };
+        insert(new_delivery("whd_1", "po_1", 5)).await;
+        insert(new_delivery("whd_2", "po_2", 10)).await;
+        insert(new_delivery("whd_3", "po_3", 1)).await;
+        let delivered = insert(new_delivery("whd_4", "po_4", 20)).await;
+        mockdb
+            .update_webhook_delivery(
+                &delivered,
+                PayoutWebhookDeliveryUpdate::Delivered {
+                    status_code: 204,
+                    delivered_at: now,
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        let given_up = insert(new_delivery("whd_5", "po_5", 20)).await;
+        mockdb
+            .update_webhook_delivery(
+                &given_up,
+                PayoutWebhookDeliveryUpdate::AttemptFailed {
+                    status_code: None,
+                    next_retry_at: None,
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+
+        let pending = |older_than, limit| async move {
+            mockdb
+                .find_pending_webhook_deliveries(
+                    older_than,
+                    limit,
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|delivery| delivery.delivery_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pending(now, 10).await, ["whd_2", "whd_1", "whd_3"]);
+        assert_eq!(pending(now, 2).await, ["whd_2", "whd_1"]);
+        assert_eq!(
+            pending(now - time::Duration::minutes(3), 10).await,
+            ["whd_2", "whd_1"]
+        );
+    }
+}
@@ -1,18 +1,36 @@
-use common_utils::errors::CustomResult;
+use std::collections::HashMap;
+
+use common_utils::{errors::CustomResult, pii};
 use data_models::{
     errors::StorageError,
-    payouts::payouts::{Payouts, PayoutsInterface, PayoutsNew, PayoutsUpdate},
+    payouts::{
+        amount_adjustment::PayoutAmountAdjustment,
+        ids::{CustomerId, MerchantId, PayoutId},
+        payout_attempt::{PayoutAttempt, PayoutAttemptNew},
+        payouts::{
+            stale_payout_statuses, PayoutLoadOptions, PayoutPageCursor,
+            PayoutProcessingTimeFilters, Payouts, PayoutsInterface, PayoutsNew, PayoutsUpdate,
+            PersistenceInfo,
+        },
+    },
 };
-use diesel_models::enums as storage_enums;
+use diesel_models::{enums as storage_enums, payout_webhook_event::PayoutWebhookEventNew};
 
 use super::MockDb;
+use crate::{
+    payouts::{
+        balance::{ensure_sufficient_balance, payout_balance_key},
+        payouts::{activate_payouts_in_store, claim_payout_for_retry_in_store},
+    },
+    DataModelExt,
+};
 
 #[async_trait::async_trait]
 impl PayoutsInterface for MockDb {
     async fn find_payout_by_merchant_id_payout_id(
         &self,
-        _merchant_id: &str,
-        _payout_id: &str,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
         _storage_scheme: storage_enums::MerchantStorageScheme,
     ) -> CustomResult<Payouts, StorageError> {
         // TODO: Implement function for `MockDb`
@@ -21,12 +39,44 @@ impl PayoutsInterface for MockDb {
 
     async fn update_payout(
         &self,
-        _this: &Payouts,
-        _payout_update: PayoutsUpdate,
+        this: &Payouts,
+        payout_update: PayoutsUpdate,
         _storage_scheme: storage_enums::MerchantStorageScheme,
     ) -> CustomResult<Payouts, StorageError> {
-        // TODO: Implement function for `MockDb`
-        Err(StorageError::MockDbError)?
+        self.payout_read_only.ensure_writable()?;
+        payout_update.validate_transition(this)?;
+
+        let mut payouts = self.payouts.lock().await;
+        let stored = payouts
+            .iter_mut()
+            .find(|payout| {
+                payout.merchant_id == this.merchant_id && payout.payout_id == this.payout_id
+            })
+            .ok_or_else(|| {
+                StorageError::ValueNotFound(format!(
+                    "No payout available for merchant_id = {} and payout_id = {}",
+                    this.merchant_id, this.payout_id
+                ))
+            })?;
+        if stored.sequence_number != this.sequence_number {
+            return Err(StorageError::StaleUpdate { entity: "payouts" }.into());
+        }
+
+        let amount_adjustment = this.amount_adjustment(&payout_update);
+        let sequence_number = stored.sequence_number + 1;
+        *stored = diesel_models::payouts::Payouts {
+            sequence_number,
+            ..payout_update
+                .to_storage_model()
+                .apply_changeset(stored.clone())
+        };
+        if let Some(adjustment) = amount_adjustment {
+            self.payout_amount_adjustments
+                .lock()
+                .await
+                .push(adjustment.to_storage_model());
+        }
+        Ok(Payouts::from_storage_model(stored.clone()))
     }
 
     async fn insert_payout(
@@ -34,17 +84,2813 @@ impl PayoutsInterface for MockDb {
         _payout: PayoutsNew,
         _storage_scheme: storage_enums::MerchantStorageScheme,
     ) -> CustomResult<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
         // TODO: Implement function for `MockDb`
         Err(StorageError::MockDbError)?
     }
 
     async fn find_optional_payout_by_merchant_id_payout_id(
         &self,
-        _merchant_id: &str,
-        _payout_id: &str,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Option<Payouts>, StorageError> {
+        Ok(self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .find(|payout| *merchant_id == payout.merchant_id && *payout_id == payout.payout_id)
+            .cloned()
+            .map(Payouts::from_storage_model))
+    }
+
+    async fn clone_payout_as_new(
+        &self,
+        _merchant_id: &MerchantId,
+        _source_payout_id: &PayoutId,
+        _new_payout_id: &PayoutId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn update_payout_metadata_path(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _json_path: &[String],
+        _value: pii::SecretSerdeValue,
+        _create_missing: bool,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn check_payout_balance(
+        &self,
+        merchant_id: &MerchantId,
+        amount: i64,
+        currency: storage_enums::Currency,
+    ) -> CustomResult<(), StorageError> {
+        let available = self
+            .payout_balances
+            .lock()
+            .await
+            .get(&payout_balance_key(merchant_id, currency))
+            .copied()
+            .unwrap_or_default();
+        ensure_sufficient_balance(available, amount)
+    }
+
+    async fn distinct_payout_currencies(
+        &self,
+        _merchant_id: &MerchantId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<storage_enums::Currency>, StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn list_payouts_by_source(
+        &self,
+        _merchant_id: &MerchantId,
+        _created_by: storage_enums::PayoutCreationSource,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn list_payouts_by_merchant_id_statuses(
+        &self,
+        merchant_id: &MerchantId,
+        statuses: Vec<storage_enums::PayoutStatus>,
+        limit: i64,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .filter(|payout| {
+                *merchant_id == payout.merchant_id
+                    && (statuses.is_empty() || statuses.contains(&payout.status))
+            })
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .collect::<Vec<_>>();
+        Payouts::sort_for_listing(&mut payouts);
+        payouts.truncate(usize::try_from(limit).unwrap_or_default());
+        Ok(payouts)
+    }
+
+    async fn list_high_risk_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        min_score: i16,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .filter(|payout| {
+                *merchant_id == payout.merchant_id
+                    && payout.is_high_risk
+                    && payout
+                        .risk_score
+                        .map_or(false, |risk_score| risk_score >= min_score)
+            })
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .collect::<Vec<_>>();
+        payouts.sort_by(|first, second| {
+            (second.created_at, &second.payout_id).cmp(&(first.created_at, &first.payout_id))
+        });
+        Ok(payouts)
+    }
+
+    async fn find_latest_payout_by_customer_id(
+        &self,
+        _merchant_id: &MerchantId,
+        _customer_id: &CustomerId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Option<Payouts>, StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn find_optional_payouts_batch(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_ids: &[PayoutId],
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<HashMap<PayoutId, Option<Payouts>>, StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn find_payouts_by_network_token_ref(
+        &self,
+        _merchant_id: &MerchantId,
+        _network_token_ref: &str,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn find_payouts_by_source_payment_id(
+        &self,
+        merchant_id: &MerchantId,
+        source_payment_id: &str,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .filter(|payout| {
+                payout.merchant_id == merchant_id
+                    && payout.source_payment_id.as_deref() == Some(source_payment_id)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        payouts.sort_by(|first, second| {
+            (second.created_at, &second.payout_id).cmp(&(first.created_at, &first.payout_id))
+        });
+        Ok(payouts
+            .into_iter()
+            .map(Payouts::from_storage_model)
+            .collect())
+    }
+
+    async fn insert_split_payouts(
+        &self,
+        parent: PayoutsNew,
+        splits: Vec<PayoutsNew>,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<(Payouts, Vec<Payouts>), StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let splits = parent.link_splits(splits)?;
+        let now = common_utils::date_time::now();
+        let parent = parent.into_payout(now);
+        let splits = splits
+            .into_iter()
+            .map(|split| split.into_payout(now))
+            .collect::<Vec<_>>();
+
+        let mut payouts = self.payouts.lock().await;
+        let inserted = std::iter::once(&parent).chain(&splits).collect::<Vec<_>>();
+        for (index, payout) in inserted.iter().enumerate() {
+            let is_duplicate = payouts.iter().any(|stored| {
+                stored.merchant_id == payout.merchant_id && stored.payout_id == payout.payout_id
+            }) || inserted[..index]
+                .iter()
+                .any(|other| other.payout_id == payout.payout_id);
+            if is_duplicate {
+                return Err(StorageError::DuplicateValue {
+                    entity: "payouts",
+                    key: Some(payout.payout_id.clone()),
+                }
+                .into());
+            }
+        }
+        payouts.extend(
+            inserted
+                .into_iter()
+                .map(|payout| payout.clone().to_storage_model()),
+        );
+        Ok((parent, splits))
+    }
+
+    async fn list_child_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        parent_payout_id: &PayoutId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .filter(|payout| {
+                *merchant_id == payout.merchant_id
+                    && payout.parent_payout_id.as_deref() == Some(&**parent_payout_id)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        payouts.sort_by(|first, second| {
+            (second.created_at, &second.payout_id).cmp(&(first.created_at, &first.payout_id))
+        });
+        Ok(payouts
+            .into_iter()
+            .map(Payouts::from_storage_model)
+            .collect())
+    }
+
+    async fn list_amount_adjustments(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<PayoutAmountAdjustment>, StorageError> {
+        Ok(self
+            .payout_amount_adjustments
+            .lock()
+            .await
+            .iter()
+            .filter(|adjustment| {
+                *merchant_id == adjustment.merchant_id && *payout_id == adjustment.payout_id
+            })
+            .cloned()
+            .map(PayoutAmountAdjustment::from_storage_model)
+            .collect())
+    }
+
+    async fn find_payouts_by_metadata_key(
+        &self,
+        merchant_id: &MerchantId,
+        key: &str,
+        value: &serde_json::Value,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .filter(|payout| *merchant_id == payout.merchant_id)
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .filter(|payout| payout.metadata_contains(key, value))
+            .collect::<Vec<_>>();
+        payouts.sort_by(|first, second| {
+            (second.created_at, &second.payout_id).cmp(&(first.created_at, &first.payout_id))
+        });
+        Ok(payouts)
+    }
+
+    async fn claim_payout_for_retry(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        max_attempts: i16,
+        storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Option<Payouts>, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        claim_payout_for_retry_in_store(
+            self,
+            merchant_id,
+            payout_id,
+            max_attempts,
+            common_utils::date_time::now(),
+            storage_scheme,
+        )
+        .await
+    }
+
+    async fn find_payouts_needing_retry(
+        &self,
+        now: time::PrimitiveDateTime,
+        max_attempts: i16,
+        limit: i64,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .filter(|payout| payout.is_claimable_for_retry(now, max_attempts))
+            .collect::<Vec<_>>();
+        payouts.sort_by(|first, second| {
+            (first.last_modified_at, &first.payout_id)
+                .cmp(&(second.last_modified_at, &second.payout_id))
+        });
+        payouts.truncate(usize::try_from(limit).unwrap_or(0));
+        Ok(payouts)
+    }
+
+    async fn list_payouts_by_status_before(
+        &self,
+        status: storage_enums::PayoutStatus,
+        before: time::PrimitiveDateTime,
+        after_cursor: Option<&PayoutPageCursor>,
+        limit: i64,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .filter(|payout| payout.status == status && payout.last_modified_at < before)
+            .filter(|payout| match after_cursor {
+                Some(cursor) => cursor.precedes(payout),
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        payouts.sort_by(|first, second| {
+            (first.last_modified_at, &first.payout_id)
+                .cmp(&(second.last_modified_at, &second.payout_id))
+        });
+        payouts.truncate(usize::try_from(limit).unwrap_or(0));
+        Ok(payouts)
+    }
+
+    async fn count_payouts_by_status(
+        &self,
+        _merchant_id: &MerchantId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<HashMap<storage_enums::PayoutStatus, i64>, StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn sum_payout_amounts_by_merchant_id(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<i128, StorageError> {
+        let payouts = self.payouts.lock().await;
+        Ok(payouts
+            .iter()
+            .filter(|payout| *merchant_id == payout.merchant_id)
+            .map(|payout| i128::from(payout.amount))
+            .sum())
+    }
+
+    async fn average_payout_processing_seconds(
+        &self,
+        merchant_id: &MerchantId,
+        filters: &PayoutProcessingTimeFilters,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Option<f64>, StorageError> {
+        let payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .filter(|payout| *merchant_id == payout.merchant_id)
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .filter(|payout| filters.matches(payout))
+            .collect::<Vec<_>>();
+        Ok(Payouts::average_processing_seconds(&payouts))
+    }
+
+    async fn find_payout_by_connector_idempotency_key(
+        &self,
+        _merchant_id: &MerchantId,
+        _connector_idempotency_key: &str,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Option<Payouts>, StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn find_payout_by_display_seq(
+        &self,
+        merchant_id: &MerchantId,
+        display_seq: i64,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Payouts, StorageError> {
+        let payouts = self.payouts.lock().await;
+        let stored = payouts
+            .iter()
+            .find(|payout| *merchant_id == payout.merchant_id && payout.display_seq == display_seq)
+            .ok_or(StorageError::ValueNotFound(format!(
+                "payout with display sequence {display_seq}"
+            )))?;
+        Ok(Payouts::from_storage_model(stored.clone()))
+    }
+
+    async fn diff_payouts_since(
+        &self,
+        _merchant_id: &MerchantId,
+        _since: time::PrimitiveDateTime,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn list_payouts_modified_by(
+        &self,
+        merchant_id: &MerchantId,
+        actor: &str,
+        since: time::PrimitiveDateTime,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .filter(|payout| {
+                *merchant_id == payout.merchant_id
+                    && payout.last_modified_by.as_deref() == Some(actor)
+                    && payout.last_modified_at >= since
+            })
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .collect::<Vec<_>>();
+        Payouts::sort_for_listing(&mut payouts);
+        Ok(payouts)
+    }
+
+    async fn find_stale_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        no_change_for: time::Duration,
+        statuses: Vec<storage_enums::PayoutStatus>,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let statuses = stale_payout_statuses(statuses);
+        let unchanged_since = common_utils::date_time::now() - no_change_for;
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .filter(|payout| {
+                *merchant_id == payout.merchant_id
+                    && statuses.contains(&payout.status)
+                    && payout.status_unchanged_since() < unchanged_since
+            })
+            .collect::<Vec<_>>();
+        Payouts::sort_for_listing(&mut payouts);
+        Ok(payouts)
+    }
+
+    async fn find_payout_with_attempts(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<(Payouts, Vec<PayoutAttempt>), StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn recompute_payout_attempt_count(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<i16, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let attempts = self
+            .payout_attempt
+            .lock()
+            .await
+            .iter()
+            .filter(|attempt| {
+                *merchant_id == attempt.merchant_id && *payout_id == attempt.payout_id
+            })
+            .count();
+        let mut payouts = self.payouts.lock().await;
+        let stored = payouts
+            .iter_mut()
+            .find(|payout| *merchant_id == payout.merchant_id && *payout_id == payout.payout_id)
+            .ok_or(StorageError::ValueNotFound(format!(
+                "payout does not exist for key mid_{merchant_id}_po_{payout_id}"
+            )))?;
+        let payout = Payouts::from_storage_model(stored.clone());
+        let attempts = i64::try_from(attempts).unwrap_or(i64::MAX);
+        if let Some(repair) = payout.attempt_count_repair(attempts)? {
+            *stored = repair.to_storage_model().apply_changeset(stored.clone());
+        }
+        Ok(stored.attempt_count)
+    }
+
+    async fn activate_due_payouts(
+        &self,
+        now: time::PrimitiveDateTime,
+        limit: i64,
+        storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<PayoutId>, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .collect();
+        let limit = usize::try_from(limit).unwrap_or_default();
+        Ok(activate_payouts_in_store(self, payouts, now, limit, storage_scheme).await)
+    }
+
+    async fn confirm_payout(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn reroute_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        new_connector: &str,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let mut payouts = self.payouts.lock().await;
+        let stored = payouts
+            .iter_mut()
+            .find(|payout| *merchant_id == payout.merchant_id && *payout_id == payout.payout_id)
+            .ok_or(StorageError::ValueNotFound(format!("payout {payout_id}")))?;
+
+        let payout = Payouts::from_storage_model(stored.clone());
+        let reroute_update = payout.reroute_update(new_connector.to_owned());
+        reroute_update.validate_transition(&payout)?;
+        *stored = reroute_update
+            .to_storage_model()
+            .apply_changeset(stored.clone());
+        Ok(Payouts::from_storage_model(stored.clone()))
+    }
+
+    async fn update_payout_if_status(
+        &self,
+        this: &Payouts,
+        payout: PayoutsUpdate,
         _storage_scheme: storage_enums::MerchantStorageScheme,
     ) -> CustomResult<Option<Payouts>, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        payout.validate_transition(this)?;
+        let mut payouts = self.payouts.lock().await;
+        let Some(stored) = payouts.iter_mut().find(|stored| {
+            this.merchant_id == stored.merchant_id
+                && this.payout_id == stored.payout_id
+                && this.status == stored.status
+        }) else {
+            return Ok(None);
+        };
+        *stored = payout.to_storage_model().apply_changeset(stored.clone());
+        Ok(Some(Payouts::from_storage_model(stored.clone())))
+    }
+
+    async fn insert_payout_with_persistence_info(
+        &self,
+        _payout: PayoutsNew,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<(Payouts, PersistenceInfo), StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn soft_delete_all_payouts_for_merchant(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<usize, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let now = common_utils::date_time::now();
+        let mut payouts = self.payouts.lock().await;
+        let mut deleted = 0;
+        for payout in payouts
+            .iter_mut()
+            .filter(|payout| *merchant_id == payout.merchant_id && payout.deleted_at.is_none())
+        {
+            payout.deleted_at = Some(now);
+            deleted += 1;
+        }
+        Ok(deleted)
+    }
+
+    async fn delete_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<bool, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let mut payouts = self.payouts.lock().await;
+        let count = payouts.len();
+        payouts.retain(|payout| {
+            !(*merchant_id == payout.merchant_id && *payout_id == payout.payout_id)
+        });
+        Ok(payouts.len() < count)
+    }
+
+    async fn backfill_payout_profile_ids(
+        &self,
+        merchant_id: &MerchantId,
+        default_profile_id: &str,
+        _batch_size: i64,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<usize, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let now = common_utils::date_time::now();
+        let mut payouts = self.payouts.lock().await;
+        let mut backfilled = 0;
+        for payout in payouts
+            .iter_mut()
+            .filter(|payout| *merchant_id == payout.merchant_id && payout.profile_id.is_empty())
+        {
+            payout.profile_id = default_profile_id.to_owned();
+            payout.last_modified_at = now;
+            payout.sequence_number += 1;
+            backfilled += 1;
+        }
+        Ok(backfilled)
+    }
+
+    async fn import_payout(
+        &self,
+        _payout: PayoutsNew,
+        _preserve_timestamps: bool,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn update_payouts_status_batch(
+        &self,
+        _merchant_id: &MerchantId,
+        _transitions: Vec<(PayoutId, storage_enums::PayoutStatus)>,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<(PayoutId, CustomResult<(), StorageError>)>, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn get_payout_status(
+        &self,
+        _merchant_id: &MerchantId,
+        _payout_id: &PayoutId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<storage_enums::PayoutStatus, StorageError> {
         // TODO: Implement function for `MockDb`
         Err(StorageError::MockDbError)?
     }
+
+    async fn record_webhook_event_once(
+        &self,
+        connector: &str,
+        event_id: &str,
+    ) -> CustomResult<bool, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let mut events = self.payout_webhook_events.lock().await;
+        if events
+            .iter()
+            .any(|event| event.connector == connector && event.event_id == event_id)
+        {
+            return Ok(false);
+        }
+        events.push(PayoutWebhookEventNew {
+            connector: connector.to_owned(),
+            event_id: event_id.to_owned(),
+            created_at: common_utils::date_time::now(),
+        });
+        Ok(true)
+    }
+
+    async fn forget_webhook_event(
+        &self,
+        connector: &str,
+        event_id: &str,
+    ) -> CustomResult<(), StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        self.payout_webhook_events
+            .lock()
+            .await
+            .retain(|event| !(event.connector == connector && event.event_id == event_id));
+        Ok(())
+    }
+
+    async fn get_payout_daily_count(
+        &self,
+        _merchant_id: &MerchantId,
+        _date: time::Date,
+    ) -> CustomResult<i64, StorageError> {
+        // TODO: Implement function for `MockDb`
+        Err(StorageError::MockDbError)?
+    }
+
+    async fn cancel_payout_with_reversal(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        reason: String,
+        reversal: Option<PayoutAttemptNew>,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<(Payouts, Option<PayoutAttempt>), StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        // Both locks are held from the checks to the writes, so that either both apply or neither
+        let mut payouts = self.payouts.lock().await;
+        let mut attempts = self.payout_attempt.lock().await;
+        let stored = payouts
+            .iter_mut()
+            .find(|payout| *merchant_id == payout.merchant_id && *payout_id == payout.payout_id)
+            .ok_or(StorageError::ValueNotFound(format!("payout {payout_id}")))?;
+
+        let cancel_update =
+            Payouts::from_storage_model(stored.clone()).cancel_update(reason, reversal.as_ref())?;
+        let reversal = match reversal {
+            Some(reversal)
+                if attempts.iter().any(|attempt| {
+                    attempt.merchant_id == reversal.merchant_id
+                        && attempt.payout_attempt_id == reversal.payout_attempt_id
+                }) =>
+            {
+                return Err(StorageError::DuplicateValue {
+                    entity: "payout attempt",
+                    key: Some(reversal.payout_attempt_id),
+                }
+                .into());
+            }
+            reversal => {
+                reversal.map(|reversal| reversal.into_attempt(common_utils::date_time::now()))
+            }
+        };
+
+        *stored = cancel_update
+            .to_storage_model()
+            .apply_changeset(stored.clone());
+        if let Some(attempt) = &reversal {
+            attempts.push(attempt.clone().to_storage_model());
+        }
+        Ok((Payouts::from_storage_model(stored.clone()), reversal))
+    }
+
+    async fn find_payout_with_load_options(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        load_options: PayoutLoadOptions,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Payouts, StorageError> {
+        self.payouts
+            .lock()
+            .await
+            .iter()
+            .find(|payout| *merchant_id == payout.merchant_id && *payout_id == payout.payout_id)
+            .map(|payout| load_options.apply(Payouts::from_storage_model(payout.clone())))
+            .ok_or(StorageError::ValueNotFound(format!("payout {payout_id}")))
+            .map_err(Into::into)
+    }
+
+    async fn find_payouts_with_inconsistent_timestamps(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<Vec<Payouts>, StorageError> {
+        let mut payouts = self
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .filter(|payout| *merchant_id == payout.merchant_id)
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .filter(Payouts::has_inconsistent_timestamps)
+            .collect::<Vec<_>>();
+        Payouts::sort_for_listing(&mut payouts);
+        Ok(payouts)
+    }
+
+    async fn repair_payout_timestamps(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: storage_enums::MerchantStorageScheme,
+    ) -> CustomResult<usize, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let mut payouts = self.payouts.lock().await;
+        let mut repaired = 0;
+        for payout in payouts.iter_mut().filter(|payout| {
+            *merchant_id == payout.merchant_id && payout.last_modified_at < payout.created_at
+        }) {
+            payout.last_modified_at = payout.created_at;
+            payout.sequence_number += 1;
+            repaired += 1;
+        }
+        Ok(repaired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use data_models::{
+        errors::StorageError,
+        payouts::{
+            ids::PayoutId,
+            payout_attempt::PayoutAttemptNew,
+            payouts::{
+                PayoutLoadOptions, PayoutPageCursor, PayoutProcessingTimeFilters, Payouts,
+                PayoutsInterface, PayoutsUpdate,
+            },
+        },
+    };
+    use diesel_models::{
+        enums::{MerchantStorageScheme, PayoutStatus},
+        payouts::PayoutsNew,
+    };
+
+    use crate::{
+        payouts::payouts::{activate_payouts_in_store, reconcile_payout_status},
+        DataModelExt, MockDb,
+    };
+
+    fn stored_payout(
+        merchant_id: &str,
+        payout_id: &str,
+        source_payment_id: Option<&str>,
+    ) -> diesel_models::payouts::Payouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(PayoutsNew {
+            payout_id: payout_id.to_string(),
+            merchant_id: merchant_id.to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            source_payment_id: source_payment_id.map(ToString::to_string),
+            ..Default::default()
+        })
+        .unwrap();
+        serde_json::from_value(stored).unwrap()
+    }
+
+    fn stored_attempt(
+        merchant_id: &str,
+        payout_id: &str,
+        payout_attempt_id: &str,
+    ) -> diesel_models::payout_attempt::PayoutAttempt {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(diesel_models::payout_attempt::PayoutAttemptNew {
+            payout_attempt_id: payout_attempt_id.to_string(),
+            payout_id: payout_id.to_string(),
+            merchant_id: merchant_id.to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            ..Default::default()
+        })
+        .unwrap();
+        serde_json::from_value(stored).unwrap()
+    }
+
+    #[tokio::test]
+    async fn payouts_are_found_by_their_source_payment() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb.payouts.lock().await.extend([
+            stored_payout("merchant_1", "po_1", Some("pay_1")),
+            stored_payout("merchant_1", "po_2", Some("pay_1")),
+            stored_payout("merchant_1", "po_3", Some("pay_2")),
+            stored_payout("merchant_1", "po_4", None),
+            stored_payout("merchant_2", "po_5", Some("pay_1")),
+        ]);
+
+        let payouts = mockdb
+            .find_payouts_by_source_payment_id(
+                "merchant_1",
+                "pay_1",
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        let mut payout_ids = payouts
+            .iter()
+            .map(|payout| payout.payout_id.as_str())
+            .collect::<Vec<_>>();
+        payout_ids.sort_unstable();
+        assert_eq!(payout_ids, ["po_1", "po_2"]);
+        assert!(payouts
+            .iter()
+            .all(|payout| payout.source_payment_id.as_deref() == Some("pay_1")));
+
+        assert!(mockdb
+            .find_payouts_by_source_payment_id(
+                "merchant_1",
+                "pay_3",
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn payout_with_future_next_retry_is_excluded_until_due() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let now = common_utils::date_time::now();
+        let retryable = |payout_id: &str, next_retry_at| diesel_models::payouts::Payouts {
+            status: diesel_models::enums::PayoutStatus::Pending,
+            attempt_count: 1,
+            last_modified_at: now - time::Duration::hours(1),
+            next_retry_at,
+            ..stored_payout("merchant_1", payout_id, None)
+        };
+        mockdb.payouts.lock().await.extend([
+            retryable("po_1", None),
+            retryable("po_2", Some(now + time::Duration::minutes(30))),
+            retryable("po_3", Some(now - time::Duration::minutes(1))),
+        ]);
+
+        assert_eq!(
+            payout_ids_needing_retry(&mockdb, now).await,
+            ["po_1", "po_3"]
+        );
+        // Once due, the payout is retried like the others
+        assert_eq!(
+            payout_ids_needing_retry(&mockdb, now + time::Duration::minutes(30)).await,
+            ["po_1", "po_2", "po_3"]
+        );
+    }
+
+    #[tokio::test]
+    async fn high_risk_payouts_are_filtered_by_score() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let assessed = |merchant_id: &str, payout_id: &str, risk_score, is_high_risk| {
+            data_models::payouts::payouts::PayoutsUpdate::RiskUpdate {
+                risk_score,
+                is_high_risk,
+            }
+            .to_storage_model()
+            .apply_changeset(stored_payout(merchant_id, payout_id, None))
+        };
+        let flagged = assessed("merchant_1", "po_1", 90, true);
+        assert_eq!(flagged.risk_score, Some(90));
+        assert!(flagged.is_high_risk);
+        mockdb.payouts.lock().await.extend([
+            flagged,
+            assessed("merchant_1", "po_2", 70, true),
+            assessed("merchant_1", "po_3", 40, true),
+            // Scored above the threshold, but not flagged by the risk engine
+            assessed("merchant_1", "po_4", 95, false),
+            stored_payout("merchant_1", "po_5", None),
+            assessed("merchant_2", "po_6", 99, true),
+        ]);
+
+        let mockdb = &mockdb;
+        let high_risk_payout_ids = |min_score| async move {
+            mockdb
+                .list_high_risk_payouts(
+                    &"merchant_1".into(),
+                    min_score,
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|payout| payout.payout_id)
+                .collect::<Vec<_>>()
+        };
+        let mut above_threshold = high_risk_payout_ids(70).await;
+        above_threshold.sort();
+        assert_eq!(above_threshold, ["po_1", "po_2"]);
+        assert_eq!(high_risk_payout_ids(91).await, Vec::<String>::new());
+    }
+
+    #[tokio::test]
+    async fn pages_by_status_cover_every_payout_once() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let before = common_utils::date_time::now();
+        // Payouts share their modification time in groups of three, so that pages are cut
+        // between payouts modified at the same time
+        let payout = |index: i64, status| diesel_models::payouts::Payouts {
+            status,
+            last_modified_at: before - time::Duration::minutes(100 - index / 3),
+            ..stored_payout("merchant_1", &format!("po_{index:02}"), None)
+        };
+        let mut expected = Vec::new();
+        for index in 0..60 {
+            let status = if index % 4 == 0 {
+                PayoutStatus::Success
+            } else {
+                PayoutStatus::Pending
+            };
+            let stored = payout(index, status);
+            if status == PayoutStatus::Pending {
+                expected.push(stored.payout_id.clone());
+            }
+            mockdb.payouts.lock().await.push(stored);
+        }
+        // Modified after the window
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(diesel_models::payouts::Payouts {
+                last_modified_at: before + time::Duration::minutes(1),
+                ..payout(60, PayoutStatus::Pending)
+            });
+
+        let mut listed = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = mockdb
+                .list_payouts_by_status_before(
+                    PayoutStatus::Pending,
+                    before,
+                    cursor.as_ref(),
+                    7,
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap();
+            let Some(last) = page.last() else {
+                break;
+            };
+            assert!(page.len() <= 7);
+            cursor = Some(last.page_cursor());
+            listed.extend(page.into_iter().map(|payout| payout.payout_id));
+        }
+        assert_eq!(listed, expected);
+    }
+
+    #[tokio::test]
+    async fn payouts_updated_mid_scan_leave_the_window_without_skips() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let before = common_utils::date_time::now();
+        for index in 0..20 {
+            mockdb
+                .payouts
+                .lock()
+                .await
+                .push(diesel_models::payouts::Payouts {
+                    status: PayoutStatus::Pending,
+                    last_modified_at: before - time::Duration::minutes(50 - index / 2),
+                    ..stored_payout("merchant_1", &format!("po_{index:02}"), None)
+                });
+        }
+        let list = |cursor: Option<PayoutPageCursor>| {
+            let mockdb = &mockdb;
+            async move {
+                mockdb
+                    .list_payouts_by_status_before(
+                        PayoutStatus::Pending,
+                        before,
+                        cursor.as_ref(),
+                        5,
+                        MerchantStorageScheme::PostgresOnly,
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let first_page = list(None).await;
+        let mut listed = first_page
+            .iter()
+            .map(|payout| payout.payout_id.clone())
+            .collect::<Vec<_>>();
+        let mut cursor = first_page.last().map(Payouts::page_cursor);
+        // A listed payout and one still to be listed are updated between pages
+        for updated in ["po_02", "po_12"] {
+            let mut payouts = mockdb.payouts.lock().await;
+            if let Some(payout) = payouts
+                .iter_mut()
+                .find(|payout| payout.payout_id == updated)
+            {
+                payout.last_modified_at = before + time::Duration::seconds(1);
+            }
+        }
+        loop {
+            let page = list(cursor.clone()).await;
+            let Some(last) = page.last() else {
+                break;
+            };
+            cursor = Some(last.page_cursor());
+            listed.extend(page.into_iter().map(|payout| payout.payout_id));
+        }
+
+        let expected = (0..20)
+            .map(|index| format!("po_{index:02}"))
+            .filter(|payout_id| payout_id != "po_12")
+            .collect::<Vec<_>>();
+        assert_eq!(listed, expected);
+    }
+
+    #[tokio::test]
+    async fn each_amount_change_records_one_adjustment() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let stored = diesel_models::payouts::Payouts {
+            amount: 1000,
+            ..stored_payout("merchant_1", "po_1", None)
+        };
+        mockdb.payouts.lock().await.push(stored.clone());
+        let amount_update = |payout: &Payouts, amount| PayoutsUpdate::Update {
+            amount,
+            destination_currency: payout.destination_currency,
+            source_currency: payout.source_currency,
+            description: payout.description.clone(),
+            recurring: payout.recurring,
+            auto_fulfill: payout.auto_fulfill,
+            return_url: payout.return_url.clone(),
+            entity_type: payout.entity_type,
+            metadata: None,
+            profile_id: None,
+            status: None,
+            last_modified_by: None,
+        };
+
+        let mut payout = Payouts::from_storage_model(stored);
+        // The second and third updates leave the amount as is
+        for amount in [Some(1500), Some(1500), None, Some(900)] {
+            let update = match amount {
+                Some(amount) => amount_update(&payout, amount),
+                None => PayoutsUpdate::RecurringUpdate { recurring: true },
+            };
+            payout = mockdb
+                .update_payout(&payout, update, MerchantStorageScheme::PostgresOnly)
+                .await
+                .unwrap();
+        }
+        assert_eq!(payout.amount, 900);
+
+        let adjustments = mockdb
+            .list_amount_adjustments(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            adjustments
+                .iter()
+                .map(|adjustment| (adjustment.old_amount, adjustment.new_amount))
+                .collect::<Vec<_>>(),
+            [(1000, 1500), (1500, 900)]
+        );
+        assert!(adjustments
+            .iter()
+            .all(|adjustment| adjustment.payout_id == "po_1"));
+
+        // A stale update changes nothing, and records no adjustment
+        let stale = Payouts {
+            sequence_number: payout.sequence_number - 1,
+            ..payout.clone()
+        };
+        assert!(matches!(
+            mockdb
+                .update_payout(
+                    &stale,
+                    amount_update(&stale, 2000),
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap_err()
+                .current_context(),
+            StorageError::StaleUpdate { .. }
+        ));
+        assert_eq!(mockdb.payout_amount_adjustments.lock().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn payouts_are_found_by_a_metadata_key() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let with_metadata =
+            |merchant_id: &str, payout_id: &str, metadata| diesel_models::payouts::Payouts {
+                metadata: Some(masking::Secret::new(metadata)),
+                ..stored_payout(merchant_id, payout_id, None)
+            };
+        mockdb.payouts.lock().await.extend([
+            with_metadata(
+                "merchant_1",
+                "po_1",
+                serde_json::json!({ "order_id": "ord_1", "batch": 3 }),
+            ),
+            with_metadata(
+                "merchant_1",
+                "po_2",
+                serde_json::json!({ "order_id": "ord_2" }),
+            ),
+            with_metadata(
+                "merchant_1",
+                "po_3",
+                serde_json::json!({ "batch": "ord_1" }),
+            ),
+            stored_payout("merchant_1", "po_4", None),
+            with_metadata(
+                "merchant_2",
+                "po_5",
+                serde_json::json!({ "order_id": "ord_1" }),
+            ),
+        ]);
+
+        let mockdb = &mockdb;
+        let matching = |key: &'static str, value: serde_json::Value| async move {
+            mockdb
+                .find_payouts_by_metadata_key(
+                    &"merchant_1".into(),
+                    key,
+                    &value,
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|payout| payout.payout_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            matching("order_id", serde_json::json!("ord_1")).await,
+            ["po_1"]
+        );
+        assert_eq!(matching("batch", serde_json::json!(3)).await, ["po_1"]);
+        // The value is matched with its type
+        assert!(matching("batch", serde_json::json!("3")).await.is_empty());
+        assert!(matching("order_id", serde_json::json!("ord_3"))
+            .await
+            .is_empty());
+        assert!(matching("customer_id", serde_json::json!("ord_1"))
+            .await
+            .is_empty());
+    }
+
+    async fn payout_ids_needing_retry(
+        mockdb: &MockDb,
+        now: time::PrimitiveDateTime,
+    ) -> Vec<String> {
+        mockdb
+            .find_payouts_needing_retry(now, 3, 10, MerchantStorageScheme::PostgresOnly)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|payout| payout.payout_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reroute_changes_all_connector_fields_together() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(diesel_models::payouts::Payouts {
+                status: diesel_models::enums::PayoutStatus::Pending,
+                attempt_count: 1,
+                connector: Some("adyen".to_string()),
+                connector_payout_id: Some("adyen_po_1".to_string()),
+                connector_response: Some(masking::Secret::new(
+                    serde_json::json!({ "resultCode": "Refused" }),
+                )),
+                connector_idempotency_key: Some("idem_1".to_string()),
+                ..stored_payout("merchant_1", "po_1", None)
+            });
+
+        let rerouted = mockdb
+            .reroute_payout(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                "wise",
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(rerouted.connector.as_deref(), Some("wise"));
+        assert_eq!(rerouted.connector_payout_id, None);
+        assert!(rerouted.connector_response.is_none());
+        assert_eq!(rerouted.attempt_count, 2);
+        assert!(rerouted.connector_idempotency_key.is_some());
+        assert_ne!(
+            rerouted.connector_idempotency_key.as_deref(),
+            Some("idem_1")
+        );
+        assert_eq!(rerouted.sequence_number, 1);
+
+        let stored = mockdb.payouts.lock().await.first().cloned().unwrap();
+        assert_eq!(stored.connector.as_deref(), Some("wise"));
+        assert_eq!(
+            stored.connector_idempotency_key,
+            rerouted.connector_idempotency_key
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_reroute_leaves_payout_unchanged() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let held = diesel_models::payouts::Payouts {
+            status: diesel_models::enums::PayoutStatus::HeldForReview,
+            attempt_count: 1,
+            connector: Some("adyen".to_string()),
+            connector_payout_id: Some("adyen_po_1".to_string()),
+            connector_idempotency_key: Some("idem_1".to_string()),
+            ..stored_payout("merchant_1", "po_1", None)
+        };
+        mockdb.payouts.lock().await.push(held.clone());
+
+        assert!(mockdb
+            .reroute_payout(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                "wise",
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .is_err());
+        // None of the fields is changed, not even the ones the rejection doesn't depend on
+        assert_eq!(mockdb.payouts.lock().await.first(), Some(&held));
+
+        assert!(mockdb
+            .reroute_payout(
+                &"merchant_2".into(),
+                &"po_1".into(),
+                "wise",
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .is_err());
+        assert_eq!(mockdb.payouts.lock().await.first(), Some(&held));
+    }
+
+    #[tokio::test]
+    async fn webhook_event_is_recorded_once() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+
+        assert!(mockdb
+            .record_webhook_event_once("adyen", "evt_1")
+            .await
+            .unwrap());
+        assert!(!mockdb
+            .record_webhook_event_once("adyen", "evt_1")
+            .await
+            .unwrap());
+
+        // Event ids are only unique per connector
+        assert!(mockdb
+            .record_webhook_event_once("wise", "evt_1")
+            .await
+            .unwrap());
+        assert!(mockdb
+            .record_webhook_event_once("adyen", "evt_2")
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn webhook_event_which_failed_to_apply_is_applied_when_redelivered() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(diesel_models::payouts::Payouts {
+                status: PayoutStatus::HeldForReview,
+                ..stored_payout("merchant_1", "po_1", None)
+            });
+        let merchant_id = data_models::payouts::ids::MerchantId::from("merchant_1");
+        let payout_id = data_models::payouts::ids::PayoutId::from("po_1");
+        let deliver = || {
+            reconcile_payout_status(
+                &mockdb,
+                "adyen",
+                "evt_1",
+                &merchant_id,
+                &payout_id,
+                PayoutStatus::Success,
+                MerchantStorageScheme::PostgresOnly,
+            )
+        };
+
+        // A held payout cannot be moved to success, the update fails
+        assert!(deliver().await.is_err());
+        assert!(mockdb.payout_webhook_events.lock().await.is_empty());
+
+        if let Some(payout) = mockdb.payouts.lock().await.first_mut() {
+            payout.status = PayoutStatus::Pending;
+        }
+        let applied = deliver().await.unwrap().unwrap();
+        assert_eq!(applied.status, PayoutStatus::Success);
+
+        // Once applied, the event is not applied again
+        assert!(deliver().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn diverged_attempt_count_is_recomputed_from_attempts() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb.payouts.lock().await.extend([
+            diesel_models::payouts::Payouts {
+                attempt_count: 5,
+                ..stored_payout("merchant_1", "po_1", None)
+            },
+            diesel_models::payouts::Payouts {
+                attempt_count: 1,
+                ..stored_payout("merchant_1", "po_2", None)
+            },
+        ]);
+        mockdb.payout_attempt.lock().await.extend([
+            stored_attempt("merchant_1", "po_1", "po_1_1"),
+            stored_attempt("merchant_1", "po_1", "po_1_2"),
+            stored_attempt("merchant_1", "po_2", "po_2_1"),
+            // The attempts of another merchant's payout with the same id are not counted
+            stored_attempt("merchant_2", "po_1", "po_1_1"),
+        ]);
+
+        assert_eq!(
+            mockdb
+                .recompute_payout_attempt_count(
+                    &"merchant_1".into(),
+                    &"po_1".into(),
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap(),
+            2
+        );
+        let before = mockdb.payouts.lock().await.clone();
+        assert_eq!(
+            mockdb
+                .recompute_payout_attempt_count(
+                    &"merchant_1".into(),
+                    &"po_2".into(),
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap(),
+            1
+        );
+
+        let payouts = mockdb.payouts.lock().await;
+        assert_eq!(payouts.first().unwrap().attempt_count, 2);
+        // A count which is right already is left untouched
+        assert_eq!(*payouts, before);
+    }
+
+    /// A store holding a single payout, `po_1` of `merchant_1`, with payout writes switched off.
+    async fn read_only_mockdb() -> MockDb {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(stored_payout("merchant_1", "po_1", None));
+        mockdb.payout_read_only.set(true);
+        mockdb
+    }
+
+    /// Asserts that the write was rejected for the store being read only, leaving `po_1` as it
+    /// was stored by [`read_only_mockdb`].
+    async fn assert_rejected_as_read_only<T: std::fmt::Debug>(
+        mockdb: &MockDb,
+        result: error_stack::Result<T, StorageError>,
+    ) {
+        assert!(matches!(
+            result.unwrap_err().current_context(),
+            StorageError::ReadOnlyMode
+        ));
+        let payouts = mockdb.payouts.lock().await;
+        assert_eq!(payouts.len(), 1);
+        assert_eq!(payouts.first().unwrap().sequence_number, 0);
+    }
+
+    #[tokio::test]
+    async fn clone_payout_as_new_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let result = mockdb
+            .clone_payout_as_new(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                &"po_2".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn update_payout_metadata_path_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let result = mockdb
+            .update_payout_metadata_path(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                &["key".to_string()],
+                masking::Secret::new(serde_json::json!("value")),
+                true,
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn claim_payout_for_retry_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let result = mockdb
+            .claim_payout_for_retry(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                3,
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn recompute_payout_attempt_count_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let result = mockdb
+            .recompute_payout_attempt_count(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn activate_due_payouts_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let result = mockdb
+            .activate_due_payouts(
+                common_utils::date_time::now(),
+                10,
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn confirm_payout_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let result = mockdb
+            .confirm_payout(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn reroute_payout_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let result = mockdb
+            .reroute_payout(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                "adyen",
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn update_payout_if_status_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let this =
+            Payouts::from_storage_model(mockdb.payouts.lock().await.first().cloned().unwrap());
+        let result = mockdb
+            .update_payout_if_status(
+                &this,
+                PayoutsUpdate::StatusUpdate {
+                    status: PayoutStatus::Success,
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn insert_payout_with_persistence_info_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let new = data_models::payouts::payouts::PayoutsNew {
+            payout_id: "po_2".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            ..Default::default()
+        };
+        let result = mockdb
+            .insert_payout_with_persistence_info(new, MerchantStorageScheme::PostgresOnly)
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn import_payout_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let new = data_models::payouts::payouts::PayoutsNew {
+            payout_id: "po_2".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            ..Default::default()
+        };
+        let result = mockdb
+            .import_payout(new, true, MerchantStorageScheme::PostgresOnly)
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn update_payouts_status_batch_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let result = mockdb
+            .update_payouts_status_batch(
+                &"merchant_1".into(),
+                vec![("po_1".into(), PayoutStatus::Success)],
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+    }
+
+    #[tokio::test]
+    async fn forget_webhook_event_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        mockdb.payout_webhook_events.lock().await.push(
+            diesel_models::payout_webhook_event::PayoutWebhookEventNew {
+                connector: "adyen".to_string(),
+                event_id: "evt_1".to_string(),
+                created_at: common_utils::date_time::now(),
+            },
+        );
+        let result = mockdb.forget_webhook_event("adyen", "evt_1").await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+        assert_eq!(mockdb.payout_webhook_events.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn record_webhook_event_once_is_rejected_while_read_only() {
+        let mockdb = read_only_mockdb().await;
+        let result = mockdb.record_webhook_event_once("adyen", "evt_1").await;
+        assert_rejected_as_read_only(&mockdb, result).await;
+        assert!(mockdb.payout_webhook_events.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn writes_are_rejected_while_read_only() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let stored = stored_payout("merchant_1", "po_1", Some("pay_1"));
+        mockdb.payouts.lock().await.push(stored.clone());
+        mockdb.payout_read_only.set(true);
+
+        let is_read_only = |error: error_stack::Report<StorageError>| {
+            matches!(error.current_context(), StorageError::ReadOnlyMode)
+        };
+        let new = data_models::payouts::payouts::PayoutsNew {
+            payout_id: "po_2".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            ..Default::default()
+        };
+        assert!(is_read_only(
+            mockdb
+                .insert_payout(new, MerchantStorageScheme::PostgresOnly)
+                .await
+                .unwrap_err()
+        ));
+        assert!(is_read_only(
+            mockdb
+                .update_payout(
+                    &Payouts::from_storage_model(stored.clone()),
+                    PayoutsUpdate::StatusUpdate {
+                        status: PayoutStatus::Success,
+                    },
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap_err()
+        ));
+        assert!(is_read_only(
+            mockdb
+                .soft_delete_all_payouts_for_merchant(
+                    &"merchant_1".into(),
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap_err()
+        ));
+        assert_eq!(mockdb.payouts.lock().await.as_slice(), [stored]);
+
+        // Reads keep working
+        assert_eq!(
+            mockdb
+                .find_payouts_by_source_payment_id(
+                    "merchant_1",
+                    "pay_1",
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Writes are accepted again once the maintenance is over
+        mockdb.payout_read_only.set(false);
+        assert_eq!(
+            mockdb
+                .soft_delete_all_payouts_for_merchant(
+                    &"merchant_1".into(),
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn payouts_without_a_profile_are_backfilled_once() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let with_profile = diesel_models::payouts::Payouts {
+            profile_id: "pro_existing".to_string(),
+            ..stored_payout("merchant_1", "po_3", None)
+        };
+        mockdb.payouts.lock().await.extend([
+            stored_payout("merchant_1", "po_1", None),
+            stored_payout("merchant_1", "po_2", None),
+            with_profile,
+            stored_payout("merchant_2", "po_4", None),
+        ]);
+        let backfill = || {
+            mockdb.backfill_payout_profile_ids(
+                &"merchant_1".into(),
+                "pro_default",
+                1,
+                MerchantStorageScheme::PostgresOnly,
+            )
+        };
+
+        assert_eq!(backfill().await.unwrap(), 2);
+        let profile_ids = mockdb
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .map(|payout| (payout.payout_id.clone(), payout.profile_id.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            profile_ids,
+            [
+                ("po_1".to_string(), "pro_default".to_string()),
+                ("po_2".to_string(), "pro_default".to_string()),
+                ("po_3".to_string(), "pro_existing".to_string()),
+                ("po_4".to_string(), String::new()),
+            ]
+        );
+
+        assert_eq!(backfill().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn payouts_are_listed_by_any_of_several_statuses() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let with_status = |payout_id, status| diesel_models::payouts::Payouts {
+            status,
+            ..stored_payout("merchant_1", payout_id, None)
+        };
+        mockdb.payouts.lock().await.extend([
+            with_status("po_1", PayoutStatus::Pending),
+            with_status("po_2", PayoutStatus::Scheduled),
+            with_status("po_3", PayoutStatus::Success),
+            with_status("po_4", PayoutStatus::RequiresFulfillment),
+            diesel_models::payouts::Payouts {
+                status: PayoutStatus::Pending,
+                ..stored_payout("merchant_2", "po_5", None)
+            },
+        ]);
+        let mockdb = &mockdb;
+        let list = |statuses, limit| async move {
+            mockdb
+                .list_payouts_by_merchant_id_statuses(
+                    &"merchant_1".into(),
+                    statuses,
+                    limit,
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|payout| payout.payout_id)
+                .collect::<Vec<_>>()
+        };
+
+        let active = vec![
+            PayoutStatus::Pending,
+            PayoutStatus::Scheduled,
+            PayoutStatus::RequiresFulfillment,
+        ];
+        assert_eq!(list(active.clone(), 10).await, ["po_4", "po_2", "po_1"]);
+        assert_eq!(list(active, 2).await, ["po_4", "po_2"]);
+
+        // Without statuses every payout of the merchant is listed
+        assert_eq!(list(Vec::new(), 10).await, ["po_4", "po_3", "po_2", "po_1"]);
+    }
+
+    #[tokio::test]
+    async fn payouts_are_listed_by_the_operator_who_modified_them() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let now = common_utils::date_time::now();
+        mockdb.payouts.lock().await.extend([
+            stored_payout("merchant_1", "po_1", None),
+            stored_payout("merchant_1", "po_2", None),
+            stored_payout("merchant_1", "po_3", None),
+            // Modified by the operator before the window
+            diesel_models::payouts::Payouts {
+                last_modified_by: Some("operator_1".to_string()),
+                last_modified_at: now - time::Duration::hours(2),
+                ..stored_payout("merchant_1", "po_4", None)
+            },
+            stored_payout("merchant_2", "po_5", None),
+        ]);
+        let mockdb = &mockdb;
+        let update_by =
+            |merchant_id: &'static str, payout_id: &'static str, actor: Option<&str>| {
+                let actor = actor.map(ToString::to_string);
+                async move {
+                    let stored = mockdb
+                        .payouts
+                        .lock()
+                        .await
+                        .iter()
+                        .find(|payout| {
+                            payout.merchant_id == merchant_id && payout.payout_id == payout_id
+                        })
+                        .cloned()
+                        .unwrap();
+                    let payout = Payouts::from_storage_model(stored);
+                    let update = match actor {
+                        Some(actor) => PayoutsUpdate::Update {
+                            amount: payout.amount,
+                            destination_currency: payout.destination_currency,
+                            source_currency: payout.source_currency,
+                            description: payout.description.clone(),
+                            recurring: payout.recurring,
+                            auto_fulfill: payout.auto_fulfill,
+                            return_url: payout.return_url.clone(),
+                            entity_type: payout.entity_type,
+                            metadata: None,
+                            profile_id: None,
+                            status: None,
+                            last_modified_by: Some(actor),
+                        },
+                        None => PayoutsUpdate::RecurringUpdate { recurring: true },
+                    };
+                    mockdb
+                        .update_payout(&payout, update, MerchantStorageScheme::PostgresOnly)
+                        .await
+                        .unwrap()
+                }
+            };
+
+        let updated = update_by("merchant_1", "po_1", Some("operator_1")).await;
+        assert_eq!(updated.last_modified_by.as_deref(), Some("operator_1"));
+        // An update which does not name its operator keeps the last one
+        update_by("merchant_1", "po_2", Some("operator_1")).await;
+        let updated = update_by("merchant_1", "po_2", None).await;
+        assert_eq!(updated.last_modified_by.as_deref(), Some("operator_1"));
+        // Another operator updated the payout since
+        update_by("merchant_1", "po_3", Some("operator_1")).await;
+        update_by("merchant_1", "po_3", Some("operator_2")).await;
+        update_by("merchant_2", "po_5", Some("operator_1")).await;
+
+        let modified_by = |actor, since| async move {
+            let mut payout_ids = mockdb
+                .list_payouts_modified_by(
+                    &"merchant_1".into(),
+                    actor,
+                    since,
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|payout| payout.payout_id)
+                .collect::<Vec<_>>();
+            payout_ids.sort();
+            payout_ids
+        };
+        let hour_ago = now - time::Duration::hours(1);
+        assert_eq!(modified_by("operator_1", hour_ago).await, ["po_1", "po_2"]);
+        assert_eq!(
+            modified_by("operator_1", now - time::Duration::hours(3)).await,
+            ["po_1", "po_2", "po_4"]
+        );
+        assert_eq!(modified_by("operator_2", hour_ago).await, ["po_3"]);
+        assert!(modified_by("operator_3", hour_ago).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn payout_amounts_sum_beyond_i64_max() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb.payouts.lock().await.extend(
+            ["po_1", "po_2", "po_3"]
+                .into_iter()
+                .map(|payout_id| diesel_models::payouts::Payouts {
+                    amount: i64::MAX,
+                    ..stored_payout("merchant_1", payout_id, None)
+                })
+                .chain([stored_payout("merchant_2", "po_4", None)]),
+        );
+
+        let sum = mockdb
+            .sum_payout_amounts_by_merchant_id(
+                &"merchant_1".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(sum, i128::from(i64::MAX) * 3);
+        assert!(sum > i128::from(i64::MAX));
+
+        let sum = mockdb
+            .sum_payout_amounts_by_merchant_id(
+                &"merchant_3".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(sum, 0);
+    }
+
+    #[tokio::test]
+    async fn average_processing_time_of_fulfilled_payouts() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let fulfilled_at = common_utils::date_time::now();
+        let fulfilled =
+            |payout_id: &str, seconds: i64, connector: &str| diesel_models::payouts::Payouts {
+                created_at: fulfilled_at - time::Duration::seconds(seconds),
+                fulfilled_at: Some(fulfilled_at),
+                connector: Some(connector.to_string()),
+                status: PayoutStatus::Success,
+                ..stored_payout("merchant_1", payout_id, None)
+            };
+        mockdb.payouts.lock().await.extend([
+            fulfilled("po_1", 60, "adyen"),
+            fulfilled("po_2", 120, "adyen"),
+            fulfilled("po_3", 300, "wise"),
+            // Not fulfilled, left out of the average
+            stored_payout("merchant_1", "po_4", None),
+            diesel_models::payouts::Payouts {
+                merchant_id: "merchant_2".to_string(),
+                ..fulfilled("po_5", 3600, "adyen")
+            },
+        ]);
+        let average = |filters: PayoutProcessingTimeFilters| {
+            let mockdb = &mockdb;
+            async move {
+                mockdb
+                    .average_payout_processing_seconds(
+                        &"merchant_1".into(),
+                        &filters,
+                        MerchantStorageScheme::PostgresOnly,
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert_eq!(
+            average(PayoutProcessingTimeFilters::default()).await,
+            Some(160.0)
+        );
+        assert_eq!(
+            average(PayoutProcessingTimeFilters {
+                connectors: Some(vec!["adyen".to_string()]),
+                ..Default::default()
+            })
+            .await,
+            Some(90.0)
+        );
+        assert_eq!(
+            average(PayoutProcessingTimeFilters {
+                fulfilled_before: Some(fulfilled_at),
+                ..Default::default()
+            })
+            .await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn payout_moved_to_success_is_stamped_as_fulfilled() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let stored = diesel_models::payouts::Payouts {
+            status: PayoutStatus::Pending,
+            ..stored_payout("merchant_1", "po_1", None)
+        };
+        mockdb.payouts.lock().await.push(stored.clone());
+
+        let payout = Payouts::from_storage_model(stored);
+        assert_eq!(payout.processing_seconds(), None);
+        let fulfilled = mockdb
+            .update_payout(
+                &payout,
+                PayoutsUpdate::StatusUpdate {
+                    status: PayoutStatus::Success,
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(fulfilled.fulfilled_at, Some(fulfilled.last_modified_at));
+        assert!(fulfilled.processing_seconds().unwrap() >= 0.0);
+
+        // Later updates keep the time the payout was fulfilled at
+        let updated = mockdb
+            .update_payout(
+                &fulfilled,
+                PayoutsUpdate::RecurringUpdate { recurring: true },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.fulfilled_at, fulfilled.fulfilled_at);
+    }
+
+    fn reversal_attempt(payout_attempt_id: &str) -> PayoutAttemptNew {
+        PayoutAttemptNew {
+            payout_attempt_id: payout_attempt_id.to_string(),
+            payout_id: "po_1".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            status: PayoutStatus::Cancelled,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn payout_is_cancelled_without_a_reversal() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(diesel_models::payouts::Payouts {
+                status: PayoutStatus::Pending,
+                ..stored_payout("merchant_1", "po_1", None)
+            });
+
+        let (cancelled, reversal) = mockdb
+            .cancel_payout_with_reversal(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                "requested by the merchant".to_string(),
+                None,
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status, PayoutStatus::Cancelled);
+        assert_eq!(
+            cancelled.cancellation_reason.as_deref(),
+            Some("requested by the merchant")
+        );
+        assert!(reversal.is_none());
+        assert!(mockdb.payout_attempt.lock().await.is_empty());
+
+        // A cancelled payout cannot be cancelled again
+        let error = mockdb
+            .cancel_payout_with_reversal(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                "requested twice".to_string(),
+                None,
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InvalidValue { field: "status" }
+        ));
+    }
+
+    #[tokio::test]
+    async fn payout_is_cancelled_along_its_reversal() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(diesel_models::payouts::Payouts {
+                status: PayoutStatus::Pending,
+                ..stored_payout("merchant_1", "po_1", None)
+            });
+
+        let (cancelled, reversal) = mockdb
+            .cancel_payout_with_reversal(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                "requested by the merchant".to_string(),
+                Some(reversal_attempt("po_1_2")),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status, PayoutStatus::Cancelled);
+        assert_eq!(reversal.unwrap().payout_attempt_id, "po_1_2");
+
+        let attempts = mockdb.payout_attempt.lock().await;
+        assert_eq!(
+            attempts
+                .iter()
+                .map(|attempt| attempt.payout_attempt_id.as_str())
+                .collect::<Vec<_>>(),
+            ["po_1_2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn cancellation_is_rolled_back_when_the_reversal_fails() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let payout = diesel_models::payouts::Payouts {
+            status: PayoutStatus::Pending,
+            ..stored_payout("merchant_1", "po_1", None)
+        };
+        mockdb.payouts.lock().await.push(payout.clone());
+        mockdb
+            .payout_attempt
+            .lock()
+            .await
+            .push(stored_attempt("merchant_1", "po_1", "po_1_1"));
+
+        // The reversal reuses the id of an existing attempt, its insert fails
+        let error = mockdb
+            .cancel_payout_with_reversal(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                "requested by the merchant".to_string(),
+                Some(reversal_attempt("po_1_1")),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::DuplicateValue { .. }
+        ));
+        assert_eq!(mockdb.payouts.lock().await.first(), Some(&payout));
+        assert_eq!(mockdb.payout_attempt.lock().await.len(), 1);
+
+        // Neither is a reversal of another payout accepted
+        let error = mockdb
+            .cancel_payout_with_reversal(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                "requested by the merchant".to_string(),
+                Some(PayoutAttemptNew {
+                    payout_id: "po_2".to_string(),
+                    ..reversal_attempt("po_2_1")
+                }),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InvalidValue { field: "payout_id" }
+        ));
+        assert_eq!(mockdb.payouts.lock().await.first(), Some(&payout));
+    }
+
+    #[tokio::test]
+    async fn payouts_with_skewed_timestamps_are_found_and_repaired_once() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let skewed = |merchant_id: &str, payout_id: &str| {
+            let payout = stored_payout(merchant_id, payout_id, None);
+            diesel_models::payouts::Payouts {
+                last_modified_at: payout.created_at - time::Duration::seconds(30),
+                ..payout
+            }
+        };
+        mockdb.payouts.lock().await.extend([
+            skewed("merchant_1", "po_1"),
+            stored_payout("merchant_1", "po_2", None),
+            skewed("merchant_2", "po_3"),
+        ]);
+
+        let inconsistent = mockdb
+            .find_payouts_with_inconsistent_timestamps(
+                &"merchant_1".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            inconsistent
+                .iter()
+                .map(|payout| payout.payout_id.as_str())
+                .collect::<Vec<_>>(),
+            ["po_1"]
+        );
+
+        let repair = || {
+            mockdb
+                .repair_payout_timestamps(&"merchant_1".into(), MerchantStorageScheme::PostgresOnly)
+        };
+        assert_eq!(repair().await.unwrap(), 1);
+        let stored = mockdb.payouts.lock().await.clone();
+        let repaired = stored
+            .iter()
+            .find(|payout| payout.payout_id == "po_1")
+            .unwrap();
+        assert_eq!(repaired.last_modified_at, repaired.created_at);
+        assert_eq!(repaired.sequence_number, 1);
+        // Other merchants are left as is
+        assert!(stored.iter().any(
+            |payout| payout.payout_id == "po_3" && payout.last_modified_at < payout.created_at
+        ));
+
+        assert!(mockdb
+            .find_payouts_with_inconsistent_timestamps(
+                &"merchant_1".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(repair().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn heavy_fields_are_only_loaded_when_requested() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(diesel_models::payouts::Payouts {
+                connector_response: Some(masking::Secret::new(
+                    serde_json::json!({ "resultCode": "Authorised" }),
+                )),
+                routing_decision: Some(masking::Secret::new(
+                    serde_json::json!({ "algorithm_id": "algo_1", "score": 0.5 }),
+                )),
+                customer_snapshot: Some(masking::Secret::new(
+                    serde_json::json!({ "customer_id": "cus_1" }),
+                )),
+                ..stored_payout("merchant_1", "po_1", None)
+            });
+
+        let light = mockdb
+            .find_payout_with_load_options(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                PayoutLoadOptions::default(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(light.payout_id, "po_1");
+        assert!(light.connector_response.is_none());
+        assert!(light.routing_decision.is_none());
+        assert!(light.customer_snapshot.is_none());
+
+        let loaded = mockdb
+            .find_payout_with_load_options(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                PayoutLoadOptions {
+                    connector_response: true,
+                    customer_snapshot: true,
+                    ..Default::default()
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert!(loaded.connector_response.is_some());
+        assert!(loaded.routing_decision.is_none());
+        assert!(loaded.customer_snapshot.is_some());
+
+        let stored = mockdb.payouts.lock().await.first().cloned().unwrap();
+        assert_eq!(
+            mockdb
+                .find_payout_with_load_options(
+                    &"merchant_1".into(),
+                    &"po_1".into(),
+                    PayoutLoadOptions::ALL,
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap(),
+            Payouts::from_storage_model(stored)
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_payout_tolerates_missing_payouts() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let mut payouts = mockdb.payouts.lock().await;
+        payouts.push(stored_payout("merchant_1", "po_1", None));
+        payouts.push(stored_payout("merchant_1", "po_2", None));
+        drop(payouts);
+        let mockdb = &mockdb;
+        let delete = |payout_id: &'static str| async move {
+            mockdb
+                .delete_payout(
+                    &"merchant_1".into(),
+                    &payout_id.into(),
+                    MerchantStorageScheme::PostgresOnly,
+                )
+                .await
+                .unwrap()
+        };
+
+        assert!(delete("po_1").await);
+        assert_eq!(mockdb.payouts.lock().await.len(), 1);
+        // Deleting it again is a no-op, as is deleting a payout which never existed
+        assert!(!delete("po_1").await);
+        assert!(!delete("po_unknown").await);
+
+        let remaining = mockdb.payouts.lock().await;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].payout_id, "po_2");
+    }
+
+    fn new_payout(payout_id: &str, amount: i64) -> data_models::payouts::payouts::PayoutsNew {
+        data_models::payouts::payouts::PayoutsNew {
+            payout_id: payout_id.to_string(),
+            merchant_id: "merchant_1".to_string(),
+            amount,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn split_payouts_are_listed_under_their_parent() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+
+        let (parent, splits) = mockdb
+            .insert_split_payouts(
+                new_payout("po_parent", 1000),
+                vec![new_payout("po_split_1", 600), new_payout("po_split_2", 400)],
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(parent.parent_payout_id, None);
+        assert!(splits
+            .iter()
+            .all(|split| split.parent_payout_id.as_deref() == Some("po_parent")));
+
+        let children = mockdb
+            .list_child_payouts(
+                &"merchant_1".into(),
+                &"po_parent".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|payout| payout.payout_id)
+            .collect::<Vec<_>>();
+        assert_eq!(children, ["po_split_2", "po_split_1"]);
+        assert!(mockdb
+            .list_child_payouts(
+                &"merchant_1".into(),
+                &"po_split_1".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn split_payouts_are_inserted_all_or_none() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(stored_payout("merchant_1", "po_existing", None));
+
+        let error = mockdb
+            .insert_split_payouts(
+                new_payout("po_parent", 1000),
+                vec![new_payout("po_split_1", 600), new_payout("po_split_2", 300)],
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InvalidValue { field: "amount" }
+        ));
+
+        let error = mockdb
+            .insert_split_payouts(
+                new_payout("po_parent", 1000),
+                vec![
+                    new_payout("po_split_1", 600),
+                    new_payout("po_existing", 400),
+                ],
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::DuplicateValue { .. }
+        ));
+        assert_eq!(mockdb.payouts.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn find_stale_payouts_skips_recent_and_terminal_payouts() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let now = common_utils::date_time::now();
+        let stored = |payout_id, status, age: time::Duration| diesel_models::payouts::Payouts {
+            status,
+            last_modified_at: now - age,
+            status_changed_at: Some(now - age),
+            ..stored_payout("merchant_1", payout_id, None)
+        };
+        mockdb.payouts.lock().await.extend([
+            stored("po_stuck", PayoutStatus::Pending, time::Duration::hours(3)),
+            stored(
+                "po_recent",
+                PayoutStatus::Pending,
+                time::Duration::minutes(5),
+            ),
+            stored("po_failed", PayoutStatus::Failed, time::Duration::hours(3)),
+            stored(
+                "po_review",
+                PayoutStatus::HeldForReview,
+                time::Duration::days(1),
+            ),
+        ]);
+
+        let stale = mockdb
+            .find_stale_payouts(
+                &"merchant_1".into(),
+                time::Duration::hours(1),
+                vec![
+                    PayoutStatus::Pending,
+                    PayoutStatus::HeldForReview,
+                    PayoutStatus::Failed,
+                ],
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|payout| payout.payout_id)
+            .collect::<Vec<_>>();
+        assert_eq!(stale.len(), 2);
+        assert!(stale.contains(&"po_stuck".to_string()));
+        assert!(stale.contains(&"po_review".to_string()));
+
+        // Only terminal statuses asked for, none of which can be stale
+        assert!(mockdb
+            .find_stale_payouts(
+                &"merchant_1".into(),
+                time::Duration::hours(1),
+                vec![PayoutStatus::Failed],
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn only_status_changes_refresh_stale_payouts() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let two_hours_ago = common_utils::date_time::now() - time::Duration::hours(2);
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(diesel_models::payouts::Payouts {
+                status: PayoutStatus::Pending,
+                created_at: two_hours_ago,
+                last_modified_at: two_hours_ago,
+                ..stored_payout("merchant_1", "po_1", None)
+            });
+        let merchant_id = data_models::payouts::ids::MerchantId::from("merchant_1");
+        let find_stale = |status| {
+            mockdb.find_stale_payouts(
+                &merchant_id,
+                time::Duration::hours(1),
+                vec![status],
+                MerchantStorageScheme::PostgresOnly,
+            )
+        };
+        // Never changed status, stuck since its creation
+        assert_eq!(find_stale(PayoutStatus::Pending).await.unwrap().len(), 1);
+
+        let payout = mockdb
+            .find_payout_by_merchant_id_payout_id(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        let payout = mockdb
+            .update_payout(
+                &payout,
+                PayoutsUpdate::RecurringUpdate { recurring: true },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(payout.status_changed_at, None);
+        // Updated just now, but still in the same status
+        assert_eq!(find_stale(PayoutStatus::Pending).await.unwrap().len(), 1);
+
+        let payout = mockdb
+            .update_payout(
+                &payout,
+                PayoutsUpdate::StatusUpdate {
+                    status: PayoutStatus::RequiresFulfillment,
+                },
+                MerchantStorageScheme::PostgresOnly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(payout.status_changed_at, Some(payout.last_modified_at));
+        assert!(find_stale(PayoutStatus::RequiresFulfillment)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn retry_claim_is_read_back_from_the_store() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .push(diesel_models::payouts::Payouts {
+                status: PayoutStatus::Pending,
+                last_modified_at: common_utils::date_time::now() - time::Duration::minutes(10),
+                ..stored_payout("merchant_1", "po_1", None)
+            });
+        let before = mockdb
+            .find_optional_payout_by_merchant_id_payout_id(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                MerchantStorageScheme::RedisKv,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        let claimed = mockdb
+            .claim_payout_for_retry(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                3,
+                MerchantStorageScheme::RedisKv,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let after = mockdb
+            .find_optional_payout_by_merchant_id_payout_id(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                MerchantStorageScheme::RedisKv,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(after, claimed);
+        assert_eq!(after.attempt_count, before.attempt_count + 1);
+        assert_eq!(after.sequence_number, before.sequence_number + 1);
+
+        // The claim holds a lease, and an update prepared before it is rejected rather than
+        // overwriting it
+        assert!(mockdb
+            .claim_payout_for_retry(
+                &"merchant_1".into(),
+                &"po_1".into(),
+                3,
+                MerchantStorageScheme::RedisKv,
+            )
+            .await
+            .unwrap()
+            .is_none());
+        let error = mockdb
+            .update_payout(
+                &before,
+                PayoutsUpdate::RecurringUpdate { recurring: true },
+                MerchantStorageScheme::RedisKv,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::StaleUpdate { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn due_payouts_are_activated_past_a_stale_one() {
+        #[allow(clippy::expect_used)]
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create Mock store");
+        let now = common_utils::date_time::now();
+        let due = |payout_id| diesel_models::payouts::Payouts {
+            status: PayoutStatus::Scheduled,
+            scheduled_at: Some(now - time::Duration::minutes(1)),
+            ..stored_payout("merchant_1", payout_id, None)
+        };
+        mockdb
+            .payouts
+            .lock()
+            .await
+            .extend([due("po_1"), due("po_2"), due("po_3")]);
+        let read = mockdb
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .cloned()
+            .map(Payouts::from_storage_model)
+            .collect::<Vec<_>>();
+        // po_2 is updated once read, its activation is stale
+        if let Some(payout) = mockdb
+            .payouts
+            .lock()
+            .await
+            .iter_mut()
+            .find(|payout| payout.payout_id == "po_2")
+        {
+            payout.sequence_number += 1;
+        }
+
+        let activated =
+            activate_payouts_in_store(&mockdb, read, now, 10, MerchantStorageScheme::RedisKv).await;
+        assert_eq!(activated, ["po_1", "po_3"].map(PayoutId::from));
+        let statuses = mockdb
+            .payouts
+            .lock()
+            .await
+            .iter()
+            .map(|payout| (payout.payout_id.clone(), payout.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                ("po_1".to_string(), PayoutStatus::Pending),
+                ("po_2".to_string(), PayoutStatus::Scheduled),
+                ("po_3".to_string(), PayoutStatus::Pending),
+            ]
+        );
+    }
 }
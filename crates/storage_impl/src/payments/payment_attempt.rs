@@ -333,7 +333,7 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PaymentAttempt, errors::StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .insert_payment_attempt(payment_attempt, storage_scheme)
                     .await
@@ -466,7 +466,7 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PaymentAttempt, errors::StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .update_payment_attempt_with_attempt_id(this, payment_attempt, storage_scheme)
                     .await
@@ -581,7 +581,7 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PaymentAttempt, errors::StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .find_payment_attempt_by_connector_transaction_id_payment_id_merchant_id(
                         connector_transaction_id,
@@ -636,7 +636,9 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
                 )
         };
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => database_call().await,
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                database_call().await
+            }
             MerchantStorageScheme::RedisKv => {
                 let key = format!("mid_{merchant_id}_pid_{payment_id}");
                 let pattern = "pa_*";
@@ -685,7 +687,9 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
                 )
         };
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => database_call().await,
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                database_call().await
+            }
             MerchantStorageScheme::RedisKv => {
                 let key = format!("mid_{merchant_id}_pid_{payment_id}");
                 let pattern = "pa_*";
@@ -729,7 +733,7 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PaymentAttempt, errors::StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .find_payment_attempt_by_merchant_id_connector_txn_id(
                         merchant_id,
@@ -787,7 +791,7 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PaymentAttempt, errors::StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .find_payment_attempt_by_payment_id_merchant_id_attempt_id(
                         payment_id,
@@ -830,7 +834,7 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PaymentAttempt, errors::StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .find_payment_attempt_by_attempt_id_merchant_id(
                         attempt_id,
@@ -887,7 +891,7 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PaymentAttempt, errors::StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .find_payment_attempt_by_preprocessing_id_merchant_id(
                         preprocessing_id,
@@ -944,7 +948,7 @@ impl<T: DatabaseStore> PaymentAttemptInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<Vec<PaymentAttempt>, errors::StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .find_attempts_by_merchant_id_payment_id(
                         merchant_id,
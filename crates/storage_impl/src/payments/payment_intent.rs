@@ -52,7 +52,7 @@ impl<T: DatabaseStore> PaymentIntentInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PaymentIntent, StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .insert_payment_intent(new, storage_scheme)
                     .await
@@ -145,7 +145,7 @@ impl<T: DatabaseStore> PaymentIntentInterface for KVRouterStore<T> {
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PaymentIntent, StorageError> {
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .update_payment_intent(this, payment_intent_update, storage_scheme)
                     .await
@@ -209,7 +209,9 @@ impl<T: DatabaseStore> PaymentIntentInterface for KVRouterStore<T> {
                 })
         };
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => database_call().await,
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                database_call().await
+            }
 
             MerchantStorageScheme::RedisKv => {
                 let key = format!("mid_{merchant_id}_pid_{payment_id}");
@@ -1,10 +1,40 @@
+pub mod admin;
+pub mod amount_adjustment;
+pub mod audit;
+pub mod balance;
+pub mod batch_read;
+pub mod daily_count;
+pub mod dead_letter;
+pub mod display_seq;
+pub mod dual_write;
+pub mod id_format;
+pub mod integrity;
+pub mod kv_dump;
+pub mod kv_index;
+pub mod kv_key;
+pub mod list_version;
+pub mod load_options;
 pub mod payout_attempt;
 #[allow(clippy::module_inception)]
 pub mod payouts;
+pub mod pending_ops;
+pub mod read_cache;
+pub mod read_only;
+pub mod stream_trim;
+pub mod summary_cache;
+pub mod webhook_delivery;
+#[cfg(feature = "kv_write_check")]
+pub mod write_check;
 
-use diesel_models::{payout_attempt::PayoutAttempt, payouts::Payouts};
+use diesel_models::{
+    payout_attempt::PayoutAttempt, payout_webhook_delivery::PayoutWebhookDelivery, payouts::Payouts,
+};
 
 use crate::redis::kv_store::KvStorePartition;
 
+/// Default tolerance, in seconds, for the `created_at` of an inserted payout being in the future
+pub const DEFAULT_CREATED_AT_SKEW_TOLERANCE_IN_SECS: u32 = 300;
+
 impl KvStorePartition for Payouts {}
 impl KvStorePartition for PayoutAttempt {}
+impl KvStorePartition for PayoutWebhookDelivery {}
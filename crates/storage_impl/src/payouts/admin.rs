@@ -0,0 +1,109 @@
+use data_models::{errors::StorageError, payouts::payouts::Payouts};
+use diesel_models::payouts::Payouts as DieselPayouts;
+use error_stack::ResultExt;
+use router_env::{instrument, logger, tracing};
+
+use crate::{
+    diesel_error_to_data_error, utils::pg_connection_read, DataModelExt, DatabaseStore, RouterStore,
+};
+
+/// Proof that the caller acts on behalf of the platform operator, not of a merchant.
+///
+/// It can only be obtained through [`PlatformAdmin::authenticate`], which makes the store
+/// methods taking it unreachable from merchant authenticated flows.
+#[derive(Debug)]
+pub struct PlatformAdmin {
+    _private: (),
+}
+
+impl PlatformAdmin {
+    /// Returns the marker when `provided_api_key` is the configured `admin_api_key`.
+    pub fn authenticate(provided_api_key: &str, admin_api_key: &str) -> Option<Self> {
+        (!admin_api_key.is_empty() && provided_api_key == admin_api_key)
+            .then_some(Self { _private: () })
+    }
+}
+
+/// Maximum number of payouts sharing a payout id fetched by
+/// [`RouterStore::find_payout_by_payout_id_any_merchant`], only used to detect collisions.
+const ANY_MERCHANT_LOOKUP_LIMIT: i64 = 2;
+
+impl<T: DatabaseStore> RouterStore<T> {
+    /// Finds a payout by its id, whichever merchant it belongs to.
+    ///
+    /// Reads from the database only, payouts still pending in the KV store are not found. Should
+    /// the id be used by more than one merchant, the most recently created payout is returned.
+    #[instrument(skip_all)]
+    pub async fn find_payout_by_payout_id_any_merchant(
+        &self,
+        _admin: &PlatformAdmin,
+        payout_id: &str,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        let payouts = DieselPayouts::filter_by_payout_id_any_merchant(
+            &conn,
+            payout_id,
+            ANY_MERCHANT_LOOKUP_LIMIT,
+        )
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+        .attach_printable_lazy(|| format!("Error while finding payout {payout_id}"))?;
+
+        Ok(first_payout_any_merchant(payout_id, payouts))
+    }
+}
+
+fn first_payout_any_merchant(payout_id: &str, payouts: Vec<DieselPayouts>) -> Option<Payouts> {
+    if payouts.len() > 1 {
+        logger::warn!(
+            payout_id,
+            "Payout id is used by more than one merchant, returning the most recent payout"
+        );
+    }
+    payouts.into_iter().next().map(Payouts::from_storage_model)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn stored_payout(merchant_id: &str) -> DieselPayouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(diesel_models::payouts::PayoutsNew {
+            payout_id: "po_1".to_string(),
+            merchant_id: merchant_id.to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            ..Default::default()
+        })
+        .unwrap();
+        serde_json::from_value(stored).unwrap()
+    }
+
+    #[test]
+    fn payout_of_any_merchant_is_found() {
+        let payout = first_payout_any_merchant(
+            "po_1",
+            vec![stored_payout("merchant_2"), stored_payout("merchant_1")],
+        )
+        .unwrap();
+        assert_eq!(payout.merchant_id, "merchant_2");
+    }
+
+    #[test]
+    fn missing_payout_is_not_found() {
+        assert!(first_payout_any_merchant("po_1", Vec::new()).is_none());
+    }
+
+    #[test]
+    fn marker_requires_the_admin_api_key() {
+        assert!(PlatformAdmin::authenticate("admin_key", "admin_key").is_some());
+        assert!(PlatformAdmin::authenticate("merchant_key", "admin_key").is_none());
+        assert!(PlatformAdmin::authenticate("", "").is_none());
+    }
+}
@@ -0,0 +1,76 @@
+use data_models::{errors::StorageError, payouts::amount_adjustment::PayoutAmountAdjustment};
+use diesel_models::{
+    kv,
+    payout_amount_adjustment::{
+        PayoutAmountAdjustment as DieselPayoutAmountAdjustment,
+        PayoutAmountAdjustmentNew as DieselPayoutAmountAdjustmentNew,
+    },
+    payouts::Payouts as DieselPayouts,
+};
+use error_stack::ResultExt;
+
+use crate::{redis::kv_store::PartitionKey, DataModelExt, DatabaseStore, KVRouterStore};
+
+impl DataModelExt for PayoutAmountAdjustment {
+    type StorageModel = DieselPayoutAmountAdjustmentNew;
+
+    fn to_storage_model(self) -> Self::StorageModel {
+        DieselPayoutAmountAdjustmentNew {
+            merchant_id: self.merchant_id,
+            payout_id: self.payout_id,
+            old_amount: self.old_amount,
+            new_amount: self.new_amount,
+            reason: self.reason,
+            created_at: self.created_at,
+        }
+    }
+
+    fn from_storage_model(storage_model: Self::StorageModel) -> Self {
+        Self {
+            merchant_id: storage_model.merchant_id,
+            payout_id: storage_model.payout_id,
+            old_amount: storage_model.old_amount,
+            new_amount: storage_model.new_amount,
+            reason: storage_model.reason,
+            created_at: storage_model.created_at,
+        }
+    }
+}
+
+pub(crate) fn adjustment_from_stored(
+    stored: DieselPayoutAmountAdjustment,
+) -> PayoutAmountAdjustment {
+    PayoutAmountAdjustment {
+        merchant_id: stored.merchant_id,
+        payout_id: stored.payout_id,
+        old_amount: stored.old_amount,
+        new_amount: stored.new_amount,
+        reason: stored.reason,
+        created_at: stored.created_at,
+    }
+}
+
+/// Pushes the insert of `adjustment` to the drainer stream of the payout held under `key`, right
+/// after the update which changed its amount, so that the drainer applies both in order.
+pub(crate) async fn push_amount_adjustment<T: DatabaseStore>(
+    store: &KVRouterStore<T>,
+    key: &str,
+    adjustment: PayoutAmountAdjustment,
+) -> error_stack::Result<(), StorageError> {
+    let payout_id = adjustment.payout_id.clone();
+    let redis_entry = kv::TypedSql {
+        op: kv::DBOperation::Insert {
+            insertable: kv::Insertable::PayoutAmountAdjustment(adjustment.to_storage_model()),
+        },
+    };
+    store
+        .push_to_drainer_stream::<DieselPayouts>(
+            redis_entry,
+            PartitionKey::MerchantIdPaymentIdCombination { combination: key },
+        )
+        .await
+        .change_context(StorageError::KVError)
+        .attach_printable_lazy(|| {
+            format!("failed to record the amount adjustment of payout {payout_id}")
+        })
+}
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use data_models::payouts::payouts::Payouts;
+use diesel_models::enums::{Currency, PayoutCreationSource, PayoutStatus};
+use router_env::logger;
+use serde::Serialize;
+use time::PrimitiveDateTime;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutAuditOperation {
+    Insert,
+    Update,
+}
+
+/// A mutation of a payout, as forwarded to an [`AuditSink`].
+///
+/// Only identifiers and the state of the payout are carried, its customer, address,
+/// description, return URL and metadata are never part of the event.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct PayoutAuditEvent {
+    pub operation: PayoutAuditOperation,
+    pub merchant_id: String,
+    pub payout_id: String,
+    pub profile_id: String,
+    pub status: PayoutStatus,
+    pub amount: i64,
+    pub destination_currency: Currency,
+    pub attempt_count: i16,
+    pub created_by: PayoutCreationSource,
+    #[serde(with = "common_utils::custom_serde::iso8601")]
+    pub occurred_at: PrimitiveDateTime,
+}
+
+impl PayoutAuditEvent {
+    pub fn new(operation: PayoutAuditOperation, payout: &Payouts) -> Self {
+        Self {
+            operation,
+            merchant_id: payout.merchant_id.clone(),
+            payout_id: payout.payout_id.clone(),
+            profile_id: payout.profile_id.clone(),
+            status: payout.status,
+            amount: payout.amount,
+            destination_currency: payout.destination_currency,
+            attempt_count: payout.attempt_count,
+            created_by: payout.created_by,
+            occurred_at: payout.last_modified_at,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to record payout audit event")]
+pub struct AuditSinkError;
+
+/// External sink receiving an event for every payout successfully inserted or updated through
+/// the store.
+#[async_trait::async_trait]
+pub trait AuditSink: std::fmt::Debug + Send + Sync {
+    async fn record(&self, event: &PayoutAuditEvent) -> error_stack::Result<(), AuditSinkError>;
+}
+
+/// Records the mutation of `payout` in `sink`, if one is configured.
+///
+/// Failures are only logged, since the mutation has already been persisted by the time this is
+/// called.
+pub async fn record_payout_audit_event(
+    sink: Option<&Arc<dyn AuditSink>>,
+    operation: PayoutAuditOperation,
+    payout: &Payouts,
+) {
+    if let Some(sink) = sink {
+        let event = PayoutAuditEvent::new(operation, payout);
+        if let Err(error) = sink.record(&event).await {
+            logger::error!(
+                ?error,
+                payout_id = %event.payout_id,
+                "Failed to record payout audit event"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::sync::Mutex;
+
+    use error_stack::IntoReport;
+
+    use super::*;
+    use crate::DataModelExt;
+
+    #[derive(Debug, Default)]
+    struct CapturingSink {
+        events: Mutex<Vec<PayoutAuditEvent>>,
+    }
+
+    #[async_trait::async_trait]
+    impl AuditSink for CapturingSink {
+        async fn record(
+            &self,
+            event: &PayoutAuditEvent,
+        ) -> error_stack::Result<(), AuditSinkError> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct FailingSink;
+
+    #[async_trait::async_trait]
+    impl AuditSink for FailingSink {
+        async fn record(
+            &self,
+            _event: &PayoutAuditEvent,
+        ) -> error_stack::Result<(), AuditSinkError> {
+            Err(AuditSinkError).into_report()
+        }
+    }
+
+    fn sample_payout() -> Payouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(diesel_models::payouts::PayoutsNew {
+            payout_id: "po_1".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            customer_id: "cus_1".to_string(),
+            description: Some("rent for jane doe".to_string()),
+            amount: 1000,
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            ..Default::default()
+        })
+        .unwrap();
+        Payouts::from_storage_model(serde_json::from_value(stored).unwrap())
+    }
+
+    #[tokio::test]
+    async fn insert_and_update_are_recorded() {
+        let sink = Arc::new(CapturingSink::default());
+        let dyn_sink: Arc<dyn AuditSink> = sink.clone();
+        let mut payout = sample_payout();
+
+        record_payout_audit_event(Some(&dyn_sink), PayoutAuditOperation::Insert, &payout).await;
+        payout.status = PayoutStatus::Success;
+        record_payout_audit_event(Some(&dyn_sink), PayoutAuditOperation::Update, &payout).await;
+
+        let events = sink.events.lock().unwrap();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.operation, event.status))
+                .collect::<Vec<_>>(),
+            [
+                (PayoutAuditOperation::Insert, PayoutStatus::default()),
+                (PayoutAuditOperation::Update, PayoutStatus::Success),
+            ]
+        );
+        assert!(events.iter().all(|event| event.payout_id == "po_1"));
+    }
+
+    #[test]
+    fn event_does_not_carry_customer_details() {
+        let event = PayoutAuditEvent::new(PayoutAuditOperation::Insert, &sample_payout());
+        let serialized = serde_json::to_string(&event).unwrap();
+
+        assert!(!serialized.contains("cus_1"));
+        assert!(!serialized.contains("jane doe"));
+    }
+
+    #[tokio::test]
+    async fn sink_failure_does_not_fail_the_operation() {
+        let sink: Arc<dyn AuditSink> = Arc::new(FailingSink);
+        record_payout_audit_event(Some(&sink), PayoutAuditOperation::Insert, &sample_payout())
+            .await;
+    }
+}
@@ -0,0 +1,148 @@
+use data_models::{
+    errors::StorageError,
+    payouts::{
+        ids::MerchantId,
+        payouts::{PayoutsInterface, PayoutsNew},
+    },
+};
+use diesel_models::enums::Currency;
+use error_stack::{IntoReport, ResultExt};
+
+use crate::redis::kv_store::RedisConnInterface;
+
+/// Key of the counter holding the merchant's available payout float in `currency`, in the
+/// currency's minor unit.
+pub fn payout_balance_key(merchant_id: &str, currency: Currency) -> String {
+    format!("payout_balance_{merchant_id}_{currency}")
+}
+
+/// Returns the merchant's available payout float in `currency`, `0` if none was ever funded.
+pub async fn get_payout_balance(
+    store: &(dyn RedisConnInterface + Send + Sync),
+    merchant_id: &str,
+    currency: Currency,
+) -> error_stack::Result<i64, StorageError> {
+    store
+        .get_redis_conn()
+        .change_context(StorageError::KVError)?
+        .get_key::<Option<i64>>(&payout_balance_key(merchant_id, currency))
+        .await
+        .change_context(StorageError::KVError)
+        .map(|balance| balance.unwrap_or_default())
+}
+
+pub fn ensure_sufficient_balance(
+    available: i64,
+    amount: i64,
+) -> error_stack::Result<(), StorageError> {
+    if available >= amount {
+        Ok(())
+    } else {
+        Err(StorageError::InsufficientFunds { available }).into_report()
+    }
+}
+
+/// Checks the merchant's float covers the payout about to be inserted, when `enabled`.
+///
+/// The payout is funded from the merchant's float in its source currency.
+///
+/// The check is advisory only: the float is read, never reserved nor decremented, so concurrent
+/// payouts may each pass it against the same float. Whatever moves the funds has to enforce the
+/// balance.
+pub async fn precheck_payout_balance<S>(
+    store: &S,
+    enabled: bool,
+    new: &PayoutsNew,
+) -> error_stack::Result<(), StorageError>
+where
+    S: PayoutsInterface + Sync + ?Sized,
+{
+    if !enabled {
+        return Ok(());
+    }
+
+    store
+        .check_payout_balance(
+            &MerchantId::from(&new.merchant_id),
+            new.amount,
+            new.source_currency,
+        )
+        .await
+        .attach_printable_lazy(|| {
+            format!(
+                "Insufficient {} balance for payout {} of merchant {}",
+                new.source_currency, new.payout_id, new.merchant_id
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::mock_db::MockDb;
+
+    #[allow(clippy::expect_used)]
+    async fn store_with_usd_balance(balance: i64) -> MockDb {
+        let mockdb = MockDb::new(&redis_interface::RedisSettings::default())
+            .await
+            .expect("Failed to create a mock DB");
+        mockdb
+            .payout_balances
+            .lock()
+            .await
+            .insert(payout_balance_key("merchant_1", Currency::USD), balance);
+        mockdb
+    }
+
+    fn new_payout(amount: i64, source_currency: Currency) -> PayoutsNew {
+        PayoutsNew {
+            payout_id: "po_1".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            amount,
+            source_currency,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn sufficient_balance_passes() {
+        let store = store_with_usd_balance(1000).await;
+        precheck_payout_balance(&store, true, &new_payout(1000, Currency::USD))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn insufficient_balance_reports_available_amount() {
+        let store = store_with_usd_balance(999).await;
+        let error = precheck_payout_balance(&store, true, &new_payout(1000, Currency::USD))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InsufficientFunds { available: 999 }
+        ));
+    }
+
+    #[tokio::test]
+    async fn balance_is_tracked_per_currency() {
+        let store = store_with_usd_balance(1000).await;
+        let error = precheck_payout_balance(&store, true, &new_payout(1000, Currency::EUR))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InsufficientFunds { available: 0 }
+        ));
+    }
+
+    #[tokio::test]
+    async fn disabled_check_passes_through() {
+        let store = store_with_usd_balance(0).await;
+        precheck_payout_balance(&store, false, &new_payout(1000, Currency::USD))
+            .await
+            .unwrap();
+    }
+}
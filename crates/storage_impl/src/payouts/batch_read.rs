@@ -0,0 +1,173 @@
+use std::future::Future;
+
+use common_utils::errors::CustomResult;
+use futures::{StreamExt, TryStreamExt};
+use redis_interface::{errors::RedisError, RedisConnectionPool};
+
+use super::{integrity::get_verified_payout_values, kv_key::PayoutKvKey};
+use crate::{DatabaseStore, KVRouterStore};
+
+/// Default number of payouts read from KV in a single pipeline by the batch finders
+pub const DEFAULT_PAYOUT_BATCH_PIPELINE_SIZE: usize = 100;
+
+/// Default number of pipelines of a batch read in flight at once
+pub const DEFAULT_PAYOUT_BATCH_MAX_CONCURRENT_PIPELINES: usize = 4;
+
+/// Bounds the Redis commands issued by a batch read of payouts from KV.
+///
+/// The payouts are read in pipelines of at most `pipeline_size` payouts, of which at most
+/// `max_concurrent_pipelines` are in flight at once, rather than in a single pipeline which
+/// would hold Redis for as long as it takes to serve all of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PayoutBatchReadLimits {
+    pipeline_size: usize,
+    max_concurrent_pipelines: usize,
+}
+
+impl PayoutBatchReadLimits {
+    /// Both limits are raised to `1` if set to `0`.
+    pub fn new(pipeline_size: usize, max_concurrent_pipelines: usize) -> Self {
+        Self {
+            pipeline_size: pipeline_size.max(1),
+            max_concurrent_pipelines: max_concurrent_pipelines.max(1),
+        }
+    }
+}
+
+impl Default for PayoutBatchReadLimits {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_PAYOUT_BATCH_PIPELINE_SIZE,
+            DEFAULT_PAYOUT_BATCH_MAX_CONCURRENT_PIPELINES,
+        )
+    }
+}
+
+/// Reads the values of `keys` with `read_pipeline`, called on consecutive chunks of `keys`
+/// within `limits`, and returns the values of all the chunks in the order of `keys`.
+///
+/// Fails with the error of the first failed pipeline.
+pub async fn read_in_pipelines<'a, K, V, E, F, Fut>(
+    keys: &'a [K],
+    limits: PayoutBatchReadLimits,
+    read_pipeline: F,
+) -> Result<Vec<V>, E>
+where
+    F: FnMut(&'a [K]) -> Fut,
+    Fut: Future<Output = Result<Vec<V>, E>>,
+{
+    let pipelines = futures::stream::iter(keys.chunks(limits.pipeline_size))
+        .map(read_pipeline)
+        .buffered(limits.max_concurrent_pipelines)
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(pipelines.into_iter().flatten().collect())
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Reads the serialized payouts held under `keys` like [`get_verified_payout_values`], within
+    /// the [`PayoutBatchReadLimits`] of this store.
+    pub async fn read_kv_payouts_batch(
+        &self,
+        redis_conn: &RedisConnectionPool,
+        keys: &[PayoutKvKey],
+    ) -> CustomResult<Vec<Option<Vec<u8>>>, RedisError> {
+        read_in_pipelines(keys, self.payout_batch_read_limits, |keys| {
+            get_verified_payout_values(redis_conn, self.payout_checksum.as_ref(), keys)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct PipelineLog {
+        sizes: Mutex<Vec<usize>>,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl PipelineLog {
+        async fn read(&self, keys: &[u32]) -> Result<Vec<u32>, &'static str> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            self.sizes.lock().unwrap().push(keys.len());
+            // Gives the other pipelines the chance to start before this one completes
+            tokio::task::yield_now().await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(keys.iter().map(|key| key * 2).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn thousands_of_ids_are_read_in_bounded_pipelines() {
+        let keys = (0..5_000).collect::<Vec<u32>>();
+        let log = PipelineLog::default();
+
+        let values = read_in_pipelines(&keys, PayoutBatchReadLimits::new(100, 4), |keys| {
+            log.read(keys)
+        })
+        .await
+        .unwrap();
+
+        // The values of the pipelines are merged back in the order of the keys
+        assert_eq!(values, keys.iter().map(|key| key * 2).collect::<Vec<_>>());
+        let sizes = log.sizes.lock().unwrap().clone();
+        assert_eq!(sizes.len(), 50);
+        assert!(sizes.iter().all(|size| *size == 100));
+        let max_in_flight = log.max_in_flight.load(Ordering::SeqCst);
+        assert!((1..=4).contains(&max_in_flight));
+    }
+
+    #[tokio::test]
+    async fn last_pipeline_holds_the_remaining_ids() {
+        let keys = (0..250).collect::<Vec<u32>>();
+        let log = PipelineLog::default();
+
+        let values = read_in_pipelines(&keys, PayoutBatchReadLimits::new(100, 2), |keys| {
+            log.read(keys)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(values.len(), 250);
+        assert_eq!(*log.sizes.lock().unwrap(), [100, 100, 50]);
+    }
+
+    #[tokio::test]
+    async fn failed_pipeline_fails_the_read() {
+        let keys = (0..300).collect::<Vec<u32>>();
+
+        let result = read_in_pipelines(&keys, PayoutBatchReadLimits::new(100, 2), |keys| {
+            let failed = keys.contains(&150);
+            async move {
+                if failed {
+                    Err("pipeline failed")
+                } else {
+                    Ok(keys.to_vec())
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Err("pipeline failed"));
+    }
+
+    #[test]
+    fn zero_limits_are_raised_to_one() {
+        assert_eq!(
+            PayoutBatchReadLimits::new(0, 0),
+            PayoutBatchReadLimits::new(1, 1)
+        );
+    }
+}
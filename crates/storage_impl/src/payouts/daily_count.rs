@@ -0,0 +1,232 @@
+use data_models::errors::StorageError;
+use error_stack::ResultExt;
+use router_env::logger;
+use time::{Date, PrimitiveDateTime, Time};
+
+use crate::redis::kv_store::RedisConnInterface;
+
+/// Key of the counter of the payouts created by the merchant on `date`, in UTC.
+pub fn payout_daily_count_key(merchant_id: &str, date: Date) -> String {
+    format!(
+        "mid_{merchant_id}_po_count_{:04}{:02}{:02}",
+        date.year(),
+        u8::from(date.month()),
+        date.day()
+    )
+}
+
+/// The unix timestamp of the midnight ending `date`, when its counter expires.
+fn end_of_day_timestamp(date: Date) -> i64 {
+    date.next_day()
+        .map(|next_day| PrimitiveDateTime::new(next_day, Time::MIDNIGHT))
+        .unwrap_or(PrimitiveDateTime::MAX)
+        .assume_utc()
+        .unix_timestamp()
+}
+
+/// The daily counters of the payouts created by each merchant, kept for quota dashboards.
+#[async_trait::async_trait]
+pub trait PayoutDailyCounter {
+    /// Increments the counter under `key`, created if missing, and makes it expire at
+    /// `expire_at`. Returns the new value of the counter.
+    async fn increment_daily_count(
+        &self,
+        key: &str,
+        expire_at: i64,
+    ) -> error_stack::Result<i64, StorageError>;
+
+    /// Reads the counter under `key`, `None` if it is missing.
+    async fn read_daily_count(&self, key: &str) -> error_stack::Result<Option<i64>, StorageError>;
+}
+
+#[async_trait::async_trait]
+impl<S> PayoutDailyCounter for S
+where
+    S: RedisConnInterface + Sync + ?Sized,
+{
+    async fn increment_daily_count(
+        &self,
+        key: &str,
+        expire_at: i64,
+    ) -> error_stack::Result<i64, StorageError> {
+        let redis_conn = self
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?;
+        let count = redis_conn
+            .increment_key(key)
+            .await
+            .change_context(StorageError::KVError)?;
+        redis_conn
+            .set_expire_at(key, expire_at)
+            .await
+            .change_context(StorageError::KVError)?;
+        Ok(count)
+    }
+
+    async fn read_daily_count(&self, key: &str) -> error_stack::Result<Option<i64>, StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .get_key::<Option<i64>>(key)
+            .await
+            .change_context(StorageError::KVError)
+    }
+}
+
+/// Counts a payout created by the merchant at `now`, on the counter of that day which rolls
+/// over at midnight UTC.
+///
+/// Failures are only logged, since the payout has already been persisted by the time this is
+/// called.
+pub async fn record_payout_created<C>(counter: &C, merchant_id: &str, now: PrimitiveDateTime)
+where
+    C: PayoutDailyCounter + Sync + ?Sized,
+{
+    let date = now.date();
+    let result = counter
+        .increment_daily_count(
+            &payout_daily_count_key(merchant_id, date),
+            end_of_day_timestamp(date),
+        )
+        .await;
+
+    if let Err(error) = result {
+        logger::error!(
+            ?error,
+            "Failed to count a payout created by {merchant_id} on {date}"
+        );
+    }
+}
+
+/// Returns how many payouts the merchant created on `date`, `0` if it created none or the day
+/// is over.
+pub async fn get_payout_daily_count<C>(
+    counter: &C,
+    merchant_id: &str,
+    date: Date,
+) -> error_stack::Result<i64, StorageError>
+where
+    C: PayoutDailyCounter + Sync + ?Sized,
+{
+    counter
+        .read_daily_count(&payout_daily_count_key(merchant_id, date))
+        .await
+        .map(|count| count.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::{collections::HashMap, sync::Mutex};
+
+    use time::macros::datetime;
+
+    use super::*;
+
+    /// Counters expiring against a clock set by the test.
+    #[derive(Default)]
+    struct FakeRedis {
+        /// Values of the counters with the unix timestamp they expire at.
+        counters: Mutex<HashMap<String, (i64, i64)>>,
+        now: Mutex<i64>,
+    }
+
+    impl FakeRedis {
+        fn set_now(&self, now: PrimitiveDateTime) {
+            *self.now.lock().unwrap() = now.assume_utc().unix_timestamp();
+        }
+
+        fn live_count(&self, key: &str) -> Option<i64> {
+            let now = *self.now.lock().unwrap();
+            self.counters
+                .lock()
+                .unwrap()
+                .get(key)
+                .filter(|(_, expire_at)| *expire_at > now)
+                .map(|(count, _)| *count)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PayoutDailyCounter for FakeRedis {
+        async fn increment_daily_count(
+            &self,
+            key: &str,
+            expire_at: i64,
+        ) -> error_stack::Result<i64, StorageError> {
+            let count = self.live_count(key).unwrap_or_default() + 1;
+            self.counters
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), (count, expire_at));
+            Ok(count)
+        }
+
+        async fn read_daily_count(
+            &self,
+            key: &str,
+        ) -> error_stack::Result<Option<i64>, StorageError> {
+            Ok(self.live_count(key))
+        }
+    }
+
+    #[test]
+    fn key_embeds_the_utc_date() {
+        assert_eq!(
+            payout_daily_count_key("merchant_1", datetime!(2024-04-09 23:59).date()),
+            "mid_merchant_1_po_count_20240409"
+        );
+        assert_eq!(
+            end_of_day_timestamp(datetime!(2024-04-09 23:59).date()),
+            datetime!(2024-04-10 0:00).assume_utc().unix_timestamp()
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_counter_reads_as_zero() {
+        let redis = FakeRedis::default();
+        let today = datetime!(2024-04-09 10:00);
+        redis.set_now(today);
+
+        assert_eq!(
+            get_payout_daily_count(&redis, "merchant_1", today.date())
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn counter_resets_across_a_day_boundary() {
+        let redis = FakeRedis::default();
+        let before_midnight = datetime!(2024-04-09 23:59);
+        let after_midnight = datetime!(2024-04-10 0:01);
+
+        redis.set_now(before_midnight);
+        record_payout_created(&redis, "merchant_1", before_midnight).await;
+        record_payout_created(&redis, "merchant_1", before_midnight).await;
+        record_payout_created(&redis, "merchant_2", before_midnight).await;
+        assert_eq!(
+            get_payout_daily_count(&redis, "merchant_1", before_midnight.date())
+                .await
+                .unwrap(),
+            2
+        );
+
+        redis.set_now(after_midnight);
+        record_payout_created(&redis, "merchant_1", after_midnight).await;
+        assert_eq!(
+            get_payout_daily_count(&redis, "merchant_1", after_midnight.date())
+                .await
+                .unwrap(),
+            1
+        );
+        // The counter of the previous day expired at midnight
+        assert_eq!(
+            get_payout_daily_count(&redis, "merchant_1", before_midnight.date())
+                .await
+                .unwrap(),
+            0
+        );
+    }
+}
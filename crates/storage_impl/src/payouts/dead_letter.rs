@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use data_models::{
+    errors::StorageError,
+    payouts::ids::{MerchantId, PayoutId},
+};
+use diesel_models::kv::{self, DBOperation, TypedSql};
+use error_stack::{IntoReport, ResultExt};
+use redis_interface::errors::RedisError;
+use router_env::{instrument, logger, tracing};
+
+use crate::{
+    diesel_error_to_data_error, redis::kv_store::RedisConnInterface, utils::pg_connection_write,
+    DatabaseStore, KVRouterStore,
+};
+
+/// Maximum number of entries read from the dead-letter stream of a payout.
+pub const PAYOUT_DEAD_LETTER_READ_COUNT: u64 = 1000;
+
+/// A payout write which the drainer failed to apply too many times, and moved to the
+/// dead-letter stream of the payout.
+#[derive(Debug)]
+pub struct DeadLetteredPayoutOp {
+    /// Id of the entry holding the write in the dead-letter stream.
+    pub entry_id: String,
+    pub payout_id: String,
+    pub request_id: String,
+    /// Unix timestamp at which the write was pushed to the drainer stream.
+    pub pushed_at: i64,
+    pub operation: DBOperation,
+}
+
+/// Parses the dead-letter stream entry `fields`, `None` if it holds anything but a payout write.
+fn dead_lettered_op(
+    entry_id: String,
+    fields: &HashMap<String, String>,
+) -> Option<DeadLetteredPayoutOp> {
+    let operation = fields
+        .get("typed_sql")
+        .and_then(|typed_sql| serde_json::from_str::<TypedSql>(typed_sql).ok())?
+        .op;
+    let (_, payout_id) = operation.payout_ids()?;
+    Some(DeadLetteredPayoutOp {
+        entry_id,
+        payout_id: payout_id.to_owned(),
+        request_id: fields.get("request_id").cloned().unwrap_or_default(),
+        pushed_at: fields
+            .get("pushed_at")
+            .and_then(|pushed_at| pushed_at.parse().ok())
+            .unwrap_or_default(),
+        operation,
+    })
+}
+
+/// Finds the payout write held by the entry `entry_id` of a dead-letter stream.
+fn find_dead_lettered_op(
+    entries: Vec<(String, HashMap<String, String>)>,
+    entry_id: &str,
+) -> Option<DeadLetteredPayoutOp> {
+    entries
+        .into_iter()
+        .find(|(id, _)| id == entry_id)
+        .and_then(|(id, fields)| dead_lettered_op(id, &fields))
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Lists the payout writes of the merchant which the drainer moved to dead-letter streams,
+    /// grouped by payout and oldest first.
+    #[instrument(skip_all)]
+    pub async fn list_dead_lettered_payout_ops(
+        &self,
+        merchant_id: &MerchantId,
+    ) -> error_stack::Result<Vec<DeadLetteredPayoutOp>, StorageError> {
+        let mut payout_ids = self
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .smembers(&kv::payout_dead_letter_index(merchant_id))
+            .await
+            .change_context(StorageError::KVError)?;
+        payout_ids.sort();
+
+        let mut ops = Vec::new();
+        for payout_id in payout_ids {
+            let entries = self
+                .read_dead_letter_stream(merchant_id, &payout_id)
+                .await?;
+            ops.extend(
+                entries
+                    .into_iter()
+                    .filter_map(|(entry_id, fields)| dead_lettered_op(entry_id, &fields)),
+            );
+        }
+        Ok(ops)
+    }
+
+    /// Applies the dead-lettered write held by the entry `entry_id` of the dead-letter stream of
+    /// the payout to the database again. The entry is removed from the stream once the write
+    /// succeeds, and left in place if it fails again.
+    #[instrument(skip_all)]
+    pub async fn reprocess_dead_lettered_op(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        entry_id: &str,
+    ) -> error_stack::Result<(), StorageError> {
+        let entries = self.read_dead_letter_stream(merchant_id, payout_id).await?;
+        let remaining_entries = entries.len();
+        let op = find_dead_lettered_op(entries, entry_id)
+            .ok_or_else(|| {
+                StorageError::ValueNotFound(format!(
+                    "dead-lettered op {entry_id} of payout {payout_id}"
+                ))
+            })
+            .into_report()?;
+
+        let conn = pg_connection_write(self).await?;
+        op.operation.execute(&conn).await.map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })?;
+
+        let redis_conn = self
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?;
+        redis_conn
+            .stream_delete_entries(
+                &kv::payout_dead_letter_stream(merchant_id, payout_id),
+                entry_id,
+            )
+            .await
+            .change_context(StorageError::KVError)?;
+        if remaining_entries == 1 {
+            redis_conn
+                .srem(
+                    &kv::payout_dead_letter_index(merchant_id),
+                    payout_id.as_ref(),
+                )
+                .await
+                .change_context(StorageError::KVError)?;
+        }
+
+        logger::info!(%payout_id, %entry_id, "Reprocessed a dead-lettered payout write");
+        Ok(())
+    }
+
+    async fn read_dead_letter_stream(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<Vec<(String, HashMap<String, String>)>, StorageError> {
+        let stream_name = kv::payout_dead_letter_stream(merchant_id, payout_id);
+        match self
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .stream_read_entries(
+                stream_name.as_str(),
+                "0-0",
+                Some(PAYOUT_DEAD_LETTER_READ_COUNT),
+            )
+            .await
+        {
+            Ok(mut streams) => Ok(streams.remove(&stream_name).unwrap_or_default()),
+            Err(error)
+                if matches!(
+                    error.current_context(),
+                    RedisError::StreamEmptyOrNotAvailable
+                ) =>
+            {
+                Ok(Vec::new())
+            }
+            Err(error) => Err(error.change_context(StorageError::KVError)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use diesel_models::{
+        kv::{Insertable, PayoutsUpdateMems, Updateable},
+        payout_attempt::PayoutAttemptNew as DieselPayoutAttemptNew,
+        payouts::{
+            Payouts as DieselPayouts, PayoutsNew as DieselPayoutsNew,
+            PayoutsUpdate as DieselPayoutsUpdate,
+        },
+    };
+
+    use super::*;
+
+    fn stored_payout(payout_id: &str) -> DieselPayouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(DieselPayoutsNew {
+            payout_id: payout_id.to_string(),
+            merchant_id: "merchant_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            ..Default::default()
+        })
+        .unwrap();
+        serde_json::from_value(stored).unwrap()
+    }
+
+    fn stream_entry(op: DBOperation, request_id: &str) -> HashMap<String, String> {
+        TypedSql { op }
+            .to_field_value_pairs(request_id.to_string(), "mid_merchant_1_po_1".to_string())
+            .unwrap()
+            .into_iter()
+            .map(|(field, value)| (field.to_string(), value))
+            .collect()
+    }
+
+    fn update_op(payout_id: &str) -> DBOperation {
+        DBOperation::Update {
+            updatable: Updateable::PayoutsUpdate(PayoutsUpdateMems {
+                orig: stored_payout(payout_id),
+                update_data: DieselPayoutsUpdate::RecurringUpdate { recurring: true },
+            }),
+        }
+    }
+
+    #[test]
+    fn persistently_failing_write_is_dead_lettered_and_reprocessed() {
+        let op = update_op("po_1");
+        let (merchant_id, payout_id) = op.payout_ids().unwrap();
+        let dead_letter_stream = kv::payout_dead_letter_stream(merchant_id, payout_id);
+        assert_eq!(dead_letter_stream, "mid_merchant_1_po_po_1_dlq");
+        assert_eq!(
+            kv::payout_dead_letter_index(merchant_id),
+            "mid_merchant_1_po_dlq"
+        );
+
+        // The drainer keeps retrying the entry, and moves it once it failed more than
+        // `max_retries` times
+        let max_retries = 3;
+        let failed_attempts = (1..)
+            .find(|failed_attempts| kv::exceeds_retry_limit(*failed_attempts, max_retries))
+            .unwrap();
+        assert_eq!(failed_attempts, 4);
+
+        let entries = vec![
+            ("1-0".to_string(), stream_entry(update_op("po_1"), "req_1")),
+            ("2-0".to_string(), stream_entry(op, "req_2")),
+        ];
+        let reprocessed = find_dead_lettered_op(entries, "2-0").unwrap();
+        assert_eq!(reprocessed.entry_id, "2-0");
+        assert_eq!(reprocessed.payout_id, "po_1");
+        assert_eq!(reprocessed.request_id, "req_2");
+        assert!(reprocessed.pushed_at > 0);
+        assert!(matches!(
+            reprocessed.operation,
+            DBOperation::Update {
+                updatable: Updateable::PayoutsUpdate(_)
+            }
+        ));
+
+        let entries = vec![("1-0".to_string(), stream_entry(update_op("po_1"), "req_1"))];
+        assert!(find_dead_lettered_op(entries, "2-0").is_none());
+    }
+
+    #[test]
+    fn writes_to_other_tables_are_not_dead_lettered() {
+        let op = DBOperation::Insert {
+            insertable: Insertable::PayoutAttempt(DieselPayoutAttemptNew {
+                payout_id: "po_1".to_string(),
+                merchant_id: "merchant_1".to_string(),
+                ..Default::default()
+            }),
+        };
+        assert!(op.payout_ids().is_none());
+        assert!(dead_lettered_op("1-0".to_string(), &stream_entry(op, "req_1")).is_none());
+
+        let op = DBOperation::Insert {
+            insertable: Insertable::Payouts(DieselPayoutsNew {
+                payout_id: "po_1".to_string(),
+                merchant_id: "merchant_1".to_string(),
+                ..Default::default()
+            }),
+        };
+        assert_eq!(op.payout_ids(), Some(("merchant_1", "po_1")));
+        assert!(dead_lettered_op("1-0".to_string(), &HashMap::new()).is_none());
+    }
+}
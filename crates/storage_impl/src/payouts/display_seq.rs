@@ -0,0 +1,257 @@
+use data_models::errors::StorageError;
+use diesel_models::{kv, payout_display_sequence::PayoutDisplaySequence};
+use error_stack::ResultExt;
+use router_env::logger;
+
+use crate::{
+    diesel_error_to_data_error,
+    redis::kv_store::RedisConnInterface,
+    utils::{pg_connection_read, pg_connection_write},
+    DatabaseStore, KVRouterStore, RouterStore,
+};
+
+/// Assigns the next display sequence number of the merchant from its counter in the database.
+pub async fn next_display_seq_in_database<T: DatabaseStore>(
+    store: &RouterStore<T>,
+    merchant_id: &str,
+) -> error_stack::Result<i64, StorageError> {
+    let conn = pg_connection_write(store).await?;
+    PayoutDisplaySequence::next(&conn, merchant_id)
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+}
+
+/// The counter of the display sequence numbers of the payouts of a merchant written to KV.
+#[async_trait::async_trait]
+pub trait PayoutDisplaySeqCounter {
+    async fn counter_exists(&self, merchant_id: &str) -> error_stack::Result<bool, StorageError>;
+
+    /// Sets the counter to `last_taken` unless it exists already.
+    async fn seed_counter(
+        &self,
+        merchant_id: &str,
+        last_taken: i64,
+    ) -> error_stack::Result<(), StorageError>;
+
+    /// Increments the counter, returning its new value.
+    async fn increment_counter(&self, merchant_id: &str) -> error_stack::Result<i64, StorageError>;
+
+    /// The last display sequence number taken by a payout of the merchant in the database.
+    async fn last_taken_in_database(
+        &self,
+        merchant_id: &str,
+    ) -> error_stack::Result<i64, StorageError>;
+}
+
+/// Assigns the next display sequence number of the merchant from `counter`.
+///
+/// A missing counter, for a merchant whose payouts were only written to the database so far, is
+/// seeded with the last number taken there before it is incremented. Of several callers seeding
+/// it at once only the first one sets it, so that no number is assigned twice.
+pub async fn next_kv_display_seq<C>(
+    counter: &C,
+    merchant_id: &str,
+) -> error_stack::Result<i64, StorageError>
+where
+    C: PayoutDisplaySeqCounter + Sync + ?Sized,
+{
+    if !counter.counter_exists(merchant_id).await? {
+        let last_taken = counter.last_taken_in_database(merchant_id).await?;
+        counter.seed_counter(merchant_id, last_taken).await?;
+    }
+    counter.increment_counter(merchant_id).await
+}
+
+#[async_trait::async_trait]
+impl<T: DatabaseStore> PayoutDisplaySeqCounter for KVRouterStore<T> {
+    async fn counter_exists(&self, merchant_id: &str) -> error_stack::Result<bool, StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .exists::<()>(&kv::payout_display_seq_counter(merchant_id))
+            .await
+            .change_context(StorageError::KVError)
+    }
+
+    async fn seed_counter(
+        &self,
+        merchant_id: &str,
+        last_taken: i64,
+    ) -> error_stack::Result<(), StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .set_key_if_not_exists(&kv::payout_display_seq_counter(merchant_id), last_taken)
+            .await
+            .change_context(StorageError::KVError)
+            .map(|_| ())
+    }
+
+    async fn increment_counter(&self, merchant_id: &str) -> error_stack::Result<i64, StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .increment_key(&kv::payout_display_seq_counter(merchant_id))
+            .await
+            .change_context(StorageError::KVError)
+    }
+
+    async fn last_taken_in_database(
+        &self,
+        merchant_id: &str,
+    ) -> error_stack::Result<i64, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        PayoutDisplaySequence::last_taken(&conn, merchant_id)
+            .await
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Records which payout was written to KV with `display_seq`, for as long as the payout is
+    /// held in KV. A failure is only logged, the payout is then found by its number once drained
+    /// to the database.
+    pub async fn record_kv_display_seq(
+        &self,
+        merchant_id: &str,
+        display_seq: i64,
+        payout_id: &str,
+    ) {
+        let key = kv::payout_by_display_seq(merchant_id, display_seq);
+        let recorded = async {
+            self.get_redis_conn()
+                .change_context(StorageError::KVError)?
+                .set_key_with_expiry(&key, payout_id, self.ttl_for_kv.into())
+                .await
+                .change_context(StorageError::KVError)
+        }
+        .await;
+        if let Err(error) = recorded {
+            logger::warn!(?error, %key, "Failed to record the display sequence number of a payout");
+        }
+    }
+
+    /// Drops the KV counter of the merchant after a number was assigned from the database, so
+    /// that it is seeded again past that number by the next payout written to KV. A failure is
+    /// only logged.
+    pub async fn reset_kv_display_seq_counter(&self, merchant_id: &str) {
+        let key = kv::payout_display_seq_counter(merchant_id);
+        let deleted = async {
+            self.get_redis_conn()
+                .change_context(StorageError::KVError)?
+                .delete_key(&key)
+                .await
+                .change_context(StorageError::KVError)
+        }
+        .await;
+        if let Err(error) = deleted {
+            logger::warn!(?error, %key, "Failed to reset the payout display sequence counter");
+        }
+    }
+
+    /// The id of the payout written to KV with `display_seq`, `None` if it is not held in KV.
+    pub async fn find_kv_payout_id_by_display_seq(
+        &self,
+        merchant_id: &str,
+        display_seq: i64,
+    ) -> error_stack::Result<Option<String>, StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .get_key::<Option<String>>(&kv::payout_by_display_seq(merchant_id, display_seq))
+            .await
+            .change_context(StorageError::KVError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::{collections::HashMap, sync::Mutex};
+
+    use super::*;
+
+    /// An in-memory counter yielding between every step, so that concurrent callers interleave
+    /// like they would against Redis.
+    #[derive(Default)]
+    struct InMemoryCounter {
+        counters: Mutex<HashMap<String, i64>>,
+        last_taken_in_database: i64,
+    }
+
+    #[async_trait::async_trait]
+    impl PayoutDisplaySeqCounter for InMemoryCounter {
+        async fn counter_exists(
+            &self,
+            merchant_id: &str,
+        ) -> error_stack::Result<bool, StorageError> {
+            tokio::task::yield_now().await;
+            Ok(self.counters.lock().unwrap().contains_key(merchant_id))
+        }
+
+        async fn seed_counter(
+            &self,
+            merchant_id: &str,
+            last_taken: i64,
+        ) -> error_stack::Result<(), StorageError> {
+            tokio::task::yield_now().await;
+            self.counters
+                .lock()
+                .unwrap()
+                .entry(merchant_id.to_string())
+                .or_insert(last_taken);
+            Ok(())
+        }
+
+        async fn increment_counter(
+            &self,
+            merchant_id: &str,
+        ) -> error_stack::Result<i64, StorageError> {
+            tokio::task::yield_now().await;
+            let mut counters = self.counters.lock().unwrap();
+            let counter = counters.entry(merchant_id.to_string()).or_insert(0);
+            *counter += 1;
+            Ok(*counter)
+        }
+
+        async fn last_taken_in_database(
+            &self,
+            _merchant_id: &str,
+        ) -> error_stack::Result<i64, StorageError> {
+            tokio::task::yield_now().await;
+            Ok(self.last_taken_in_database)
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_inserts_get_unique_increasing_numbers() {
+        let counter = InMemoryCounter {
+            last_taken_in_database: 41,
+            ..Default::default()
+        };
+
+        // Every caller finds the counter missing and seeds it
+        let mut assigned = futures::future::join_all(
+            (0..100).map(|_| next_kv_display_seq(&counter, "merchant_1")),
+        )
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assigned.sort_unstable();
+        assert_eq!(assigned, (42..142).collect::<Vec<_>>());
+
+        // Later payouts keep increasing, independently of other merchants
+        assert_eq!(
+            next_kv_display_seq(&counter, "merchant_1").await.unwrap(),
+            142
+        );
+        assert_eq!(
+            next_kv_display_seq(&counter, "merchant_2").await.unwrap(),
+            42
+        );
+    }
+}
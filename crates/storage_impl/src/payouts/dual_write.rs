@@ -0,0 +1,486 @@
+use data_models::{errors::StorageError, payouts::payouts::Payouts};
+use diesel_models::payouts::Payouts as DieselPayouts;
+use error_stack::ResultExt;
+use redis_interface::errors::RedisError;
+use router_env::logger;
+
+use super::{kv_index::index_kv_payout, kv_key::read_from_first_key, payouts::payout_hash_fields};
+use crate::{redis::kv_store::RedisConnInterface, DataModelExt, DatabaseStore, KVRouterStore};
+
+/// The KV side of a payout of a merchant on the `DualWrite` storage scheme.
+///
+/// Postgres stays the source of truth for such payouts: writes are made there first and mirrored
+/// to the KV hash of the payout, while reads are served from Postgres and compared with a shadow
+/// read of the KV hash, so that divergences surface before the merchant is moved to `RedisKv`.
+#[async_trait::async_trait]
+pub trait PayoutMirror {
+    /// Overwrites the KV hash of the payout with `payout`, as written to Postgres.
+    async fn mirror_payout(&self, payout: &Payouts) -> error_stack::Result<(), StorageError>;
+
+    /// Reads the payout from its KV hash, `None` if it is not there.
+    async fn shadow_read_payout(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<Option<Payouts>, StorageError>;
+}
+
+#[async_trait::async_trait]
+impl<T: DatabaseStore> PayoutMirror for KVRouterStore<T> {
+    async fn mirror_payout(&self, payout: &Payouts) -> error_stack::Result<(), StorageError> {
+        let payout_key = self.payout_kv_key(&payout.merchant_id, &payout.payout_id);
+        let redis_fields = payout_hash_fields(
+            &payout_key,
+            &payout.clone().to_storage_model(),
+            self.payout_checksum.as_ref(),
+        )?;
+
+        // The payout is already persisted in Postgres, so it is not pushed to the drainer stream
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .set_hash_fields(&payout_key.key, redis_fields, Some(self.ttl_for_kv.into()))
+            .await
+            .change_context(StorageError::KVError)?;
+        self.record_kv_write(&payout_key.key);
+        index_kv_payout(self, &payout.merchant_id, &payout.payout_id).await;
+        Ok(())
+    }
+
+    async fn shadow_read_payout(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        let redis_conn = self
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?;
+        match read_from_first_key(&self.payout_kv_read_keys(merchant_id, payout_id), |key| {
+            redis_conn.get_hash_field_and_deserialize::<DieselPayouts>(
+                &key.key,
+                &key.field,
+                "DieselPayouts",
+            )
+        })
+        .await
+        {
+            Ok(payout) => Ok(Some(Payouts::from_storage_model(payout))),
+            Err(error) if matches!(error.current_context(), RedisError::NotFound) => Ok(None),
+            Err(error) => Err(error.change_context(StorageError::KVError)),
+        }
+    }
+}
+
+/// Mirrors the payout written to Postgres by `write` to the KV store.
+///
+/// Only the Postgres write can fail the operation, a failure to mirror the payout is logged.
+pub async fn write_and_mirror<M, F>(
+    mirror: &M,
+    write: F,
+) -> error_stack::Result<Payouts, StorageError>
+where
+    M: PayoutMirror + Sync + ?Sized,
+    F: std::future::Future<Output = error_stack::Result<Payouts, StorageError>> + Send,
+{
+    write_and_report_mirror(mirror, write)
+        .await
+        .map(|(payout, _)| payout)
+}
+
+/// Like [`write_and_mirror`], also telling whether the payout was mirrored to the KV store.
+pub async fn write_and_report_mirror<M, F>(
+    mirror: &M,
+    write: F,
+) -> error_stack::Result<(Payouts, bool), StorageError>
+where
+    M: PayoutMirror + Sync + ?Sized,
+    F: std::future::Future<Output = error_stack::Result<Payouts, StorageError>> + Send,
+{
+    let payout = write.await?;
+    match mirror.mirror_payout(&payout).await {
+        Ok(()) => Ok((payout, true)),
+        Err(error) => {
+            logger::warn!(
+                ?error,
+                payout_id = %payout.payout_id,
+                "Failed to mirror dual-written payout to the KV store"
+            );
+            Ok((payout, false))
+        }
+    }
+}
+
+/// Marks the KV copies of the payouts soft deleted in Postgres as deleted too, returning how
+/// many copies were marked.
+///
+/// Payouts without a KV copy, or whose copy is already marked, are skipped. Failing to mark a
+/// copy is logged, as Postgres already holds the deletion.
+pub async fn mark_deleted_in_kv<M>(mirror: &M, deleted: &[Payouts]) -> usize
+where
+    M: PayoutMirror + Sync + ?Sized,
+{
+    patch_kv_copies(mirror, deleted, "mark as deleted", |copy, payout| {
+        payout
+            .deleted_at
+            .is_some_and(|deleted_at| copy.mark_deleted(deleted_at))
+    })
+    .await
+}
+
+/// Sets the profile backfilled in Postgres on the KV copies of the payouts which still have
+/// none, returning how many copies were refreshed.
+///
+/// Payouts without a KV copy, or whose copy already has a profile, are skipped. Failing to
+/// refresh a copy is logged, as Postgres already holds the profile.
+pub async fn backfill_profile_ids_in_kv<M>(mirror: &M, backfilled: &[Payouts]) -> usize
+where
+    M: PayoutMirror + Sync + ?Sized,
+{
+    patch_kv_copies(
+        mirror,
+        backfilled,
+        "backfill the profile of",
+        |copy, payout| copy.backfill_profile_id(&payout.profile_id),
+    )
+    .await
+}
+
+/// Clamps the `last_modified_at` of the KV copies of the payouts repaired in Postgres, returning
+/// how many copies were repaired.
+///
+/// Payouts without a KV copy, or whose copy is consistent, are skipped. Failing to repair a copy
+/// is logged, as Postgres already holds the repaired timestamps.
+pub async fn repair_timestamps_in_kv<M>(mirror: &M, repaired: &[Payouts]) -> usize
+where
+    M: PayoutMirror + Sync + ?Sized,
+{
+    patch_kv_copies(mirror, repaired, "repair the timestamps of", |copy, _| {
+        copy.repair_timestamps()
+    })
+    .await
+}
+
+/// Applies `patch` to the KV copy of each of `payouts`, writing back the copies it changed and
+/// returning how many there were.
+async fn patch_kv_copies<M, F>(mirror: &M, payouts: &[Payouts], action: &str, patch: F) -> usize
+where
+    M: PayoutMirror + Sync + ?Sized,
+    F: Fn(&mut Payouts, &Payouts) -> bool,
+{
+    let mut patched = 0;
+    for payout in payouts {
+        let result = match mirror
+            .shadow_read_payout(&payout.merchant_id, &payout.payout_id)
+            .await
+        {
+            Ok(Some(mut copy)) if patch(&mut copy, payout) => {
+                mirror.mirror_payout(&copy).await.map(|()| true)
+            }
+            Ok(_) => Ok(false),
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(true) => patched += 1,
+            Ok(false) => {}
+            Err(error) => logger::warn!(
+                ?error,
+                payout_id = %payout.payout_id,
+                "Failed to {action} a payout in the KV store"
+            ),
+        }
+    }
+    patched
+}
+
+/// How the KV copy of a payout compares with the one read from Postgres.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ShadowReadOutcome {
+    Consistent,
+    MissingInKv,
+    /// Holds the names of the fields which differ.
+    Diverged(Vec<&'static str>),
+    Failed,
+}
+
+pub fn compare_shadow_read(
+    primary: &Payouts,
+    shadow: error_stack::Result<Option<Payouts>, StorageError>,
+) -> ShadowReadOutcome {
+    match shadow {
+        Ok(Some(shadow)) if shadow == *primary => ShadowReadOutcome::Consistent,
+        Ok(Some(shadow)) => ShadowReadOutcome::Diverged(diverging_fields(primary, &shadow)),
+        Ok(None) => ShadowReadOutcome::MissingInKv,
+        Err(_) => ShadowReadOutcome::Failed,
+    }
+}
+
+fn diverging_fields(primary: &Payouts, shadow: &Payouts) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if primary.status != shadow.status {
+        fields.push("status");
+    }
+    if primary.amount != shadow.amount {
+        fields.push("amount");
+    }
+    if primary.attempt_count != shadow.attempt_count {
+        fields.push("attempt_count");
+    }
+    if primary.payout_method_id != shadow.payout_method_id {
+        fields.push("payout_method_id");
+    }
+    if primary.metadata != shadow.metadata {
+        fields.push("metadata");
+    }
+    if primary.sequence_number != shadow.sequence_number {
+        fields.push("sequence_number");
+    }
+    if primary.last_modified_at != shadow.last_modified_at {
+        fields.push("last_modified_at");
+    }
+    if fields.is_empty() {
+        fields.push("other");
+    }
+    fields
+}
+
+/// Shadow reads `primary`, as read from Postgres, from the KV store and logs how they compare.
+pub async fn shadow_read<M>(mirror: &M, primary: &Payouts) -> ShadowReadOutcome
+where
+    M: PayoutMirror + Sync + ?Sized,
+{
+    let shadow = mirror
+        .shadow_read_payout(&primary.merchant_id, &primary.payout_id)
+        .await;
+    let outcome = compare_shadow_read(primary, shadow);
+    match &outcome {
+        ShadowReadOutcome::Consistent => {}
+        ShadowReadOutcome::MissingInKv => logger::warn!(
+            payout_id = %primary.payout_id,
+            "Dual-written payout is missing from the KV store"
+        ),
+        ShadowReadOutcome::Diverged(fields) => logger::warn!(
+            payout_id = %primary.payout_id,
+            diverging_fields = ?fields,
+            "Dual-written payout diverges between Postgres and the KV store"
+        ),
+        ShadowReadOutcome::Failed => logger::warn!(
+            payout_id = %primary.payout_id,
+            "Failed to shadow read dual-written payout from the KV store"
+        ),
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::sync::Mutex;
+
+    use diesel_models::enums::PayoutStatus;
+    use error_stack::IntoReport;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeKv {
+        payouts: Mutex<Vec<Payouts>>,
+        unavailable: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl PayoutMirror for FakeKv {
+        async fn mirror_payout(&self, payout: &Payouts) -> error_stack::Result<(), StorageError> {
+            if self.unavailable {
+                return Err(StorageError::KVError).into_report();
+            }
+            self.payouts.lock().unwrap().push(payout.clone());
+            Ok(())
+        }
+
+        async fn shadow_read_payout(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<Option<Payouts>, StorageError> {
+            Ok(self
+                .payouts
+                .lock()
+                .unwrap()
+                .iter()
+                .rev()
+                .find(|payout| payout.payout_id == payout_id)
+                .cloned())
+        }
+    }
+
+    fn sample_payout() -> Payouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(diesel_models::payouts::PayoutsNew {
+            payout_id: "po_1".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            ..Default::default()
+        })
+        .unwrap();
+        Payouts::from_storage_model(serde_json::from_value(stored).unwrap())
+    }
+
+    #[tokio::test]
+    async fn write_reaches_both_stores() {
+        let kv = FakeKv::default();
+        let postgres = Mutex::new(Vec::new());
+
+        let (written, mirrored) = write_and_report_mirror(&kv, async {
+            let payout = sample_payout();
+            postgres.lock().unwrap().push(payout.clone());
+            Ok(payout)
+        })
+        .await
+        .unwrap();
+        assert!(mirrored);
+
+        assert_eq!(*postgres.lock().unwrap(), [written.clone()]);
+        assert_eq!(*kv.payouts.lock().unwrap(), [written.clone()]);
+        assert_eq!(
+            shadow_read(&kv, &written).await,
+            ShadowReadOutcome::Consistent
+        );
+    }
+
+    #[tokio::test]
+    async fn kv_failure_does_not_fail_the_write() {
+        let kv = FakeKv {
+            unavailable: true,
+            ..Default::default()
+        };
+
+        let (written, mirrored) = write_and_report_mirror(&kv, async { Ok(sample_payout()) })
+            .await
+            .unwrap();
+        assert!(!mirrored);
+        assert_eq!(
+            shadow_read(&kv, &written).await,
+            ShadowReadOutcome::MissingInKv
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_postgres_write_is_not_mirrored() {
+        let kv = FakeKv::default();
+
+        write_and_mirror(&kv, async {
+            Err(StorageError::DatabaseConnectionError).into_report()
+        })
+        .await
+        .unwrap_err();
+        assert!(kv.payouts.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn divergence_is_reported() {
+        let kv = FakeKv::default();
+        let mut payout = sample_payout();
+        kv.mirror_payout(&payout).await.unwrap();
+
+        // Updated in Postgres while the mirror write was lost
+        payout.status = PayoutStatus::Success;
+        payout.sequence_number += 1;
+        assert_eq!(
+            shadow_read(&kv, &payout).await,
+            ShadowReadOutcome::Diverged(vec!["status", "sequence_number"])
+        );
+    }
+
+    #[tokio::test]
+    async fn soft_deleted_payouts_are_marked_once_in_kv() {
+        let kv = FakeKv::default();
+        let deleted_at = common_utils::date_time::now();
+        let copy = |payout_id: &str, deleted_at| Payouts {
+            payout_id: payout_id.to_string(),
+            deleted_at,
+            ..sample_payout()
+        };
+        kv.mirror_payout(&copy("po_1", None)).await.unwrap();
+        kv.mirror_payout(&copy("po_2", Some(deleted_at)))
+            .await
+            .unwrap();
+
+        // `po_2` is already marked and `po_3` has no KV copy
+        let deleted = ["po_1", "po_2", "po_3"].map(|payout_id| copy(payout_id, Some(deleted_at)));
+        assert_eq!(mark_deleted_in_kv(&kv, &deleted).await, 1);
+        assert_eq!(
+            kv.shadow_read_payout("merchant_1", "po_1")
+                .await
+                .unwrap()
+                .unwrap()
+                .deleted_at,
+            Some(deleted_at)
+        );
+
+        assert_eq!(mark_deleted_in_kv(&kv, &deleted).await, 0);
+        assert_eq!(kv.payouts.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn backfilled_profiles_refresh_kv_copies_without_a_profile() {
+        let kv = FakeKv::default();
+        let copy = |payout_id: &str, profile_id: &str| Payouts {
+            payout_id: payout_id.to_string(),
+            profile_id: profile_id.to_string(),
+            ..sample_payout()
+        };
+        kv.mirror_payout(&copy("po_1", "")).await.unwrap();
+        kv.mirror_payout(&copy("po_2", "pro_existing"))
+            .await
+            .unwrap();
+
+        // `po_2` already has a profile and `po_3` has no KV copy
+        let backfilled = ["po_1", "po_2", "po_3"].map(|payout_id| copy(payout_id, "pro_default"));
+        assert_eq!(backfill_profile_ids_in_kv(&kv, &backfilled).await, 1);
+        let profile_id = |payout_id| {
+            let payouts = kv.payouts.lock().unwrap();
+            payouts
+                .iter()
+                .rev()
+                .find(|payout| payout.payout_id == payout_id)
+                .map(|payout| payout.profile_id.clone())
+        };
+        assert_eq!(profile_id("po_1").as_deref(), Some("pro_default"));
+        assert_eq!(profile_id("po_2").as_deref(), Some("pro_existing"));
+
+        assert_eq!(backfill_profile_ids_in_kv(&kv, &backfilled).await, 0);
+    }
+
+    #[tokio::test]
+    async fn repaired_timestamps_refresh_inconsistent_kv_copies() {
+        let kv = FakeKv::default();
+        let payout = sample_payout();
+        let skewed = Payouts {
+            last_modified_at: payout.created_at - time::Duration::minutes(1),
+            ..payout.clone()
+        };
+        kv.mirror_payout(&skewed).await.unwrap();
+        kv.mirror_payout(&Payouts {
+            payout_id: "po_2".to_string(),
+            ..payout.clone()
+        })
+        .await
+        .unwrap();
+
+        // `po_2` is consistent and `po_3` has no KV copy
+        let repaired = ["po_1", "po_2", "po_3"].map(|payout_id| Payouts {
+            payout_id: payout_id.to_string(),
+            ..payout.clone()
+        });
+        assert_eq!(repair_timestamps_in_kv(&kv, &repaired).await, 1);
+        let copy = kv
+            .shadow_read_payout("merchant_1", "po_1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(copy.last_modified_at, copy.created_at);
+
+        assert_eq!(repair_timestamps_in_kv(&kv, &repaired).await, 0);
+    }
+}
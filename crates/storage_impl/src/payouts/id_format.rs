@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use data_models::errors::StorageError;
+use error_stack::{IntoReport, ResultExt};
+
+/// Checks the ids of the payouts inserted through a store, see
+/// [`crate::RouterStore::with_payout_id_validator`].
+pub trait PayoutIdValidator: std::fmt::Debug + Send + Sync {
+    /// Fails with `InvalidValue` on the `payout_id` field if `payout_id` is not accepted.
+    fn validate_payout_id(&self, payout_id: &str) -> error_stack::Result<(), StorageError>;
+}
+
+/// The format the ids of payouts are required to follow by some integrations.
+#[derive(Clone, Debug)]
+pub enum PayoutIdFormat {
+    /// Ids starting with `prefix`, between `min_length` and `max_length` characters long,
+    /// prefix included.
+    Prefixed {
+        prefix: String,
+        min_length: usize,
+        max_length: usize,
+    },
+    /// Ids matching `pattern`, which should be anchored to match whole ids only.
+    Pattern(regex::Regex),
+}
+
+impl PayoutIdValidator for PayoutIdFormat {
+    fn validate_payout_id(&self, payout_id: &str) -> error_stack::Result<(), StorageError> {
+        let conforms = match self {
+            Self::Prefixed {
+                prefix,
+                min_length,
+                max_length,
+            } => {
+                let length = payout_id.chars().count();
+                payout_id.starts_with(prefix.as_str())
+                    && (*min_length..=*max_length).contains(&length)
+            }
+            Self::Pattern(pattern) => pattern.is_match(payout_id),
+        };
+        if conforms {
+            return Ok(());
+        }
+        Err(StorageError::InvalidValue { field: "payout_id" })
+            .into_report()
+            .attach_printable(format!(
+                "payout id {payout_id} does not follow the format {self:?}"
+            ))
+    }
+}
+
+/// Checks `payout_id` with `validator`, if one is configured.
+pub(crate) fn validate_payout_id(
+    validator: Option<&Arc<dyn PayoutIdValidator>>,
+    payout_id: &str,
+) -> error_stack::Result<(), StorageError> {
+    validator.map_or(Ok(()), |validator| validator.validate_payout_id(payout_id))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn prefixed() -> PayoutIdFormat {
+        PayoutIdFormat::Prefixed {
+            prefix: "po_".to_string(),
+            min_length: 10,
+            max_length: 20,
+        }
+    }
+
+    fn assert_rejected(format: &PayoutIdFormat, payout_id: &str) {
+        assert!(matches!(
+            format
+                .validate_payout_id(payout_id)
+                .unwrap_err()
+                .current_context(),
+            StorageError::InvalidValue { field: "payout_id" }
+        ));
+    }
+
+    #[test]
+    fn conforming_id_is_accepted() {
+        assert!(prefixed().validate_payout_id("po_12345678").is_ok());
+        let pattern = PayoutIdFormat::Pattern(regex::Regex::new("^po_[0-9a-z]{8}$").unwrap());
+        assert!(pattern.validate_payout_id("po_1a2b3c4d").is_ok());
+    }
+
+    #[test]
+    fn too_short_id_is_rejected() {
+        assert_rejected(&prefixed(), "po_1234");
+        let pattern = PayoutIdFormat::Pattern(regex::Regex::new("^po_[0-9a-z]{8}$").unwrap());
+        assert_rejected(&pattern, "po_1a2b");
+    }
+
+    #[test]
+    fn id_with_wrong_prefix_is_rejected() {
+        assert_rejected(&prefixed(), "pay_12345678");
+        let pattern = PayoutIdFormat::Pattern(regex::Regex::new("^po_[0-9a-z]{8}$").unwrap());
+        assert_rejected(&pattern, "xpo_1a2b3c4d");
+    }
+
+    #[test]
+    fn any_id_is_accepted_without_validator() {
+        assert!(validate_payout_id(None, "anything").is_ok());
+        let validator: Arc<dyn PayoutIdValidator> = Arc::new(prefixed());
+        assert!(validate_payout_id(Some(&validator), "anything").is_err());
+    }
+}
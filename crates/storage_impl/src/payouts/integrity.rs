@@ -0,0 +1,265 @@
+use common_utils::errors::CustomResult;
+use data_models::errors::StorageError;
+use diesel_models::payouts::Payouts as DieselPayouts;
+use error_stack::{IntoReport, ResultExt};
+use redis_interface::{errors::RedisError, RedisConnectionPool};
+use ring::hmac;
+use router_env::logger;
+
+use super::kv_key::{hex_encode, PayoutKvKey};
+use crate::{
+    metrics,
+    redis::kv_store::{kv_wrapper, KvOperation},
+    DatabaseStore, KVRouterStore,
+};
+
+/// Keyed checksum of the payouts written to KV, so that a payout modified outside of the
+/// application is detected when read.
+///
+/// The checksum is an HMAC-SHA256 of the serialized payout, held in a field next to it. Payouts
+/// are signed with the current secret and verified with it or any of the previous ones, so that
+/// the secret can be rotated without failing the checks of the payouts written before.
+#[derive(Clone)]
+pub struct PayoutChecksum {
+    current: hmac::Key,
+    previous: Vec<hmac::Key>,
+}
+
+impl std::fmt::Debug for PayoutChecksum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PayoutChecksum").finish_non_exhaustive()
+    }
+}
+
+impl PayoutChecksum {
+    pub fn new(secret: &[u8], previous_secrets: &[&[u8]]) -> Self {
+        Self {
+            current: hmac::Key::new(hmac::HMAC_SHA256, secret),
+            previous: previous_secrets
+                .iter()
+                .map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret))
+                .collect(),
+        }
+    }
+
+    /// Hex encoded checksum of `value` with the current secret.
+    pub fn sign(&self, value: &[u8]) -> String {
+        sign_with(&self.current, value)
+    }
+
+    /// Fails with `IntegrityCheckFailed` unless `checksum` is the one of `value` with the current
+    /// secret or any of the previous ones.
+    pub fn verify(&self, value: &[u8], checksum: &[u8]) -> error_stack::Result<(), StorageError> {
+        let verified = std::iter::once(&self.current)
+            .chain(&self.previous)
+            .any(|key| {
+                ring::constant_time::verify_slices_are_equal(
+                    sign_with(key, value).as_bytes(),
+                    checksum,
+                )
+                .is_ok()
+            });
+        if verified {
+            Ok(())
+        } else {
+            Err(StorageError::IntegrityCheckFailed).into_report()
+        }
+    }
+}
+
+fn sign_with(key: &hmac::Key, value: &[u8]) -> String {
+    hex_encode(hmac::sign(key, value).as_ref())
+}
+
+/// Reads the serialized payouts held under `keys` in one round trip, in the same order, along
+/// with their checksums if `checksum` is set.
+///
+/// A payout failing its integrity check is reported as missing, so that it is read from the
+/// database instead. A payout without a checksum, written before checksums were enabled, is not
+/// checked.
+pub async fn get_verified_payout_values(
+    redis_conn: &RedisConnectionPool,
+    checksum: Option<&PayoutChecksum>,
+    keys: &[PayoutKvKey],
+) -> CustomResult<Vec<Option<Vec<u8>>>, RedisError> {
+    let Some(checksum) = checksum else {
+        let key_fields = keys
+            .iter()
+            .map(|key| (key.key.clone(), key.field.clone()))
+            .collect::<Vec<_>>();
+        return redis_conn.get_multiple_hash_fields(&key_fields).await;
+    };
+
+    let key_fields = keys
+        .iter()
+        .flat_map(|key| {
+            [
+                (key.key.clone(), key.field.clone()),
+                (key.key.clone(), key.checksum_field()),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let mut values = redis_conn
+        .get_multiple_hash_fields(&key_fields)
+        .await?
+        .into_iter();
+    Ok(keys
+        .iter()
+        .map(|key| {
+            let value = values.next().flatten();
+            let expected = values.next().flatten();
+            verified_value(checksum, key, value, expected)
+        })
+        .collect())
+}
+
+/// Reads the payout held under `key`, see [`get_verified_payout_values`]. Fails with `NotFound`
+/// if the payout is missing or fails its integrity check.
+pub async fn get_verified_payout<T>(
+    redis_conn: &RedisConnectionPool,
+    checksum: &PayoutChecksum,
+    key: &PayoutKvKey,
+) -> CustomResult<T, RedisError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let value = get_verified_payout_values(redis_conn, Some(checksum), std::slice::from_ref(key))
+        .await?
+        .into_iter()
+        .next()
+        .flatten()
+        .filter(|value| !value.is_empty())
+        .ok_or(RedisError::NotFound)
+        .into_report()?;
+    serde_json::from_slice(&value)
+        .into_report()
+        .change_context(RedisError::JsonDeserializationFailed)
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Reads the payout held under `key`, checking its integrity if a [`PayoutChecksum`] is
+    /// configured. Fails with `NotFound` if the payout is missing or fails its integrity check.
+    pub async fn read_kv_payout(
+        &self,
+        key: &PayoutKvKey,
+    ) -> CustomResult<DieselPayouts, RedisError> {
+        match self.payout_checksum.as_ref() {
+            Some(checksum) => {
+                get_verified_payout(
+                    &self.get_redis_read_conn([key.key.as_str()])?,
+                    checksum,
+                    key,
+                )
+                .await
+            }
+            None => kv_wrapper::<DieselPayouts, _, _>(
+                self,
+                KvOperation::<DieselPayouts>::HGet(&key.field),
+                &key.key,
+            )
+            .await?
+            .try_into_hget(),
+        }
+    }
+}
+
+fn verified_value(
+    checksum: &PayoutChecksum,
+    key: &PayoutKvKey,
+    value: Option<Vec<u8>>,
+    expected: Option<Vec<u8>>,
+) -> Option<Vec<u8>> {
+    let value = value?;
+    let Some(expected) = expected else {
+        return Some(value);
+    };
+    match checksum.verify(&value, &expected) {
+        Ok(()) => Some(value),
+        Err(error) => {
+            logger::warn!(
+                ?error,
+                key = %key.key,
+                "A payout read from KV failed its integrity check, reading it from the database"
+            );
+            metrics::PAYOUT_KV_INTEGRITY_CHECK_FAILED.add(&metrics::CONTEXT, 1, &[]);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    const PAYOUT: &[u8] = br#"{"payout_id":"po_1","amount":100}"#;
+
+    fn key() -> PayoutKvKey {
+        PayoutKvKey::raw("merchant_1", "po_1")
+    }
+
+    #[test]
+    fn valid_value_passes() {
+        let checksum = PayoutChecksum::new(b"secret", &[]);
+        let signature = checksum.sign(PAYOUT);
+        checksum.verify(PAYOUT, signature.as_bytes()).unwrap();
+        assert_eq!(
+            verified_value(
+                &checksum,
+                &key(),
+                Some(PAYOUT.to_vec()),
+                Some(signature.into_bytes())
+            ),
+            Some(PAYOUT.to_vec())
+        );
+    }
+
+    #[test]
+    fn tampered_value_fails_and_falls_back() {
+        let checksum = PayoutChecksum::new(b"secret", &[]);
+        let signature = checksum.sign(PAYOUT);
+        let tampered = br#"{"payout_id":"po_1","amount":100000}"#;
+
+        let error = checksum.verify(tampered, signature.as_bytes()).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::IntegrityCheckFailed
+        ));
+        // Reported as missing from KV, so that the payout is read from the database
+        assert_eq!(
+            verified_value(
+                &checksum,
+                &key(),
+                Some(tampered.to_vec()),
+                Some(signature.into_bytes())
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn value_without_checksum_is_not_checked() {
+        let checksum = PayoutChecksum::new(b"secret", &[]);
+        assert_eq!(
+            verified_value(&checksum, &key(), Some(PAYOUT.to_vec()), None),
+            Some(PAYOUT.to_vec())
+        );
+    }
+
+    #[test]
+    fn values_signed_before_rotation_pass() {
+        let signed_before = PayoutChecksum::new(b"old secret", &[]).sign(PAYOUT);
+        let rotated = PayoutChecksum::new(b"new secret", &[b"old secret"]);
+
+        rotated.verify(PAYOUT, signed_before.as_bytes()).unwrap();
+        rotated
+            .verify(PAYOUT, rotated.sign(PAYOUT).as_bytes())
+            .unwrap();
+        assert_ne!(rotated.sign(PAYOUT), signed_before);
+
+        // Once the old secret is retired, the values it signed fail their check
+        let retired = PayoutChecksum::new(b"new secret", &[]);
+        assert!(retired.verify(PAYOUT, signed_before.as_bytes()).is_err());
+    }
+}
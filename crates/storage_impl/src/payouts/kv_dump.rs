@@ -0,0 +1,572 @@
+use std::collections::BTreeSet;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use data_models::{errors::StorageError, payouts::ids::MerchantId};
+use error_stack::{IntoReport, ResultExt};
+use redis_interface::HsetnxReply;
+use router_env::{instrument, logger, tracing};
+
+use super::kv_index::PayoutKvIndex;
+use crate::{redis::kv_store::RedisConnInterface, DatabaseStore, KVRouterStore};
+
+/// Leading bytes of a dump, followed by the version of its format.
+const DUMP_MAGIC: &[u8; 4] = b"PKVD";
+const DUMP_VERSION: u8 = 1;
+
+/// Snapshot of the payouts of a merchant written to KV: the index of the merchant and every
+/// field of the hashes of the payouts it lists, see [`KVRouterStore::dump_merchant_payout_kv`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PayoutKvDump {
+    pub merchant_id: String,
+    pub index: Vec<String>,
+    pub hashes: Vec<DumpedHash>,
+}
+
+/// A Redis hash of a [`PayoutKvDump`], with its fields ordered by name.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DumpedHash {
+    pub key: String,
+    pub fields: Vec<(String, Vec<u8>)>,
+}
+
+/// The outcome of [`KVRouterStore::restore_merchant_payout_kv`].
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct PayoutKvRestoreReport {
+    /// Fields written back from the dump.
+    pub restored_fields: usize,
+    /// Fields of the dump left as is, as they were written again since the dump was taken.
+    pub skipped_fields: usize,
+}
+
+impl PayoutKvDump {
+    /// Serializes the dump, with each string and value prefixed by its length.
+    pub fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+        buf.put_slice(DUMP_MAGIC);
+        buf.put_u8(DUMP_VERSION);
+        put_bytes(&mut buf, self.merchant_id.as_bytes());
+        put_len(&mut buf, self.index.len());
+        for payout_id in &self.index {
+            put_bytes(&mut buf, payout_id.as_bytes());
+        }
+        put_len(&mut buf, self.hashes.len());
+        for hash in &self.hashes {
+            put_bytes(&mut buf, hash.key.as_bytes());
+            put_len(&mut buf, hash.fields.len());
+            for (field, value) in &hash.fields {
+                put_bytes(&mut buf, field.as_bytes());
+                put_bytes(&mut buf, value);
+            }
+        }
+        buf.freeze()
+    }
+
+    /// Parses a dump produced by [`Self::encode`], failing with `DeserializationFailed` if it is
+    /// truncated or of another format.
+    pub fn decode(mut dump: &[u8]) -> error_stack::Result<Self, StorageError> {
+        let magic = take(&mut dump, DUMP_MAGIC.len())?;
+        if magic != DUMP_MAGIC || take(&mut dump, 1)? != [DUMP_VERSION] {
+            return Err(StorageError::DeserializationFailed)
+                .into_report()
+                .attach_printable("not a payout KV dump of a supported version");
+        }
+
+        let merchant_id = take_string(&mut dump)?;
+        let index = (0..take_len(&mut dump)?)
+            .map(|_| take_string(&mut dump))
+            .collect::<Result<Vec<_>, _>>()?;
+        let hashes = (0..take_len(&mut dump)?)
+            .map(|_| {
+                let key = take_string(&mut dump)?;
+                let fields = (0..take_len(&mut dump)?)
+                    .map(|_| Ok((take_string(&mut dump)?, take_bytes(&mut dump)?.to_vec())))
+                    .collect::<error_stack::Result<Vec<_>, StorageError>>()?;
+                Ok(DumpedHash { key, fields })
+            })
+            .collect::<error_stack::Result<Vec<_>, StorageError>>()?;
+
+        if dump.has_remaining() {
+            return Err(StorageError::DeserializationFailed)
+                .into_report()
+                .attach_printable("trailing bytes after the payout KV dump");
+        }
+        Ok(Self {
+            merchant_id,
+            index,
+            hashes,
+        })
+    }
+}
+
+fn put_len(buf: &mut BytesMut, len: usize) {
+    // Redis caps strings at 512 MB, well within `u32`
+    buf.put_u32(u32::try_from(len).unwrap_or(u32::MAX));
+}
+
+fn put_bytes(buf: &mut BytesMut, bytes: &[u8]) {
+    put_len(buf, bytes.len());
+    buf.put_slice(bytes);
+}
+
+fn take<'a>(dump: &mut &'a [u8], len: usize) -> error_stack::Result<&'a [u8], StorageError> {
+    if dump.len() < len {
+        return Err(StorageError::DeserializationFailed)
+            .into_report()
+            .attach_printable("the payout KV dump is truncated");
+    }
+    let (taken, rest) = dump.split_at(len);
+    *dump = rest;
+    Ok(taken)
+}
+
+fn take_len(dump: &mut &[u8]) -> error_stack::Result<usize, StorageError> {
+    let mut len = take(dump, 4)?;
+    usize::try_from(len.get_u32())
+        .into_report()
+        .change_context(StorageError::DeserializationFailed)
+}
+
+fn take_bytes<'a>(dump: &mut &'a [u8]) -> error_stack::Result<&'a [u8], StorageError> {
+    let len = take_len(dump)?;
+    take(dump, len)
+}
+
+fn take_string(dump: &mut &[u8]) -> error_stack::Result<String, StorageError> {
+    String::from_utf8(take_bytes(dump)?.to_vec())
+        .into_report()
+        .change_context(StorageError::DeserializationFailed)
+}
+
+/// The Redis hashes holding the payouts of a merchant written to KV.
+#[async_trait::async_trait]
+pub trait PayoutKvHashes {
+    /// The keys of the hashes holding the payout and its attempts.
+    fn payout_hash_keys(&self, merchant_id: &str, payout_id: &str) -> Vec<String>;
+
+    /// Every field of the hash at `key`, ordered by name, empty if the hash is missing.
+    async fn get_hash(
+        &self,
+        key: &str,
+    ) -> error_stack::Result<Vec<(String, Vec<u8>)>, StorageError>;
+
+    /// Sets the field unless it is already set, returns whether it was set.
+    async fn set_hash_field_if_not_exist(
+        &self,
+        key: &str,
+        field: &str,
+        value: &[u8],
+    ) -> error_stack::Result<bool, StorageError>;
+}
+
+#[async_trait::async_trait]
+impl<T: DatabaseStore> PayoutKvHashes for KVRouterStore<T> {
+    fn payout_hash_keys(&self, merchant_id: &str, payout_id: &str) -> Vec<String> {
+        let mut keys = self
+            .payout_kv_read_keys(merchant_id, payout_id)
+            .into_iter()
+            .map(|key| key.key)
+            .collect::<Vec<_>>();
+        keys.push(format!("mid_{merchant_id}_poa_{payout_id}"));
+        keys
+    }
+
+    async fn get_hash(
+        &self,
+        key: &str,
+    ) -> error_stack::Result<Vec<(String, Vec<u8>)>, StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .get_all_hash_fields(key)
+            .await
+            .change_context(StorageError::KVError)
+    }
+
+    async fn set_hash_field_if_not_exist(
+        &self,
+        key: &str,
+        field: &str,
+        value: &[u8],
+    ) -> error_stack::Result<bool, StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .set_hash_field_if_not_exist(key, field, value, Some(self.ttl_for_kv))
+            .await
+            .change_context(StorageError::KVError)
+            .map(|reply| matches!(reply, HsetnxReply::KeySet))
+    }
+}
+
+/// Snapshots the index of the merchant and the hashes of the payouts it lists, see
+/// [`KVRouterStore::dump_merchant_payout_kv`].
+pub async fn dump_payout_kv<S>(
+    store: &S,
+    merchant_id: &str,
+) -> error_stack::Result<PayoutKvDump, StorageError>
+where
+    S: PayoutKvIndex + PayoutKvHashes + Sync + ?Sized,
+{
+    let index = store
+        .indexed_payout_ids(merchant_id)
+        .await?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+
+    let mut hashes = Vec::new();
+    for payout_id in &index {
+        for key in store.payout_hash_keys(merchant_id, payout_id) {
+            let fields = store.get_hash(&key).await?;
+            if !fields.is_empty() {
+                hashes.push(DumpedHash { key, fields });
+            }
+        }
+    }
+    Ok(PayoutKvDump {
+        merchant_id: merchant_id.to_string(),
+        index: index.into_iter().collect(),
+        hashes,
+    })
+}
+
+/// Writes `dump` back, see [`KVRouterStore::restore_merchant_payout_kv`].
+pub async fn restore_payout_kv<S>(
+    store: &S,
+    merchant_id: &str,
+    dump: &PayoutKvDump,
+) -> error_stack::Result<PayoutKvRestoreReport, StorageError>
+where
+    S: PayoutKvIndex + PayoutKvHashes + Sync + ?Sized,
+{
+    let key_prefix = format!("mid_{merchant_id}_");
+    if dump.merchant_id != merchant_id
+        || dump
+            .hashes
+            .iter()
+            .any(|hash| !hash.key.starts_with(&key_prefix))
+    {
+        return Err(StorageError::InvalidValue {
+            field: "merchant_id",
+        })
+        .into_report()
+        .attach_printable(format!(
+            "the payout KV dump of merchant {} cannot be restored for merchant {merchant_id}",
+            dump.merchant_id
+        ));
+    }
+
+    let mut report = PayoutKvRestoreReport::default();
+    for hash in &dump.hashes {
+        for (field, value) in &hash.fields {
+            if store
+                .set_hash_field_if_not_exist(&hash.key, field, value)
+                .await?
+            {
+                report.restored_fields += 1;
+            } else {
+                report.skipped_fields += 1;
+            }
+        }
+    }
+    for payout_id in &dump.index {
+        store.add_to_index(merchant_id, payout_id).await?;
+    }
+    Ok(report)
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Serializes the KV state of the payouts of the merchant, for restoring it with
+    /// [`Self::restore_merchant_payout_kv`] after losing the Redis instance.
+    ///
+    /// Only the payouts listed in the KV index of the merchant are dumped, along with their
+    /// attempts, see [`Self::audit_kv_index_consistency`] for repairing the index first.
+    #[instrument(skip_all)]
+    pub async fn dump_merchant_payout_kv(
+        &self,
+        merchant_id: &MerchantId,
+    ) -> error_stack::Result<Bytes, StorageError> {
+        let dump = dump_payout_kv(self, merchant_id).await?;
+        logger::info!(
+            %merchant_id,
+            payouts = dump.index.len(),
+            hashes = dump.hashes.len(),
+            "Dumped the KV state of the payouts of the merchant"
+        );
+        Ok(dump.encode())
+    }
+
+    /// Writes back a dump taken with [`Self::dump_merchant_payout_kv`] for the same merchant.
+    ///
+    /// Fields are written with `HSETNX`, so that the ones written again since the dump was
+    /// taken are left as is rather than rolled back.
+    #[instrument(skip_all)]
+    pub async fn restore_merchant_payout_kv(
+        &self,
+        merchant_id: &MerchantId,
+        dump: &[u8],
+    ) -> error_stack::Result<PayoutKvRestoreReport, StorageError> {
+        let dump = PayoutKvDump::decode(dump)?;
+        let report = restore_payout_kv(self, merchant_id, &dump).await?;
+        logger::info!(
+            %merchant_id,
+            restored_fields = report.restored_fields,
+            skipped_fields = report.skipped_fields,
+            "Restored the KV state of the payouts of the merchant"
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        sync::Mutex,
+    };
+
+    use diesel_models::enums::PayoutStatus;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeKv {
+        index: Mutex<HashSet<String>>,
+        hashes: Mutex<HashMap<String, BTreeMap<String, Vec<u8>>>>,
+    }
+
+    impl FakeKv {
+        fn with_payouts(payout_ids: &[&str]) -> Self {
+            let kv = Self::default();
+            for payout_id in payout_ids {
+                kv.index.lock().unwrap().insert(payout_id.to_string());
+                let fields = BTreeMap::from([
+                    (
+                        format!("po_{payout_id}"),
+                        format!("{{\"payout_id\":\"{payout_id}\"}}"),
+                    ),
+                    (format!("po_{payout_id}_status"), "\"pending\"".to_string()),
+                ]);
+                kv.hashes.lock().unwrap().insert(
+                    format!("mid_merchant_1_po_{payout_id}"),
+                    fields
+                        .into_iter()
+                        .map(|(field, value)| (field, value.into_bytes()))
+                        .collect(),
+                );
+                kv.hashes.lock().unwrap().insert(
+                    format!("mid_merchant_1_poa_{payout_id}"),
+                    BTreeMap::from([(format!("poa_{payout_id}_1"), b"{}".to_vec())]),
+                );
+            }
+            kv
+        }
+
+        fn clear(&self) {
+            self.index.lock().unwrap().clear();
+            self.hashes.lock().unwrap().clear();
+        }
+
+        fn snapshot(&self) -> (HashSet<String>, HashMap<String, BTreeMap<String, Vec<u8>>>) {
+            (
+                self.index.lock().unwrap().clone(),
+                self.hashes.lock().unwrap().clone(),
+            )
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PayoutKvIndex for FakeKv {
+        async fn indexed_payout_ids(
+            &self,
+            _merchant_id: &str,
+        ) -> error_stack::Result<Vec<String>, StorageError> {
+            Ok(self.index.lock().unwrap().iter().cloned().collect())
+        }
+
+        async fn stored_payout_ids(
+            &self,
+            _merchant_id: &str,
+        ) -> error_stack::Result<Vec<String>, StorageError> {
+            Ok(Vec::new())
+        }
+
+        async fn has_payout_hash(
+            &self,
+            merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<bool, StorageError> {
+            Ok(self
+                .hashes
+                .lock()
+                .unwrap()
+                .contains_key(&format!("mid_{merchant_id}_po_{payout_id}")))
+        }
+
+        async fn add_to_index(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<(), StorageError> {
+            self.index.lock().unwrap().insert(payout_id.to_string());
+            Ok(())
+        }
+
+        async fn remove_from_index(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<(), StorageError> {
+            self.index.lock().unwrap().remove(payout_id);
+            Ok(())
+        }
+
+        async fn payout_value_size(
+            &self,
+            merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<Option<usize>, StorageError> {
+            Ok(self
+                .hashes
+                .lock()
+                .unwrap()
+                .get(&format!("mid_{merchant_id}_po_{payout_id}"))
+                .and_then(|fields| fields.get(&format!("po_{payout_id}")))
+                .map(Vec::len))
+        }
+
+        async fn kv_payout_status(
+            &self,
+            _merchant_id: &str,
+            _payout_id: &str,
+        ) -> error_stack::Result<Option<PayoutStatus>, StorageError> {
+            Ok(None)
+        }
+
+        async fn refresh_payout_ttl(
+            &self,
+            _merchant_id: &str,
+            _payout_id: &str,
+        ) -> error_stack::Result<(), StorageError> {
+            Ok(())
+        }
+
+        async fn refresh_index_ttl(
+            &self,
+            _merchant_id: &str,
+        ) -> error_stack::Result<(), StorageError> {
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PayoutKvHashes for FakeKv {
+        fn payout_hash_keys(&self, merchant_id: &str, payout_id: &str) -> Vec<String> {
+            vec![
+                format!("mid_{merchant_id}_po_{payout_id}"),
+                format!("mid_{merchant_id}_poa_{payout_id}"),
+            ]
+        }
+
+        async fn get_hash(
+            &self,
+            key: &str,
+        ) -> error_stack::Result<Vec<(String, Vec<u8>)>, StorageError> {
+            Ok(self
+                .hashes
+                .lock()
+                .unwrap()
+                .get(key)
+                .map(|fields| fields.clone().into_iter().collect())
+                .unwrap_or_default())
+        }
+
+        async fn set_hash_field_if_not_exist(
+            &self,
+            key: &str,
+            field: &str,
+            value: &[u8],
+        ) -> error_stack::Result<bool, StorageError> {
+            let mut hashes = self.hashes.lock().unwrap();
+            let fields = hashes.entry(key.to_string()).or_default();
+            if fields.contains_key(field) {
+                return Ok(false);
+            }
+            fields.insert(field.to_string(), value.to_vec());
+            Ok(true)
+        }
+    }
+
+    #[tokio::test]
+    async fn dump_round_trips_through_clear_and_restore() {
+        let kv = FakeKv::with_payouts(&["po_1", "po_2"]);
+        let before = kv.snapshot();
+
+        let dump = dump_payout_kv(&kv, "merchant_1").await.unwrap();
+        assert_eq!(dump.index, ["po_1", "po_2"]);
+        assert_eq!(dump.hashes.len(), 4);
+        let encoded = dump.encode();
+        assert_eq!(PayoutKvDump::decode(&encoded).unwrap(), dump);
+
+        kv.clear();
+        let report = restore_payout_kv(&kv, "merchant_1", &PayoutKvDump::decode(&encoded).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            PayoutKvRestoreReport {
+                restored_fields: 6,
+                skipped_fields: 0,
+            }
+        );
+        assert_eq!(kv.snapshot(), before);
+    }
+
+    #[tokio::test]
+    async fn restore_does_not_clobber_newer_fields() {
+        let kv = FakeKv::with_payouts(&["po_1"]);
+        let dump = dump_payout_kv(&kv, "merchant_1").await.unwrap();
+
+        kv.clear();
+        kv.set_hash_field_if_not_exist("mid_merchant_1_po_po_1", "po_po_1_status", b"\"success\"")
+            .await
+            .unwrap();
+        let report = restore_payout_kv(&kv, "merchant_1", &dump).await.unwrap();
+        assert_eq!(report.skipped_fields, 1);
+        assert_eq!(
+            kv.hashes.lock().unwrap()["mid_merchant_1_po_po_1"]["po_po_1_status"],
+            b"\"success\"".to_vec()
+        );
+
+        // A dump is only restored for the merchant it was taken for
+        let error = restore_payout_kv(&kv, "merchant_2", &dump)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InvalidValue {
+                field: "merchant_id"
+            }
+        ));
+    }
+
+    #[test]
+    fn truncated_or_foreign_dumps_are_rejected() {
+        let encoded = PayoutKvDump {
+            merchant_id: "merchant_1".to_string(),
+            index: vec!["po_1".to_string()],
+            hashes: Vec::new(),
+        }
+        .encode();
+
+        for dump in [
+            &encoded[..encoded.len() - 1],
+            b"{\"payouts\":[]}".as_slice(),
+        ] {
+            let error = PayoutKvDump::decode(dump).unwrap_err();
+            assert!(matches!(
+                error.current_context(),
+                StorageError::DeserializationFailed
+            ));
+        }
+    }
+}
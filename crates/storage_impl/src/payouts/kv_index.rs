@@ -0,0 +1,723 @@
+use std::collections::BTreeSet;
+
+use data_models::{
+    errors::StorageError,
+    payouts::{ids::MerchantId, payouts::is_non_terminal_status},
+};
+use diesel_models::{enums::PayoutStatus, kv, payouts::Payouts as DieselPayouts};
+use error_stack::ResultExt;
+use redis_interface::errors::RedisError;
+use router_env::{instrument, logger, tracing};
+
+use super::kv_key::read_from_first_key;
+use crate::{
+    diesel_error_to_data_error, metrics, redis::kv_store::RedisConnInterface,
+    utils::pg_connection_read, DatabaseStore, KVRouterStore,
+};
+
+/// How the index of the payouts of a merchant written to KV compares with the payout hashes
+/// actually present, see [`KVRouterStore::audit_kv_index_consistency`].
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct IndexAuditReport {
+    /// Payouts listed in the index whose hash is missing.
+    pub indexed_without_hash: Vec<String>,
+    /// Payouts whose hash is present but which are missing from the index.
+    pub hash_not_indexed: Vec<String>,
+    /// Whether the index was repaired to match the hashes present.
+    pub repaired: bool,
+}
+
+impl IndexAuditReport {
+    pub fn is_consistent(&self) -> bool {
+        self.indexed_without_hash.is_empty() && self.hash_not_indexed.is_empty()
+    }
+}
+
+/// The memory taken in KV by the payouts of a merchant, see
+/// [`KVRouterStore::estimate_merchant_kv_footprint`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct KvFootprint {
+    /// Number of payouts listed in the index whose hash is present.
+    pub payout_count: usize,
+    /// Total size in bytes of the values of these payouts, as held in Redis.
+    pub total_bytes: u64,
+}
+
+/// The index of the payouts of a merchant written to KV, and the payout hashes it tracks.
+#[async_trait::async_trait]
+pub trait PayoutKvIndex {
+    /// The ids of the payouts listed in the index of the merchant.
+    async fn indexed_payout_ids(
+        &self,
+        merchant_id: &str,
+    ) -> error_stack::Result<Vec<String>, StorageError>;
+
+    /// The ids of the payouts of the merchant persisted in Postgres, whose hash may be present
+    /// without being indexed.
+    async fn stored_payout_ids(
+        &self,
+        merchant_id: &str,
+    ) -> error_stack::Result<Vec<String>, StorageError>;
+
+    async fn has_payout_hash(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<bool, StorageError>;
+
+    async fn add_to_index(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<(), StorageError>;
+
+    async fn remove_from_index(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<(), StorageError>;
+
+    /// The size in bytes of the value of the payout as held in KV, after any encoding applied on
+    /// write, or `None` if its hash is missing.
+    async fn payout_value_size(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<Option<usize>, StorageError>;
+
+    /// The status of the payout held in KV, or `None` if its hash is missing.
+    async fn kv_payout_status(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<Option<PayoutStatus>, StorageError>;
+
+    /// Extends the life of the hashes of the payout and of its attempts to the KV TTL.
+    async fn refresh_payout_ttl(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<(), StorageError>;
+
+    /// Extends the life of the index of the merchant to the KV TTL.
+    async fn refresh_index_ttl(&self, merchant_id: &str) -> error_stack::Result<(), StorageError>;
+}
+
+#[async_trait::async_trait]
+impl<T: DatabaseStore> PayoutKvIndex for KVRouterStore<T> {
+    async fn indexed_payout_ids(
+        &self,
+        merchant_id: &str,
+    ) -> error_stack::Result<Vec<String>, StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .smembers(&kv::payout_index(merchant_id))
+            .await
+            .change_context(StorageError::KVError)
+    }
+
+    async fn stored_payout_ids(
+        &self,
+        merchant_id: &str,
+    ) -> error_stack::Result<Vec<String>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::get_payout_ids_by_merchant_id(&conn, merchant_id)
+            .await
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    async fn has_payout_hash(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<bool, StorageError> {
+        let redis_conn = self
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?;
+        match read_from_first_key(&self.payout_kv_read_keys(merchant_id, payout_id), |key| {
+            redis_conn.get_hash_field_and_deserialize::<DieselPayouts>(
+                &key.key,
+                &key.field,
+                "DieselPayouts",
+            )
+        })
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(error) if matches!(error.current_context(), RedisError::NotFound) => Ok(false),
+            Err(error) => Err(error.change_context(StorageError::KVError)),
+        }
+    }
+
+    async fn add_to_index(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<(), StorageError> {
+        let index = kv::payout_index(merchant_id);
+        let redis_conn = self
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?;
+        redis_conn
+            .sadd(&index, payout_id)
+            .await
+            .change_context(StorageError::KVError)?;
+        // The index lives as long as the most recently written hash it lists
+        redis_conn
+            .set_expiry(&index, self.ttl_for_kv.into())
+            .await
+            .change_context(StorageError::KVError)
+    }
+
+    async fn remove_from_index(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<(), StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .srem(&kv::payout_index(merchant_id), payout_id)
+            .await
+            .change_context(StorageError::KVError)
+    }
+
+    async fn payout_value_size(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<Option<usize>, StorageError> {
+        let keys = self.payout_kv_read_keys(merchant_id, payout_id);
+        let redis_conn = self
+            .get_redis_read_conn(keys.iter().map(|key| key.key.as_str()))
+            .change_context(StorageError::KVError)?;
+        let redis_conn = &redis_conn;
+        // The raw value is measured rather than the payout deserialized from it, which is what
+        // Redis actually holds
+        match read_from_first_key(&keys, |key| async move {
+            let value = redis_conn
+                .get_hash_field::<Vec<u8>>(&key.key, &key.field)
+                .await?;
+            if value.is_empty() {
+                Err(RedisError::NotFound.into())
+            } else {
+                Ok(value.len())
+            }
+        })
+        .await
+        {
+            Ok(size) => Ok(Some(size)),
+            Err(error) if matches!(error.current_context(), RedisError::NotFound) => Ok(None),
+            Err(error) => Err(error.change_context(StorageError::KVError)),
+        }
+    }
+
+    async fn kv_payout_status(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<Option<PayoutStatus>, StorageError> {
+        match read_from_first_key(&self.payout_kv_read_keys(merchant_id, payout_id), |key| {
+            self.read_kv_payout(key)
+        })
+        .await
+        {
+            Ok(payout) => Ok(Some(payout.status)),
+            Err(error) if matches!(error.current_context(), RedisError::NotFound) => Ok(None),
+            Err(error) => Err(error.change_context(StorageError::KVError)),
+        }
+    }
+
+    async fn refresh_payout_ttl(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> error_stack::Result<(), StorageError> {
+        let redis_conn = self
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?;
+        // Expiring a key which is missing is a no-op, so every key the payout may be held under
+        // is refreshed
+        let keys = self
+            .payout_kv_read_keys(merchant_id, payout_id)
+            .into_iter()
+            .map(|key| key.key)
+            .chain([format!("mid_{merchant_id}_poa_{payout_id}")]);
+        for key in keys {
+            redis_conn
+                .set_expiry(&key, self.ttl_for_kv.into())
+                .await
+                .change_context(StorageError::KVError)?;
+        }
+        Ok(())
+    }
+
+    async fn refresh_index_ttl(&self, merchant_id: &str) -> error_stack::Result<(), StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .set_expiry(&kv::payout_index(merchant_id), self.ttl_for_kv.into())
+            .await
+            .change_context(StorageError::KVError)
+    }
+}
+
+/// Compares the index of the merchant with the payout hashes present, see
+/// [`KVRouterStore::audit_kv_index_consistency`].
+pub async fn audit_index<I>(
+    index: &I,
+    merchant_id: &str,
+    repair: bool,
+) -> error_stack::Result<IndexAuditReport, StorageError>
+where
+    I: PayoutKvIndex + Sync + ?Sized,
+{
+    let indexed = index
+        .indexed_payout_ids(merchant_id)
+        .await?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    let stored = index.stored_payout_ids(merchant_id).await?;
+
+    let mut report = IndexAuditReport::default();
+    for payout_id in &indexed {
+        if !index.has_payout_hash(merchant_id, payout_id).await? {
+            report.indexed_without_hash.push(payout_id.clone());
+        }
+    }
+    let not_indexed = stored
+        .into_iter()
+        .filter(|payout_id| !indexed.contains(payout_id))
+        .collect::<BTreeSet<_>>();
+    for payout_id in not_indexed {
+        if index.has_payout_hash(merchant_id, &payout_id).await? {
+            report.hash_not_indexed.push(payout_id);
+        }
+    }
+
+    if repair && !report.is_consistent() {
+        for payout_id in &report.indexed_without_hash {
+            index.remove_from_index(merchant_id, payout_id).await?;
+        }
+        for payout_id in &report.hash_not_indexed {
+            index.add_to_index(merchant_id, payout_id).await?;
+        }
+        report.repaired = true;
+    }
+    Ok(report)
+}
+
+/// Extends the life in KV of the payouts listed in the index of the merchant which are not in a
+/// terminal status, see [`KVRouterStore::refresh_active_payout_ttls`]. Returns the ids of the
+/// payouts refreshed.
+pub async fn refresh_active_ttls<I>(
+    index: &I,
+    merchant_id: &str,
+) -> error_stack::Result<Vec<String>, StorageError>
+where
+    I: PayoutKvIndex + Sync + ?Sized,
+{
+    let indexed = index
+        .indexed_payout_ids(merchant_id)
+        .await?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+
+    let mut refreshed = Vec::new();
+    for payout_id in indexed {
+        match index.kv_payout_status(merchant_id, &payout_id).await? {
+            Some(status) if is_non_terminal_status(status) => {
+                index.refresh_payout_ttl(merchant_id, &payout_id).await?;
+                refreshed.push(payout_id);
+            }
+            // Terminal payouts are left to expire, as are the entries whose hash is gone
+            Some(_) | None => {}
+        }
+    }
+    if !refreshed.is_empty() {
+        // The index outlives the payouts it lists
+        index.refresh_index_ttl(merchant_id).await?;
+    }
+    Ok(refreshed)
+}
+
+/// Sums the sizes of the values of the payouts listed in the index of the merchant, see
+/// [`KVRouterStore::estimate_merchant_kv_footprint`]. Entries of the index whose hash is gone are
+/// not counted.
+pub async fn kv_footprint<I>(
+    index: &I,
+    merchant_id: &str,
+) -> error_stack::Result<KvFootprint, StorageError>
+where
+    I: PayoutKvIndex + Sync + ?Sized,
+{
+    let indexed = index
+        .indexed_payout_ids(merchant_id)
+        .await?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+
+    let mut footprint = KvFootprint::default();
+    for payout_id in indexed {
+        if let Some(size) = index.payout_value_size(merchant_id, &payout_id).await? {
+            footprint.payout_count += 1;
+            footprint.total_bytes = footprint
+                .total_bytes
+                .saturating_add(u64::try_from(size).unwrap_or(u64::MAX));
+        }
+    }
+    Ok(footprint)
+}
+
+/// Adds the payout to the index of its merchant after its hash was written. A failure is logged
+/// only, the drift it leaves is reported by [`KVRouterStore::audit_kv_index_consistency`].
+pub async fn index_kv_payout<I>(index: &I, merchant_id: &str, payout_id: &str)
+where
+    I: PayoutKvIndex + Sync + ?Sized,
+{
+    if let Err(error) = index.add_to_index(merchant_id, payout_id).await {
+        logger::warn!(?error, %payout_id, "Failed to add a payout to the KV index");
+    }
+}
+
+/// Removes the payout from the index of its merchant after its hash was deleted. A failure is
+/// logged only, like in [`index_kv_payout`].
+pub async fn unindex_kv_payout<I>(index: &I, merchant_id: &str, payout_id: &str)
+where
+    I: PayoutKvIndex + Sync + ?Sized,
+{
+    if let Err(error) = index.remove_from_index(merchant_id, payout_id).await {
+        logger::warn!(?error, %payout_id, "Failed to remove a payout from the KV index");
+    }
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Reports the payouts listed in the KV index of the merchant whose hash is missing, and the
+    /// payouts whose hash is present but which are missing from the index. With `repair`, the
+    /// former are removed from the index and the latter added to it.
+    ///
+    /// Hashes are only looked for under the payouts listed in the index or persisted in
+    /// Postgres, so the hash of a payout not drained yet and missing from the index goes
+    /// unnoticed.
+    #[instrument(skip_all)]
+    pub async fn audit_kv_index_consistency(
+        &self,
+        merchant_id: &MerchantId,
+        repair: bool,
+    ) -> error_stack::Result<IndexAuditReport, StorageError> {
+        let report = audit_index(self, merchant_id, repair).await?;
+        record_index_drift(&report);
+        if !report.is_consistent() {
+            logger::warn!(
+                %merchant_id,
+                indexed_without_hash = report.indexed_without_hash.len(),
+                hash_not_indexed = report.hash_not_indexed.len(),
+                repaired = report.repaired,
+                "The KV index of payouts drifted from the payout hashes"
+            );
+        }
+        Ok(report)
+    }
+
+    /// Extends the life of the KV hashes of the payouts of the merchant which are still active,
+    /// so that a payout pending for longer than the KV TTL does not expire out of KV before it
+    /// completes. Payouts in a terminal status are left to expire.
+    ///
+    /// Only the payouts listed in the KV index of the merchant are refreshed. Returns their ids.
+    #[instrument(skip_all)]
+    pub async fn refresh_active_payout_ttls(
+        &self,
+        merchant_id: &MerchantId,
+    ) -> error_stack::Result<Vec<String>, StorageError> {
+        let refreshed = refresh_active_ttls(self, merchant_id).await?;
+        logger::info!(
+            %merchant_id,
+            refreshed = refreshed.len(),
+            "Refreshed the KV TTL of active payouts"
+        );
+        Ok(refreshed)
+    }
+
+    /// Estimates the Redis memory taken by the payouts of the merchant, from the size of their
+    /// values as stored, so after any compression applied on write. Keys, Redis overhead and
+    /// the hashes of payout attempts are not counted.
+    ///
+    /// Only the payouts listed in the KV index of the merchant are counted, see
+    /// [`Self::audit_kv_index_consistency`] for the payouts it may miss.
+    #[instrument(skip_all)]
+    pub async fn estimate_merchant_kv_footprint(
+        &self,
+        merchant_id: &MerchantId,
+    ) -> error_stack::Result<KvFootprint, StorageError> {
+        let footprint = kv_footprint(self, merchant_id).await?;
+        logger::info!(
+            %merchant_id,
+            payout_count = footprint.payout_count,
+            total_bytes = footprint.total_bytes,
+            "Estimated the KV footprint of payouts"
+        );
+        Ok(footprint)
+    }
+}
+
+fn record_index_drift(report: &IndexAuditReport) {
+    for (drift, count) in [
+        ("indexed_without_hash", report.indexed_without_hash.len()),
+        ("hash_not_indexed", report.hash_not_indexed.len()),
+    ] {
+        let keyvalue = router_env::opentelemetry::KeyValue::new("drift", drift);
+        metrics::PAYOUT_KV_INDEX_DRIFT.add(
+            &metrics::CONTEXT,
+            u64::try_from(count).unwrap_or(u64::MAX),
+            &[keyvalue],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Mutex,
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeIndex {
+        indexed: Mutex<HashSet<String>>,
+        stored: Vec<String>,
+        hashes: HashSet<String>,
+        sizes: Mutex<HashMap<String, usize>>,
+        statuses: HashMap<String, PayoutStatus>,
+        refreshed: Mutex<Vec<String>>,
+        index_refreshed: Mutex<bool>,
+    }
+
+    #[async_trait::async_trait]
+    impl PayoutKvIndex for FakeIndex {
+        async fn indexed_payout_ids(
+            &self,
+            _merchant_id: &str,
+        ) -> error_stack::Result<Vec<String>, StorageError> {
+            Ok(self.indexed.lock().unwrap().iter().cloned().collect())
+        }
+
+        async fn stored_payout_ids(
+            &self,
+            _merchant_id: &str,
+        ) -> error_stack::Result<Vec<String>, StorageError> {
+            Ok(self.stored.clone())
+        }
+
+        async fn has_payout_hash(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<bool, StorageError> {
+            Ok(self.hashes.contains(payout_id))
+        }
+
+        async fn add_to_index(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<(), StorageError> {
+            self.indexed.lock().unwrap().insert(payout_id.to_string());
+            Ok(())
+        }
+
+        async fn remove_from_index(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<(), StorageError> {
+            self.indexed.lock().unwrap().remove(payout_id);
+            Ok(())
+        }
+
+        async fn payout_value_size(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<Option<usize>, StorageError> {
+            Ok(self.sizes.lock().unwrap().get(payout_id).copied())
+        }
+
+        async fn kv_payout_status(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<Option<PayoutStatus>, StorageError> {
+            Ok(self.statuses.get(payout_id).copied())
+        }
+
+        async fn refresh_payout_ttl(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<(), StorageError> {
+            self.refreshed.lock().unwrap().push(payout_id.to_string());
+            Ok(())
+        }
+
+        async fn refresh_index_ttl(
+            &self,
+            _merchant_id: &str,
+        ) -> error_stack::Result<(), StorageError> {
+            *self.index_refreshed.lock().unwrap() = true;
+            Ok(())
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(ToString::to_string).collect()
+    }
+
+    /// `po_1` is consistent, `po_2` lost its hash and `po_3` was never indexed.
+    fn drifted_index() -> FakeIndex {
+        FakeIndex {
+            indexed: Mutex::new(ids(&["po_1", "po_2"]).into_iter().collect()),
+            stored: ids(&["po_1", "po_3", "po_4"]),
+            hashes: ids(&["po_1", "po_3"]).into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn drift_is_detected_in_both_directions() {
+        let index = drifted_index();
+        let report = audit_index(&index, "merchant_1", false).await.unwrap();
+        assert_eq!(
+            report,
+            IndexAuditReport {
+                indexed_without_hash: ids(&["po_2"]),
+                hash_not_indexed: ids(&["po_3"]),
+                repaired: false,
+            }
+        );
+        // Without `repair` the index is left as is
+        assert_eq!(
+            *index.indexed.lock().unwrap(),
+            ids(&["po_1", "po_2"]).into_iter().collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn repaired_index_matches_the_hashes() {
+        let index = drifted_index();
+        let report = audit_index(&index, "merchant_1", true).await.unwrap();
+        assert!(report.repaired);
+        assert_eq!(
+            *index.indexed.lock().unwrap(),
+            ids(&["po_1", "po_3"]).into_iter().collect()
+        );
+
+        let report = audit_index(&index, "merchant_1", true).await.unwrap();
+        assert!(report.is_consistent());
+        assert!(!report.repaired);
+    }
+
+    #[tokio::test]
+    async fn only_active_payouts_have_their_ttl_refreshed() {
+        // `po_4` is listed in the index but its hash already expired
+        let index = FakeIndex {
+            indexed: Mutex::new(ids(&["po_1", "po_2", "po_3", "po_4"]).into_iter().collect()),
+            statuses: HashMap::from([
+                ("po_1".to_string(), PayoutStatus::Pending),
+                ("po_2".to_string(), PayoutStatus::Success),
+                ("po_3".to_string(), PayoutStatus::RequiresFulfillment),
+            ]),
+            ..Default::default()
+        };
+
+        let refreshed = refresh_active_ttls(&index, "merchant_1").await.unwrap();
+        assert_eq!(refreshed, ids(&["po_1", "po_3"]));
+        assert_eq!(*index.refreshed.lock().unwrap(), ids(&["po_1", "po_3"]));
+        assert!(*index.index_refreshed.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn terminal_payouts_are_left_to_expire() {
+        let index = FakeIndex {
+            indexed: Mutex::new(ids(&["po_1", "po_2"]).into_iter().collect()),
+            statuses: HashMap::from([
+                ("po_1".to_string(), PayoutStatus::Failed),
+                ("po_2".to_string(), PayoutStatus::Cancelled),
+            ]),
+            ..Default::default()
+        };
+
+        assert!(refresh_active_ttls(&index, "merchant_1")
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(index.refreshed.lock().unwrap().is_empty());
+        // Nothing is left to keep the index alive for
+        assert!(!*index.index_refreshed.lock().unwrap());
+    }
+
+    impl FakeIndex {
+        fn write(&self, payout_id: &str, size: usize) {
+            self.indexed.lock().unwrap().insert(payout_id.to_string());
+            self.sizes
+                .lock()
+                .unwrap()
+                .insert(payout_id.to_string(), size);
+        }
+
+        fn delete(&self, payout_id: &str) {
+            self.indexed.lock().unwrap().remove(payout_id);
+            self.sizes.lock().unwrap().remove(payout_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn footprint_follows_the_payouts_written_and_deleted() {
+        let index = FakeIndex::default();
+        assert_eq!(
+            kv_footprint(&index, "merchant_1").await.unwrap(),
+            KvFootprint::default()
+        );
+
+        index.write("po_1", 300);
+        index.write("po_2", 500);
+        assert_eq!(
+            kv_footprint(&index, "merchant_1").await.unwrap(),
+            KvFootprint {
+                payout_count: 2,
+                total_bytes: 800,
+            }
+        );
+
+        // Rewriting a payout counts its new size only
+        index.write("po_1", 200);
+        index.delete("po_2");
+        assert_eq!(
+            kv_footprint(&index, "merchant_1").await.unwrap(),
+            KvFootprint {
+                payout_count: 1,
+                total_bytes: 200,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn footprint_skips_index_entries_without_hash() {
+        let index = FakeIndex::default();
+        index.write("po_1", 300);
+        // Still listed in the index, but its hash already expired
+        index.indexed.lock().unwrap().insert("po_2".to_string());
+
+        assert_eq!(
+            kv_footprint(&index, "merchant_1").await.unwrap(),
+            KvFootprint {
+                payout_count: 1,
+                total_bytes: 300,
+            }
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt::Write;
+
+use common_utils::errors::CustomResult;
+use redis_interface::errors::RedisError;
+
+use crate::{DatabaseStore, KVRouterStore};
+
+/// Keyed hash of the payout ids appearing in the Redis keys of payouts, so that raw payout ids
+/// are not exposed to whoever can list the keys of the Redis instance.
+///
+/// The hash is an HMAC-SHA256 of the payout id, every server must be configured with the same
+/// secret to find the payouts written by the others.
+#[derive(Clone)]
+pub struct PayoutKeyHasher {
+    key: ring::hmac::Key,
+}
+
+impl std::fmt::Debug for PayoutKeyHasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PayoutKeyHasher").finish_non_exhaustive()
+    }
+}
+
+impl PayoutKeyHasher {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            key: ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret),
+        }
+    }
+
+    /// Hex encoded hash of `payout_id`.
+    pub fn hash(&self, payout_id: &str) -> String {
+        hex_encode(ring::hmac::sign(&self.key, payout_id.as_bytes()).as_ref())
+    }
+}
+
+pub(super) fn hex_encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+            // Writing to a `String` never fails
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// Key and field of the Redis hash holding a payout.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PayoutKvKey {
+    pub key: String,
+    pub field: String,
+}
+
+impl PayoutKvKey {
+    /// The key holding the payout under its raw payout id, as written when no
+    /// [`PayoutKeyHasher`] is configured.
+    pub fn raw(merchant_id: &str, payout_id: &str) -> Self {
+        Self {
+            key: format!("mid_{merchant_id}_po_{payout_id}"),
+            field: format!("po_{payout_id}"),
+        }
+    }
+
+    /// The field holding the status of the payout alone next to the full payout, so that
+    /// status polls do not deserialize the whole payout.
+    ///
+    /// The status of a test payout is held under a field of its own, so that a status poll in
+    /// live mode does not find it.
+    pub fn status_field(&self, test_mode: bool) -> String {
+        if test_mode {
+            format!("{}_test_status", self.field)
+        } else {
+            format!("{}_status", self.field)
+        }
+    }
+
+    /// The field holding the sequence number of the payout alone next to the full payout, which
+    /// updates compare and set atomically.
+    pub fn sequence_field(&self) -> String {
+        format!("{}_seq", self.field)
+    }
+
+    /// The field holding the checksum of the full payout, see
+    /// [`super::integrity::PayoutChecksum`].
+    pub fn checksum_field(&self) -> String {
+        format!("{}_checksum", self.field)
+    }
+
+    /// The key a payout is written to, holding the hash of its payout id if `hasher` is set.
+    pub fn new(hasher: Option<&PayoutKeyHasher>, merchant_id: &str, payout_id: &str) -> Self {
+        match hasher {
+            Some(hasher) => Self::raw(merchant_id, &hasher.hash(payout_id)),
+            None => Self::raw(merchant_id, payout_id),
+        }
+    }
+
+    /// The keys a payout is read from, in order.
+    ///
+    /// While payout ids are hashed, payouts written before hashing was enabled are still held
+    /// under their raw key until they expire, so that key is read when the hashed one is missing.
+    pub fn read_candidates(
+        hasher: Option<&PayoutKeyHasher>,
+        merchant_id: &str,
+        payout_id: &str,
+    ) -> Vec<Self> {
+        match hasher {
+            Some(_) => vec![
+                Self::new(hasher, merchant_id, payout_id),
+                Self::raw(merchant_id, payout_id),
+            ],
+            None => vec![Self::raw(merchant_id, payout_id)],
+        }
+    }
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// The key the payout is written to, see [`PayoutKvKey::new`].
+    pub fn payout_kv_key(&self, merchant_id: &str, payout_id: &str) -> PayoutKvKey {
+        PayoutKvKey::new(self.payout_key_hasher.as_ref(), merchant_id, payout_id)
+    }
+
+    /// The keys the payout is read from, see [`PayoutKvKey::read_candidates`].
+    pub fn payout_kv_read_keys(&self, merchant_id: &str, payout_id: &str) -> Vec<PayoutKvKey> {
+        PayoutKvKey::read_candidates(self.payout_key_hasher.as_ref(), merchant_id, payout_id)
+    }
+}
+
+/// Reads a payout with `read` from the first of `keys` holding it, see
+/// [`PayoutKvKey::read_candidates`]. Fails with `NotFound` if none of them does.
+pub async fn read_from_first_key<'a, T, F, Fut>(
+    keys: &'a [PayoutKvKey],
+    mut read: F,
+) -> CustomResult<T, RedisError>
+where
+    F: FnMut(&'a PayoutKvKey) -> Fut,
+    Fut: std::future::Future<Output = CustomResult<T, RedisError>>,
+{
+    let mut not_found = None;
+    for key in keys {
+        match read(key).await {
+            Err(error) if matches!(error.current_context(), RedisError::NotFound) => {
+                not_found = Some(error);
+            }
+            result => return result,
+        }
+    }
+    Err(not_found.unwrap_or_else(|| error_stack::report!(RedisError::NotFound)))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::collections::HashMap;
+
+    use error_stack::IntoReport;
+
+    use super::*;
+
+    async fn read_from(
+        redis: &HashMap<PayoutKvKey, &'static str>,
+        keys: &[PayoutKvKey],
+    ) -> CustomResult<&'static str, RedisError> {
+        read_from_first_key(keys, |key| {
+            let value = redis
+                .get(key)
+                .copied()
+                .ok_or(RedisError::NotFound)
+                .into_report();
+            async move { value }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn hashed_key_round_trips_without_the_raw_payout_id() {
+        let hasher = PayoutKeyHasher::new(b"secret");
+        let written = PayoutKvKey::new(Some(&hasher), "merchant_1", "po_1");
+        let hash = hasher.hash("po_1");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(written.key, format!("mid_merchant_1_po_{hash}"));
+        assert_eq!(written.field, format!("po_{hash}"));
+        assert_eq!(
+            written,
+            PayoutKvKey::new(Some(&hasher), "merchant_1", "po_1")
+        );
+        assert_ne!(
+            written,
+            PayoutKvKey::new(Some(&PayoutKeyHasher::new(b"other")), "merchant_1", "po_1")
+        );
+
+        let redis = HashMap::from([(written, "payout")]);
+        let keys = PayoutKvKey::read_candidates(Some(&hasher), "merchant_1", "po_1");
+        assert_eq!(read_from(&redis, &keys).await.unwrap(), "payout");
+    }
+
+    #[tokio::test]
+    async fn raw_key_is_read_while_migrating_to_hashed_keys() {
+        let hasher = PayoutKeyHasher::new(b"secret");
+        let redis = HashMap::from([(PayoutKvKey::raw("merchant_1", "po_1"), "raw payout")]);
+
+        let keys = PayoutKvKey::read_candidates(Some(&hasher), "merchant_1", "po_1");
+        assert_eq!(read_from(&redis, &keys).await.unwrap(), "raw payout");
+
+        // Once rewritten, the payout is read from its hashed key
+        let mut redis = redis;
+        redis.insert(
+            PayoutKvKey::new(Some(&hasher), "merchant_1", "po_1"),
+            "hashed payout",
+        );
+        assert_eq!(read_from(&redis, &keys).await.unwrap(), "hashed payout");
+
+        let keys = PayoutKvKey::read_candidates(Some(&hasher), "merchant_1", "po_2");
+        let error = read_from(&redis, &keys).await.unwrap_err();
+        assert!(matches!(error.current_context(), RedisError::NotFound));
+    }
+
+    #[test]
+    fn raw_keys_are_used_without_hasher() {
+        assert_eq!(
+            PayoutKvKey::read_candidates(None, "merchant_1", "po_1"),
+            vec![PayoutKvKey {
+                key: "mid_merchant_1_po_po_1".to_string(),
+                field: "po_po_1".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use data_models::errors::StorageError;
+use error_stack::ResultExt;
+use router_env::logger;
+
+use crate::redis::kv_store::RedisConnInterface;
+
+/// Key of the counter holding the current version of a merchant's payout list summaries.
+///
+/// Every update to a payout of the merchant bumps this version, so summary caches which embed
+/// the version in their key (see [`payout_list_summary_cache_key`]) stop being served.
+pub fn payout_list_version_key(merchant_id: &str) -> String {
+    format!("payout_list_version_{merchant_id}")
+}
+
+/// Cache key of a payout list summary computed at `version` of the merchant's payout list.
+///
+/// `summary_id` identifies the summary within the merchant, for instance a hash of the filters
+/// applied to the list.
+pub fn payout_list_summary_cache_key(merchant_id: &str, version: i64, summary_id: &str) -> String {
+    format!("payout_list_summary_{merchant_id}_v{version}_{summary_id}")
+}
+
+/// Returns the current version of the merchant's payout list, `0` if it was never bumped.
+pub async fn get_payout_list_version(
+    store: &(dyn RedisConnInterface + Send + Sync),
+    merchant_id: &str,
+) -> error_stack::Result<i64, StorageError> {
+    store
+        .get_redis_conn()
+        .change_context(StorageError::KVError)?
+        .get_key::<Option<i64>>(&payout_list_version_key(merchant_id))
+        .await
+        .change_context(StorageError::KVError)
+        .map(|version| version.unwrap_or_default())
+}
+
+/// Bumps the version of the merchant's payout list, invalidating every cached summary.
+///
+/// Failures are only logged, since the payout update which triggered the bump has already
+/// been persisted by the time this is called.
+pub async fn bump_payout_list_version(
+    store: &(dyn RedisConnInterface + Send + Sync),
+    merchant_id: &str,
+) {
+    let result = async {
+        store
+            .get_redis_conn()?
+            .increment_key(&payout_list_version_key(merchant_id))
+            .await
+    }
+    .await;
+
+    if let Err(error) = result {
+        logger::error!(
+            ?error,
+            "Failed to bump payout list version for {merchant_id}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_key_changes_with_list_version() {
+        let stale = payout_list_summary_cache_key("merchant_1", 1, "all");
+        let current = payout_list_summary_cache_key("merchant_1", 2, "all");
+        assert_ne!(stale, current);
+    }
+
+    #[test]
+    fn summary_key_is_scoped_to_merchant() {
+        assert_ne!(
+            payout_list_summary_cache_key("merchant_1", 1, "all"),
+            payout_list_summary_cache_key("merchant_2", 1, "all")
+        );
+    }
+}
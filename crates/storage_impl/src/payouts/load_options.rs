@@ -0,0 +1,142 @@
+use std::fmt;
+
+use common_utils::errors::CustomResult;
+use diesel_models::payouts::Payouts as DieselPayouts;
+use error_stack::{IntoReport, ResultExt};
+use redis_interface::errors::RedisError;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, Visitor};
+
+use super::{integrity::get_verified_payout_values, kv_key::PayoutKvKey};
+use crate::{DatabaseStore, KVRouterStore};
+
+/// Deserializes a payout serialized to JSON, skipping over the values of the fields of
+/// `skipped_fields` rather than deserializing them. The fields skipped are `None`.
+pub fn deserialize_payout_skipping(
+    value: &[u8],
+    skipped_fields: &[&str],
+) -> serde_json::Result<DieselPayouts> {
+    let mut deserializer = serde_json::Deserializer::from_slice(value);
+    let fields = SkippingFields(skipped_fields).deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    serde_json::from_value(serde_json::Value::Object(fields))
+}
+
+/// Collects the fields of a JSON object, except the ones it holds.
+struct SkippingFields<'a>(&'a [&'a str]);
+
+impl<'de> DeserializeSeed<'de> for SkippingFields<'_> {
+    type Value = serde_json::Map<String, serde_json::Value>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SkippingFields<'_> {
+    type Value = serde_json::Map<String, serde_json::Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a payout")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut fields = serde_json::Map::new();
+        while let Some(field) = map.next_key::<String>()? {
+            if self.0.contains(&field.as_str()) {
+                map.next_value::<IgnoredAny>()?;
+            } else {
+                let value = map.next_value()?;
+                fields.insert(field, value);
+            }
+        }
+        Ok(fields)
+    }
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Reads the payout held under `key` like [`Self::read_kv_payout`], skipping over the fields
+    /// of `skipped_fields`, see [`deserialize_payout_skipping`].
+    pub async fn read_kv_payout_skipping(
+        &self,
+        key: &PayoutKvKey,
+        skipped_fields: &[&str],
+    ) -> CustomResult<DieselPayouts, RedisError> {
+        let value = get_verified_payout_values(
+            &self.get_redis_read_conn([key.key.as_str()])?,
+            self.payout_checksum.as_ref(),
+            std::slice::from_ref(key),
+        )
+        .await?
+        .into_iter()
+        .next()
+        .flatten()
+        .filter(|value| !value.is_empty())
+        .ok_or(RedisError::NotFound)
+        .into_report()?;
+        deserialize_payout_skipping(&value, skipped_fields)
+            .into_report()
+            .change_context(RedisError::JsonDeserializationFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn heavy_payout() -> Vec<u8> {
+        let now = common_utils::date_time::now();
+        serde_json::to_vec(&diesel_models::payouts::PayoutsNew {
+            payout_id: "po_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            connector_response: Some(masking::Secret::new(serde_json::json!({"id": "tr_1"}))),
+            routing_decision: Some(masking::Secret::new(serde_json::json!({"score": 0.5}))),
+            customer_snapshot: Some(masking::Secret::new(
+                serde_json::json!({"customer_id": "cus_1"}),
+            )),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn heavy_fields_not_requested_are_skipped() {
+        let payout =
+            deserialize_payout_skipping(&heavy_payout(), &DieselPayouts::SKIPPABLE_COLUMNS)
+                .unwrap();
+
+        assert_eq!(payout.payout_id, "po_1");
+        assert!(payout.connector_response.is_none());
+        assert!(payout.routing_decision.is_none());
+        assert!(payout.customer_snapshot.is_none());
+    }
+
+    #[test]
+    fn heavy_fields_requested_are_deserialized() {
+        let payout = deserialize_payout_skipping(&heavy_payout(), &["routing_decision"]).unwrap();
+        assert!(payout.connector_response.is_some());
+        assert!(payout.routing_decision.is_none());
+        assert!(payout.customer_snapshot.is_some());
+
+        let payout = deserialize_payout_skipping(&heavy_payout(), &[]).unwrap();
+        assert_eq!(
+            payout,
+            serde_json::from_slice::<DieselPayouts>(&heavy_payout()).unwrap()
+        );
+    }
+
+    #[test]
+    fn trailing_characters_are_rejected() {
+        let mut value = heavy_payout();
+        value.extend_from_slice(b"}");
+        assert!(deserialize_payout_skipping(&value, &["routing_decision"]).is_err());
+    }
+}
@@ -1,8 +1,11 @@
 use common_utils::{ext_traits::Encode, fallback_reverse_lookup_not_found};
 use data_models::{
     errors,
-    payouts::payout_attempt::{
-        PayoutAttempt, PayoutAttemptInterface, PayoutAttemptNew, PayoutAttemptUpdate,
+    payouts::{
+        decline_code::normalize_payout_decline_code,
+        payout_attempt::{
+            PayoutAttempt, PayoutAttemptInterface, PayoutAttemptNew, PayoutAttemptUpdate,
+        },
     },
 };
 use diesel_models::{
@@ -15,14 +18,14 @@ use diesel_models::{
     ReverseLookupNew,
 };
 use error_stack::{IntoReport, ResultExt};
-use redis_interface::HsetnxReply;
-use router_env::{instrument, tracing};
+use redis_interface::{errors::RedisError, HsetnxReply};
+use router_env::{instrument, logger, tracing};
 
 use crate::{
     diesel_error_to_data_error,
     errors::RedisErrorExt,
     lookup::ReverseLookupInterface,
-    redis::kv_store::{kv_wrapper, KvOperation},
+    redis::kv_store::{kv_wrapper, KvOperation, RedisConnInterface},
     utils::{self, pg_connection_read, pg_connection_write},
     DataModelExt, DatabaseStore, KVRouterStore,
 };
@@ -35,8 +38,12 @@ impl<T: DatabaseStore> PayoutAttemptInterface for KVRouterStore<T> {
         new_payout_attempt: PayoutAttemptNew,
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PayoutAttempt, errors::StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(&new_payout_attempt.merchant_id, storage_scheme)
+            .await?
+            .scheme();
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .insert_payout_attempt(new_payout_attempt, storage_scheme)
                     .await
@@ -46,28 +53,9 @@ impl<T: DatabaseStore> PayoutAttemptInterface for KVRouterStore<T> {
                     "mid_{}_poa_{}",
                     new_payout_attempt.merchant_id, new_payout_attempt.payout_id
                 );
-                let now = common_utils::date_time::now();
-                let created_attempt = PayoutAttempt {
-                    payout_attempt_id: new_payout_attempt.payout_attempt_id.clone(),
-                    payout_id: new_payout_attempt.payout_id.clone(),
-                    customer_id: new_payout_attempt.customer_id.clone(),
-                    merchant_id: new_payout_attempt.merchant_id.clone(),
-                    address_id: new_payout_attempt.address_id.clone(),
-                    connector: new_payout_attempt.connector.clone(),
-                    connector_payout_id: new_payout_attempt.connector_payout_id.clone(),
-                    payout_token: new_payout_attempt.payout_token.clone(),
-                    status: new_payout_attempt.status,
-                    is_eligible: new_payout_attempt.is_eligible,
-                    error_message: new_payout_attempt.error_message.clone(),
-                    error_code: new_payout_attempt.error_code.clone(),
-                    business_country: new_payout_attempt.business_country,
-                    business_label: new_payout_attempt.business_label.clone(),
-                    created_at: new_payout_attempt.created_at.unwrap_or(now),
-                    last_modified_at: new_payout_attempt.last_modified_at.unwrap_or(now),
-                    profile_id: new_payout_attempt.profile_id.clone(),
-                    merchant_connector_id: new_payout_attempt.merchant_connector_id.clone(),
-                    routing_info: new_payout_attempt.routing_info.clone(),
-                };
+                let created_attempt = new_payout_attempt
+                    .clone()
+                    .into_attempt(common_utils::date_time::now());
 
                 let redis_entry = kv::TypedSql {
                     op: kv::DBOperation::Insert {
@@ -79,18 +67,11 @@ impl<T: DatabaseStore> PayoutAttemptInterface for KVRouterStore<T> {
 
                 // Reverse lookup for payout_attempt_id
                 let field = format!("poa_{}", created_attempt.payout_attempt_id);
-                let reverse_lookup = ReverseLookupNew {
-                    lookup_id: format!(
-                        "poa_{}_{}",
-                        &created_attempt.merchant_id, &created_attempt.payout_attempt_id,
-                    ),
-                    pk_id: key.clone(),
-                    sk_id: field.clone(),
-                    source: "payout_attempt".to_string(),
-                    updated_by: storage_scheme.to_string(),
-                };
-                self.insert_reverse_lookup(reverse_lookup, storage_scheme)
-                    .await?;
+                self.insert_reverse_lookup(
+                    payout_attempt_reverse_lookup(&created_attempt, &key, &field, storage_scheme),
+                    storage_scheme,
+                )
+                .await?;
 
                 match kv_wrapper::<DieselPayoutAttempt, _, _>(
                     self,
@@ -124,8 +105,12 @@ impl<T: DatabaseStore> PayoutAttemptInterface for KVRouterStore<T> {
         payout_update: PayoutAttemptUpdate,
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PayoutAttempt, errors::StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(&this.merchant_id, storage_scheme)
+            .await?
+            .scheme();
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .update_payout_attempt(this, payout_update, storage_scheme)
                     .await
@@ -179,8 +164,12 @@ impl<T: DatabaseStore> PayoutAttemptInterface for KVRouterStore<T> {
         payout_attempt_id: &str,
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<PayoutAttempt, errors::StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 self.router_store
                     .find_payout_attempt_by_merchant_id_payout_attempt_id(
                         merchant_id,
@@ -227,6 +216,150 @@ impl<T: DatabaseStore> PayoutAttemptInterface for KVRouterStore<T> {
             }
         }
     }
+
+    #[instrument(skip_all)]
+    async fn list_payout_attempts(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutAttempt>, errors::StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                self.router_store
+                    .list_payout_attempts(merchant_id, payout_id, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::RedisKv => {
+                // Attempts made before the merchant moved to KV, or drained since, are only
+                // found in the database
+                let key = format!("mid_{merchant_id}_poa_{payout_id}");
+                let held = match kv_wrapper::<DieselPayoutAttempt, _, _>(
+                    self,
+                    KvOperation::<DieselPayoutAttempt>::Scan("poa_*"),
+                    &key,
+                )
+                .await
+                {
+                    Ok(result) => result
+                        .try_into_scan()
+                        .change_context(errors::StorageError::KVError)?
+                        .into_iter()
+                        .map(PayoutAttempt::from_storage_model)
+                        .collect(),
+                    Err(error) if matches!(error.current_context(), RedisError::NotFound) => {
+                        Vec::new()
+                    }
+                    Err(error) => return Err(error.to_redis_failed_response(&key)),
+                };
+                let stored = self
+                    .router_store
+                    .list_payout_attempts(merchant_id, payout_id, storage_scheme)
+                    .await?;
+                Ok(PayoutAttempt::merge_into_timeline(held, stored))
+            }
+        }
+    }
+}
+
+/// The reverse lookup finding the attempt by its id, held in the KV hash `key` under `field`.
+fn payout_attempt_reverse_lookup(
+    attempt: &PayoutAttempt,
+    key: &str,
+    field: &str,
+    storage_scheme: MerchantStorageScheme,
+) -> ReverseLookupNew {
+    ReverseLookupNew {
+        lookup_id: format!(
+            "poa_{}_{}",
+            &attempt.merchant_id, &attempt.payout_attempt_id,
+        ),
+        pk_id: key.to_string(),
+        sk_id: field.to_string(),
+        source: "payout_attempt".to_string(),
+        updated_by: storage_scheme.to_string(),
+    }
+}
+
+/// An attempt written to the KV hash of the attempts of its payout, whose insert is not pushed
+/// to the drainer yet, see [`reserve_kv_payout_attempt`].
+pub(crate) struct ReservedPayoutAttempt {
+    pub(crate) attempt: PayoutAttempt,
+    /// The insert of the attempt, to push to the drainer along the writes it was reserved for.
+    pub(crate) redis_entry: kv::TypedSql,
+    key: String,
+    field: String,
+}
+
+impl ReservedPayoutAttempt {
+    /// Removes the attempt from the KV hash, once the writes it was reserved for failed.
+    pub(crate) async fn release<T: DatabaseStore>(self, store: &KVRouterStore<T>) {
+        let released = async {
+            store
+                .get_redis_conn()?
+                .delete_hash_field(&self.key, &self.field)
+                .await
+        };
+        if let Err(error) = released.await {
+            logger::warn!(
+                ?error,
+                payout_attempt_id = %self.attempt.payout_attempt_id,
+                "Failed to release a reserved payout attempt"
+            );
+        }
+    }
+}
+
+/// Writes `new` to the KV hash of the attempts of its payout like
+/// [`PayoutAttemptInterface::insert_payout_attempt`], without pushing its insert to the drainer,
+/// so that the caller can push it along its own writes, or release the attempt if those fail.
+pub(crate) async fn reserve_kv_payout_attempt<T: DatabaseStore>(
+    store: &KVRouterStore<T>,
+    new: PayoutAttemptNew,
+    storage_scheme: MerchantStorageScheme,
+) -> error_stack::Result<ReservedPayoutAttempt, errors::StorageError> {
+    let key = format!("mid_{}_poa_{}", new.merchant_id, new.payout_id);
+    let attempt = new.clone().into_attempt(common_utils::date_time::now());
+    let field = format!("poa_{}", attempt.payout_attempt_id);
+    store
+        .insert_reverse_lookup(
+            payout_attempt_reverse_lookup(&attempt, &key, &field, storage_scheme),
+            storage_scheme,
+        )
+        .await?;
+
+    let reply = store
+        .get_redis_conn()
+        .change_context(errors::StorageError::KVError)?
+        .serialize_and_set_hash_field_if_not_exist(
+            &key,
+            &field,
+            attempt.clone().to_storage_model(),
+            Some(store.ttl_for_kv),
+        )
+        .await
+        .map_err(|err| err.to_redis_failed_response(&key))?;
+    match reply {
+        HsetnxReply::KeyNotSet => Err(errors::StorageError::DuplicateValue {
+            entity: "payout attempt",
+            key: Some(key),
+        })
+        .into_report(),
+        HsetnxReply::KeySet => Ok(ReservedPayoutAttempt {
+            attempt,
+            redis_entry: kv::TypedSql {
+                op: kv::DBOperation::Insert {
+                    insertable: kv::Insertable::PayoutAttempt(new.to_storage_model()),
+                },
+            },
+            key,
+            field,
+        }),
+    }
 }
 
 #[async_trait::async_trait]
@@ -287,6 +420,30 @@ impl<T: DatabaseStore> PayoutAttemptInterface for crate::RouterStore<T> {
             er.change_context(new_err)
         })
     }
+
+    #[instrument(skip_all)]
+    async fn list_payout_attempts(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutAttempt>, errors::StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayoutAttempt::filter_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+            .await
+            .map(|attempts| {
+                let mut attempts = attempts
+                    .into_iter()
+                    .map(PayoutAttempt::from_storage_model)
+                    .collect::<Vec<_>>();
+                PayoutAttempt::sort_for_timeline(&mut attempts);
+                attempts
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
 }
 
 impl DataModelExt for PayoutAttempt {
@@ -313,6 +470,7 @@ impl DataModelExt for PayoutAttempt {
             profile_id: self.profile_id,
             merchant_connector_id: self.merchant_connector_id,
             routing_info: self.routing_info,
+            unified_code: self.unified_code,
         }
     }
 
@@ -337,6 +495,7 @@ impl DataModelExt for PayoutAttempt {
             profile_id: storage_model.profile_id,
             merchant_connector_id: storage_model.merchant_connector_id,
             routing_info: storage_model.routing_info,
+            unified_code: storage_model.unified_code,
         }
     }
 }
@@ -364,6 +523,7 @@ impl DataModelExt for PayoutAttemptNew {
             profile_id: self.profile_id,
             merchant_connector_id: self.merchant_connector_id,
             routing_info: self.routing_info,
+            unified_code: self.unified_code,
         }
     }
 
@@ -388,6 +548,7 @@ impl DataModelExt for PayoutAttemptNew {
             profile_id: storage_model.profile_id,
             merchant_connector_id: storage_model.merchant_connector_id,
             routing_info: storage_model.routing_info,
+            unified_code: storage_model.unified_code,
         }
     }
 }
@@ -408,6 +569,23 @@ impl DataModelExt for PayoutAttemptUpdate {
                 error_code,
                 is_eligible,
             },
+            Self::ErrorUpdate {
+                connector,
+                connector_payout_id,
+                status,
+                error_message,
+                error_code,
+                is_eligible,
+            } => DieselPayoutAttemptUpdate::ErrorUpdate {
+                connector_payout_id,
+                status,
+                error_message,
+                unified_code: Some(
+                    normalize_payout_decline_code(&connector, &error_code).to_string(),
+                ),
+                error_code: Some(error_code),
+                is_eligible,
+            },
             Self::PayoutTokenUpdate { payout_token } => {
                 DieselPayoutAttemptUpdate::PayoutTokenUpdate { payout_token }
             }
@@ -1,25 +1,66 @@
-use common_utils::ext_traits::Encode;
+use std::collections::HashMap;
+
+use async_bb8_diesel::AsyncConnection;
+use common_utils::{ext_traits::Encode, pii};
 use data_models::{
     errors::StorageError,
-    payouts::payouts::{Payouts, PayoutsInterface, PayoutsNew, PayoutsUpdate},
+    payouts::{
+        amount_adjustment::PayoutAmountAdjustment,
+        ids::{CustomerId, MerchantId, PayoutId},
+        payout_attempt::{PayoutAttempt, PayoutAttemptInterface, PayoutAttemptNew},
+        payouts::{
+            check_status_transitions, set_json_path, sort_distinct_currencies,
+            stale_payout_statuses, sum_status_counts, total_payout_amount, PayoutLoadOptions,
+            PayoutPageCursor, PayoutProcessingTimeFilters, Payouts, PayoutsInterface, PayoutsNew,
+            PayoutsUpdate, PersistenceInfo, TestModeContext, PAYOUT_RETRY_CLAIM_LEASE,
+        },
+    },
 };
 use diesel_models::{
-    enums::MerchantStorageScheme,
+    business_profile::BusinessProfile,
+    configs::Config,
+    enums::{Currency, MerchantStorageScheme, PayoutCreationSource, PayoutStatus},
     kv,
+    payout_amount_adjustment::PayoutAmountAdjustment as DieselPayoutAmountAdjustment,
+    payout_attempt::PayoutAttempt as DieselPayoutAttempt,
+    payout_webhook_event::{PayoutWebhookEvent, PayoutWebhookEventNew},
     payouts::{
         Payouts as DieselPayouts, PayoutsNew as DieselPayoutsNew,
         PayoutsUpdate as DieselPayoutsUpdate,
     },
 };
 use error_stack::{IntoReport, ResultExt};
-use redis_interface::HsetnxReply;
-use router_env::{instrument, tracing};
+use masking::{ExposeInterface, Secret};
+use redis_interface::{errors::RedisError, ConditionalHsetReply, DelReply, HsetnxReply};
+use router_env::{instrument, logger, tracing};
+use time::PrimitiveDateTime;
 
+use super::{
+    amount_adjustment::{adjustment_from_stored, push_amount_adjustment},
+    audit::{record_payout_audit_event, PayoutAuditOperation},
+    balance::{ensure_sufficient_balance, get_payout_balance, precheck_payout_balance},
+    daily_count::{get_payout_daily_count, record_payout_created},
+    display_seq::{next_display_seq_in_database, next_kv_display_seq},
+    dual_write::{
+        backfill_profile_ids_in_kv, mark_deleted_in_kv, repair_timestamps_in_kv, shadow_read,
+        write_and_mirror, write_and_report_mirror, PayoutMirror,
+    },
+    id_format::validate_payout_id,
+    integrity::PayoutChecksum,
+    kv_index::{index_kv_payout, unindex_kv_payout},
+    kv_key::{read_from_first_key, PayoutKvKey},
+    list_version::{bump_payout_list_version, get_payout_list_version},
+    payout_attempt::reserve_kv_payout_attempt,
+};
 use crate::{
     diesel_error_to_data_error,
     errors::RedisErrorExt,
-    redis::kv_store::{kv_wrapper, KvOperation},
-    utils::{self, pg_connection_read, pg_connection_write},
+    redis::{
+        cache::{ACCOUNTS_CACHE, CONFIG_CACHE},
+        kv_store::{kv_wrapper, FieldCondition, KvOperation, PartitionKey},
+        scheme_override::ensure_scheme_supported,
+    },
+    utils::{self, pg_connection_read, pg_connection_write, same_field_names},
     DataModelExt, DatabaseStore, KVRouterStore,
 };
 
@@ -31,50 +72,49 @@ impl<T: DatabaseStore> PayoutsInterface for KVRouterStore<T> {
         new: PayoutsNew,
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<Payouts, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let new = self.router_store.payout_test_mode.apply_to(new);
+        let storage_scheme = self
+            .resolve_storage_scheme(&new.merchant_id, storage_scheme)
+            .await?
+            .scheme();
         match storage_scheme {
             MerchantStorageScheme::PostgresOnly => {
-                self.router_store.insert_payout(new, storage_scheme).await
+                let merchant_id = new.merchant_id.clone();
+                let payout = self.router_store.insert_payout(new, storage_scheme).await?;
+                self.reset_kv_display_seq_counter(&merchant_id).await;
+                Ok(payout)
+            }
+            MerchantStorageScheme::DualWrite => {
+                let merchant_id = new.merchant_id.clone();
+                let payout =
+                    write_and_mirror(self, self.router_store.insert_payout(new, storage_scheme))
+                        .await?;
+                self.reset_kv_display_seq_counter(&merchant_id).await;
+                Ok(payout)
             }
             MerchantStorageScheme::RedisKv => {
-                let key = format!("mid_{}_po_{}", new.merchant_id, new.payout_id);
-                let field = format!("po_{}", new.payout_id);
-                let now = common_utils::date_time::now();
-                let created_payout = Payouts {
-                    payout_id: new.payout_id.clone(),
-                    merchant_id: new.merchant_id.clone(),
-                    customer_id: new.customer_id.clone(),
-                    address_id: new.address_id.clone(),
-                    payout_type: new.payout_type,
-                    payout_method_id: new.payout_method_id.clone(),
-                    amount: new.amount,
-                    destination_currency: new.destination_currency,
-                    source_currency: new.source_currency,
-                    description: new.description.clone(),
-                    recurring: new.recurring,
-                    auto_fulfill: new.auto_fulfill,
-                    return_url: new.return_url.clone(),
-                    entity_type: new.entity_type,
-                    metadata: new.metadata.clone(),
-                    created_at: new.created_at.unwrap_or(now),
-                    last_modified_at: new.last_modified_at.unwrap_or(now),
-                    profile_id: new.profile_id.clone(),
-                    status: new.status,
-                    attempt_count: new.attempt_count,
+                let new = validate_new_payout(&self.router_store, self, new).await?;
+                let new = PayoutsNew {
+                    display_seq: next_kv_display_seq(self, &new.merchant_id).await?,
+                    ..new
                 };
 
+                let PayoutKvKey { key, field } =
+                    self.payout_kv_key(&new.merchant_id, &new.payout_id);
+                let now = common_utils::date_time::now();
+                let created_payout = new.clone().into_payout(now);
+
                 let redis_entry = kv::TypedSql {
                     op: kv::DBOperation::Insert {
                         insertable: kv::Insertable::Payouts(new.to_storage_model()),
                     },
                 };
 
+                let diesel_payout = created_payout.clone().to_storage_model();
                 match kv_wrapper::<DieselPayouts, _, _>(
                     self,
-                    KvOperation::<DieselPayouts>::HSetNx(
-                        &field,
-                        &created_payout.clone().to_storage_model(),
-                        redis_entry,
-                    ),
+                    KvOperation::<DieselPayouts>::HSetNx(&field, &diesel_payout, redis_entry),
                     &key,
                 )
                 .await
@@ -86,7 +126,39 @@ impl<T: DatabaseStore> PayoutsInterface for KVRouterStore<T> {
                         key: Some(key),
                     })
                     .into_report(),
-                    Ok(HsetnxReply::KeySet) => Ok(created_payout),
+                    Ok(HsetnxReply::KeySet) => {
+                        write_payout_sibling_fields(
+                            self,
+                            &PayoutKvKey { key, field },
+                            &diesel_payout,
+                        )
+                        .await;
+                        index_kv_payout(
+                            self,
+                            &created_payout.merchant_id,
+                            &created_payout.payout_id,
+                        )
+                        .await;
+                        self.record_kv_display_seq(
+                            &created_payout.merchant_id,
+                            created_payout.display_seq,
+                            &created_payout.payout_id,
+                        )
+                        .await;
+                        record_payout_created(
+                            self,
+                            &created_payout.merchant_id,
+                            common_utils::date_time::now(),
+                        )
+                        .await;
+                        record_payout_audit_event(
+                            self.router_store.payout_audit_sink.as_ref(),
+                            PayoutAuditOperation::Insert,
+                            &created_payout,
+                        )
+                        .await;
+                        Ok(created_payout)
+                    }
                     Err(error) => Err(error.change_context(StorageError::KVError)),
                 }
             }
@@ -100,98 +172,194 @@ impl<T: DatabaseStore> PayoutsInterface for KVRouterStore<T> {
         payout_update: PayoutsUpdate,
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<Payouts, StorageError> {
-        match storage_scheme {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(&this.merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let updated_payout = match storage_scheme {
             MerchantStorageScheme::PostgresOnly => {
                 self.router_store
                     .update_payout(this, payout_update, storage_scheme)
                     .await
             }
+            MerchantStorageScheme::DualWrite => {
+                write_and_mirror(
+                    self,
+                    self.router_store
+                        .update_payout(this, payout_update, storage_scheme),
+                )
+                .await
+            }
             MerchantStorageScheme::RedisKv => {
-                let key = format!("mid_{}_po_{}", this.merchant_id, this.payout_id);
-                let field = format!("po_{}", this.payout_id);
+                payout_update.validate_transition(this)?;
+                validate_payout_return_url_allowed(
+                    self,
+                    &this.merchant_id,
+                    payout_update.return_url(),
+                )
+                .await?;
 
+                let payout_key = self.payout_kv_key(&this.merchant_id, &this.payout_id);
+                let amount_adjustment = this.amount_adjustment(&payout_update);
                 let diesel_payout_update = payout_update.to_storage_model();
                 let origin_diesel_payout = this.clone().to_storage_model();
 
                 let diesel_payout = diesel_payout_update
                     .clone()
                     .apply_changeset(origin_diesel_payout.clone());
-                // Check for database presence as well Maybe use a read replica here ?
 
-                let redis_value = diesel_payout
-                    .encode_to_string_of_json()
-                    .change_context(StorageError::SerializationFailed)?;
+                let redis_fields =
+                    payout_hash_fields(&payout_key, &diesel_payout, self.payout_checksum.as_ref())?;
+                let sequence_field = payout_key.sequence_field();
+                let expected_sequence = this.sequence_number.to_string();
 
-                let redis_entry = kv::TypedSql {
-                    op: kv::DBOperation::Update {
-                        updatable: kv::Updateable::PayoutsUpdate(kv::PayoutsUpdateMems {
-                            orig: origin_diesel_payout,
-                            update_data: diesel_payout_update,
-                        }),
-                    },
+                // The sequence number of the hash is compared and the payout written by a single
+                // Lua script, so that two updates prepared against the same sequence number
+                // cannot both apply
+                let set_if_sequence = |apply_if_missing| {
+                    let redis_entry = kv::TypedSql {
+                        op: kv::DBOperation::Update {
+                            updatable: kv::Updateable::PayoutsUpdate(kv::PayoutsUpdateMems {
+                                orig: origin_diesel_payout.clone(),
+                                update_data: diesel_payout_update.clone(),
+                            }),
+                        },
+                    };
+                    let operation = KvOperation::<DieselPayouts>::HsetMultipleIf(
+                        redis_fields.clone(),
+                        FieldCondition {
+                            field: &sequence_field,
+                            expected: &expected_sequence,
+                            apply_if_missing,
+                        },
+                        redis_entry,
+                    );
+                    let key = payout_key.key.as_str();
+                    async move {
+                        kv_wrapper::<(), _, _>(self, operation, key)
+                            .await
+                            .map_err(|err| err.to_redis_failed_response(key))?
+                            .try_into_hsetif()
+                            .change_context(StorageError::KVError)
+                    }
                 };
 
-                kv_wrapper::<(), _, _>(
-                    self,
-                    KvOperation::<DieselPayouts>::Hset((&field, redis_value), redis_entry),
-                    &key,
-                )
-                .await
-                .map_err(|err| err.to_redis_failed_response(&key))?
-                .try_into_hset()
-                .change_context(StorageError::KVError)?;
+                let reply = match set_if_sequence(false).await? {
+                    // The payout has expired from Redis, is still held under its raw key, or
+                    // was written before its sequence number had a field of its own. Its
+                    // sequence number is read from wherever it is and the check is made again,
+                    // this time applying if the field is still missing, as when the update
+                    // recreates an expired payout
+                    ConditionalHsetReply::FieldMissing => {
+                        if let Some(current) = self
+                            .find_optional_payout_by_merchant_id_payout_id(
+                                &this.merchant_id,
+                                &this.payout_id,
+                                storage_scheme,
+                            )
+                            .await?
+                        {
+                            ensure_sequence_matches(this, current.sequence_number)?;
+                        }
+                        set_if_sequence(true).await?
+                    }
+                    reply => reply,
+                };
+                if reply == ConditionalHsetReply::FieldsSet {
+                    if let Some(adjustment) = amount_adjustment {
+                        push_amount_adjustment(self, &payout_key.key, adjustment).await?;
+                    }
 
-                Ok(Payouts::from_storage_model(diesel_payout))
+                    bump_payout_list_version(self, &this.merchant_id).await;
+                    let updated_payout = Payouts::from_storage_model(diesel_payout);
+                    #[cfg(feature = "kv_write_check")]
+                    super::write_check::check_kv_write(self, &updated_payout).await;
+                    record_payout_audit_event(
+                        self.router_store.payout_audit_sink.as_ref(),
+                        PayoutAuditOperation::Update,
+                        &updated_payout,
+                    )
+                    .await;
+                    Ok(updated_payout)
+                } else {
+                    Err(StorageError::StaleUpdate { entity: "payouts" })
+                        .into_report()
+                        .attach_printable(format!(
+                            "payout {} was updated since sequence number {}",
+                            this.payout_id, this.sequence_number
+                        ))
+                }
             }
-        }
+        };
+        // Also invalidated on failure, a stale update means the cached payout is stale as well
+        self.invalidate_cached_payout(&this.merchant_id, &this.payout_id);
+        updated_payout
     }
 
     #[instrument(skip_all)]
     async fn find_payout_by_merchant_id_payout_id(
         &self,
-        merchant_id: &str,
-        payout_id: &str,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<Payouts, StorageError> {
+        let cache_ticket = match self.payout_read_cache.as_deref() {
+            Some(cache) => match cache.get(merchant_id, payout_id) {
+                Some(payout) => return admit_payout(self.router_store.payout_test_mode, payout),
+                None => Some(cache.read_ticket()),
+            },
+            None => None,
+        };
+
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
         let database_call = || async {
             let conn = pg_connection_read(self).await?;
             DieselPayouts::find_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
                 .await
                 .map_err(|er| {
-                    let new_err = diesel_error_to_data_error(er.current_context());
+                    let new_err = payout_lookup_error(merchant_id, payout_id, er.current_context());
                     er.change_context(new_err)
                 })
         };
-        match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => database_call().await,
+        let payout = match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                database_call().await
+            }
             MerchantStorageScheme::RedisKv => {
-                let key = format!("mid_{merchant_id}_po_{payout_id}");
-                let field = format!("po_{payout_id}");
+                let keys = self.payout_kv_read_keys(merchant_id, payout_id);
                 Box::pin(utils::try_redis_get_else_try_database_get(
-                    async {
-                        kv_wrapper::<DieselPayouts, _, _>(
-                            self,
-                            KvOperation::<DieselPayouts>::HGet(&field),
-                            &key,
-                        )
-                        .await?
-                        .try_into_hget()
-                    },
+                    read_from_first_key(&keys, |key| self.read_kv_payout(key)),
                     database_call,
                 ))
                 .await
             }
         }
-        .map(Payouts::from_storage_model)
+        .map(Payouts::from_storage_model)?;
+
+        if storage_scheme == MerchantStorageScheme::DualWrite {
+            shadow_read(self, &payout).await;
+        }
+        if let (Some(cache), Some(ticket)) = (self.payout_read_cache.as_deref(), cache_ticket) {
+            cache.populate(ticket, &payout);
+        }
+        admit_payout(self.router_store.payout_test_mode, payout)
     }
 
     #[instrument(skip_all)]
     async fn find_optional_payout_by_merchant_id_payout_id(
         &self,
-        merchant_id: &str,
-        payout_id: &str,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
         storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
         let database_call = || async {
             let conn = pg_connection_read(self).await?;
             DieselPayouts::find_optional_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
@@ -202,10 +370,10 @@ impl<T: DatabaseStore> PayoutsInterface for KVRouterStore<T> {
                 })
         };
         match storage_scheme {
-            MerchantStorageScheme::PostgresOnly => {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
                 let maybe_payouts = database_call().await?;
                 Ok(maybe_payouts.and_then(|payout| {
-                    if payout.payout_id == payout_id {
+                    if *payout_id == payout.payout_id {
                         Some(payout)
                     } else {
                         None
@@ -213,204 +381,4081 @@ impl<T: DatabaseStore> PayoutsInterface for KVRouterStore<T> {
                 }))
             }
             MerchantStorageScheme::RedisKv => {
-                let key = format!("mid_{merchant_id}_po_{payout_id}");
-                let field = format!("po_{payout_id}");
+                let keys = self.payout_kv_read_keys(merchant_id, payout_id);
                 Box::pin(utils::try_redis_get_else_try_database_get(
-                    async {
-                        kv_wrapper::<DieselPayouts, _, _>(
-                            self,
-                            KvOperation::<DieselPayouts>::HGet(&field),
-                            &key,
-                        )
-                        .await?
-                        .try_into_hget()
-                        .map(Some)
-                    },
+                    read_from_first_key(&keys, |key| async move {
+                        self.read_kv_payout(key).await.map(Some)
+                    }),
                     database_call,
                 ))
                 .await
             }
         }
-        .map(|payout| payout.map(Payouts::from_storage_model))
+        .map(|payout| {
+            self.router_store
+                .payout_test_mode
+                .admit(payout.map(Payouts::from_storage_model))
+        })
     }
-}
 
-#[async_trait::async_trait]
-impl<T: DatabaseStore> PayoutsInterface for crate::RouterStore<T> {
     #[instrument(skip_all)]
-    async fn insert_payout(
+    async fn clone_payout_as_new(
         &self,
-        new: PayoutsNew,
-        _storage_scheme: MerchantStorageScheme,
+        merchant_id: &MerchantId,
+        source_payout_id: &PayoutId,
+        new_payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<Payouts, StorageError> {
-        let conn = pg_connection_write(self).await?;
-        new.to_storage_model()
-            .insert(&conn)
-            .await
-            .map_err(|er| {
-                let new_err = diesel_error_to_data_error(er.current_context());
-                er.change_context(new_err)
-            })
-            .map(Payouts::from_storage_model)
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let source_payout = self
+            .find_payout_by_merchant_id_payout_id(merchant_id, source_payout_id, storage_scheme)
+            .await?;
+        self.insert_payout(
+            source_payout.to_new_payout_for_retry(new_payout_id.to_string()),
+            storage_scheme,
+        )
+        .await
     }
 
     #[instrument(skip_all)]
-    async fn update_payout(
+    async fn update_payout_metadata_path(
         &self,
-        this: &Payouts,
-        payout: PayoutsUpdate,
-        _storage_scheme: MerchantStorageScheme,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        json_path: &[String],
+        value: pii::SecretSerdeValue,
+        create_missing: bool,
+        storage_scheme: MerchantStorageScheme,
     ) -> error_stack::Result<Payouts, StorageError> {
-        let conn = pg_connection_write(self).await?;
-        this.clone()
-            .to_storage_model()
-            .update(&conn, payout.to_storage_model())
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                let update = self.router_store.update_payout_metadata_path(
+                    merchant_id,
+                    payout_id,
+                    json_path,
+                    value,
+                    create_missing,
+                    storage_scheme,
+                );
+                let updated_payout = if storage_scheme == MerchantStorageScheme::DualWrite {
+                    write_and_mirror(self, update).await
+                } else {
+                    update.await
+                };
+                self.invalidate_cached_payout(merchant_id, payout_id);
+                updated_payout
+            }
+            MerchantStorageScheme::RedisKv => {
+                let payout = self
+                    .find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+                    .await?;
+
+                let mut metadata = payout
+                    .metadata
+                    .clone()
+                    .map(|metadata| metadata.expose())
+                    .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
+                set_json_path(&mut metadata, json_path, value.expose(), create_missing);
+
+                self.update_payout(
+                    &payout,
+                    PayoutsUpdate::MetadataUpdate {
+                        metadata: Some(Secret::new(metadata)),
+                    },
+                    storage_scheme,
+                )
+                .await
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn check_payout_balance(
+        &self,
+        merchant_id: &MerchantId,
+        amount: i64,
+        currency: Currency,
+    ) -> error_stack::Result<(), StorageError> {
+        self.router_store
+            .check_payout_balance(merchant_id, amount, currency)
             .await
-            .map_err(|er| {
-                let new_err = diesel_error_to_data_error(er.current_context());
-                er.change_context(new_err)
-            })
-            .map(Payouts::from_storage_model)
     }
 
     #[instrument(skip_all)]
-    async fn find_payout_by_merchant_id_payout_id(
+    async fn distinct_payout_currencies(
         &self,
-        merchant_id: &str,
-        payout_id: &str,
-        _storage_scheme: MerchantStorageScheme,
-    ) -> error_stack::Result<Payouts, StorageError> {
-        let conn = pg_connection_read(self).await?;
-        DieselPayouts::find_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Currency>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .distinct_payout_currencies(merchant_id, storage_scheme)
             .await
-            .map(Payouts::from_storage_model)
-            .map_err(|er| {
-                let new_err = diesel_error_to_data_error(er.current_context());
-                er.change_context(new_err)
-            })
     }
 
     #[instrument(skip_all)]
-    async fn find_optional_payout_by_merchant_id_payout_id(
+    async fn list_payouts_by_source(
         &self,
-        merchant_id: &str,
-        payout_id: &str,
-        _storage_scheme: MerchantStorageScheme,
-    ) -> error_stack::Result<Option<Payouts>, StorageError> {
-        let conn = pg_connection_read(self).await?;
-        DieselPayouts::find_optional_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+        merchant_id: &MerchantId,
+        created_by: PayoutCreationSource,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .list_payouts_by_source(merchant_id, created_by, storage_scheme)
             .await
-            .map(|x| x.map(Payouts::from_storage_model))
-            .map_err(|er| {
-                let new_err = diesel_error_to_data_error(er.current_context());
-                er.change_context(new_err)
-            })
     }
-}
 
-impl DataModelExt for Payouts {
-    type StorageModel = DieselPayouts;
+    #[instrument(skip_all)]
+    async fn list_payouts_by_merchant_id_statuses(
+        &self,
+        merchant_id: &MerchantId,
+        statuses: Vec<PayoutStatus>,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .list_payouts_by_merchant_id_statuses(merchant_id, statuses, limit, storage_scheme)
+            .await
+    }
 
-    fn to_storage_model(self) -> Self::StorageModel {
-        DieselPayouts {
-            payout_id: self.payout_id,
-            merchant_id: self.merchant_id,
-            customer_id: self.customer_id,
-            address_id: self.address_id,
-            payout_type: self.payout_type,
-            payout_method_id: self.payout_method_id,
-            amount: self.amount,
-            destination_currency: self.destination_currency,
-            source_currency: self.source_currency,
-            description: self.description,
-            recurring: self.recurring,
-            auto_fulfill: self.auto_fulfill,
-            return_url: self.return_url,
-            entity_type: self.entity_type,
-            metadata: self.metadata,
-            created_at: self.created_at,
-            last_modified_at: self.last_modified_at,
-            profile_id: self.profile_id,
-            status: self.status,
-            attempt_count: self.attempt_count,
-        }
+    #[instrument(skip_all)]
+    async fn list_high_risk_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        min_score: i16,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .list_high_risk_payouts(merchant_id, min_score, storage_scheme)
+            .await
     }
 
-    fn from_storage_model(storage_model: Self::StorageModel) -> Self {
-        Self {
-            payout_id: storage_model.payout_id,
-            merchant_id: storage_model.merchant_id,
-            customer_id: storage_model.customer_id,
-            address_id: storage_model.address_id,
-            payout_type: storage_model.payout_type,
-            payout_method_id: storage_model.payout_method_id,
-            amount: storage_model.amount,
-            destination_currency: storage_model.destination_currency,
-            source_currency: storage_model.source_currency,
-            description: storage_model.description,
-            recurring: storage_model.recurring,
-            auto_fulfill: storage_model.auto_fulfill,
-            return_url: storage_model.return_url,
-            entity_type: storage_model.entity_type,
-            metadata: storage_model.metadata,
-            created_at: storage_model.created_at,
-            last_modified_at: storage_model.last_modified_at,
-            profile_id: storage_model.profile_id,
-            status: storage_model.status,
-            attempt_count: storage_model.attempt_count,
-        }
+    #[instrument(skip_all)]
+    async fn find_latest_payout_by_customer_id(
+        &self,
+        merchant_id: &MerchantId,
+        customer_id: &CustomerId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .find_latest_payout_by_customer_id(merchant_id, customer_id, storage_scheme)
+            .await
     }
-}
-impl DataModelExt for PayoutsNew {
-    type StorageModel = DieselPayoutsNew;
 
-    fn to_storage_model(self) -> Self::StorageModel {
-        DieselPayoutsNew {
-            payout_id: self.payout_id,
-            merchant_id: self.merchant_id,
-            customer_id: self.customer_id,
-            address_id: self.address_id,
-            payout_type: self.payout_type,
-            payout_method_id: self.payout_method_id,
-            amount: self.amount,
-            destination_currency: self.destination_currency,
-            source_currency: self.source_currency,
-            description: self.description,
-            recurring: self.recurring,
-            auto_fulfill: self.auto_fulfill,
-            return_url: self.return_url,
-            entity_type: self.entity_type,
-            metadata: self.metadata,
-            created_at: self.created_at,
-            last_modified_at: self.last_modified_at,
-            profile_id: self.profile_id,
-            status: self.status,
-            attempt_count: self.attempt_count,
+    #[instrument(skip_all)]
+    async fn find_optional_payouts_batch(
+        &self,
+        merchant_id: &MerchantId,
+        payout_ids: &[PayoutId],
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<HashMap<PayoutId, Option<Payouts>>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                self.router_store
+                    .find_optional_payouts_batch(merchant_id, payout_ids, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::RedisKv => {
+                let summary_cache = self.payout_summary_cache.as_deref();
+                let list_version = match summary_cache {
+                    Some(_) => get_payout_list_version(self, merchant_id)
+                        .await
+                        .map_err(|error| {
+                            logger::error!(?error, "Failed to read payout list version");
+                        })
+                        .ok(),
+                    None => None,
+                };
+                let cached = match (summary_cache, list_version) {
+                    (Some(cache), Some(list_version)) => {
+                        cache.get_many(merchant_id, list_version, payout_ids)
+                    }
+                    _ => HashMap::new(),
+                };
+                let payout_ids = payout_ids
+                    .iter()
+                    .filter(|payout_id| !cached.contains_key(*payout_id))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if payout_ids.is_empty() {
+                    return Ok(cached
+                        .into_iter()
+                        .map(|(payout_id, payout)| {
+                            let payout = self.router_store.payout_test_mode.admit(Some(payout));
+                            (payout_id, payout)
+                        })
+                        .collect());
+                }
+
+                let keys = payout_ids
+                    .iter()
+                    .map(|payout_id| self.payout_kv_key(merchant_id, payout_id))
+                    .collect::<Vec<_>>();
+                let redis_conn = self
+                    .get_redis_read_conn(keys.iter().map(|key| key.key.as_str()))
+                    .change_context(StorageError::KVError)?;
+                let values = self
+                    .read_kv_payouts_batch(&redis_conn, &keys)
+                    .await
+                    .change_context(StorageError::KVError)?;
+
+                let (mut payouts, mut misses) = decode_kv_payouts_batch(&payout_ids, values);
+                if self.payout_key_hasher.is_some() && !misses.is_empty() {
+                    // Payouts written before payout ids were hashed are still held under their
+                    // raw key
+                    let raw_keys = misses
+                        .iter()
+                        .map(|payout_id| PayoutKvKey::raw(merchant_id, payout_id))
+                        .collect::<Vec<_>>();
+                    let values = self
+                        .read_kv_payouts_batch(&redis_conn, &raw_keys)
+                        .await
+                        .change_context(StorageError::KVError)?;
+                    let (raw_payouts, raw_misses) = decode_kv_payouts_batch(&misses, values);
+                    payouts.extend(raw_payouts);
+                    misses = raw_misses;
+                }
+                if !misses.is_empty() {
+                    payouts.extend(
+                        self.router_store
+                            .find_optional_payouts_batch(merchant_id, &misses, storage_scheme)
+                            .await?,
+                    );
+                }
+                if let (Some(cache), Some(list_version)) = (summary_cache, list_version) {
+                    cache.populate(merchant_id, list_version, payouts.values().flatten());
+                }
+                payouts.extend(
+                    cached
+                        .into_iter()
+                        .map(|(payout_id, payout)| (payout_id, Some(payout))),
+                );
+                Ok(payouts
+                    .into_iter()
+                    .map(|(payout_id, payout)| {
+                        (payout_id, self.router_store.payout_test_mode.admit(payout))
+                    })
+                    .collect())
+            }
         }
     }
 
-    fn from_storage_model(storage_model: Self::StorageModel) -> Self {
-        Self {
-            payout_id: storage_model.payout_id,
-            merchant_id: storage_model.merchant_id,
-            customer_id: storage_model.customer_id,
-            address_id: storage_model.address_id,
-            payout_type: storage_model.payout_type,
-            payout_method_id: storage_model.payout_method_id,
-            amount: storage_model.amount,
-            destination_currency: storage_model.destination_currency,
-            source_currency: storage_model.source_currency,
-            description: storage_model.description,
-            recurring: storage_model.recurring,
-            auto_fulfill: storage_model.auto_fulfill,
-            return_url: storage_model.return_url,
-            entity_type: storage_model.entity_type,
-            metadata: storage_model.metadata,
-            created_at: storage_model.created_at,
-            last_modified_at: storage_model.last_modified_at,
-            profile_id: storage_model.profile_id,
-            status: storage_model.status,
-            attempt_count: storage_model.attempt_count,
+    #[instrument(skip_all)]
+    async fn find_payouts_by_network_token_ref(
+        &self,
+        merchant_id: &MerchantId,
+        network_token_ref: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .find_payouts_by_network_token_ref(merchant_id, network_token_ref, storage_scheme)
+            .await
+    }
+
+    /// In KV, the payout is claimed through `update_payout`, so that the claim is written to its
+    /// hash and drained to the database like any other update.
+    #[instrument(skip_all)]
+    async fn claim_payout_for_retry(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        max_attempts: i16,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let claimed_payout = match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => {
+                self.router_store
+                    .claim_payout_for_retry(merchant_id, payout_id, max_attempts, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::DualWrite => {
+                match self
+                    .router_store
+                    .claim_payout_for_retry(merchant_id, payout_id, max_attempts, storage_scheme)
+                    .await?
+                {
+                    Some(payout) => write_and_mirror(self, async { Ok(payout) }).await.map(Some),
+                    None => Ok(None),
+                }
+            }
+            MerchantStorageScheme::RedisKv => {
+                claim_payout_for_retry_in_store(
+                    self,
+                    merchant_id,
+                    payout_id,
+                    max_attempts,
+                    common_utils::date_time::now(),
+                    storage_scheme,
+                )
+                .await
+            }
+        };
+        self.invalidate_cached_payout(merchant_id, payout_id);
+        claimed_payout
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payouts_needing_retry(
+        &self,
+        now: PrimitiveDateTime,
+        max_attempts: i16,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        ensure_scheme_supported(self, storage_scheme)?;
+        // Candidates are found in the database, the claim checks them again against the payout
+        // held in KV, see `claim_payout_for_retry`
+        self.router_store
+            .find_payouts_needing_retry(now, max_attempts, limit, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn list_payouts_by_status_before(
+        &self,
+        status: PayoutStatus,
+        before: PrimitiveDateTime,
+        after_cursor: Option<&PayoutPageCursor>,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        ensure_scheme_supported(self, storage_scheme)?;
+        self.router_store
+            .list_payouts_by_status_before(status, before, after_cursor, limit, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn count_payouts_by_status(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<HashMap<PayoutStatus, i64>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .count_payouts_by_status(merchant_id, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn sum_payout_amounts_by_merchant_id(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<i128, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .sum_payout_amounts_by_merchant_id(merchant_id, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn average_payout_processing_seconds(
+        &self,
+        merchant_id: &MerchantId,
+        filters: &PayoutProcessingTimeFilters,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<f64>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .average_payout_processing_seconds(merchant_id, filters, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payout_by_connector_idempotency_key(
+        &self,
+        merchant_id: &MerchantId,
+        connector_idempotency_key: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .find_payout_by_connector_idempotency_key(
+                merchant_id,
+                connector_idempotency_key,
+                storage_scheme,
+            )
+            .await
+    }
+
+    /// In KV, a payout not drained to the database yet is found through the number recorded
+    /// when it was inserted.
+    #[instrument(skip_all)]
+    async fn find_payout_by_display_seq(
+        &self,
+        merchant_id: &MerchantId,
+        display_seq: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                self.router_store
+                    .find_payout_by_display_seq(merchant_id, display_seq, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::RedisKv => {
+                match self
+                    .find_kv_payout_id_by_display_seq(merchant_id, display_seq)
+                    .await?
+                {
+                    Some(payout_id) => {
+                        self.find_payout_by_merchant_id_payout_id(
+                            merchant_id,
+                            &payout_id.as_str().into(),
+                            storage_scheme,
+                        )
+                        .await
+                    }
+                    None => {
+                        self.router_store
+                            .find_payout_by_display_seq(merchant_id, display_seq, storage_scheme)
+                            .await
+                    }
+                }
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn diff_payouts_since(
+        &self,
+        merchant_id: &MerchantId,
+        since: PrimitiveDateTime,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .diff_payouts_since(merchant_id, since, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn list_payouts_modified_by(
+        &self,
+        merchant_id: &MerchantId,
+        actor: &str,
+        since: PrimitiveDateTime,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .list_payouts_modified_by(merchant_id, actor, since, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn find_stale_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        no_change_for: time::Duration,
+        statuses: Vec<PayoutStatus>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .find_stale_payouts(merchant_id, no_change_for, statuses, storage_scheme)
+            .await
+    }
+
+    /// The payout is read from the KV store, its attempts from the database, concurrently.
+    #[instrument(skip_all)]
+    async fn find_payout_with_attempts(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, Vec<PayoutAttempt>), StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        futures::try_join!(
+            self.find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme),
+            self.list_payout_attempts(merchant_id, payout_id, storage_scheme),
+        )
+    }
+
+    #[instrument(skip_all)]
+    async fn recompute_payout_attempt_count(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<i16, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        // Attempts are only ever written to the database. The payout is updated through
+        // `update_payout`, which also refreshes its hash when it is held in KV.
+        let attempts = count_payout_attempts(&self.router_store, merchant_id, payout_id).await?;
+        recompute_payout_attempt_count_in_store(
+            self,
+            merchant_id,
+            payout_id,
+            attempts,
+            storage_scheme,
+        )
+        .await
+    }
+
+    #[instrument(skip_all)]
+    async fn activate_due_payouts(
+        &self,
+        now: PrimitiveDateTime,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutId>, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        ensure_scheme_supported(self, storage_scheme)?;
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                let activated =
+                    activate_due_payouts_in_database(&self.router_store, now, limit).await?;
+                let mut activated_ids = Vec::with_capacity(activated.len());
+                for payout in activated {
+                    self.invalidate_cached_payout(&payout.merchant_id, &payout.payout_id);
+                    if storage_scheme == MerchantStorageScheme::DualWrite {
+                        if let Err(error) = self.mirror_payout(&payout).await {
+                            logger::warn!(
+                                ?error,
+                                payout_id = %payout.payout_id,
+                                "Failed to mirror an activated payout to KV"
+                            );
+                        }
+                    }
+                    activated_ids.push(PayoutId::from(payout.payout_id));
+                }
+                Ok(activated_ids)
+            }
+            MerchantStorageScheme::RedisKv => {
+                // The KV copy of a payout may be ahead of the database, so every candidate is
+                // read again and activated through `update_payout`, which checks the transition
+                let conn = pg_connection_read(self).await?;
+                let candidates = DieselPayouts::filter_due_scheduled(&conn, now, limit)
+                    .await
+                    .map_err(|er| {
+                        let new_err = diesel_error_to_data_error(er.current_context());
+                        er.change_context(new_err)
+                    })?;
+                let mut current = Vec::with_capacity(candidates.len());
+                for candidate in candidates {
+                    match self
+                        .find_payout_by_merchant_id_payout_id(
+                            &MerchantId::from(&candidate.merchant_id),
+                            &PayoutId::from(&candidate.payout_id),
+                            storage_scheme,
+                        )
+                        .await
+                    {
+                        Ok(payout) => current.push(payout),
+                        Err(error) => logger::warn!(
+                            ?error,
+                            payout_id = %candidate.payout_id,
+                            "Skipped a due payout which could not be read"
+                        ),
+                    }
+                }
+
+                let limit = usize::try_from(limit).unwrap_or_default();
+                Ok(activate_payouts_in_store(self, current, now, limit, storage_scheme).await)
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn confirm_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        confirm_payout_in_store(self, merchant_id, payout_id, storage_scheme).await
+    }
+
+    #[instrument(skip_all)]
+    async fn reroute_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        new_connector: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        reroute_payout_in_store(self, merchant_id, payout_id, new_connector, storage_scheme).await
+    }
+
+    /// In KV, the status field of the hash is checked and the payout written by a single Lua
+    /// script, see [`KvOperation::HsetMultipleIf`].
+    #[instrument(skip_all)]
+    async fn update_payout_if_status(
+        &self,
+        this: &Payouts,
+        payout_update: PayoutsUpdate,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(&this.merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let updated_payout = match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => {
+                self.router_store
+                    .update_payout_if_status(this, payout_update, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::DualWrite => {
+                match self
+                    .router_store
+                    .update_payout_if_status(this, payout_update, storage_scheme)
+                    .await?
+                {
+                    Some(payout) => write_and_mirror(self, async { Ok(payout) }).await.map(Some),
+                    None => Ok(None),
+                }
+            }
+            MerchantStorageScheme::RedisKv => {
+                payout_update.validate_transition(this)?;
+                validate_payout_return_url_allowed(
+                    self,
+                    &this.merchant_id,
+                    payout_update.return_url(),
+                )
+                .await?;
+
+                let payout_key = self.payout_kv_key(&this.merchant_id, &this.payout_id);
+                let amount_adjustment = this.amount_adjustment(&payout_update);
+                let diesel_payout_update = payout_update.to_storage_model();
+                let origin_diesel_payout = this.clone().to_storage_model();
+                let diesel_payout = diesel_payout_update
+                    .clone()
+                    .apply_changeset(origin_diesel_payout.clone());
+                let redis_fields =
+                    payout_hash_fields(&payout_key, &diesel_payout, self.payout_checksum.as_ref())?;
+                let status_field = payout_key.status_field(this.test_mode);
+                let expected_status = this
+                    .status
+                    .encode_to_string_of_json()
+                    .change_context(StorageError::SerializationFailed)?;
+
+                let set_if_status = |apply_if_missing| {
+                    let redis_entry = kv::TypedSql {
+                        op: kv::DBOperation::Update {
+                            updatable: kv::Updateable::PayoutsUpdate(kv::PayoutsUpdateMems {
+                                orig: origin_diesel_payout.clone(),
+                                update_data: diesel_payout_update.clone(),
+                            }),
+                        },
+                    };
+                    let operation = KvOperation::<DieselPayouts>::HsetMultipleIf(
+                        redis_fields.clone(),
+                        FieldCondition {
+                            field: &status_field,
+                            expected: &expected_status,
+                            apply_if_missing,
+                        },
+                        redis_entry,
+                    );
+                    let key = payout_key.key.as_str();
+                    async move {
+                        kv_wrapper::<(), _, _>(self, operation, key)
+                            .await
+                            .map_err(|err| err.to_redis_failed_response(key))?
+                            .try_into_hsetif()
+                            .change_context(StorageError::KVError)
+                    }
+                };
+
+                let reply = match set_if_status(false).await? {
+                    // The payout has expired from Redis or is still held under its raw key, its
+                    // status is read from wherever it is and the check is made again, this time
+                    // applying if the field is still missing
+                    ConditionalHsetReply::FieldMissing => {
+                        let current = self
+                            .find_optional_payout_by_merchant_id_payout_id(
+                                &this.merchant_id,
+                                &this.payout_id,
+                                storage_scheme,
+                            )
+                            .await?;
+                        match current {
+                            Some(current) if current.status == this.status => {
+                                set_if_status(true).await?
+                            }
+                            _ => ConditionalHsetReply::ConditionFailed,
+                        }
+                    }
+                    reply => reply,
+                };
+
+                if reply == ConditionalHsetReply::FieldsSet {
+                    if let Some(adjustment) = amount_adjustment {
+                        push_amount_adjustment(self, &payout_key.key, adjustment).await?;
+                    }
+                    bump_payout_list_version(self, &this.merchant_id).await;
+                    let updated_payout = Payouts::from_storage_model(diesel_payout);
+                    record_payout_audit_event(
+                        self.router_store.payout_audit_sink.as_ref(),
+                        PayoutAuditOperation::Update,
+                        &updated_payout,
+                    )
+                    .await;
+                    Ok(Some(updated_payout))
+                } else {
+                    Ok(None)
+                }
+            }
+        };
+        self.invalidate_cached_payout(&this.merchant_id, &this.payout_id);
+        updated_payout
+    }
+
+    #[instrument(skip_all)]
+    async fn insert_payout_with_persistence_info(
+        &self,
+        new: PayoutsNew,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, PersistenceInfo), StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(&new.merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::DualWrite => {
+                let (payout, kv_mirrored) = write_and_report_mirror(
+                    self,
+                    self.router_store.insert_payout(new, storage_scheme),
+                )
+                .await?;
+                Ok((
+                    payout,
+                    PersistenceInfo::for_insert(storage_scheme, kv_mirrored),
+                ))
+            }
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::RedisKv => {
+                let payout = self.insert_payout(new, storage_scheme).await?;
+                Ok((payout, PersistenceInfo::for_insert(storage_scheme, false)))
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn soft_delete_all_payouts_for_merchant(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<usize, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let deleted = soft_delete_payouts_in_database(&self.router_store, merchant_id).await?;
+        for payout in &deleted {
+            self.invalidate_cached_payout(&payout.merchant_id, &payout.payout_id);
+        }
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => {}
+            MerchantStorageScheme::DualWrite | MerchantStorageScheme::RedisKv => {
+                mark_deleted_in_kv(self, &deleted).await;
+            }
+        }
+        Ok(deleted.len())
+    }
+
+    #[instrument(skip_all)]
+    async fn delete_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<bool, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let deleted = match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => {
+                self.router_store
+                    .delete_payout(merchant_id, payout_id, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::DualWrite => {
+                let deleted = self
+                    .router_store
+                    .delete_payout(merchant_id, payout_id, storage_scheme)
+                    .await?;
+                // Postgres already holds the deletion, a copy failing to be deleted from KV is
+                // left to expire with its TTL
+                if let Err(error) = delete_kv_copies(self, merchant_id, payout_id).await {
+                    logger::warn!(
+                        ?error,
+                        %payout_id,
+                        "Failed to delete the KV copy of a deleted payout"
+                    );
+                }
+                Ok(deleted)
+            }
+            MerchantStorageScheme::RedisKv => {
+                delete_payout_in_kv(self, merchant_id, payout_id).await
+            }
+        };
+        self.invalidate_cached_payout(merchant_id, payout_id);
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => {}
+            MerchantStorageScheme::DualWrite | MerchantStorageScheme::RedisKv => {
+                unindex_kv_payout(self, merchant_id, payout_id).await;
+            }
+        }
+        deleted
+    }
+
+    #[instrument(skip_all)]
+    async fn backfill_payout_profile_ids(
+        &self,
+        merchant_id: &MerchantId,
+        default_profile_id: &str,
+        batch_size: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<usize, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        validate_profile_belongs_to_merchant(self, merchant_id, default_profile_id).await?;
+        let backfilled = backfill_profile_ids_in_database(
+            &self.router_store,
+            merchant_id,
+            default_profile_id,
+            batch_size,
+        )
+        .await?;
+        for payout in &backfilled {
+            self.invalidate_cached_payout(&payout.merchant_id, &payout.payout_id);
+        }
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => {}
+            MerchantStorageScheme::DualWrite | MerchantStorageScheme::RedisKv => {
+                backfill_profile_ids_in_kv(self, &backfilled).await;
+            }
+        }
+        Ok(backfilled.len())
+    }
+
+    #[instrument(skip_all)]
+    async fn import_payout(
+        &self,
+        new: PayoutsNew,
+        preserve_timestamps: bool,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(&new.merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let new = prepare_payout_import(
+            self.router_store.payout_imports_enabled,
+            new,
+            preserve_timestamps,
+        )?;
+        self.insert_payout(new, storage_scheme).await
+    }
+
+    #[instrument(skip_all)]
+    async fn update_payouts_status_batch(
+        &self,
+        merchant_id: &MerchantId,
+        transitions: Vec<(PayoutId, PayoutStatus)>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<(PayoutId, error_stack::Result<(), StorageError>)>, StorageError>
+    {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        update_payouts_status_batch_in_store(self, merchant_id, transitions, storage_scheme).await
+    }
+
+    #[instrument(skip_all)]
+    async fn record_webhook_event_once(
+        &self,
+        connector: &str,
+        event_id: &str,
+    ) -> error_stack::Result<bool, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        // Processed events are always recorded in the database, so that deduplication outlives
+        // the KV entries
+        self.router_store
+            .record_webhook_event_once(connector, event_id)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn forget_webhook_event(
+        &self,
+        connector: &str,
+        event_id: &str,
+    ) -> error_stack::Result<(), StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        self.router_store
+            .forget_webhook_event(connector, event_id)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn get_payout_daily_count(
+        &self,
+        merchant_id: &MerchantId,
+        date: time::Date,
+    ) -> error_stack::Result<i64, StorageError> {
+        get_payout_daily_count(self, merchant_id, date).await
+    }
+
+    #[instrument(skip_all)]
+    async fn cancel_payout_with_reversal(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        reason: String,
+        reversal: Option<PayoutAttemptNew>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, Option<PayoutAttempt>), StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let cancelled = match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => {
+                self.router_store
+                    .cancel_payout_with_reversal(
+                        merchant_id,
+                        payout_id,
+                        reason,
+                        reversal,
+                        storage_scheme,
+                    )
+                    .await
+            }
+            MerchantStorageScheme::DualWrite => {
+                async {
+                    let (cancelled, reversal) = self
+                        .router_store
+                        .cancel_payout_with_reversal(
+                            merchant_id,
+                            payout_id,
+                            reason,
+                            reversal,
+                            storage_scheme,
+                        )
+                        .await?;
+                    let cancelled =
+                        write_and_mirror(self, std::future::ready(Ok(cancelled))).await?;
+                    Ok((cancelled, reversal))
+                }
+                .await
+            }
+            MerchantStorageScheme::RedisKv => {
+                cancel_kv_payout_with_reversal(
+                    self,
+                    merchant_id,
+                    payout_id,
+                    reason,
+                    reversal,
+                    storage_scheme,
+                )
+                .await
+            }
+        };
+        self.invalidate_cached_payout(merchant_id, payout_id);
+        cancelled
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payout_with_load_options(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        load_options: PayoutLoadOptions,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        // The cache only holds fully loaded payouts, the payouts read here are never cached
+        if let Some(payout) = self
+            .payout_read_cache
+            .as_deref()
+            .and_then(|cache| cache.get(merchant_id, payout_id))
+        {
+            return admit_payout(
+                self.router_store.payout_test_mode,
+                load_options.apply(payout),
+            );
+        }
+
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let skipped_fields = load_options.skipped_fields();
+        let database_call = || async {
+            let conn = pg_connection_read(self).await?;
+            DieselPayouts::find_by_merchant_id_payout_id_skipping(
+                &conn,
+                merchant_id,
+                payout_id,
+                &skipped_fields,
+            )
+            .await
+            .map_err(|er| {
+                let new_err = payout_lookup_error(merchant_id, payout_id, er.current_context());
+                er.change_context(new_err)
+            })
+        };
+        let payout = match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                database_call().await
+            }
+            MerchantStorageScheme::RedisKv => {
+                let keys = self.payout_kv_read_keys(merchant_id, payout_id);
+                Box::pin(utils::try_redis_get_else_try_database_get(
+                    read_from_first_key(&keys, |key| {
+                        self.read_kv_payout_skipping(key, &skipped_fields)
+                    }),
+                    database_call,
+                ))
+                .await
+            }
+        }
+        .map(Payouts::from_storage_model)?;
+        admit_payout(self.router_store.payout_test_mode, payout)
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payouts_with_inconsistent_timestamps(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        // The KV store can't be queried on the timestamps, the repair patches its copies
+        self.router_store
+            .find_payouts_with_inconsistent_timestamps(merchant_id, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn repair_payout_timestamps(
+        &self,
+        merchant_id: &MerchantId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<usize, StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        let repaired = repair_timestamps_in_database(&self.router_store, merchant_id).await?;
+        for payout in &repaired {
+            self.invalidate_cached_payout(&payout.merchant_id, &payout.payout_id);
+        }
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => {}
+            MerchantStorageScheme::DualWrite | MerchantStorageScheme::RedisKv => {
+                repair_timestamps_in_kv(self, &repaired).await;
+            }
+        }
+        Ok(repaired.len())
+    }
+
+    #[instrument(skip_all)]
+    async fn get_payout_status(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<PayoutStatus, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                self.router_store
+                    .get_payout_status(merchant_id, payout_id, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::RedisKv => {
+                let test_mode_context = self.router_store.payout_test_mode;
+                let keys = self.payout_kv_read_keys(merchant_id, payout_id);
+                Box::pin(utils::try_redis_get_else_try_database_get(
+                    read_from_first_key(&keys, |key| async move {
+                        let status_field =
+                            key.status_field(test_mode_context.test_mode().unwrap_or(false));
+                        match kv_wrapper::<PayoutStatus, _, _>(
+                            self,
+                            KvOperation::<DieselPayouts>::HGet(&status_field),
+                            &key.key,
+                        )
+                        .await
+                        .and_then(|result| result.try_into_hget())
+                        {
+                            // The status field is missing if writing it after the insert of the
+                            // payout failed, or if the payout is not of the mode of the context,
+                            // the full payout is read instead
+                            Err(error)
+                                if matches!(error.current_context(), RedisError::NotFound) =>
+                            {
+                                let payout = self.read_kv_payout(key).await?;
+                                if test_mode_context.admits(payout.test_mode) {
+                                    Ok(payout.status)
+                                } else {
+                                    Err(RedisError::NotFound).into_report()
+                                }
+                            }
+                            result => result,
+                        }
+                    }),
+                    || {
+                        self.router_store
+                            .get_payout_status(merchant_id, payout_id, storage_scheme)
+                    },
+                ))
+                .await
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payouts_by_source_payment_id(
+        &self,
+        merchant_id: &MerchantId,
+        source_payment_id: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .find_payouts_by_source_payment_id(merchant_id, source_payment_id, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn insert_split_payouts(
+        &self,
+        parent: PayoutsNew,
+        splits: Vec<PayoutsNew>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, Vec<Payouts>), StorageError> {
+        self.router_store.payout_read_only.ensure_writable()?;
+        let storage_scheme = self
+            .resolve_storage_scheme(&parent.merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly => {
+                let merchant_id = parent.merchant_id.clone();
+                let inserted = self
+                    .router_store
+                    .insert_split_payouts(parent, splits, storage_scheme)
+                    .await?;
+                self.reset_kv_display_seq_counter(&merchant_id).await;
+                Ok(inserted)
+            }
+            MerchantStorageScheme::DualWrite => {
+                let merchant_id = parent.merchant_id.clone();
+                let (parent, splits) = self
+                    .router_store
+                    .insert_split_payouts(parent, splits, storage_scheme)
+                    .await?;
+                self.reset_kv_display_seq_counter(&merchant_id).await;
+                mirror_split_payouts(self, parent, splits).await
+            }
+            MerchantStorageScheme::RedisKv => {
+                // Numbers are taken from the KV counter, which the other payouts of the merchant
+                // are numbered from
+                let (parent, splits) = prepare_split_payouts(
+                    &self.router_store,
+                    self,
+                    parent,
+                    splits,
+                    |merchant_id| async move { next_kv_display_seq(self, &merchant_id).await },
+                )
+                .await?;
+                let (parent, splits) =
+                    insert_split_payouts_in_database(&self.router_store, parent, splits).await?;
+                for payout in std::iter::once(&parent).chain(&splits) {
+                    self.record_kv_display_seq(
+                        &payout.merchant_id,
+                        payout.display_seq,
+                        &payout.payout_id,
+                    )
+                    .await;
+                }
+                mirror_split_payouts(self, parent, splits).await
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn list_child_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        parent_payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        // Splits are always inserted in Postgres, and their parent never changes afterwards
+        self.router_store
+            .list_child_payouts(merchant_id, parent_payout_id, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn list_amount_adjustments(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutAmountAdjustment>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        self.router_store
+            .list_amount_adjustments(merchant_id, payout_id, storage_scheme)
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payouts_by_metadata_key(
+        &self,
+        merchant_id: &MerchantId,
+        key: &str,
+        value: &serde_json::Value,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        // The KV store holds the same metadata blob, but can't be queried on it
+        self.router_store
+            .find_payouts_by_metadata_key(merchant_id, key, value, storage_scheme)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: DatabaseStore> PayoutsInterface for crate::RouterStore<T> {
+    #[instrument(skip_all)]
+    async fn insert_payout(
+        &self,
+        new: PayoutsNew,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let new = validate_new_payout(self, self, new).await?;
+        let new = PayoutsNew {
+            display_seq: next_display_seq_in_database(self, &new.merchant_id).await?,
+            ..new
+        };
+
+        let conn = pg_connection_write(self).await?;
+        let payout = new
+            .to_storage_model()
+            .insert(&conn)
+            .await
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+            .map(Payouts::from_storage_model)?;
+
+        record_payout_created(self, &payout.merchant_id, common_utils::date_time::now()).await;
+        record_payout_audit_event(
+            self.payout_audit_sink.as_ref(),
+            PayoutAuditOperation::Insert,
+            &payout,
+        )
+        .await;
+        Ok(payout)
+    }
+
+    #[instrument(skip_all)]
+    async fn update_payout(
+        &self,
+        this: &Payouts,
+        payout: PayoutsUpdate,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        payout.validate_transition(this)?;
+        validate_payout_return_url_allowed(self, &this.merchant_id, payout.return_url()).await?;
+
+        let conn = pg_connection_write(self).await?;
+        let origin_payout = this.clone().to_storage_model();
+        let payout_update = payout.to_storage_model();
+        let updated_payout = match this.amount_adjustment(&payout) {
+            None => {
+                origin_payout
+                    .update_if_sequence_matches(&conn, payout_update)
+                    .await
+            }
+            Some(adjustment) => conn
+                .transaction_async(|conn| async move {
+                    let updated_payout = origin_payout
+                        .update_if_sequence_matches(&conn, payout_update)
+                        .await
+                        .map_err(|er| *er.current_context())?;
+                    adjustment
+                        .to_storage_model()
+                        .insert(&conn)
+                        .await
+                        .map_err(|er| *er.current_context())?;
+                    Ok::<_, diesel_models::errors::DatabaseError>(updated_payout)
+                })
+                .await
+                .into_report(),
+        };
+        let updated_payout = match updated_payout {
+            Ok(updated_payout) => Payouts::from_storage_model(updated_payout),
+            Err(er) => return Err(sequenced_update_error(&conn, this, er).await),
+        };
+
+        bump_payout_list_version(self, &updated_payout.merchant_id).await;
+        record_payout_audit_event(
+            self.payout_audit_sink.as_ref(),
+            PayoutAuditOperation::Update,
+            &updated_payout,
+        )
+        .await;
+        Ok(updated_payout)
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payout_by_merchant_id_payout_id(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::find_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+            .await
+            .map(Payouts::from_storage_model)
+            .map_err(|er| {
+                let new_err = payout_lookup_error(merchant_id, payout_id, er.current_context());
+                er.change_context(new_err)
+            })
+            .and_then(|payout| admit_payout(self.payout_test_mode, payout))
+    }
+
+    #[instrument(skip_all)]
+    async fn find_optional_payout_by_merchant_id_payout_id(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::find_optional_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+            .await
+            .map(|x| {
+                self.payout_test_mode
+                    .admit(x.map(Payouts::from_storage_model))
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn clone_payout_as_new(
+        &self,
+        merchant_id: &MerchantId,
+        source_payout_id: &PayoutId,
+        new_payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let source_payout = self
+            .find_payout_by_merchant_id_payout_id(merchant_id, source_payout_id, storage_scheme)
+            .await?;
+        self.insert_payout(
+            source_payout.to_new_payout_for_retry(new_payout_id.to_string()),
+            storage_scheme,
+        )
+        .await
+    }
+
+    #[instrument(skip_all)]
+    async fn update_payout_metadata_path(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        json_path: &[String],
+        value: pii::SecretSerdeValue,
+        create_missing: bool,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let conn = pg_connection_write(self).await?;
+        let updated_payout = DieselPayouts::update_metadata_path_by_merchant_id_payout_id(
+            &conn,
+            merchant_id,
+            payout_id,
+            json_path,
+            value,
+            create_missing,
+        )
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+        .map(Payouts::from_storage_model)?;
+
+        bump_payout_list_version(self, merchant_id).await;
+        record_payout_audit_event(
+            self.payout_audit_sink.as_ref(),
+            PayoutAuditOperation::Update,
+            &updated_payout,
+        )
+        .await;
+        Ok(updated_payout)
+    }
+
+    #[instrument(skip_all)]
+    async fn check_payout_balance(
+        &self,
+        merchant_id: &MerchantId,
+        amount: i64,
+        currency: Currency,
+    ) -> error_stack::Result<(), StorageError> {
+        let available = get_payout_balance(self, merchant_id, currency).await?;
+        ensure_sufficient_balance(available, amount)
+    }
+
+    #[instrument(skip_all)]
+    async fn distinct_payout_currencies(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Currency>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::get_distinct_destination_currencies_by_merchant_id(&conn, merchant_id)
+            .await
+            .map(sort_distinct_currencies)
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn list_payouts_by_source(
+        &self,
+        merchant_id: &MerchantId,
+        created_by: PayoutCreationSource,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_created_by(&conn, merchant_id, created_by)
+            .await
+            .map(|payouts| {
+                self.payout_test_mode.retain(
+                    payouts
+                        .into_iter()
+                        .map(Payouts::from_storage_model)
+                        .collect(),
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn list_payouts_by_merchant_id_statuses(
+        &self,
+        merchant_id: &MerchantId,
+        statuses: Vec<PayoutStatus>,
+        limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_statuses(&conn, merchant_id, statuses, limit)
+            .await
+            .map(|payouts| {
+                self.payout_test_mode.retain(
+                    payouts
+                        .into_iter()
+                        .map(Payouts::from_storage_model)
+                        .collect(),
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn list_high_risk_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        min_score: i16,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_high_risk_by_merchant_id(&conn, merchant_id, min_score)
+            .await
+            .map(|payouts| {
+                self.payout_test_mode.retain(
+                    payouts
+                        .into_iter()
+                        .map(Payouts::from_storage_model)
+                        .collect(),
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn find_latest_payout_by_customer_id(
+        &self,
+        merchant_id: &MerchantId,
+        customer_id: &CustomerId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::find_latest_by_merchant_id_customer_id(&conn, merchant_id, customer_id)
+            .await
+            .map(|payouts| {
+                Payouts::latest(
+                    self.payout_test_mode.retain(
+                        payouts
+                            .into_iter()
+                            .map(Payouts::from_storage_model)
+                            .collect(),
+                    ),
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn find_optional_payouts_batch(
+        &self,
+        merchant_id: &MerchantId,
+        payout_ids: &[PayoutId],
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<HashMap<PayoutId, Option<Payouts>>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        let raw_payout_ids = payout_ids
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        DieselPayouts::filter_by_merchant_id_payout_ids(&conn, merchant_id, &raw_payout_ids)
+            .await
+            .map(|payouts| {
+                payouts_by_requested_id(
+                    payout_ids,
+                    payouts
+                        .into_iter()
+                        .map(Payouts::from_storage_model)
+                        .filter(|payout| self.payout_test_mode.admits(payout.test_mode)),
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payouts_by_network_token_ref(
+        &self,
+        merchant_id: &MerchantId,
+        network_token_ref: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_network_token_ref(
+            &conn,
+            merchant_id,
+            network_token_ref,
+        )
+        .await
+        .map(|payouts| {
+            self.payout_test_mode.retain(
+                payouts
+                    .into_iter()
+                    .map(Payouts::from_storage_model)
+                    .collect(),
+            )
+        })
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn claim_payout_for_retry(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        max_attempts: i16,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let conn = pg_connection_write(self).await?;
+        let claimed_payout = DieselPayouts::claim_for_retry(
+            &conn,
+            merchant_id,
+            payout_id,
+            max_attempts,
+            common_utils::date_time::now(),
+            PAYOUT_RETRY_CLAIM_LEASE,
+        )
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })?
+        .map(Payouts::from_storage_model);
+
+        if let Some(payout) = &claimed_payout {
+            bump_payout_list_version(self, merchant_id).await;
+            record_payout_audit_event(
+                self.payout_audit_sink.as_ref(),
+                PayoutAuditOperation::Update,
+                payout,
+            )
+            .await;
+        }
+        Ok(claimed_payout)
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payouts_needing_retry(
+        &self,
+        now: PrimitiveDateTime,
+        max_attempts: i16,
+        limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_needing_retry(
+            &conn,
+            max_attempts,
+            now,
+            PAYOUT_RETRY_CLAIM_LEASE,
+            limit,
+        )
+        .await
+        .map(|payouts| {
+            self.payout_test_mode.retain(
+                payouts
+                    .into_iter()
+                    .map(Payouts::from_storage_model)
+                    .collect(),
+            )
+        })
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn list_payouts_by_status_before(
+        &self,
+        status: PayoutStatus,
+        before: PrimitiveDateTime,
+        after_cursor: Option<&PayoutPageCursor>,
+        limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_status_modified_before(
+            &conn,
+            status,
+            before,
+            after_cursor.map(|cursor| (cursor.last_modified_at, cursor.payout_id.as_str())),
+            limit,
+        )
+        .await
+        .map(|payouts| {
+            self.payout_test_mode.retain(
+                payouts
+                    .into_iter()
+                    .map(Payouts::from_storage_model)
+                    .collect(),
+            )
+        })
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn count_payouts_by_status(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<HashMap<PayoutStatus, i64>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::get_status_counts_by_merchant_id(&conn, merchant_id)
+            .await
+            .map(sum_status_counts)
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn sum_payout_amounts_by_merchant_id(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<i128, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        let sum = DieselPayouts::sum_amount_by_merchant_id(&conn, merchant_id)
+            .await
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })?;
+        total_payout_amount(sum)
+    }
+
+    #[instrument(skip_all)]
+    async fn average_payout_processing_seconds(
+        &self,
+        merchant_id: &MerchantId,
+        filters: &PayoutProcessingTimeFilters,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<f64>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::average_processing_seconds_by_merchant_id(
+            &conn,
+            merchant_id,
+            filters.profile_id.clone(),
+            filters.connectors.clone(),
+            filters.currencies.clone(),
+            filters.fulfilled_after,
+            filters.fulfilled_before,
+        )
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payout_by_connector_idempotency_key(
+        &self,
+        merchant_id: &MerchantId,
+        connector_idempotency_key: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::find_optional_by_merchant_id_connector_idempotency_key(
+            &conn,
+            merchant_id,
+            connector_idempotency_key,
+        )
+        .await
+        .map(|payout| {
+            self.payout_test_mode
+                .admit(payout.map(Payouts::from_storage_model))
+        })
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payout_by_display_seq(
+        &self,
+        merchant_id: &MerchantId,
+        display_seq: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::find_by_merchant_id_display_seq(&conn, merchant_id, display_seq)
+            .await
+            .map(Payouts::from_storage_model)
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+            .and_then(|payout| admit_payout(self.payout_test_mode, payout))
+    }
+
+    #[instrument(skip_all)]
+    async fn diff_payouts_since(
+        &self,
+        merchant_id: &MerchantId,
+        since: PrimitiveDateTime,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_modified_since(&conn, merchant_id, since)
+            .await
+            .map(|payouts| {
+                Payouts::modified_since(
+                    self.payout_test_mode.retain(
+                        payouts
+                            .into_iter()
+                            .map(Payouts::from_storage_model)
+                            .collect(),
+                    ),
+                    since,
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn list_payouts_modified_by(
+        &self,
+        merchant_id: &MerchantId,
+        actor: &str,
+        since: PrimitiveDateTime,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_modified_by_since(&conn, merchant_id, actor, since)
+            .await
+            .map(|payouts| {
+                self.payout_test_mode.retain(
+                    payouts
+                        .into_iter()
+                        .map(Payouts::from_storage_model)
+                        .collect(),
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn find_stale_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        no_change_for: time::Duration,
+        statuses: Vec<PayoutStatus>,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let statuses = stale_payout_statuses(statuses);
+        if statuses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let unchanged_since = common_utils::date_time::now() - no_change_for;
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_statuses_unchanged_since(
+            &conn,
+            merchant_id,
+            statuses,
+            unchanged_since,
+        )
+        .await
+        .map(|payouts| {
+            self.payout_test_mode.retain(
+                payouts
+                    .into_iter()
+                    .map(Payouts::from_storage_model)
+                    .collect(),
+            )
+        })
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payout_with_attempts(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, Vec<PayoutAttempt>), StorageError> {
+        futures::try_join!(
+            self.find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme),
+            self.list_payout_attempts(merchant_id, payout_id, storage_scheme),
+        )
+    }
+
+    #[instrument(skip_all)]
+    async fn recompute_payout_attempt_count(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<i16, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let attempts = count_payout_attempts(self, merchant_id, payout_id).await?;
+        recompute_payout_attempt_count_in_store(
+            self,
+            merchant_id,
+            payout_id,
+            attempts,
+            storage_scheme,
+        )
+        .await
+    }
+
+    #[instrument(skip_all)]
+    async fn activate_due_payouts(
+        &self,
+        now: PrimitiveDateTime,
+        limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutId>, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        activate_due_payouts_in_database(self, now, limit)
+            .await
+            .map(|activated| {
+                activated
+                    .into_iter()
+                    .map(|payout| PayoutId::from(payout.payout_id))
+                    .collect()
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn confirm_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        confirm_payout_in_store(self, merchant_id, payout_id, storage_scheme).await
+    }
+
+    #[instrument(skip_all)]
+    async fn reroute_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        new_connector: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        reroute_payout_in_store(self, merchant_id, payout_id, new_connector, storage_scheme).await
+    }
+
+    #[instrument(skip_all)]
+    async fn update_payout_if_status(
+        &self,
+        this: &Payouts,
+        payout: PayoutsUpdate,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Option<Payouts>, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        payout.validate_transition(this)?;
+        validate_payout_return_url_allowed(self, &this.merchant_id, payout.return_url()).await?;
+
+        let conn = pg_connection_write(self).await?;
+        let origin_payout = this.clone().to_storage_model();
+        let payout_update = payout.to_storage_model();
+        let updated_payout = match this.amount_adjustment(&payout) {
+            None => origin_payout.update_if_status(&conn, payout_update).await,
+            Some(adjustment) => conn
+                .transaction_async(|conn| async move {
+                    let updated_payout = origin_payout
+                        .update_if_status(&conn, payout_update)
+                        .await
+                        .map_err(|er| *er.current_context())?;
+                    // Nothing changed if the status of the payout moved on meanwhile
+                    if updated_payout.is_some() {
+                        adjustment
+                            .to_storage_model()
+                            .insert(&conn)
+                            .await
+                            .map_err(|er| *er.current_context())?;
+                    }
+                    Ok::<_, diesel_models::errors::DatabaseError>(updated_payout)
+                })
+                .await
+                .into_report(),
+        }
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })?
+        .map(Payouts::from_storage_model);
+
+        if let Some(updated_payout) = &updated_payout {
+            bump_payout_list_version(self, &updated_payout.merchant_id).await;
+            record_payout_audit_event(
+                self.payout_audit_sink.as_ref(),
+                PayoutAuditOperation::Update,
+                updated_payout,
+            )
+            .await;
+        }
+        Ok(updated_payout)
+    }
+
+    #[instrument(skip_all)]
+    async fn insert_payout_with_persistence_info(
+        &self,
+        new: PayoutsNew,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, PersistenceInfo), StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let payout = self.insert_payout(new, storage_scheme).await?;
+        Ok((
+            payout,
+            PersistenceInfo::for_insert(MerchantStorageScheme::PostgresOnly, false),
+        ))
+    }
+
+    #[instrument(skip_all)]
+    async fn soft_delete_all_payouts_for_merchant(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<usize, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        soft_delete_payouts_in_database(self, merchant_id)
+            .await
+            .map(|deleted| deleted.len())
+    }
+
+    #[instrument(skip_all)]
+    async fn delete_payout(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<bool, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let conn = pg_connection_write(self).await?;
+        let deleted = DieselPayouts::delete_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+            .await
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })?;
+        if deleted {
+            bump_payout_list_version(self, merchant_id).await;
+        }
+        Ok(deleted)
+    }
+
+    #[instrument(skip_all)]
+    async fn backfill_payout_profile_ids(
+        &self,
+        merchant_id: &MerchantId,
+        default_profile_id: &str,
+        batch_size: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<usize, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        validate_profile_belongs_to_merchant(self, merchant_id, default_profile_id).await?;
+        backfill_profile_ids_in_database(self, merchant_id, default_profile_id, batch_size)
+            .await
+            .map(|backfilled| backfilled.len())
+    }
+
+    #[instrument(skip_all)]
+    async fn import_payout(
+        &self,
+        new: PayoutsNew,
+        preserve_timestamps: bool,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let new = prepare_payout_import(self.payout_imports_enabled, new, preserve_timestamps)?;
+        self.insert_payout(new, storage_scheme).await
+    }
+
+    #[instrument(skip_all)]
+    async fn update_payouts_status_batch(
+        &self,
+        merchant_id: &MerchantId,
+        transitions: Vec<(PayoutId, PayoutStatus)>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<(PayoutId, error_stack::Result<(), StorageError>)>, StorageError>
+    {
+        self.payout_read_only.ensure_writable()?;
+        update_payouts_status_batch_in_store(self, merchant_id, transitions, storage_scheme).await
+    }
+
+    #[instrument(skip_all)]
+    async fn record_webhook_event_once(
+        &self,
+        connector: &str,
+        event_id: &str,
+    ) -> error_stack::Result<bool, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let conn = pg_connection_write(self).await?;
+        PayoutWebhookEventNew {
+            connector: connector.to_owned(),
+            event_id: event_id.to_owned(),
+            created_at: common_utils::date_time::now(),
+        }
+        .insert_if_absent(&conn)
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn forget_webhook_event(
+        &self,
+        connector: &str,
+        event_id: &str,
+    ) -> error_stack::Result<(), StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let conn = pg_connection_write(self).await?;
+        PayoutWebhookEvent::delete_by_connector_event_id(&conn, connector, event_id)
+            .await
+            .map(|_| ())
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn get_payout_daily_count(
+        &self,
+        merchant_id: &MerchantId,
+        date: time::Date,
+    ) -> error_stack::Result<i64, StorageError> {
+        get_payout_daily_count(self, merchant_id, date).await
+    }
+
+    #[instrument(skip_all)]
+    async fn cancel_payout_with_reversal(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        reason: String,
+        reversal: Option<PayoutAttemptNew>,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, Option<PayoutAttempt>), StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let payout = self
+            .find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+            .await?;
+        let cancel_update = payout.cancel_update(reason, reversal.as_ref())?;
+
+        let conn = pg_connection_write(self).await?;
+        let origin_payout = payout.clone().to_storage_model();
+        let payout_update = cancel_update.to_storage_model();
+        let reversal = reversal.map(|reversal| reversal.to_storage_model());
+        let (cancelled, reversal) = conn
+            .transaction_async(|conn| async move {
+                let cancelled = origin_payout
+                    .update_if_sequence_matches(&conn, payout_update)
+                    .await
+                    .map_err(|er| *er.current_context())?;
+                // A failed insert of the reversal rolls the cancellation back
+                let reversal = match reversal {
+                    Some(reversal) => Some(
+                        reversal
+                            .insert(&conn)
+                            .await
+                            .map_err(|er| *er.current_context())?,
+                    ),
+                    None => None,
+                };
+                Ok::<_, diesel_models::errors::DatabaseError>((cancelled, reversal))
+            })
+            .await
+            .into_report()
+            .map_err(|er| {
+                let new_err = match er.current_context() {
+                    diesel_models::errors::DatabaseError::NotFound => {
+                        StorageError::StaleUpdate { entity: "payouts" }
+                    }
+                    error => diesel_error_to_data_error(error),
+                };
+                er.change_context(new_err)
+            })
+            .attach_printable_lazy(|| format!("failed to cancel payout {}", payout.payout_id))?;
+        let cancelled = Payouts::from_storage_model(cancelled);
+        let reversal = reversal.map(PayoutAttempt::from_storage_model);
+
+        bump_payout_list_version(self, &cancelled.merchant_id).await;
+        record_payout_audit_event(
+            self.payout_audit_sink.as_ref(),
+            PayoutAuditOperation::Update,
+            &cancelled,
+        )
+        .await;
+        Ok((cancelled, reversal))
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payout_with_load_options(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        load_options: PayoutLoadOptions,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Payouts, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::find_by_merchant_id_payout_id_skipping(
+            &conn,
+            merchant_id,
+            payout_id,
+            &load_options.skipped_fields(),
+        )
+        .await
+        .map(Payouts::from_storage_model)
+        .map_err(|er| {
+            let new_err = payout_lookup_error(merchant_id, payout_id, er.current_context());
+            er.change_context(new_err)
+        })
+        .and_then(|payout| admit_payout(self.payout_test_mode, payout))
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payouts_with_inconsistent_timestamps(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_inconsistent_timestamps(&conn, merchant_id)
+            .await
+            .map(|payouts| {
+                self.payout_test_mode.retain(
+                    payouts
+                        .into_iter()
+                        .map(Payouts::from_storage_model)
+                        .collect(),
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn repair_payout_timestamps(
+        &self,
+        merchant_id: &MerchantId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<usize, StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        repair_timestamps_in_database(self, merchant_id)
+            .await
+            .map(|repaired| repaired.len())
+    }
+
+    #[instrument(skip_all)]
+    async fn get_payout_status(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<PayoutStatus, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        let (status, test_mode) =
+            DieselPayouts::find_status_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+                .await
+                .map_err(|er| {
+                    let new_err = payout_lookup_error(merchant_id, payout_id, er.current_context());
+                    er.change_context(new_err)
+                })?;
+        if self.payout_test_mode.admits(test_mode) {
+            Ok(status)
+        } else {
+            Err(payout_not_found(merchant_id, payout_id)).into_report()
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payouts_by_source_payment_id(
+        &self,
+        merchant_id: &MerchantId,
+        source_payment_id: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_source_payment_id(
+            &conn,
+            merchant_id,
+            source_payment_id,
+        )
+        .await
+        .map(|payouts| {
+            self.payout_test_mode.retain(
+                payouts
+                    .into_iter()
+                    .map(Payouts::from_storage_model)
+                    .collect(),
+            )
+        })
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn insert_split_payouts(
+        &self,
+        parent: PayoutsNew,
+        splits: Vec<PayoutsNew>,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<(Payouts, Vec<Payouts>), StorageError> {
+        self.payout_read_only.ensure_writable()?;
+        let (parent, splits) =
+            prepare_split_payouts(self, self, parent, splits, |merchant_id| async move {
+                next_display_seq_in_database(self, &merchant_id).await
+            })
+            .await?;
+        insert_split_payouts_in_database(self, parent, splits).await
+    }
+
+    #[instrument(skip_all)]
+    async fn list_child_payouts(
+        &self,
+        merchant_id: &MerchantId,
+        parent_payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_parent_payout_id(&conn, merchant_id, parent_payout_id)
+            .await
+            .map(|payouts| {
+                self.payout_test_mode.retain(
+                    payouts
+                        .into_iter()
+                        .map(Payouts::from_storage_model)
+                        .collect(),
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn list_amount_adjustments(
+        &self,
+        merchant_id: &MerchantId,
+        payout_id: &PayoutId,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutAmountAdjustment>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayoutAmountAdjustment::filter_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+            .await
+            .map(|adjustments| {
+                adjustments
+                    .into_iter()
+                    .map(adjustment_from_stored)
+                    .collect()
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn find_payouts_by_metadata_key(
+        &self,
+        merchant_id: &MerchantId,
+        key: &str,
+        value: &serde_json::Value,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<Payouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::filter_by_merchant_id_metadata_key(&conn, merchant_id, key, value)
+            .await
+            .map(|payouts| {
+                self.payout_test_mode.retain(
+                    payouts
+                        .into_iter()
+                        .map(Payouts::from_storage_model)
+                        .collect(),
+                )
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+}
+
+/// Activates the scheduled payouts due at `now` in the database, see
+/// [`PayoutsInterface::activate_due_payouts`].
+async fn activate_due_payouts_in_database<T: DatabaseStore>(
+    store: &crate::RouterStore<T>,
+    now: PrimitiveDateTime,
+    limit: i64,
+) -> error_stack::Result<Vec<Payouts>, StorageError> {
+    let conn = pg_connection_write(store).await?;
+    let activated = DieselPayouts::activate_due(&conn, now, limit)
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })?
+        .into_iter()
+        .map(Payouts::from_storage_model)
+        .collect::<Vec<_>>();
+
+    for payout in &activated {
+        bump_payout_list_version(store, &payout.merchant_id).await;
+        record_payout_audit_event(
+            store.payout_audit_sink.as_ref(),
+            PayoutAuditOperation::Update,
+            payout,
+        )
+        .await;
+    }
+    Ok(activated)
+}
+
+/// Soft deletes the payouts of the merchant in the database, returning the payouts deleted by
+/// this call, see [`PayoutsInterface::soft_delete_all_payouts_for_merchant`].
+async fn soft_delete_payouts_in_database<T: DatabaseStore>(
+    store: &crate::RouterStore<T>,
+    merchant_id: &MerchantId,
+) -> error_stack::Result<Vec<Payouts>, StorageError> {
+    let conn = pg_connection_write(store).await?;
+    let deleted = DieselPayouts::soft_delete_by_merchant_id(&conn, merchant_id)
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })?
+        .into_iter()
+        .map(Payouts::from_storage_model)
+        .collect::<Vec<_>>();
+
+    if !deleted.is_empty() {
+        bump_payout_list_version(store, merchant_id).await;
+    }
+    for payout in &deleted {
+        record_payout_audit_event(
+            store.payout_audit_sink.as_ref(),
+            PayoutAuditOperation::Update,
+            payout,
+        )
+        .await;
+    }
+    Ok(deleted)
+}
+
+/// Mirrors the parent payout and its splits inserted in Postgres to the KV store, see
+/// [`write_and_mirror`].
+async fn mirror_split_payouts<T: DatabaseStore>(
+    store: &KVRouterStore<T>,
+    parent: Payouts,
+    splits: Vec<Payouts>,
+) -> error_stack::Result<(Payouts, Vec<Payouts>), StorageError> {
+    let parent = write_and_mirror(store, std::future::ready(Ok(parent))).await?;
+    let mut mirrored_splits = Vec::with_capacity(splits.len());
+    for split in splits {
+        mirrored_splits.push(write_and_mirror(store, std::future::ready(Ok(split))).await?);
+    }
+    Ok((parent, mirrored_splits))
+}
+
+/// Deletes the payout held in KV and pushes its deletion to the drainer stream, after the writes
+/// of the payout still queued there, returning whether the payout was in KV or in the database.
+///
+/// The database row is deleted by the drainer, a payout drained to the database is reported as
+/// deleted again by a repeated call until then.
+async fn delete_payout_in_kv<T: DatabaseStore>(
+    store: &KVRouterStore<T>,
+    merchant_id: &MerchantId,
+    payout_id: &PayoutId,
+) -> error_stack::Result<bool, StorageError> {
+    let conn = pg_connection_read(store).await?;
+    let in_database =
+        DieselPayouts::find_optional_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+            .await
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })?
+            .is_some();
+
+    let payout_key = store.payout_kv_key(merchant_id, payout_id);
+    let redis_entry = kv::TypedSql {
+        op: kv::DBOperation::Delete {
+            deletable: kv::Deletable::Payouts(kv::PayoutsDeleteMems {
+                merchant_id: merchant_id.to_string(),
+                payout_id: payout_id.to_string(),
+            }),
+        },
+    };
+    let reply = kv_wrapper::<DieselPayouts, _, _>(
+        store,
+        KvOperation::<DieselPayouts>::Del(redis_entry),
+        &payout_key.key,
+    )
+    .await
+    .map_err(|err| err.to_redis_failed_response(&payout_key.key))?
+    .try_into_del()
+    .change_context(StorageError::KVError)?;
+    // Payouts written before their keys were hashed are still held under their legacy key
+    let legacy_copy_deleted = delete_kv_copies(store, merchant_id, payout_id).await?;
+    let in_kv = matches!(reply, DelReply::KeyDeleted) || legacy_copy_deleted;
+
+    let deleted = in_kv || in_database;
+    if deleted {
+        bump_payout_list_version(store, merchant_id).await;
+    }
+    Ok(deleted)
+}
+
+/// Deletes every key the payout may be held under in KV, see [`PayoutKvKey::read_candidates`],
+/// without pushing anything to the drainer stream. Returns whether any of them was there.
+async fn delete_kv_copies<T: DatabaseStore>(
+    store: &KVRouterStore<T>,
+    merchant_id: &str,
+    payout_id: &str,
+) -> error_stack::Result<bool, StorageError> {
+    let redis_conn = store
+        .get_redis_conn()
+        .change_context(StorageError::KVError)?;
+    let mut deleted = false;
+    for key in store.payout_kv_read_keys(merchant_id, payout_id) {
+        let reply = redis_conn
+            .delete_key(&key.key)
+            .await
+            .change_context(StorageError::KVError)?;
+        deleted |= matches!(reply, DelReply::KeyDeleted);
+        store.record_kv_write(&key.key);
+    }
+    Ok(deleted)
+}
+
+/// Assigns `profile_id` to the payouts of the merchant without a profile in the database,
+/// `batch_size` payouts per transaction, returning the payouts updated by this call, see
+/// [`PayoutsInterface::backfill_payout_profile_ids`].
+async fn backfill_profile_ids_in_database<T: DatabaseStore>(
+    store: &crate::RouterStore<T>,
+    merchant_id: &MerchantId,
+    profile_id: &str,
+    batch_size: i64,
+) -> error_stack::Result<Vec<Payouts>, StorageError> {
+    let conn = pg_connection_write(store).await?;
+    let batch_size = batch_size.max(1);
+    let mut backfilled = Vec::new();
+    loop {
+        let batch = conn
+            .transaction_async(|conn| async move {
+                let pending = DieselPayouts::filter_by_merchant_id_without_profile(
+                    &conn,
+                    merchant_id,
+                    batch_size,
+                )
+                .await
+                .map_err(|er| *er.current_context())?;
+                if pending.is_empty() {
+                    return Ok(None);
+                }
+                let payout_ids = pending.into_iter().map(|payout| payout.payout_id).collect();
+                DieselPayouts::backfill_profile_id(&conn, merchant_id, payout_ids, profile_id)
+                    .await
+                    .map(Some)
+                    .map_err(|er| *er.current_context())
+            })
+            .await
+            .into_report()
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })?;
+        // Every payout of a batch has a profile once it commits, so the next batch moves on
+        let Some(batch) = batch else {
+            break;
+        };
+        backfilled.extend(batch.into_iter().map(Payouts::from_storage_model));
+    }
+
+    if !backfilled.is_empty() {
+        bump_payout_list_version(store, merchant_id).await;
+    }
+    for payout in &backfilled {
+        record_payout_audit_event(
+            store.payout_audit_sink.as_ref(),
+            PayoutAuditOperation::Update,
+            payout,
+        )
+        .await;
+    }
+    Ok(backfilled)
+}
+
+/// Repairs the timestamps of the payouts of the merchant in the database, see
+/// [`PayoutsInterface::repair_payout_timestamps`], returning the payouts repaired.
+async fn repair_timestamps_in_database<T: DatabaseStore>(
+    store: &crate::RouterStore<T>,
+    merchant_id: &MerchantId,
+) -> error_stack::Result<Vec<Payouts>, StorageError> {
+    let conn = pg_connection_write(store).await?;
+    let repaired = DieselPayouts::repair_timestamps_by_merchant_id(&conn, merchant_id)
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })?
+        .into_iter()
+        .map(Payouts::from_storage_model)
+        .collect::<Vec<_>>();
+
+    if !repaired.is_empty() {
+        bump_payout_list_version(store, merchant_id).await;
+    }
+    for payout in &repaired {
+        record_payout_audit_event(
+            store.payout_audit_sink.as_ref(),
+            PayoutAuditOperation::Update,
+            payout,
+        )
+        .await;
+    }
+    Ok(repaired)
+}
+
+/// Confirms the payout through `store`, see [`PayoutsInterface::confirm_payout`].
+async fn confirm_payout_in_store<S>(
+    store: &S,
+    merchant_id: &MerchantId,
+    payout_id: &PayoutId,
+    storage_scheme: MerchantStorageScheme,
+) -> error_stack::Result<Payouts, StorageError>
+where
+    S: PayoutsInterface + Sync + ?Sized,
+{
+    let payout = store
+        .find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+        .await?;
+    let Some(confirm_update) = payout.confirm_update()? else {
+        return Ok(payout);
+    };
+
+    match store
+        .update_payout(&payout, confirm_update, storage_scheme)
+        .await
+    {
+        // A concurrent confirmation of the payout won the race, which is as good as this one
+        Err(error) if matches!(error.current_context(), StorageError::StaleUpdate { .. }) => {
+            let current = store
+                .find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+                .await?;
+            match current.confirm_update()? {
+                None => Ok(current),
+                Some(_) => Err(error),
+            }
+        }
+        result => result,
+    }
+}
+
+/// Cancels the payout held in KV, see [`PayoutsInterface::cancel_payout_with_reversal`].
+///
+/// The reversal is written to KV before the payout is cancelled, and removed again if the
+/// cancellation fails, for instance because the payout was updated concurrently. Its insert is
+/// only pushed to the drainer once the payout is cancelled, to the stream of the payout.
+async fn cancel_kv_payout_with_reversal<T: DatabaseStore>(
+    store: &KVRouterStore<T>,
+    merchant_id: &MerchantId,
+    payout_id: &PayoutId,
+    reason: String,
+    reversal: Option<PayoutAttemptNew>,
+    storage_scheme: MerchantStorageScheme,
+) -> error_stack::Result<(Payouts, Option<PayoutAttempt>), StorageError> {
+    let payout = store
+        .find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+        .await?;
+    let cancel_update = payout.cancel_update(reason, reversal.as_ref())?;
+    let reserved = match reversal {
+        Some(reversal) => Some(reserve_kv_payout_attempt(store, reversal, storage_scheme).await?),
+        None => None,
+    };
+
+    let cancelled = match store
+        .update_payout(&payout, cancel_update, storage_scheme)
+        .await
+    {
+        Ok(cancelled) => cancelled,
+        Err(error) => {
+            if let Some(reserved) = reserved {
+                reserved.release(store).await;
+            }
+            return Err(error);
+        }
+    };
+    let Some(reserved) = reserved else {
+        return Ok((cancelled, None));
+    };
+
+    let payout_key = store.payout_kv_key(merchant_id, payout_id);
+    store
+        .push_to_drainer_stream::<DieselPayouts>(
+            reserved.redis_entry,
+            PartitionKey::MerchantIdPaymentIdCombination {
+                combination: &payout_key.key,
+            },
+        )
+        .await
+        .change_context(StorageError::KVError)
+        .attach_printable_lazy(|| {
+            format!("failed to record the reversal attempt of payout {payout_id}")
+        })?;
+    Ok((cancelled, Some(reserved.attempt)))
+}
+
+/// Activates up to `limit` of `payouts` which are due at `now` through `store`, see
+/// [`PayoutsInterface::activate_due_payouts`]. Returns the ids of the payouts activated.
+///
+/// A payout whose activation fails, as when it was updated since it was read, is logged and
+/// skipped rather than failing the payouts activated before it, like a payout locked by another
+/// activation is skipped in the database.
+pub(crate) async fn activate_payouts_in_store<S>(
+    store: &S,
+    payouts: Vec<Payouts>,
+    now: PrimitiveDateTime,
+    limit: usize,
+    storage_scheme: MerchantStorageScheme,
+) -> Vec<PayoutId>
+where
+    S: PayoutsInterface + Sync + ?Sized,
+{
+    let mut activated_ids = Vec::new();
+    for payout in Payouts::due_for_activation(payouts, now, limit) {
+        match store
+            .update_payout(&payout, PayoutsUpdate::ActivateUpdate, storage_scheme)
+            .await
+        {
+            Ok(activated) => activated_ids.push(PayoutId::from(activated.payout_id)),
+            Err(error) => logger::warn!(
+                ?error,
+                payout_id = %payout.payout_id,
+                "Skipped a due payout which could not be activated"
+            ),
+        }
+    }
+    activated_ids
+}
+
+/// Claims the payout for a retry through `store`, see
+/// [`PayoutsInterface::claim_payout_for_retry`].
+///
+/// The payout is read from `store` and its attempt count incremented through `update_payout`,
+/// whose check of the sequence number lets a single one of concurrent claims through. The other
+/// claims, like those of a payout which is not claimable at `now`, return `None`.
+pub(crate) async fn claim_payout_for_retry_in_store<S>(
+    store: &S,
+    merchant_id: &MerchantId,
+    payout_id: &PayoutId,
+    max_attempts: i16,
+    now: PrimitiveDateTime,
+    storage_scheme: MerchantStorageScheme,
+) -> error_stack::Result<Option<Payouts>, StorageError>
+where
+    S: PayoutsInterface + Sync + ?Sized,
+{
+    let Some(payout) = store
+        .find_optional_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+        .await?
+    else {
+        return Ok(None);
+    };
+    if !payout.is_claimable_for_retry(now, max_attempts) {
+        return Ok(None);
+    }
+    let claim = PayoutsUpdate::AttemptCountUpdate {
+        attempt_count: payout.attempt_count.saturating_add(1),
+    };
+    match store.update_payout(&payout, claim, storage_scheme).await {
+        Ok(claimed) => Ok(Some(claimed)),
+        Err(error) if matches!(error.current_context(), StorageError::StaleUpdate { .. }) => {
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Sets the attempt count of the payout to `attempts` through `store`, see
+/// [`PayoutsInterface::recompute_payout_attempt_count`].
+///
+/// A concurrent update of the payout fails the repair with `StaleUpdate`, it can then be run
+/// again.
+async fn recompute_payout_attempt_count_in_store<S>(
+    store: &S,
+    merchant_id: &MerchantId,
+    payout_id: &PayoutId,
+    attempts: i64,
+    storage_scheme: MerchantStorageScheme,
+) -> error_stack::Result<i16, StorageError>
+where
+    S: PayoutsInterface + Sync + ?Sized,
+{
+    let payout = store
+        .find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+        .await?;
+    let Some(repair) = payout.attempt_count_repair(attempts)? else {
+        return Ok(payout.attempt_count);
+    };
+    logger::info!(
+        payout_id = %payout.payout_id,
+        recorded = payout.attempt_count,
+        attempts,
+        "Repairing the attempt count of a payout"
+    );
+    store
+        .update_payout(&payout, repair, storage_scheme)
+        .await
+        .map(|payout| payout.attempt_count)
+}
+
+/// Reroutes the payout through `store`, see [`PayoutsInterface::reroute_payout`].
+///
+/// Unlike a confirmation, losing a race against a concurrent update is an error: the new
+/// connector was picked for a payout which is no longer current.
+async fn reroute_payout_in_store<S>(
+    store: &S,
+    merchant_id: &MerchantId,
+    payout_id: &PayoutId,
+    new_connector: &str,
+    storage_scheme: MerchantStorageScheme,
+) -> error_stack::Result<Payouts, StorageError>
+where
+    S: PayoutsInterface + Sync + ?Sized,
+{
+    let payout = store
+        .find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+        .await?;
+    let reroute_update = payout.reroute_update(new_connector.to_owned());
+    store
+        .update_payout(&payout, reroute_update, storage_scheme)
+        .await
+}
+
+/// Moves the payouts through `store`, see [`PayoutsInterface::update_payouts_status_batch`].
+async fn update_payouts_status_batch_in_store<S>(
+    store: &S,
+    merchant_id: &MerchantId,
+    transitions: Vec<(PayoutId, PayoutStatus)>,
+    storage_scheme: MerchantStorageScheme,
+) -> error_stack::Result<Vec<(PayoutId, error_stack::Result<(), StorageError>)>, StorageError>
+where
+    S: PayoutsInterface + Sync + ?Sized,
+{
+    let payout_ids = transitions
+        .iter()
+        .map(|(payout_id, _)| payout_id.clone())
+        .collect::<Vec<_>>();
+    let current = store
+        .find_optional_payouts_batch(merchant_id, &payout_ids, storage_scheme)
+        .await?;
+
+    let mut outcomes = check_status_transitions(&current, &transitions);
+    for ((_, outcome), (payout_id, status)) in outcomes.iter_mut().zip(transitions) {
+        let Some(Some(payout)) = current.get(&payout_id).filter(|_| outcome.is_ok()) else {
+            continue;
+        };
+        *outcome = store
+            .update_payout(
+                payout,
+                PayoutsUpdate::StatusUpdate { status },
+                storage_scheme,
+            )
+            .await
+            .map(|_| ());
+    }
+    Ok(outcomes)
+}
+
+/// The fields of the hash of `payout`: the full payout, its status alone under
+/// [`PayoutKvKey::status_field`], its sequence number alone under
+/// [`PayoutKvKey::sequence_field`], and the checksum of the full payout under
+/// [`PayoutKvKey::checksum_field`] if `checksum` is set. They are always written together, so
+/// that the status field, the sequence number and the checksum never diverge from the full
+/// payout.
+pub fn payout_hash_fields(
+    key: &PayoutKvKey,
+    payout: &DieselPayouts,
+    checksum: Option<&PayoutChecksum>,
+) -> error_stack::Result<Vec<(String, String)>, StorageError> {
+    let value = encode_naming_failed_field(payout, &payout.payout_id)?;
+    let mut fields = vec![(
+        key.status_field(payout.test_mode),
+        payout
+            .status
+            .encode_to_string_of_json()
+            .change_context(StorageError::SerializationFailed)?,
+    )];
+    fields.push((key.sequence_field(), payout.sequence_number.to_string()));
+    if let Some(checksum) = checksum {
+        fields.push((key.checksum_field(), checksum.sign(value.as_bytes())));
+    }
+    fields.insert(0, (key.field.clone(), value));
+    Ok(fields)
+}
+
+/// Serializes `value` to JSON. On failure, the error names the field which failed to serialize,
+/// such as `metadata`, and the payout `payout_id` it belongs to.
+fn encode_naming_failed_field<S: serde::Serialize>(
+    value: &S,
+    payout_id: &str,
+) -> error_stack::Result<String, StorageError> {
+    let mut encoded = Vec::new();
+    serde_path_to_error::serialize(value, &mut serde_json::Serializer::new(&mut encoded)).map_err(
+        |error| {
+            let field = error.path().to_string();
+            error_stack::report!(StorageError::SerializationFailed).attach_printable(format!(
+                "failed to serialize the field `{field}` of payout {payout_id}: {}",
+                error.inner()
+            ))
+        },
+    )?;
+    String::from_utf8(encoded)
+        .into_report()
+        .change_context(StorageError::SerializationFailed)
+}
+
+/// Writes the fields of the hash of a payout just inserted with `HSETNX`, which sets the full
+/// payout only, see [`payout_hash_fields`]. A failure is logged only, the full payout is read
+/// while its status field is missing, and is not checked while its checksum is.
+async fn write_payout_sibling_fields<T: DatabaseStore>(
+    store: &KVRouterStore<T>,
+    key: &PayoutKvKey,
+    payout: &DieselPayouts,
+) {
+    let written = async {
+        let sibling_fields = payout_hash_fields(key, payout, store.payout_checksum.as_ref())?
+            .into_iter()
+            .filter(|(field, _)| *field != key.field)
+            .collect::<Vec<_>>();
+        store
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .set_hash_fields(&key.key, sibling_fields, Some(store.ttl_for_kv.into()))
+            .await
+            .change_context(StorageError::KVError)
+    }
+    .await;
+    if let Err(error) = written {
+        logger::warn!(?error, key = %key.key, "Failed to write the sibling fields of a payout");
+    }
+}
+
+/// Applies the status of a payout reported by the webhook event `event_id` of `connector`,
+/// returning the payout, or `None` if the event was already processed.
+///
+/// The event is recorded before the status is applied, so that concurrent deliveries of the
+/// event cannot both apply it. It is forgotten if the status cannot be applied, so that the
+/// connector's retry of the event applies it.
+pub async fn reconcile_payout_status<S>(
+    store: &S,
+    connector: &str,
+    event_id: &str,
+    merchant_id: &MerchantId,
+    payout_id: &PayoutId,
+    status: PayoutStatus,
+    storage_scheme: MerchantStorageScheme,
+) -> error_stack::Result<Option<Payouts>, StorageError>
+where
+    S: PayoutsInterface + Sync + ?Sized,
+{
+    if !store.record_webhook_event_once(connector, event_id).await? {
+        logger::info!(%connector, %event_id, "Skipped an already processed payout webhook event");
+        return Ok(None);
+    }
+
+    let applied: error_stack::Result<Payouts, StorageError> = async {
+        let payout = store
+            .find_payout_by_merchant_id_payout_id(merchant_id, payout_id, storage_scheme)
+            .await?;
+        if payout.status == status {
+            return Ok(payout);
+        }
+        store
+            .update_payout(
+                &payout,
+                PayoutsUpdate::StatusUpdate { status },
+                storage_scheme,
+            )
+            .await
+    }
+    .await;
+    if applied.is_err() {
+        if let Err(error) = store.forget_webhook_event(connector, event_id).await {
+            logger::error!(
+                ?error,
+                %connector,
+                %event_id,
+                "Failed to forget a payout webhook event which was not applied"
+            );
+        }
+    }
+    applied.map(Some)
+}
+
+/// Counts the attempts of the payout on the master, a lagging replica would have a repair set
+/// the attempt count to a stale value.
+async fn count_payout_attempts<T: DatabaseStore>(
+    store: &T,
+    merchant_id: &str,
+    payout_id: &str,
+) -> error_stack::Result<i64, StorageError> {
+    let conn = pg_connection_write(store).await?;
+    DieselPayoutAttempt::count_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+        .await
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })
+}
+
+/// Maps the failure of an update of `this` conditioned on its sequence number.
+///
+/// An update which matched no row is told apart by reading the payout again: it is missing, or
+/// it was updated since `this` was read and the update is stale.
+async fn sequenced_update_error(
+    conn: &diesel_models::PgPooledConn,
+    this: &Payouts,
+    er: error_stack::Report<diesel_models::errors::DatabaseError>,
+) -> error_stack::Report<StorageError> {
+    if !matches!(
+        er.current_context(),
+        diesel_models::errors::DatabaseError::NotFound
+    ) {
+        let new_err = diesel_error_to_data_error(er.current_context());
+        return er.change_context(new_err);
+    }
+    match DieselPayouts::find_optional_by_merchant_id_payout_id(
+        conn,
+        &this.merchant_id,
+        &this.payout_id,
+    )
+    .await
+    {
+        Ok(Some(stored)) if stored.sequence_number != this.sequence_number => er
+            .change_context(StorageError::StaleUpdate { entity: "payouts" })
+            .attach_printable(format!(
+                "payout {} was updated after sequence number {}",
+                this.payout_id, this.sequence_number
+            )),
+        Ok(_) => er.change_context(payout_not_found(&this.merchant_id, &this.payout_id)),
+        Err(read_er) => {
+            let new_err = payout_lookup_error(
+                &this.merchant_id,
+                &this.payout_id,
+                read_er.current_context(),
+            );
+            read_er.change_context(new_err)
+        }
+    }
+}
+
+/// Maps a database error raised while looking up a payout.
+///
+/// A missing payout is reported along with the key it was looked up by, in the same form as the
+/// error raised by the KV path, so that logs and API responses point at the payout in question.
+fn payout_lookup_error(
+    merchant_id: &str,
+    payout_id: &str,
+    error: &diesel_models::errors::DatabaseError,
+) -> StorageError {
+    match error {
+        diesel_models::errors::DatabaseError::NotFound => payout_not_found(merchant_id, payout_id),
+        _ => diesel_error_to_data_error(error),
+    }
+}
+
+fn payout_not_found(merchant_id: &str, payout_id: &str) -> StorageError {
+    StorageError::ValueNotFound(format!(
+        "payout does not exist for key mid_{merchant_id}_po_{payout_id}"
+    ))
+}
+
+/// Fails with the error of a missing payout if `payout` is not of the mode of `context`, so that
+/// the payouts of the other mode cannot be told apart from missing ones.
+fn admit_payout(
+    context: TestModeContext,
+    payout: Payouts,
+) -> error_stack::Result<Payouts, StorageError> {
+    if context.admits(payout.test_mode) {
+        Ok(payout)
+    } else {
+        Err(payout_not_found(&payout.merchant_id, &payout.payout_id)).into_report()
+    }
+}
+
+/// Decodes the payouts fetched from Redis for `payout_ids`, in the same order.
+///
+/// Returns the payouts found along with the ids that have to be looked up in the database, the
+/// ones missing from Redis or whose entry cannot be decoded.
+fn decode_kv_payouts_batch(
+    payout_ids: &[PayoutId],
+    values: Vec<Option<Vec<u8>>>,
+) -> (HashMap<PayoutId, Option<Payouts>>, Vec<PayoutId>) {
+    let mut payouts = HashMap::with_capacity(payout_ids.len());
+    let mut misses = Vec::new();
+    for (payout_id, value) in payout_ids.iter().zip(values) {
+        match value
+            .filter(|bytes| !bytes.is_empty())
+            .map(|bytes| serde_json::from_slice::<DieselPayouts>(&bytes))
+        {
+            Some(Ok(payout)) => {
+                payouts.insert(payout_id.clone(), Some(Payouts::from_storage_model(payout)));
+            }
+            Some(Err(error)) => {
+                logger::warn!(?error, %payout_id, "Failed to decode payout from Redis");
+                misses.push(payout_id.clone());
+            }
+            None => misses.push(payout_id.clone()),
+        }
+    }
+    (payouts, misses)
+}
+
+/// Keys `payouts` by their id, with a `None` entry for each of `payout_ids` not found.
+fn payouts_by_requested_id(
+    payout_ids: &[PayoutId],
+    payouts: impl IntoIterator<Item = Payouts>,
+) -> HashMap<PayoutId, Option<Payouts>> {
+    let mut payouts_by_id = payout_ids
+        .iter()
+        .map(|payout_id| (payout_id.clone(), None))
+        .collect::<HashMap<_, _>>();
+    for payout in payouts {
+        payouts_by_id.insert(PayoutId::from(&payout.payout_id), Some(payout));
+    }
+    payouts_by_id
+}
+
+/// Ensures that the business profile referenced by a payout belongs to the payout's merchant.
+///
+/// The owning merchant of a profile never changes, so it is cached in memory to avoid looking up
+/// the business profile on every insert.
+async fn validate_profile_belongs_to_merchant<T: DatabaseStore>(
+    store: &T,
+    merchant_id: &str,
+    profile_id: &str,
+) -> error_stack::Result<(), StorageError> {
+    let cache_key = format!("payout_profile_merchant_{profile_id}");
+    let profile_merchant_id = match ACCOUNTS_CACHE.get_val::<String>(&cache_key).await {
+        Some(profile_merchant_id) => Some(profile_merchant_id),
+        None => {
+            let conn = pg_connection_read(store).await?;
+            match BusinessProfile::find_by_profile_id(&conn, profile_id).await {
+                Ok(profile) => {
+                    ACCOUNTS_CACHE
+                        .push(cache_key, profile.merchant_id.clone())
+                        .await;
+                    Some(profile.merchant_id)
+                }
+                Err(er)
+                    if matches!(
+                        er.current_context(),
+                        diesel_models::errors::DatabaseError::NotFound
+                    ) =>
+                {
+                    None
+                }
+                Err(er) => {
+                    let new_err = diesel_error_to_data_error(er.current_context());
+                    return Err(er.change_context(new_err));
+                }
+            }
+        }
+    };
+
+    check_profile_merchant(merchant_id, profile_merchant_id.as_deref()).attach_printable_lazy(
+        || format!("Profile {profile_id} does not belong to merchant {merchant_id}"),
+    )
+}
+
+/// Key of the config holding the currencies the merchant may pay out in, as a JSON array of
+/// currency codes. Merchants without it may pay out in any currency.
+pub fn payout_allowed_currencies_key(merchant_id: &str) -> String {
+    format!("payout_allowed_currencies_{merchant_id}")
+}
+
+/// Rejects a payout in a currency the merchant was not onboarded for, see
+/// [`payout_allowed_currencies_key`].
+async fn validate_payout_currency_allowed<T: DatabaseStore>(
+    store: &T,
+    merchant_id: &str,
+    destination_currency: Currency,
+) -> error_stack::Result<(), StorageError> {
+    let allowed_currencies = find_allowed_payout_currencies(store, merchant_id).await?;
+    check_payout_currency_allowed(allowed_currencies.as_deref(), destination_currency)
+        .attach_printable_lazy(|| {
+            format!("Merchant {merchant_id} may not pay out in {destination_currency}")
+        })
+}
+
+/// The currencies the merchant may pay out in, `None` if it is not restricted. The config is
+/// cached under its key, so that updating it through the configs API invalidates the cache.
+async fn find_allowed_payout_currencies<T: DatabaseStore>(
+    store: &T,
+    merchant_id: &str,
+) -> error_stack::Result<Option<Vec<Currency>>, StorageError> {
+    let cache_key = payout_allowed_currencies_key(merchant_id);
+    if let Some(allowed_currencies) = CONFIG_CACHE
+        .get_val::<Option<Vec<Currency>>>(&cache_key)
+        .await
+    {
+        return Ok(allowed_currencies);
+    }
+
+    let conn = pg_connection_read(store).await?;
+    let allowed_currencies = match Config::find_by_key(&conn, &cache_key).await {
+        Ok(config) => Some(parse_allowed_payout_currencies(&config.config)?),
+        Err(er)
+            if matches!(
+                er.current_context(),
+                diesel_models::errors::DatabaseError::NotFound
+            ) =>
+        {
+            None
+        }
+        Err(er) => {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            return Err(er.change_context(new_err));
+        }
+    };
+    CONFIG_CACHE
+        .push(cache_key, allowed_currencies.clone())
+        .await;
+    Ok(allowed_currencies)
+}
+
+fn parse_allowed_payout_currencies(
+    config: &str,
+) -> error_stack::Result<Vec<Currency>, StorageError> {
+    serde_json::from_str(config)
+        .into_report()
+        .change_context(StorageError::DeserializationFailed)
+        .attach_printable("invalid allowed payout currencies config")
+}
+
+fn check_payout_currency_allowed(
+    allowed_currencies: Option<&[Currency]>,
+    destination_currency: Currency,
+) -> error_stack::Result<(), StorageError> {
+    match allowed_currencies {
+        Some(allowed_currencies) if !allowed_currencies.contains(&destination_currency) => {
+            Err(StorageError::InvalidValue {
+                field: "destination_currency",
+            })
+            .into_report()
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Key of the config holding the domains the return URL of a payout of the merchant may point
+/// to, as a JSON array of host names. A domain also allows its subdomains. Merchants without
+/// it, or with an empty list, may use any return URL.
+pub fn payout_return_url_domains_key(merchant_id: &str) -> String {
+    format!("payout_return_url_domains_{merchant_id}")
+}
+
+/// Rejects a return URL pointing outside the domains the merchant allowed, so that payouts
+/// cannot be used to redirect customers to arbitrary sites, see
+/// [`payout_return_url_domains_key`].
+async fn validate_payout_return_url_allowed<T: DatabaseStore>(
+    store: &T,
+    merchant_id: &str,
+    return_url: Option<&str>,
+) -> error_stack::Result<(), StorageError> {
+    let Some(return_url) = return_url else {
+        return Ok(());
+    };
+    let allowed_domains = find_allowed_return_url_domains(store, merchant_id).await?;
+    check_return_url_allowed(&allowed_domains, return_url).attach_printable_lazy(|| {
+        format!("Return URL {return_url} is not allowed for merchant {merchant_id}")
+    })
+}
+
+/// The domains the return URL of a payout of the merchant may point to, empty if it is not
+/// restricted. The config is cached under its key, like the allowed currencies.
+async fn find_allowed_return_url_domains<T: DatabaseStore>(
+    store: &T,
+    merchant_id: &str,
+) -> error_stack::Result<Vec<String>, StorageError> {
+    let cache_key = payout_return_url_domains_key(merchant_id);
+    if let Some(allowed_domains) = CONFIG_CACHE.get_val::<Vec<String>>(&cache_key).await {
+        return Ok(allowed_domains);
+    }
+
+    let conn = pg_connection_read(store).await?;
+    let allowed_domains = match Config::find_by_key(&conn, &cache_key).await {
+        Ok(config) => parse_allowed_return_url_domains(&config.config)?,
+        Err(er)
+            if matches!(
+                er.current_context(),
+                diesel_models::errors::DatabaseError::NotFound
+            ) =>
+        {
+            Vec::new()
+        }
+        Err(er) => {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            return Err(er.change_context(new_err));
+        }
+    };
+    CONFIG_CACHE.push(cache_key, allowed_domains.clone()).await;
+    Ok(allowed_domains)
+}
+
+fn parse_allowed_return_url_domains(
+    config: &str,
+) -> error_stack::Result<Vec<String>, StorageError> {
+    serde_json::from_str(config)
+        .into_report()
+        .change_context(StorageError::DeserializationFailed)
+        .attach_printable("invalid allowed payout return URL domains config")
+}
+
+/// Accepts an HTTP(S) return URL whose host is one of `allowed_domains` or a subdomain of one,
+/// ignoring case. Any return URL is accepted when `allowed_domains` is empty.
+fn check_return_url_allowed(
+    allowed_domains: &[String],
+    return_url: &str,
+) -> error_stack::Result<(), StorageError> {
+    if allowed_domains.is_empty() {
+        return Ok(());
+    }
+
+    let host = return_url
+        .parse::<http::Uri>()
+        .ok()
+        .filter(|uri| matches!(uri.scheme_str(), Some("http" | "https")))
+        .and_then(|uri| uri.host().map(str::to_ascii_lowercase));
+    let is_allowed = host.is_some_and(|host| {
+        allowed_domains.iter().any(|domain| {
+            let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+            host == domain
+                || host
+                    .strip_suffix(&domain)
+                    .is_some_and(|subdomain| subdomain.ends_with('.'))
+        })
+    });
+    if is_allowed {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidValue {
+            field: "return_url",
+        })
+        .into_report()
+    }
+}
+
+/// Key of the config holding the limits of the amount of a payout in `currency`, see
+/// [`PayoutAmountLimits`]. Payouts in a currency without it are not limited.
+pub fn payout_amount_limits_key(currency: Currency) -> String {
+    format!("payout_amount_limits_{currency}")
+}
+
+/// The smallest and largest amount of a payout in a currency, in its minor unit, as imposed by
+/// the rails paying out in it. Either bound may be left out of the config.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize)]
+pub struct PayoutAmountLimits {
+    #[serde(default)]
+    pub min: i64,
+    #[serde(default = "unlimited_payout_amount")]
+    pub max: i64,
+}
+
+fn unlimited_payout_amount() -> i64 {
+    i64::MAX
+}
+
+/// Rejects a payout whose amount is outside the limits of its destination currency, see
+/// [`payout_amount_limits_key`].
+async fn validate_payout_amount_limits<T: DatabaseStore>(
+    store: &T,
+    amount: i64,
+    destination_currency: Currency,
+) -> error_stack::Result<(), StorageError> {
+    let limits = find_payout_amount_limits(store, destination_currency).await?;
+    check_payout_amount_limits(limits.as_ref(), amount).attach_printable_lazy(|| {
+        format!("Payout of {amount} {destination_currency} is out of range")
+    })
+}
+
+/// The limits of the amount of a payout in `currency`, `None` if it is not limited. The config
+/// is cached under its key, so that updating it through the configs API invalidates the cache.
+async fn find_payout_amount_limits<T: DatabaseStore>(
+    store: &T,
+    currency: Currency,
+) -> error_stack::Result<Option<PayoutAmountLimits>, StorageError> {
+    let cache_key = payout_amount_limits_key(currency);
+    if let Some(limits) = CONFIG_CACHE
+        .get_val::<Option<PayoutAmountLimits>>(&cache_key)
+        .await
+    {
+        return Ok(limits);
+    }
+
+    let conn = pg_connection_read(store).await?;
+    let limits = match Config::find_by_key(&conn, &cache_key).await {
+        Ok(config) => Some(parse_payout_amount_limits(&config.config)?),
+        Err(er)
+            if matches!(
+                er.current_context(),
+                diesel_models::errors::DatabaseError::NotFound
+            ) =>
+        {
+            None
+        }
+        Err(er) => {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            return Err(er.change_context(new_err));
+        }
+    };
+    CONFIG_CACHE.push(cache_key, limits.clone()).await;
+    Ok(limits)
+}
+
+fn parse_payout_amount_limits(
+    config: &str,
+) -> error_stack::Result<PayoutAmountLimits, StorageError> {
+    serde_json::from_str(config)
+        .into_report()
+        .change_context(StorageError::DeserializationFailed)
+        .attach_printable("invalid payout amount limits config")
+}
+
+fn check_payout_amount_limits(
+    limits: Option<&PayoutAmountLimits>,
+    amount: i64,
+) -> error_stack::Result<(), StorageError> {
+    match limits {
+        Some(limits) if !(limits.min..=limits.max).contains(&amount) => {
+            Err(StorageError::AmountOutOfRange {
+                min: limits.min,
+                max: limits.max,
+            })
+            .into_report()
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Statuses of the payouts in flight at the connector, counted against the limit of
+/// [`payout_max_in_flight_key`].
+pub const IN_FLIGHT_PAYOUT_STATUSES: [PayoutStatus; 1] = [PayoutStatus::Pending];
+
+/// Key of the config holding the maximum number of payouts of the merchant which may be in
+/// flight at once, see [`IN_FLIGHT_PAYOUT_STATUSES`]. Merchants without it are not limited.
+pub fn payout_max_in_flight_key(merchant_id: &str) -> String {
+    format!("payout_max_in_flight_{merchant_id}")
+}
+
+/// Rejects a payout of a merchant which already has as many payouts in flight as its limit, see
+/// [`payout_max_in_flight_key`].
+///
+/// Payouts are counted in the database, so payouts on the Redis KV scheme which are not drained
+/// yet are not counted.
+async fn validate_in_flight_payout_limit<T: DatabaseStore>(
+    store: &T,
+    merchant_id: &str,
+) -> error_stack::Result<(), StorageError> {
+    let Some(limit) = find_max_in_flight_payouts(store, merchant_id).await? else {
+        return Ok(());
+    };
+    let conn = pg_connection_read(store).await?;
+    let in_flight = DieselPayouts::count_by_merchant_id_statuses(
+        &conn,
+        merchant_id,
+        &IN_FLIGHT_PAYOUT_STATUSES,
+    )
+    .await
+    .map_err(|er| {
+        let new_err = diesel_error_to_data_error(er.current_context());
+        er.change_context(new_err)
+    })?;
+    check_in_flight_payout_limit(Some(limit), in_flight).attach_printable_lazy(|| {
+        format!("Merchant {merchant_id} has {in_flight} payouts in flight")
+    })
+}
+
+/// The maximum number of payouts of the merchant in flight, `None` if it is not limited. The
+/// config is cached under its key, so that updating it through the configs API invalidates the
+/// cache.
+async fn find_max_in_flight_payouts<T: DatabaseStore>(
+    store: &T,
+    merchant_id: &str,
+) -> error_stack::Result<Option<u32>, StorageError> {
+    let cache_key = payout_max_in_flight_key(merchant_id);
+    if let Some(limit) = CONFIG_CACHE.get_val::<Option<u32>>(&cache_key).await {
+        return Ok(limit);
+    }
+
+    let conn = pg_connection_read(store).await?;
+    let limit = match Config::find_by_key(&conn, &cache_key).await {
+        Ok(config) => Some(parse_max_in_flight_payouts(&config.config)?),
+        Err(er)
+            if matches!(
+                er.current_context(),
+                diesel_models::errors::DatabaseError::NotFound
+            ) =>
+        {
+            None
+        }
+        Err(er) => {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            return Err(er.change_context(new_err));
+        }
+    };
+    CONFIG_CACHE.push(cache_key, limit).await;
+    Ok(limit)
+}
+
+fn parse_max_in_flight_payouts(config: &str) -> error_stack::Result<u32, StorageError> {
+    config
+        .trim()
+        .parse()
+        .into_report()
+        .change_context(StorageError::DeserializationFailed)
+        .attach_printable("invalid max in-flight payouts config")
+}
+
+fn check_in_flight_payout_limit(
+    limit: Option<u32>,
+    in_flight: i64,
+) -> error_stack::Result<(), StorageError> {
+    match limit {
+        Some(limit) if in_flight >= i64::from(limit) => {
+            Err(StorageError::ConcurrencyLimitReached { limit }).into_report()
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that imports are enabled and prepares `new` to be imported, see
+/// [`PayoutsInterface::import_payout`].
+fn prepare_payout_import(
+    imports_enabled: bool,
+    new: PayoutsNew,
+    preserve_timestamps: bool,
+) -> error_stack::Result<PayoutsNew, StorageError> {
+    if !imports_enabled {
+        return Err(StorageError::ImportsDisabled)
+            .into_report()
+            .attach_printable(format!("payout {} was not imported", new.payout_id));
+    }
+    new.for_import(preserve_timestamps, common_utils::date_time::now())
+}
+
+/// Applies the test mode of `router_store` to the payout about to be inserted through `store`
+/// and runs the checks of [`PayoutsInterface::insert_payout`] on it, with the settings of
+/// `router_store`. The display sequence number is left to be assigned.
+async fn validate_new_payout<T, S>(
+    router_store: &crate::RouterStore<T>,
+    store: &S,
+    new: PayoutsNew,
+) -> error_stack::Result<PayoutsNew, StorageError>
+where
+    T: DatabaseStore,
+    S: DatabaseStore + PayoutsInterface + Sync,
+{
+    let new = router_store.payout_test_mode.apply_to(new);
+    validate_payout_id(router_store.payout_id_validator.as_ref(), &new.payout_id)?;
+    validate_profile_belongs_to_merchant(store, &new.merchant_id, &new.profile_id).await?;
+    validate_payout_currency_allowed(store, &new.merchant_id, new.destination_currency).await?;
+    validate_payout_return_url_allowed(store, &new.merchant_id, new.return_url.as_deref()).await?;
+    validate_payout_amount_limits(store, new.amount, new.destination_currency).await?;
+    validate_in_flight_payout_limit(store, &new.merchant_id).await?;
+    precheck_payout_balance(store, router_store.payout_balance_check, &new).await?;
+    validate_created_at_not_in_future(
+        new.created_at,
+        common_utils::date_time::now(),
+        router_store.payout_created_at_skew_tolerance_in_secs,
+    )?;
+    Ok(new)
+}
+
+/// Links `splits` to `parent` and validates each of them, see
+/// [`PayoutsInterface::insert_split_payouts`]. Display sequence numbers are assigned by
+/// `next_display_seq`, in order.
+async fn prepare_split_payouts<T, S, F, Fut>(
+    router_store: &crate::RouterStore<T>,
+    store: &S,
+    parent: PayoutsNew,
+    splits: Vec<PayoutsNew>,
+    next_display_seq: F,
+) -> error_stack::Result<(PayoutsNew, Vec<PayoutsNew>), StorageError>
+where
+    T: DatabaseStore,
+    S: DatabaseStore + PayoutsInterface + Sync,
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = error_stack::Result<i64, StorageError>>,
+{
+    // The test mode of the parent is applied before the splits inherit its id
+    let parent = validate_new_payout(router_store, store, parent).await?;
+    let splits = parent.link_splits(splits)?;
+    let parent = PayoutsNew {
+        display_seq: next_display_seq(parent.merchant_id.clone()).await?,
+        ..parent
+    };
+    let mut prepared_splits = Vec::with_capacity(splits.len());
+    for split in splits {
+        let split = validate_new_payout(router_store, store, split).await?;
+        prepared_splits.push(PayoutsNew {
+            display_seq: next_display_seq(split.merchant_id.clone()).await?,
+            ..split
+        });
+    }
+    Ok((parent, prepared_splits))
+}
+
+/// Inserts the parent payout and its splits prepared by [`prepare_split_payouts`] in a single
+/// transaction.
+async fn insert_split_payouts_in_database<T: DatabaseStore>(
+    store: &crate::RouterStore<T>,
+    parent: PayoutsNew,
+    splits: Vec<PayoutsNew>,
+) -> error_stack::Result<(Payouts, Vec<Payouts>), StorageError> {
+    let parent = parent.to_storage_model();
+    let splits = splits
+        .into_iter()
+        .map(DataModelExt::to_storage_model)
+        .collect::<Vec<_>>();
+    let conn = pg_connection_write(store).await?;
+    let (parent, splits) = conn
+        .transaction_async(|conn| async move {
+            let parent = parent
+                .insert(&conn)
+                .await
+                .map_err(|er| *er.current_context())?;
+            let mut inserted_splits = Vec::with_capacity(splits.len());
+            for split in splits {
+                inserted_splits.push(
+                    split
+                        .insert(&conn)
+                        .await
+                        .map_err(|er| *er.current_context())?,
+                );
+            }
+            Ok::<_, diesel_models::errors::DatabaseError>((parent, inserted_splits))
+        })
+        .await
+        .into_report()
+        .map_err(|er| {
+            let new_err = diesel_error_to_data_error(er.current_context());
+            er.change_context(new_err)
+        })?;
+
+    let parent = Payouts::from_storage_model(parent);
+    let splits = splits
+        .into_iter()
+        .map(Payouts::from_storage_model)
+        .collect::<Vec<_>>();
+    for payout in std::iter::once(&parent).chain(&splits) {
+        record_payout_created(store, &payout.merchant_id, common_utils::date_time::now()).await;
+        record_payout_audit_event(
+            store.payout_audit_sink.as_ref(),
+            PayoutAuditOperation::Insert,
+            payout,
+        )
+        .await;
+    }
+    Ok((parent, splits))
+}
+
+/// Rejects a client supplied `created_at` lying further in the future than the skew tolerance,
+/// which would skew reports and break keyset pagination.
+fn validate_created_at_not_in_future(
+    created_at: Option<PrimitiveDateTime>,
+    now: PrimitiveDateTime,
+    skew_tolerance_in_secs: u32,
+) -> error_stack::Result<(), StorageError> {
+    match created_at {
+        Some(created_at)
+            if (created_at - now).whole_seconds() > i64::from(skew_tolerance_in_secs) =>
+        {
+            Err(StorageError::InvalidValue {
+                field: "created_at",
+            })
+            .into_report()
+            .attach_printable(format!(
+                "created_at {created_at} is more than {skew_tolerance_in_secs} seconds in the future"
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Drops the payout from the read cache, if one is configured, after a local write of it.
+    fn invalidate_cached_payout(&self, merchant_id: &str, payout_id: &str) {
+        if let Some(cache) = self.payout_read_cache.as_deref() {
+            cache.invalidate(merchant_id, payout_id);
+        }
+    }
+}
+
+/// Rejects an update prepared against `this` when the stored payout is at `current_sequence`,
+/// i.e. it was updated since `this` was read.
+fn ensure_sequence_matches(
+    this: &Payouts,
+    current_sequence: i64,
+) -> error_stack::Result<(), StorageError> {
+    if this.sequence_number == current_sequence {
+        Ok(())
+    } else {
+        Err(StorageError::StaleUpdate { entity: "payouts" })
+            .into_report()
+            .attach_printable(format!(
+                "payout {} is at sequence number {current_sequence}, update expected {}",
+                this.payout_id, this.sequence_number
+            ))
+    }
+}
+
+fn check_profile_merchant(
+    merchant_id: &str,
+    profile_merchant_id: Option<&str>,
+) -> error_stack::Result<(), StorageError> {
+    match profile_merchant_id {
+        Some(profile_merchant_id) if profile_merchant_id == merchant_id => Ok(()),
+        _ => Err(StorageError::InvalidValue {
+            field: "profile_id",
+        })
+        .into_report(),
+    }
+}
+
+// The conversions below map the fields one to one, so that a field added to or removed from only
+// one side of them must fail the build rather than be dropped on its way to or from storage.
+const _: () = assert!(
+    same_field_names(DieselPayouts::FIELD_NAMES, Payouts::FIELD_NAMES),
+    "the fields of `DieselPayouts` and `Payouts` diverged"
+);
+const _: () = assert!(
+    same_field_names(DieselPayoutsNew::FIELD_NAMES, PayoutsNew::FIELD_NAMES),
+    "the fields of `DieselPayoutsNew` and `PayoutsNew` diverged"
+);
+
+impl DataModelExt for Payouts {
+    type StorageModel = DieselPayouts;
+
+    fn to_storage_model(self) -> Self::StorageModel {
+        DieselPayouts {
+            payout_id: self.payout_id,
+            merchant_id: self.merchant_id,
+            customer_id: self.customer_id,
+            address_id: self.address_id,
+            payout_type: self.payout_type,
+            payout_method_id: self.payout_method_id,
+            amount: self.amount,
+            destination_currency: self.destination_currency,
+            source_currency: self.source_currency,
+            description: self.description,
+            recurring: self.recurring,
+            auto_fulfill: self.auto_fulfill,
+            return_url: self.return_url,
+            entity_type: self.entity_type,
+            metadata: self.metadata,
+            created_at: self.created_at,
+            last_modified_at: self.last_modified_at,
+            profile_id: self.profile_id,
+            status: self.status,
+            attempt_count: self.attempt_count,
+            original_payout_id: self.original_payout_id,
+            hold_reason: self.hold_reason,
+            created_by: self.created_by,
+            network_token_ref: self.network_token_ref,
+            connector_idempotency_key: self.connector_idempotency_key,
+            sequence_number: self.sequence_number,
+            scheduled_at: self.scheduled_at,
+            routing_decision: self.routing_decision,
+            deleted_at: self.deleted_at,
+            source_payment_id: self.source_payment_id,
+            test_mode: self.test_mode,
+            next_retry_at: self.next_retry_at,
+            connector: self.connector,
+            connector_payout_id: self.connector_payout_id,
+            connector_response: self.connector_response,
+            exchange_rate: self.exchange_rate,
+            exchange_rate_timestamp: self.exchange_rate_timestamp,
+            display_seq: self.display_seq,
+            risk_score: self.risk_score,
+            is_high_risk: self.is_high_risk,
+            customer_snapshot: self.customer_snapshot,
+            cancellation_reason: self.cancellation_reason,
+            last_modified_by: self.last_modified_by,
+            fulfilled_at: self.fulfilled_at,
+            parent_payout_id: self.parent_payout_id,
+            status_changed_at: self.status_changed_at,
+        }
+    }
+
+    fn from_storage_model(storage_model: Self::StorageModel) -> Self {
+        Self {
+            payout_id: storage_model.payout_id,
+            merchant_id: storage_model.merchant_id,
+            customer_id: storage_model.customer_id,
+            address_id: storage_model.address_id,
+            payout_type: storage_model.payout_type,
+            payout_method_id: storage_model.payout_method_id,
+            amount: storage_model.amount,
+            destination_currency: storage_model.destination_currency,
+            source_currency: storage_model.source_currency,
+            description: storage_model.description,
+            recurring: storage_model.recurring,
+            auto_fulfill: storage_model.auto_fulfill,
+            return_url: storage_model.return_url,
+            entity_type: storage_model.entity_type,
+            metadata: storage_model.metadata,
+            created_at: storage_model.created_at,
+            last_modified_at: storage_model.last_modified_at,
+            profile_id: storage_model.profile_id,
+            status: storage_model.status,
+            attempt_count: storage_model.attempt_count,
+            original_payout_id: storage_model.original_payout_id,
+            hold_reason: storage_model.hold_reason,
+            created_by: storage_model.created_by,
+            network_token_ref: storage_model.network_token_ref,
+            connector_idempotency_key: storage_model.connector_idempotency_key,
+            sequence_number: storage_model.sequence_number,
+            scheduled_at: storage_model.scheduled_at,
+            routing_decision: storage_model.routing_decision,
+            deleted_at: storage_model.deleted_at,
+            source_payment_id: storage_model.source_payment_id,
+            test_mode: storage_model.test_mode,
+            next_retry_at: storage_model.next_retry_at,
+            connector: storage_model.connector,
+            connector_payout_id: storage_model.connector_payout_id,
+            connector_response: storage_model.connector_response,
+            exchange_rate: storage_model.exchange_rate,
+            exchange_rate_timestamp: storage_model.exchange_rate_timestamp,
+            display_seq: storage_model.display_seq,
+            risk_score: storage_model.risk_score,
+            is_high_risk: storage_model.is_high_risk,
+            customer_snapshot: storage_model.customer_snapshot,
+            cancellation_reason: storage_model.cancellation_reason,
+            last_modified_by: storage_model.last_modified_by,
+            fulfilled_at: storage_model.fulfilled_at,
+            parent_payout_id: storage_model.parent_payout_id,
+            status_changed_at: storage_model.status_changed_at,
+        }
+    }
+}
+impl DataModelExt for PayoutsNew {
+    type StorageModel = DieselPayoutsNew;
+
+    fn to_storage_model(self) -> Self::StorageModel {
+        DieselPayoutsNew {
+            payout_id: self.payout_id,
+            merchant_id: self.merchant_id,
+            customer_id: self.customer_id,
+            address_id: self.address_id,
+            payout_type: self.payout_type,
+            payout_method_id: self.payout_method_id,
+            amount: self.amount,
+            destination_currency: self.destination_currency,
+            source_currency: self.source_currency,
+            description: self.description,
+            recurring: self.recurring,
+            auto_fulfill: self.auto_fulfill,
+            return_url: self.return_url,
+            entity_type: self.entity_type,
+            metadata: self.metadata,
+            created_at: self.created_at,
+            last_modified_at: self.last_modified_at,
+            profile_id: self.profile_id,
+            status: self.status,
+            attempt_count: self.attempt_count,
+            original_payout_id: self.original_payout_id,
+            hold_reason: self.hold_reason,
+            created_by: self.created_by,
+            network_token_ref: self.network_token_ref,
+            connector_idempotency_key: self.connector_idempotency_key,
+            sequence_number: self.sequence_number,
+            scheduled_at: self.scheduled_at,
+            routing_decision: self.routing_decision,
+            deleted_at: self.deleted_at,
+            source_payment_id: self.source_payment_id,
+            test_mode: self.test_mode,
+            next_retry_at: self.next_retry_at,
+            connector: self.connector,
+            connector_payout_id: self.connector_payout_id,
+            connector_response: self.connector_response,
+            exchange_rate: self.exchange_rate,
+            exchange_rate_timestamp: self.exchange_rate_timestamp,
+            display_seq: self.display_seq,
+            risk_score: self.risk_score,
+            is_high_risk: self.is_high_risk,
+            customer_snapshot: self.customer_snapshot,
+            parent_payout_id: self.parent_payout_id,
+        }
+    }
+
+    fn from_storage_model(storage_model: Self::StorageModel) -> Self {
+        Self {
+            payout_id: storage_model.payout_id,
+            merchant_id: storage_model.merchant_id,
+            customer_id: storage_model.customer_id,
+            address_id: storage_model.address_id,
+            payout_type: storage_model.payout_type,
+            payout_method_id: storage_model.payout_method_id,
+            amount: storage_model.amount,
+            destination_currency: storage_model.destination_currency,
+            source_currency: storage_model.source_currency,
+            description: storage_model.description,
+            recurring: storage_model.recurring,
+            auto_fulfill: storage_model.auto_fulfill,
+            return_url: storage_model.return_url,
+            entity_type: storage_model.entity_type,
+            metadata: storage_model.metadata,
+            created_at: storage_model.created_at,
+            last_modified_at: storage_model.last_modified_at,
+            profile_id: storage_model.profile_id,
+            status: storage_model.status,
+            attempt_count: storage_model.attempt_count,
+            original_payout_id: storage_model.original_payout_id,
+            hold_reason: storage_model.hold_reason,
+            created_by: storage_model.created_by,
+            network_token_ref: storage_model.network_token_ref,
+            connector_idempotency_key: storage_model.connector_idempotency_key,
+            sequence_number: storage_model.sequence_number,
+            scheduled_at: storage_model.scheduled_at,
+            routing_decision: storage_model.routing_decision,
+            deleted_at: storage_model.deleted_at,
+            source_payment_id: storage_model.source_payment_id,
+            test_mode: storage_model.test_mode,
+            next_retry_at: storage_model.next_retry_at,
+            connector: storage_model.connector,
+            connector_payout_id: storage_model.connector_payout_id,
+            connector_response: storage_model.connector_response,
+            exchange_rate: storage_model.exchange_rate,
+            exchange_rate_timestamp: storage_model.exchange_rate_timestamp,
+            display_seq: storage_model.display_seq,
+            risk_score: storage_model.risk_score,
+            is_high_risk: storage_model.is_high_risk,
+            customer_snapshot: storage_model.customer_snapshot,
+            parent_payout_id: storage_model.parent_payout_id,
         }
     }
 }
@@ -430,6 +4475,7 @@ impl DataModelExt for PayoutsUpdate {
                 metadata,
                 profile_id,
                 status,
+                last_modified_by,
             } => DieselPayoutsUpdate::Update {
                 amount,
                 destination_currency,
@@ -442,6 +4488,7 @@ impl DataModelExt for PayoutsUpdate {
                 metadata,
                 profile_id,
                 status,
+                last_modified_by,
             },
             Self::PayoutMethodIdUpdate { payout_method_id } => {
                 DieselPayoutsUpdate::PayoutMethodIdUpdate { payout_method_id }
@@ -452,6 +4499,121 @@ impl DataModelExt for PayoutsUpdate {
             Self::AttemptCountUpdate { attempt_count } => {
                 DieselPayoutsUpdate::AttemptCountUpdate { attempt_count }
             }
+            Self::MetadataUpdate { metadata } => DieselPayoutsUpdate::MetadataUpdate { metadata },
+            Self::HoldUpdate { hold_reason } => DieselPayoutsUpdate::HoldUpdate { hold_reason },
+            Self::CancelUpdate {
+                cancellation_reason,
+            } => DieselPayoutsUpdate::CancelUpdate {
+                cancellation_reason,
+            },
+            Self::ResumeUpdate { status } => DieselPayoutsUpdate::ResumeUpdate { status },
+            Self::NetworkTokenUpdate { network_token_ref } => {
+                DieselPayoutsUpdate::NetworkTokenUpdate { network_token_ref }
+            }
+            Self::ConnectorIdempotencyKeyUpdate {
+                connector_idempotency_key,
+            } => DieselPayoutsUpdate::ConnectorIdempotencyKeyUpdate {
+                connector_idempotency_key,
+            },
+            Self::ActivateUpdate => DieselPayoutsUpdate::ActivateUpdate,
+            Self::ConfirmUpdate => DieselPayoutsUpdate::ConfirmUpdate,
+            Self::StatusUpdate { status } => DieselPayoutsUpdate::StatusUpdate { status },
+            Self::RoutingDecisionUpdate { routing_decision } => {
+                DieselPayoutsUpdate::RoutingDecisionUpdate {
+                    routing_decision: routing_decision.to_secret_value(),
+                }
+            }
+            Self::SourcePaymentUpdate { source_payment_id } => {
+                DieselPayoutsUpdate::SourcePaymentUpdate { source_payment_id }
+            }
+            Self::NextRetryUpdate { next_retry_at } => {
+                DieselPayoutsUpdate::NextRetryUpdate { next_retry_at }
+            }
+            Self::RerouteUpdate {
+                connector,
+                connector_idempotency_key,
+                attempt_count,
+            } => DieselPayoutsUpdate::RerouteUpdate {
+                connector,
+                connector_idempotency_key,
+                attempt_count,
+            },
+            Self::FxUpdate {
+                exchange_rate,
+                exchange_rate_timestamp,
+            } => DieselPayoutsUpdate::FxUpdate {
+                exchange_rate,
+                exchange_rate_timestamp,
+            },
+            Self::RiskUpdate {
+                risk_score,
+                is_high_risk,
+            } => DieselPayoutsUpdate::RiskUpdate {
+                risk_score,
+                is_high_risk,
+            },
+            Self::CombinedUpdate {
+                amount,
+                destination_currency,
+                source_currency,
+                description,
+                recurring,
+                auto_fulfill,
+                return_url,
+                entity_type,
+                metadata,
+                payout_method_id,
+                profile_id,
+                status,
+                attempt_count,
+                network_token_ref,
+                connector_idempotency_key,
+                routing_decision,
+                source_payment_id,
+                next_retry_at,
+                exchange_rate,
+                exchange_rate_timestamp,
+                risk_score,
+                is_high_risk,
+                last_modified_by,
+            } => DieselPayoutsUpdate::CombinedUpdate {
+                amount,
+                destination_currency,
+                source_currency,
+                description,
+                recurring,
+                auto_fulfill,
+                return_url,
+                entity_type,
+                metadata,
+                payout_method_id,
+                profile_id,
+                status,
+                attempt_count,
+                network_token_ref,
+                connector_idempotency_key,
+                routing_decision,
+                source_payment_id,
+                next_retry_at,
+                exchange_rate,
+                exchange_rate_timestamp,
+                risk_score,
+                is_high_risk,
+                last_modified_by,
+            },
+            Self::Patch {
+                payout_method_id,
+                description,
+                return_url,
+                metadata,
+                network_token_ref,
+            } => DieselPayoutsUpdate::Patch {
+                payout_method_id,
+                description,
+                return_url,
+                metadata,
+                network_token_ref,
+            },
         }
     }
 
@@ -460,3 +4622,830 @@ impl DataModelExt for PayoutsUpdate {
         todo!("Reverse map should no longer be needed")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::panic)]
+    use std::str::FromStr;
+
+    use data_models::{
+        errors::StorageError,
+        payouts::{ids::PayoutId, payouts::PayoutsNew},
+    };
+    use diesel_models::enums::{Currency, PayoutCreationSource};
+
+    use super::{
+        check_in_flight_payout_limit, check_payout_amount_limits, check_payout_currency_allowed,
+        check_profile_merchant, check_return_url_allowed, decode_kv_payouts_batch,
+        ensure_sequence_matches, parse_allowed_payout_currencies, parse_allowed_return_url_domains,
+        parse_max_in_flight_payouts, parse_payout_amount_limits, payout_lookup_error,
+        payouts_by_requested_id, prepare_payout_import, validate_created_at_not_in_future,
+        IN_FLIGHT_PAYOUT_STATUSES,
+    };
+    use crate::{utils::same_field_names, DataModelExt};
+
+    const CREATION_SOURCES: [PayoutCreationSource; 4] = [
+        PayoutCreationSource::Api,
+        PayoutCreationSource::Dashboard,
+        PayoutCreationSource::BatchFile,
+        PayoutCreationSource::Recurring,
+    ];
+
+    #[test]
+    fn allowed_payout_currency_is_accepted() {
+        let allowed = parse_allowed_payout_currencies(r#"["USD", "EUR"]"#).unwrap();
+        assert!(check_payout_currency_allowed(Some(&allowed), Currency::EUR).is_ok());
+    }
+
+    #[test]
+    fn disallowed_payout_currency_is_rejected() {
+        let allowed = parse_allowed_payout_currencies(r#"["USD"]"#).unwrap();
+        let error = check_payout_currency_allowed(Some(&allowed), Currency::GBP).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InvalidValue {
+                field: "destination_currency"
+            }
+        ));
+
+        let error = check_payout_currency_allowed(Some(&[]), Currency::USD).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InvalidValue {
+                field: "destination_currency"
+            }
+        ));
+    }
+
+    #[test]
+    fn return_url_on_allowed_host_is_accepted() {
+        let allowed = parse_allowed_return_url_domains(r#"["example.com", "shop.io"]"#).unwrap();
+        for return_url in [
+            "https://example.com/payouts/done",
+            "https://checkout.Example.com?status=done",
+            "http://shop.io:8080/return",
+        ] {
+            assert!(
+                check_return_url_allowed(&allowed, return_url).is_ok(),
+                "{return_url}"
+            );
+        }
+    }
+
+    #[test]
+    fn return_url_on_disallowed_host_is_rejected() {
+        let allowed = parse_allowed_return_url_domains(r#"["example.com"]"#).unwrap();
+        for return_url in [
+            "https://evil.com/return",
+            "https://notexample.com",
+            "https://example.com.evil.com",
+            "https://example.com@evil.com/return",
+            "javascript://example.com/%0aalert(1)",
+            "/relative/path",
+            "not a url",
+        ] {
+            let error = check_return_url_allowed(&allowed, return_url).unwrap_err();
+            assert!(
+                matches!(
+                    error.current_context(),
+                    StorageError::InvalidValue {
+                        field: "return_url"
+                    }
+                ),
+                "{return_url}"
+            );
+        }
+    }
+
+    #[test]
+    fn empty_return_url_allow_list_is_permissive() {
+        let allowed = parse_allowed_return_url_domains("[]").unwrap();
+        assert!(check_return_url_allowed(&allowed, "https://anywhere.example.org").is_ok());
+        let error = parse_allowed_return_url_domains("example.com").unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::DeserializationFailed
+        ));
+    }
+
+    #[test]
+    fn unrestricted_merchant_may_pay_out_in_any_currency() {
+        for currency in [Currency::USD, Currency::GBP, Currency::JPY] {
+            assert!(check_payout_currency_allowed(None, currency).is_ok());
+        }
+        let error = parse_allowed_payout_currencies("USD").unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::DeserializationFailed
+        ));
+    }
+
+    #[test]
+    fn payout_below_the_minimum_is_rejected() {
+        let limits = parse_payout_amount_limits(r#"{"min": 100, "max": 10000}"#).unwrap();
+        let error = check_payout_amount_limits(Some(&limits), 99).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::AmountOutOfRange {
+                min: 100,
+                max: 10000
+            }
+        ));
+    }
+
+    #[test]
+    fn payout_above_the_maximum_is_rejected() {
+        let limits = parse_payout_amount_limits(r#"{"min": 100, "max": 10000}"#).unwrap();
+        let error = check_payout_amount_limits(Some(&limits), 10001).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::AmountOutOfRange {
+                min: 100,
+                max: 10000
+            }
+        ));
+    }
+
+    #[test]
+    fn payout_within_the_limits_is_accepted() {
+        let limits = parse_payout_amount_limits(r#"{"min": 100, "max": 10000}"#).unwrap();
+        for amount in [100, 5000, 10000] {
+            assert!(check_payout_amount_limits(Some(&limits), amount).is_ok());
+        }
+
+        // Bounds left out of the config are not enforced
+        let limits = parse_payout_amount_limits(r#"{"max": 10000}"#).unwrap();
+        assert!(check_payout_amount_limits(Some(&limits), 0).is_ok());
+        let limits = parse_payout_amount_limits(r#"{"min": 100}"#).unwrap();
+        assert!(check_payout_amount_limits(Some(&limits), i64::MAX).is_ok());
+        assert!(check_payout_amount_limits(None, i64::MAX).is_ok());
+    }
+
+    #[test]
+    fn payout_beyond_the_in_flight_limit_is_rejected() {
+        let limit = parse_max_in_flight_payouts("3").unwrap();
+        for in_flight in 0..3 {
+            assert!(check_in_flight_payout_limit(Some(limit), in_flight).is_ok());
+        }
+        // The 4th concurrent payout
+        let error = check_in_flight_payout_limit(Some(limit), 3).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::ConcurrencyLimitReached { limit: 3 }
+        ));
+
+        assert!(check_in_flight_payout_limit(None, i64::MAX).is_ok());
+        let error = parse_max_in_flight_payouts("three").unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::DeserializationFailed
+        ));
+    }
+
+    #[test]
+    fn terminal_payouts_are_not_in_flight() {
+        use diesel_models::enums::PayoutStatus;
+
+        assert!(IN_FLIGHT_PAYOUT_STATUSES.contains(&PayoutStatus::Pending));
+        for status in [
+            PayoutStatus::Success,
+            PayoutStatus::Failed,
+            PayoutStatus::Cancelled,
+            PayoutStatus::Ineligible,
+        ] {
+            assert!(!IN_FLIGHT_PAYOUT_STATUSES.contains(&status));
+        }
+    }
+
+    #[test]
+    fn imported_payout_keeps_its_original_timestamps() {
+        let created_at = common_utils::date_time::now() - time::Duration::days(400);
+        let last_modified_at = created_at + time::Duration::days(3);
+        let new = PayoutsNew {
+            payout_id: "po_imported".to_string(),
+            created_at: Some(created_at),
+            last_modified_at: Some(last_modified_at),
+            ..Default::default()
+        };
+
+        let error = prepare_payout_import(false, new.clone(), true).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::ImportsDisabled
+        ));
+
+        let stored = prepare_payout_import(true, new, true)
+            .unwrap()
+            .to_storage_model();
+        assert_eq!(stored.created_at, Some(created_at));
+        assert_eq!(stored.last_modified_at, Some(last_modified_at));
+    }
+
+    #[test]
+    fn payout_not_found_carries_lookup_key() {
+        let error = payout_lookup_error(
+            "merchant_1",
+            "payout_1",
+            &diesel_models::errors::DatabaseError::NotFound,
+        );
+        match error {
+            StorageError::ValueNotFound(message) => {
+                assert!(message.starts_with("payout does not exist"));
+                assert!(message.contains("mid_merchant_1_po_payout_1"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn payout_lookup_error_preserves_other_database_errors() {
+        let error = payout_lookup_error(
+            "merchant_1",
+            "payout_1",
+            &diesel_models::errors::DatabaseError::DatabaseConnectionError,
+        );
+        assert!(matches!(error, StorageError::DatabaseConnectionError));
+    }
+
+    #[test]
+    fn profile_of_same_merchant_is_accepted() {
+        assert!(check_profile_merchant("merchant_1", Some("merchant_1")).is_ok());
+    }
+
+    #[test]
+    fn profile_of_another_merchant_is_rejected() {
+        let error = check_profile_merchant("merchant_1", Some("merchant_2")).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InvalidValue {
+                field: "profile_id"
+            }
+        ));
+    }
+
+    #[test]
+    fn missing_profile_is_rejected() {
+        let error = check_profile_merchant("merchant_1", None).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InvalidValue {
+                field: "profile_id"
+            }
+        ));
+    }
+
+    #[test]
+    fn creation_source_round_trips_through_storage_model() {
+        for created_by in CREATION_SOURCES {
+            let new = PayoutsNew {
+                payout_id: "po_1".to_string(),
+                created_by,
+                ..Default::default()
+            };
+            let storage_model = new.clone().to_storage_model();
+            assert_eq!(storage_model.created_by, created_by);
+            assert_eq!(PayoutsNew::from_storage_model(storage_model), new);
+        }
+    }
+
+    #[test]
+    fn network_token_ref_round_trips_through_storage_model() {
+        let new = PayoutsNew {
+            payout_id: "po_1".to_string(),
+            recurring: true,
+            network_token_ref: Some("ntr_1".to_string()),
+            ..Default::default()
+        };
+        let storage_model = new.clone().to_storage_model();
+        assert_eq!(storage_model.network_token_ref.as_deref(), Some("ntr_1"));
+        assert_eq!(PayoutsNew::from_storage_model(storage_model), new);
+    }
+
+    #[test]
+    fn network_token_update_is_applied_to_stored_payout() {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::from_value::<diesel_models::payouts::Payouts>(
+            serde_json::to_value(diesel_models::payouts::PayoutsNew {
+                payout_id: "po_1".to_string(),
+                created_at: Some(now),
+                last_modified_at: Some(now),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        let updated = data_models::payouts::payouts::PayoutsUpdate::NetworkTokenUpdate {
+            network_token_ref: Some("ntr_1".to_string()),
+        }
+        .to_storage_model()
+        .apply_changeset(stored);
+        assert_eq!(updated.network_token_ref.as_deref(), Some("ntr_1"));
+    }
+
+    /// Stands in for a metadata value which fails to serialize.
+    struct UnserializableMetadata;
+
+    impl serde::Serialize for UnserializableMetadata {
+        fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("unsupported metadata value"))
+        }
+    }
+
+    #[test]
+    fn serialization_failure_names_the_failing_field() {
+        #[derive(serde::Serialize)]
+        struct PayoutWithMetadata {
+            payout_id: &'static str,
+            metadata: UnserializableMetadata,
+        }
+
+        let error = super::encode_naming_failed_field(
+            &PayoutWithMetadata {
+                payout_id: "po_1",
+                metadata: UnserializableMetadata,
+            },
+            "po_1",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::SerializationFailed
+        ));
+        let message = format!("{error:?}");
+        assert!(message.contains("`metadata`"), "{message}");
+        assert!(message.contains("po_1"), "{message}");
+        assert!(message.contains("unsupported metadata value"), "{message}");
+
+        let payout = stored_payout(0).to_storage_model();
+        assert_eq!(
+            super::encode_naming_failed_field(&payout, &payout.payout_id).unwrap(),
+            serde_json::to_string(&payout).unwrap()
+        );
+    }
+
+    #[test]
+    fn field_sets_diverge_when_a_field_is_removed_from_one_side() {
+        let storage_fields = diesel_models::payouts::Payouts::FIELD_NAMES;
+        let domain_fields = data_models::payouts::payouts::Payouts::FIELD_NAMES;
+        assert!(same_field_names(storage_fields, domain_fields));
+
+        // The fields are compared as sets, regardless of their declaration order
+        let reordered = domain_fields.iter().rev().copied().collect::<Vec<_>>();
+        assert!(same_field_names(storage_fields, &reordered));
+
+        let without_next_retry_at = domain_fields
+            .iter()
+            .copied()
+            .filter(|field| *field != "next_retry_at")
+            .collect::<Vec<_>>();
+        assert!(!same_field_names(storage_fields, &without_next_retry_at));
+        assert!(!same_field_names(&without_next_retry_at, storage_fields));
+
+        let renamed = domain_fields
+            .iter()
+            .copied()
+            .map(|field| {
+                if field == "next_retry_at" {
+                    "retry_at"
+                } else {
+                    field
+                }
+            })
+            .collect::<Vec<_>>();
+        assert!(!same_field_names(storage_fields, &renamed));
+    }
+
+    #[test]
+    fn next_retry_update_is_applied_to_stored_payout() {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::from_value::<diesel_models::payouts::Payouts>(
+            serde_json::to_value(diesel_models::payouts::PayoutsNew {
+                payout_id: "po_1".to_string(),
+                created_at: Some(now),
+                last_modified_at: Some(now),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        let next_retry_at = now + time::Duration::minutes(15);
+        let delayed = data_models::payouts::payouts::PayoutsUpdate::NextRetryUpdate {
+            next_retry_at: Some(next_retry_at),
+        }
+        .to_storage_model()
+        .apply_changeset(stored);
+        assert_eq!(delayed.next_retry_at, Some(next_retry_at));
+
+        // The payout is kept in KV as JSON, the delay must survive the round trip
+        let read_back = serde_json::from_slice::<diesel_models::payouts::Payouts>(
+            &serde_json::to_vec(&delayed).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(read_back.next_retry_at, delayed.next_retry_at);
+
+        let cleared = data_models::payouts::payouts::PayoutsUpdate::NextRetryUpdate {
+            next_retry_at: None,
+        }
+        .to_storage_model()
+        .apply_changeset(read_back);
+        assert_eq!(cleared.next_retry_at, None);
+    }
+
+    #[test]
+    fn connector_idempotency_key_is_set_and_read_back() {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::from_value::<diesel_models::payouts::Payouts>(
+            serde_json::to_value(diesel_models::payouts::PayoutsNew {
+                payout_id: "po_1".to_string(),
+                created_at: Some(now),
+                last_modified_at: Some(now),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(stored.connector_idempotency_key.is_none());
+
+        let updated = data_models::payouts::payouts::PayoutsUpdate::ConnectorIdempotencyKeyUpdate {
+            connector_idempotency_key: "idem_1".to_string(),
+        }
+        .to_storage_model()
+        .apply_changeset(stored);
+        let payout = data_models::payouts::payouts::Payouts::from_storage_model(updated);
+        assert_eq!(payout.connector_idempotency_key.as_deref(), Some("idem_1"));
+        assert_eq!(
+            payout
+                .to_storage_model()
+                .connector_idempotency_key
+                .as_deref(),
+            Some("idem_1")
+        );
+    }
+
+    #[test]
+    fn exchange_rate_is_stored_and_read_back_with_its_timestamp() {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::from_value::<diesel_models::payouts::Payouts>(
+            serde_json::to_value(diesel_models::payouts::PayoutsNew {
+                payout_id: "po_1".to_string(),
+                created_at: Some(now),
+                last_modified_at: Some(now),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(stored.exchange_rate.is_none());
+
+        let quoted_at = now - time::Duration::minutes(1);
+        let converted = data_models::payouts::payouts::PayoutsUpdate::FxUpdate {
+            exchange_rate: "1.0842150".parse().unwrap(),
+            exchange_rate_timestamp: quoted_at,
+        }
+        .to_storage_model()
+        .apply_changeset(stored);
+
+        // The payout is kept in KV as JSON, the rate must survive the round trip without losing
+        // any of its digits
+        let read_back = serde_json::from_slice::<diesel_models::payouts::Payouts>(
+            &serde_json::to_vec(&converted).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(read_back.exchange_rate, converted.exchange_rate);
+        assert_eq!(
+            read_back
+                .exchange_rate
+                .map(|exchange_rate| exchange_rate.to_string()),
+            Some("1.0842150".to_string())
+        );
+        assert_eq!(read_back.exchange_rate_timestamp, Some(quoted_at));
+
+        let payout = data_models::payouts::payouts::Payouts::from_storage_model(read_back);
+        assert_eq!(payout.exchange_rate, converted.exchange_rate);
+        assert_eq!(payout.exchange_rate_timestamp, Some(quoted_at));
+    }
+
+    #[test]
+    fn creation_source_round_trips_through_stored_text() {
+        for created_by in CREATION_SOURCES {
+            let stored = created_by.to_string();
+            assert_eq!(PayoutCreationSource::from_str(&stored).unwrap(), created_by);
+        }
+        // Rows created before the column was added default to `api`
+        assert_eq!(
+            PayoutCreationSource::from_str("api").unwrap(),
+            PayoutCreationSource::default()
+        );
+    }
+
+    #[test]
+    fn far_future_created_at_is_rejected() {
+        let now = common_utils::date_time::now();
+        let error =
+            validate_created_at_not_in_future(Some(now + time::Duration::days(1)), now, 300)
+                .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::InvalidValue {
+                field: "created_at"
+            }
+        ));
+    }
+
+    #[test]
+    fn slightly_future_created_at_within_tolerance_is_accepted() {
+        let now = common_utils::date_time::now();
+        validate_created_at_not_in_future(Some(now + time::Duration::seconds(30)), now, 300)
+            .unwrap();
+        validate_created_at_not_in_future(None, now, 300).unwrap();
+    }
+
+    fn kv_payout_with_created_at(created_at: &str) -> serde_json::Value {
+        let mut payout =
+            serde_json::to_value(diesel_models::payouts::PayoutsNew::default()).unwrap();
+        payout["created_at"] = serde_json::json!(created_at);
+        payout
+    }
+
+    #[test]
+    fn offset_bearing_created_at_is_stored_in_utc() {
+        let payout: diesel_models::payouts::PayoutsNew =
+            serde_json::from_value(kv_payout_with_created_at("2024-03-14T10:30:00.000+05:30"))
+                .unwrap();
+        assert_eq!(
+            payout.created_at,
+            Some(time::macros::datetime!(2024-03-14 05:00:00))
+        );
+    }
+
+    #[test]
+    fn created_at_without_offset_is_rejected() {
+        assert!(
+            serde_json::from_value::<diesel_models::payouts::PayoutsNew>(
+                kv_payout_with_created_at("2024-03-14T10:30:00.000"),
+            )
+            .is_err()
+        );
+    }
+
+    fn kv_payout_entry(payout_id: &str) -> Vec<u8> {
+        let now = common_utils::date_time::now();
+        serde_json::to_vec(&diesel_models::payouts::PayoutsNew {
+            payout_id: payout_id.to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn kv_batch_falls_back_to_database_for_missing_and_corrupt_entries() {
+        let payout_ids = ["po_found", "po_missing", "po_corrupt", "po_empty"]
+            .map(PayoutId::from)
+            .to_vec();
+        let values = vec![
+            Some(kv_payout_entry("po_found")),
+            None,
+            Some(b"{not json".to_vec()),
+            Some(Vec::new()),
+        ];
+
+        let (payouts, misses) = decode_kv_payouts_batch(&payout_ids, values);
+
+        assert_eq!(payouts.len(), 1);
+        assert_eq!(payouts["po_found"].as_ref().unwrap().payout_id, "po_found");
+        assert_eq!(
+            misses,
+            ["po_missing", "po_corrupt", "po_empty"].map(PayoutId::from)
+        );
+    }
+
+    #[test]
+    fn database_batch_has_an_entry_for_every_requested_id() {
+        let payout_ids = ["po_found", "po_missing"].map(PayoutId::from).to_vec();
+        let payout =
+            serde_json::from_slice::<diesel_models::payouts::Payouts>(&kv_payout_entry("po_found"))
+                .unwrap();
+
+        let payouts = payouts_by_requested_id(
+            &payout_ids,
+            [data_models::payouts::payouts::Payouts::from_storage_model(
+                payout,
+            )],
+        );
+
+        assert_eq!(payouts.len(), 2);
+        assert!(payouts["po_found"].is_some());
+        assert!(payouts["po_missing"].is_none());
+    }
+
+    fn stored_payout(sequence_number: i64) -> data_models::payouts::payouts::Payouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(diesel_models::payouts::PayoutsNew {
+            payout_id: "po_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            sequence_number,
+            ..Default::default()
+        })
+        .unwrap();
+        data_models::payouts::payouts::Payouts::from_storage_model(
+            serde_json::from_value(stored).unwrap(),
+        )
+    }
+
+    #[test]
+    fn in_order_updates_are_accepted_and_bump_the_sequence() {
+        let payout = stored_payout(0);
+        ensure_sequence_matches(&payout, 0).unwrap();
+
+        let updated =
+            data_models::payouts::payouts::PayoutsUpdate::AttemptCountUpdate { attempt_count: 1 }
+                .to_storage_model()
+                .apply_changeset(payout.to_storage_model());
+        assert_eq!(updated.sequence_number, 1);
+
+        let updated = data_models::payouts::payouts::Payouts::from_storage_model(updated);
+        ensure_sequence_matches(&updated, 1).unwrap();
+    }
+
+    #[test]
+    fn out_of_order_update_is_rejected() {
+        // Read at sequence 1, while another update already moved the payout to sequence 2
+        let payout = stored_payout(1);
+        let error = ensure_sequence_matches(&payout, 2).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::StaleUpdate { entity: "payouts" }
+        ));
+    }
+
+    #[test]
+    fn routing_decision_is_stored_and_read_back_from_kv() {
+        let decision = data_models::payouts::payouts::PayoutRoutingDecision {
+            algorithm_id: "algo_1".to_string(),
+            score: 0.75,
+        };
+        let updated = data_models::payouts::payouts::PayoutsUpdate::RoutingDecisionUpdate {
+            routing_decision: decision.clone(),
+        }
+        .to_storage_model()
+        .apply_changeset(stored_payout(0).to_storage_model());
+
+        // The KV hash holds the payout as JSON
+        let stored = serde_json::to_string(&updated).unwrap();
+        let read = data_models::payouts::payouts::Payouts::from_storage_model(
+            serde_json::from_str(&stored).unwrap(),
+        );
+        assert_eq!(read.parsed_routing_decision().unwrap(), Some(decision));
+        assert_eq!(stored_payout(0).parsed_routing_decision().unwrap(), None);
+    }
+
+    #[test]
+    fn customer_snapshot_is_stored_in_kv_and_kept_across_updates() {
+        let snapshot = data_models::payouts::payouts::PayoutCustomerSnapshot {
+            customer_id: "cus_1".to_string(),
+            name: Some(masking::Secret::new("jane doe".to_string())),
+            email: None,
+            phone: None,
+            phone_country_code: None,
+            description: None,
+            metadata: None,
+        };
+        let payout = data_models::payouts::payouts::Payouts {
+            customer_snapshot: Some(snapshot.to_secret_value()),
+            ..stored_payout(0)
+        };
+        let updated =
+            data_models::payouts::payouts::PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 }
+                .to_storage_model()
+                .apply_changeset(payout.to_storage_model());
+
+        // The KV hash holds the payout as JSON
+        let stored = serde_json::to_string(&updated).unwrap();
+        let read = data_models::payouts::payouts::Payouts::from_storage_model(
+            serde_json::from_str(&stored).unwrap(),
+        );
+        assert_eq!(read.parsed_customer_snapshot().unwrap(), Some(snapshot));
+    }
+
+    #[test]
+    fn operator_of_an_update_is_recorded_in_kv_and_drained() {
+        let payout = stored_payout(0);
+        let update = data_models::payouts::payouts::PayoutsUpdate::Update {
+            amount: payout.amount,
+            destination_currency: payout.destination_currency,
+            source_currency: payout.source_currency,
+            description: None,
+            recurring: true,
+            auto_fulfill: payout.auto_fulfill,
+            return_url: None,
+            entity_type: payout.entity_type,
+            metadata: None,
+            profile_id: None,
+            status: None,
+            last_modified_by: Some("operator_1".to_string()),
+        }
+        .to_storage_model();
+
+        // The update pushed to the drainer stream carries the operator
+        let drained: diesel_models::payouts::PayoutsUpdate =
+            serde_json::from_str(&serde_json::to_string(&update).unwrap()).unwrap();
+        let updated = drained.apply_changeset(payout.to_storage_model());
+        assert_eq!(updated.last_modified_by.as_deref(), Some("operator_1"));
+
+        // An update which does not name its operator leaves the recorded one as is
+        let updated =
+            data_models::payouts::payouts::PayoutsUpdate::AttemptCountUpdate { attempt_count: 2 }
+                .to_storage_model()
+                .apply_changeset(updated);
+        let read = data_models::payouts::payouts::Payouts::from_storage_model(updated);
+        assert_eq!(read.last_modified_by.as_deref(), Some("operator_1"));
+    }
+
+    #[test]
+    fn status_field_follows_the_full_payout_across_updates() {
+        let key = super::PayoutKvKey::raw("merchant_1", "po_1");
+        let mut payout = stored_payout(0).to_storage_model();
+        for status in [
+            diesel_models::enums::PayoutStatus::Pending,
+            diesel_models::enums::PayoutStatus::Success,
+        ] {
+            payout = data_models::payouts::payouts::PayoutsUpdate::StatusUpdate { status }
+                .to_storage_model()
+                .apply_changeset(payout);
+
+            let fields = super::payout_hash_fields(&key, &payout, None)
+                .unwrap()
+                .into_iter()
+                .collect::<std::collections::HashMap<_, _>>();
+            assert_eq!(fields.len(), 3);
+            let full = serde_json::from_str::<diesel_models::payouts::Payouts>(&fields[&key.field])
+                .unwrap();
+            let status_field = serde_json::from_str::<diesel_models::enums::PayoutStatus>(
+                &fields[&key.status_field(false)],
+            )
+            .unwrap();
+            assert_eq!(status_field, status);
+            assert_eq!(full.status, status_field);
+        }
+    }
+
+    #[test]
+    fn status_of_test_payout_is_not_found_in_live_mode() {
+        let key = super::PayoutKvKey::raw("merchant_1", "po_1");
+        let payout = diesel_models::payouts::Payouts {
+            test_mode: true,
+            ..stored_payout(0).to_storage_model()
+        };
+
+        let fields = super::payout_hash_fields(&key, &payout, None)
+            .unwrap()
+            .into_iter()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert!(fields.contains_key(&key.status_field(true)));
+        assert!(!fields.contains_key(&key.status_field(false)));
+    }
+
+    #[test]
+    fn sequence_field_is_what_the_next_update_expects() {
+        let key = super::PayoutKvKey::raw("merchant_1", "po_1");
+        let read = stored_payout(0);
+        let updated =
+            data_models::payouts::payouts::PayoutsUpdate::RecurringUpdate { recurring: true }
+                .to_storage_model()
+                .apply_changeset(read.clone().to_storage_model());
+
+        let fields = super::payout_hash_fields(&key, &updated, None)
+            .unwrap()
+            .into_iter()
+            .collect::<std::collections::HashMap<_, _>>();
+        let sequence_field = &fields[&key.sequence_field()];
+        // An update prepared from the payout written passes the check, one prepared from the
+        // payout read before it does not
+        let written = data_models::payouts::payouts::Payouts::from_storage_model(updated);
+        assert_eq!(*sequence_field, written.sequence_number.to_string());
+        assert_ne!(*sequence_field, read.sequence_number.to_string());
+    }
+
+    #[test]
+    fn checksum_field_is_the_checksum_of_the_full_payout() {
+        let key = super::PayoutKvKey::raw("merchant_1", "po_1");
+        let checksum = super::PayoutChecksum::new(b"secret", &[]);
+        let fields =
+            super::payout_hash_fields(&key, &stored_payout(0).to_storage_model(), Some(&checksum))
+                .unwrap()
+                .into_iter()
+                .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(fields.len(), 4);
+        checksum
+            .verify(
+                fields[&key.field].as_bytes(),
+                fields[&key.checksum_field()].as_bytes(),
+            )
+            .unwrap();
+    }
+}
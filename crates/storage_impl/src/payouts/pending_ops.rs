@@ -0,0 +1,151 @@
+use data_models::errors::StorageError;
+use diesel_models::kv;
+use error_stack::ResultExt;
+use router_env::{instrument, logger, tracing};
+
+use crate::{redis::kv_store::RedisConnInterface, DatabaseStore, KVRouterStore};
+
+/// The number of payout writes pushed to the drainer streams and not drained yet, across all
+/// merchants, see [`KVRouterStore::has_pending_kv_payout_ops`].
+#[async_trait::async_trait]
+pub trait PendingPayoutOps {
+    async fn pending_payout_ops(&self) -> error_stack::Result<i64, StorageError>;
+
+    async fn add_pending_payout_ops(&self, count: i64) -> error_stack::Result<(), StorageError>;
+}
+
+#[async_trait::async_trait]
+impl<T: DatabaseStore> PendingPayoutOps for KVRouterStore<T> {
+    async fn pending_payout_ops(&self) -> error_stack::Result<i64, StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .get_key::<Option<i64>>(kv::PAYOUT_PENDING_OPS)
+            .await
+            .map(Option::unwrap_or_default)
+            .change_context(StorageError::KVError)
+    }
+
+    async fn add_pending_payout_ops(&self, count: i64) -> error_stack::Result<(), StorageError> {
+        self.get_redis_conn()
+            .change_context(StorageError::KVError)?
+            .increment_key_by(kv::PAYOUT_PENDING_OPS, count)
+            .await
+            .map(|_| ())
+            .change_context(StorageError::KVError)
+    }
+}
+
+/// Counts `count` payout writes pushed to the drainer streams. A failure is logged only, the
+/// writes are then not waited for by [`KVRouterStore::has_pending_kv_payout_ops`].
+pub async fn count_pushed_payout_ops<C>(counter: &C, count: i64)
+where
+    C: PendingPayoutOps + Sync + ?Sized,
+{
+    if let Err(error) = counter.add_pending_payout_ops(count).await {
+        logger::warn!(
+            ?error,
+            count,
+            "Failed to count pending payout KV operations"
+        );
+    }
+}
+
+/// Whether payout writes are pending, erring on the side of reporting them pending when the
+/// counter cannot be read.
+pub async fn has_pending_ops<C>(counter: &C) -> bool
+where
+    C: PendingPayoutOps + Sync + ?Sized,
+{
+    match counter.pending_payout_ops().await {
+        // The drainer may decrement the counter before the push is counted, so that it is
+        // briefly negative
+        Ok(pending) => pending > 0,
+        Err(error) => {
+            logger::error!(?error, "Failed to read the pending payout KV operations");
+            true
+        }
+    }
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Whether any payout write pushed to the drainer streams has not been drained yet, so that
+    /// a shutdown can wait for the drainer to catch up.
+    ///
+    /// Reads a single counter maintained on push and on drain instead of scanning the streams.
+    /// Payout writes are reported pending when the counter cannot be read.
+    #[instrument(skip_all)]
+    pub async fn has_pending_kv_payout_ops(&self) -> bool {
+        has_pending_ops(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeCounter {
+        pending: AtomicI64,
+        unavailable: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl PendingPayoutOps for FakeCounter {
+        async fn pending_payout_ops(&self) -> error_stack::Result<i64, StorageError> {
+            if self.unavailable {
+                Err(StorageError::KVError)?
+            }
+            Ok(self.pending.load(Ordering::SeqCst))
+        }
+
+        async fn add_pending_payout_ops(
+            &self,
+            count: i64,
+        ) -> error_stack::Result<(), StorageError> {
+            if self.unavailable {
+                Err(StorageError::KVError)?
+            }
+            self.pending.fetch_add(count, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn pending_until_drained() {
+        let counter = FakeCounter::default();
+        assert!(!has_pending_ops(&counter).await);
+
+        count_pushed_payout_ops(&counter, 1).await;
+        count_pushed_payout_ops(&counter, 1).await;
+        assert!(has_pending_ops(&counter).await);
+
+        // Drained by the drainer
+        counter.add_pending_payout_ops(-1).await.unwrap();
+        assert!(has_pending_ops(&counter).await);
+        counter.add_pending_payout_ops(-1).await.unwrap();
+        assert!(!has_pending_ops(&counter).await);
+    }
+
+    #[tokio::test]
+    async fn drain_counted_before_push_is_not_pending() {
+        let counter = FakeCounter::default();
+        counter.add_pending_payout_ops(-1).await.unwrap();
+        assert!(!has_pending_ops(&counter).await);
+
+        count_pushed_payout_ops(&counter, 1).await;
+        assert!(!has_pending_ops(&counter).await);
+    }
+
+    #[tokio::test]
+    async fn unreadable_counter_is_pending() {
+        let counter = FakeCounter {
+            unavailable: true,
+            ..Default::default()
+        };
+        assert!(has_pending_ops(&counter).await);
+    }
+}
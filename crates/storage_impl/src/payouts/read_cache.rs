@@ -0,0 +1,237 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use data_models::payouts::payouts::Payouts;
+
+/// Default time for which a payout is served from the [`PayoutReadCache`].
+pub const DEFAULT_PAYOUT_READ_CACHE_TTL_IN_SECS: u64 = 5;
+
+/// A bounded, process-wide cache of recently read payouts, evicting the least recently used
+/// payout once full.
+///
+/// Only writes made through this process are observed: every local write of a payout
+/// invalidates its entry, and a read which started before a local write never populates the
+/// cache, so a cached payout is never older than the last local write. Writes made by other
+/// processes are observed once the entry expires.
+#[derive(Debug)]
+pub struct PayoutReadCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    /// Keys of `entries` by the tick they were last used at, least recently used first.
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    /// Bumped on every invalidation, see [`ReadTicket`].
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    payout: Payouts,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+/// Taken before reading a payout from the store, the payout read is only cached if no payout
+/// was invalidated since the ticket was taken.
+#[derive(Clone, Copy, Debug)]
+pub struct ReadTicket(u64);
+
+impl PayoutReadCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            inner: Mutex::new(CacheState::default()),
+        }
+    }
+
+    fn cache_key(merchant_id: &str, payout_id: &str) -> String {
+        format!("mid_{merchant_id}_po_{payout_id}")
+    }
+
+    pub fn get(&self, merchant_id: &str, payout_id: &str) -> Option<Payouts> {
+        self.get_at(merchant_id, payout_id, Instant::now())
+    }
+
+    fn get_at(&self, merchant_id: &str, payout_id: &str, now: Instant) -> Option<Payouts> {
+        let key = Self::cache_key(merchant_id, payout_id);
+        let mut state = self.inner.lock().ok()?;
+        let state = &mut *state;
+
+        let entry = state.entries.get_mut(&key)?;
+        if entry.expires_at <= now {
+            state.recency.remove(&entry.last_used);
+            state.entries.remove(&key);
+            return None;
+        }
+
+        state.tick += 1;
+        state.recency.remove(&entry.last_used);
+        entry.last_used = state.tick;
+        let payout = entry.payout.clone();
+        state.recency.insert(state.tick, key);
+        Some(payout)
+    }
+
+    pub fn read_ticket(&self) -> ReadTicket {
+        ReadTicket(
+            self.inner
+                .lock()
+                .map(|state| state.generation)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Caches `payout`, read from the store after `ticket` was taken.
+    pub fn populate(&self, ticket: ReadTicket, payout: &Payouts) {
+        self.populate_at(ticket, payout, Instant::now())
+    }
+
+    fn populate_at(&self, ticket: ReadTicket, payout: &Payouts, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        let Ok(mut state) = self.inner.lock() else {
+            return;
+        };
+        // A payout was written locally while this one was being read, it may be stale
+        if state.generation != ticket.0 {
+            return;
+        }
+
+        let key = Self::cache_key(&payout.merchant_id, &payout.payout_id);
+        if let Some(previous) = state.entries.remove(&key) {
+            state.recency.remove(&previous.last_used);
+        }
+        while state.entries.len() >= self.capacity {
+            let Some((_, evicted)) = state.recency.pop_first() else {
+                break;
+            };
+            state.entries.remove(&evicted);
+        }
+
+        state.tick += 1;
+        let tick = state.tick;
+        state.recency.insert(tick, key.clone());
+        state.entries.insert(
+            key,
+            CacheEntry {
+                payout: payout.clone(),
+                expires_at: now + self.ttl,
+                last_used: tick,
+            },
+        );
+    }
+
+    /// Drops the cached payout, to be called after every local write of the payout.
+    pub fn invalidate(&self, merchant_id: &str, payout_id: &str) {
+        let key = Self::cache_key(merchant_id, payout_id);
+        if let Ok(mut state) = self.inner.lock() {
+            state.generation += 1;
+            if let Some(entry) = state.entries.remove(&key) {
+                state.recency.remove(&entry.last_used);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use diesel_models::enums::PayoutStatus;
+
+    use super::*;
+    use crate::DataModelExt;
+
+    fn sample_payout(payout_id: &str) -> Payouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(diesel_models::payouts::PayoutsNew {
+            payout_id: payout_id.to_string(),
+            merchant_id: "merchant_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            ..Default::default()
+        })
+        .unwrap();
+        Payouts::from_storage_model(serde_json::from_value(stored).unwrap())
+    }
+
+    fn cache() -> PayoutReadCache {
+        PayoutReadCache::new(2, Duration::from_secs(5))
+    }
+
+    #[test]
+    fn read_payout_is_served_from_cache() {
+        let cache = cache();
+        let payout = sample_payout("po_1");
+
+        assert!(cache.get("merchant_1", "po_1").is_none());
+        cache.populate(cache.read_ticket(), &payout);
+        assert_eq!(cache.get("merchant_1", "po_1"), Some(payout));
+        assert!(cache.get("merchant_2", "po_1").is_none());
+    }
+
+    #[test]
+    fn update_invalidates_cached_payout() {
+        let cache = cache();
+        cache.populate(cache.read_ticket(), &sample_payout("po_1"));
+
+        cache.invalidate("merchant_1", "po_1");
+        assert!(cache.get("merchant_1", "po_1").is_none());
+    }
+
+    #[test]
+    fn read_started_before_a_write_is_not_cached() {
+        let cache = cache();
+        let ticket = cache.read_ticket();
+        let read_payout = sample_payout("po_1");
+
+        // The payout is updated while the read is in flight
+        cache.invalidate("merchant_1", "po_1");
+        cache.populate(ticket, &read_payout);
+        assert!(cache.get("merchant_1", "po_1").is_none());
+
+        let mut updated_payout = sample_payout("po_1");
+        updated_payout.status = PayoutStatus::Success;
+        cache.populate(cache.read_ticket(), &updated_payout);
+        assert_eq!(cache.get("merchant_1", "po_1"), Some(updated_payout));
+    }
+
+    #[test]
+    fn cached_payout_expires_after_ttl() {
+        let cache = cache();
+        let now = Instant::now();
+        cache.populate_at(cache.read_ticket(), &sample_payout("po_1"), now);
+
+        assert!(cache
+            .get_at("merchant_1", "po_1", now + Duration::from_secs(4))
+            .is_some());
+        assert!(cache
+            .get_at("merchant_1", "po_1", now + Duration::from_secs(5))
+            .is_none());
+    }
+
+    #[test]
+    fn least_recently_used_payout_is_evicted() {
+        let cache = cache();
+        cache.populate(cache.read_ticket(), &sample_payout("po_1"));
+        cache.populate(cache.read_ticket(), &sample_payout("po_2"));
+
+        assert!(cache.get("merchant_1", "po_1").is_some());
+        cache.populate(cache.read_ticket(), &sample_payout("po_3"));
+
+        assert!(cache.get("merchant_1", "po_1").is_some());
+        assert!(cache.get("merchant_1", "po_2").is_none());
+        assert!(cache.get("merchant_1", "po_3").is_some());
+    }
+}
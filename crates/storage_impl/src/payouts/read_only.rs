@@ -0,0 +1,55 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use data_models::errors::StorageError;
+
+/// Rejects payout writes while the database is under maintenance, finders keep working.
+///
+/// Clones share the same switch, so turning it on through any handle of a store takes effect
+/// for every clone of that store.
+#[derive(Clone, Debug, Default)]
+pub struct PayoutReadOnlyMode(Arc<AtomicBool>);
+
+impl PayoutReadOnlyMode {
+    pub fn set(&self, read_only: bool) {
+        self.0.store(read_only, Ordering::Release);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Fails with `ReadOnlyMode` while the switch is on.
+    pub fn ensure_writable(&self) -> error_stack::Result<(), StorageError> {
+        if self.is_active() {
+            return Err(StorageError::ReadOnlyMode.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn writes_are_rejected_only_while_active() {
+        let read_only = PayoutReadOnlyMode::default();
+        assert!(read_only.ensure_writable().is_ok());
+
+        let shared = read_only.clone();
+        shared.set(true);
+        assert!(read_only.is_active());
+        assert!(matches!(
+            read_only.ensure_writable().unwrap_err().current_context(),
+            StorageError::ReadOnlyMode
+        ));
+
+        shared.set(false);
+        assert!(read_only.ensure_writable().is_ok());
+    }
+}
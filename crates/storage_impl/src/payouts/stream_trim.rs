@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use data_models::{errors::StorageError, payouts::ids::MerchantId};
+use diesel_models::{
+    kv::{DBOperation, Insertable, TypedSql, Updateable},
+    payouts::Payouts as DieselPayouts,
+};
+use error_stack::ResultExt;
+use redis_interface::{errors::RedisError, StreamCapKind, StreamCapTrim};
+use router_env::{instrument, logger, tracing};
+
+use crate::{
+    diesel_error_to_data_error, redis::kv_store::RedisConnInterface, utils::pg_connection_read,
+    DatabaseStore, KVRouterStore,
+};
+
+/// Maximum number of entries of a drainer stream inspected by a single
+/// [`KVRouterStore::trim_applied_payout_streams`] run.
+pub const PAYOUT_STREAM_TRIM_READ_COUNT: u64 = 500;
+
+/// A payout write found in a drainer stream entry.
+#[derive(Debug, Eq, PartialEq)]
+struct StreamedPayoutWrite {
+    payout_id: String,
+    /// Sequence number of the payout once the write is applied to the database.
+    sequence_number: i64,
+}
+
+/// Extracts the write of a payout of `merchant_id` held by the drainer stream entry `fields`,
+/// `None` if the entry holds anything else.
+fn streamed_payout_write(
+    merchant_id: &str,
+    fields: &HashMap<String, String>,
+) -> Option<StreamedPayoutWrite> {
+    let typed_sql = fields
+        .get("typed_sql")
+        .and_then(|typed_sql| serde_json::from_str::<TypedSql>(typed_sql).ok())?;
+    match typed_sql.op {
+        DBOperation::Insert {
+            insertable: Insertable::Payouts(new),
+        } if new.merchant_id == merchant_id => Some(StreamedPayoutWrite {
+            payout_id: new.payout_id,
+            sequence_number: new.sequence_number,
+        }),
+        DBOperation::Update {
+            updatable: Updateable::PayoutsUpdate(update),
+        } if update.orig.merchant_id == merchant_id => Some(StreamedPayoutWrite {
+            payout_id: update.orig.payout_id,
+            sequence_number: update.orig.sequence_number + 1,
+        }),
+        _ => None,
+    }
+}
+
+/// Whether `write` is reflected by `stored`, the payout as read from the database.
+fn is_drained(write: &StreamedPayoutWrite, stored: Option<&DieselPayouts>) -> bool {
+    stored.is_some_and(|stored| stored.sequence_number >= write.sequence_number)
+}
+
+/// Number of entries to trim from the head of a stream of `stream_length` entries, given
+/// whether each of the oldest entries is drained, oldest first.
+///
+/// Only the drained entries preceding the first undrained one are trimmed, and the last
+/// `keep_last` entries of the stream are always kept.
+fn entries_to_trim(drained: &[bool], stream_length: usize, keep_last: usize) -> usize {
+    let drained_prefix = drained.iter().take_while(|drained| **drained).count();
+    drained_prefix.min(stream_length.saturating_sub(keep_last))
+}
+
+impl<T: DatabaseStore> KVRouterStore<T> {
+    /// Trims the drainer streams of the entries writing payouts of the merchant which have
+    /// been applied to the database, keeping at least the last `keep_last` entries of each
+    /// stream. Returns the number of entries trimmed.
+    ///
+    /// An entry is only trimmed along with every entry before it, so trimming a stream stops at
+    /// its first entry which is not known to be drained, including entries of other merchants
+    /// or tables.
+    #[instrument(skip_all)]
+    pub async fn trim_applied_payout_streams(
+        &self,
+        merchant_id: &MerchantId,
+        keep_last: usize,
+    ) -> error_stack::Result<usize, StorageError> {
+        let redis_conn = self
+            .get_redis_conn()
+            .change_context(StorageError::KVError)?;
+        let mut trimmed = 0;
+        for partition in 0..self.drainer_num_partitions {
+            let stream_name = self.get_drainer_stream_name(&format!("shard_{partition}"));
+            let entries = match redis_conn
+                .stream_read_entries(
+                    stream_name.as_str(),
+                    "0-0",
+                    Some(PAYOUT_STREAM_TRIM_READ_COUNT),
+                )
+                .await
+            {
+                Ok(mut streams) => streams.remove(&stream_name).unwrap_or_default(),
+                Err(error)
+                    if matches!(
+                        error.current_context(),
+                        RedisError::StreamEmptyOrNotAvailable
+                    ) =>
+                {
+                    continue
+                }
+                Err(error) => return Err(error.change_context(StorageError::KVError)),
+            };
+
+            let mut drained = Vec::with_capacity(entries.len());
+            for (_, fields) in &entries {
+                let entry_drained = match streamed_payout_write(merchant_id, fields) {
+                    Some(write) => {
+                        let stored = self.find_stored_payout(merchant_id, &write).await?;
+                        is_drained(&write, stored.as_ref())
+                    }
+                    None => false,
+                };
+                drained.push(entry_drained);
+                if !entry_drained {
+                    break;
+                }
+            }
+
+            let stream_length = redis_conn
+                .stream_get_length(stream_name.as_str())
+                .await
+                .change_context(StorageError::KVError)?;
+            let trim_count = entries_to_trim(&drained, stream_length, keep_last);
+            let Some((last_trimmed_id, _)) = trim_count
+                .checked_sub(1)
+                .and_then(|index| entries.get(index))
+            else {
+                continue;
+            };
+
+            // XTRIM removes the entries below the given id, the entry with the id itself is
+            // deleted separately
+            redis_conn
+                .stream_trim_entries(
+                    &stream_name,
+                    (
+                        StreamCapKind::MinID,
+                        StreamCapTrim::Exact,
+                        last_trimmed_id.as_str(),
+                    ),
+                )
+                .await
+                .change_context(StorageError::KVError)?;
+            redis_conn
+                .stream_delete_entries(&stream_name, last_trimmed_id.as_str())
+                .await
+                .change_context(StorageError::KVError)?;
+
+            logger::debug!(%stream_name, trim_count, "Trimmed applied payout stream entries");
+            trimmed += trim_count;
+        }
+        Ok(trimmed)
+    }
+
+    async fn find_stored_payout(
+        &self,
+        merchant_id: &str,
+        write: &StreamedPayoutWrite,
+    ) -> error_stack::Result<Option<DieselPayouts>, StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayouts::find_optional_by_merchant_id_payout_id(&conn, merchant_id, &write.payout_id)
+            .await
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use diesel_models::{
+        kv::PayoutsUpdateMems,
+        payouts::{PayoutsNew as DieselPayoutsNew, PayoutsUpdate as DieselPayoutsUpdate},
+    };
+
+    use super::*;
+
+    fn stored_payout(payout_id: &str, sequence_number: i64) -> DieselPayouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(DieselPayoutsNew {
+            payout_id: payout_id.to_string(),
+            merchant_id: "merchant_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            sequence_number,
+            ..Default::default()
+        })
+        .unwrap();
+        serde_json::from_value(stored).unwrap()
+    }
+
+    fn stream_entry(op: DBOperation) -> HashMap<String, String> {
+        TypedSql { op }
+            .to_field_value_pairs("req_1".to_string(), "mid_merchant_1_po_1".to_string())
+            .unwrap()
+            .into_iter()
+            .map(|(field, value)| (field.to_string(), value))
+            .collect()
+    }
+
+    fn insert_entry(payout_id: &str) -> HashMap<String, String> {
+        stream_entry(DBOperation::Insert {
+            insertable: Insertable::Payouts(DieselPayoutsNew {
+                payout_id: payout_id.to_string(),
+                merchant_id: "merchant_1".to_string(),
+                ..Default::default()
+            }),
+        })
+    }
+
+    fn update_entry(orig: DieselPayouts) -> HashMap<String, String> {
+        stream_entry(DBOperation::Update {
+            updatable: Updateable::PayoutsUpdate(PayoutsUpdateMems {
+                orig,
+                update_data: DieselPayoutsUpdate::RecurringUpdate { recurring: true },
+            }),
+        })
+    }
+
+    #[test]
+    fn payout_writes_of_the_merchant_are_recognized() {
+        assert_eq!(
+            streamed_payout_write("merchant_1", &insert_entry("po_1")),
+            Some(StreamedPayoutWrite {
+                payout_id: "po_1".to_string(),
+                sequence_number: 0,
+            })
+        );
+        assert_eq!(
+            streamed_payout_write("merchant_1", &update_entry(stored_payout("po_1", 3))),
+            Some(StreamedPayoutWrite {
+                payout_id: "po_1".to_string(),
+                sequence_number: 4,
+            })
+        );
+        assert!(streamed_payout_write("merchant_2", &insert_entry("po_1")).is_none());
+        assert!(streamed_payout_write("merchant_1", &HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn write_is_drained_once_reflected_in_the_database() {
+        let update =
+            streamed_payout_write("merchant_1", &update_entry(stored_payout("po_1", 3))).unwrap();
+
+        assert!(!is_drained(&update, None));
+        assert!(!is_drained(&update, Some(&stored_payout("po_1", 3))));
+        assert!(is_drained(&update, Some(&stored_payout("po_1", 4))));
+        assert!(is_drained(&update, Some(&stored_payout("po_1", 5))));
+    }
+
+    #[test]
+    fn only_drained_entries_are_trimmed() {
+        let database = [stored_payout("po_1", 0), stored_payout("po_2", 1)];
+        let entries = [
+            insert_entry("po_1"),
+            insert_entry("po_2"),
+            update_entry(stored_payout("po_2", 0)),
+            // Not drained yet, neither it nor the entries after it may be trimmed
+            update_entry(stored_payout("po_2", 1)),
+            insert_entry("po_3"),
+        ];
+        let drained = entries
+            .iter()
+            .map(|fields| {
+                let write = streamed_payout_write("merchant_1", fields).unwrap();
+                let stored = database
+                    .iter()
+                    .find(|payout| payout.payout_id == write.payout_id);
+                is_drained(&write, stored)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(drained, [true, true, true, false, false]);
+        assert_eq!(entries_to_trim(&drained, entries.len(), 0), 3);
+        assert_eq!(entries_to_trim(&drained, entries.len(), 3), 2);
+        assert_eq!(entries_to_trim(&drained, entries.len(), 10), 0);
+        assert_eq!(entries_to_trim(&[false, true], 2, 0), 0);
+    }
+}
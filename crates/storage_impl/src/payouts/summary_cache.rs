@@ -0,0 +1,201 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use data_models::payouts::{ids::PayoutId, payouts::Payouts};
+
+/// Default time for which a payout is served from the [`PayoutSummaryCache`].
+pub const DEFAULT_PAYOUT_SUMMARY_CACHE_TTL_IN_SECS: u64 = 2;
+
+/// A bounded, process-wide cache of the payouts read in batches from KV, such as the payouts
+/// summarized by list pages.
+///
+/// Payouts are cached under the version of their merchant's payout list at which they were
+/// read, see [`super::list_version`]. Every write of a payout bumps that version once persisted,
+/// after which the payouts cached under the previous versions are no longer served, whichever
+/// process made the write. The TTL bounds the staleness left by a failed bump.
+#[derive(Debug)]
+pub struct PayoutSummaryCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<HashMap<SummaryCacheKey, CacheEntry>>,
+}
+
+/// `(merchant_id, payout_id, list_version)`
+type SummaryCacheKey = (String, String, i64);
+
+#[derive(Debug)]
+struct CacheEntry {
+    payout: Payouts,
+    expires_at: Instant,
+}
+
+impl PayoutSummaryCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached payouts among `payout_ids`, read at `list_version` of the merchant's
+    /// payout list, keyed by payout id.
+    pub fn get_many(
+        &self,
+        merchant_id: &str,
+        list_version: i64,
+        payout_ids: &[PayoutId],
+    ) -> HashMap<PayoutId, Payouts> {
+        self.get_many_at(merchant_id, list_version, payout_ids, Instant::now())
+    }
+
+    fn get_many_at(
+        &self,
+        merchant_id: &str,
+        list_version: i64,
+        payout_ids: &[PayoutId],
+        now: Instant,
+    ) -> HashMap<PayoutId, Payouts> {
+        let Ok(entries) = self.entries.lock() else {
+            return HashMap::new();
+        };
+        payout_ids
+            .iter()
+            .filter_map(|payout_id| {
+                let key = (merchant_id.to_owned(), payout_id.to_string(), list_version);
+                entries
+                    .get(&key)
+                    .filter(|entry| entry.expires_at > now)
+                    .map(|entry| (payout_id.clone(), entry.payout.clone()))
+            })
+            .collect()
+    }
+
+    /// Caches `payouts` of the merchant, read after `list_version` of its payout list was read.
+    pub fn populate<'a>(
+        &self,
+        merchant_id: &str,
+        list_version: i64,
+        payouts: impl IntoIterator<Item = &'a Payouts>,
+    ) {
+        self.populate_at(merchant_id, list_version, payouts, Instant::now())
+    }
+
+    fn populate_at<'a>(
+        &self,
+        merchant_id: &str,
+        list_version: i64,
+        payouts: impl IntoIterator<Item = &'a Payouts>,
+        now: Instant,
+    ) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        // Entries of previous list versions are never served again, they only wait to expire
+        entries.retain(|(cached_merchant_id, _, cached_version), entry| {
+            entry.expires_at > now
+                && !(cached_merchant_id == merchant_id && *cached_version < list_version)
+        });
+
+        for payout in payouts {
+            if entries.len() >= self.capacity {
+                break;
+            }
+            entries.insert(
+                (
+                    merchant_id.to_owned(),
+                    payout.payout_id.clone(),
+                    list_version,
+                ),
+                CacheEntry {
+                    payout: payout.clone(),
+                    expires_at: now + self.ttl,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::DataModelExt;
+
+    fn sample_payout(payout_id: &str) -> Payouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(diesel_models::payouts::PayoutsNew {
+            payout_id: payout_id.to_string(),
+            merchant_id: "merchant_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            ..Default::default()
+        })
+        .unwrap();
+        Payouts::from_storage_model(serde_json::from_value(stored).unwrap())
+    }
+
+    fn payout_ids() -> Vec<PayoutId> {
+        vec![PayoutId::from("po_1"), PayoutId::from("po_2")]
+    }
+
+    #[test]
+    fn payouts_read_at_the_current_version_are_served_from_cache() {
+        let cache = PayoutSummaryCache::new(10, Duration::from_secs(2));
+        let payout = sample_payout("po_1");
+        cache.populate("merchant_1", 3, [&payout]);
+
+        let cached = cache.get_many("merchant_1", 3, &payout_ids());
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached.get("po_1"), Some(&payout));
+        assert!(cache.get_many("merchant_2", 3, &payout_ids()).is_empty());
+    }
+
+    #[test]
+    fn version_bump_invalidates_cached_payouts() {
+        let cache = PayoutSummaryCache::new(10, Duration::from_secs(2));
+        cache.populate("merchant_1", 3, [&sample_payout("po_1")]);
+
+        // A payout of the merchant was written, bumping its list version
+        assert!(cache.get_many("merchant_1", 4, &payout_ids()).is_empty());
+
+        cache.populate("merchant_1", 4, [&sample_payout("po_2")]);
+        assert!(cache.get_many("merchant_1", 3, &payout_ids()).is_empty());
+        assert_eq!(cache.get_many("merchant_1", 4, &payout_ids()).len(), 1);
+    }
+
+    #[test]
+    fn cached_payouts_expire_after_ttl() {
+        let cache = PayoutSummaryCache::new(10, Duration::from_secs(2));
+        let now = Instant::now();
+        cache.populate_at("merchant_1", 3, [&sample_payout("po_1")], now);
+
+        assert_eq!(
+            cache
+                .get_many_at("merchant_1", 3, &payout_ids(), now + Duration::from_secs(1))
+                .len(),
+            1
+        );
+        assert!(cache
+            .get_many_at("merchant_1", 3, &payout_ids(), now + Duration::from_secs(2))
+            .is_empty());
+    }
+
+    #[test]
+    fn full_cache_keeps_its_entries() {
+        let cache = PayoutSummaryCache::new(1, Duration::from_secs(2));
+        cache.populate(
+            "merchant_1",
+            3,
+            [&sample_payout("po_1"), &sample_payout("po_2")],
+        );
+
+        let cached = cache.get_many("merchant_1", 3, &payout_ids());
+        assert_eq!(cached.len(), 1);
+        assert!(cached.contains_key("po_1"));
+    }
+}
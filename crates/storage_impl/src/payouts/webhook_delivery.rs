@@ -0,0 +1,462 @@
+use common_utils::ext_traits::Encode;
+use data_models::{
+    errors,
+    payouts::webhook_delivery::{
+        PayoutWebhookDelivery, PayoutWebhookDeliveryInterface, PayoutWebhookDeliveryNew,
+        PayoutWebhookDeliveryUpdate,
+    },
+};
+use diesel_models::{
+    enums::MerchantStorageScheme,
+    kv,
+    payout_webhook_delivery::{
+        PayoutWebhookDelivery as DieselPayoutWebhookDelivery,
+        PayoutWebhookDeliveryNew as DieselPayoutWebhookDeliveryNew,
+        PayoutWebhookDeliveryUpdate as DieselPayoutWebhookDeliveryUpdate,
+    },
+};
+use error_stack::{IntoReport, ResultExt};
+use redis_interface::{errors::RedisError, HsetnxReply};
+use router_env::{instrument, tracing};
+use time::PrimitiveDateTime;
+
+use crate::{
+    diesel_error_to_data_error,
+    errors::RedisErrorExt,
+    redis::{
+        kv_store::{kv_wrapper, KvOperation},
+        scheme_override::ensure_scheme_supported,
+    },
+    utils::{self, pg_connection_read, pg_connection_write},
+    DataModelExt, DatabaseStore, KVRouterStore,
+};
+
+/// The KV hash holding the deliveries of the notifications about a payout.
+fn webhook_delivery_key(merchant_id: &str, payout_id: &str) -> String {
+    format!("mid_{merchant_id}_powd_{payout_id}")
+}
+
+/// The field of the KV hash of [`webhook_delivery_key`] holding a delivery.
+fn webhook_delivery_field(delivery_id: &str) -> String {
+    format!("powd_{delivery_id}")
+}
+
+#[async_trait::async_trait]
+impl<T: DatabaseStore> PayoutWebhookDeliveryInterface for KVRouterStore<T> {
+    #[instrument(skip_all)]
+    async fn insert_webhook_delivery(
+        &self,
+        new: PayoutWebhookDeliveryNew,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<PayoutWebhookDelivery, errors::StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(&new.merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                self.router_store
+                    .insert_webhook_delivery(new, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::RedisKv => {
+                let key = webhook_delivery_key(&new.merchant_id, &new.payout_id);
+                let field = webhook_delivery_field(&new.delivery_id);
+                let created_delivery = new.clone().into_delivery();
+
+                let redis_entry = kv::TypedSql {
+                    op: kv::DBOperation::Insert {
+                        insertable: kv::Insertable::PayoutWebhookDelivery(new.to_storage_model()),
+                    },
+                };
+
+                match kv_wrapper::<DieselPayoutWebhookDelivery, _, _>(
+                    self,
+                    KvOperation::<DieselPayoutWebhookDelivery>::HSetNx(
+                        &field,
+                        &created_delivery.clone().to_storage_model(),
+                        redis_entry,
+                    ),
+                    &key,
+                )
+                .await
+                .map_err(|err| err.to_redis_failed_response(&key))?
+                .try_into_hsetnx()
+                {
+                    Ok(HsetnxReply::KeyNotSet) => Err(errors::StorageError::DuplicateValue {
+                        entity: "payout webhook delivery",
+                        key: Some(key),
+                    })
+                    .into_report(),
+                    Ok(HsetnxReply::KeySet) => Ok(created_delivery),
+                    Err(error) => Err(error.change_context(errors::StorageError::KVError)),
+                }
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn update_webhook_delivery(
+        &self,
+        this: &PayoutWebhookDelivery,
+        delivery_update: PayoutWebhookDeliveryUpdate,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<PayoutWebhookDelivery, errors::StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(&this.merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                self.router_store
+                    .update_webhook_delivery(this, delivery_update, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::RedisKv => {
+                let key = webhook_delivery_key(&this.merchant_id, &this.payout_id);
+                let field = webhook_delivery_field(&this.delivery_id);
+
+                let diesel_delivery_update = delivery_update.to_storage_model();
+                let origin_diesel_delivery = this.clone().to_storage_model();
+                let diesel_delivery = diesel_delivery_update
+                    .clone()
+                    .apply_changeset(origin_diesel_delivery.clone());
+
+                let redis_value = diesel_delivery
+                    .encode_to_string_of_json()
+                    .change_context(errors::StorageError::SerializationFailed)?;
+
+                let redis_entry = kv::TypedSql {
+                    op: kv::DBOperation::Update {
+                        updatable: kv::Updateable::PayoutWebhookDeliveryUpdate(
+                            kv::PayoutWebhookDeliveryUpdateMems {
+                                orig: origin_diesel_delivery,
+                                update_data: diesel_delivery_update,
+                            },
+                        ),
+                    },
+                };
+
+                kv_wrapper::<(), _, _>(
+                    self,
+                    KvOperation::<DieselPayoutWebhookDelivery>::Hset(
+                        (&field, redis_value),
+                        redis_entry,
+                    ),
+                    &key,
+                )
+                .await
+                .map_err(|err| err.to_redis_failed_response(&key))?
+                .try_into_hset()
+                .change_context(errors::StorageError::KVError)?;
+
+                Ok(PayoutWebhookDelivery::from_storage_model(diesel_delivery))
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn list_webhook_deliveries(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutWebhookDelivery>, errors::StorageError> {
+        let storage_scheme = self
+            .resolve_storage_scheme(merchant_id, storage_scheme)
+            .await?
+            .scheme();
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => {
+                self.router_store
+                    .list_webhook_deliveries(merchant_id, payout_id, storage_scheme)
+                    .await
+            }
+            MerchantStorageScheme::RedisKv => {
+                // Deliveries made before the merchant moved to KV, or drained since, are only
+                // found in the database
+                let key = webhook_delivery_key(merchant_id, payout_id);
+                let held = match kv_wrapper::<DieselPayoutWebhookDelivery, _, _>(
+                    self,
+                    KvOperation::<DieselPayoutWebhookDelivery>::Scan("powd_*"),
+                    &key,
+                )
+                .await
+                {
+                    Ok(result) => result
+                        .try_into_scan()
+                        .change_context(errors::StorageError::KVError)?
+                        .into_iter()
+                        .map(PayoutWebhookDelivery::from_storage_model)
+                        .collect(),
+                    Err(error)
+                        if matches!(
+                            error.current_context(),
+                            RedisError::NotFound | RedisError::CircuitOpen
+                        ) =>
+                    {
+                        Vec::new()
+                    }
+                    Err(error) => return Err(error.to_redis_failed_response(&key)),
+                };
+                let stored = self
+                    .router_store
+                    .list_webhook_deliveries(merchant_id, payout_id, storage_scheme)
+                    .await?;
+                Ok(merge_deliveries(held, stored))
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn find_pending_webhook_deliveries(
+        &self,
+        older_than: PrimitiveDateTime,
+        limit: i64,
+        storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutWebhookDelivery>, errors::StorageError> {
+        ensure_scheme_supported(self, storage_scheme)?;
+        let stored = self
+            .router_store
+            .find_pending_webhook_deliveries(older_than, limit, storage_scheme)
+            .await?;
+        match storage_scheme {
+            MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => Ok(stored),
+            MerchantStorageScheme::RedisKv => {
+                let mut deliveries = Vec::with_capacity(stored.len());
+                for stored in stored {
+                    let key = webhook_delivery_key(&stored.merchant_id, &stored.payout_id);
+                    let field = webhook_delivery_field(&stored.delivery_id);
+                    let current = Box::pin(utils::try_redis_get_else_try_database_get(
+                        async {
+                            kv_wrapper(
+                                self,
+                                KvOperation::<DieselPayoutWebhookDelivery>::HGet(&field),
+                                &key,
+                            )
+                            .await?
+                            .try_into_hget()
+                            .map(PayoutWebhookDelivery::from_storage_model)
+                        },
+                        || async { Ok(stored) },
+                    ))
+                    .await?;
+                    if current.is_due(older_than) {
+                        deliveries.push(current);
+                    }
+                }
+                PayoutWebhookDelivery::sort_for_retry(&mut deliveries);
+                Ok(deliveries)
+            }
+        }
+    }
+}
+
+/// Merges the deliveries of a payout held in KV with the ones stored in the database, oldest
+/// first. The KV copy of a delivery found in both is the current one.
+fn merge_deliveries(
+    held: Vec<PayoutWebhookDelivery>,
+    stored: Vec<PayoutWebhookDelivery>,
+) -> Vec<PayoutWebhookDelivery> {
+    let mut deliveries = stored
+        .into_iter()
+        .filter(|stored| {
+            !held
+                .iter()
+                .any(|held| held.delivery_id == stored.delivery_id)
+        })
+        .chain(held)
+        .collect::<Vec<_>>();
+    deliveries.sort_by(|a, b| {
+        a.created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.delivery_id.cmp(&b.delivery_id))
+    });
+    deliveries
+}
+
+#[async_trait::async_trait]
+impl<T: DatabaseStore> PayoutWebhookDeliveryInterface for crate::RouterStore<T> {
+    #[instrument(skip_all)]
+    async fn insert_webhook_delivery(
+        &self,
+        new: PayoutWebhookDeliveryNew,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<PayoutWebhookDelivery, errors::StorageError> {
+        let conn = pg_connection_write(self).await?;
+        new.to_storage_model()
+            .insert(&conn)
+            .await
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+            .map(PayoutWebhookDelivery::from_storage_model)
+    }
+
+    #[instrument(skip_all)]
+    async fn update_webhook_delivery(
+        &self,
+        this: &PayoutWebhookDelivery,
+        delivery_update: PayoutWebhookDeliveryUpdate,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<PayoutWebhookDelivery, errors::StorageError> {
+        let conn = pg_connection_write(self).await?;
+        this.clone()
+            .to_storage_model()
+            .update(&conn, delivery_update.to_storage_model())
+            .await
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+            .map(PayoutWebhookDelivery::from_storage_model)
+    }
+
+    #[instrument(skip_all)]
+    async fn list_webhook_deliveries(
+        &self,
+        merchant_id: &str,
+        payout_id: &str,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutWebhookDelivery>, errors::StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayoutWebhookDelivery::filter_by_merchant_id_payout_id(&conn, merchant_id, payout_id)
+            .await
+            .map(|deliveries| {
+                deliveries
+                    .into_iter()
+                    .map(PayoutWebhookDelivery::from_storage_model)
+                    .collect()
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+
+    #[instrument(skip_all)]
+    async fn find_pending_webhook_deliveries(
+        &self,
+        older_than: PrimitiveDateTime,
+        limit: i64,
+        _storage_scheme: MerchantStorageScheme,
+    ) -> error_stack::Result<Vec<PayoutWebhookDelivery>, errors::StorageError> {
+        let conn = pg_connection_read(self).await?;
+        DieselPayoutWebhookDelivery::filter_pending(&conn, older_than, limit)
+            .await
+            .map(|deliveries| {
+                deliveries
+                    .into_iter()
+                    .map(PayoutWebhookDelivery::from_storage_model)
+                    .collect()
+            })
+            .map_err(|er| {
+                let new_err = diesel_error_to_data_error(er.current_context());
+                er.change_context(new_err)
+            })
+    }
+}
+
+impl DataModelExt for PayoutWebhookDelivery {
+    type StorageModel = DieselPayoutWebhookDelivery;
+
+    fn to_storage_model(self) -> Self::StorageModel {
+        DieselPayoutWebhookDelivery {
+            delivery_id: self.delivery_id,
+            merchant_id: self.merchant_id,
+            payout_id: self.payout_id,
+            target_url: self.target_url,
+            attempt_count: self.attempt_count,
+            last_status_code: self.last_status_code,
+            next_retry_at: self.next_retry_at,
+            delivered_at: self.delivered_at,
+            created_at: self.created_at,
+            last_modified_at: self.last_modified_at,
+        }
+    }
+
+    fn from_storage_model(storage_model: Self::StorageModel) -> Self {
+        Self {
+            delivery_id: storage_model.delivery_id,
+            merchant_id: storage_model.merchant_id,
+            payout_id: storage_model.payout_id,
+            target_url: storage_model.target_url,
+            attempt_count: storage_model.attempt_count,
+            last_status_code: storage_model.last_status_code,
+            next_retry_at: storage_model.next_retry_at,
+            delivered_at: storage_model.delivered_at,
+            created_at: storage_model.created_at,
+            last_modified_at: storage_model.last_modified_at,
+        }
+    }
+}
+
+impl DataModelExt for PayoutWebhookDeliveryNew {
+    type StorageModel = DieselPayoutWebhookDeliveryNew;
+
+    fn to_storage_model(self) -> Self::StorageModel {
+        DieselPayoutWebhookDeliveryNew {
+            delivery_id: self.delivery_id,
+            merchant_id: self.merchant_id,
+            payout_id: self.payout_id,
+            target_url: self.target_url,
+            next_retry_at: self.next_retry_at,
+            created_at: self.created_at,
+            last_modified_at: self.created_at,
+        }
+    }
+
+    fn from_storage_model(storage_model: Self::StorageModel) -> Self {
+        Self {
+            delivery_id: storage_model.delivery_id,
+            merchant_id: storage_model.merchant_id,
+            payout_id: storage_model.payout_id,
+            target_url: storage_model.target_url,
+            next_retry_at: storage_model.next_retry_at,
+            created_at: storage_model.created_at,
+        }
+    }
+}
+
+impl DataModelExt for PayoutWebhookDeliveryUpdate {
+    type StorageModel = DieselPayoutWebhookDeliveryUpdate;
+
+    fn to_storage_model(self) -> Self::StorageModel {
+        match self {
+            Self::AttemptFailed {
+                status_code,
+                next_retry_at,
+            } => DieselPayoutWebhookDeliveryUpdate::AttemptFailed {
+                status_code,
+                next_retry_at,
+            },
+            Self::Delivered {
+                status_code,
+                delivered_at,
+            } => DieselPayoutWebhookDeliveryUpdate::Delivered {
+                status_code,
+                delivered_at,
+            },
+        }
+    }
+
+    fn from_storage_model(storage_model: Self::StorageModel) -> Self {
+        match storage_model {
+            DieselPayoutWebhookDeliveryUpdate::AttemptFailed {
+                status_code,
+                next_retry_at,
+            } => Self::AttemptFailed {
+                status_code,
+                next_retry_at,
+            },
+            DieselPayoutWebhookDeliveryUpdate::Delivered {
+                status_code,
+                delivered_at,
+            } => Self::Delivered {
+                status_code,
+                delivered_at,
+            },
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Read-back of the payouts written to KV, enabled with the `kv_write_check` feature to catch
+//! serialization and merge bugs in debug and test builds. Each checked write costs an extra
+//! Redis read, so the feature is off by default.
+
+use data_models::payouts::payouts::Payouts;
+use router_env::logger;
+
+use super::dual_write::{compare_shadow_read, PayoutMirror, ShadowReadOutcome};
+
+/// Re-reads the KV hash of `written`, the payout returned by a KV write, and logs an error if
+/// the hash does not hold that same payout.
+///
+/// The write has already happened, so a mismatch is only reported, never returned as an error.
+pub async fn check_kv_write<M>(store: &M, written: &Payouts) -> ShadowReadOutcome
+where
+    M: PayoutMirror + Sync + ?Sized,
+{
+    let read_back = store
+        .shadow_read_payout(&written.merchant_id, &written.payout_id)
+        .await;
+    let outcome = compare_shadow_read(written, read_back);
+    if outcome != ShadowReadOutcome::Consistent {
+        logger::error!(
+            ?outcome,
+            merchant_id = %written.merchant_id,
+            payout_id = %written.payout_id,
+            "KV hash of the payout does not hold the payout returned by the write"
+        );
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::sync::Mutex;
+
+    use data_models::errors::StorageError;
+    use diesel_models::{
+        enums::PayoutStatus,
+        payouts::{Payouts as DieselPayouts, PayoutsUpdate as DieselPayoutsUpdate},
+    };
+
+    use super::*;
+    use crate::DataModelExt;
+
+    /// Holds the hashes of payouts, written from a changeset merged onto the origin payout.
+    #[derive(Default)]
+    struct FakeKv {
+        hashes: Mutex<Vec<DieselPayouts>>,
+    }
+
+    impl FakeKv {
+        /// Writes `update` merged onto `origin` the way the KV update does, applying `corrupt` to
+        /// the merged payout before it is stored, and returns the payout the write returns.
+        fn write_update(
+            &self,
+            origin: &Payouts,
+            update: DieselPayoutsUpdate,
+            corrupt: impl FnOnce(&mut DieselPayouts),
+        ) -> Payouts {
+            let merged = update.apply_changeset(origin.clone().to_storage_model());
+            let mut stored = merged.clone();
+            corrupt(&mut stored);
+            let serialized = serde_json::to_vec(&stored).unwrap();
+            self.hashes
+                .lock()
+                .unwrap()
+                .push(serde_json::from_slice(&serialized).unwrap());
+            Payouts::from_storage_model(merged)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PayoutMirror for FakeKv {
+        async fn mirror_payout(&self, payout: &Payouts) -> error_stack::Result<(), StorageError> {
+            self.hashes
+                .lock()
+                .unwrap()
+                .push(payout.clone().to_storage_model());
+            Ok(())
+        }
+
+        async fn shadow_read_payout(
+            &self,
+            _merchant_id: &str,
+            payout_id: &str,
+        ) -> error_stack::Result<Option<Payouts>, StorageError> {
+            Ok(self
+                .hashes
+                .lock()
+                .unwrap()
+                .iter()
+                .rev()
+                .find(|payout| payout.payout_id == payout_id)
+                .cloned()
+                .map(Payouts::from_storage_model))
+        }
+    }
+
+    fn sample_payout() -> Payouts {
+        let now = common_utils::date_time::now();
+        let stored = serde_json::to_value(diesel_models::payouts::PayoutsNew {
+            payout_id: "po_1".to_string(),
+            merchant_id: "merchant_1".to_string(),
+            created_at: Some(now),
+            last_modified_at: Some(now),
+            ..Default::default()
+        })
+        .unwrap();
+        Payouts::from_storage_model(serde_json::from_value(stored).unwrap())
+    }
+
+    fn status_update() -> DieselPayoutsUpdate {
+        DieselPayoutsUpdate::StatusUpdate {
+            status: PayoutStatus::Success,
+        }
+    }
+
+    #[tokio::test]
+    async fn faithful_write_reads_back_consistently() {
+        let kv = FakeKv::default();
+        let written = kv.write_update(&sample_payout(), status_update(), |_| {});
+
+        assert_eq!(
+            check_kv_write(&kv, &written).await,
+            ShadowReadOutcome::Consistent
+        );
+    }
+
+    #[tokio::test]
+    async fn corrupted_merge_is_caught_by_the_read_back() {
+        let kv = FakeKv::default();
+        let origin = sample_payout();
+        // The merge loses the status change on its way to the hash
+        let written = kv.write_update(&origin, status_update(), |stored| {
+            stored.status = origin.status;
+        });
+
+        assert_eq!(
+            check_kv_write(&kv, &written).await,
+            ShadowReadOutcome::Diverged(vec!["status"])
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_hash_is_caught_by_the_read_back() {
+        let written = sample_payout();
+
+        assert_eq!(
+            check_kv_write(&FakeKv::default(), &written).await,
+            ShadowReadOutcome::MissingInKv
+        );
+    }
+}
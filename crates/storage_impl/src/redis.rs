@@ -1,6 +1,9 @@
 pub mod cache;
+pub mod circuit_breaker;
 pub mod kv_store;
 pub mod pub_sub;
+pub mod read_after_write;
+pub mod scheme_override;
 
 use std::sync::{atomic, Arc};
 
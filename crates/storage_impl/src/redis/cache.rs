@@ -33,6 +33,9 @@ const CACHE_TTI: u64 = 10 * 60;
 /// Max Capacity of Cache in MB
 const MAX_CAPACITY: u64 = 30;
 
+/// Time to live and time to idle 30 secs
+const SHORT_CACHE_TTL: u64 = 30;
+
 /// Config Cache with time_to_live as 30 mins and time_to_idle as 10 mins.
 pub static CONFIG_CACHE: Lazy<Cache> = Lazy::new(|| Cache::new(CACHE_TTL, CACHE_TTI, None));
 
@@ -40,6 +43,11 @@ pub static CONFIG_CACHE: Lazy<Cache> = Lazy::new(|| Cache::new(CACHE_TTL, CACHE_
 pub static ACCOUNTS_CACHE: Lazy<Cache> =
     Lazy::new(|| Cache::new(CACHE_TTL, CACHE_TTI, Some(MAX_CAPACITY)));
 
+/// Storage scheme override cache with time_to_live and time_to_idle as 30 secs, so that
+/// overrides take effect shortly after being changed.
+pub static STORAGE_SCHEME_OVERRIDE_CACHE: Lazy<Cache> =
+    Lazy::new(|| Cache::new(SHORT_CACHE_TTL, SHORT_CACHE_TTL, None));
+
 /// Trait which defines the behaviour of types that's gonna be stored in Cache
 pub trait Cacheable: Any + Send + Sync + DynClone {
     fn as_any(&self) -> &dyn Any;
@@ -0,0 +1,261 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use redis_interface::errors::RedisError;
+use router_env::logger;
+
+/// Time for which KV operations are short-circuited once the breaker opened, if not configured.
+pub const DEFAULT_CIRCUIT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// The state of a [`RedisCircuitBreaker`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CircuitState {
+    /// Redis is attempted by every KV operation
+    Closed,
+    /// Redis is failing, KV operations are short-circuited until the cooldown elapsed
+    Open,
+    /// The cooldown elapsed, a single KV operation probes Redis while the others are still
+    /// short-circuited
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { consecutive_failures: u32 },
+    Open { since: Instant },
+    HalfOpen { probe_started_at: Instant },
+}
+
+/// Stops sending KV operations to Redis after `failure_threshold` consecutive failures, for
+/// `cooldown`, so that a Redis outage does not add its latency to every operation.
+///
+/// While the breaker is open, KV reads go straight to the database and KV writes fail with
+/// [`RedisError::CircuitOpen`]. Once the cooldown elapsed, the next operation probes Redis: its
+/// success closes the breaker, its failure opens it for another cooldown. A probe which never
+/// reports back, e.g. because it was cancelled, is replaced by another one after a cooldown.
+///
+/// Only failures of Redis itself are counted, see [`is_redis_failure`]: a missing key or a
+/// rejected duplicate is an answer from a healthy Redis.
+#[derive(Debug)]
+pub struct RedisCircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl RedisCircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::Closed {
+                consecutive_failures: 0,
+            }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match self.state.lock().as_deref() {
+            Ok(BreakerState::Closed { .. }) | Err(_) => CircuitState::Closed,
+            Ok(BreakerState::Open { .. }) => CircuitState::Open,
+            Ok(BreakerState::HalfOpen { .. }) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Whether a KV operation may be sent to Redis, to be followed by [`Self::record`] with its
+    /// result if so.
+    pub fn allows_request(&self) -> bool {
+        self.allows_request_at(Instant::now())
+    }
+
+    fn allows_request_at(&self, now: Instant) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            // Without knowing the state, Redis is attempted as it would be without a breaker
+            return true;
+        };
+        match *state {
+            BreakerState::Closed { .. } => true,
+            BreakerState::Open { since: started_at }
+            | BreakerState::HalfOpen {
+                probe_started_at: started_at,
+            } => {
+                if now.saturating_duration_since(started_at) < self.cooldown {
+                    return false;
+                }
+                logger::info!("Probing Redis after the circuit breaker cooldown");
+                *state = BreakerState::HalfOpen {
+                    probe_started_at: now,
+                };
+                true
+            }
+        }
+    }
+
+    /// Records the result of a KV operation allowed by [`Self::allows_request`].
+    pub fn record<T>(&self, result: &error_stack::Result<T, RedisError>) {
+        let failed = result
+            .as_ref()
+            .is_err_and(|error| is_redis_failure(error.current_context()));
+        if failed {
+            self.record_failure_at(Instant::now())
+        } else {
+            self.record_success()
+        }
+    }
+
+    fn record_success(&self) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if matches!(*state, BreakerState::HalfOpen { .. }) {
+            logger::info!("Redis recovered, closing the circuit breaker");
+        }
+        *state = BreakerState::Closed {
+            consecutive_failures: 0,
+        };
+    }
+
+    fn record_failure_at(&self, now: Instant) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        match *state {
+            BreakerState::Closed {
+                consecutive_failures,
+            } if consecutive_failures + 1 < self.failure_threshold => {
+                *state = BreakerState::Closed {
+                    consecutive_failures: consecutive_failures + 1,
+                };
+            }
+            BreakerState::Closed { .. } | BreakerState::HalfOpen { .. } => {
+                logger::warn!(
+                    cooldown_in_ms = self.cooldown.as_millis(),
+                    "Redis is failing, opening the circuit breaker"
+                );
+                *state = BreakerState::Open { since: now };
+            }
+            // A failure of an operation allowed before the breaker opened
+            BreakerState::Open { .. } => {}
+        }
+    }
+}
+
+/// Whether `error` tells that Redis is failing, rather than answering a command it rejected.
+pub fn is_redis_failure(error: &RedisError) -> bool {
+    !matches!(
+        error,
+        RedisError::NotFound
+            | RedisError::SetNxFailed
+            | RedisError::SetAddMembersFailed
+            | RedisError::JsonSerializationFailed
+            | RedisError::JsonDeserializationFailed
+            | RedisError::CircuitOpen
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use error_stack::IntoReport;
+
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_millis(500);
+
+    fn fail(breaker: &RedisCircuitBreaker, now: Instant) {
+        assert!(breaker.allows_request_at(now));
+        breaker.record_failure_at(now);
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let breaker = RedisCircuitBreaker::new(3, COOLDOWN);
+        let now = Instant::now();
+
+        fail(&breaker, now);
+        fail(&breaker, now);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        fail(&breaker, now);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allows_request_at(now));
+        assert!(!breaker.allows_request_at(now + COOLDOWN - Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let breaker = RedisCircuitBreaker::new(2, COOLDOWN);
+        let now = Instant::now();
+
+        fail(&breaker, now);
+        assert!(breaker.allows_request_at(now));
+        breaker.record_success();
+        fail(&breaker, now);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        fail(&breaker, now);
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn half_open_allows_a_single_probe() {
+        let breaker = RedisCircuitBreaker::new(1, COOLDOWN);
+        let opened_at = Instant::now();
+        fail(&breaker, opened_at);
+
+        let probed_at = opened_at + COOLDOWN;
+        assert!(breaker.allows_request_at(probed_at));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(!breaker.allows_request_at(probed_at));
+
+        // A probe which never reported back is replaced after a cooldown
+        assert!(breaker.allows_request_at(probed_at + COOLDOWN));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn successful_probe_closes_the_breaker() {
+        let breaker = RedisCircuitBreaker::new(1, COOLDOWN);
+        let opened_at = Instant::now();
+        fail(&breaker, opened_at);
+
+        assert!(breaker.allows_request_at(opened_at + COOLDOWN));
+        breaker.record(&Ok(()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.allows_request_at(opened_at + COOLDOWN));
+    }
+
+    #[test]
+    fn failed_probe_reopens_the_breaker() {
+        let breaker = RedisCircuitBreaker::new(3, COOLDOWN);
+        let opened_at = Instant::now();
+        (0..3).for_each(|_| fail(&breaker, opened_at));
+
+        let probed_at = opened_at + COOLDOWN;
+        fail(&breaker, probed_at);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.allows_request_at(probed_at + COOLDOWN - Duration::from_millis(1)));
+        assert!(breaker.allows_request_at(probed_at + COOLDOWN));
+    }
+
+    #[test]
+    fn answers_of_a_healthy_redis_are_not_failures() {
+        let breaker = RedisCircuitBreaker::new(1, COOLDOWN);
+
+        breaker.record::<()>(&Err(RedisError::NotFound).into_report());
+        breaker.record::<()>(&Err(RedisError::SetNxFailed).into_report());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        breaker.record::<()>(&Err(RedisError::RedisConnectionError).into_report());
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn short_circuited_read_is_served_by_the_database() {
+        let read = crate::utils::try_redis_get_else_try_database_get(
+            async { Err(RedisError::CircuitOpen).into_report() },
+            || async { Ok("from_database") },
+        )
+        .await;
+        assert!(matches!(read, Ok("from_database")));
+    }
+}
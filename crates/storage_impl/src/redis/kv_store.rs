@@ -1,13 +1,22 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, sync::Arc, time::Duration};
 
 use common_utils::errors::CustomResult;
-use error_stack::IntoReport;
+use error_stack::{IntoReport, ResultExt};
 use redis_interface::errors::RedisError;
 use router_derive::TryGetEnumVariant;
 use router_env::logger;
 use serde::de;
 
-use crate::{metrics, store::kv::TypedSql, KVRouterStore, UniqueConstraints};
+use crate::{
+    errors::{RedisErrorExt, RedisRedirection},
+    metrics,
+    store::kv::TypedSql,
+    KVRouterStore, UniqueConstraints,
+};
+
+/// Maximum number of cluster redirections followed for a single Redis command, so that a
+/// cluster in an inconsistent state cannot make a command loop forever.
+pub const MAX_REDIS_REDIRECTIONS: u8 = 3;
 
 pub trait KvStorePartition {
     fn partition_number(key: PartitionKey<'_>, num_partitions: u8) -> u32 {
@@ -53,21 +62,63 @@ pub trait RedisConnInterface {
 /// An enum to represent what operation to do on
 pub enum KvOperation<'a, S: serde::Serialize + Debug> {
     Hset((&'a str, String), TypedSql),
+    /// Sets several fields of the hash at once, so that they are updated together.
+    HsetMultiple(Vec<(String, String)>, TypedSql),
+    /// Sets several fields of the hash at once like `HsetMultiple`, only if the
+    /// [`FieldCondition`] holds. The check and the write are atomic.
+    HsetMultipleIf(Vec<(String, String)>, FieldCondition<'a>, TypedSql),
     SetNx(&'a S, TypedSql),
     HSetNx(&'a str, &'a S, TypedSql),
+    /// Deletes the key, pushing the write to the drainer stream whether the key was there or
+    /// not, as the row may have been drained to the database already.
+    Del(TypedSql),
     HGet(&'a str),
     Get,
     Scan(&'a str),
 }
 
+/// The value a field of the hash must hold for a [`KvOperation::HsetMultipleIf`] to apply.
+#[derive(Clone, Copy, Debug)]
+pub struct FieldCondition<'a> {
+    pub field: &'a str,
+    pub expected: &'a str,
+    /// Whether the operation applies if the field does not exist, e.g. after the hash expired
+    pub apply_if_missing: bool,
+}
+
+/// The Redis endpoint a KV operation is sent to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RedisEndpointRole {
+    Primary,
+    Replica,
+}
+
+impl<S: serde::Serialize + Debug> KvOperation<'_, S> {
+    pub fn endpoint_role(&self) -> RedisEndpointRole {
+        match self {
+            KvOperation::HGet(_) | KvOperation::Get | KvOperation::Scan(_) => {
+                RedisEndpointRole::Replica
+            }
+            KvOperation::Hset(_, _)
+            | KvOperation::HsetMultiple(_, _)
+            | KvOperation::HsetMultipleIf(_, _, _)
+            | KvOperation::SetNx(_, _)
+            | KvOperation::HSetNx(_, _, _)
+            | KvOperation::Del(_) => RedisEndpointRole::Primary,
+        }
+    }
+}
+
 #[derive(TryGetEnumVariant)]
 #[error(RedisError::UnknownResult)]
 pub enum KvResult<T: de::DeserializeOwned> {
     HGet(T),
     Get(T),
     Hset(()),
+    HsetIf(redis_interface::ConditionalHsetReply),
     SetNx(redis_interface::SetnxReply),
     HSetNx(redis_interface::HsetnxReply),
+    Del(redis_interface::DelReply),
     Scan(Vec<T>),
 }
 
@@ -78,8 +129,11 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             KvOperation::Hset(_, _) => f.write_str("Hset"),
+            KvOperation::HsetMultiple(_, _) => f.write_str("HsetMultiple"),
+            KvOperation::HsetMultipleIf(_, _, _) => f.write_str("HsetMultipleIf"),
             KvOperation::SetNx(_, _) => f.write_str("Setnx"),
             KvOperation::HSetNx(_, _, _) => f.write_str("HSetNx"),
+            KvOperation::Del(_) => f.write_str("Del"),
             KvOperation::HGet(_) => f.write_str("Hget"),
             KvOperation::Get => f.write_str("Get"),
             KvOperation::Scan(_) => f.write_str("Scan"),
@@ -97,12 +151,26 @@ where
     D: crate::database::store::DatabaseStore,
     S: serde::Serialize + Debug + KvStorePartition + UniqueConstraints + Sync,
 {
-    let redis_conn = store.get_redis_conn()?;
-
     let key = key.as_ref();
+    let circuit_breaker = store.redis_circuit_breaker.as_deref();
+    if circuit_breaker.is_some_and(|breaker| !breaker.allows_request()) {
+        metrics::KV_OPERATION_SHORT_CIRCUITED.add(&metrics::CONTEXT, 1, &[]);
+        return Err(RedisError::CircuitOpen)
+            .into_report()
+            .attach_printable_lazy(|| format!("Skipped the KV operation {op} on {key}"));
+    }
+    let endpoint_role = op.endpoint_role();
+    let redis_conn = match endpoint_role {
+        RedisEndpointRole::Primary => store.get_redis_conn()?,
+        RedisEndpointRole::Replica => store.get_redis_read_conn([key])?,
+    };
+
     let type_name = std::any::type_name::<T>();
     let operation = op.to_string();
 
+    #[cfg(feature = "chaos")]
+    crate::chaos::check_redis_operation(store.router_store.fault_injector.as_ref(), &operation)?;
+
     let ttl = store.ttl_for_kv;
 
     let partition_key = PartitionKey::MerchantIdPaymentIdCombination { combination: key };
@@ -112,10 +180,26 @@ where
             KvOperation::Hset(value, sql) => {
                 logger::debug!(kv_operation= %operation, value = ?value);
 
-                redis_conn
-                    .set_hash_fields(key, value, Some(ttl.into()))
+                follow_redirections(redis_conn.as_ref(), || {
+                    redis_conn.set_hash_fields(key, value.clone(), Some(ttl.into()))
+                })
+                .await?;
+
+                store
+                    .push_to_drainer_stream::<S>(sql, partition_key)
                     .await?;
 
+                Ok(KvResult::Hset(()))
+            }
+
+            KvOperation::HsetMultiple(values, sql) => {
+                logger::debug!(kv_operation= %operation, values = ?values);
+
+                follow_redirections(redis_conn.as_ref(), || {
+                    redis_conn.set_hash_fields(key, values.clone(), Some(ttl.into()))
+                })
+                .await?;
+
                 store
                     .push_to_drainer_stream::<S>(sql, partition_key)
                     .await?;
@@ -123,24 +207,51 @@ where
                 Ok(KvResult::Hset(()))
             }
 
+            KvOperation::HsetMultipleIf(values, condition, sql) => {
+                logger::debug!(kv_operation= %operation, values = ?values, condition = ?condition);
+
+                let reply = follow_redirections(redis_conn.as_ref(), || {
+                    redis_conn.set_hash_fields_if_field_equals(
+                        key,
+                        condition.field,
+                        condition.expected,
+                        condition.apply_if_missing,
+                        &values,
+                        Some(ttl.into()),
+                    )
+                })
+                .await?;
+
+                // Only a write which applied is replayed to the database
+                if reply == redis_interface::ConditionalHsetReply::FieldsSet {
+                    store
+                        .push_to_drainer_stream::<S>(sql, partition_key)
+                        .await?;
+                }
+
+                Ok(KvResult::HsetIf(reply))
+            }
+
             KvOperation::HGet(field) => {
-                let result = redis_conn
-                    .get_hash_field_and_deserialize(key, field, type_name)
-                    .await?;
+                let result = follow_redirections(redis_conn.as_ref(), || {
+                    redis_conn.get_hash_field_and_deserialize(key, field, type_name)
+                })
+                .await?;
                 Ok(KvResult::HGet(result))
             }
 
             KvOperation::Scan(pattern) => {
-                let result: Vec<T> = redis_conn
-                    .hscan_and_deserialize(key, pattern, None)
-                    .await
-                    .and_then(|result| {
-                        if result.is_empty() {
-                            Err(RedisError::NotFound).into_report()
-                        } else {
-                            Ok(result)
-                        }
-                    })?;
+                let result: Vec<T> = follow_redirections(redis_conn.as_ref(), || {
+                    redis_conn.hscan_and_deserialize(key, pattern, None)
+                })
+                .await
+                .and_then(|result| {
+                    if result.is_empty() {
+                        Err(RedisError::NotFound).into_report()
+                    } else {
+                        Ok(result)
+                    }
+                })?;
                 Ok(KvResult::Scan(result))
             }
 
@@ -149,9 +260,15 @@ where
 
                 value.check_for_constraints(&redis_conn).await?;
 
-                let result = redis_conn
-                    .serialize_and_set_hash_field_if_not_exist(key, field, value, Some(ttl))
-                    .await?;
+                let result = follow_redirections(redis_conn.as_ref(), || {
+                    redis_conn.serialize_and_set_hash_field_if_not_exist(
+                        key,
+                        field,
+                        value,
+                        Some(ttl),
+                    )
+                })
+                .await?;
 
                 if matches!(result, redis_interface::HsetnxReply::KeySet) {
                     store
@@ -166,9 +283,10 @@ where
             KvOperation::SetNx(value, sql) => {
                 logger::debug!(kv_operation= %operation, value = ?value);
 
-                let result = redis_conn
-                    .serialize_and_set_key_if_not_exist(key, value, Some(ttl.into()))
-                    .await?;
+                let result = follow_redirections(redis_conn.as_ref(), || {
+                    redis_conn.serialize_and_set_key_if_not_exist(key, value, Some(ttl.into()))
+                })
+                .await?;
 
                 value.check_for_constraints(&redis_conn).await?;
 
@@ -182,15 +300,43 @@ where
                 }
             }
 
+            KvOperation::Del(sql) => {
+                let reply =
+                    follow_redirections(redis_conn.as_ref(), || redis_conn.delete_key(key)).await?;
+
+                store
+                    .push_to_drainer_stream::<S>(sql, partition_key)
+                    .await?;
+
+                Ok(KvResult::Del(reply))
+            }
+
             KvOperation::Get => {
-                let result = redis_conn.get_and_deserialize_key(key, type_name).await?;
+                let result = follow_redirections(redis_conn.as_ref(), || {
+                    redis_conn.get_and_deserialize_key(key, type_name)
+                })
+                .await?;
                 Ok(KvResult::Get(result))
             }
         }
     };
 
+    let result = crate::deadline::within_deadline(
+        store.router_store.deadline,
+        RedisError::DeadlineExceeded,
+        result,
+    )
+    .await;
+    if let Some(breaker) = circuit_breaker {
+        breaker.record(&result);
+    }
+    if endpoint_role == RedisEndpointRole::Primary {
+        // A write which failed past its Redis command, e.g. on the drainer stream push, still
+        // reached the primary
+        store.record_kv_write(key);
+    }
+
     result
-        .await
         .map(|result| {
             logger::debug!(kv_operation= %operation, status="success");
             let keyvalue = router_env::opentelemetry::KeyValue::new("operation", operation.clone());
@@ -206,3 +352,277 @@ where
             err
         })
 }
+
+/// Reacts to the cluster redirections a Redis command is answered with.
+#[async_trait::async_trait]
+pub trait RedirectionHandler: Sync {
+    /// Refreshes the cached slot map, after a `MOVED` redirection.
+    async fn refresh_slot_map(&self) -> CustomResult<(), RedisError>;
+
+    /// Sends `ASKING` ahead of the retried command, after an `ASK` redirection.
+    async fn asking(&self) -> CustomResult<(), RedisError>;
+}
+
+#[async_trait::async_trait]
+impl RedirectionHandler for redis_interface::RedisConnectionPool {
+    async fn refresh_slot_map(&self) -> CustomResult<(), RedisError> {
+        self.sync_cluster_slots().await
+    }
+
+    async fn asking(&self) -> CustomResult<(), RedisError> {
+        redis_interface::RedisConnectionPool::asking(self).await
+    }
+}
+
+/// Runs `command`, retrying it after following the `MOVED` and `ASK` redirections it is
+/// answered with, up to [`MAX_REDIS_REDIRECTIONS`] times.
+pub async fn follow_redirections<H, T, F, Fut>(
+    handler: &H,
+    mut command: F,
+) -> CustomResult<T, RedisError>
+where
+    H: RedirectionHandler + ?Sized,
+    F: FnMut() -> Fut,
+    Fut: futures::Future<Output = CustomResult<T, RedisError>>,
+{
+    let mut redirections = 0;
+    loop {
+        let error = match command().await {
+            Ok(result) => return Ok(result),
+            Err(error) => error,
+        };
+        let Some(redirection) = error.redirection() else {
+            return Err(error);
+        };
+        if redirections >= MAX_REDIS_REDIRECTIONS {
+            return Err(error.attach_printable(format!(
+                "Gave up after following {MAX_REDIS_REDIRECTIONS} cluster redirections"
+            )));
+        }
+        redirections += 1;
+
+        logger::warn!(?redirection, "Following Redis cluster redirection");
+        match redirection {
+            RedisRedirection::Moved { .. } => handler.refresh_slot_map().await?,
+            RedisRedirection::Ask { .. } => handler.asking().await?,
+        }
+    }
+}
+
+/// Awaits a push to the drainer stream, failing it with [`RedisError::StreamAppendFailed`] if
+/// it does not complete within `timeout`.
+///
+/// A timed out push may still be applied by Redis later on, the entry is then drained as usual.
+pub async fn push_with_timeout<F>(
+    push: F,
+    timeout: Option<Duration>,
+) -> CustomResult<(), RedisError>
+where
+    F: futures::Future<Output = CustomResult<(), RedisError>>,
+{
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, push)
+            .await
+            .into_report()
+            .change_context(RedisError::StreamAppendFailed)
+            .attach_printable_lazy(|| {
+                format!(
+                    "Drainer stream push timed out after {}ms",
+                    timeout.as_millis()
+                )
+            })?,
+        None => push.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::store::{
+        kv::{DBOperation, Insertable},
+        ReverseLookupNew,
+    };
+
+    fn typed_sql() -> TypedSql {
+        TypedSql {
+            op: DBOperation::Insert {
+                insertable: Insertable::ReverseLookUp(ReverseLookupNew {
+                    lookup_id: String::new(),
+                    pk_id: String::new(),
+                    sk_id: String::new(),
+                    source: String::new(),
+                    updated_by: String::new(),
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn read_operations_target_replica() {
+        assert_eq!(
+            KvOperation::<()>::HGet("field").endpoint_role(),
+            RedisEndpointRole::Replica
+        );
+        assert_eq!(
+            KvOperation::<()>::Get.endpoint_role(),
+            RedisEndpointRole::Replica
+        );
+        assert_eq!(
+            KvOperation::<()>::Scan("pattern").endpoint_role(),
+            RedisEndpointRole::Replica
+        );
+    }
+
+    #[test]
+    fn write_operations_target_primary() {
+        assert_eq!(
+            KvOperation::<()>::Hset(("field", String::new()), typed_sql()).endpoint_role(),
+            RedisEndpointRole::Primary
+        );
+        assert_eq!(
+            KvOperation::SetNx(&(), typed_sql()).endpoint_role(),
+            RedisEndpointRole::Primary
+        );
+        assert_eq!(
+            KvOperation::HSetNx("field", &(), typed_sql()).endpoint_role(),
+            RedisEndpointRole::Primary
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_stream_push_times_out() {
+        let slow_push = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        };
+
+        let error = push_with_timeout(slow_push, Some(Duration::from_millis(10)))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            RedisError::StreamAppendFailed
+        ));
+    }
+
+    #[tokio::test]
+    async fn stream_push_within_timeout_succeeds() {
+        assert!(
+            push_with_timeout(async { Ok(()) }, Some(Duration::from_secs(5)))
+                .await
+                .is_ok()
+        );
+        assert!(push_with_timeout(async { Ok(()) }, None).await.is_ok());
+    }
+
+    #[derive(Default)]
+    struct RecordingHandler {
+        slot_map_refreshes: std::sync::atomic::AtomicUsize,
+        askings: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl RedirectionHandler for RecordingHandler {
+        async fn refresh_slot_map(&self) -> CustomResult<(), RedisError> {
+            self.slot_map_refreshes
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn asking(&self) -> CustomResult<(), RedisError> {
+            self.askings
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn redirected(message: &'static str) -> CustomResult<String, RedisError> {
+        Err(fred::error::RedisError::new(
+            fred::error::RedisErrorKind::Cluster,
+            message,
+        ))
+        .into_report()
+        .change_context(RedisError::GetHashFieldFailed)
+    }
+
+    #[tokio::test]
+    async fn moved_is_followed_once_then_succeeds() {
+        let handler = RecordingHandler::default();
+        let mut calls = 0;
+
+        let result = follow_redirections(&handler, || {
+            calls += 1;
+            let reply = if calls == 1 {
+                redirected("MOVED 3999 127.0.0.1:6381")
+            } else {
+                Ok("value".to_string())
+            };
+            async move { reply }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "value");
+        assert_eq!(calls, 2);
+        assert_eq!(
+            handler
+                .slot_map_refreshes
+                .load(std::sync::atomic::Ordering::SeqCst),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn ask_sends_asking_before_retrying() {
+        let handler = RecordingHandler::default();
+        let mut calls = 0;
+
+        let result = follow_redirections(&handler, || {
+            calls += 1;
+            let reply = if calls == 1 {
+                redirected("ASK 3999 127.0.0.1:6381")
+            } else {
+                Ok("value".to_string())
+            };
+            async move { reply }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "value");
+        assert_eq!(handler.askings.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn redirections_are_capped() {
+        let handler = RecordingHandler::default();
+        let mut calls = 0;
+
+        let error = follow_redirections(&handler, || {
+            calls += 1;
+            let reply = redirected("MOVED 3999 127.0.0.1:6381");
+            async move { reply }
+        })
+        .await
+        .unwrap_err();
+
+        assert!(error.redirection().is_some());
+        assert_eq!(calls, usize::from(MAX_REDIS_REDIRECTIONS) + 1);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let handler = RecordingHandler::default();
+        let mut calls = 0;
+
+        let error = follow_redirections(&handler, || {
+            calls += 1;
+            async { Err::<String, _>(RedisError::NotFound).into_report() }
+        })
+        .await
+        .unwrap_err();
+
+        assert!(matches!(error.current_context(), RedisError::NotFound));
+        assert_eq!(calls, 1);
+    }
+}
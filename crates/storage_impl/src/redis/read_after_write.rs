@@ -0,0 +1,114 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Number of tracked keys past which the keys whose window elapsed are dropped on the next write.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Sends the reads of a KV key to the Redis primary for `window` after the key was written
+/// through this process, so that a read following a write does not observe a replica which did
+/// not replicate the write yet.
+///
+/// Past the window, reads of the key go back to the replica. Writes made by other processes are
+/// not tracked, their reads are subject to the replication lag as before.
+#[derive(Debug)]
+pub struct PrimaryReadWindow {
+    window: Duration,
+    written: Mutex<HashMap<String, Instant>>,
+}
+
+impl PrimaryReadWindow {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            written: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Opens the window of `key`, to be called after every write of the key.
+    pub fn record_write(&self, key: &str) {
+        self.record_write_at(key, Instant::now())
+    }
+
+    fn record_write_at(&self, key: &str, now: Instant) {
+        let Ok(mut written) = self.written.lock() else {
+            return;
+        };
+        if written.len() >= PRUNE_THRESHOLD {
+            written.retain(|_, window_end| *window_end > now);
+        }
+        written.insert(key.to_string(), now + self.window);
+    }
+
+    /// Whether a read of `key` must be served by the primary.
+    pub fn reads_from_primary(&self, key: &str) -> bool {
+        self.reads_from_primary_at(key, Instant::now())
+    }
+
+    fn reads_from_primary_at(&self, key: &str, now: Instant) -> bool {
+        let Ok(mut written) = self.written.lock() else {
+            // Without knowing whether the key was written, the primary is always up to date
+            return true;
+        };
+        match written.get(key) {
+            Some(window_end) if *window_end > now => true,
+            Some(_) => {
+                written.remove(key);
+                false
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "mid_merchant_1_po_po_1";
+
+    #[test]
+    fn read_right_after_write_hits_primary() {
+        let window = PrimaryReadWindow::new(Duration::from_millis(500));
+        let written_at = Instant::now();
+        assert!(!window.reads_from_primary_at(KEY, written_at));
+
+        window.record_write_at(KEY, written_at);
+        assert!(window.reads_from_primary_at(KEY, written_at));
+        assert!(window.reads_from_primary_at(KEY, written_at + Duration::from_millis(499)));
+        // The window is per key
+        assert!(!window.reads_from_primary_at("mid_merchant_1_po_po_2", written_at));
+        assert!(!window.reads_from_primary_at("mid_merchant_2_po_po_1", written_at));
+    }
+
+    #[test]
+    fn later_read_goes_back_to_replica() {
+        let window = PrimaryReadWindow::new(Duration::from_millis(500));
+        let written_at = Instant::now();
+        window.record_write_at(KEY, written_at);
+
+        assert!(!window.reads_from_primary_at(KEY, written_at + Duration::from_millis(500)));
+
+        // Another write reopens the window
+        let rewritten_at = written_at + Duration::from_secs(1);
+        window.record_write_at(KEY, rewritten_at);
+        assert!(window.reads_from_primary_at(KEY, rewritten_at + Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn elapsed_windows_are_pruned() {
+        let window = PrimaryReadWindow::new(Duration::from_millis(500));
+        let written_at = Instant::now();
+        for payout in 0..PRUNE_THRESHOLD {
+            window.record_write_at(&format!("mid_merchant_1_po_po_{payout}"), written_at);
+        }
+
+        window.record_write_at(KEY, written_at + Duration::from_secs(1));
+        assert_eq!(
+            window.written.lock().map(|written| written.len()).ok(),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use std::{collections::HashMap, str::FromStr};
+
+use data_models::errors::StorageError;
+use diesel_models::enums::MerchantStorageScheme;
+use error_stack::{IntoReport, ResultExt};
+use router_env::logger;
+
+use super::{cache::STORAGE_SCHEME_OVERRIDE_CACHE, kv_store::RedisConnInterface};
+
+/// Key holding the storage scheme a merchant is forced to, e.g. `postgres_only` to move a
+/// merchant off KV during a Redis incident.
+pub fn storage_scheme_override_key(merchant_id: &str) -> String {
+    format!("storage_scheme_override_{merchant_id}")
+}
+
+/// Returns the storage scheme to use for the merchant, which is the operator override if one
+/// is set and `requested` otherwise.
+///
+/// Overrides from `config_overrides` take precedence, since they remain available while Redis
+/// is not. Overrides set in Redis are cached in memory for a short while, so changes to them are
+/// picked up without a redeploy. Failures to read them are logged and fall back to `requested`.
+pub async fn effective_storage_scheme(
+    store: &(dyn RedisConnInterface + Send + Sync),
+    config_overrides: &HashMap<String, MerchantStorageScheme>,
+    merchant_id: &str,
+    requested: MerchantStorageScheme,
+) -> MerchantStorageScheme {
+    if let Some(storage_scheme) = config_overrides.get(merchant_id) {
+        return *storage_scheme;
+    }
+
+    let key = storage_scheme_override_key(merchant_id);
+    let storage_scheme_override = match STORAGE_SCHEME_OVERRIDE_CACHE
+        .get_val::<Option<MerchantStorageScheme>>(&key)
+        .await
+    {
+        Some(storage_scheme_override) => storage_scheme_override,
+        None => match fetch_storage_scheme_override(store, &key).await {
+            Ok(storage_scheme_override) => {
+                STORAGE_SCHEME_OVERRIDE_CACHE
+                    .push(key, storage_scheme_override)
+                    .await;
+                storage_scheme_override
+            }
+            Err(error) => {
+                logger::error!(
+                    ?error,
+                    "Failed to fetch storage scheme override for {merchant_id}"
+                );
+                None
+            }
+        },
+    };
+
+    storage_scheme_override.unwrap_or(requested)
+}
+
+/// A storage scheme resolved for a merchant from its configured scheme, the operator overrides
+/// and the capabilities of the store, see [`resolve_storage_scheme`].
+///
+/// It can only be obtained by resolving it, so code holding one cannot have been handed a
+/// scheme the store cannot serve.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ResolvedScheme(MerchantStorageScheme);
+
+impl ResolvedScheme {
+    pub fn scheme(self) -> MerchantStorageScheme {
+        self.0
+    }
+}
+
+/// Resolves the storage scheme of the merchant: the operator override if one is set, see
+/// [`effective_storage_scheme`], `configured` otherwise, adapted to what `store` can serve.
+///
+/// Without Redis, `DualWrite` falls back to `PostgresOnly`, as its mirror writes would fail
+/// anyway, while `RedisKv` fails with `SchemeUnsupported`, see [`ensure_scheme_supported`].
+pub async fn resolve_storage_scheme(
+    store: &(dyn RedisConnInterface + Send + Sync),
+    config_overrides: &HashMap<String, MerchantStorageScheme>,
+    merchant_id: &str,
+    configured: MerchantStorageScheme,
+) -> error_stack::Result<ResolvedScheme, StorageError> {
+    let storage_scheme =
+        effective_storage_scheme(store, config_overrides, merchant_id, configured).await;
+    match storage_scheme {
+        MerchantStorageScheme::DualWrite if store.get_redis_conn().is_err() => {
+            logger::warn!(
+                "Redis is not available, serving {merchant_id} from the database only instead of \
+                 dual writing"
+            );
+            Ok(ResolvedScheme(MerchantStorageScheme::PostgresOnly))
+        }
+        storage_scheme => {
+            ensure_scheme_supported(store, storage_scheme)?;
+            Ok(ResolvedScheme(storage_scheme))
+        }
+    }
+}
+
+/// Ensures that `store` can serve `storage_scheme`, failing with `SchemeUnsupported` otherwise.
+///
+/// Only `RedisKv` requires Redis: `DualWrite` commits to the database first and mirrors to Redis
+/// on a best effort basis.
+pub fn ensure_scheme_supported(
+    store: &(dyn RedisConnInterface + Send + Sync),
+    storage_scheme: MerchantStorageScheme,
+) -> error_stack::Result<(), StorageError> {
+    match storage_scheme {
+        MerchantStorageScheme::RedisKv => store
+            .get_redis_conn()
+            .map(|_| ())
+            .change_context(StorageError::SchemeUnsupported {
+                scheme: storage_scheme,
+            })
+            .attach_printable("Redis is not available"),
+        MerchantStorageScheme::PostgresOnly | MerchantStorageScheme::DualWrite => Ok(()),
+    }
+}
+
+async fn fetch_storage_scheme_override(
+    store: &(dyn RedisConnInterface + Send + Sync),
+    key: &str,
+) -> error_stack::Result<Option<MerchantStorageScheme>, redis_interface::errors::RedisError> {
+    store
+        .get_redis_conn()?
+        .get_key::<Option<String>>(key)
+        .await?
+        .map(|storage_scheme| MerchantStorageScheme::from_str(&storage_scheme))
+        .transpose()
+        .into_report()
+        .change_context(redis_interface::errors::RedisError::JsonDeserializationFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::sync::Arc;
+
+    use redis_interface::errors::RedisError;
+
+    use super::*;
+
+    struct UnavailableRedis;
+
+    impl RedisConnInterface for UnavailableRedis {
+        fn get_redis_conn(
+            &self,
+        ) -> error_stack::Result<Arc<redis_interface::RedisConnectionPool>, RedisError> {
+            Err(RedisError::RedisConnectionError.into())
+        }
+    }
+
+    #[tokio::test]
+    async fn override_forces_database_path() {
+        STORAGE_SCHEME_OVERRIDE_CACHE
+            .push(
+                storage_scheme_override_key("merchant_override"),
+                Some(MerchantStorageScheme::PostgresOnly),
+            )
+            .await;
+
+        let storage_scheme = effective_storage_scheme(
+            &UnavailableRedis,
+            &HashMap::new(),
+            "merchant_override",
+            MerchantStorageScheme::RedisKv,
+        )
+        .await;
+        assert_eq!(storage_scheme, MerchantStorageScheme::PostgresOnly);
+    }
+
+    #[tokio::test]
+    async fn config_override_takes_precedence() {
+        let config_overrides = HashMap::from([(
+            "merchant_config_override".to_string(),
+            MerchantStorageScheme::PostgresOnly,
+        )]);
+
+        let storage_scheme = effective_storage_scheme(
+            &UnavailableRedis,
+            &config_overrides,
+            "merchant_config_override",
+            MerchantStorageScheme::RedisKv,
+        )
+        .await;
+        assert_eq!(storage_scheme, MerchantStorageScheme::PostgresOnly);
+    }
+
+    #[tokio::test]
+    async fn requested_scheme_is_used_without_override() {
+        STORAGE_SCHEME_OVERRIDE_CACHE
+            .push(
+                storage_scheme_override_key("merchant_no_override"),
+                None::<MerchantStorageScheme>,
+            )
+            .await;
+
+        let storage_scheme = effective_storage_scheme(
+            &UnavailableRedis,
+            &HashMap::new(),
+            "merchant_no_override",
+            MerchantStorageScheme::RedisKv,
+        )
+        .await;
+        assert_eq!(storage_scheme, MerchantStorageScheme::RedisKv);
+    }
+
+    #[tokio::test]
+    async fn unreadable_override_falls_back_to_requested_scheme() {
+        let storage_scheme = effective_storage_scheme(
+            &UnavailableRedis,
+            &HashMap::new(),
+            "merchant_redis_down",
+            MerchantStorageScheme::RedisKv,
+        )
+        .await;
+        assert_eq!(storage_scheme, MerchantStorageScheme::RedisKv);
+    }
+
+    #[test]
+    fn redis_kv_is_unsupported_without_redis() {
+        let error =
+            ensure_scheme_supported(&UnavailableRedis, MerchantStorageScheme::RedisKv).unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::SchemeUnsupported {
+                scheme: MerchantStorageScheme::RedisKv
+            }
+        ));
+        assert_eq!(
+            error.current_context().to_string(),
+            "SchemeUnsupported: the redis_kv storage scheme is not supported by this store"
+        );
+    }
+
+    #[test]
+    fn database_schemes_are_supported_without_redis() {
+        for storage_scheme in [
+            MerchantStorageScheme::PostgresOnly,
+            MerchantStorageScheme::DualWrite,
+        ] {
+            ensure_scheme_supported(&UnavailableRedis, storage_scheme).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn overridden_merchant_is_supported_without_redis() {
+        let config_overrides = HashMap::from([(
+            "merchant_moved_off_kv".to_string(),
+            MerchantStorageScheme::PostgresOnly,
+        )]);
+
+        let storage_scheme = effective_storage_scheme(
+            &UnavailableRedis,
+            &config_overrides,
+            "merchant_moved_off_kv",
+            MerchantStorageScheme::RedisKv,
+        )
+        .await;
+        ensure_scheme_supported(&UnavailableRedis, storage_scheme).unwrap();
+    }
+
+    #[tokio::test]
+    async fn configured_scheme_is_resolved_without_override() {
+        let resolved = resolve_storage_scheme(
+            &UnavailableRedis,
+            &HashMap::new(),
+            "merchant_resolve_configured",
+            MerchantStorageScheme::PostgresOnly,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resolved.scheme(), MerchantStorageScheme::PostgresOnly);
+
+        // A merchant configured for KV is moved off it by the settings
+        let config_overrides = HashMap::from([(
+            "merchant_resolve_configured".to_string(),
+            MerchantStorageScheme::PostgresOnly,
+        )]);
+        let resolved = resolve_storage_scheme(
+            &UnavailableRedis,
+            &config_overrides,
+            "merchant_resolve_configured",
+            MerchantStorageScheme::RedisKv,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resolved.scheme(), MerchantStorageScheme::PostgresOnly);
+    }
+
+    #[tokio::test]
+    async fn operator_override_is_resolved() {
+        STORAGE_SCHEME_OVERRIDE_CACHE
+            .push(
+                storage_scheme_override_key("merchant_resolve_override"),
+                Some(MerchantStorageScheme::PostgresOnly),
+            )
+            .await;
+
+        let resolved = resolve_storage_scheme(
+            &UnavailableRedis,
+            &HashMap::new(),
+            "merchant_resolve_override",
+            MerchantStorageScheme::RedisKv,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resolved.scheme(), MerchantStorageScheme::PostgresOnly);
+    }
+
+    #[tokio::test]
+    async fn resolution_falls_back_to_what_the_store_can_serve() {
+        let resolved = resolve_storage_scheme(
+            &UnavailableRedis,
+            &HashMap::new(),
+            "merchant_resolve_dual_write",
+            MerchantStorageScheme::DualWrite,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resolved.scheme(), MerchantStorageScheme::PostgresOnly);
+
+        let error = resolve_storage_scheme(
+            &UnavailableRedis,
+            &HashMap::new(),
+            "merchant_resolve_kv",
+            MerchantStorageScheme::RedisKv,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StorageError::SchemeUnsupported {
+                scheme: MerchantStorageScheme::RedisKv
+            }
+        ));
+    }
+}
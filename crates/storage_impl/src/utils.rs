@@ -11,6 +11,12 @@ pub async fn pg_connection_read<T: DatabaseStore>(
     PooledConnection<'_, async_bb8_diesel::ConnectionManager<PgConnection>>,
     StorageError,
 > {
+    #[cfg(feature = "chaos")]
+    crate::chaos::check_database_operation(
+        store.fault_injector(),
+        crate::chaos::OperationKind::DatabaseRead,
+    )?;
+
     // If only OLAP is enabled get replica pool.
     #[cfg(all(feature = "olap", not(feature = "oltp")))]
     let pool = store.get_replica_pool();
@@ -26,10 +32,13 @@ pub async fn pg_connection_read<T: DatabaseStore>(
     ))]
     let pool = store.get_master_pool();
 
-    pool.get()
-        .await
-        .into_report()
-        .change_context(StorageError::DatabaseConnectionError)
+    crate::deadline::within_deadline(store.deadline(), StorageError::DeadlineExceeded, async {
+        pool.get()
+            .await
+            .into_report()
+            .change_context(StorageError::DatabaseConnectionError)
+    })
+    .await
 }
 
 pub async fn pg_connection_write<T: DatabaseStore>(
@@ -38,13 +47,22 @@ pub async fn pg_connection_write<T: DatabaseStore>(
     PooledConnection<'_, async_bb8_diesel::ConnectionManager<PgConnection>>,
     StorageError,
 > {
+    #[cfg(feature = "chaos")]
+    crate::chaos::check_database_operation(
+        store.fault_injector(),
+        crate::chaos::OperationKind::DatabaseWrite,
+    )?;
+
     // Since all writes should happen to master DB only choose master DB.
     let pool = store.get_master_pool();
 
-    pool.get()
-        .await
-        .into_report()
-        .change_context(StorageError::DatabaseConnectionError)
+    crate::deadline::within_deadline(store.deadline(), StorageError::DeadlineExceeded, async {
+        pool.get()
+            .await
+            .into_report()
+            .change_context(StorageError::DatabaseConnectionError)
+    })
+    .await
 }
 
 pub async fn try_redis_get_else_try_database_get<F, RFut, DFut, T>(
@@ -64,8 +82,46 @@ where
                 metrics::KV_MISS.add(&metrics::CONTEXT, 1, &[]);
                 database_call_closure().await
             }
+            // Redis is failing, it was not attempted
+            redis_interface::errors::RedisError::CircuitOpen => database_call_closure().await,
             // Keeping the key empty here since the error would never go here.
             _ => Err(redis_error.to_redis_failed_response("")),
         },
     }
 }
+
+/// Whether the two lists hold the same field names, in any order, see
+/// [`router_derive::FieldNames`].
+///
+/// This is a `const fn` so that the field sets of a storage model and its domain model can be
+/// compared in a `const` assertion, failing the build as soon as they diverge.
+pub const fn same_field_names(first: &[&str], second: &[&str]) -> bool {
+    // Field names are unique within a struct, so equal lengths and inclusion make equal sets
+    first.len() == second.len() && all_field_names_in(first, second)
+}
+
+const fn all_field_names_in(names: &[&str], others: &[&str]) -> bool {
+    match names {
+        [] => true,
+        [name, rest @ ..] => field_name_in(name, others) && all_field_names_in(rest, others),
+    }
+}
+
+const fn field_name_in(name: &str, names: &[&str]) -> bool {
+    match names {
+        [] => false,
+        [first, rest @ ..] => {
+            bytes_eq(first.as_bytes(), name.as_bytes()) || field_name_in(name, rest)
+        }
+    }
+}
+
+const fn bytes_eq(first: &[u8], second: &[u8]) -> bool {
+    match (first, second) {
+        ([], []) => true,
+        ([first_byte, first @ ..], [second_byte, second @ ..]) => {
+            *first_byte == *second_byte && bytes_eq(first, second)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS original_payout_id VARCHAR(64);
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS hold_reason;
+
+DELETE FROM pg_enum
+WHERE enumlabel = 'held_for_review'
+AND enumtypid = (
+  SELECT oid FROM pg_type WHERE typname = 'PayoutStatus'
+);
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TYPE "PayoutStatus" ADD VALUE IF NOT EXISTS 'held_for_review';
+
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS hold_reason VARCHAR(255);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE payout_attempt
+DROP COLUMN IF EXISTS unified_code;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE payout_attempt
+ADD COLUMN IF NOT EXISTS unified_code VARCHAR(255);
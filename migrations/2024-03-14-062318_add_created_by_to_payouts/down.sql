@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS created_by;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS created_by VARCHAR(32) NOT NULL DEFAULT 'api';
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS payouts_merchant_id_network_token_ref_index;
+
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS network_token_ref;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS network_token_ref VARCHAR(128);
+
+CREATE INDEX IF NOT EXISTS payouts_merchant_id_network_token_ref_index ON payouts (merchant_id, network_token_ref);
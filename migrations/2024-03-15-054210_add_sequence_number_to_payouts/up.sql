@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS sequence_number BIGINT NOT NULL DEFAULT 0;
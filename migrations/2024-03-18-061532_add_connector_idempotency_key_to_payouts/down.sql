@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS payouts_merchant_id_connector_idempotency_key_index;
+
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS connector_idempotency_key;
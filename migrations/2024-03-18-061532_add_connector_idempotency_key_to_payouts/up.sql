@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS connector_idempotency_key VARCHAR(64);
+
+CREATE UNIQUE INDEX IF NOT EXISTS payouts_merchant_id_connector_idempotency_key_index ON payouts (merchant_id, connector_idempotency_key);
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TYPE "MerchantStorageScheme" ADD VALUE IF NOT EXISTS 'dual_write';
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS payouts_status_scheduled_at_index;
+
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS scheduled_at;
+
+DELETE FROM pg_enum
+WHERE enumlabel = 'scheduled'
+AND enumtypid = (
+  SELECT oid FROM pg_type WHERE typname = 'PayoutStatus'
+);
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+ALTER TYPE "PayoutStatus" ADD VALUE IF NOT EXISTS 'scheduled';
+
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS scheduled_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS payouts_status_scheduled_at_index ON payouts (status, scheduled_at);
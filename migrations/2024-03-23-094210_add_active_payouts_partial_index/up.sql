@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX IF NOT EXISTS payouts_active_status_index ON payouts (status, scheduled_at, last_modified_at)
+WHERE status IN ('pending', 'scheduled', 'requires_fulfillment');
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS payouts_merchant_id_source_payment_id_index;
+
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS source_payment_id;
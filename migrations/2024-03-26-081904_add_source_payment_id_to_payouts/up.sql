@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS source_payment_id VARCHAR(64);
+
+CREATE INDEX IF NOT EXISTS payouts_merchant_id_source_payment_id_index ON payouts (merchant_id, source_payment_id);
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX IF NOT EXISTS payouts_merchant_id_in_flight_index ON payouts (merchant_id)
+WHERE status = 'pending';
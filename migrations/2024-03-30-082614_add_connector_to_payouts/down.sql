@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS connector,
+DROP COLUMN IF EXISTS connector_payout_id,
+DROP COLUMN IF EXISTS connector_response;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS connector VARCHAR(64),
+ADD COLUMN IF NOT EXISTS connector_payout_id VARCHAR(128),
+ADD COLUMN IF NOT EXISTS connector_response JSONB;
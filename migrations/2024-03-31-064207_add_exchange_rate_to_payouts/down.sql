@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS exchange_rate,
+DROP COLUMN IF EXISTS exchange_rate_timestamp;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS exchange_rate NUMERIC,
+ADD COLUMN IF NOT EXISTS exchange_rate_timestamp TIMESTAMP;
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS payouts_merchant_id_display_seq_index;
+
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS display_seq;
+
+DROP TABLE IF EXISTS payout_display_sequences;
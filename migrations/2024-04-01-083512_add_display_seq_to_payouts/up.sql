@@ -0,0 +1,28 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS payout_display_sequences (
+    merchant_id VARCHAR(64) PRIMARY KEY,
+    last_value BIGINT NOT NULL
+);
+
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS display_seq BIGINT;
+
+-- Number the existing payouts of every merchant in the order they were created
+UPDATE payouts
+SET display_seq = numbered.display_seq
+FROM (
+    SELECT merchant_id, payout_id,
+        ROW_NUMBER() OVER (PARTITION BY merchant_id ORDER BY created_at, payout_id) AS display_seq
+    FROM payouts
+) AS numbered
+WHERE payouts.merchant_id = numbered.merchant_id
+    AND payouts.payout_id = numbered.payout_id;
+
+INSERT INTO payout_display_sequences (merchant_id, last_value)
+SELECT merchant_id, MAX(display_seq) FROM payouts GROUP BY merchant_id
+ON CONFLICT (merchant_id) DO NOTHING;
+
+ALTER TABLE payouts
+ALTER COLUMN display_seq SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS payouts_merchant_id_display_seq_index ON payouts (merchant_id, display_seq);
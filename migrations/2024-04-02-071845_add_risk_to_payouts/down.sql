@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS payouts_high_risk_index;
+
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS risk_score,
+DROP COLUMN IF EXISTS is_high_risk;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS risk_score SMALLINT,
+ADD COLUMN IF NOT EXISTS is_high_risk BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS payouts_high_risk_index ON payouts (merchant_id, risk_score)
+WHERE is_high_risk;
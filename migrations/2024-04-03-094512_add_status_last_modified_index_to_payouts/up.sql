@@ -0,0 +1,2 @@
+-- Your SQL goes here
+CREATE INDEX IF NOT EXISTS payouts_status_last_modified_at_index ON payouts (status, last_modified_at, payout_id);
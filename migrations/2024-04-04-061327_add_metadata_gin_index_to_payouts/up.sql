@@ -0,0 +1,2 @@
+-- Your SQL goes here
+CREATE INDEX IF NOT EXISTS payouts_metadata_gin_index ON payouts USING GIN (metadata jsonb_path_ops);
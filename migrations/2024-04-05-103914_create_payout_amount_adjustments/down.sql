@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS payout_amount_adjustments;
+
+DROP FUNCTION IF EXISTS reject_payout_amount_adjustment_change;
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS payout_amount_adjustments (
+    id SERIAL PRIMARY KEY,
+    merchant_id VARCHAR(64) NOT NULL,
+    payout_id VARCHAR(64) NOT NULL,
+    old_amount BIGINT NOT NULL,
+    new_amount BIGINT NOT NULL,
+    reason VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now()::TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS payout_amount_adjustments_merchant_id_payout_id_index ON payout_amount_adjustments (merchant_id, payout_id);
+
+-- Adjustments are an audit record, they are only ever appended
+CREATE OR REPLACE FUNCTION reject_payout_amount_adjustment_change() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'payout_amount_adjustments is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER payout_amount_adjustments_append_only
+BEFORE UPDATE OR DELETE ON payout_amount_adjustments
+FOR EACH ROW EXECUTE FUNCTION reject_payout_amount_adjustment_change();
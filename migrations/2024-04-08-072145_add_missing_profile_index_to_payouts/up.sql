@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX IF NOT EXISTS payouts_merchant_id_missing_profile_index ON payouts (merchant_id, payout_id)
+WHERE profile_id = '';
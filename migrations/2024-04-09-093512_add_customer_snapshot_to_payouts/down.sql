@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE payouts DROP COLUMN IF EXISTS customer_snapshot;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE payouts ADD COLUMN IF NOT EXISTS cancellation_reason VARCHAR(255);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS payouts_merchant_id_last_modified_by_index;
+
+ALTER TABLE payouts DROP COLUMN IF EXISTS last_modified_by;
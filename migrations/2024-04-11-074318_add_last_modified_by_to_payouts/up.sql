@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE payouts ADD COLUMN IF NOT EXISTS last_modified_by VARCHAR(64);
+
+CREATE INDEX IF NOT EXISTS payouts_merchant_id_last_modified_by_index ON payouts (merchant_id, last_modified_by, last_modified_at);
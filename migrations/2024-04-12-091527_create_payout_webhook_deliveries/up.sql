@@ -0,0 +1,19 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS payout_webhook_deliveries (
+    delivery_id VARCHAR(64) PRIMARY KEY,
+    merchant_id VARCHAR(64) NOT NULL,
+    payout_id VARCHAR(64) NOT NULL,
+    target_url VARCHAR(2048) NOT NULL,
+    attempt_count INTEGER NOT NULL DEFAULT 0,
+    last_status_code INTEGER,
+    next_retry_at TIMESTAMP,
+    delivered_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT now()::TIMESTAMP,
+    last_modified_at TIMESTAMP NOT NULL DEFAULT now()::TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS payout_webhook_deliveries_merchant_id_payout_id_index ON payout_webhook_deliveries (merchant_id, payout_id);
+
+-- Deliveries left to retry, scanned across merchants by the webhook retry workers
+CREATE INDEX IF NOT EXISTS payout_webhook_deliveries_pending_index ON payout_webhook_deliveries (next_retry_at)
+WHERE delivered_at IS NULL AND next_retry_at IS NOT NULL;
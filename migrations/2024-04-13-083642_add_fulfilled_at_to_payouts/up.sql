@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE payouts ADD COLUMN IF NOT EXISTS fulfilled_at TIMESTAMP;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS payouts_merchant_id_parent_payout_id_index;
+
+ALTER TABLE payouts
+DROP COLUMN IF EXISTS parent_payout_id;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE payouts
+ADD COLUMN IF NOT EXISTS parent_payout_id VARCHAR(64);
+
+CREATE INDEX IF NOT EXISTS payouts_merchant_id_parent_payout_id_index ON payouts (merchant_id, parent_payout_id);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS payouts_stamp_status_changed_at ON payouts;
+
+DROP FUNCTION IF EXISTS stamp_payout_status_changed_at;
+
+ALTER TABLE payouts DROP COLUMN IF EXISTS status_changed_at;
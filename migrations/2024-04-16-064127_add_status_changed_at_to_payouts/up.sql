@@ -0,0 +1,19 @@
+-- Your SQL goes here
+ALTER TABLE payouts ADD COLUMN IF NOT EXISTS status_changed_at TIMESTAMP;
+
+-- No history of the status changes was kept so far, the last update is the closest estimate
+UPDATE payouts SET status_changed_at = last_modified_at WHERE status_changed_at IS NULL;
+
+-- Stamped by the database on every status transition, whichever query makes it
+CREATE OR REPLACE FUNCTION stamp_payout_status_changed_at() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.status IS DISTINCT FROM OLD.status THEN
+        NEW.status_changed_at := NEW.last_modified_at;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER payouts_stamp_status_changed_at
+BEFORE UPDATE ON payouts
+FOR EACH ROW EXECUTE FUNCTION stamp_payout_status_changed_at();
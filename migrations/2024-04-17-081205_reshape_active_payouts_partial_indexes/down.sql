@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS payouts_active_status_last_modified_at_index;
+DROP INDEX IF EXISTS payouts_active_status_index;
+
+CREATE INDEX IF NOT EXISTS payouts_status_scheduled_at_index ON payouts (status, scheduled_at);
+
+CREATE INDEX IF NOT EXISTS payouts_active_status_index ON payouts (status, scheduled_at, last_modified_at)
+WHERE status IN ('pending', 'scheduled', 'requires_fulfillment');
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+DROP INDEX IF EXISTS payouts_active_status_index;
+DROP INDEX IF EXISTS payouts_status_scheduled_at_index;
+
+CREATE INDEX IF NOT EXISTS payouts_active_status_index ON payouts (status, scheduled_at, payout_id)
+WHERE status IN ('pending', 'scheduled', 'requires_fulfillment');
+
+CREATE INDEX IF NOT EXISTS payouts_active_status_last_modified_at_index ON payouts (status, last_modified_at, payout_id)
+WHERE status IN ('pending', 'scheduled', 'requires_fulfillment');
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS payouts_stamp_fulfilled_at ON payouts;
+
+DROP FUNCTION IF EXISTS stamp_payout_fulfilled_at;
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- Stamped by the database when a payout moves to success, so that later updates of a
+-- successful payout keep the time it was fulfilled at
+CREATE OR REPLACE FUNCTION stamp_payout_fulfilled_at() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.status = 'success' AND OLD.status IS DISTINCT FROM 'success' THEN
+        NEW.fulfilled_at := NEW.last_modified_at;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER payouts_stamp_fulfilled_at
+BEFORE UPDATE ON payouts
+FOR EACH ROW EXECUTE FUNCTION stamp_payout_fulfilled_at();